@@ -1,13 +1,26 @@
 use async_trait::async_trait;
 use axum_extra::extract::Multipart;
-use axum_extra::extract::multipart::MultipartError;
-use std::path::PathBuf;
+use axum_extra::extract::multipart::{Field, MultipartError};
+use std::path::{Path, PathBuf};
 use tokio::io::AsyncWriteExt;
 use tracing::debug;
 
 #[async_trait]
 pub trait MultipartExt: Sized {
     async fn write_file(self, path_buf: PathBuf) -> Result<PathBuf, WriteMultipartError>;
+
+    /// Writes all files into `path_buf` like [MultipartExt::write_file] and returns their paths
+    /// in the order of the request together with the names and values of all text fields
+    async fn write_files_and_collect_fields(
+        self,
+        path_buf: PathBuf,
+    ) -> Result<(Vec<PathBuf>, Vec<(String, String)>), WriteMultipartError>;
+
+    /// Like [MultipartExt::write_files_and_collect_fields] but requests without files are accepted
+    async fn write_optional_files_and_collect_fields(
+        self,
+        path_buf: PathBuf,
+    ) -> Result<(Vec<PathBuf>, Vec<(String, String)>), WriteMultipartError>;
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -25,26 +38,60 @@ pub enum WriteMultipartError {
 #[async_trait]
 impl MultipartExt for Multipart {
     async fn write_file(mut self, path_buf: PathBuf) -> Result<PathBuf, WriteMultipartError> {
-        let now = std::time::Instant::now();
-        let Some(mut field) = self.next_field().await? else {
+        let Some(field) = self.next_field().await? else {
             return Err(WriteMultipartError::NoData);
         };
-        let file_name = field.file_name().ok_or(WriteMultipartError::NoFileName)?;
-        tokio::fs::create_dir_all(&path_buf).await?;
-        let file_path = path_buf.join(file_name);
-        let mut file = tokio::fs::File::create(&file_path).await?;
-        let mut received_bytes = 0;
-        while let Some(chunk) = field.chunk().await? {
-            file.write_all(chunk.as_ref()).await?;
-            received_bytes += chunk.len() as u128;
+        write_field(field, &path_buf).await
+    }
+
+    async fn write_files_and_collect_fields(
+        self,
+        path_buf: PathBuf,
+    ) -> Result<(Vec<PathBuf>, Vec<(String, String)>), WriteMultipartError> {
+        let (file_paths, fields) = self
+            .write_optional_files_and_collect_fields(path_buf)
+            .await?;
+        if file_paths.is_empty() {
+            return Err(WriteMultipartError::NoData);
         }
-        let elapsed_ms = now.elapsed().as_millis();
-        debug!(
-            "Received {file_path:?} ({} bytes in {} ms = {} MB/s)",
-            received_bytes,
-            elapsed_ms,
-            received_bytes / elapsed_ms / 1000
-        );
-        Ok(file_path)
+        Ok((file_paths, fields))
+    }
+
+    async fn write_optional_files_and_collect_fields(
+        mut self,
+        path_buf: PathBuf,
+    ) -> Result<(Vec<PathBuf>, Vec<(String, String)>), WriteMultipartError> {
+        let mut file_paths = Vec::new();
+        let mut fields = Vec::new();
+        while let Some(field) = self.next_field().await? {
+            if field.file_name().is_none() {
+                let name = field.name().unwrap_or_default().to_string();
+                fields.push((name, field.text().await?));
+            } else {
+                file_paths.push(write_field(field, &path_buf).await?);
+            }
+        }
+        Ok((file_paths, fields))
+    }
+}
+
+async fn write_field(mut field: Field, path: &Path) -> Result<PathBuf, WriteMultipartError> {
+    let now = std::time::Instant::now();
+    let file_name = field.file_name().ok_or(WriteMultipartError::NoFileName)?;
+    tokio::fs::create_dir_all(path).await?;
+    let file_path = path.join(file_name);
+    let mut file = tokio::fs::File::create(&file_path).await?;
+    let mut received_bytes = 0;
+    while let Some(chunk) = field.chunk().await? {
+        file.write_all(chunk.as_ref()).await?;
+        received_bytes += chunk.len() as u128;
     }
+    let elapsed_ms = now.elapsed().as_millis();
+    debug!(
+        "Received {file_path:?} ({} bytes in {} ms = {} MB/s)",
+        received_bytes,
+        elapsed_ms,
+        received_bytes / elapsed_ms / 1000
+    );
+    Ok(file_path)
 }
@@ -2,6 +2,7 @@ pub mod axum;
 pub mod bollard;
 pub mod ipaddr;
 pub mod iter;
+pub mod path;
 pub mod serde;
 pub mod time;
 pub mod vec;
@@ -0,0 +1,39 @@
+use std::path::{Component, Path};
+
+pub trait PathExtension {
+    /// Returns true if the path is relative, not empty and does not leave the directory it is
+    /// joined to, i.e. it consists only of normal components
+    fn is_contained_relative(&self) -> bool;
+}
+
+impl PathExtension for Path {
+    fn is_contained_relative(&self) -> bool {
+        let mut components = self.components().peekable();
+        components.peek().is_some()
+            && components.all(|component| matches!(component, Component::Normal(_)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ntest::test_case;
+
+    #[test_case("file.txt")]
+    #[test_case("dir/file.txt")]
+    #[test_case("dir/./file.txt")]
+    #[test_case("dir/")]
+    fn is_contained_relative_true(path: &str) {
+        assert!(Path::new(path).is_contained_relative());
+    }
+
+    #[test_case("")]
+    #[test_case("/etc/passwd")]
+    #[test_case("../file.txt")]
+    #[test_case("dir/../../file.txt")]
+    #[test_case("dir/..")]
+    #[test_case("./file.txt")]
+    fn is_contained_relative_false(path: &str) {
+        assert!(!Path::new(path).is_contained_relative());
+    }
+}
@@ -2,6 +2,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 pub trait SystemTimeExt {
     fn unix_millis(&self) -> u128;
+    fn unix_secs(&self) -> u64;
 }
 
 impl SystemTimeExt for SystemTime {
@@ -10,4 +11,10 @@ impl SystemTimeExt for SystemTime {
             .expect("Time went backwards")
             .as_millis()
     }
+
+    fn unix_secs(&self) -> u64 {
+        self.duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs()
+    }
 }
@@ -105,6 +105,28 @@ fn is_trace_level_path(path: &str) -> bool {
 async fn auth_middleware(
     axum::extract::State(watch): axum::extract::State<Arc<wall::watch::Watch>>,
     wall::watch::AuthToken(auth_token): wall::watch::AuthToken,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    authenticate(&watch, auth_token.as_deref(), request, next).await
+}
+
+/// Like [auth_middleware], but additionally accepts the session cookie of browsers as the
+/// requests to editors are plain browser navigations
+#[cfg(feature = "auth")]
+async fn editor_auth_middleware(
+    axum::extract::State(watch): axum::extract::State<Arc<wall::watch::Watch>>,
+    wall::watch::EditorAuthToken(auth_token): wall::watch::EditorAuthToken,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    authenticate(&watch, auth_token.as_deref(), request, next).await
+}
+
+#[cfg(feature = "auth")]
+async fn authenticate(
+    watch: &wall::watch::Watch,
+    auth_token: Option<&str>,
     mut request: axum::extract::Request,
     next: axum::middleware::Next,
 ) -> axum::response::Response {
@@ -113,7 +135,7 @@ async fn auth_middleware(
         request
             .extensions_mut()
             .insert(wall::watch::RolesExtension::new_with_initial_setup_roles());
-    } else if let Some(token) = auth_token.as_deref() {
+    } else if let Some(token) = auth_token {
         match watch.verify_token(token).await {
             Err(wall::watch::Error::NoAuthProvider) => {
                 debug!(
@@ -201,6 +223,41 @@ async fn create_service<
     let server = Arc::new(server);
     let app = flecsd_axum_server::server::new(server.clone());
     let app = app
+        .route(
+            "/v2/apps/:app/stage",
+            axum::routing::post(server_impl::api::v2::apps::app::stage::post::<APP>),
+        )
+        .route(
+            "/v2/device/license/file",
+            axum::routing::post(server_impl::api::v2::device::license::file::post::<L>),
+        )
+        .route(
+            "/v2/device/license/request",
+            get(server_impl::api::v2::device::license::request::get::<L>),
+        )
+        .route(
+            "/v2/exports/:export_id/increments",
+            axum::routing::post(server_impl::api::v2::exports::export_id::increments::post::<E>),
+        )
+        .route(
+            "/v2/imports/inspect",
+            axum::routing::post(server_impl::api::v2::imports::inspect::post::<IMP, E>),
+        )
+        .route(
+            "/v2/instances/:instance_id/clone",
+            axum::routing::post(server_impl::api::v2::instances::instance_id::clone::post::<I>),
+        )
+        .route(
+            "/v2/instances/:instance_id/config/editors/:port/host",
+            delete(server_impl::api::v2::instances::instance_id::config::editors::port::host::delete::<I>)
+                .put(server_impl::api::v2::instances::instance_id::config::editors::port::host::put::<I>),
+        )
+        .route(
+            "/v2/instances/:instance_id/config/editors/:port/roles",
+            delete(server_impl::api::v2::instances::instance_id::config::editors::port::roles::delete::<I>)
+                .get(server_impl::api::v2::instances::instance_id::config::editors::port::roles::get::<I>)
+                .put(server_impl::api::v2::instances::instance_id::config::editors::port::roles::put::<I>),
+        )
         .route(
             "/v2/instances/:instance_id/depends/:dependency_key",
             delete(server_impl::api::v2::instances::instance_id::depends::dependency_key::delete)
@@ -217,6 +274,32 @@ async fn create_service<
             "/v2/instances/:instance_id/depends",
             get(server_impl::api::v2::instances::instance_id::depends::get),
         )
+        .route(
+            "/v2/instances/:instance_id/exec",
+            axum::routing::post(server_impl::api::v2::instances::instance_id::exec::post::<I>),
+        )
+        .route(
+            "/v2/instances/:instance_id/exec/tty",
+            get(server_impl::api::v2::instances::instance_id::exec::tty::get::<I>),
+        )
+        .route(
+            "/v2/instances/:instance_id/files",
+            get(server_impl::api::v2::instances::instance_id::files::get::<I>),
+        )
+        .route(
+            "/v2/instances/:instance_id/files/conffiles/:file_name",
+            get(server_impl::api::v2::instances::instance_id::files::conffiles::file_name::get::<I>)
+                .put(server_impl::api::v2::instances::instance_id::files::conffiles::file_name::put::<I>),
+        )
+        .route(
+            "/v2/instances/:instance_id/files/volumes/:volume_name",
+            get(server_impl::api::v2::instances::instance_id::files::volumes::volume_name::get::<I>),
+        )
+        .route(
+            "/v2/instances/:instance_id/files/volumes/:volume_name/*path",
+            get(server_impl::api::v2::instances::instance_id::files::volumes::volume_name::path::get::<I>)
+                .put(server_impl::api::v2::instances::instance_id::files::volumes::volume_name::path::put::<I>),
+        )
         .route(
             "/v2/instances/:instance_id/provides",
             get(server_impl::api::v2::instances::instance_id::provides::get),
@@ -240,9 +323,26 @@ async fn create_service<
                 .get(server_impl::api::v2::providers::feature::default::get)
                 .put(server_impl::api::v2::providers::feature::default::put),
         )
+        .route("/v2/state", get(server_impl::api::v2::state::get))
+        .route(
+            "/v2/state/apply",
+            axum::routing::post(server_impl::api::v2::state::apply::post::<APP, I, D>),
+        )
+        .route(
+            "/v2/system/ca",
+            get(server_impl::api::v2::system::ca::get::<SYS>),
+        )
         .route(
             "/v2/system/sbom",
             get(server_impl::api::v2::system::sbom::get),
+        )
+        .route(
+            "/v2/system/storage",
+            get(server_impl::api::v2::system::storage::get::<SYS>),
+        )
+        .route(
+            "/v2/system/storage/prune",
+            axum::routing::post(server_impl::api::v2::system::storage::prune::post::<SYS>),
         );
 
     #[cfg(feature = "auth")]
@@ -284,6 +384,18 @@ async fn create_service<
         wall.watch.clone(),
         auth_middleware,
     ));
+    // Authorization requests of floxy for editors, the handler checks the roles configured for the
+    // editor
+    #[cfg(feature = "auth")]
+    let editor_access = Router::new()
+        .route(
+            "/v2/instances/:instance_id/editor/:port/access",
+            get(server_impl::api::v2::instances::instance_id::editor::port::access::get::<I>),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            wall.watch.clone(),
+            editor_auth_middleware,
+        ));
     #[cfg(feature = "auth")]
     let app = app
         .layer(axum::middleware::from_fn_with_state(
@@ -294,7 +406,8 @@ async fn create_service<
             wall.watch,
             auth_middleware,
         ))
-        .merge(unenforced);
+        .merge(unenforced)
+        .merge(editor_access);
     let app = app
         // It is not feasible to configure the body limit per route as we would have to manually
         // generated code (flecsd_axum_server::server::new). We therefore disable the limit for all
@@ -1,3 +1,5 @@
+pub mod stage;
+
 use crate::enchantment::quest_master::QuestMaster;
 use crate::relic::floxy::Floxy;
 use crate::sorcerer::appraiser::AppRaiser;
@@ -0,0 +1,114 @@
+use crate::fsm::server_impl::api::v2::models::{Accepted, AdditionalInfo};
+use crate::fsm::server_impl::state::{
+    AppRaiserState, ConsoleClientState, QuestMasterState, VaultState,
+};
+use crate::sorcerer::appraiser::AppRaiser;
+use crate::vault::pouch::AppKey;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PostPathParams {
+    pub app: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(as = StageAppRequest)]
+pub struct PostRequest {
+    /// Version of the app that should be staged
+    pub version: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/apps/{app}/stage",
+    tag = "Experimental",
+    description = "Pull and verify a version of an app without touching existing instances. Instances of other versions of the app keep running until they are updated to the staged version, which then only replaces their containers.",
+    params(PostPathParams),
+    request_body(content = PostRequest),
+    responses(
+        (status = ACCEPTED, description = "Staging of app triggered", body = Accepted),
+        (status = BAD_REQUEST, description = "Malformed request", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn post<APP: AppRaiser + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(AppRaiserState(appraiser)): State<AppRaiserState<APP>>,
+    State(ConsoleClientState(console_client)): State<ConsoleClientState>,
+    State(QuestMasterState(quest_master)): State<QuestMasterState>,
+    Path(PostPathParams { app }): Path<PostPathParams>,
+    Json(PostRequest { version }): Json<PostRequest>,
+) -> Response {
+    if version.is_empty() {
+        return AdditionalInfo::new("Version must not be empty").into_bad_request();
+    }
+    let app_key = AppKey { name: app, version };
+    match quest_master
+        .lock()
+        .await
+        .schedule_quest(format!("Stage {app_key}"), move |quest| async move {
+            appraiser
+                .stage_app(quest, vault, app_key, console_client)
+                .await
+        })
+        .await
+    {
+        Ok((id, _)) => Accepted::new(id).into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enchantment::quest_master::QuestMaster;
+    use crate::fsm::console_client::create_default;
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::appraiser::MockAppRaiser;
+    use crate::vault::tests::create_empty_test_vault;
+    use http::StatusCode;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    async fn post_with(appraiser: MockAppRaiser, version: &str) -> Response {
+        let vault = create_empty_test_vault();
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        post(
+            State(VaultState(vault.clone())),
+            State(AppRaiserState(Arc::new(appraiser))),
+            State(ConsoleClientState(create_default(vault, lore))),
+            State(QuestMasterState(QuestMaster::default())),
+            Path(PostPathParams {
+                app: "tech.flecs.some-app".to_string(),
+            }),
+            Json(PostRequest {
+                version: version.to_string(),
+            }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_202() {
+        let mut appraiser = MockAppRaiser::new();
+        appraiser
+            .expect_stage_app()
+            .withf(|_, _, app_key, _| {
+                app_key.name == "tech.flecs.some-app" && app_key.version == "1.2.3"
+            })
+            .returning(|_, _, _, _| Ok(()));
+        let response = post_with(appraiser, "1.2.3").await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn post_400() {
+        let response = post_with(MockAppRaiser::new(), "").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
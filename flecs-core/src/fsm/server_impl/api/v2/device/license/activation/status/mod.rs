@@ -1,18 +1,72 @@
+use crate::enchantment::quest_master::QuestMaster;
 use crate::fsm::console_client::ConsoleClient;
-use crate::fsm::server_impl::additional_info_from_error;
 use crate::sorcerer::licenso::Licenso;
+use crate::sorcerer::licenso::revalidation::revalidate_license;
 use crate::vault::Vault;
 use flecsd_axum_server::apis::device::DeviceLicenseActivationStatusGetResponse as GetResponse;
 use flecsd_axum_server::models::DeviceLicenseActivationStatusGet200Response as GetResponse200;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub async fn get<L: Licenso>(
     vault: Arc<Vault>,
     licenso: Arc<L>,
+    quest_master: QuestMaster,
     client_config: ConsoleClient,
+    grace_period: Duration,
 ) -> GetResponse {
-    match licenso.validate_license(&vault, client_config).await {
-        Ok(is_valid) => GetResponse::Status200_Success(GetResponse200 { is_valid }),
-        Err(e) => GetResponse::Status500_InternalServerError(additional_info_from_error(e)),
+    let validation = revalidate_license(
+        &quest_master,
+        licenso.as_ref(),
+        &vault,
+        client_config,
+        grace_period,
+    )
+    .await;
+    GetResponse::Status200_Success(GetResponse200 {
+        is_valid: validation.is_valid(),
+        last_validated: validation.last_validated,
+        grace_until: validation.grace_until,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::license::{LicenseRevalidation, LicenseState, LicenseValidation};
+    use crate::sorcerer::licenso::MockLicenso;
+    use crate::vault::tests::create_empty_test_vault;
+
+    #[tokio::test]
+    async fn get_200_grace_period() {
+        let validation = LicenseValidation {
+            state: LicenseState::GracePeriod,
+            last_validated: Some(1_750_000_000),
+            grace_until: Some(1_750_604_800),
+        };
+        let mut licenso = MockLicenso::new();
+        licenso
+            .expect_revalidate_license()
+            .once()
+            .return_const(LicenseRevalidation {
+                previous: Some(validation.clone()),
+                current: validation,
+            });
+        let (_server, config) = crate::tests::create_test_server_and_config().await;
+        assert_eq!(
+            get(
+                create_empty_test_vault(),
+                Arc::new(licenso),
+                QuestMaster::default(),
+                config,
+                Duration::from_secs(604_800),
+            )
+            .await,
+            GetResponse::Status200_Success(GetResponse200 {
+                is_valid: true,
+                last_validated: Some(1_750_000_000),
+                grace_until: Some(1_750_604_800),
+            })
+        );
     }
 }
@@ -0,0 +1,90 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{LicensoState, LoreState, VaultState};
+use crate::jeweler::license::{LicenseFile, LicenseFileError, OfflineLicense};
+use crate::relic::system::serial::SerialNumberReaderImpl;
+use crate::sorcerer::licenso::{ImportLicenseError, Licenso};
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+
+#[utoipa::path(
+    post,
+    path = "/device/license/file",
+    tag = "Experimental",
+    description = "Activate the device offline with a signed license file. The signature is verified against the license keys of FLECS or the license verifying keys configured for the console instead, the license has to entitle the device to 'offline-activation'. A license bound to a serial number is only accepted on the device with this serial number. The license is valid without a connection to the console until it expires.",
+    request_body(content = LicenseFile),
+    responses(
+        (status = OK, description = "License file accepted", body = OfflineLicense),
+        (status = BAD_REQUEST, description = "License file invalid, expired, without entitlement or issued for another device", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn post<L: Licenso>(
+    State(VaultState(vault)): State<VaultState>,
+    State(LoreState(lore)): State<LoreState>,
+    State(LicensoState(licenso)): State<LicensoState<L>>,
+    Json(license_file): Json<LicenseFile>,
+) -> Response {
+    let serial_number_reader = SerialNumberReaderImpl::from(&lore.system);
+    match licenso
+        .import_license_file(
+            &vault,
+            &serial_number_reader,
+            &lore.console.license_verifying_keys,
+            license_file,
+        )
+        .await
+    {
+        Ok(license) => (StatusCode::OK, Json(license)).into_response(),
+        Err(e @ ImportLicenseError::File(LicenseFileError::NoTrustedKeys)) => {
+            AdditionalInfo::new(e.to_string()).into_internal_server_error()
+        }
+        Err(e) => AdditionalInfo::new(e.to_string()).into_bad_request(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::license::tests::{test_license, test_signing_key};
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::licenso::MockLicenso;
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    async fn post_with(licenso: MockLicenso) -> Response {
+        post(
+            State(VaultState(create_empty_test_vault())),
+            State(LoreState(Arc::new(lore::test_lore(
+                testdir!(),
+                &MockVarReader::new(),
+            )))),
+            State(LicensoState(Arc::new(licenso))),
+            Json(LicenseFile::create(&test_signing_key(4), &test_license())),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_200() {
+        let mut licenso = MockLicenso::new();
+        licenso
+            .expect_import_license_file()
+            .once()
+            .returning(|_, _, _, _| Ok(test_license()));
+        assert_eq!(post_with(licenso).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn post_400() {
+        let mut licenso = MockLicenso::new();
+        licenso
+            .expect_import_license_file()
+            .once()
+            .returning(|_, _, _, _| Err(ImportLicenseError::Expired(1_800_000_000)));
+        assert_eq!(post_with(licenso).await.status(), StatusCode::BAD_REQUEST);
+    }
+}
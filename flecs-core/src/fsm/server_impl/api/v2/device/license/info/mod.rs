@@ -8,7 +8,11 @@ pub async fn get(vault: Arc<Vault>) -> GetResponse {
     let secrets = vault.get_secrets().await;
     GetResponse::Status200_Success(GetResponse200 {
         // TODO: Use correct type, as soon as serial numbers are implemented
-        r#type: "Via user license".to_string(),
+        r#type: if secrets.offline_license.is_some() {
+            "Via license file".to_string()
+        } else {
+            "Via user license".to_string()
+        },
         session_id: Some(console_session_id_to_core_session_id(
             secrets.get_session_id(),
         )),
@@ -1,2 +1,4 @@
 pub mod activation;
+pub mod file;
 pub mod info;
+pub mod request;
@@ -0,0 +1,81 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{LicensoState, LoreState};
+use crate::jeweler::license::LicenseRequest;
+use crate::relic::system::serial::SerialNumberReaderImpl;
+use crate::sorcerer::licenso::Licenso;
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+
+const FILE_NAME: &str = "flecs-license-request.json";
+
+#[utoipa::path(
+    get,
+    path = "/device/license/request",
+    tag = "Experimental",
+    description = "Create a license request for devices without a connection to the console. The request contains the serial number, system info and core version of the device and is exchanged for a signed license file, which is uploaded via POST /device/license/file.",
+    responses(
+        (status = OK, description = "License request of the device", body = LicenseRequest),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<L: Licenso>(
+    State(LoreState(lore)): State<LoreState>,
+    State(LicensoState(licenso)): State<LicensoState<L>>,
+) -> Response {
+    let serial_number_reader = SerialNumberReaderImpl::from(&lore.system);
+    match licenso.create_license_request(&serial_number_reader).await {
+        Ok(request) => (
+            StatusCode::OK,
+            [(
+                http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{FILE_NAME}\""),
+            )],
+            Json(request),
+        )
+            .into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore;
+    use crate::relic::system::info::try_create_system_info;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::licenso::MockLicenso;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut licenso = MockLicenso::new();
+        licenso
+            .expect_create_license_request()
+            .once()
+            .returning(|_| {
+                Ok(LicenseRequest {
+                    serial_number: None,
+                    system_info: try_create_system_info().unwrap(),
+                    core_version: "5.0.0".to_string(),
+                    created_at: 1_750_000_000,
+                })
+            });
+        let response = get(
+            State(LoreState(Arc::new(lore::test_lore(
+                testdir!(),
+                &MockVarReader::new(),
+            )))),
+            State(LicensoState(Arc::new(licenso))),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(
+            response
+                .headers()
+                .contains_key(http::header::CONTENT_DISPOSITION)
+        );
+    }
+}
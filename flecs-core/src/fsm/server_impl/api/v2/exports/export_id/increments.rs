@@ -0,0 +1,153 @@
+use crate::fsm::server_impl::api::v2::models::{Accepted, AdditionalInfo};
+use crate::fsm::server_impl::state::{
+    ExportiusState, FloxyState, LoreState, QuestMasterState, VaultState,
+};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::quest::QuestResult;
+use crate::sorcerer::exportius::Exportius;
+use crate::vault::pouch::AppKey;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PostPathParams {
+    /// Id of the base export
+    pub export_id: String,
+}
+
+#[serde_as]
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = CreateIncrementRequest)]
+pub struct PostRequest {
+    pub apps: Vec<AppKey>,
+    #[serde_as(as = "Option<Vec<DisplayFromStr>>")]
+    #[serde(default)]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub instances: Option<Vec<InstanceId>>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/exports/{export_id}/increments",
+    tag = "Experimental",
+    description = "Create an incremental export on top of the specified export. Files and image layers which are unchanged since the base export are omitted from the archive. Importing the increment requires the base export and all its bases, either uploaded together with the increment or present in the exports of the device.",
+    params(PostPathParams),
+    request_body(content = PostRequest),
+    responses(
+        (status = ACCEPTED, description = "Creation of incremental export triggered", body = Accepted),
+        (status = NOT_FOUND, description = "Base export not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn post<E: Exportius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(ExportiusState(exportius)): State<ExportiusState<E>>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    State(LoreState(lore)): State<LoreState>,
+    State(QuestMasterState(quest_master)): State<QuestMasterState>,
+    Path(PostPathParams { export_id }): Path<PostPathParams>,
+    Json(request): Json<PostRequest>,
+) -> Response {
+    match exportius
+        .get_export(&lore.export.base_path, export_id.clone())
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => return AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+    match quest_master
+        .lock()
+        .await
+        .schedule_quest_with_result(
+            format!("Create incremental export based on {export_id}"),
+            move |quest| async move {
+                let id = exportius
+                    .create_export_archive(
+                        quest,
+                        vault,
+                        floxy,
+                        lore,
+                        request.apps,
+                        request.instances.unwrap_or_default(),
+                        Some(export_id),
+                    )
+                    .await?;
+                Ok(QuestResult::ExportId(id))
+            },
+        )
+        .await
+    {
+        Ok((id, _)) => Accepted::new(id).into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enchantment::quest_master::QuestMaster;
+    use crate::lore;
+    use crate::relic::floxy::MockFloxy;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::exportius::MockExportius;
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    #[tokio::test]
+    async fn post_404() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut exportius = MockExportius::new();
+        exportius
+            .expect_get_export()
+            .once()
+            .returning(|_, _| Ok(None));
+        let response = post(
+            State(VaultState(create_empty_test_vault())),
+            State(ExportiusState(Arc::new(exportius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            State(LoreState(lore)),
+            State(QuestMasterState(QuestMaster::default())),
+            Path(PostPathParams {
+                export_id: "1234".to_string(),
+            }),
+            Json(PostRequest::default()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn post_202() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut exportius = MockExportius::new();
+        exportius
+            .expect_get_export()
+            .once()
+            .returning(|_, _| Ok(Some("/exports/1234.tar".into())));
+        exportius
+            .expect_create_export_archive()
+            .withf(|_, _, _, _, _, _, base| base.as_deref() == Some("1234"))
+            .returning(|_, _, _, _, _, _, _| Ok("5678".to_string()));
+        let response = post(
+            State(VaultState(create_empty_test_vault())),
+            State(ExportiusState(Arc::new(exportius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            State(LoreState(lore)),
+            State(QuestMasterState(QuestMaster::default())),
+            Path(PostPathParams {
+                export_id: "1234".to_string(),
+            }),
+            Json(PostRequest::default()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+}
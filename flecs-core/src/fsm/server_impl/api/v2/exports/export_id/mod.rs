@@ -1,3 +1,4 @@
+pub mod increments;
 use crate::lore::ExportLoreRef;
 use crate::sorcerer::exportius::Exportius;
 use flecsd_axum_server::apis::flecsport::{
@@ -46,7 +46,7 @@ pub async fn post<E: Exportius>(
         .await
         .schedule_quest_with_result("Create export".to_string(), |quest| async move {
             let id = exportius
-                .create_export_archive(quest, vault, floxy, lore, apps, instance_ids)
+                .create_export_archive(quest, vault, floxy, lore, apps, instance_ids, None)
                 .await?;
             Ok(QuestResult::ExportId(id))
         })
@@ -0,0 +1,192 @@
+use super::decryption_from_fields;
+use crate::forge::axum::MultipartExt;
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{ExportiusState, ImportiusState, LoreState};
+use crate::sorcerer::exportius::Exportius;
+use crate::sorcerer::importius::{ImportInspection, Importius, InspectImportError};
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::Multipart;
+use http::StatusCode;
+use serde::Deserialize;
+use std::path::PathBuf;
+use tracing::warn;
+use utoipa::ToSchema;
+
+/// Multipart form of the inspection request, exactly one of 'file' and 'exportId' is required
+#[allow(dead_code)]
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = InspectImportRequest)]
+pub struct PostRequest {
+    /// Uploaded export archive
+    #[schema(value_type = Option<String>, format = Binary)]
+    file: Option<Vec<u8>>,
+    /// Id of an export stored on the device
+    export_id: Option<String>,
+    /// Passphrase to decrypt the archive, can be repeated
+    passphrase: Option<String>,
+    /// Age identity to decrypt the archive, can be repeated
+    identity: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/imports/inspect",
+    tag = "Experimental",
+    description = "Describe the content of an export archive without importing it. Only the manifest of the archive is read, the archive is either uploaded or an export stored on the device. Uploaded archives are removed after the inspection.",
+    request_body(content = PostRequest, content_type = "multipart/form-data"),
+    responses(
+        (status = OK, description = "Content of the archive", body = ImportInspection),
+        (status = BAD_REQUEST, description = "Malformed request or archive", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Export not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn post<I: Importius, E: Exportius>(
+    State(LoreState(lore)): State<LoreState>,
+    State(ImportiusState(importius)): State<ImportiusState<I>>,
+    State(ExportiusState(exportius)): State<ExportiusState<E>>,
+    request: Multipart,
+) -> Response {
+    let (file_paths, fields) = match request
+        .write_optional_files_and_collect_fields(lore.import.base_path.clone())
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => return AdditionalInfo::new(e.to_string()).into_bad_request(),
+    };
+    let export_id = fields
+        .iter()
+        .find(|(name, _)| name == "exportId")
+        .map(|(_, value)| value.clone());
+    let (archive_path, uploaded) = match (&file_paths[..], export_id) {
+        ([file_path], None) => (file_path.clone(), true),
+        ([], Some(export_id)) => {
+            match exportius
+                .get_export(&lore.export.base_path, export_id)
+                .await
+            {
+                Ok(Some(archive_path)) => (archive_path, false),
+                Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+                Err(e) => return AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+            }
+        }
+        _ => {
+            remove_uploads(file_paths).await;
+            return AdditionalInfo::new("Expected either exactly one file or the field 'exportId'")
+                .into_bad_request();
+        }
+    };
+    let result = importius
+        .inspect_archive(lore, archive_path.clone(), decryption_from_fields(&fields))
+        .await;
+    if uploaded {
+        remove_uploads(vec![archive_path]).await;
+    }
+    match result {
+        Ok(inspection) => (StatusCode::OK, Json(inspection)).into_response(),
+        Err(e @ InspectImportError::IO(_)) => {
+            AdditionalInfo::new(e.to_string()).into_internal_server_error()
+        }
+        Err(e) => AdditionalInfo::new(e.to_string()).into_bad_request(),
+    }
+}
+
+async fn remove_uploads(file_paths: Vec<PathBuf>) {
+    for file_path in file_paths {
+        if let Err(e) = tokio::fs::remove_file(&file_path).await {
+            warn!("Could not remove uploaded archive {file_path:?}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::exportius::MockExportius;
+    use crate::sorcerer::importius::MockImportius;
+    use axum::body::Body;
+    use axum::extract::FromRequest;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    const BOUNDARY: &str = "INSPECT_BOUNDARY";
+
+    async fn multipart(fields: &[(&str, &str)]) -> Multipart {
+        let mut body = String::new();
+        for (name, value) in fields {
+            body.push_str(&format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            ));
+        }
+        body.push_str(&format!("--{BOUNDARY}--\r\n"));
+        let request = http::Request::builder()
+            .method("POST")
+            .header(
+                http::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::from(body))
+            .unwrap();
+        Multipart::from_request(request, &()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn post_400() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let response = post(
+            State(LoreState(lore)),
+            State(ImportiusState(Arc::new(MockImportius::new()))),
+            State(ExportiusState(Arc::new(MockExportius::new()))),
+            multipart(&[("passphrase", "secret")]).await,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn post_404() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut exportius = MockExportius::new();
+        exportius
+            .expect_get_export()
+            .once()
+            .returning(|_, _| Ok(None));
+        let response = post(
+            State(LoreState(lore)),
+            State(ImportiusState(Arc::new(MockImportius::new()))),
+            State(ExportiusState(Arc::new(exportius))),
+            multipart(&[("exportId", "1234")]).await,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn post_400_invalid_archive() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut exportius = MockExportius::new();
+        exportius
+            .expect_get_export()
+            .once()
+            .returning(|_, _| Ok(Some("/exports/1234.tar".into())));
+        let mut importius = MockImportius::new();
+        importius
+            .expect_inspect_archive()
+            .once()
+            .withf(|_, archive_path, _| archive_path == std::path::Path::new("/exports/1234.tar"))
+            .returning(|_, _, _| Err(InspectImportError::MissingManifest));
+        let response = post(
+            State(LoreState(lore)),
+            State(ImportiusState(Arc::new(importius))),
+            State(ExportiusState(Arc::new(exportius))),
+            multipart(&[("exportId", "1234")]).await,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,15 +1,43 @@
+pub mod inspect;
+
 use crate::enchantment::quest_master::QuestMaster;
 use crate::forge::axum::{MultipartExt, WriteMultipartError};
 use crate::lore::Lore;
+use crate::quest::QuestResult;
 use crate::relic::device::usb::UsbDeviceReader;
+use crate::relic::encryption::Decryption;
 use crate::relic::floxy::Floxy;
-use crate::sorcerer::importius::{ImportPathInfo, Importius};
+use crate::sorcerer::importius::{ImportOptions, ImportPathInfo, Importius};
 use crate::vault::Vault;
 use axum_extra::extract::Multipart;
 use flecsd_axum_server::apis::flecsport::ImportsPostResponse as PostResponse;
 use flecsd_axum_server::models;
 use futures_util::TryFutureExt;
 use std::sync::Arc;
+use tracing::warn;
+
+/// Collects the key material supplied in the text fields 'passphrase' and 'identity' of the
+/// import request
+fn decryption_from_fields(fields: &[(String, String)]) -> Decryption {
+    let mut decryption = Decryption::default();
+    for (name, value) in fields {
+        match name.as_str() {
+            "passphrase" => decryption.passphrases.push(value.clone()),
+            "identity" => decryption.identities.push(value.clone()),
+            _ => {}
+        }
+    }
+    decryption
+}
+
+/// Parses the json encoded [ImportOptions] from the text field 'options' of the import request,
+/// the default options are used if the field is missing
+fn options_from_fields(fields: &[(String, String)]) -> Result<ImportOptions, serde_json::Error> {
+    match fields.iter().find(|(name, _)| name == "options") {
+        Some((_, value)) => serde_json::from_str(value),
+        None => Ok(ImportOptions::default()),
+    }
+}
 
 pub async fn post<I: Importius, U: UsbDeviceReader + 'static>(
     vault: Arc<Vault>,
@@ -20,31 +48,62 @@ pub async fn post<I: Importius, U: UsbDeviceReader + 'static>(
     quest_master: QuestMaster,
     request: Multipart,
 ) -> PostResponse {
-    match request.write_file(lore.import.base_path.clone()).await {
+    match request
+        .write_files_and_collect_fields(lore.import.base_path.clone())
+        .await
+    {
         Err(e @ WriteMultipartError::NoData) | Err(e @ WriteMultipartError::NoFileName) => {
             PostResponse::Status400_MalformedRequest(models::AdditionalInfo::new(e.to_string()))
         }
         Err(e) => {
             PostResponse::Status500_InternalServerError(models::AdditionalInfo::new(e.to_string()))
         }
-        Ok(file_path) => {
+        Ok((file_paths, fields)) => {
+            let options = match options_from_fields(&fields) {
+                Ok(options) => options,
+                Err(e) => {
+                    for file_path in file_paths {
+                        if let Err(e) = tokio::fs::remove_file(&file_path).await {
+                            warn!("Could not remove uploaded import {file_path:?}: {e}");
+                        }
+                    }
+                    return PostResponse::Status400_MalformedRequest(models::AdditionalInfo::new(
+                        format!("Invalid import options: {e}"),
+                    ));
+                }
+            };
+            // The first file is imported, further files are the bases of an incremental import
+            let mut file_paths = file_paths.into_iter();
             let path_info = ImportPathInfo {
-                archive_path: file_path,
+                archive_path: file_paths.next().expect("At least one file is written"),
                 temp_path: lore.import.base_path.clone(),
                 base_path: lore.base_path.clone(),
+                decryption: decryption_from_fields(&fields),
+                base_archives: file_paths.collect(),
+            };
+            let description = if options.dry_run {
+                format!("Planning import of {:?}", path_info.archive_path)
+            } else {
+                format!("Importing {:?}", path_info.archive_path)
             };
             match quest_master
                 .lock()
                 .await
-                .schedule_quest(
-                    format!("Importing {:?}", path_info.archive_path),
-                    move |quest| async move {
-                        importius
-                            .import_archive(quest, vault, floxy, lore, usb_device_reader, path_info)
-                            .map_err(|e| anyhow::anyhow!(e))
-                            .await
-                    },
-                )
+                .schedule_quest_with_result(description, move |quest| async move {
+                    importius
+                        .import_archive(
+                            quest,
+                            vault,
+                            floxy,
+                            lore,
+                            usb_device_reader,
+                            path_info,
+                            options,
+                        )
+                        .map_ok(QuestResult::ImportPlan)
+                        .map_err(|e| anyhow::anyhow!(e))
+                        .await
+                })
                 .await
             {
                 Ok((id, _)) => PostResponse::Status202_Accepted(models::JobMeta::new(id.0 as i32)),
@@ -55,3 +114,63 @@ pub async fn post<I: Importius, U: UsbDeviceReader + 'static>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::instance::InstanceId;
+    use crate::sorcerer::importius::ConflictPolicy;
+    use std::collections::HashMap;
+
+    #[test]
+    fn decryption_from_fields_ok() {
+        let decryption = decryption_from_fields(&[
+            ("passphrase".to_string(), "correct horse".to_string()),
+            ("identity".to_string(), "AGE-SECRET-KEY-1".to_string()),
+            ("other".to_string(), "value".to_string()),
+            ("passphrase".to_string(), "battery staple".to_string()),
+        ]);
+        assert_eq!(
+            decryption.passphrases,
+            vec!["correct horse".to_string(), "battery staple".to_string()]
+        );
+        assert_eq!(decryption.identities, vec!["AGE-SECRET-KEY-1".to_string()]);
+    }
+
+    #[test]
+    fn options_from_fields_default() {
+        assert_eq!(
+            options_from_fields(&[("passphrase".to_string(), "secret".to_string())]).unwrap(),
+            ImportOptions::default()
+        );
+    }
+
+    #[test]
+    fn options_from_fields_ok() {
+        let options = options_from_fields(&[(
+            "options".to_string(),
+            r#"{"instances": ["0000abcd"], "deployments": [], "conflictPolicy": "new-instance-id", "parentAdapters": {"eth0": "enp1s0"}, "dryRun": true}"#.to_string(),
+        )])
+        .unwrap();
+        assert_eq!(
+            options,
+            ImportOptions {
+                apps: None,
+                instances: Some(vec![InstanceId::new(0xabcd)]),
+                deployments: Some(Vec::new()),
+                conflict_policy: ConflictPolicy::NewInstanceId,
+                parent_adapters: HashMap::from([("eth0".to_string(), "enp1s0".to_string())]),
+                dry_run: true,
+            }
+        );
+    }
+
+    #[test]
+    fn options_from_fields_err() {
+        assert!(
+            options_from_fields(&[("options".to_string(), "{\"unknown\": 1}".to_string())])
+                .is_err()
+        );
+        assert!(options_from_fields(&[("options".to_string(), "no json".to_string())]).is_err());
+    }
+}
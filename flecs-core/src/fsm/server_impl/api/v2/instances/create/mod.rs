@@ -28,6 +28,7 @@ pub async fn post<A: AppRaiser, I: Instancius + 'static>(
         ));
     }
     let instance_name = request.instance_name;
+    let deployment_id = request.deployment_id;
     let (id, _quest) = quest_master
         .lock()
         .await
@@ -41,6 +42,7 @@ pub async fn post<A: AppRaiser, I: Instancius + 'static>(
                         lore,
                         app_key,
                         instance_name.unwrap_or_default(),
+                        deployment_id,
                     )
                     .await?;
                 Ok(QuestResult::InstanceId(id))
@@ -87,6 +89,7 @@ mod tests {
                         version: "1.2.3".to_string()
                     },
                     instance_name: None,
+                    deployment_id: None,
                 },
             )
             .await,
@@ -105,13 +108,14 @@ mod tests {
         let mut instancius = MockInstancius::new();
         instancius
             .expect_create_instance()
-            .withf(move |_, _, _, app_key, name| {
+            .withf(move |_, _, _, app_key, name, deployment_id| {
                 app_key.name == expected_key.name
                     && app_key.version == expected_key.version
                     && name.is_empty()
+                    && deployment_id.is_none()
             })
             .once()
-            .returning(|_, _, _, _, _| Ok(InstanceId::new(1)));
+            .returning(|_, _, _, _, _, _| Ok(InstanceId::new(1)));
         let mut appraiser = MockAppRaiser::new();
         appraiser
             .expect_does_app_exist()
@@ -128,6 +132,7 @@ mod tests {
             PostRequest {
                 app_key: test_key.clone(),
                 instance_name: None,
+                deployment_id: None,
             },
         )
         .await;
@@ -0,0 +1,171 @@
+use crate::fsm::server_impl::api::v2::models::{Accepted, AdditionalInfo};
+use crate::fsm::server_impl::state::{FloxyState, InstanciusState, QuestMasterState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::quest::QuestResult;
+use crate::sorcerer::instancius::Instancius;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::{IntoParams, ToSchema};
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PostPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(as = CloneInstanceRequest)]
+pub struct PostRequest {
+    /// Name of the new instance, defaults to the name of the cloned instance with suffix '-clone'
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Copy the current config files of the cloned instance instead of the defaults of the app
+    #[serde(default)]
+    pub copy_config_files: bool,
+    /// Copy the content of all volumes of the cloned instance
+    #[serde(default)]
+    pub copy_volumes: bool,
+}
+
+#[utoipa::path(
+    post,
+    path = "/instances/{instance_id}/clone",
+    tag = "Experimental",
+    description = "Create a new instance with the configuration of the specified instance. The new instance is connected to the same networks with new addresses and uses free host ports for all port mappings. Running instances are halted while config files or volumes are copied.",
+    params(PostPathParams),
+    request_body(content = PostRequest),
+    responses(
+        (status = ACCEPTED, description = "Cloning of instance triggered, the result of the job is the id of the new instance", body = Accepted),
+        (status = NOT_FOUND, description = "Instance not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn post<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    State(QuestMasterState(quest_master)): State<QuestMasterState>,
+    Path(PostPathParams { instance_id }): Path<PostPathParams>,
+    Json(request): Json<PostRequest>,
+) -> Response {
+    if !instancius
+        .does_instance_exist(vault.clone(), instance_id)
+        .await
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    match quest_master
+        .lock()
+        .await
+        .schedule_quest_with_result(
+            format!("Clone instance {instance_id}"),
+            move |quest| async move {
+                let clone_id = instancius
+                    .clone_instance(
+                        quest,
+                        vault,
+                        floxy,
+                        instance_id,
+                        request.name,
+                        request.copy_config_files,
+                        request.copy_volumes,
+                    )
+                    .await?;
+                tracing::info!("Cloned instance {instance_id} as {clone_id}");
+                Ok(QuestResult::InstanceId(clone_id))
+            },
+        )
+        .await
+    {
+        Ok((id, _)) => Accepted::new(id).into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enchantment::quest_master::QuestMaster;
+    use crate::fsm::server_impl::await_quest_completion;
+    use crate::relic::floxy::MockFloxy;
+    use crate::sorcerer::instancius::MockInstancius;
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn post_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_does_instance_exist()
+            .once()
+            .returning(|_, _| false);
+        let response = post(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            State(QuestMasterState(QuestMaster::default())),
+            Path(PostPathParams {
+                instance_id: InstanceId::new(6),
+            }),
+            Json(PostRequest::default()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn post_202() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_does_instance_exist()
+            .once()
+            .returning(|_, _| true);
+        instancius
+            .expect_clone_instance()
+            .withf(|_, _, _, id, name, copy_config_files, copy_volumes| {
+                *id == InstanceId::new(6)
+                    && name.as_deref() == Some("clone")
+                    && *copy_config_files
+                    && !*copy_volumes
+            })
+            .once()
+            .returning(|_, _, _, _, _, _, _| Ok(InstanceId::new(7)));
+        let quest_master = QuestMaster::default();
+        let response = post(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            State(QuestMasterState(quest_master.clone())),
+            Path(PostPathParams {
+                instance_id: InstanceId::new(6),
+            }),
+            Json(PostRequest {
+                name: Some("clone".to_string()),
+                copy_config_files: true,
+                copy_volumes: false,
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let accepted: Accepted = serde_json::from_slice(&body).unwrap();
+        await_quest_completion(quest_master.clone()).await;
+        let quest = quest_master
+            .lock()
+            .await
+            .query_quest(accepted.quest_id)
+            .unwrap();
+        assert_eq!(
+            quest.lock().await.result,
+            QuestResult::InstanceId(InstanceId::new(7))
+        );
+    }
+}
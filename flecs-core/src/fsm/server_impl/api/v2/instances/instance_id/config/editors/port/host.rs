@@ -0,0 +1,250 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{FloxyState, InstanciusState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::sorcerer::instancius::{EditorCertificate, Instancius};
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::{IntoParams, ToSchema};
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeletePathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+    pub port: u16,
+}
+
+#[utoipa::path(
+    delete,
+    path = "/instances/{instance_id}/config/editors/{port}/host",
+    tag = "Experimental",
+    summary = "Stop serving the specified editor under its dedicated host name",
+    params(DeletePathParams),
+    responses(
+        (status = OK, description = "Host name and certificate of the editor removed"),
+        (status = NOT_FOUND, description = "Instance or editor not found or no host name configured"),
+        (status = BAD_REQUEST, description = "Bad request", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn delete<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    Path(DeletePathParams { instance_id, port }): Path<DeletePathParams>,
+) -> Response {
+    match instancius
+        .delete_instance_editor_host(vault, floxy, instance_id, port)
+        .await
+    {
+        Ok(Some(_)) => StatusCode::OK.into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub type PutPathParams = DeletePathParams;
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[schema(as = PutEditorHostRequest)]
+pub struct PutRequest {
+    /// Host name the editor is served under, e.g. "node-red.flecs.local"
+    pub host_name: String,
+    /// Serve the editor via https with the given certificate, plain http if not specified
+    #[serde(default)]
+    pub tls: Option<EditorCertificate>,
+}
+
+#[utoipa::path(
+    put,
+    path = "/instances/{instance_id}/config/editors/{port}/host",
+    tag = "Experimental",
+    summary = "Serve the specified editor under a dedicated host name, optionally via https",
+    params(PutPathParams),
+    request_body(content = PutRequest),
+    responses(
+        (status = OK, description = "Host name of the editor was overwritten"),
+        (status = CREATED, description = "Host name of the editor was set"),
+        (status = BAD_REQUEST, description = "Invalid host name or certificate", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or editor not found"),
+        (status = CONFLICT, description = "Host name is already used by another editor", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn put<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    Path(PutPathParams { instance_id, port }): Path<PutPathParams>,
+    Json(PutRequest { host_name, tls }): Json<PutRequest>,
+) -> Response {
+    match instancius
+        .put_instance_editor_host(vault, floxy, instance_id, port, host_name, tls)
+        .await
+    {
+        Ok(Some(_)) => StatusCode::OK.into_response(),
+        Ok(None) => StatusCode::CREATED.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::instance::docker::config::EditorHost;
+    use crate::relic::floxy::MockFloxy;
+    use crate::relic::tls::CertificateError;
+    use crate::sorcerer::instancius::{InstanceEditorHostError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+
+    const INSTANCE_ID: InstanceId = InstanceId::new(6);
+
+    fn editor_host() -> EditorHost {
+        EditorHost {
+            host_name: "editor.flecs.local".to_string(),
+            tls: None,
+        }
+    }
+
+    async fn put_with(instancius: MockInstancius) -> Response {
+        put(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            Path(PutPathParams {
+                instance_id: INSTANCE_ID,
+                port: 1880,
+            }),
+            Json(PutRequest {
+                host_name: "editor.flecs.local".to_string(),
+                tls: Some(EditorCertificate::SelfSigned),
+            }),
+        )
+        .await
+    }
+
+    async fn delete_with(instancius: MockInstancius) -> Response {
+        delete(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            Path(DeletePathParams {
+                instance_id: INSTANCE_ID,
+                port: 1880,
+            }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn put_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_host()
+            .withf(|_, _, id, port, host_name, tls| {
+                *id == INSTANCE_ID
+                    && *port == 1880
+                    && host_name == "editor.flecs.local"
+                    && *tls == Some(EditorCertificate::SelfSigned)
+            })
+            .once()
+            .returning(|_, _, _, _, _, _| Ok(Some(editor_host())));
+        assert_eq!(put_with(instancius).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn put_201() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_host()
+            .once()
+            .returning(|_, _, _, _, _, _| Ok(None));
+        assert_eq!(put_with(instancius).await.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn put_400() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_host()
+            .once()
+            .returning(|_, _, _, _, _, _| {
+                Err(InstanceEditorHostError::Certificate(
+                    CertificateError::KeyMismatch,
+                ))
+            });
+        assert_eq!(put_with(instancius).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn put_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_host()
+            .once()
+            .returning(|_, _, id, port, _, _| {
+                Err(InstanceEditorHostError::EditorNotFound(id, port))
+            });
+        assert_eq!(put_with(instancius).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn put_409() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_host()
+            .once()
+            .returning(|_, _, _, _, host_name, _| {
+                Err(InstanceEditorHostError::HostNameInUse {
+                    host_name,
+                    instance_id: InstanceId::new(7),
+                    port: 8080,
+                })
+            });
+        assert_eq!(put_with(instancius).await.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn put_500() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_host()
+            .once()
+            .returning(|_, _, _, _, _, _| {
+                Err(InstanceEditorHostError::Other(anyhow::anyhow!("TestError")))
+            });
+        assert_eq!(
+            put_with(instancius).await.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_delete_instance_editor_host()
+            .withf(|_, _, id, port| *id == INSTANCE_ID && *port == 1880)
+            .once()
+            .returning(|_, _, _, _| Ok(Some(editor_host())));
+        assert_eq!(delete_with(instancius).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn delete_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_delete_instance_editor_host()
+            .once()
+            .returning(|_, _, _, _| Ok(None));
+        assert_eq!(
+            delete_with(instancius).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
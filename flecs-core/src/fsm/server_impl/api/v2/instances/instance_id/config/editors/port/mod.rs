@@ -1,4 +1,6 @@
+pub mod host;
 pub mod path_prefix;
+pub mod roles;
 
 use crate::jeweler::gem::instance::InstanceId;
 use crate::sorcerer::instancius::{InstanceEditorPathPrefixError, Instancius};
@@ -0,0 +1,295 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{FloxyState, InstanciusState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::jeweler::gem::manifest::single::EditorRoles;
+use crate::sorcerer::instancius::Instancius;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+    pub port: u16,
+}
+
+#[utoipa::path(
+    get,
+    path = "/instances/{instance_id}/config/editors/{port}/roles",
+    tag = "Experimental",
+    summary = "Get the roles required to access the specified editor",
+    params(GetPathParams),
+    responses(
+        (status = OK, description = "Roles required to access the editor, access is not restricted if empty", body = EditorRoles),
+        (status = NOT_FOUND, description = "Instance or editor not found"),
+        (status = BAD_REQUEST, description = "Bad request", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(GetPathParams { instance_id, port }): Path<GetPathParams>,
+) -> Response {
+    match instancius
+        .get_instance_editor_roles(vault, instance_id, port)
+        .await
+    {
+        Ok(roles) => Json(roles).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub type PutPathParams = GetPathParams;
+
+#[utoipa::path(
+    put,
+    path = "/instances/{instance_id}/config/editors/{port}/roles",
+    tag = "Experimental",
+    summary = "Overwrite the roles required to access the specified editor",
+    params(PutPathParams),
+    request_body(content = EditorRoles),
+    responses(
+        (status = OK, description = "Roles of the editor were overwritten"),
+        (status = CREATED, description = "Roles of the editor were set"),
+        (status = BAD_REQUEST, description = "Bad request", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or editor not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn put<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    Path(PutPathParams { instance_id, port }): Path<PutPathParams>,
+    Json(roles): Json<EditorRoles>,
+) -> Response {
+    match instancius
+        .put_instance_editor_roles(vault, floxy, instance_id, port, roles)
+        .await
+    {
+        Ok(Some(_)) => StatusCode::OK.into_response(),
+        Ok(None) => StatusCode::CREATED.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+pub type DeletePathParams = GetPathParams;
+
+#[utoipa::path(
+    delete,
+    path = "/instances/{instance_id}/config/editors/{port}/roles",
+    tag = "Experimental",
+    summary = "Remove the roles configured for the specified editor, the roles declared by the app apply again",
+    params(DeletePathParams),
+    responses(
+        (status = OK, description = "Roles of the editor removed"),
+        (status = NOT_FOUND, description = "Instance or editor not found or no roles configured"),
+        (status = BAD_REQUEST, description = "Bad request", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn delete<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    Path(DeletePathParams { instance_id, port }): Path<DeletePathParams>,
+) -> Response {
+    match instancius
+        .delete_instance_editor_roles(vault, floxy, instance_id, port)
+        .await
+    {
+        Ok(Some(_)) => StatusCode::OK.into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relic::floxy::MockFloxy;
+    use crate::sorcerer::instancius::{InstanceEditorRolesError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+
+    const INSTANCE_ID: InstanceId = InstanceId::new(6);
+
+    fn editor_roles() -> EditorRoles {
+        EditorRoles {
+            read: ["operator".to_string()].into(),
+            write: ["engineer".to_string()].into(),
+        }
+    }
+
+    fn path_params() -> GetPathParams {
+        GetPathParams {
+            instance_id: INSTANCE_ID,
+            port: 1880,
+        }
+    }
+
+    async fn get_with(instancius: MockInstancius) -> Response {
+        get(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            Path(path_params()),
+        )
+        .await
+    }
+
+    async fn put_with(instancius: MockInstancius) -> Response {
+        put(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            Path(path_params()),
+            Json(editor_roles()),
+        )
+        .await
+    }
+
+    async fn delete_with(instancius: MockInstancius) -> Response {
+        delete(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            Path(path_params()),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_editor_roles()
+            .withf(|_, id, port| *id == INSTANCE_ID && *port == 1880)
+            .once()
+            .returning(|_, _, _| Ok(editor_roles()));
+        let response = get_with(instancius).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<EditorRoles>(&body).unwrap(),
+            editor_roles()
+        );
+    }
+
+    #[tokio::test]
+    async fn get_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_editor_roles()
+            .once()
+            .returning(|_, id, port| Err(InstanceEditorRolesError::EditorNotFound(id, port)));
+        assert_eq!(get_with(instancius).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn get_500() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_editor_roles()
+            .once()
+            .returning(|_, _, _| {
+                Err(InstanceEditorRolesError::Other(anyhow::anyhow!(
+                    "TestError"
+                )))
+            });
+        assert_eq!(
+            get_with(instancius).await.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn put_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_roles()
+            .withf(|_, _, id, port, roles| {
+                *id == INSTANCE_ID && *port == 1880 && *roles == editor_roles()
+            })
+            .once()
+            .returning(|_, _, _, _, _| Ok(Some(EditorRoles::default())));
+        assert_eq!(put_with(instancius).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn put_201() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_roles()
+            .once()
+            .returning(|_, _, _, _, _| Ok(None));
+        assert_eq!(put_with(instancius).await.status(), StatusCode::CREATED);
+    }
+
+    #[tokio::test]
+    async fn put_400() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_roles()
+            .once()
+            .returning(|_, _, id, _, _| Err(InstanceEditorRolesError::NotSupported(id)));
+        assert_eq!(put_with(instancius).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn put_400_invalid_role() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_roles()
+            .once()
+            .returning(|_, _, _, _, _| Err(InstanceEditorRolesError::InvalidRole(String::new())));
+        let response = put_with(instancius).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(serde_json::from_slice::<AdditionalInfo>(&body).is_ok());
+    }
+
+    #[tokio::test]
+    async fn put_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_editor_roles()
+            .once()
+            .returning(|_, _, id, _, _| Err(InstanceEditorRolesError::InstanceNotFound(id)));
+        assert_eq!(put_with(instancius).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn delete_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_delete_instance_editor_roles()
+            .withf(|_, _, id, port| *id == INSTANCE_ID && *port == 1880)
+            .once()
+            .returning(|_, _, _, _| Ok(Some(editor_roles())));
+        assert_eq!(delete_with(instancius).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn delete_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_delete_instance_editor_roles()
+            .once()
+            .returning(|_, _, _, _| Ok(None));
+        assert_eq!(
+            delete_with(instancius).await.status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -1,7 +1,8 @@
-use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::api::v2::models::{
+    AdditionalInfo, PutDependencyRequest as PutRequest,
+};
 use crate::fsm::server_impl::state::{ProvidiusState, VaultState};
 use crate::jeweler::gem::instance::InstanceId;
-use crate::jeweler::gem::instance::ProviderReference as PutRequest;
 use crate::jeweler::gem::manifest::DependencyKey;
 use crate::sorcerer::providius::{
     ClearDependencyError, Dependency, GetDependencyError, SetDependencyError,
@@ -95,7 +96,7 @@ pub type PutPathParams = DeletePathParams;
     summary = "Set a provider for the specified feature of the specified instance",
     request_body(
         content = PutRequest,
-        description = "The provider that should be used",
+        description = "The provider that should be used, or the providers in the order they should be used if the preceding ones are not available",
     ),
     params(PutPathParams),
     responses(
@@ -114,7 +115,7 @@ pub async fn put(
         instance_id,
         dependency_key,
     }): Path<PutPathParams>,
-    Json(provider_reference): Json<PutRequest>,
+    Json(request): Json<PutRequest>,
 ) -> Result<Response, SetDependencyError> {
     let features = dependency_key.features();
     if features.len() != 1 {
@@ -123,6 +124,10 @@ pub async fn put(
         )
         .into_bad_request());
     }
+    let Some((provider_reference, fallback_providers)) = request.into_provider_and_fallbacks()
+    else {
+        return Ok(AdditionalInfo::new("No provider specified".to_string()).into_bad_request());
+    };
     match providius
         .set_dependency(
             vault,
@@ -130,6 +135,7 @@ pub async fn put(
             features[0].clone(),
             instance_id,
             provider_reference,
+            fallback_providers,
         )
         .await?
     {
@@ -1,8 +1,9 @@
 use crate::fsm::server_impl::api::v2::instances::instance_id::depends::dependency_key::InstanceNotFoundOrNotDependent;
-use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::api::v2::models::{
+    AdditionalInfo, PutDependencyRequest as PutRequest,
+};
 use crate::fsm::server_impl::state::{ProvidiusState, VaultState};
 use crate::jeweler::gem::instance::InstanceId;
-use crate::jeweler::gem::instance::ProviderReference as PutRequest;
 use crate::jeweler::gem::manifest::{DependencyKey, FeatureKey};
 use crate::sorcerer::providius::SetDependencyError;
 use axum::Json;
@@ -29,7 +30,7 @@ pub struct PutPathParams {
     summary = "Set a provider for the specified feature of the specified instance",
     request_body(
         content = PutRequest,
-        description = "The provider that should be used",
+        description = "The provider that should be used, or the providers in the order they should be used if the preceding ones are not available",
     ),
     params(PutPathParams),
     responses(
@@ -51,8 +52,19 @@ pub async fn put(
     }): Path<PutPathParams>,
     Json(request): Json<PutRequest>,
 ) -> Result<Response, SetDependencyError> {
+    let Some((provider_reference, fallback_providers)) = request.into_provider_and_fallbacks()
+    else {
+        return Ok(AdditionalInfo::new("No provider specified".to_string()).into_bad_request());
+    };
     match providius
-        .set_dependency(vault, dependency_key, feature, instance_id, request)
+        .set_dependency(
+            vault,
+            dependency_key,
+            feature,
+            instance_id,
+            provider_reference,
+            fallback_providers,
+        )
         .await?
     {
         Some(_) => Ok(StatusCode::OK.into_response()),
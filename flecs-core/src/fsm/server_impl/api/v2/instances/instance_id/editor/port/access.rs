@@ -0,0 +1,185 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{InstanciusState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::sorcerer::instancius::Instancius;
+use crate::wall::watch::RolesExtension;
+use axum::Extension;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::{HeaderMap, Method, StatusCode};
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::IntoParams;
+
+/// Set by floxy to the method of the request to the editor, authorization subrequests are always
+/// sent via GET
+pub const ORIGINAL_METHOD_HEADER: &str = "X-Original-Method";
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+    pub port: u16,
+}
+
+#[utoipa::path(
+    get,
+    path = "/instances/{instance_id}/editor/{port}/access",
+    tag = "Experimental",
+    summary = "Check if the user may access the specified editor, used by floxy to authorize editor requests",
+    params(
+        GetPathParams,
+        ("X-Original-Method" = String, Header, description = "Method of the request to the editor"),
+    ),
+    responses(
+        (status = NO_CONTENT, description = "Access granted"),
+        (status = UNAUTHORIZED, description = "Access requires authentication"),
+        (status = FORBIDDEN, description = "Access denied"),
+        (status = BAD_REQUEST, description = "Missing or invalid original method", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or editor not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Extension(RolesExtension(roles)): Extension<RolesExtension>,
+    Path(GetPathParams { instance_id, port }): Path<GetPathParams>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(method) = headers
+        .get(ORIGINAL_METHOD_HEADER)
+        .and_then(|method| Method::from_bytes(method.as_bytes()).ok())
+    else {
+        return AdditionalInfo::new(format!(
+            "Missing or invalid header {ORIGINAL_METHOD_HEADER}"
+        ))
+        .into_bad_request();
+    };
+    match instancius
+        .get_instance_editor_roles(vault, instance_id, port)
+        .await
+    {
+        Ok(editor_roles) if editor_roles.allows(&method, &roles) => {
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(_) if roles.is_empty() => StatusCode::UNAUTHORIZED.into_response(),
+        Ok(_) => StatusCode::FORBIDDEN.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::manifest::single::EditorRoles;
+    use crate::sorcerer::instancius::{InstanceEditorRolesError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::collections::HashSet;
+    use std::sync::Arc;
+
+    const INSTANCE_ID: InstanceId = InstanceId::new(6);
+
+    fn editor_instancius() -> MockInstancius {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_editor_roles()
+            .withf(|_, id, port| *id == INSTANCE_ID && *port == 1880)
+            .returning(|_, _, _| {
+                Ok(EditorRoles {
+                    read: ["operator".to_string()].into(),
+                    write: ["engineer".to_string()].into(),
+                })
+            });
+        instancius
+    }
+
+    async fn get_with(
+        instancius: MockInstancius,
+        roles: &[&str],
+        method: Option<&'static str>,
+    ) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(method) = method {
+            headers.insert(ORIGINAL_METHOD_HEADER, method.parse().unwrap());
+        }
+        get(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            Extension(RolesExtension(
+                roles
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<HashSet<_>>(),
+            )),
+            Path(GetPathParams {
+                instance_id: INSTANCE_ID,
+                port: 1880,
+            }),
+            headers,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_204() {
+        assert_eq!(
+            get_with(editor_instancius(), &["operator"], Some("GET"))
+                .await
+                .status(),
+            StatusCode::NO_CONTENT
+        );
+        assert_eq!(
+            get_with(editor_instancius(), &["engineer"], Some("POST"))
+                .await
+                .status(),
+            StatusCode::NO_CONTENT
+        );
+    }
+
+    #[tokio::test]
+    async fn get_401() {
+        assert_eq!(
+            get_with(editor_instancius(), &[], Some("GET"))
+                .await
+                .status(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn get_403() {
+        assert_eq!(
+            get_with(editor_instancius(), &["operator"], Some("POST"))
+                .await
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+    }
+
+    #[tokio::test]
+    async fn get_400() {
+        let mut instancius = MockInstancius::new();
+        instancius.expect_get_instance_editor_roles().never();
+        assert_eq!(
+            get_with(instancius, &["engineer"], None).await.status(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    async fn get_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_editor_roles()
+            .once()
+            .returning(|_, id, port| Err(InstanceEditorRolesError::EditorNotFound(id, port)));
+        assert_eq!(
+            get_with(instancius, &["engineer"], Some("GET"))
+                .await
+                .status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
@@ -1,3 +1,6 @@
+#[cfg(feature = "auth")]
+pub mod access;
+
 use crate::jeweler::gem::instance::InstanceId;
 use crate::lore::FloxyLoreRef;
 use crate::relic::floxy::Floxy;
@@ -0,0 +1,157 @@
+pub mod tty;
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{InstanciusState, VaultState};
+use crate::jeweler::gem::instance::{ExecOutput, InstanceId};
+use crate::sorcerer::instancius::Instancius;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use std::time::Duration;
+use utoipa::{IntoParams, ToSchema};
+
+const DEFAULT_EXEC_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PostPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+#[schema(as = ExecInstanceRequest)]
+pub struct PostRequest {
+    /// Command and its arguments, the command is not run inside a shell
+    pub cmd: Vec<String>,
+    /// Maximum time in seconds to wait for the command to finish, defaults to 60
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/instances/{instance_id}/exec",
+    tag = "Experimental",
+    description = "Execute a command inside the running instance and wait for it to finish",
+    params(PostPathParams),
+    request_body(content = PostRequest),
+    responses(
+        (status = OK, description = "Command executed", body = ExecOutput),
+        (status = BAD_REQUEST, description = "Empty command or instance does not support executing commands", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance not found"),
+        (status = CONFLICT, description = "Instance not running", body = AdditionalInfo),
+        (status = GATEWAY_TIMEOUT, description = "Command did not finish in time", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn post<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(PostPathParams { instance_id }): Path<PostPathParams>,
+    Json(request): Json<PostRequest>,
+) -> Response {
+    if request.cmd.is_empty() {
+        return AdditionalInfo::new("No command specified".to_string()).into_bad_request();
+    }
+    let timeout = request
+        .timeout
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_EXEC_TIMEOUT);
+    match instancius
+        .exec_instance(vault, instance_id, request.cmd, timeout)
+        .await
+    {
+        Ok(output) => (StatusCode::OK, Json(output)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::instancius::{ExecInstanceError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+
+    async fn post_with(instancius: MockInstancius, cmd: Vec<String>) -> Response {
+        post(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            Path(PostPathParams {
+                instance_id: InstanceId::new(6),
+            }),
+            Json(PostRequest { cmd, timeout: None }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_exec_instance()
+            .withf(|_, id, cmd, timeout| {
+                id.value == 6 && cmd == &["ls".to_string()] && *timeout == DEFAULT_EXEC_TIMEOUT
+            })
+            .once()
+            .returning(|_, _, _, _| {
+                Ok(ExecOutput {
+                    exit_code: Some(0),
+                    stdout: "config.json".to_string(),
+                    stderr: String::new(),
+                    truncated: false,
+                })
+            });
+        let response = post_with(instancius, vec!["ls".to_string()]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn post_400() {
+        let mut instancius = MockInstancius::new();
+        instancius.expect_exec_instance().never();
+        let response = post_with(instancius, Vec::new()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn post_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_exec_instance()
+            .once()
+            .returning(|_, id, _, _| Err(ExecInstanceError::NotFound(id)));
+        let response = post_with(instancius, vec!["ls".to_string()]).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn post_409() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_exec_instance()
+            .once()
+            .returning(|_, id, _, _| Err(ExecInstanceError::NotRunning(id)));
+        let response = post_with(instancius, vec!["ls".to_string()]).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn post_504() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_exec_instance()
+            .once()
+            .returning(|_, instance_id, _, timeout| {
+                Err(ExecInstanceError::Timeout {
+                    instance_id,
+                    timeout,
+                })
+            });
+        let response = post_with(instancius, vec!["ls".to_string()]).await;
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+}
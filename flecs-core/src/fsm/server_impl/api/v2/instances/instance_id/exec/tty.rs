@@ -0,0 +1,234 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{InstanciusState, VaultState};
+use crate::jeweler::gem::instance::{ExecSession, InstanceId};
+use crate::relic::docker::container::ExecInput;
+use crate::sorcerer::instancius::Instancius;
+use crate::vault::Vault;
+use axum::extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code};
+use axum::extract::{Path, Query, State};
+use axum::response::{IntoResponse, Response};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+use utoipa::{IntoParams, ToSchema};
+
+const DEFAULT_SHELL: &str = "/bin/sh";
+/// Ctrl+C followed by Ctrl+D, see [terminate_command]
+const TERMINATE_INPUT: &[u8] = b"\x03\x04";
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetQueryParams {
+    /// Command that is started with the terminal, defaults to /bin/sh. The command is split at
+    /// whitespace into its arguments and not run inside a shell
+    #[serde(default)]
+    pub cmd: Option<String>,
+}
+
+/// Control messages are sent as text messages, binary messages are forwarded to the terminal
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlMessage {
+    Resize { width: u16, height: u16 },
+}
+
+#[utoipa::path(
+    get,
+    path = "/instances/{instance_id}/exec/tty",
+    tag = "Experimental",
+    description = "Open an interactive terminal inside the running instance via WebSocket. Binary messages are forwarded to the terminal, the output of the terminal is sent as binary messages. Text messages are interpreted as control messages e.g. to resize the terminal. The connection is closed when the command exits, the command is interrupted and its input is closed when the client disconnects. If the command can not be started the connection is closed with status 1011.",
+    params(GetPathParams, GetQueryParams),
+    responses(
+        (status = SWITCHING_PROTOCOLS, description = "Terminal opened"),
+        (status = BAD_REQUEST, description = "Instance does not support executing commands", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance not found"),
+        (status = CONFLICT, description = "Instance not running", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(GetPathParams { instance_id }): Path<GetPathParams>,
+    Query(GetQueryParams { cmd }): Query<GetQueryParams>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    if let Err(e) = instancius
+        .check_instance_exec(vault.clone(), instance_id)
+        .await
+    {
+        return e.into_response();
+    }
+    let cmd = command_args(cmd.as_deref());
+    ws.on_upgrade(move |socket| forward_session(socket, cmd, vault, instancius, instance_id))
+}
+
+fn command_args(cmd: Option<&str>) -> Vec<String> {
+    let args: Vec<String> = cmd
+        .unwrap_or_default()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    if args.is_empty() {
+        vec![DEFAULT_SHELL.to_string()]
+    } else {
+        args
+    }
+}
+
+/// The exec instance is only created once the connection is upgraded so that no command is
+/// left running if the upgrade fails
+async fn forward_session<I: Instancius>(
+    mut socket: WebSocket,
+    cmd: Vec<String>,
+    vault: Arc<Vault>,
+    instancius: Arc<I>,
+    instance_id: InstanceId,
+) {
+    let ExecSession {
+        id: exec_id,
+        mut output,
+        mut input,
+    } = match instancius
+        .exec_instance_tty(vault.clone(), instance_id, cmd)
+        .await
+    {
+        Ok(session) => session,
+        Err(e) => {
+            warn!("Could not open terminal in instance {instance_id}: {e}");
+            let _ = socket
+                .send(Message::Close(Some(CloseFrame {
+                    code: close_code::ERROR,
+                    reason: e.to_string().into(),
+                })))
+                .await;
+            return;
+        }
+    };
+    let (mut sender, mut receiver) = socket.split();
+    // Resolves to true if the command exited and false if the client is gone
+    let forward_output = async {
+        while let Some(data) = output.next().await {
+            match data {
+                Ok(data) => {
+                    if sender.send(Message::Binary(data.to_vec())).await.is_err() {
+                        return false;
+                    }
+                }
+                Err(e) => {
+                    warn!("Could not read terminal of instance {instance_id}: {e}");
+                    return false;
+                }
+            }
+        }
+        let _ = sender.send(Message::Close(None)).await;
+        true
+    };
+    let forward_input = async {
+        while let Some(Ok(message)) = receiver.next().await {
+            match message {
+                Message::Binary(data) => {
+                    if let Err(e) = input.write_all(&data).await {
+                        warn!("Could not write to terminal of instance {instance_id}: {e}");
+                        return;
+                    }
+                }
+                Message::Text(text) => match serde_json::from_str::<ControlMessage>(&text) {
+                    Ok(ControlMessage::Resize { width, height }) => {
+                        if let Err(e) = instancius
+                            .resize_instance_exec(
+                                vault.clone(),
+                                instance_id,
+                                &exec_id,
+                                width,
+                                height,
+                            )
+                            .await
+                        {
+                            warn!("Could not resize terminal of instance {instance_id}: {e}");
+                        }
+                    }
+                    Err(e) => warn!("Ignoring invalid control message: {e}"),
+                },
+                Message::Close(_) => return,
+                Message::Ping(_) | Message::Pong(_) => {}
+            }
+        }
+    };
+    let command_exited = tokio::select! {
+        command_exited = forward_output => command_exited,
+        _ = forward_input => false,
+    };
+    if command_exited {
+        return;
+    }
+    if let Err(e) = terminate_command(&mut input).await {
+        warn!("Could not terminate command in terminal of instance {instance_id}: {e}");
+    }
+}
+
+/// Docker keeps exec instances running when their input is closed, the foreground process of
+/// the terminal is therefore interrupted and the end of the input is signaled like a user
+/// pressing Ctrl+C and Ctrl+D would before the input is closed
+async fn terminate_command(input: &mut ExecInput) -> std::io::Result<()> {
+    input.write_all(TERMINATE_INPUT).await?;
+    input.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    #[test]
+    fn command_args_split() {
+        assert_eq!(
+            command_args(Some("ls  -la /data")),
+            vec!["ls".to_string(), "-la".to_string(), "/data".to_string()]
+        );
+    }
+
+    #[test]
+    fn command_args_default() {
+        assert_eq!(command_args(None), vec![DEFAULT_SHELL.to_string()]);
+        assert_eq!(command_args(Some(" ")), vec![DEFAULT_SHELL.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn terminate_command_closes_input() {
+        let (writer, mut reader) = tokio::io::duplex(64);
+        let mut input: ExecInput = Box::pin(writer);
+        terminate_command(&mut input).await.unwrap();
+        let mut written = Vec::new();
+        reader.read_to_end(&mut written).await.unwrap();
+        assert_eq!(written, TERMINATE_INPUT);
+    }
+
+    #[test]
+    fn control_message_resize() {
+        assert_eq!(
+            serde_json::from_str::<ControlMessage>(r#"{"type":"resize","width":80,"height":24}"#)
+                .unwrap(),
+            ControlMessage::Resize {
+                width: 80,
+                height: 24
+            }
+        );
+    }
+
+    #[test]
+    fn control_message_invalid() {
+        assert!(serde_json::from_str::<ControlMessage>(r#"{"type":"unknown"}"#).is_err());
+        assert!(serde_json::from_str::<ControlMessage>("ls -la").is_err());
+    }
+}
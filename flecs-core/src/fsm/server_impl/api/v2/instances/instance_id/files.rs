@@ -0,0 +1,98 @@
+pub mod conffiles;
+pub mod volumes;
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{InstanciusState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::jeweler::gem::instance::docker::files::FileLocations;
+use crate::sorcerer::instancius::Instancius;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+}
+
+#[utoipa::path(
+    get,
+    path = "/instances/{instance_id}/files",
+    tag = "Experimental",
+    description = "Get the config files and volumes of the instance which can be browsed",
+    params(GetPathParams),
+    responses(
+        (status = OK, description = "Config files and volumes of the instance", body = FileLocations),
+        (status = BAD_REQUEST, description = "Instance does not support browsing files", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(GetPathParams { instance_id }): Path<GetPathParams>,
+) -> Response {
+    match instancius
+        .get_instance_file_locations(vault, instance_id)
+        .await
+    {
+        Ok(locations) => (StatusCode::OK, Json(locations)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::instancius::{InstanceFilesError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+
+    async fn get_with(instancius: MockInstancius) -> Response {
+        get(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            Path(GetPathParams {
+                instance_id: InstanceId::new(6),
+            }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_file_locations()
+            .withf(|_, id| id.value == 6)
+            .once()
+            .returning(|_, _| Ok(FileLocations::default()));
+        assert_eq!(get_with(instancius).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_400() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_file_locations()
+            .once()
+            .returning(|_, id| Err(InstanceFilesError::Unsupported(id)));
+        assert_eq!(get_with(instancius).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn get_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_file_locations()
+            .once()
+            .returning(|_, id| Err(InstanceFilesError::NotFound(id)));
+        assert_eq!(get_with(instancius).await.status(), StatusCode::NOT_FOUND);
+    }
+}
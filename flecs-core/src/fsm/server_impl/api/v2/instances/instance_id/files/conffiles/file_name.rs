@@ -0,0 +1,184 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{InstanciusState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::sorcerer::instancius::Instancius;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use http::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+    /// Host file name of the config file as declared in the manifest
+    pub file_name: String,
+}
+
+pub type PutPathParams = GetPathParams;
+
+#[utoipa::path(
+    get,
+    path = "/instances/{instance_id}/files/conffiles/{file_name}",
+    tag = "Experimental",
+    description = "Download a config file of the instance. The file is read from the container if the instance is running.",
+    params(GetPathParams),
+    responses(
+        (status = OK, description = "Content of the config file", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = BAD_REQUEST, description = "Instance does not support browsing files", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or config file not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(GetPathParams {
+        instance_id,
+        file_name,
+    }): Path<GetPathParams>,
+) -> Response {
+    match instancius
+        .get_instance_config_file(vault, instance_id, &file_name)
+        .await
+    {
+        Ok(data) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, "application/octet-stream")],
+            data,
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/instances/{instance_id}/files/conffiles/{file_name}",
+    tag = "Experimental",
+    description = "Upload a config file of the instance. The file is written into the container if the instance is running, otherwise it is used on the next start.",
+    params(PutPathParams),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = OK, description = "Config file written"),
+        (status = BAD_REQUEST, description = "Instance does not support browsing files", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or config file not found"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn put<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(PutPathParams {
+        instance_id,
+        file_name,
+    }): Path<PutPathParams>,
+    body: Bytes,
+) -> Response {
+    match instancius
+        .put_instance_config_file(vault, instance_id, &file_name, &body)
+        .await
+    {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::instancius::{InstanceFilesError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+
+    fn path_params() -> Path<GetPathParams> {
+        Path(GetPathParams {
+            instance_id: InstanceId::new(6),
+            file_name: "default.conf".to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_config_file()
+            .withf(|_, id, file_name| id.value == 6 && file_name == "default.conf")
+            .once()
+            .returning(|_, _, _| Ok(b"key=value".to_vec()));
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            path_params(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.as_ref(), b"key=value");
+    }
+
+    #[tokio::test]
+    async fn get_404() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_config_file()
+            .once()
+            .returning(|_, instance_id, name| {
+                Err(InstanceFilesError::UnknownConfigFile {
+                    instance_id,
+                    name: name.to_string(),
+                })
+            });
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            path_params(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn put_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_config_file()
+            .withf(|_, id, file_name, data| {
+                id.value == 6 && file_name == "default.conf" && data == b"key=value"
+            })
+            .once()
+            .returning(|_, _, _, _| Ok(()));
+        let response = put(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            path_params(),
+            Bytes::from_static(b"key=value"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn put_500() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_config_file()
+            .once()
+            .returning(|_, _, _, _| Err(anyhow::anyhow!("TestError").into()));
+        let response = put(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            path_params(),
+            Bytes::from_static(b"key=value"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
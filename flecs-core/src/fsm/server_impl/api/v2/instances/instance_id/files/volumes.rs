@@ -0,0 +1 @@
+pub mod volume_name;
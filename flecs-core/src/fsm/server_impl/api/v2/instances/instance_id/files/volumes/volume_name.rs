@@ -0,0 +1,100 @@
+pub mod path;
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{InstanciusState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::jeweler::gem::instance::docker::files::FileEntry;
+use crate::sorcerer::instancius::Instancius;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+    pub volume_name: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/instances/{instance_id}/files/volumes/{volume_name}",
+    tag = "Experimental",
+    description = "Recursively list the files and directories inside a volume of the running instance",
+    params(GetPathParams),
+    responses(
+        (status = OK, description = "Files and directories inside the volume", body = [FileEntry]),
+        (status = BAD_REQUEST, description = "Instance does not support browsing files", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or volume not found"),
+        (status = CONFLICT, description = "Instance not running", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(GetPathParams {
+        instance_id,
+        volume_name,
+    }): Path<GetPathParams>,
+) -> Response {
+    match instancius
+        .get_instance_volume_files(vault, instance_id, &volume_name)
+        .await
+    {
+        Ok(entries) => (StatusCode::OK, Json(entries)).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::instancius::{InstanceFilesError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    async fn get_with(instancius: MockInstancius) -> Response {
+        get(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            Path(GetPathParams {
+                instance_id: InstanceId::new(6),
+                volume_name: "volume-1".to_string(),
+            }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_volume_files()
+            .withf(|_, id, volume_name| id.value == 6 && volume_name == "volume-1")
+            .once()
+            .returning(|_, _, _| {
+                Ok(vec![FileEntry {
+                    path: PathBuf::from("data.db"),
+                    size: 1024,
+                    is_dir: false,
+                }])
+            });
+        assert_eq!(get_with(instancius).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_409() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_volume_files()
+            .once()
+            .returning(|_, id, _| Err(InstanceFilesError::NotRunning(id)));
+        assert_eq!(get_with(instancius).await.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{InstanciusState, VaultState};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::sorcerer::instancius::Instancius;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use http::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde_with::{DisplayFromStr, serde_as};
+use std::path::PathBuf;
+use utoipa::IntoParams;
+
+#[serde_as]
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPathParams {
+    #[serde_as(as = "DisplayFromStr")]
+    pub instance_id: InstanceId,
+    pub volume_name: String,
+    /// Path of the file relative to the volume
+    #[param(value_type = String)]
+    pub path: PathBuf,
+}
+
+pub type PutPathParams = GetPathParams;
+
+#[utoipa::path(
+    get,
+    path = "/instances/{instance_id}/files/volumes/{volume_name}/{path}",
+    tag = "Experimental",
+    description = "Download a file inside a volume of the running instance",
+    params(GetPathParams),
+    responses(
+        (status = OK, description = "Content of the file", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = BAD_REQUEST, description = "Path leaves the volume or instance does not support browsing files", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or volume not found"),
+        (status = CONFLICT, description = "Instance not running", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(GetPathParams {
+        instance_id,
+        volume_name,
+        path,
+    }): Path<GetPathParams>,
+) -> Response {
+    match instancius
+        .get_instance_volume_file(vault, instance_id, &volume_name, &path)
+        .await
+    {
+        Ok(data) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, "application/octet-stream")],
+            data,
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/instances/{instance_id}/files/volumes/{volume_name}/{path}",
+    tag = "Experimental",
+    description = "Upload a file into a volume of the running instance, the parent directory has to exist",
+    params(PutPathParams),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = OK, description = "File written"),
+        (status = BAD_REQUEST, description = "Path leaves the volume or instance does not support browsing files", body = AdditionalInfo),
+        (status = NOT_FOUND, description = "Instance or volume not found"),
+        (status = CONFLICT, description = "Instance not running", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn put<I: Instancius + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    Path(PutPathParams {
+        instance_id,
+        volume_name,
+        path,
+    }): Path<PutPathParams>,
+    body: Bytes,
+) -> Response {
+    match instancius
+        .put_instance_volume_file(vault, instance_id, &volume_name, &path, &body)
+        .await
+    {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::instancius::{InstanceFilesError, MockInstancius};
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+
+    fn path_params(path: &str) -> Path<GetPathParams> {
+        Path(GetPathParams {
+            instance_id: InstanceId::new(6),
+            volume_name: "volume-1".to_string(),
+            path: PathBuf::from(path),
+        })
+    }
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_get_instance_volume_file()
+            .withf(|_, id, volume_name, path| {
+                id.value == 6 && volume_name == "volume-1" && path == PathBuf::from("sub/data.db")
+            })
+            .once()
+            .returning(|_, _, _, _| Ok(b"data".to_vec()));
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            path_params("sub/data.db"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn put_400() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_volume_file()
+            .once()
+            .returning(|_, _, _, path, _| Err(InstanceFilesError::InvalidPath(path.to_path_buf())));
+        let response = put(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            path_params("../secret"),
+            Bytes::from_static(b"data"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn put_200() {
+        let mut instancius = MockInstancius::new();
+        instancius
+            .expect_put_instance_volume_file()
+            .withf(|_, _, _, path, data| path == PathBuf::from("data.db") && data == b"data")
+            .once()
+            .returning(|_, _, _, _, _| Ok(()));
+        let response = put(
+            State(VaultState(create_empty_test_vault())),
+            State(InstanciusState(Arc::new(instancius))),
+            path_params("data.db"),
+            Bytes::from_static(b"data"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
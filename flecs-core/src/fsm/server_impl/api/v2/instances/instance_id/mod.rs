@@ -1,6 +1,9 @@
+pub mod clone;
 pub mod config;
 pub mod depends;
 pub mod editor;
+pub mod exec;
+pub mod files;
 pub mod logs;
 pub mod provides;
 pub mod start;
@@ -15,6 +15,7 @@ pub mod manifests;
 pub mod models;
 pub mod providers;
 pub mod quests;
+pub mod state;
 pub mod system;
 
 #[derive(Debug, Serialize)]
@@ -87,11 +88,31 @@ impl Modify for RenameOps {
 #[cfg_attr(
     feature = "auth",
     openapi(paths(
+        apps::app::stage::post,
+        device::license::file::post,
+        device::license::request::get,
+        exports::export_id::increments::post,
+        imports::inspect::post,
+        instances::instance_id::clone::post,
+        instances::instance_id::config::editors::port::host::delete,
+        instances::instance_id::config::editors::port::host::put,
+        instances::instance_id::config::editors::port::roles::delete,
+        instances::instance_id::config::editors::port::roles::get,
+        instances::instance_id::config::editors::port::roles::put,
         instances::instance_id::depends::get,
         instances::instance_id::depends::dependency_key::delete,
         instances::instance_id::depends::dependency_key::get,
         instances::instance_id::depends::dependency_key::put,
         instances::instance_id::depends::dependency_key::feature::put,
+        instances::instance_id::editor::port::access::get,
+        instances::instance_id::exec::post,
+        instances::instance_id::exec::tty::get,
+        instances::instance_id::files::get,
+        instances::instance_id::files::conffiles::file_name::get,
+        instances::instance_id::files::conffiles::file_name::put,
+        instances::instance_id::files::volumes::volume_name::get,
+        instances::instance_id::files::volumes::volume_name::path::get,
+        instances::instance_id::files::volumes::volume_name::path::put,
         instances::instance_id::provides::get,
         instances::instance_id::provides::feature::get,
         providers::get,
@@ -108,17 +129,41 @@ impl Modify for RenameOps {
         providers::auth::default::put,
         providers::auth::first_time_setup::flecsport::post,
         providers::auth::id::get,
+        state::get,
+        state::apply::post,
+        system::ca::get,
         system::sbom::get,
+        system::storage::get,
+        system::storage::prune::post,
     ))
 )]
 #[cfg_attr(
     not(feature = "auth"),
     openapi(paths(
+        apps::app::stage::post,
+        device::license::file::post,
+        device::license::request::get,
+        exports::export_id::increments::post,
+        imports::inspect::post,
+        instances::instance_id::clone::post,
+        instances::instance_id::config::editors::port::host::delete,
+        instances::instance_id::config::editors::port::host::put,
+        instances::instance_id::config::editors::port::roles::delete,
+        instances::instance_id::config::editors::port::roles::get,
+        instances::instance_id::config::editors::port::roles::put,
         instances::instance_id::depends::get,
         instances::instance_id::depends::dependency_key::delete,
         instances::instance_id::depends::dependency_key::get,
         instances::instance_id::depends::dependency_key::put,
         instances::instance_id::depends::dependency_key::feature::put,
+        instances::instance_id::exec::post,
+        instances::instance_id::exec::tty::get,
+        instances::instance_id::files::get,
+        instances::instance_id::files::conffiles::file_name::get,
+        instances::instance_id::files::conffiles::file_name::put,
+        instances::instance_id::files::volumes::volume_name::get,
+        instances::instance_id::files::volumes::volume_name::path::get,
+        instances::instance_id::files::volumes::volume_name::path::put,
         instances::instance_id::provides::get,
         instances::instance_id::provides::feature::get,
         providers::get,
@@ -127,7 +172,12 @@ impl Modify for RenameOps {
         providers::feature::default::get,
         providers::feature::default::put,
         providers::feature::id::get,
+        state::get,
+        state::apply::post,
+        system::ca::get,
         system::sbom::get,
+        system::storage::get,
+        system::storage::prune::post,
     ))
 )]
 pub struct ApiDoc;
@@ -2,12 +2,18 @@ use crate::fsm::server_impl::api::v2::instances::instance_id::depends::dependenc
 use crate::jeweler::gem::instance::ProviderReference;
 use crate::jeweler::gem::manifest::providers::auth::AuthProvider;
 use crate::quest::QuestId;
+use crate::relic::tls::CertificateError;
 use crate::sorcerer;
+use crate::sorcerer::instancius::{
+    ExecInstanceError, InstanceEditorHostError, InstanceEditorPathPrefixError,
+    InstanceEditorRolesError, InstanceFilesError,
+};
 use crate::sorcerer::providius::{
     ClearDependencyError, DeleteDefaultProviderError, GetDependenciesError, GetDependencyError,
     GetFeatureProvidesError, GetProvidesError, Provider, SetCoreAuthProviderError,
     SetDefaultProviderError, SetDependencyError,
 };
+use crate::sorcerer::statius::PlanStateError;
 use crate::vault::pouch::AppKey;
 use crate::vault::pouch::provider::ProviderId;
 use axum::Json;
@@ -79,6 +85,9 @@ pub struct FeatureProviders {
     #[serde_as(as = "Option<DisplayFromStr>")]
     pub default: Option<ProviderId>,
     pub providers: HashMap<String, Provider>,
+    /// JSON Schema of the provided config, only available for typed features
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, ToSchema)]
@@ -101,6 +110,35 @@ pub struct PutProviderReferenceRequest {
     pub provider: ProviderReference,
 }
 
+/// Either a single provider or a list of providers, the first available provider of the list is
+/// used
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum PutDependencyRequest {
+    Provider(ProviderReference),
+    Providers(Vec<ProviderReference>),
+}
+
+impl PutDependencyRequest {
+    /// Splits the request into the provider and its fallback providers, None if the list of
+    /// providers is empty
+    pub fn into_provider_and_fallbacks(
+        self,
+    ) -> Option<(ProviderReference, Vec<ProviderReference>)> {
+        match self {
+            Self::Provider(provider) => Some((provider, Vec::new())),
+            Self::Providers(mut providers) => {
+                if providers.is_empty() {
+                    None
+                } else {
+                    let provider = providers.remove(0);
+                    Some((provider, providers))
+                }
+            }
+        }
+    }
+}
+
 #[serde_as]
 #[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct AuthProvidersAndDefaults {
@@ -295,6 +333,101 @@ impl IntoResponse for DeleteDefaultProviderError {
     }
 }
 
+impl IntoResponse for PlanStateError {
+    fn into_response(self) -> Response {
+        match self {
+            e @ Self::DuplicateInstanceName(_)
+            | e @ Self::DuplicateNetworkName(_)
+            | e @ Self::UnknownProvider { .. } => {
+                AdditionalInfo::new(e.to_string()).into_bad_request()
+            }
+            e @ Self::AmbiguousInstanceName(_)
+            | e @ Self::AppMismatch { .. }
+            | e @ Self::NoNetworkDeployment => AdditionalInfo::new(e.to_string()).into_conflict(),
+            e @ Self::Other(_) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+        }
+    }
+}
+
+impl IntoResponse for ExecInstanceError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND.into_response(),
+            e @ Self::Unsupported(_) => AdditionalInfo::new(e.to_string()).into_bad_request(),
+            e @ Self::NotRunning(_) => AdditionalInfo::new(e.to_string()).into_conflict(),
+            e @ Self::Timeout { .. } => (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(AdditionalInfo::new(e.to_string())),
+            )
+                .into_response(),
+            e @ Self::Other(_) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+        }
+    }
+}
+
+impl IntoResponse for InstanceFilesError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::NotFound(_) | Self::UnknownConfigFile { .. } | Self::UnknownVolume { .. } => {
+                StatusCode::NOT_FOUND.into_response()
+            }
+            e @ Self::Unsupported(_) | e @ Self::InvalidPath(_) => {
+                AdditionalInfo::new(e.to_string()).into_bad_request()
+            }
+            e @ Self::NotRunning(_) => AdditionalInfo::new(e.to_string()).into_conflict(),
+            e @ Self::Other(_) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+        }
+    }
+}
+
+impl IntoResponse for InstanceEditorHostError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::InstanceNotFound(_) | Self::EditorNotFound(..) => {
+                StatusCode::NOT_FOUND.into_response()
+            }
+            e @ Self::NotSupported(_)
+            | e @ Self::InvalidHostName(_)
+            | e @ Self::Certificate(
+                CertificateError::InvalidCertificate(_)
+                | CertificateError::InvalidPrivateKey(_)
+                | CertificateError::KeyMismatch
+                | CertificateError::InvalidHostName(_),
+            ) => AdditionalInfo::new(e.to_string()).into_bad_request(),
+            e @ Self::HostNameInUse { .. } => AdditionalInfo::new(e.to_string()).into_conflict(),
+            e @ Self::Certificate(_) | e @ Self::Other(_) => {
+                AdditionalInfo::new(e.to_string()).into_internal_server_error()
+            }
+        }
+    }
+}
+
+impl IntoResponse for InstanceEditorRolesError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::InstanceNotFound(_) | Self::EditorNotFound(..) => {
+                StatusCode::NOT_FOUND.into_response()
+            }
+            e @ Self::NotSupported(_) | e @ Self::InvalidRole(_) => {
+                AdditionalInfo::new(e.to_string()).into_bad_request()
+            }
+            e @ Self::Other(_) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+        }
+    }
+}
+
+impl IntoResponse for InstanceEditorPathPrefixError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::InstanceNotFound(_) | Self::EditorNotFound(..) => {
+                StatusCode::NOT_FOUND.into_response()
+            }
+            e @ Self::NotSupported(_) => AdditionalInfo::new(e.to_string()).into_bad_request(),
+            e @ Self::Other(_) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+        }
+    }
+}
+
 #[cfg(feature = "auth")]
 pub mod auth {
     use serde::{Deserialize, Serialize};
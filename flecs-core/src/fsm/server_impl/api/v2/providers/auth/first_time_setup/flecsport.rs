@@ -2,7 +2,7 @@ use crate::fsm::server_impl::api::v2::models::{Accepted, AdditionalInfo};
 use crate::fsm::server_impl::state::{
     FloxyState, ImportiusState, LoreState, QuestMasterState, UsbDeviceReaderState, VaultState,
 };
-use crate::sorcerer::importius::{ImportPathInfo, Importius};
+use crate::sorcerer::importius::{ImportOptions, ImportPathInfo, Importius};
 use axum::extract::State;
 use axum::response::{IntoResponse, Response};
 use futures_util::TryFutureExt;
@@ -30,6 +30,8 @@ pub async fn post<I: Importius>(
         archive_path: lore.auth.initial_auth_provider_flecsport_path.clone(),
         temp_path: lore.import.base_path.clone(),
         base_path: lore.base_path.clone(),
+        decryption: Default::default(),
+        base_archives: Vec::new(),
     };
     match quest_master
         .lock()
@@ -41,7 +43,16 @@ pub async fn post<I: Importius>(
             ),
             move |quest| async move {
                 importius
-                    .import_archive(quest, vault, floxy, lore, usb_device_reader, path_info)
+                    .import_archive(
+                        quest,
+                        vault,
+                        floxy,
+                        lore,
+                        usb_device_reader,
+                        path_info,
+                        ImportOptions::default(),
+                    )
+                    .map_ok(|_| ())
                     .map_err(|e| anyhow::anyhow!(e))
                     .await
             },
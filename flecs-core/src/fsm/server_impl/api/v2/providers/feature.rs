@@ -1,6 +1,7 @@
 use crate::fsm::server_impl::api::v2::models::{AdditionalInfo, FeatureProviders};
 use crate::fsm::server_impl::state::{ProvidiusState, VaultState};
 use crate::jeweler::gem::manifest::FeatureKey;
+use crate::jeweler::gem::manifest::providers::features::{FeatureSchema, feature_schema};
 use crate::sorcerer::providius::{Provider, ProvidersAndDefaults};
 use axum::Json;
 use axum::extract::{Path, State};
@@ -21,7 +22,7 @@ pub struct GetPathParams {
     get,
     path = "/providers/{feature}",
     tag = "Experimental",
-    description = "Get providers for the specified feature",
+    description = "Get providers for the specified feature and the schema of the feature if it is typed",
     params(GetPathParams),
     responses(
         (status = OK, description = "Default provider was found", body = FeatureProviders),
@@ -53,6 +54,7 @@ pub async fn get(
                 )
             })
             .collect(),
+        schema: feature_schema(&feature).map(FeatureSchema::json_schema),
     };
 
     (StatusCode::OK, Json(provider)).into_response()
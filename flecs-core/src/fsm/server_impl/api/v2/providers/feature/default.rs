@@ -1,5 +1,5 @@
 use crate::fsm::server_impl::api::v2::models::{AdditionalInfo, PutDefaultProviderRequest};
-use crate::fsm::server_impl::state::{ProvidiusState, VaultState};
+use crate::fsm::server_impl::state::{FloxyState, ProvidiusState, QuestMasterState, VaultState};
 use crate::jeweler::gem::manifest::FeatureKey;
 use crate::sorcerer::providius::{
     DeleteDefaultProviderError, GetProviderError, Provider, SetDefaultProviderError,
@@ -9,6 +9,7 @@ use axum::extract::{Path, State};
 use axum::response::{IntoResponse, Response};
 use http::StatusCode;
 use serde::Deserialize;
+use tracing::warn;
 use utoipa::IntoParams;
 
 #[derive(Debug, Deserialize, IntoParams)]
@@ -87,13 +88,31 @@ pub async fn get(
 pub async fn put(
     State(VaultState(vault)): State<VaultState>,
     State(ProvidiusState(providius)): State<ProvidiusState>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    State(QuestMasterState(quest_master)): State<QuestMasterState>,
     Path(PutPathParams { feature }): Path<PutPathParams>,
     Json(PutDefaultProviderRequest { provider_id }): Json<PutDefaultProviderRequest>,
 ) -> Result<Response, SetDefaultProviderError> {
-    match providius
-        .set_default_provider(vault, feature, provider_id)
-        .await?
-    {
+    let previous = providius
+        .set_default_provider(vault.clone(), feature.clone(), provider_id)
+        .await?;
+    // Running instances using the default provider have to be restarted with the new provider
+    if previous.is_some_and(|previous| previous != provider_id) {
+        let result = quest_master
+            .lock()
+            .await
+            .schedule_quest(
+                format!("Update instances depending on the default provider for {feature}"),
+                move |quest| async move { providius.update_dependents(quest, vault, floxy).await },
+            )
+            .await;
+        if let Err(e) = result {
+            warn!(
+                "Could not update instances depending on the default provider for {feature}: {e}"
+            );
+        }
+    }
+    match previous {
         Some(_) => Ok(StatusCode::OK.into_response()),
         None => Ok(StatusCode::CREATED.into_response()),
     }
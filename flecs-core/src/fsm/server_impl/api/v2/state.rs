@@ -0,0 +1,117 @@
+pub mod apply;
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{StatiusState, VaultState};
+use crate::sorcerer::statius::DeviceState;
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use http::header::{ACCEPT, CONTENT_TYPE};
+use http::{HeaderMap, HeaderName, StatusCode};
+
+const YAML_CONTENT_TYPE: &str = "application/yaml";
+
+fn is_yaml(headers: &HeaderMap, header: HeaderName) -> bool {
+    headers
+        .get(header)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("yaml"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/state",
+    tag = "Experimental",
+    description = "Export the apps, networks, instances and default providers of the device in the format accepted by /state/apply. The state is returned as yaml if requested via the Accept header, as json otherwise.",
+    responses(
+        (status = OK, description = "Current state of the device", body = DeviceState, content_type = ["application/json", "application/yaml"]),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    ),
+)]
+pub async fn get(
+    State(VaultState(vault)): State<VaultState>,
+    State(StatiusState(statius)): State<StatiusState>,
+    headers: HeaderMap,
+) -> Response {
+    let state = match statius.export_state(vault).await {
+        Ok(state) => state,
+        Err(e) => return AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    };
+    if !is_yaml(&headers, ACCEPT) {
+        return (StatusCode::OK, Json(state)).into_response();
+    }
+    match serde_norway::to_string(&state) {
+        Ok(state) => (StatusCode::OK, [(CONTENT_TYPE, YAML_CONTENT_TYPE)], state).into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::statius::MockStatius;
+    use crate::vault::pouch::AppKey;
+    use crate::vault::tests::create_empty_test_vault;
+    use http::HeaderValue;
+    use std::sync::Arc;
+
+    fn statius() -> MockStatius {
+        let mut statius = MockStatius::new();
+        statius.expect_export_state().once().returning(|_| {
+            Ok(DeviceState {
+                apps: vec![AppKey {
+                    name: "tech.flecs.test".to_string(),
+                    version: "1.0.0".to_string(),
+                }],
+                ..DeviceState::default()
+            })
+        });
+        statius
+    }
+
+    #[tokio::test]
+    async fn get_json() {
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(StatiusState(Arc::new(statius()))),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE),
+            Some(&HeaderValue::from_static("application/json"))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_yaml() {
+        let headers = HeaderMap::from_iter([(ACCEPT, HeaderValue::from_static(YAML_CONTENT_TYPE))]);
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(StatiusState(Arc::new(statius()))),
+            headers,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE),
+            Some(&HeaderValue::from_static(YAML_CONTENT_TYPE))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_500() {
+        let mut statius = MockStatius::new();
+        statius
+            .expect_export_state()
+            .once()
+            .returning(|_| Err(anyhow::anyhow!("TestError")));
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(StatiusState(Arc::new(statius))),
+            HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
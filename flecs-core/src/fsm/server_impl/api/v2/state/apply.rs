@@ -0,0 +1,382 @@
+use super::is_yaml;
+use crate::fsm::console_client::ConsoleClient;
+use crate::fsm::server_impl::api::v2::models::{Accepted, AdditionalInfo};
+use crate::fsm::server_impl::state::{
+    AppRaiserState, ConsoleClientState, DeploymentoState, FloxyState, InstanciusState, LoreState,
+    ProvidiusState, QuestMasterState, StatiusState, VaultState,
+};
+use crate::jeweler::gem::instance::InstanceId;
+use crate::lore::Lore;
+use crate::quest::SyncQuest;
+use crate::relic::floxy::Floxy;
+use crate::sorcerer::appraiser::AppRaiser;
+use crate::sorcerer::deploymento::Deploymento;
+use crate::sorcerer::instancius::Instancius;
+use crate::sorcerer::providius::Providius;
+use crate::sorcerer::statius::{DeviceState, StateAction};
+use crate::vault::Vault;
+use axum::Json;
+use axum::body::Bytes;
+use axum::extract::{Query, State};
+use axum::response::{IntoResponse, Response};
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, StatusCode};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct PostQueryParams {
+    /// Only compute and return the plan without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Remove instances and apps that are not part of the desired state
+    #[serde(default)]
+    pub prune: bool,
+}
+
+struct ApplyContext<APP: AppRaiser, I: Instancius, D: Deploymento> {
+    vault: Arc<Vault>,
+    lore: Arc<Lore>,
+    floxy: Arc<dyn Floxy>,
+    console_client: ConsoleClient,
+    app_raiser: Arc<APP>,
+    instancius: Arc<I>,
+    deploymento: Arc<D>,
+    providius: Arc<dyn Providius>,
+}
+
+impl<APP: AppRaiser, I: Instancius, D: Deploymento> ApplyContext<APP, I, D> {
+    /// Returns the name and id of the instance if the action created one
+    async fn execute(
+        self: Arc<Self>,
+        quest: SyncQuest,
+        action: StateAction,
+        id: Option<InstanceId>,
+    ) -> anyhow::Result<Option<(String, InstanceId)>> {
+        let vault = self.vault.clone();
+        let floxy = self.floxy.clone();
+        let id = || id.ok_or_else(|| anyhow::anyhow!("Instance of action '{action}' not found"));
+        match &action {
+            StateAction::InstallApp { app_key } => {
+                self.app_raiser
+                    .install_app(quest, vault, app_key.clone(), self.console_client.clone())
+                    .await?
+            }
+            StateAction::CreateNetwork {
+                deployment_id,
+                network,
+            } => {
+                self.deploymento
+                    .create_network(vault, deployment_id.clone(), network.clone().into())
+                    .await?;
+            }
+            StateAction::StopInstance { .. } => {
+                self.instancius
+                    .stop_instance(quest, vault, floxy, id()?)
+                    .await?
+            }
+            StateAction::UpdateInstance { app_key, .. } => {
+                self.instancius
+                    .update_instance(
+                        quest,
+                        vault,
+                        floxy,
+                        id()?,
+                        app_key.version.clone(),
+                        self.lore.instance.base_path.clone(),
+                    )
+                    .await?
+            }
+            StateAction::DeleteInstance { .. } => {
+                self.instancius
+                    .delete_instance(quest, vault, floxy, id()?)
+                    .await?
+            }
+            StateAction::UninstallApp { app_key } => {
+                self.app_raiser
+                    .uninstall_app(quest, vault, floxy, app_key.clone())
+                    .await?
+            }
+            StateAction::CreateInstance { name, app_key } => {
+                let id = self
+                    .instancius
+                    .create_instance(
+                        quest,
+                        vault,
+                        self.lore.clone(),
+                        app_key.clone(),
+                        name.clone(),
+                        None,
+                    )
+                    .await?;
+                return Ok(Some((name.clone(), id)));
+            }
+            StateAction::ConfigureInstance { config, .. } => {
+                self.instancius
+                    .put_instance_config(vault, id()?, config.clone())
+                    .await?
+            }
+            StateAction::SetDefaultProvider { feature, .. } => {
+                self.providius
+                    .set_default_provider(vault, feature.clone(), id()?)
+                    .await?;
+            }
+            StateAction::StartInstance { .. } => {
+                self.instancius
+                    .start_instance(quest, vault, floxy, id()?)
+                    .await?
+            }
+        }
+        Ok(None)
+    }
+}
+
+fn instance_of_action(
+    action: &StateAction,
+    created: &HashMap<String, InstanceId>,
+) -> Option<InstanceId> {
+    let (name, id) = match action {
+        StateAction::StopInstance { name, id }
+        | StateAction::ConfigureInstance { name, id, .. }
+        | StateAction::SetDefaultProvider { name, id, .. }
+        | StateAction::StartInstance { name, id } => (name, *id),
+        StateAction::UpdateInstance { name, id, .. } | StateAction::DeleteInstance { name, id } => {
+            (name, Some(*id))
+        }
+        StateAction::InstallApp { .. }
+        | StateAction::CreateNetwork { .. }
+        | StateAction::UninstallApp { .. }
+        | StateAction::CreateInstance { .. } => return None,
+    };
+    id.or_else(|| created.get(name).copied())
+}
+
+/// Executes the actions one after another as sub quests, the first failing action aborts the
+/// remaining plan
+async fn apply_plan<APP: AppRaiser + 'static, I: Instancius + 'static, D: Deploymento + 'static>(
+    quest: SyncQuest,
+    context: Arc<ApplyContext<APP, I, D>>,
+    plan: Vec<StateAction>,
+) -> anyhow::Result<()> {
+    let mut created = HashMap::new();
+    for action in plan {
+        let id = instance_of_action(&action, &created);
+        let context = context.clone();
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest(action.to_string(), |quest| {
+                context.execute(quest, action, id)
+            })
+            .await
+            .2;
+        if let Some((name, id)) = result.await? {
+            created.insert(name, id);
+        }
+    }
+    Ok(())
+}
+
+fn parse_state(headers: &HeaderMap, body: &[u8]) -> Result<DeviceState, String> {
+    if is_yaml(headers, CONTENT_TYPE) {
+        serde_norway::from_slice(body).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_slice(body).map_err(|e| e.to_string())
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/state/apply",
+    tag = "Experimental",
+    description = "Bring the device into the given state. The difference to the current state is computed and executed as one job which installs apps, creates networks and instances, replaces instance configs and sets default providers. Instances are identified by their name. The state is accepted as json or yaml depending on the Content-Type header.",
+    params(PostQueryParams),
+    request_body(content = DeviceState, content_type = ["application/json", "application/yaml"]),
+    responses(
+        (status = OK, description = "Plan to reach the desired state, nothing was changed (dry run)", body = Vec<StateAction>),
+        (status = ACCEPTED, description = "Applying of state triggered", body = Accepted),
+        (status = BAD_REQUEST, description = "Invalid state", body = AdditionalInfo),
+        (status = CONFLICT, description = "The state can not be applied to the current state of the device", body = AdditionalInfo),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn post<APP: AppRaiser + 'static, I: Instancius + 'static, D: Deploymento + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(LoreState(lore)): State<LoreState>,
+    State(FloxyState(floxy)): State<FloxyState>,
+    State(QuestMasterState(quest_master)): State<QuestMasterState>,
+    State(ConsoleClientState(console_client)): State<ConsoleClientState>,
+    State(AppRaiserState(app_raiser)): State<AppRaiserState<APP>>,
+    State(InstanciusState(instancius)): State<InstanciusState<I>>,
+    State(DeploymentoState(deploymento)): State<DeploymentoState<D>>,
+    State(ProvidiusState(providius)): State<ProvidiusState>,
+    State(StatiusState(statius)): State<StatiusState>,
+    Query(PostQueryParams { dry_run, prune }): Query<PostQueryParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let desired = match parse_state(&headers, &body) {
+        Ok(desired) => desired,
+        Err(e) => return AdditionalInfo::new(format!("Invalid state: {e}")).into_bad_request(),
+    };
+    let plan = match statius.plan_state(vault.clone(), &desired, prune).await {
+        Ok(plan) => plan,
+        Err(e) => return e.into_response(),
+    };
+    if dry_run {
+        return (StatusCode::OK, Json(plan)).into_response();
+    }
+    let context = Arc::new(ApplyContext {
+        vault,
+        lore,
+        floxy,
+        console_client,
+        app_raiser,
+        instancius,
+        deploymento,
+        providius,
+    });
+    match quest_master
+        .lock()
+        .await
+        .schedule_quest("Apply device state".to_string(), move |quest| {
+            apply_plan(quest, context, plan)
+        })
+        .await
+    {
+        Ok((id, _)) => Accepted::new(id).into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enchantment::quest_master::QuestMaster;
+    use crate::fsm::console_client::create_default;
+    use crate::lore;
+    use crate::relic::floxy::MockFloxy;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::appraiser::MockAppRaiser;
+    use crate::sorcerer::deploymento::MockDeploymento;
+    use crate::sorcerer::instancius::MockInstancius;
+    use crate::sorcerer::providius::MockProvidius;
+    use crate::sorcerer::statius::{MockStatius, PlanStateError};
+    use crate::vault::pouch::AppKey;
+    use crate::vault::tests::create_empty_test_vault;
+    use http::HeaderValue;
+    use testdir::testdir;
+
+    fn app_key() -> AppKey {
+        AppKey {
+            name: "tech.flecs.test".to_string(),
+            version: "1.0.0".to_string(),
+        }
+    }
+
+    async fn post_with(
+        statius: MockStatius,
+        dry_run: bool,
+        headers: HeaderMap,
+        body: &'static str,
+    ) -> Response {
+        let vault = create_empty_test_vault();
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        post::<MockAppRaiser, MockInstancius, MockDeploymento>(
+            State(VaultState(vault.clone())),
+            State(LoreState(lore.clone())),
+            State(FloxyState(Arc::new(MockFloxy::new()))),
+            State(QuestMasterState(QuestMaster::default())),
+            State(ConsoleClientState(create_default(vault, lore))),
+            State(AppRaiserState(Arc::new(MockAppRaiser::new()))),
+            State(InstanciusState(Arc::new(MockInstancius::new()))),
+            State(DeploymentoState(Arc::new(MockDeploymento::new()))),
+            State(ProvidiusState(Arc::new(MockProvidius::new()))),
+            State(StatiusState(Arc::new(statius))),
+            Query(PostQueryParams {
+                dry_run,
+                prune: false,
+            }),
+            headers,
+            Bytes::from_static(body.as_bytes()),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn post_dry_run_yaml() {
+        let mut statius = MockStatius::new();
+        statius
+            .expect_plan_state()
+            .once()
+            .withf(|_, desired, prune| desired.apps == vec![app_key()] && !prune)
+            .returning(|_, _, _| Ok(vec![StateAction::InstallApp { app_key: app_key() }]));
+        let headers =
+            HeaderMap::from_iter([(CONTENT_TYPE, HeaderValue::from_static("application/yaml"))]);
+        let response = post_with(
+            statius,
+            true,
+            headers,
+            "apps:\n  - name: tech.flecs.test\n    version: 1.0.0\n",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn post_400() {
+        let response = post_with(MockStatius::new(), true, HeaderMap::new(), "{\"apps\": 5}").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn post_409() {
+        let mut statius = MockStatius::new();
+        statius
+            .expect_plan_state()
+            .once()
+            .returning(|_, _, _| Err(PlanStateError::AmbiguousInstanceName("test".to_string())));
+        let response = post_with(statius, false, HeaderMap::new(), "{}").await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn instance_of_action_created() {
+        let created = HashMap::from([("test".to_string(), InstanceId::new(10))]);
+        assert_eq!(
+            instance_of_action(
+                &StateAction::StartInstance {
+                    name: "test".to_string(),
+                    id: None
+                },
+                &created
+            ),
+            Some(InstanceId::new(10))
+        );
+        assert_eq!(
+            instance_of_action(
+                &StateAction::StartInstance {
+                    name: "test".to_string(),
+                    id: Some(InstanceId::new(2))
+                },
+                &created
+            ),
+            Some(InstanceId::new(2))
+        );
+        assert_eq!(
+            instance_of_action(
+                &StateAction::CreateInstance {
+                    name: "test".to_string(),
+                    app_key: app_key(),
+                },
+                &created
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{LoreState, SystemusState};
+use crate::sorcerer::systemus::Systemus;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+use http::header::CONTENT_TYPE;
+
+const PEM_CONTENT_TYPE: &str = "application/x-pem-file";
+
+#[utoipa::path(
+    get,
+    path = "/system/ca",
+    tag = "System",
+    description = "Certificate of the device certificate authority which issues the server certificate of the device. Clients can add it to their trusted certificates to connect to the device via https.",
+    responses(
+        (status = OK, description = "PEM encoded certificate of the device certificate authority", body = String, content_type = "application/x-pem-file"),
+        (status = NOT_FOUND, description = "Device certificate authority was not created yet"),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<SYS: Systemus>(
+    State(LoreState(lore)): State<LoreState>,
+    State(SystemusState(systemus)): State<SystemusState<SYS>>,
+) -> Response {
+    match systemus.device_ca_certificate(&lore.certificate) {
+        Ok(Some(certificate)) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, PEM_CONTENT_TYPE)],
+            certificate,
+        )
+            .into_response(),
+        Ok(None) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::systemus::MockSystemus;
+    use http::HeaderValue;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    async fn get_with(systemus: MockSystemus) -> Response {
+        get(
+            State(LoreState(Arc::new(lore::test_lore(
+                testdir!(),
+                &MockVarReader::new(),
+            )))),
+            State(SystemusState(Arc::new(systemus))),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_device_ca_certificate()
+            .once()
+            .returning(|_| Ok(Some("certificate".to_string())));
+        let response = get_with(systemus).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE),
+            Some(&HeaderValue::from_static(PEM_CONTENT_TYPE))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_404() {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_device_ca_certificate()
+            .once()
+            .returning(|_| Ok(None));
+        assert_eq!(get_with(systemus).await.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn get_500() {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_device_ca_certificate()
+            .once()
+            .returning(|_| Err(anyhow::anyhow!("TestError")));
+        assert_eq!(
+            get_with(systemus).await.status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -1,10 +1,30 @@
+use crate::relic::system::serial::SerialNumberReader;
 use flecsd_axum_server::apis::system::SystemInfoGetResponse as GetResponse;
 use tracing::error;
 
-pub fn get() -> Result<GetResponse, ()> {
-    Ok(GetResponse::Status200_Sucess(
-        crate::relic::system::info::try_create_system_info().map_err(|e| {
-            error!("Could not create SystemInfo: {e}");
-        })?,
-    ))
+pub fn get(serial_number_reader: &dyn SerialNumberReader) -> Result<GetResponse, ()> {
+    let mut info = crate::relic::system::info::try_create_system_info().map_err(|e| {
+        error!("Could not create SystemInfo: {e}");
+    })?;
+    info.serial_number = serial_number_reader.read_serial_number();
+    Ok(GetResponse::Status200_Sucess(info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relic::system::serial::MockSerialNumberReader;
+
+    #[test]
+    fn get_200_serial_number() {
+        let mut reader = MockSerialNumberReader::new();
+        reader
+            .expect_read_serial_number()
+            .once()
+            .return_const(Some("SN-1234".to_string()));
+        let Ok(GetResponse::Status200_Sucess(info)) = get(&reader) else {
+            panic!("Expected system info");
+        };
+        assert_eq!(info.serial_number.as_deref(), Some("SN-1234"));
+    }
 }
@@ -1,6 +1,8 @@
+pub mod ca;
 pub mod devices;
 pub mod info;
 pub mod network_adapters;
 pub mod ping;
 pub mod sbom;
+pub mod storage;
 pub mod version;
@@ -0,0 +1,79 @@
+pub mod prune;
+
+use crate::fsm::server_impl::api::v2::models::AdditionalInfo;
+use crate::fsm::server_impl::state::{LoreState, SystemusState, VaultState};
+use crate::sorcerer::systemus::{StorageUsage, Systemus};
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use http::StatusCode;
+
+#[utoipa::path(
+    get,
+    path = "/system/storage",
+    tag = "Experimental",
+    description = "Disk space used by app images, instance volumes, instance backups and exports. Images and volumes not belonging to FLECS are listed as well, as they are candidates for pruning.",
+    responses(
+        (status = OK, description = "Storage usage", body = StorageUsage),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn get<SYS: Systemus>(
+    State(VaultState(vault)): State<VaultState>,
+    State(LoreState(lore)): State<LoreState>,
+    State(SystemusState(systemus)): State<SystemusState<SYS>>,
+) -> Response {
+    match systemus.storage_usage(vault, lore).await {
+        Ok(usage) => (StatusCode::OK, Json(usage)).into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::systemus::MockSystemus;
+    use crate::vault::tests::create_empty_test_vault;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    #[tokio::test]
+    async fn get_200() {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_storage_usage()
+            .once()
+            .returning(|_, _| Ok(StorageUsage::default()));
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(LoreState(Arc::new(lore::test_lore(
+                testdir!(),
+                &MockVarReader::new(),
+            )))),
+            State(SystemusState(Arc::new(systemus))),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn get_500() {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_storage_usage()
+            .once()
+            .returning(|_, _| Err(anyhow::anyhow!("TestError")));
+        let response = get(
+            State(VaultState(create_empty_test_vault())),
+            State(LoreState(Arc::new(lore::test_lore(
+                testdir!(),
+                &MockVarReader::new(),
+            )))),
+            State(SystemusState(Arc::new(systemus))),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::fsm::server_impl::api::v2::models::{Accepted, AdditionalInfo};
+use crate::fsm::server_impl::state::{LoreState, QuestMasterState, SystemusState, VaultState};
+use crate::quest::QuestResult;
+use crate::sorcerer::systemus::{PruneOptions, Systemus};
+use axum::Json;
+use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+
+#[utoipa::path(
+    post,
+    path = "/system/storage/prune",
+    tag = "Experimental",
+    description = "Remove images which are neither referenced by an app or instance nor used by a container, volumes of deleted instances and old exports and instance backups. Only what is selected in the request is removed. The result of the job lists the removed images, volumes, exports and backups.",
+    request_body(content = PruneOptions),
+    responses(
+        (status = ACCEPTED, description = "Pruning of storage triggered", body = Accepted),
+        (status = INTERNAL_SERVER_ERROR, description = "Internal server error", body = AdditionalInfo),
+    )
+)]
+pub async fn post<SYS: Systemus + 'static>(
+    State(VaultState(vault)): State<VaultState>,
+    State(LoreState(lore)): State<LoreState>,
+    State(SystemusState(systemus)): State<SystemusState<SYS>>,
+    State(QuestMasterState(quest_master)): State<QuestMasterState>,
+    Json(options): Json<PruneOptions>,
+) -> Response {
+    match quest_master
+        .lock()
+        .await
+        .schedule_quest_with_result("Prune storage".to_string(), move |quest| async move {
+            let report = systemus.prune_storage(quest, vault, lore, options).await?;
+            Ok(QuestResult::PruneReport(report))
+        })
+        .await
+    {
+        Ok((id, _)) => Accepted::new(id).into_response(),
+        Err(e) => AdditionalInfo::new(e.to_string()).into_internal_server_error(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enchantment::quest_master::QuestMaster;
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::systemus::{MockSystemus, PruneReport};
+    use crate::vault::tests::create_empty_test_vault;
+    use http::StatusCode;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    #[tokio::test]
+    async fn post_202() {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_prune_storage()
+            .withf(|_, _, _, options| options.images && options.keep_exports == Some(2))
+            .returning(|_, _, _, _| Ok(PruneReport::default()));
+        let response = post(
+            State(VaultState(create_empty_test_vault())),
+            State(LoreState(Arc::new(lore::test_lore(
+                testdir!(),
+                &MockVarReader::new(),
+            )))),
+            State(SystemusState(Arc::new(systemus))),
+            State(QuestMasterState(QuestMaster::default())),
+            Json(PruneOptions {
+                images: true,
+                keep_exports: Some(2),
+                ..PruneOptions::default()
+            }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+}
@@ -1,5 +1,6 @@
 use crate::fsm::server_impl::ServerImpl;
 use crate::relic::device::usb::UsbDeviceReader;
+use crate::relic::system::serial::SerialNumberReaderImpl;
 use crate::sorcerer::appraiser::AppRaiser;
 use crate::sorcerer::authmancer::Authmancer;
 use crate::sorcerer::deploymento::Deploymento;
@@ -48,6 +49,7 @@ impl<
         Ok(super::api::v2::device::license::activation::post(
             self.vault.clone(),
             self.sorcerers.licenso.clone(),
+            &SerialNumberReaderImpl::from(&self.lore.system),
             self.console_client.clone(),
         )
         .await)
@@ -62,7 +64,9 @@ impl<
         Ok(super::api::v2::device::license::activation::status::get(
             self.vault.clone(),
             self.sorcerers.licenso.clone(),
+            self.enchantments.quest_master.clone(),
             self.console_client.clone(),
+            self.lore.console.license_grace_period,
         )
         .await)
     }
@@ -1,3 +1,4 @@
+use crate::fsm::console_client::ConsoleClient;
 use crate::fsm::server_impl::ServerImpl;
 use crate::relic::device::usb::UsbDeviceReaderImpl;
 use crate::relic::floxy::Floxy;
@@ -11,6 +12,7 @@ use crate::sorcerer::licenso::Licenso;
 use crate::sorcerer::mage_quester::MageQuester;
 use crate::sorcerer::manifesto::Manifesto;
 use crate::sorcerer::providius::Providius;
+use crate::sorcerer::statius::Statius;
 use crate::sorcerer::systemus::Systemus;
 use crate::vault::Vault;
 use axum::extract::FromRef;
@@ -132,10 +134,350 @@ impl<
     }
 }
 
-#[cfg(feature = "auth")]
+pub struct StatiusState(pub Arc<dyn Statius>);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for StatiusState
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.sorcerers.statius.clone())
+    }
+}
+
+pub struct AppRaiserState<APP: AppRaiser + 'static>(pub Arc<APP>);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for AppRaiserState<APP>
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.sorcerers.app_raiser.clone())
+    }
+}
+
+pub struct DeploymentoState<D: Deploymento + 'static>(pub Arc<D>);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for DeploymentoState<D>
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.sorcerers.deploymento.clone())
+    }
+}
+
+pub struct ConsoleClientState(pub ConsoleClient);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for ConsoleClientState
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.console_client.clone())
+    }
+}
+
+pub struct InstanciusState<I: Instancius + 'static>(pub Arc<I>);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for InstanciusState<I>
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.sorcerers.instancius.clone())
+    }
+}
+
+pub struct ExportiusState<E: Exportius + 'static>(pub Arc<E>);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for ExportiusState<E>
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.sorcerers.exportius.clone())
+    }
+}
+
 pub struct LoreState(pub Arc<crate::lore::Lore>);
 
-#[cfg(feature = "auth")]
 impl<
     APP: AppRaiser + 'static,
     AUTH: Authmancer + 'static,
@@ -191,10 +533,8 @@ impl<
     }
 }
 
-#[cfg(feature = "auth")]
 pub struct ImportiusState<I: Importius + 'static>(pub Arc<I>);
 
-#[cfg(feature = "auth")]
 impl<
     APP: AppRaiser + 'static,
     AUTH: Authmancer + 'static,
@@ -250,10 +590,122 @@ impl<
     }
 }
 
-#[cfg(feature = "auth")]
+pub struct LicensoState<L: Licenso + 'static>(pub Arc<L>);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for LicensoState<L>
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.sorcerers.licenso.clone())
+    }
+}
+
+pub struct SystemusState<SYS: Systemus + 'static>(pub Arc<SYS>);
+
+impl<
+    APP: AppRaiser + 'static,
+    AUTH: Authmancer + 'static,
+    I: Instancius + 'static,
+    L: Licenso + 'static,
+    Q: MageQuester + 'static,
+    M: Manifesto + 'static,
+    SYS: Systemus + 'static,
+    D: Deploymento + 'static,
+    E: Exportius + 'static,
+    IMP: Importius + 'static,
+>
+    FromRef<
+        Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    > for SystemusState<SYS>
+{
+    fn from_ref(
+        input: &Arc<
+            ServerImpl<
+                APP,
+                AUTH,
+                I,
+                L,
+                Q,
+                M,
+                SYS,
+                D,
+                E,
+                IMP,
+                UsbDeviceReaderImpl,
+                NetworkAdapterReaderImpl,
+                NetDeviceReaderImpl,
+            >,
+        >,
+    ) -> Self {
+        Self(input.sorcerers.systemus.clone())
+    }
+}
+
 pub struct FloxyState(pub Arc<dyn Floxy>);
 
-#[cfg(feature = "auth")]
 impl<
     APP: AppRaiser + 'static,
     AUTH: Authmancer + 'static,
@@ -368,10 +820,8 @@ impl<
     }
 }
 
-#[cfg(feature = "auth")]
 pub struct QuestMasterState(pub crate::enchantment::quest_master::QuestMaster);
 
-#[cfg(feature = "auth")]
 impl<
     APP: AppRaiser + 'static,
     AUTH: Authmancer + 'static,
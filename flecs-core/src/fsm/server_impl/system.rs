@@ -1,5 +1,6 @@
 use crate::fsm::server_impl::ServerImpl;
 use crate::relic::device::usb::UsbDeviceReader;
+use crate::relic::system::serial::SerialNumberReaderImpl;
 use crate::sorcerer::appraiser::AppRaiser;
 use crate::sorcerer::authmancer::Authmancer;
 use crate::sorcerer::deploymento::Deploymento;
@@ -84,7 +85,7 @@ impl<
         _host: Host,
         _cookies: CookieJar,
     ) -> Result<SystemInfoGetResponse, ()> {
-        super::api::v2::system::info::get()
+        super::api::v2::system::info::get(&SerialNumberReaderImpl::from(&self.lore.system))
     }
 
     async fn system_network_adapters_get(
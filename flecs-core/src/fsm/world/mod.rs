@@ -11,14 +11,18 @@ use crate::relic::{FlecsRelics, Relics};
 use crate::sorcerer::appraiser::{AppRaiser, AppraiserImpl};
 use crate::sorcerer::authmancer::{Authmancer, AuthmancerImpl};
 use crate::sorcerer::deploymento::{Deploymento, DeploymentoImpl};
+use crate::sorcerer::exportius::schedule::run_export_schedules;
 use crate::sorcerer::exportius::{Exportius, ExportiusImpl};
 use crate::sorcerer::importius::{Importius, ImportiusImpl};
 use crate::sorcerer::instancius::{Instancius, InstanciusImpl};
+use crate::sorcerer::licenso::revalidation::run_license_revalidation;
 use crate::sorcerer::licenso::{Licenso, LicensoImpl};
 use crate::sorcerer::mage_quester::{MageQuester, MageQuesterImpl};
 use crate::sorcerer::manifesto::{Manifesto, ManifestoImpl};
 #[cfg(feature = "auth")]
 use crate::sorcerer::providius::Providius;
+use crate::sorcerer::providius::failover::run_provider_health_checks;
+use crate::sorcerer::systemus::rotation::run_device_certificate_rotation;
 use crate::sorcerer::systemus::{Systemus, SystemusImpl};
 use crate::sorcerer::{FlecsSorcerers, Sorcerers};
 use crate::vault::Vault;
@@ -29,6 +33,7 @@ use net_spider::net_device::{NetDeviceReader, NetDeviceReaderImpl};
 use net_spider::network_adapter::{NetworkAdapterReader, NetworkAdapterReaderImpl};
 use std::path::Path;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
 use tracing::{error, info, warn};
 
 pub struct World<
@@ -54,6 +59,14 @@ pub struct World<
     #[cfg(feature = "auth")]
     pub wall: Wall,
     pub lore: Arc<Lore>,
+    /// Runs the export schedules, see [crate::sorcerer::exportius::schedule]
+    pub export_scheduler: JoinHandle<()>,
+    /// Periodically validates the license, see [crate::sorcerer::licenso::revalidation]
+    pub license_revalidator: JoinHandle<()>,
+    /// Periodically fails over dependencies, see [crate::sorcerer::providius::failover]
+    pub provider_health_checker: JoinHandle<()>,
+    /// Periodically renews the device certificates, see [crate::sorcerer::systemus::rotation]
+    pub certificate_rotator: JoinHandle<()>,
 }
 
 pub type FlecsWorld = World<
@@ -234,6 +247,10 @@ impl<
 > World<APP, AUTH, I, L, Q, M, SYS, D, E, IMP, UDR, NAR, NDR>
 {
     pub async fn halt(self) {
+        self.export_scheduler.abort();
+        self.license_revalidator.abort();
+        self.provider_health_checker.abort();
+        self.certificate_rotator.abort();
         self.server.shutdown().await;
         let instancius = self.sorcerers.instancius;
         let vault = self.vault;
@@ -345,6 +362,32 @@ impl<
         vault.open().await;
         #[cfg(feature = "auth")]
         let wall = Self::build_wall(lore.clone()).await?;
+        let export_scheduler = tokio::spawn(run_export_schedules(
+            enchantments.quest_master.clone(),
+            sorcerers.exportius.clone(),
+            vault.clone(),
+            relics.floxy.clone(),
+            lore.clone(),
+        ));
+        let license_revalidator = tokio::spawn(run_license_revalidation(
+            enchantments.quest_master.clone(),
+            sorcerers.licenso.clone(),
+            vault.clone(),
+            lore.clone(),
+        ));
+        let provider_health_checker = tokio::spawn(run_provider_health_checks(
+            enchantments.quest_master.clone(),
+            sorcerers.providius.clone(),
+            vault.clone(),
+            relics.floxy.clone(),
+            lore.clone(),
+        ));
+        let certificate_rotator = tokio::spawn(run_device_certificate_rotation(
+            sorcerers.systemus.clone(),
+            relics.network_adapter_reader.clone(),
+            relics.floxy.clone(),
+            lore.clone(),
+        ));
         let world = Self {
             server: crate::fsm::spawn_server(
                 sorcerers.clone(),
@@ -363,6 +406,10 @@ impl<
             #[cfg(feature = "auth")]
             wall,
             lore,
+            export_scheduler,
+            license_revalidator,
+            provider_health_checker,
+            certificate_rotator,
         };
         Ok(world)
     }
@@ -10,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 pub(crate) type AppId = String;
+/// Image archive as written by [AppDeployment::export_app]
+pub type ImageArchive = Box<dyn tokio::io::AsyncRead + Send + Unpin>;
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Token {
     pub username: String,
@@ -59,6 +61,15 @@ pub trait AppDeployment {
         manifest: AppManifest,
         path: PathBuf,
     ) -> Result<()>;
+
+    /// Loads a single image archive of an app from `image` instead of the files in the path
+    /// passed to [AppDeployment::import_app], e.g. directly from an import archive
+    async fn import_app_image(
+        &self,
+        quest: SyncQuest,
+        lore: ImportLoreRef,
+        image: ImageArchive,
+    ) -> Result<()>;
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
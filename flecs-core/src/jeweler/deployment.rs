@@ -1,6 +1,7 @@
 use crate::jeweler::GetDeploymentId;
 use crate::jeweler::app::AppDeployment;
 use crate::jeweler::network::NetworkDeployment;
+use crate::jeweler::storage::StorageDeployment;
 use crate::jeweler::volume::VolumeDeployment;
 use crate::lore::NetworkLoreRef;
 use async_trait::async_trait;
@@ -17,6 +18,7 @@ pub trait CommonDeployment:
     + AppDeployment
     + NetworkDeployment
     + VolumeDeployment
+    + StorageDeployment
     + GetDeploymentId
     + Debug
     + erased_serde::Serialize
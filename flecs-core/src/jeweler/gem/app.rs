@@ -305,6 +305,28 @@ impl App {
         }
     }
 
+    /// Checks that the app is installed on all of its deployments. Contrary to [App::install] a
+    /// single missing deployment is an error.
+    pub async fn verify_installation(&self, quest: SyncQuest) -> anyhow::Result<()> {
+        let mut missing = Vec::new();
+        for (deployment_id, data) in &self.deployments {
+            if !data
+                .deployment
+                .is_app_installed(quest.clone(), self.manifest.clone())
+                .await?
+            {
+                missing.push(deployment_id.clone());
+            }
+        }
+        anyhow::ensure!(
+            missing.is_empty(),
+            "App {} is not installed on deployment(s) {}",
+            self.key,
+            missing.join(", ")
+        );
+        Ok(())
+    }
+
     async fn uninstall_from_deployment(
         quest: SyncQuest,
         mut data: AppData,
@@ -561,6 +583,43 @@ pub mod tests {
         assert_eq!(app.status().await.unwrap(), AppStatus::NotInstalled);
     }
 
+    #[tokio::test]
+    async fn verify_installation_ok() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_is_app_installed()
+            .once()
+            .returning(|_, _| Ok(true));
+        deployment.expect_id().return_const("id".to_string());
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let app = App::new(test_key(), vec![deployment], min_app_1_0_0_manifest());
+        app.verify_installation(Quest::new_synced("TestQuest"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_installation_missing() {
+        let deployments = [("id#1", true), ("id#2", false)]
+            .into_iter()
+            .map(|(id, installed)| {
+                let mut deployment = MockedDockerDeployment::new();
+                deployment
+                    .expect_is_app_installed()
+                    .once()
+                    .returning(move |_, _| Ok(installed));
+                deployment.expect_id().return_const(id.to_string());
+                Deployment::Docker(Arc::new(deployment))
+            })
+            .collect();
+        let app = App::new(test_key(), deployments, min_app_1_0_0_manifest());
+        let error = app
+            .verify_installation(Quest::new_synced("TestQuest"))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().ends_with("id#2"), "{error}");
+    }
+
     #[tokio::test]
     async fn size_no_deployment() {
         let app = App::new(test_key(), vec![], min_app_1_0_0_manifest());
@@ -1,6 +1,6 @@
 use crate::forge::bollard::BollardNetworkExtension;
 use crate::jeweler::GetDeploymentId;
-use crate::jeweler::app::{AppDeployment, AppId, Token};
+use crate::jeweler::app::{AppDeployment, AppId, ImageArchive, Token};
 use crate::jeweler::deployment::CommonDeployment;
 use crate::jeweler::gem::deployment::compose::ComposeDeployment;
 use crate::jeweler::gem::deployment::docker::DockerDeploymentImpl;
@@ -11,6 +11,7 @@ use crate::jeweler::gem::manifest::multi::AppManifestMulti;
 use crate::jeweler::network::{
     CreateNetworkError, InspectNetworkError, Network, NetworkConfig, NetworkDeployment, NetworkId,
 };
+use crate::jeweler::storage::{DiskUsage, StorageDeployment};
 use crate::jeweler::volume::{Volume, VolumeDeployment, VolumeId};
 use crate::lore::{ExportLoreRef, ImportLoreRef, NetworkLoreRef};
 use crate::quest::SyncQuest;
@@ -429,6 +430,33 @@ impl AppDeployment for ComposeDeploymentImpl {
         }
         Ok(())
     }
+
+    async fn import_app_image(
+        &self,
+        quest: SyncQuest,
+        lore: ImportLoreRef,
+        image: ImageArchive,
+    ) -> anyhow::Result<()> {
+        relic::docker::image::load_from_reader(
+            quest,
+            self.docker_client_with_timeout(lore.as_ref().as_ref().timeout)?,
+            image,
+            ImportImageOptions::default(),
+            None,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl StorageDeployment for ComposeDeploymentImpl {
+    async fn disk_usage(&self) -> anyhow::Result<DiskUsage> {
+        DockerDeploymentImpl::disk_usage_with_client(self.docker_client()?).await
+    }
+
+    async fn remove_image(&self, image: &str) -> anyhow::Result<bool> {
+        DockerDeploymentImpl::remove_image_with_client(self.docker_client()?, image).await
+    }
 }
 
 #[async_trait]
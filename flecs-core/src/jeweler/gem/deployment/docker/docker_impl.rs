@@ -1,19 +1,21 @@
 use crate::forge::bollard::BollardNetworkExtension;
 use crate::jeweler::GetDeploymentId;
-use crate::jeweler::app::{AppDeployment, Token};
+use crate::jeweler::app::{AppDeployment, ImageArchive, Token};
 use crate::jeweler::deployment::CommonDeployment;
 use crate::jeweler::gem::deployment::docker::{AppInfo, DockerDeployment};
 use crate::jeweler::gem::instance::status::InstanceStatus;
-use crate::jeweler::gem::instance::{InstanceId, Logs};
+use crate::jeweler::gem::instance::{ExecOutput, ExecSession, InstanceId, Logs};
 use crate::jeweler::gem::manifest::AppManifest;
 use crate::jeweler::gem::manifest::single::{AppManifestSingle, ConfigFile};
 use crate::jeweler::network::{
     CreateNetworkError, InspectNetworkError, NetworkConfig, NetworkDeployment, NetworkId,
     NetworkKind,
 };
+use crate::jeweler::storage::{DiskUsage, ImageUsage, StorageDeployment, VolumeUsage};
 use crate::jeweler::volume::{Volume, VolumeDeployment, VolumeId};
 use crate::lore::{ExportLoreRef, ImportLoreRef, InstanceLoreRef, NetworkLoreRef};
 use crate::quest::{Quest, QuestId, State, SyncQuest};
+use crate::relic::async_flecstract::EntryHeader;
 use crate::vault::pouch::deployment::DeploymentId;
 use crate::{jeweler, relic};
 use async_trait::async_trait;
@@ -161,7 +163,7 @@ impl DockerDeploymentImpl {
             is_default: true,
         }
     }
-    fn network_config_fits_network(
+    pub(crate) fn network_config_fits_network(
         config: &NetworkConfig,
         network: &Network,
     ) -> anyhow::Result<bool> {
@@ -784,49 +786,12 @@ impl DockerDeploymentImpl {
             }
             _ => {}
         }
-        let driver = match &config.kind {
+        let driver = match config.kind {
             NetworkKind::Bridge | NetworkKind::MACVLAN | NetworkKind::Internal => {
                 config.kind.to_string()
             }
             NetworkKind::IpvlanL2 | NetworkKind::IpvlanL3 => {
-                let Some(parent_adapter) = &config.parent_adapter else {
-                    return Err(CreateNetworkError::NetworkConfigInvalid {
-                        location: "parent_adapter".to_string(),
-                        reason: "Can not create ipvlan network without parent".to_string(),
-                    });
-                };
-                match (config.cidr_subnet, config.gateway) {
-                    (None, _) | (_, None) => {
-                        let (parent_name, parent_adapter) = network_adapter_reader
-                            .try_read_network_adapters()?
-                            .remove_entry(parent_adapter)
-                            .ok_or_else(|| CreateNetworkError::NetworkConfigInvalid {
-                                location: "parent_adapter".to_string(),
-                                reason: format!(
-                                    "Parent network adapter {parent_adapter} does not exist"
-                                ),
-                            })?;
-                        if parent_adapter.ipv4_networks.is_empty() {
-                            return Err(CreateNetworkError::NetworkConfigInvalid {
-                                location: "parent_adapter".to_string(),
-                                reason: format!(
-                                    "Parent network adapter {parent_name} is not ready"
-                                ),
-                            });
-                        }
-                        config.cidr_subnet = Some(Ipv4Net::with_netmask(
-                            parent_adapter.ipv4_networks[0].addr(),
-                            parent_adapter.ipv4_networks[0].netmask(),
-                        ).map_err(|e| CreateNetworkError::NetworkConfigInvalid {
-                            location: "parent_adapter".to_string(),
-                            reason: format!(
-                                "Can not construct cidr network from parent network adapter {parent_name}: {e}"
-                            ),
-                        })?);
-                        config.gateway = parent_adapter.gateway;
-                    }
-                    _ => {}
-                }
+                config.fill_from_parent_adapter(network_adapter_reader)?;
                 "ipvlan".to_string()
             }
             x => {
@@ -876,6 +841,59 @@ impl DockerDeploymentImpl {
     ) -> anyhow::Result<Option<Volume>> {
         relic::docker::volume::inspect(docker_client, &volume_id).await
     }
+
+    pub async fn disk_usage_with_client(docker_client: Arc<Docker>) -> anyhow::Result<DiskUsage> {
+        let usage = relic::docker::system::disk_usage(docker_client).await?;
+        let images = usage
+            .images
+            .unwrap_or_default()
+            .into_iter()
+            .map(|image| ImageUsage {
+                id: image.id,
+                tags: image
+                    .repo_tags
+                    .into_iter()
+                    .filter(|tag| tag != "<none>:<none>")
+                    .collect(),
+                size: image.size.max(0) as u64,
+                containers: image.containers.max(0) as u64,
+            })
+            .collect();
+        let volumes = usage
+            .volumes
+            .unwrap_or_default()
+            .into_iter()
+            .map(|volume| VolumeUsage {
+                name: volume.name,
+                // Docker reports -1 if the size could not be determined
+                size: volume
+                    .usage_data
+                    .as_ref()
+                    .and_then(|usage| u64::try_from(usage.size).ok()),
+                containers: volume
+                    .usage_data
+                    .map(|usage| usage.ref_count.max(0) as u64)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Ok(DiskUsage { images, volumes })
+    }
+
+    pub async fn remove_image_with_client(
+        docker_client: Arc<Docker>,
+        image: &str,
+    ) -> anyhow::Result<bool> {
+        match relic::docker::image::remove(docker_client, image, None, None).await {
+            Ok(_) => Ok(true),
+            Err(e) => match e.downcast_ref::<bollard::errors::Error>() {
+                Some(bollard::errors::Error::DockerResponseServerError {
+                    status_code: 404,
+                    ..
+                }) => Ok(false),
+                _ => Err(e),
+            },
+        }
+    }
 }
 
 #[async_trait]
@@ -994,6 +1012,15 @@ impl DockerDeployment for DockerDeploymentImpl {
         Self::copy_to_instance(self.client()?, quest, id, src, dst, is_dst_file_path).await
     }
 
+    async fn list_instance_files(
+        &self,
+        id: InstanceId,
+        path: &Path,
+    ) -> anyhow::Result<Vec<EntryHeader>> {
+        relic::docker::container::list_archive_entries(self.client()?, path, &id.to_docker_id())
+            .await
+    }
+
     async fn copy_configs_from_instance(
         &self,
         id: InstanceId,
@@ -1111,6 +1138,52 @@ impl DockerDeployment for DockerDeploymentImpl {
         Ok(Logs { stderr, stdout })
     }
 
+    async fn exec_in_instance(
+        &self,
+        id: InstanceId,
+        cmd: Vec<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<ExecOutput> {
+        let docker_client = self.client()?;
+        Ok(relic::docker::container::exec_to_completion(
+            docker_client,
+            &id.to_docker_id(),
+            cmd,
+            timeout,
+        )
+        .await?
+        .into())
+    }
+
+    async fn exec_tty_in_instance(
+        &self,
+        id: InstanceId,
+        cmd: Vec<String>,
+    ) -> anyhow::Result<ExecSession> {
+        let docker_client = self.client()?;
+        let (id, output, input) =
+            relic::docker::container::exec_tty(docker_client, &id.to_docker_id(), cmd).await?;
+        Ok(ExecSession { id, output, input })
+    }
+
+    async fn resize_instance_exec(
+        &self,
+        id: InstanceId,
+        exec_id: &str,
+        width: u16,
+        height: u16,
+    ) -> anyhow::Result<()> {
+        let docker_client = self.client()?;
+        relic::docker::container::resize_exec(
+            docker_client,
+            &id.to_docker_id(),
+            exec_id,
+            width,
+            height,
+        )
+        .await
+    }
+
     async fn instance_default_address(
         &self,
         lore: NetworkLoreRef,
@@ -1273,6 +1346,21 @@ impl AppDeployment for DockerDeploymentImpl {
         )
         .await
     }
+    async fn import_app_image(
+        &self,
+        quest: SyncQuest,
+        lore: ImportLoreRef,
+        image: ImageArchive,
+    ) -> anyhow::Result<()> {
+        relic::docker::image::load_from_reader(
+            quest,
+            self.client_with_timeout(lore.as_ref().as_ref().timeout)?,
+            image,
+            ImportImageOptions::default(),
+            None,
+        )
+        .await
+    }
 }
 
 #[async_trait]
@@ -1324,6 +1412,17 @@ impl VolumeDeployment for DockerDeploymentImpl {
     }
 }
 
+#[async_trait]
+impl StorageDeployment for DockerDeploymentImpl {
+    async fn disk_usage(&self) -> anyhow::Result<DiskUsage> {
+        Self::disk_usage_with_client(self.client()?).await
+    }
+
+    async fn remove_image(&self, image: &str) -> anyhow::Result<bool> {
+        Self::remove_image_with_client(self.client()?, image).await
+    }
+}
+
 #[async_trait]
 impl NetworkDeployment for DockerDeploymentImpl {
     async fn create_network(
@@ -1,11 +1,12 @@
 mod docker_impl;
 use crate::jeweler::deployment::CommonDeployment;
 use crate::jeweler::gem::instance::status::InstanceStatus;
-use crate::jeweler::gem::instance::{InstanceId, Logs};
+use crate::jeweler::gem::instance::{ExecOutput, ExecSession, InstanceId, Logs};
 use crate::jeweler::gem::manifest::single::{AppManifestSingle, ConfigFile};
 use crate::jeweler::network::NetworkId;
 use crate::lore::{InstanceLoreRef, NetworkLoreRef};
 use crate::quest::SyncQuest;
+use crate::relic::async_flecstract::EntryHeader;
 use async_trait::async_trait;
 use bollard::container::Config;
 pub use docker_impl::*;
@@ -13,6 +14,7 @@ use erased_serde::serialize_trait_object;
 use std::net::{IpAddr, Ipv4Addr};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 pub type AppInfo = bollard::models::ImageInspect;
 #[async_trait]
@@ -65,6 +67,13 @@ pub trait DockerDeployment: CommonDeployment {
         is_dst_file_path: bool,
     ) -> anyhow::Result<()>;
 
+    /// Lists the entries of the archive of `path` in the instance without copying its content
+    async fn list_instance_files(
+        &self,
+        id: InstanceId,
+        path: &Path,
+    ) -> anyhow::Result<Vec<EntryHeader>>;
+
     async fn copy_configs_from_instance(
         &self,
         id: InstanceId,
@@ -92,6 +101,32 @@ pub trait DockerDeployment: CommonDeployment {
     async fn instance_status(&self, id: InstanceId) -> anyhow::Result<InstanceStatus>;
 
     async fn instance_logs(&self, quest: SyncQuest, id: InstanceId) -> anyhow::Result<Logs>;
+
+    /// Executes the command inside the running instance and waits at most `timeout` for it to
+    /// finish, see [crate::relic::docker::container::exec_to_completion]
+    async fn exec_in_instance(
+        &self,
+        id: InstanceId,
+        cmd: Vec<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<ExecOutput>;
+
+    /// Starts the command with an attached tty inside the running instance
+    async fn exec_tty_in_instance(
+        &self,
+        id: InstanceId,
+        cmd: Vec<String>,
+    ) -> anyhow::Result<ExecSession>;
+
+    /// Resizes the tty of the exec instance, fails if it was not started in the instance
+    async fn resize_instance_exec(
+        &self,
+        id: InstanceId,
+        exec_id: &str,
+        width: u16,
+        height: u16,
+    ) -> anyhow::Result<()>;
+
     async fn instance_default_address(
         &self,
         lore: NetworkLoreRef,
@@ -107,6 +142,7 @@ pub mod tests {
     use crate::Result;
     use crate::jeweler::GetDeploymentId;
     use crate::jeweler::app::AppDeployment;
+    use crate::jeweler::app::ImageArchive;
     use crate::jeweler::app::Token;
     use crate::jeweler::deployment::{CommonDeployment, DeploymentId};
     use crate::jeweler::gem::deployment::Deployment;
@@ -118,6 +154,7 @@ pub mod tests {
         CreateNetworkError, InspectNetworkError, Network, NetworkConfig, NetworkDeployment,
         NetworkId,
     };
+    use crate::jeweler::storage::{DiskUsage, StorageDeployment};
     use crate::jeweler::volume::Volume;
     use crate::jeweler::volume::VolumeDeployment;
     use crate::jeweler::volume::VolumeId;
@@ -170,6 +207,12 @@ pub mod tests {
                 manifest: AppManifest,
                 path: PathBuf
             ) -> Result<()>;
+            async fn import_app_image(
+                &self,
+                quest: SyncQuest,
+                lore: ImportLoreRef,
+                image: ImageArchive,
+            ) -> Result<()>;
         }
         #[async_trait]
         impl NetworkDeployment for edDockerDeployment {
@@ -201,6 +244,11 @@ pub mod tests {
             ) -> Result<()>;
             async fn inspect_volume(&self, id: VolumeId) -> Result<Option<Volume>>;
         }
+        #[async_trait]
+        impl StorageDeployment for edDockerDeployment {
+            async fn disk_usage(&self) -> Result<DiskUsage>;
+            async fn remove_image(&self, image: &str) -> Result<bool>;
+        }
         impl GetDeploymentId for edDockerDeployment {
             fn deployment_id(&self) -> &DeploymentId;
         }
@@ -254,6 +302,11 @@ pub mod tests {
                 dst: &Path,
                 is_dst_file_path: bool,
             ) -> Result<()>;
+            async fn list_instance_files(
+                &self,
+                id: InstanceId,
+                path: &Path,
+            ) -> Result<Vec<EntryHeader>>;
             async fn copy_configs_from_instance(
                 &self,
                 id: InstanceId,
@@ -276,6 +329,20 @@ pub mod tests {
             async fn delete_instance(&self, id: InstanceId) -> Result<bool>;
             async fn instance_status(&self, id: InstanceId) -> Result<InstanceStatus>;
             async fn instance_logs(&self, quest: SyncQuest, id: InstanceId) -> Result<Logs>;
+            async fn exec_in_instance(
+                &self,
+                id: InstanceId,
+                cmd: Vec<String>,
+                timeout: Duration,
+            ) -> Result<ExecOutput>;
+            async fn exec_tty_in_instance(&self, id: InstanceId, cmd: Vec<String>) -> Result<ExecSession>;
+            async fn resize_instance_exec(
+                &self,
+                id: InstanceId,
+                exec_id: &str,
+                width: u16,
+                height: u16,
+            ) -> Result<()>;
             async fn instance_default_address(
                 &self,
                 lore: NetworkLoreRef,
@@ -1,10 +1,12 @@
 pub mod compose;
 pub mod docker;
+pub mod podman;
 
 use crate::jeweler::GetDeploymentId;
 use crate::jeweler::deployment::CommonDeployment;
 use crate::jeweler::gem::deployment::compose::ComposeDeploymentImpl;
-use crate::jeweler::gem::deployment::docker::DockerDeploymentImpl;
+use crate::jeweler::gem::deployment::docker::{DockerDeployment, DockerDeploymentImpl};
+use crate::jeweler::gem::deployment::podman::PodmanDeploymentImpl;
 use crate::vault::pouch::deployment::DeploymentId;
 use serde::{Deserialize, Serialize};
 use std::ops::Deref;
@@ -16,6 +18,7 @@ use std::sync::Arc;
 pub enum Deployment {
     Compose(Arc<dyn compose::ComposeDeployment>),
     Docker(Arc<dyn docker::DockerDeployment>),
+    Podman(Arc<dyn podman::PodmanDeployment>),
 }
 
 impl GetDeploymentId for Deployment {
@@ -31,6 +34,7 @@ impl Deref for Deployment {
         match self {
             Deployment::Compose(compose) => compose.as_ref(),
             Deployment::Docker(docker) => docker.as_ref(),
+            Deployment::Podman(podman) => podman.as_ref(),
         }
     }
 }
@@ -40,6 +44,17 @@ impl Deployment {
         match self {
             Self::Docker(deployment) => deployment.id(),
             Self::Compose(deployment) => deployment.id(),
+            Self::Podman(deployment) => deployment.id(),
+        }
+    }
+
+    /// Docker and podman deployments both run single image apps, returns the deployment as
+    /// [DockerDeployment] if it supports them
+    pub fn as_docker_deployment(&self) -> Option<Arc<dyn DockerDeployment>> {
+        match self {
+            Self::Docker(deployment) => Some(deployment.clone()),
+            Self::Podman(deployment) => Some(deployment.clone()),
+            Self::Compose(_) => None,
         }
     }
 }
@@ -49,6 +64,7 @@ impl Deployment {
 pub enum SerializedDeployment {
     Docker(DockerDeploymentImpl),
     Compose(ComposeDeploymentImpl),
+    Podman(PodmanDeploymentImpl),
 }
 
 impl From<SerializedDeployment> for Deployment {
@@ -56,6 +72,7 @@ impl From<SerializedDeployment> for Deployment {
         match value {
             SerializedDeployment::Docker(docker) => Self::Docker(Arc::new(docker)),
             SerializedDeployment::Compose(compose) => Self::Compose(Arc::new(compose)),
+            SerializedDeployment::Podman(podman) => Self::Podman(Arc::new(podman)),
         }
     }
 }
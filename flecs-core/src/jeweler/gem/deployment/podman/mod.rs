@@ -0,0 +1,16 @@
+mod podman_impl;
+mod spec;
+use crate::jeweler::gem::deployment::docker::DockerDeployment;
+use async_trait::async_trait;
+use erased_serde::serialize_trait_object;
+pub use podman_impl::*;
+
+/// Podman runs the same single image apps as docker, instances use the podman deployment through
+/// [DockerDeployment] which is implemented against the libpod REST API.
+#[async_trait]
+pub trait PodmanDeployment: DockerDeployment {
+    /// Rootless podman can not create macvlan and ipvlan networks
+    async fn is_rootless(&self) -> anyhow::Result<bool>;
+}
+
+serialize_trait_object!(PodmanDeployment);
@@ -0,0 +1,1549 @@
+use super::PodmanDeployment;
+use super::spec::container_spec;
+use crate::forge::bollard::BollardNetworkExtension;
+use crate::jeweler::GetDeploymentId;
+use crate::jeweler::app::{AppDeployment, ImageArchive, Token};
+use crate::jeweler::deployment::CommonDeployment;
+use crate::jeweler::gem::deployment::docker::{AppInfo, DockerDeployment, DockerDeploymentImpl};
+use crate::jeweler::gem::instance::status::InstanceStatus;
+use crate::jeweler::gem::instance::{ExecOutput, ExecSession, InstanceId, Logs};
+use crate::jeweler::gem::manifest::AppManifest;
+use crate::jeweler::gem::manifest::single::{AppManifestSingle, ConfigFile};
+use crate::jeweler::network::{
+    CreateNetworkError, InspectNetworkError, Network, NetworkConfig, NetworkDeployment, NetworkId,
+    NetworkKind,
+};
+use crate::jeweler::storage::{DiskUsage, ImageUsage, StorageDeployment, VolumeUsage};
+use crate::jeweler::volume::{Volume, VolumeDeployment, VolumeId};
+use crate::lore::{ExportLoreRef, ImportLoreRef, InstanceLoreRef, NetworkLoreRef};
+use crate::quest::{Quest, State, SyncQuest};
+use crate::relic::async_flecstract::{EntryHeader, archive_to_memory};
+use crate::relic::podman::PodmanClient;
+use crate::relic::podman::network::{LibpodNetwork, Subnet};
+use crate::vault::pouch::deployment::DeploymentId;
+use crate::{jeweler, relic};
+use async_compression::tokio::bufread::GzipDecoder;
+use async_trait::async_trait;
+use axum::body::Body;
+use bollard::auth::DockerCredentials;
+use bollard::container::Config;
+use futures_util::StreamExt;
+use net_spider::network_adapter::{NetworkAdapterReader, NetworkAdapterReaderImpl};
+use serde::{Deserialize, Serialize};
+use std::fmt::Formatter;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::BufReader;
+use tokio_util::codec;
+use tracing::{debug, warn};
+
+pub const DEFAULT_PODMAN_DEPLOYMENT_ID: &str = "DefaultPodmanDeployment";
+
+#[derive(Serialize, Deserialize)]
+pub struct PodmanDeploymentImpl {
+    pub id: DeploymentId,
+    path: PathBuf,
+    #[serde(default)]
+    is_default: bool,
+    #[serde(skip, default = "default_network_adapter_reader")]
+    network_adapter_reader: Box<dyn NetworkAdapterReader>,
+}
+
+impl GetDeploymentId for PodmanDeploymentImpl {
+    fn deployment_id(&self) -> &DeploymentId {
+        &self.id
+    }
+}
+
+impl Default for PodmanDeploymentImpl {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_PODMAN_DEPLOYMENT_ID.to_string(),
+            PathBuf::from("/run/podman/podman.sock"),
+        )
+    }
+}
+
+impl std::fmt::Debug for PodmanDeploymentImpl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        #[derive(Debug)]
+        #[allow(dead_code)]
+        struct PodmanDeploymentImpl<'a> {
+            id: &'a DeploymentId,
+            path: &'a PathBuf,
+            is_default: &'a bool,
+        }
+
+        let Self {
+            id,
+            path,
+            is_default,
+            network_adapter_reader: _,
+        } = self;
+        std::fmt::Debug::fmt(
+            &PodmanDeploymentImpl {
+                id,
+                path,
+                is_default,
+            },
+            f,
+        )
+    }
+}
+
+impl PartialEq for PodmanDeploymentImpl {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.path == other.path
+    }
+}
+
+impl Eq for PodmanDeploymentImpl {}
+
+fn default_network_adapter_reader() -> Box<dyn NetworkAdapterReader> {
+    Box::new(NetworkAdapterReaderImpl)
+}
+
+impl PodmanDeploymentImpl {
+    pub fn new(id: String, path: PathBuf) -> Self {
+        Self {
+            id,
+            path,
+            is_default: false,
+            network_adapter_reader: default_network_adapter_reader(),
+        }
+    }
+
+    pub fn new_default(id: String, path: PathBuf) -> Self {
+        Self {
+            id,
+            path,
+            is_default: true,
+            network_adapter_reader: default_network_adapter_reader(),
+        }
+    }
+
+    fn client(&self) -> Arc<PodmanClient> {
+        self.client_with_timeout(Duration::from_secs(120))
+    }
+
+    fn client_with_timeout(&self, timeout: Duration) -> Arc<PodmanClient> {
+        Arc::new(PodmanClient::new(self.path.clone(), timeout))
+    }
+
+    async fn remove_container(client: Arc<PodmanClient>, container: &str) {
+        if let Err(e) = relic::podman::container::remove(client, container, true).await {
+            warn!("Could not remove container {container}: {e}");
+        }
+    }
+
+    async fn copy_config_to_instance(
+        client: Arc<PodmanClient>,
+        lore: InstanceLoreRef,
+        id: InstanceId,
+        config_files: &[ConfigFile],
+    ) -> crate::Result<()> {
+        for config_file in config_files {
+            let src = lore
+                .as_ref()
+                .as_ref()
+                .instance_config_path(&id.to_string())
+                .join(&config_file.host_file_name);
+            if let Err(e) = relic::podman::container::copy_to(
+                client.clone(),
+                Quest::new_synced(format!(
+                    "Copy config {} to instance {}",
+                    config_file.host_file_name, id
+                )),
+                &src,
+                &config_file.container_file_path,
+                &id.to_docker_id(),
+                true,
+                true,
+            )
+            .await
+            {
+                anyhow::bail!(
+                    "Could not copy config file {src:?} of instance {id} to {:?}: {e}",
+                    config_file.container_file_path
+                )
+            }
+        }
+        Ok(())
+    }
+
+    async fn copy_config_from_instance(
+        client: Arc<PodmanClient>,
+        id: InstanceId,
+        config_files: &[ConfigFile],
+        dst: PathBuf,
+    ) -> crate::Result<()> {
+        for config_file in config_files {
+            let dst = dst.join(&config_file.host_file_name);
+            if let Err(e) = relic::podman::container::copy_from(
+                Quest::new_synced(format!(
+                    "Copy config {:?} from instance {}",
+                    config_file.container_file_path, id
+                )),
+                client.clone(),
+                &config_file.container_file_path,
+                &dst,
+                &id.to_docker_id(),
+                true,
+            )
+            .await
+            {
+                anyhow::bail!(
+                    "Could not copy config file {:?} of instance {id} to {dst:?}: {e}",
+                    config_file.container_file_path
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// Translates the network config into the libpod representation, subnet and gateway of
+    /// ipvlan networks are taken from the parent adapter if not specified.
+    fn libpod_network(
+        mut config: NetworkConfig,
+        network_adapter_reader: &dyn NetworkAdapterReader,
+    ) -> Result<LibpodNetwork, CreateNetworkError> {
+        let mut options = config.options.take().unwrap_or_default();
+        let (driver, internal) = match config.kind {
+            NetworkKind::Bridge => ("bridge", false),
+            NetworkKind::Internal => ("bridge", true),
+            NetworkKind::MACVLAN => ("macvlan", false),
+            NetworkKind::IpvlanL2 | NetworkKind::IpvlanL3 => {
+                config.fill_from_parent_adapter(network_adapter_reader)?;
+                let mode = if config.kind == NetworkKind::IpvlanL2 {
+                    "l2"
+                } else {
+                    "l3"
+                };
+                options.insert("mode".to_string(), mode.to_string());
+                ("ipvlan", false)
+            }
+            x => {
+                return Err(CreateNetworkError::NetworkConfigInvalid {
+                    location: "kind".to_string(),
+                    reason: format!("Invalid network type {x}"),
+                });
+            }
+        };
+        Ok(LibpodNetwork {
+            name: config.name,
+            driver: driver.to_string(),
+            network_interface: config.parent_adapter.unwrap_or_default(),
+            subnets: config
+                .cidr_subnet
+                .map(|subnet| Subnet {
+                    subnet: subnet.to_string(),
+                    gateway: config.gateway.as_ref().map(ToString::to_string),
+                })
+                .into_iter()
+                .collect(),
+            internal,
+            options,
+            ..LibpodNetwork::default()
+        })
+    }
+
+    async fn guess_volume_path(src: &Path, volume_name: &str) -> Result<PathBuf, std::io::Error> {
+        let src = src.join(volume_name);
+        let tar_src = src.with_extension("tar");
+        let tar_gz_src = src.with_extension("tar.gz");
+        match (
+            tokio::fs::try_exists(&src).await?,
+            tokio::fs::try_exists(&tar_src).await?,
+            tokio::fs::try_exists(&tar_gz_src).await?,
+        ) {
+            (true, _, _) => Ok(src),
+            (_, true, _) => Ok(tar_src),
+            (_, _, true) => Ok(tar_gz_src),
+            _ => Err(std::io::Error::from(std::io::ErrorKind::NotFound)),
+        }
+    }
+
+    /// Uploads a directory, a tar archive or a compressed tar archive into the volume
+    async fn upload_volume_content(
+        client: Arc<PodmanClient>,
+        quest: SyncQuest,
+        name: &str,
+        src: &Path,
+    ) -> anyhow::Result<()> {
+        if tokio::fs::metadata(src).await?.is_dir() {
+            debug!("Uploading directory {src:?}");
+            let archive = archive_to_memory(src, false).await?;
+            let total = archive.len() as u64;
+            relic::podman::volume::import_archive(
+                client,
+                quest,
+                name,
+                Body::from(archive),
+                Some(total),
+            )
+            .await
+        } else if src.extension() == Some("gz".as_ref()) {
+            debug!("Uploading compressed archive {src:?}");
+            let file = tokio::fs::File::open(src).await?;
+            let decoder = GzipDecoder::new(BufReader::new(file));
+            let byte_stream = codec::FramedRead::new(decoder, codec::BytesCodec::new())
+                .map(|r| r.map(|b| b.freeze()));
+            relic::podman::volume::import_archive(
+                client,
+                quest,
+                name,
+                Body::from_stream(byte_stream),
+                None,
+            )
+            .await
+        } else {
+            debug!("Uploading archive {src:?}");
+            relic::podman::volume::import_archive_file(client, quest, name, src).await
+        }
+    }
+}
+
+#[async_trait]
+impl CommonDeployment for PodmanDeploymentImpl {
+    fn id(&self) -> &jeweler::deployment::DeploymentId {
+        &self.id
+    }
+
+    fn is_default(&self) -> bool {
+        self.is_default
+    }
+
+    async fn core_default_address(&self, lore: NetworkLoreRef) -> Option<IpAddr> {
+        self.default_network(lore)
+            .await
+            .ok()?
+            .gateways()
+            .ok()?
+            .first()
+            .copied()
+    }
+}
+
+#[async_trait]
+impl PodmanDeployment for PodmanDeploymentImpl {
+    async fn is_rootless(&self) -> anyhow::Result<bool> {
+        Ok(relic::podman::system::info(self.client())
+            .await?
+            .host
+            .security
+            .rootless)
+    }
+}
+
+#[async_trait]
+impl DockerDeployment for PodmanDeploymentImpl {
+    async fn app_info(
+        &self,
+        _quest: SyncQuest,
+        manifest: Arc<AppManifestSingle>,
+    ) -> anyhow::Result<Option<AppInfo>> {
+        relic::podman::image::inspect(self.client(), &manifest.image_with_tag()).await
+    }
+
+    async fn copy_from_app_image(
+        &self,
+        quest: SyncQuest,
+        image: String,
+        src: &Path,
+        dst: &Path,
+        is_dst_file_path: bool,
+    ) -> anyhow::Result<()> {
+        let client = self.client();
+        let spec = container_spec(
+            Config {
+                image: Some(image.clone()),
+                network_disabled: Some(true),
+                ..Config::default()
+            },
+            None,
+        )?;
+        let container = relic::podman::container::create(client.clone(), &spec).await?;
+        let copy_result = relic::podman::container::copy_from(
+            quest,
+            client.clone(),
+            src,
+            dst,
+            &container,
+            is_dst_file_path,
+        )
+        .await;
+        Self::remove_container(client, &container).await;
+        copy_result
+    }
+
+    async fn connect_network(
+        &self,
+        _quest: SyncQuest,
+        id: NetworkId,
+        address: Ipv4Addr,
+        instance_id: InstanceId,
+    ) -> anyhow::Result<()> {
+        relic::podman::network::connect(
+            self.client(),
+            &id,
+            &instance_id.to_docker_id(),
+            Some(address.to_string()),
+        )
+        .await
+    }
+
+    async fn disconnect_network(
+        &self,
+        _quest: SyncQuest,
+        id: NetworkId,
+        instance_id: InstanceId,
+    ) -> anyhow::Result<()> {
+        relic::podman::network::disconnect(self.client(), &id, &instance_id.to_docker_id(), false)
+            .await
+    }
+
+    async fn copy_from_instance(
+        &self,
+        quest: SyncQuest,
+        id: InstanceId,
+        src: &Path,
+        dst: &Path,
+        is_dst_file_path: bool,
+    ) -> anyhow::Result<()> {
+        relic::podman::container::copy_from(
+            quest,
+            self.client(),
+            src,
+            dst,
+            &id.to_docker_id(),
+            is_dst_file_path,
+        )
+        .await
+    }
+
+    async fn copy_to_instance(
+        &self,
+        quest: SyncQuest,
+        id: InstanceId,
+        src: &Path,
+        dst: &Path,
+        is_dst_file_path: bool,
+    ) -> anyhow::Result<()> {
+        relic::podman::container::copy_to(
+            self.client(),
+            quest,
+            src,
+            dst,
+            &id.to_docker_id(),
+            true,
+            is_dst_file_path,
+        )
+        .await
+    }
+
+    async fn list_instance_files(
+        &self,
+        id: InstanceId,
+        path: &Path,
+    ) -> anyhow::Result<Vec<EntryHeader>> {
+        relic::podman::container::list_archive_entries(self.client(), path, &id.to_docker_id())
+            .await
+    }
+
+    async fn copy_configs_from_instance(
+        &self,
+        id: InstanceId,
+        config_files: &[ConfigFile],
+        dst: PathBuf,
+    ) -> anyhow::Result<()> {
+        Self::copy_config_from_instance(self.client(), id, config_files, dst).await
+    }
+
+    async fn start_instance(
+        &self,
+        lore: InstanceLoreRef,
+        config: Config<String>,
+        id: Option<InstanceId>,
+        config_files: &[ConfigFile],
+    ) -> anyhow::Result<InstanceId> {
+        let client = self.client();
+        let id = id.unwrap_or_else(InstanceId::new_random);
+        let container_name = id.to_docker_id();
+        relic::podman::container::remove(client.clone(), &container_name, true).await?;
+        let spec = container_spec(config, Some(&container_name))?;
+        let container_id = relic::podman::container::create(client.clone(), &spec).await?;
+        debug!("Created container {}/{}", id, container_id);
+        if let Err(e) = Self::copy_config_to_instance(client.clone(), lore, id, config_files).await
+        {
+            Self::remove_container(client, &container_id).await;
+            return Err(e);
+        }
+        if let Err(e) = relic::podman::container::start(client.clone(), &container_name).await {
+            Self::remove_container(client, &container_id).await;
+            return Err(e);
+        }
+        Ok(id)
+    }
+
+    async fn stop_instance(
+        &self,
+        id: InstanceId,
+        lore: InstanceLoreRef,
+        config_files: &[ConfigFile],
+    ) -> anyhow::Result<()> {
+        let client = self.client();
+        relic::podman::container::stop(client.clone(), &id.to_docker_id(), None).await?;
+        Self::copy_config_from_instance(
+            client,
+            id,
+            config_files,
+            lore.as_ref().as_ref().instance_config_path(&id.to_string()),
+        )
+        .await?;
+        self.delete_instance(id).await?;
+        Ok(())
+    }
+
+    async fn delete_instance(&self, id: InstanceId) -> anyhow::Result<bool> {
+        relic::podman::container::remove(self.client(), &id.to_docker_id(), true).await
+    }
+
+    async fn instance_status(&self, id: InstanceId) -> anyhow::Result<InstanceStatus> {
+        match relic::podman::container::inspect(self.client(), &id.to_docker_id()).await? {
+            None => Ok(InstanceStatus::Stopped),
+            Some(container) => Ok(container.state.docker_status().into()),
+        }
+    }
+
+    async fn instance_logs(&self, quest: SyncQuest, id: InstanceId) -> anyhow::Result<Logs> {
+        let (stdout, stderr) =
+            relic::podman::container::logs(self.client(), quest, &id.to_docker_id()).await?;
+        Ok(Logs { stdout, stderr })
+    }
+
+    async fn exec_in_instance(
+        &self,
+        id: InstanceId,
+        cmd: Vec<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<ExecOutput> {
+        Ok(relic::podman::container::exec_to_completion(
+            self.client(),
+            Quest::new_synced(format!("Execute command in instance {id}")),
+            &id.to_docker_id(),
+            cmd,
+            timeout,
+        )
+        .await?
+        .into())
+    }
+
+    async fn exec_tty_in_instance(
+        &self,
+        id: InstanceId,
+        _cmd: Vec<String>,
+    ) -> anyhow::Result<ExecSession> {
+        anyhow::bail!(
+            "Interactive terminals are not supported for instance {id} in podman deployments"
+        )
+    }
+
+    async fn resize_instance_exec(
+        &self,
+        _id: InstanceId,
+        exec_id: &str,
+        _width: u16,
+        _height: u16,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "Interactive terminals are not supported in podman deployments, can not resize exec {exec_id}"
+        )
+    }
+
+    async fn instance_default_address(
+        &self,
+        lore: NetworkLoreRef,
+        id: InstanceId,
+    ) -> anyhow::Result<Option<IpAddr>> {
+        let address = relic::podman::container::inspect(self.client(), &id.to_docker_id())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Instance with id {id} not found"))?
+            .network_settings
+            .networks
+            .remove(&lore.as_ref().as_ref().default_network_name)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Instance with id {id} is not connected to the default network")
+            })?
+            .ip_address;
+        match address.as_str() {
+            "" => Ok(None),
+            address => Ok(Some(address.parse()?)),
+        }
+    }
+}
+
+#[async_trait]
+impl AppDeployment for PodmanDeploymentImpl {
+    async fn install_app(
+        &self,
+        quest: SyncQuest,
+        manifest: AppManifest,
+        token: Option<Token>,
+    ) -> anyhow::Result<()> {
+        let AppManifest::Single(manifest) = manifest else {
+            anyhow::bail!("PodmanDeploymentImpl supports only AppManifest::Single");
+        };
+        let client = self.client();
+        let (.., id) = quest
+            .lock()
+            .await
+            .create_sub_quest(
+                format!("Download image {}", manifest.image()),
+                |quest| async move {
+                    relic::podman::image::pull(
+                        quest,
+                        client,
+                        token.map(|token| DockerCredentials {
+                            username: Some(token.username),
+                            password: Some(token.password),
+                            ..DockerCredentials::default()
+                        }),
+                        manifest.image(),
+                        manifest.key.version.as_str(),
+                    )
+                    .await
+                },
+            )
+            .await;
+        id.await?;
+        Ok(())
+    }
+
+    async fn uninstall_app(&self, quest: SyncQuest, manifest: AppManifest) -> anyhow::Result<()> {
+        let AppManifest::Single(manifest) = manifest else {
+            anyhow::bail!("PodmanDeploymentImpl supports only AppManifest::Single");
+        };
+        let client = self.client();
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest(
+                format!("Removing image of {}", manifest.key),
+                |quest| async move {
+                    if !relic::podman::image::remove(client, &manifest.image_with_tag(), true)
+                        .await?
+                    {
+                        let mut quest = quest.lock().await;
+                        quest.state = State::Skipped;
+                        quest.detail = Some("Image does not exist".to_string());
+                    }
+                    Ok::<(), anyhow::Error>(())
+                },
+            )
+            .await
+            .2;
+        result.await
+    }
+
+    async fn is_app_installed(
+        &self,
+        quest: SyncQuest,
+        manifest: AppManifest,
+    ) -> anyhow::Result<bool> {
+        let AppManifest::Single(manifest) = manifest else {
+            anyhow::bail!("PodmanDeploymentImpl supports only AppManifest::Single");
+        };
+        Ok(self.app_info(quest, manifest).await?.is_some())
+    }
+
+    async fn installed_app_size(
+        &self,
+        quest: SyncQuest,
+        manifest: AppManifest,
+    ) -> anyhow::Result<usize> {
+        let AppManifest::Single(manifest) = manifest else {
+            anyhow::bail!("PodmanDeploymentImpl supports only AppManifest::Single");
+        };
+        Ok(self
+            .app_info(quest, manifest)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("App not installed"))?
+            .size
+            .ok_or_else(|| anyhow::anyhow!("Size was not specified"))? as usize)
+    }
+
+    async fn export_app(
+        &self,
+        quest: SyncQuest,
+        lore: ExportLoreRef,
+        manifest: AppManifest,
+        path: PathBuf,
+    ) -> anyhow::Result<()> {
+        let AppManifest::Single(manifest) = manifest else {
+            anyhow::bail!("PodmanDeploymentImpl supports only AppManifest::Single");
+        };
+        let image = manifest.image_with_tag();
+        let path = path.join(format!(
+            "{}_{}.tar",
+            manifest.key.name, manifest.key.version
+        ));
+        relic::podman::image::save(
+            quest,
+            self.client_with_timeout(lore.as_ref().as_ref().timeout),
+            &path,
+            &image,
+        )
+        .await
+    }
+
+    async fn import_app(
+        &self,
+        quest: SyncQuest,
+        lore: ImportLoreRef,
+        manifest: AppManifest,
+        path: PathBuf,
+    ) -> anyhow::Result<()> {
+        let key = manifest.key();
+        let path = path.join(format!("{}_{}.tar", key.name, key.version));
+        relic::podman::image::load(
+            quest,
+            self.client_with_timeout(lore.as_ref().as_ref().timeout),
+            &path,
+        )
+        .await
+    }
+    async fn import_app_image(
+        &self,
+        quest: SyncQuest,
+        lore: ImportLoreRef,
+        image: ImageArchive,
+    ) -> anyhow::Result<()> {
+        relic::podman::image::load_from_reader(
+            quest,
+            self.client_with_timeout(lore.as_ref().as_ref().timeout),
+            image,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl VolumeDeployment for PodmanDeploymentImpl {
+    async fn create_volume(&self, _quest: SyncQuest, name: &str) -> anyhow::Result<VolumeId> {
+        Ok(relic::podman::volume::create(self.client(), name)
+            .await?
+            .name)
+    }
+
+    async fn delete_volume(&self, _quest: SyncQuest, id: VolumeId) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            relic::podman::volume::remove(self.client(), &id).await?,
+            "Volume {id} does not exist"
+        );
+        Ok(())
+    }
+
+    /// Libpod imports volume content directly, the container path and image are only required
+    /// by docker which copies the content via a temporary container.
+    async fn import_volume(
+        &self,
+        quest: SyncQuest,
+        src: &Path,
+        _container_path: &Path,
+        name: &str,
+        _image: &str,
+    ) -> anyhow::Result<VolumeId> {
+        let client = self.client();
+        let src = Self::guess_volume_path(src, name).await?;
+        let volume_gone = {
+            let client = client.clone();
+            let name = name.to_string();
+            quest
+                .lock()
+                .await
+                .create_sub_quest("Delete existing volume".to_string(), |quest| async move {
+                    if !relic::podman::volume::remove(client, &name).await? {
+                        let mut quest = quest.lock().await;
+                        quest.state = State::Skipped;
+                        quest.detail = Some("Volume does not exist".to_string());
+                    }
+                    Ok::<(), anyhow::Error>(())
+                })
+                .await
+                .2
+        };
+        let created_volume = {
+            let client = client.clone();
+            let name = name.to_string();
+            quest
+                .lock()
+                .await
+                .create_sub_quest("Create volume".to_string(), |_quest| async move {
+                    volume_gone.await?;
+                    Ok::<VolumeId, anyhow::Error>(
+                        relic::podman::volume::create(client, &name).await?.name,
+                    )
+                })
+                .await
+                .2
+        };
+        let upload = {
+            let name = name.to_string();
+            quest
+                .lock()
+                .await
+                .create_sub_quest(
+                    format!("Uploading volume {name} from {src:?}"),
+                    |quest| async move {
+                        let volume_id = created_volume.await?;
+                        Self::upload_volume_content(client, quest, &volume_id, &src).await?;
+                        Ok::<VolumeId, anyhow::Error>(volume_id)
+                    },
+                )
+                .await
+                .2
+        };
+        upload.await
+    }
+
+    /// The volume content is exported as tar archive `<export_path>/<id>.tar`
+    async fn export_volume(
+        &self,
+        quest: SyncQuest,
+        id: VolumeId,
+        export_path: &Path,
+        _container_path: &Path,
+        _image: &str,
+    ) -> anyhow::Result<()> {
+        let dst = export_path.join(format!("{id}.tar"));
+        let client = self.client();
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest(
+                format!("Download volume content of {id}"),
+                |quest| async move {
+                    relic::podman::volume::export_to_file(quest, client, &id, &dst).await
+                },
+            )
+            .await
+            .2;
+        result.await
+    }
+
+    async fn inspect_volume(&self, id: VolumeId) -> anyhow::Result<Option<Volume>> {
+        relic::podman::volume::inspect(self.client(), &id).await
+    }
+}
+
+#[async_trait]
+impl StorageDeployment for PodmanDeploymentImpl {
+    async fn disk_usage(&self) -> anyhow::Result<DiskUsage> {
+        let usage = relic::podman::system::disk_usage(self.client()).await?;
+        Ok(DiskUsage {
+            images: images_from_disk_usage(usage.images),
+            volumes: usage
+                .volumes
+                .into_iter()
+                .map(|volume| VolumeUsage {
+                    name: volume.volume_name,
+                    size: u64::try_from(volume.size).ok(),
+                    containers: volume.links.max(0) as u64,
+                })
+                .collect(),
+        })
+    }
+
+    async fn remove_image(&self, image: &str) -> anyhow::Result<bool> {
+        relic::podman::image::remove(self.client(), image, false).await
+    }
+}
+
+/// Libpod lists images once per tag, the entries are merged by image id
+fn images_from_disk_usage(images: Vec<relic::podman::system::DiskUsageImage>) -> Vec<ImageUsage> {
+    let mut merged: Vec<ImageUsage> = Vec::new();
+    for image in images {
+        let tag = (image.repository != "<none>" && image.tag != "<none>")
+            .then(|| format!("{}:{}", image.repository, image.tag));
+        match merged.iter_mut().find(|known| known.id == image.image_id) {
+            Some(known) => known.tags.extend(tag),
+            None => merged.push(ImageUsage {
+                id: image.image_id,
+                tags: tag.into_iter().collect(),
+                size: image.size.max(0) as u64,
+                containers: image.containers.max(0) as u64,
+            }),
+        }
+    }
+    merged
+}
+
+#[async_trait]
+impl NetworkDeployment for PodmanDeploymentImpl {
+    async fn create_network(
+        &self,
+        _quest: SyncQuest,
+        config: NetworkConfig,
+    ) -> Result<Network, CreateNetworkError> {
+        let client = self.client();
+        if let Some(existing_network) =
+            relic::podman::network::inspect(client.clone(), &config.name).await?
+        {
+            return if DockerDeploymentImpl::network_config_fits_network(&config, &existing_network)?
+            {
+                Err(CreateNetworkError::ExactNetworkExists(existing_network))
+            } else {
+                Err(CreateNetworkError::DifferentNetworkExists(existing_network))
+            };
+        };
+        if matches!(
+            config.kind,
+            NetworkKind::MACVLAN | NetworkKind::IpvlanL2 | NetworkKind::IpvlanL3
+        ) && self.is_rootless().await?
+        {
+            return Err(CreateNetworkError::NetworkConfigInvalid {
+                location: "kind".to_string(),
+                reason: format!(
+                    "Network type {} is not supported by rootless podman",
+                    config.kind
+                ),
+            });
+        }
+        let network = Self::libpod_network(config, self.network_adapter_reader.as_ref())?;
+        Ok(relic::podman::network::create(client, &network).await?)
+    }
+
+    async fn default_network(&self, lore: NetworkLoreRef) -> Result<Network, InspectNetworkError> {
+        let default_network_name = lore.as_ref().as_ref().default_network_name.as_str();
+        relic::podman::network::inspect(self.client(), default_network_name)
+            .await?
+            .ok_or_else(|| {
+                InspectNetworkError::NetworkDoesNotExist(default_network_name.to_string())
+            })
+    }
+
+    async fn delete_network(&self, id: NetworkId) -> anyhow::Result<()> {
+        relic::podman::network::remove(self.client(), &id).await
+    }
+
+    async fn network(&self, id: NetworkId) -> anyhow::Result<Option<Network>> {
+        relic::podman::network::inspect(self.client(), &id).await
+    }
+
+    async fn networks(&self, _quest: SyncQuest) -> anyhow::Result<Vec<Network>> {
+        relic::podman::network::list(self.client()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::manifest::single::tests::create_test_manifest;
+    use crate::lore;
+    use crate::relic::podman::tests::{StubPodman, StubResponse};
+    use crate::relic::var::test::MockVarReader;
+    use http::{Method, StatusCode};
+    use ipnet::Ipv4Net;
+    use std::str::FromStr;
+    use testdir::testdir;
+
+    fn test_deployment(stub: &StubPodman) -> PodmanDeploymentImpl {
+        PodmanDeploymentImpl::new(
+            "TestPodmanDeployment".to_string(),
+            stub.client().socket_path().to_path_buf(),
+        )
+    }
+
+    fn info(rootless: bool) -> ((Method, &'static str), StubResponse) {
+        (
+            (Method::GET, "/info"),
+            StubResponse::json(
+                StatusCode::OK,
+                serde_json::json!({"host": {"security": {"rootless": rootless}}}),
+            ),
+        )
+    }
+
+    #[test]
+    fn deserialize_podman_deployment() {
+        let deployment: PodmanDeploymentImpl = serde_json::from_value(serde_json::json!({
+            "id": "Podman",
+            "path": "/run/user/1000/podman/podman.sock",
+        }))
+        .unwrap();
+        assert_eq!(
+            deployment,
+            PodmanDeploymentImpl::new(
+                "Podman".to_string(),
+                PathBuf::from("/run/user/1000/podman/podman.sock")
+            )
+        );
+        assert!(!deployment.is_default());
+    }
+
+    #[test]
+    fn libpod_network_bridge() {
+        let network = PodmanDeploymentImpl::libpod_network(
+            NetworkConfig {
+                kind: NetworkKind::Bridge,
+                name: "flecs".to_string(),
+                cidr_subnet: Some(Ipv4Net::from_str("172.21.0.0/16").unwrap()),
+                gateway: Some(Ipv4Addr::new(172, 21, 0, 1)),
+                parent_adapter: None,
+                options: None,
+            },
+            default_network_adapter_reader().as_ref(),
+        )
+        .unwrap();
+        assert_eq!(
+            network,
+            LibpodNetwork {
+                name: "flecs".to_string(),
+                driver: "bridge".to_string(),
+                subnets: vec![Subnet {
+                    subnet: "172.21.0.0/16".to_string(),
+                    gateway: Some("172.21.0.1".to_string()),
+                }],
+                ..LibpodNetwork::default()
+            }
+        );
+    }
+
+    #[test]
+    fn libpod_network_internal() {
+        let network = PodmanDeploymentImpl::libpod_network(
+            NetworkConfig {
+                kind: NetworkKind::Internal,
+                name: "internal".to_string(),
+                cidr_subnet: None,
+                gateway: None,
+                parent_adapter: None,
+                options: None,
+            },
+            default_network_adapter_reader().as_ref(),
+        )
+        .unwrap();
+        assert!(network.internal);
+        assert_eq!(network.driver, "bridge");
+        assert!(network.subnets.is_empty());
+    }
+
+    #[test]
+    fn libpod_network_ipvlan_l3() {
+        let network = PodmanDeploymentImpl::libpod_network(
+            NetworkConfig {
+                kind: NetworkKind::IpvlanL3,
+                name: "flecs-ipvlan_l3-eth0".to_string(),
+                cidr_subnet: Some(Ipv4Net::from_str("10.20.0.0/16").unwrap()),
+                gateway: Some(Ipv4Addr::new(10, 20, 0, 1)),
+                parent_adapter: Some("eth0".to_string()),
+                options: None,
+            },
+            default_network_adapter_reader().as_ref(),
+        )
+        .unwrap();
+        assert_eq!(network.driver, "ipvlan");
+        assert_eq!(network.network_interface, "eth0");
+        assert_eq!(network.options.get("mode").map(String::as_str), Some("l3"));
+    }
+
+    #[test]
+    fn libpod_network_ipvlan_without_parent() {
+        assert!(matches!(
+            PodmanDeploymentImpl::libpod_network(
+                NetworkConfig {
+                    kind: NetworkKind::IpvlanL2,
+                    name: "flecs-ipvlan_l2".to_string(),
+                    cidr_subnet: None,
+                    gateway: None,
+                    parent_adapter: None,
+                    options: None,
+                },
+                default_network_adapter_reader().as_ref(),
+            ),
+            Err(CreateNetworkError::NetworkConfigInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn libpod_network_invalid_kind() {
+        assert!(matches!(
+            PodmanDeploymentImpl::libpod_network(
+                NetworkConfig {
+                    kind: NetworkKind::Unknown,
+                    name: "unknown".to_string(),
+                    cidr_subnet: None,
+                    gateway: None,
+                    parent_adapter: None,
+                    options: None,
+                },
+                default_network_adapter_reader().as_ref(),
+            ),
+            Err(CreateNetworkError::NetworkConfigInvalid { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn create_network_rootless_macvlan() {
+        let stub = StubPodman::spawn(testdir!().join("podman.sock"), [info(true)]);
+        let deployment = test_deployment(&stub);
+        let result = deployment
+            .create_network(
+                Quest::new_synced("Create network".to_string()),
+                NetworkConfig {
+                    kind: NetworkKind::MACVLAN,
+                    name: "flecs-macvlan".to_string(),
+                    cidr_subnet: None,
+                    gateway: None,
+                    parent_adapter: Some("eth0".to_string()),
+                    options: None,
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(CreateNetworkError::NetworkConfigInvalid { location, .. }) if location == "kind"
+        ));
+        assert!(
+            !stub
+                .requests()
+                .iter()
+                .any(|request| request.method == Method::POST)
+        );
+    }
+
+    #[tokio::test]
+    async fn create_network_exists() {
+        let stub = StubPodman::spawn(
+            testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/networks/flecs/json"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({
+                        "name": "flecs",
+                        "driver": "bridge",
+                        "subnets": [{"subnet": "172.21.0.0/16", "gateway": "172.21.0.1"}]
+                    }),
+                ),
+            )],
+        );
+        let deployment = test_deployment(&stub);
+        let result = deployment
+            .create_network(
+                Quest::new_synced("Create network".to_string()),
+                NetworkConfig {
+                    kind: NetworkKind::Bridge,
+                    name: "flecs".to_string(),
+                    cidr_subnet: Some(Ipv4Net::from_str("172.21.0.0/16").unwrap()),
+                    gateway: Some(Ipv4Addr::new(172, 21, 0, 1)),
+                    parent_adapter: None,
+                    options: None,
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(CreateNetworkError::ExactNetworkExists(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn create_network_ok() {
+        let stub = StubPodman::spawn(
+            testdir!().join("podman.sock"),
+            [
+                info(false),
+                (
+                    (Method::POST, "/networks/create"),
+                    StubResponse::json(
+                        StatusCode::OK,
+                        serde_json::json!({
+                            "name": "flecs-macvlan",
+                            "id": "42ab",
+                            "driver": "macvlan",
+                            "network_interface": "eth0",
+                            "subnets": [{"subnet": "192.168.2.0/24", "gateway": "192.168.2.1"}]
+                        }),
+                    ),
+                ),
+            ],
+        );
+        let deployment = test_deployment(&stub);
+        let network = deployment
+            .create_network(
+                Quest::new_synced("Create network".to_string()),
+                NetworkConfig {
+                    kind: NetworkKind::MACVLAN,
+                    name: "flecs-macvlan".to_string(),
+                    cidr_subnet: Some(Ipv4Net::from_str("192.168.2.0/24").unwrap()),
+                    gateway: Some(Ipv4Addr::new(192, 168, 2, 1)),
+                    parent_adapter: Some("eth0".to_string()),
+                    options: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(network.guess_network_kind(), NetworkKind::MACVLAN);
+        assert_eq!(network.parent_network().as_deref(), Some("eth0"));
+    }
+
+    #[tokio::test]
+    async fn default_network_missing() {
+        let path = testdir!();
+        let stub = StubPodman::spawn(path.join("podman.sock"), []);
+        let lore = Arc::new(lore::test_lore(path, &MockVarReader::new()));
+        assert!(matches!(
+            test_deployment(&stub).default_network(lore).await,
+            Err(InspectNetworkError::NetworkDoesNotExist(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn instance_status_running() {
+        let stub = StubPodman::spawn(
+            testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/containers/flecs-00000010/json"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({"Id": "6f2a", "State": {"Status": "running"}}),
+                ),
+            )],
+        );
+        assert_eq!(
+            test_deployment(&stub)
+                .instance_status(InstanceId::new(0x10))
+                .await
+                .unwrap(),
+            InstanceStatus::Running
+        );
+    }
+
+    #[tokio::test]
+    async fn instance_status_no_container() {
+        let stub = StubPodman::spawn(testdir!().join("podman.sock"), []);
+        assert_eq!(
+            test_deployment(&stub)
+                .instance_status(InstanceId::new(0x10))
+                .await
+                .unwrap(),
+            InstanceStatus::Stopped
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_in_instance_ok() {
+        let stub = StubPodman::spawn(
+            testdir!().join("podman.sock"),
+            [
+                (
+                    (Method::POST, "/containers/flecs-00000010/exec"),
+                    StubResponse::json(StatusCode::CREATED, serde_json::json!({"Id": "e1"})),
+                ),
+                (
+                    (Method::POST, "/exec/e1/start"),
+                    StubResponse::empty(StatusCode::OK),
+                ),
+                (
+                    (Method::GET, "/exec/e1/json"),
+                    StubResponse::json(StatusCode::OK, serde_json::json!({"ExitCode": 0})),
+                ),
+            ],
+        );
+        assert_eq!(
+            test_deployment(&stub)
+                .exec_in_instance(
+                    InstanceId::new(0x10),
+                    vec!["true".to_string()],
+                    Duration::from_secs(10)
+                )
+                .await
+                .unwrap(),
+            ExecOutput {
+                exit_code: Some(0),
+                ..ExecOutput::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_tty_in_instance_unsupported() {
+        let stub = StubPodman::spawn(testdir!().join("podman.sock"), []);
+        assert!(
+            test_deployment(&stub)
+                .exec_tty_in_instance(InstanceId::new(0x10), vec!["sh".to_string()])
+                .await
+                .is_err()
+        );
+        assert!(stub.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn instance_default_address_ok() {
+        let path = testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::GET, "/containers/flecs-00000010/json"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({
+                        "Id": "6f2a",
+                        "NetworkSettings": {"Networks": {"flecs": {"IPAddress": "172.21.0.3"}}}
+                    }),
+                ),
+            )],
+        );
+        let lore = Arc::new(lore::test_lore(path, &MockVarReader::new()));
+        assert_eq!(
+            test_deployment(&stub)
+                .instance_default_address(lore, InstanceId::new(0x10))
+                .await
+                .unwrap(),
+            Some(IpAddr::V4(Ipv4Addr::new(172, 21, 0, 3)))
+        );
+    }
+
+    #[tokio::test]
+    async fn start_instance_ok() {
+        let path = testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [
+                (
+                    (Method::DELETE, "/containers/flecs-00000010"),
+                    StubResponse::empty(StatusCode::NOT_FOUND),
+                ),
+                (
+                    (Method::POST, "/containers/create"),
+                    StubResponse::json(StatusCode::CREATED, serde_json::json!({"Id": "6f2a"})),
+                ),
+                (
+                    (Method::POST, "/containers/flecs-00000010/start"),
+                    StubResponse::empty(StatusCode::NO_CONTENT),
+                ),
+            ],
+        );
+        let lore = Arc::new(lore::test_lore(path, &MockVarReader::new()));
+        let config = Config {
+            image: Some("alpine:3.21".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(
+            test_deployment(&stub)
+                .start_instance(lore, config, Some(InstanceId::new(0x10)), &[])
+                .await
+                .unwrap(),
+            InstanceId::new(0x10)
+        );
+        let create = stub
+            .requests()
+            .into_iter()
+            .find(|request| request.path.ends_with("/containers/create"))
+            .unwrap();
+        assert_eq!(
+            create.json_body(),
+            serde_json::json!({"name": "flecs-00000010", "image": "alpine:3.21"})
+        );
+    }
+
+    #[tokio::test]
+    async fn start_instance_start_fails() {
+        let path = testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [
+                (
+                    (Method::POST, "/containers/create"),
+                    StubResponse::json(StatusCode::CREATED, serde_json::json!({"Id": "6f2a"})),
+                ),
+                (
+                    (Method::POST, "/containers/flecs-00000010/start"),
+                    StubResponse::json(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        serde_json::json!({"message": "port already in use"}),
+                    ),
+                ),
+            ],
+        );
+        let lore = Arc::new(lore::test_lore(path, &MockVarReader::new()));
+        let config = Config {
+            image: Some("alpine:3.21".to_string()),
+            ..Config::default()
+        };
+        assert!(
+            test_deployment(&stub)
+                .start_instance(lore, config, Some(InstanceId::new(0x10)), &[])
+                .await
+                .is_err()
+        );
+        assert!(
+            stub.requests()
+                .iter()
+                .any(|request| request.method == Method::DELETE
+                    && request.path.ends_with("/containers/6f2a"))
+        );
+    }
+
+    #[tokio::test]
+    async fn is_app_installed_no_image() {
+        let stub = StubPodman::spawn(testdir!().join("podman.sock"), []);
+        assert!(
+            !test_deployment(&stub)
+                .is_app_installed(
+                    Quest::new_synced("Test".to_string()),
+                    create_test_manifest(None)
+                )
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn import_volume_from_directory() {
+        let path = testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [
+                (
+                    (Method::POST, "/volumes/create"),
+                    StubResponse::json(StatusCode::CREATED, serde_json::json!({"Name": "data"})),
+                ),
+                (
+                    (Method::POST, "/volumes/data/import"),
+                    StubResponse::empty(StatusCode::NO_CONTENT),
+                ),
+            ],
+        );
+        let src = path.join("volumes");
+        std::fs::create_dir_all(src.join("data")).unwrap();
+        std::fs::write(src.join("data").join("file.txt"), "content").unwrap();
+        let volume_id = test_deployment(&stub)
+            .import_volume(
+                Quest::new_synced("Import".to_string()),
+                &src,
+                Path::new("/data"),
+                "data",
+                "alpine:3.21",
+            )
+            .await
+            .unwrap();
+        assert_eq!(volume_id, "data");
+        let requests = stub.requests();
+        let import = requests
+            .iter()
+            .find(|request| request.path.ends_with("/volumes/data/import"))
+            .unwrap();
+        assert!(!import.body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn import_volume_missing_source() {
+        let path = testdir!();
+        let stub = StubPodman::spawn(path.join("podman.sock"), []);
+        assert!(
+            test_deployment(&stub)
+                .import_volume(
+                    Quest::new_synced("Import".to_string()),
+                    &path,
+                    Path::new("/data"),
+                    "data",
+                    "alpine:3.21",
+                )
+                .await
+                .is_err()
+        );
+        assert!(stub.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn export_volume_as_archive() {
+        let path = testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::GET, "/volumes/data/export"),
+                StubResponse {
+                    status: StatusCode::OK,
+                    body: b"volume archive".to_vec(),
+                },
+            )],
+        );
+        test_deployment(&stub)
+            .export_volume(
+                Quest::new_synced("Export".to_string()),
+                "data".to_string(),
+                &path,
+                Path::new("/data"),
+                "alpine:3.21",
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read(path.join("data.tar")).unwrap(),
+            b"volume archive"
+        );
+    }
+
+    #[tokio::test]
+    async fn disk_usage_merges_tags() {
+        let image = |repository: &str, tag: &str| {
+            serde_json::json!({
+                "Repository": repository,
+                "Tag": tag,
+                "ImageID": "9a0c4d0b2f5e",
+                "Size": 1000,
+                "Containers": 1
+            })
+        };
+        let stub = StubPodman::spawn(
+            testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/system/df"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({
+                        "Images": [
+                            image("flecs.azurecr.io/tech.flecs.mqtt-bridge", "3.0.0"),
+                            image("flecs.azurecr.io/tech.flecs.mqtt-bridge", "latest"),
+                            {"Repository": "<none>", "Tag": "<none>", "ImageID": "ffe0a1b2c3d4", "Size": 20}
+                        ],
+                        "Volumes": [{"VolumeName": "flecs-01234567-data", "Links": 0, "Size": 4096}]
+                    }),
+                ),
+            )],
+        );
+        let usage = test_deployment(&stub).disk_usage().await.unwrap();
+        assert_eq!(
+            usage.images,
+            vec![
+                ImageUsage {
+                    id: "9a0c4d0b2f5e".to_string(),
+                    tags: vec![
+                        "flecs.azurecr.io/tech.flecs.mqtt-bridge:3.0.0".to_string(),
+                        "flecs.azurecr.io/tech.flecs.mqtt-bridge:latest".to_string(),
+                    ],
+                    size: 1000,
+                    containers: 1,
+                },
+                ImageUsage {
+                    id: "ffe0a1b2c3d4".to_string(),
+                    tags: Vec::new(),
+                    size: 20,
+                    containers: 0,
+                },
+            ]
+        );
+        assert_eq!(
+            usage.volumes,
+            vec![VolumeUsage {
+                name: "flecs-01234567-data".to_string(),
+                size: Some(4096),
+                containers: 0,
+            }]
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Translation of docker container configs into the `SpecGenerator` expected by libpod when
+//! creating containers. Instances build their container config independent of the deployment,
+//! so the podman deployment has to convert it before passing it on.
+use bollard::container::Config;
+use bollard::models::{DeviceMapping, HostConfig, Mount, MountTypeEnum};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+fn split_port_key(key: &str) -> anyhow::Result<(u16, &str)> {
+    let (port, protocol) = key.split_once('/').unwrap_or((key, "tcp"));
+    Ok((port.parse()?, protocol))
+}
+
+fn environment(env: Vec<String>) -> Map<String, Value> {
+    env.into_iter()
+        .map(|variable| match variable.split_once('=') {
+            Some((name, value)) => (name.to_string(), Value::from(value)),
+            None => (variable, Value::from("")),
+        })
+        .collect()
+}
+
+fn port_mappings(
+    port_bindings: HashMap<String, Option<Vec<bollard::models::PortBinding>>>,
+) -> anyhow::Result<Vec<Value>> {
+    let mut mappings = Vec::new();
+    for (key, bindings) in port_bindings {
+        let (container_port, protocol) = split_port_key(&key)?;
+        for binding in bindings.unwrap_or_default() {
+            let mut mapping = json!({
+                "container_port": container_port,
+                "protocol": protocol,
+            });
+            if let Some(host_port) = binding.host_port.filter(|port| !port.is_empty()) {
+                mapping["host_port"] = Value::from(host_port.parse::<u16>()?);
+            }
+            if let Some(host_ip) = binding.host_ip.filter(|ip| !ip.is_empty()) {
+                mapping["host_ip"] = Value::from(host_ip);
+            }
+            mappings.push(mapping);
+        }
+    }
+    mappings.sort_by_key(|mapping| mapping["container_port"].as_u64());
+    Ok(mappings)
+}
+
+fn mounts(mounts: Vec<Mount>) -> (Vec<Value>, Vec<Value>) {
+    let mut binds = Vec::new();
+    let mut volumes = Vec::new();
+    for mount in mounts {
+        let read_only = mount.read_only.unwrap_or_default();
+        match mount.typ {
+            Some(MountTypeEnum::VOLUME) => {
+                let mut options = Vec::new();
+                if read_only {
+                    options.push("ro");
+                }
+                volumes.push(json!({
+                    "Name": mount.source.unwrap_or_default(),
+                    "Dest": mount.target.unwrap_or_default(),
+                    "Options": options,
+                }));
+            }
+            typ => {
+                let typ = match typ {
+                    Some(MountTypeEnum::TMPFS) => "tmpfs",
+                    _ => "bind",
+                };
+                let mut options = vec!["rbind"];
+                if read_only {
+                    options.push("ro");
+                }
+                binds.push(json!({
+                    "destination": mount.target.unwrap_or_default(),
+                    "source": mount.source.unwrap_or_default(),
+                    "type": typ,
+                    "options": options,
+                }));
+            }
+        }
+    }
+    (binds, volumes)
+}
+
+fn devices(devices: Vec<DeviceMapping>) -> Vec<Value> {
+    devices
+        .into_iter()
+        .filter_map(|device| {
+            let host = device.path_on_host?;
+            let container = device.path_in_container.unwrap_or_else(|| host.clone());
+            let path = match device.cgroup_permissions {
+                Some(permissions) => format!("{host}:{container}:{permissions}"),
+                None => format!("{host}:{container}"),
+            };
+            Some(json!({ "path": path }))
+        })
+        .collect()
+}
+
+/// Converts the container config into a libpod `SpecGenerator`. Networks are attached with
+/// their static addresses and aliases, if the network is disabled the container is created
+/// without network namespace.
+pub fn container_spec(config: Config<String>, name: Option<&str>) -> anyhow::Result<Value> {
+    let mut spec = Map::new();
+    if let Some(name) = name {
+        spec.insert("name".to_string(), Value::from(name));
+    }
+    let image = config
+        .image
+        .ok_or_else(|| anyhow::anyhow!("No image specified"))?;
+    spec.insert("image".to_string(), Value::from(image));
+    if let Some(hostname) = config.hostname {
+        spec.insert("hostname".to_string(), Value::from(hostname));
+    }
+    if let Some(env) = config.env {
+        spec.insert("env".to_string(), Value::Object(environment(env)));
+    }
+    if let Some(labels) = config.labels {
+        spec.insert("labels".to_string(), json!(labels));
+    }
+    if let Some(cmd) = config.cmd {
+        spec.insert("command".to_string(), json!(cmd));
+    }
+    if let Some(exposed_ports) = config.exposed_ports {
+        let mut expose = Map::new();
+        for key in exposed_ports.keys() {
+            let (port, protocol) = split_port_key(key)?;
+            expose.insert(port.to_string(), Value::from(protocol));
+        }
+        spec.insert("expose".to_string(), Value::Object(expose));
+    }
+    let HostConfig {
+        port_bindings,
+        mounts: host_mounts,
+        cap_add,
+        devices: host_devices,
+        extra_hosts,
+        ..
+    } = config.host_config.unwrap_or_default();
+    if let Some(port_bindings) = port_bindings {
+        spec.insert(
+            "portmappings".to_string(),
+            Value::from(port_mappings(port_bindings)?),
+        );
+    }
+    if let Some(host_mounts) = host_mounts {
+        let (binds, volumes) = mounts(host_mounts);
+        spec.insert("mounts".to_string(), Value::from(binds));
+        spec.insert("volumes".to_string(), Value::from(volumes));
+    }
+    if let Some(cap_add) = cap_add {
+        spec.insert("cap_add".to_string(), json!(cap_add));
+    }
+    if let Some(host_devices) = host_devices {
+        spec.insert("devices".to_string(), Value::from(devices(host_devices)));
+    }
+    if let Some(extra_hosts) = extra_hosts {
+        spec.insert("hostadd".to_string(), json!(extra_hosts));
+    }
+    if config.network_disabled == Some(true) {
+        spec.insert("netns".to_string(), json!({"nsmode": "none"}));
+    } else if let Some(networking_config) = config.networking_config {
+        let networks: Map<String, Value> = networking_config
+            .endpoints_config
+            .into_iter()
+            .map(|(network, endpoint)| {
+                let mut options = Map::new();
+                let address = endpoint
+                    .ipam_config
+                    .and_then(|ipam| ipam.ipv4_address.or(ipam.ipv6_address))
+                    .or(endpoint.ip_address)
+                    .filter(|address| !address.is_empty());
+                if let Some(address) = address {
+                    options.insert("static_ips".to_string(), json!([address]));
+                }
+                if let Some(aliases) = endpoint.aliases {
+                    options.insert("aliases".to_string(), json!(aliases));
+                }
+                (network, Value::Object(options))
+            })
+            .collect();
+        if !networks.is_empty() {
+            spec.insert("netns".to_string(), json!({"nsmode": "bridge"}));
+            spec.insert("networks".to_string(), Value::Object(networks));
+        }
+    }
+    Ok(Value::Object(spec))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::container::NetworkingConfig;
+    use bollard::models::{EndpointIpamConfig, EndpointSettings, PortBinding};
+
+    #[test]
+    fn container_spec_full() {
+        let config = Config {
+            image: Some("flecs.azurecr.io/tech.flecs.test:1.2.3".to_string()),
+            hostname: Some("flecs-test".to_string()),
+            env: Some(vec!["VAR_1=value=1".to_string(), "VAR_2".to_string()]),
+            labels: Some(HashMap::from([(
+                "tech.flecs".to_string(),
+                "test".to_string(),
+            )])),
+            cmd: Some(vec!["--verbose".to_string()]),
+            exposed_ports: Some(HashMap::from([("80/tcp".to_string(), HashMap::new())])),
+            host_config: Some(HostConfig {
+                port_bindings: Some(HashMap::from([(
+                    "80/tcp".to_string(),
+                    Some(vec![PortBinding {
+                        host_ip: None,
+                        host_port: Some("8080".to_string()),
+                    }]),
+                )])),
+                mounts: Some(vec![
+                    Mount {
+                        typ: Some(MountTypeEnum::BIND),
+                        source: Some("/etc/hosts".to_string()),
+                        target: Some("/etc/host_hosts".to_string()),
+                        ..Mount::default()
+                    },
+                    Mount {
+                        typ: Some(MountTypeEnum::VOLUME),
+                        source: Some("Instance-1234abcd-data".to_string()),
+                        target: Some("/data".to_string()),
+                        ..Mount::default()
+                    },
+                ]),
+                cap_add: Some(vec!["NET_ADMIN".to_string()]),
+                devices: Some(vec![DeviceMapping {
+                    path_on_host: Some("/dev/ttyUSB0".to_string()),
+                    path_in_container: Some("/dev/ttyUSB0".to_string()),
+                    cgroup_permissions: Some("rwm".to_string()),
+                }]),
+                extra_hosts: Some(vec!["flecs-core:172.21.0.1".to_string()]),
+                ..HostConfig::default()
+            }),
+            networking_config: Some(NetworkingConfig {
+                endpoints_config: HashMap::from([(
+                    "flecs".to_string(),
+                    EndpointSettings {
+                        ip_address: Some("172.21.0.5".to_string()),
+                        ipam_config: Some(EndpointIpamConfig {
+                            ipv4_address: Some("172.21.0.5".to_string()),
+                            ..EndpointIpamConfig::default()
+                        }),
+                        aliases: Some(vec!["test".to_string()]),
+                        ..EndpointSettings::default()
+                    },
+                )]),
+            }),
+            ..Config::default()
+        };
+        assert_eq!(
+            container_spec(config, Some("flecs-1234abcd")).unwrap(),
+            json!({
+                "name": "flecs-1234abcd",
+                "image": "flecs.azurecr.io/tech.flecs.test:1.2.3",
+                "hostname": "flecs-test",
+                "env": {"VAR_1": "value=1", "VAR_2": ""},
+                "labels": {"tech.flecs": "test"},
+                "command": ["--verbose"],
+                "expose": {"80": "tcp"},
+                "portmappings": [{"container_port": 80, "host_port": 8080, "protocol": "tcp"}],
+                "mounts": [{
+                    "destination": "/etc/host_hosts",
+                    "source": "/etc/hosts",
+                    "type": "bind",
+                    "options": ["rbind"]
+                }],
+                "volumes": [{"Name": "Instance-1234abcd-data", "Dest": "/data", "Options": []}],
+                "cap_add": ["NET_ADMIN"],
+                "devices": [{"path": "/dev/ttyUSB0:/dev/ttyUSB0:rwm"}],
+                "hostadd": ["flecs-core:172.21.0.1"],
+                "netns": {"nsmode": "bridge"},
+                "networks": {"flecs": {"static_ips": ["172.21.0.5"], "aliases": ["test"]}}
+            })
+        );
+    }
+
+    #[test]
+    fn container_spec_network_disabled() {
+        let config = Config {
+            image: Some("alpine:3.21".to_string()),
+            network_disabled: Some(true),
+            ..Config::default()
+        };
+        assert_eq!(
+            container_spec(config, None).unwrap(),
+            json!({
+                "image": "alpine:3.21",
+                "netns": {"nsmode": "none"},
+            })
+        );
+    }
+
+    #[test]
+    fn container_spec_no_image() {
+        assert!(container_spec(Config::default(), None).is_err());
+    }
+
+    #[test]
+    fn container_spec_invalid_port() {
+        let config = Config {
+            image: Some("alpine:3.21".to_string()),
+            exposed_ports: Some(HashMap::from([("http/tcp".to_string(), HashMap::new())])),
+            ..Config::default()
+        };
+        assert!(container_spec(config, None).is_err());
+    }
+
+    #[test]
+    fn port_mappings_without_host_port() {
+        assert_eq!(
+            port_mappings(HashMap::from([(
+                "53/udp".to_string(),
+                Some(vec![PortBinding {
+                    host_ip: Some("127.0.0.1".to_string()),
+                    host_port: None,
+                }]),
+            )]))
+            .unwrap(),
+            vec![json!({"container_port": 53, "protocol": "udp", "host_ip": "127.0.0.1"})]
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::jeweler::gem::instance::StoredProviderReference;
+use crate::jeweler::gem::instance::provider_connection::ProviderConnections;
 use crate::jeweler::gem::manifest::DependencyKey;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -7,4 +8,7 @@ use std::collections::HashMap;
 pub struct InstanceConfig {
     #[serde(skip_serializing_if = "HashMap::is_empty", default)]
     pub dependencies: HashMap<DependencyKey, StoredProviderReference>,
+    /// Resolved connection details of the providers in [Self::dependencies]
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub provider_connections: ProviderConnections,
 }
@@ -4,6 +4,8 @@ use crate::jeweler::deployment::DeploymentId;
 use crate::jeweler::gem::deployment::Deployment;
 use crate::jeweler::gem::deployment::compose::ComposeDeployment;
 use crate::jeweler::gem::instance::compose::config::InstanceConfig;
+use crate::jeweler::gem::instance::provider_connection;
+use crate::jeweler::gem::instance::provider_connection::ProviderConnections;
 use crate::jeweler::gem::instance::status::InstanceStatus;
 use crate::jeweler::gem::manifest::multi::AppManifestMulti;
 use crate::jeweler::gem::manifest::{AppManifest, DependencyKey, multi};
@@ -169,6 +171,18 @@ impl InstanceCommon for ComposeInstance {
     ) -> Option<StoredProviderReference> {
         self.config.dependencies.insert(feature, provider)
     }
+
+    fn provider_connections(&self) -> &ProviderConnections {
+        &self.config.provider_connections
+    }
+
+    fn replace_provider_connections(
+        &mut self,
+        mut connections: ProviderConnections,
+    ) -> ProviderConnections {
+        swap(&mut connections, &mut self.config.provider_connections);
+        connections
+    }
 }
 
 impl ComposeInstance {
@@ -180,6 +194,25 @@ impl ComposeInstance {
         self.lore().instance_workdir_path(&self.id.to_string())
     }
 
+    /// The `.env` file in the workdir is used by compose to interpolate variables, it contains
+    /// the connection details of the providers of this instance
+    async fn write_provider_environment(&self) -> anyhow::Result<()> {
+        let path = self.workdir().join(".env");
+        if self.config.provider_connections.is_empty() {
+            return match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+        tokio::fs::create_dir_all(self.workdir()).await?;
+        tokio::fs::write(
+            path,
+            provider_connection::dotenv(&self.config.provider_connections),
+        )
+        .await?;
+        Ok(())
+    }
+
     fn aggregate_status(status_vec: Vec<InstanceStatus>) -> InstanceStatus {
         if status_vec.is_empty() {
             return InstanceStatus::Stopped;
@@ -331,6 +364,7 @@ impl ComposeInstance {
         if self.status().await? == InstanceStatus::Running {
             return Ok(());
         }
+        self.write_provider_environment().await?;
         self.deployment
             .start_instance(&self.manifest, &self.workdir())
             .await?;
@@ -343,6 +377,7 @@ impl ComposeInstance {
         {
             return Ok(());
         }
+        self.write_provider_environment().await?;
         self.deployment
             .start_instance(&self.manifest, &self.workdir())
             .await?;
@@ -1,8 +1,9 @@
 use crate::forge::vec::VecExtension;
 use crate::jeweler::gem::instance::StoredProviderReference;
+use crate::jeweler::gem::instance::provider_connection::ProviderConnections;
 use crate::jeweler::gem::manifest::DependencyKey;
 use crate::jeweler::gem::manifest::single::{
-    EnvironmentVariable, PortMapping, PortRange, VolumeMount,
+    EditorRoles, EnvironmentVariable, PortMapping, PortRange, VolumeMount,
 };
 use crate::jeweler::network::NetworkId;
 use crate::jeweler::volume::VolumeId;
@@ -39,6 +40,75 @@ impl InstancePortMapping {
         self.sctp.clear();
     }
 
+    /// Returns a copy of the port mapping where every host port (range) is moved to the next host
+    /// port (range) of the same size that does not overlap with any of the `taken` port mappings
+    /// of the same protocol. Container ports are kept as they are.
+    pub fn with_fresh_host_ports<'a>(
+        &self,
+        taken: impl IntoIterator<Item = &'a InstancePortMapping>,
+    ) -> anyhow::Result<Self> {
+        let mut taken_tcp = Vec::new();
+        let mut taken_udp = Vec::new();
+        let mut taken_sctp = Vec::new();
+        for port_mapping in taken {
+            taken_tcp.extend(port_mapping.tcp.iter().cloned());
+            taken_udp.extend(port_mapping.udp.iter().cloned());
+            taken_sctp.extend(port_mapping.sctp.iter().cloned());
+        }
+        Ok(Self {
+            tcp: Self::fresh_host_ports_vec(&self.tcp, taken_tcp)?,
+            udp: Self::fresh_host_ports_vec(&self.udp, taken_udp)?,
+            sctp: Self::fresh_host_ports_vec(&self.sctp, taken_sctp)?,
+        })
+    }
+
+    fn fresh_host_ports_vec(
+        port_mappings: &[PortMapping],
+        mut taken: Vec<PortMapping>,
+    ) -> anyhow::Result<Vec<PortMapping>> {
+        let mut fresh_port_mappings = Vec::with_capacity(port_mappings.len());
+        for port_mapping in port_mappings {
+            let fresh_port_mapping =
+                Self::next_free_host_ports(port_mapping, &taken).ok_or_else(|| {
+                    anyhow::anyhow!("No free host ports available for {port_mapping}")
+                })?;
+            taken.push(fresh_port_mapping.clone());
+            fresh_port_mappings.push(fresh_port_mapping);
+        }
+        Ok(fresh_port_mappings)
+    }
+
+    /// Searches upwards from the current host port and wraps around to the first non privileged
+    /// port
+    fn next_free_host_ports(
+        port_mapping: &PortMapping,
+        taken: &[PortMapping],
+    ) -> Option<PortMapping> {
+        let (from, to) = match port_mapping {
+            PortMapping::Single(host_port, container_port) => (
+                PortRange::new(*host_port..=*host_port),
+                PortRange::new(*container_port..=*container_port),
+            ),
+            PortMapping::Range { from, to } => (*from, *to),
+        };
+        let start = *from.range().start();
+        let offset = *from.range().end() - start;
+        (start.saturating_add(1)..=u16::MAX - offset)
+            .chain(1024..start)
+            .map(|candidate| {
+                PortMapping::Range {
+                    from: PortRange::new(candidate..=candidate + offset),
+                    to,
+                }
+                .normalize()
+            })
+            .find(|candidate| {
+                !taken
+                    .iter()
+                    .any(|taken| taken.do_host_ports_overlap(candidate))
+            })
+    }
+
     /// Returns Ok(true) if an element was updated, Ok(false) if an element was added
     fn update_port_mapping_vec(
         existing_port_mappings: &mut Vec<PortMapping>,
@@ -259,11 +329,53 @@ pub struct InstanceConfig {
     pub connected_networks: HashMap<NetworkId, IpAddr>,
     #[serde(skip_serializing_if = "HashMap::is_empty", default)]
     pub usb_devices: HashMap<String, UsbPathConfig>,
+    /// Dedicated host names of editors by editor port
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "deserialize_key_map",
+        default
+    )]
+    pub editor_hosts: HashMap<u16, EditorHost>,
+    /// Roles required to access editors by editor port, overriding the roles declared in the
+    /// manifest
+    #[serde(
+        skip_serializing_if = "HashMap::is_empty",
+        deserialize_with = "deserialize_key_map",
+        default
+    )]
+    pub editor_roles: HashMap<u16, EditorRoles>,
     /// Mapping of editor port -> open port in floxy
     #[serde(skip)]
     pub mapped_editor_ports: HashMap<u16, u16>,
     #[serde(skip_serializing_if = "HashMap::is_empty", default)]
     pub dependencies: HashMap<DependencyKey, StoredProviderReference>,
+    /// Resolved connection details of the providers in [Self::dependencies]
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub provider_connections: ProviderConnections,
+}
+
+/// Dedicated host name an editor is served under by floxy
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct EditorHost {
+    pub host_name: String,
+    /// TLS is terminated by floxy if set, the certificate is stored with the instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<EditorTls>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct EditorTls {
+    /// The certificate was generated by flecs instead of being uploaded
+    pub self_signed: bool,
+}
+
+impl EditorHost {
+    pub fn url(&self) -> String {
+        match self.tls {
+            Some(_) => format!("https://{}/", self.host_name),
+            None => format!("http://{}/", self.host_name),
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -398,6 +510,93 @@ pub(crate) mod tests {
     use std::net::{Ipv4Addr, Ipv6Addr};
     use std::path::{Path, PathBuf};
 
+    #[test]
+    fn with_fresh_host_ports_single() {
+        let port_mapping = InstancePortMapping {
+            tcp: vec![
+                PortMapping::Single(8080, 80),
+                PortMapping::Single(8443, 443),
+            ],
+            udp: vec![PortMapping::Single(8080, 80)],
+            sctp: vec![],
+        };
+        let other = InstancePortMapping {
+            tcp: vec![PortMapping::Single(8081, 80)],
+            udp: vec![],
+            sctp: vec![],
+        };
+        assert_eq!(
+            port_mapping
+                .with_fresh_host_ports([&port_mapping, &other])
+                .unwrap(),
+            InstancePortMapping {
+                tcp: vec![
+                    PortMapping::Single(8082, 80),
+                    PortMapping::Single(8444, 443)
+                ],
+                udp: vec![PortMapping::Single(8081, 80)],
+                sctp: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn with_fresh_host_ports_range() {
+        let port_mapping = InstancePortMapping {
+            tcp: vec![PortMapping::Range {
+                from: PortRange::new(5000..=5009),
+                to: PortRange::new(6000..=6009),
+            }],
+            udp: vec![],
+            sctp: vec![],
+        };
+        let other = InstancePortMapping {
+            tcp: vec![PortMapping::Single(5015, 80)],
+            udp: vec![],
+            sctp: vec![],
+        };
+        assert_eq!(
+            port_mapping
+                .with_fresh_host_ports([&port_mapping, &other])
+                .unwrap(),
+            InstancePortMapping {
+                tcp: vec![PortMapping::Range {
+                    from: PortRange::new(5016..=5025),
+                    to: PortRange::new(6000..=6009),
+                }],
+                udp: vec![],
+                sctp: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn with_fresh_host_ports_wrap_around() {
+        let port_mapping = InstancePortMapping {
+            tcp: vec![PortMapping::Single(u16::MAX, 80)],
+            udp: vec![],
+            sctp: vec![],
+        };
+        assert_eq!(
+            port_mapping.with_fresh_host_ports([&port_mapping]).unwrap(),
+            InstancePortMapping {
+                tcp: vec![PortMapping::Single(1024, 80)],
+                udp: vec![],
+                sctp: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn with_fresh_host_ports_empty() {
+        assert_eq!(
+            InstancePortMapping::default()
+                .with_fresh_host_ports([])
+                .unwrap(),
+            InstancePortMapping::default()
+        );
+    }
+
     #[test]
     fn new_port_bindings_test() {
         assert_eq!(
@@ -0,0 +1,430 @@
+use super::DockerInstance;
+use crate::jeweler::gem::manifest::single::{ConfigFile, VolumeMount};
+use crate::quest::Quest;
+use crate::relic::async_flecstract::EntryHeader;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::warn;
+use utoipa::ToSchema;
+
+/// File or directory inside an instance, the path is relative to the listed directory
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEntry {
+    #[schema(value_type = String)]
+    pub path: PathBuf,
+    /// Size in bytes, always 0 for directories
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFileLocation {
+    pub name: String,
+    #[schema(value_type = String)]
+    pub container_path: PathBuf,
+    pub read_only: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeLocation {
+    pub name: String,
+    #[schema(value_type = String)]
+    pub container_path: PathBuf,
+}
+
+/// Config files and volumes of an instance which can be browsed
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FileLocations {
+    pub config_files: Vec<ConfigFileLocation>,
+    pub volumes: Vec<VolumeLocation>,
+}
+
+impl DockerInstance {
+    pub fn file_locations(&self) -> FileLocations {
+        let config_files = self
+            .manifest
+            .config_files
+            .iter()
+            .map(|config_file| ConfigFileLocation {
+                name: config_file.host_file_name.clone(),
+                container_path: config_file.container_file_path.clone(),
+                read_only: config_file.read_only,
+            })
+            .collect();
+        let mut volumes: Vec<_> = self
+            .config
+            .volume_mounts
+            .values()
+            .map(|volume| VolumeLocation {
+                name: volume.name.clone(),
+                container_path: volume.container_path.clone(),
+            })
+            .collect();
+        volumes.sort_by(|a, b| a.name.cmp(&b.name));
+        FileLocations {
+            config_files,
+            volumes,
+        }
+    }
+
+    pub fn config_file(&self, name: &str) -> Option<&ConfigFile> {
+        self.manifest
+            .config_files
+            .iter()
+            .find(|config_file| config_file.host_file_name == name)
+    }
+
+    pub fn volume_mount(&self, name: &str) -> Option<&VolumeMount> {
+        self.config
+            .volume_mounts
+            .values()
+            .find(|volume| volume.name == name)
+    }
+
+    /// Runs `f` with a new directory in the working directory of the instance which is removed
+    /// afterward
+    async fn with_transfer_dir<F, Fut, T>(&self, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(PathBuf) -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let path = self
+            .lore()
+            .instance_workdir_path(&self.id.to_string())
+            .join(format!("transfer-{:08x}", rand::random::<u32>()));
+        fs::create_dir_all(&path).await?;
+        let result = f(path.clone()).await;
+        if let Err(e) = fs::remove_dir_all(&path).await {
+            warn!("Could not remove transfer directory {path:?}: {e}");
+        }
+        result
+    }
+
+    /// Config files are copied into the container on start and back to the host on stop. The
+    /// config file is therefore read from the container if the instance is running and from the
+    /// host otherwise.
+    pub async fn read_config_file(&self, config_file: &ConfigFile) -> anyhow::Result<Vec<u8>> {
+        if !self.is_running().await? {
+            return Ok(fs::read(self.config_path().join(&config_file.host_file_name)).await?);
+        }
+        self.with_transfer_dir(|dir| async move {
+            let dst = dir.join(&config_file.host_file_name);
+            self.copy_from(
+                Quest::new_synced(format!(
+                    "Read config file {} of instance {}",
+                    config_file.host_file_name, self.id
+                )),
+                &config_file.container_file_path,
+                &dst,
+                true,
+            )
+            .await?;
+            Ok(fs::read(dst).await?)
+        })
+        .await
+    }
+
+    /// Writes the config file into the container if the instance is running and to the host
+    /// otherwise, see [DockerInstance::read_config_file]
+    pub async fn write_config_file(
+        &self,
+        config_file: &ConfigFile,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        if !self.is_running().await? {
+            let config_path = self.config_path();
+            fs::create_dir_all(&config_path).await?;
+            fs::write(config_path.join(&config_file.host_file_name), data).await?;
+            return Ok(());
+        }
+        self.with_transfer_dir(|dir| async move {
+            let src = dir.join(&config_file.host_file_name);
+            fs::write(&src, data).await?;
+            self.copy_to(
+                Quest::new_synced(format!(
+                    "Write config file {} of instance {}",
+                    config_file.host_file_name, self.id
+                )),
+                &src,
+                &config_file.container_file_path,
+                true,
+            )
+            .await
+        })
+        .await
+    }
+
+    /// Lists all files and directories below the given path in the container of the instance,
+    /// the content of the files is not copied
+    pub async fn list_files(&self, path: &Path) -> anyhow::Result<Vec<FileEntry>> {
+        if path.file_name().is_none() {
+            anyhow::bail!("Can not list files of {path:?}");
+        }
+        let headers = self.deployment.list_instance_files(self.id, path).await?;
+        Ok(file_entries(headers))
+    }
+
+    pub async fn read_file(&self, path: &Path) -> anyhow::Result<Vec<u8>> {
+        self.with_transfer_dir(|dir| async move {
+            let dst = dir.join("file");
+            self.copy_from(
+                Quest::new_synced(format!("Read {path:?} of instance {}", self.id)),
+                path,
+                &dst,
+                true,
+            )
+            .await?;
+            Ok(fs::read(dst).await?)
+        })
+        .await
+    }
+
+    /// Writes the file into the container of the instance, the parent directory has to exist
+    pub async fn write_file(&self, path: &Path, data: &[u8]) -> anyhow::Result<()> {
+        self.with_transfer_dir(|dir| async move {
+            let src = dir.join("file");
+            fs::write(&src, data).await?;
+            self.copy_to(
+                Quest::new_synced(format!("Write {path:?} of instance {}", self.id)),
+                &src,
+                path,
+                true,
+            )
+            .await
+        })
+        .await
+    }
+}
+
+/// Converts the entries of the archive of a path in a container, which are prefixed with the file
+/// name of the path, to entries relative to the path. A file is listed by its file name.
+fn file_entries(headers: Vec<EntryHeader>) -> Vec<FileEntry> {
+    let mut entries: Vec<_> = headers
+        .into_iter()
+        .filter_map(|header| {
+            let mut components = header.path.components();
+            components.next()?;
+            let relative = components.as_path();
+            if relative.as_os_str().is_empty() {
+                // The listed path itself
+                return (!header.is_dir).then_some(FileEntry {
+                    path: header.path.clone(),
+                    size: header.size,
+                    is_dir: false,
+                });
+            }
+            Some(FileEntry {
+                path: relative.to_path_buf(),
+                size: header.size,
+                is_dir: header.is_dir,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::deployment::docker::tests::MockedDockerDeployment;
+    use crate::jeweler::gem::instance::docker::tests::test_instance;
+    use crate::jeweler::gem::instance::status::InstanceStatus;
+    use crate::jeweler::gem::manifest::single::tests::create_test_manifest_full;
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use std::sync::Arc;
+    use testdir::testdir;
+
+    fn config_file() -> ConfigFile {
+        ConfigFile {
+            host_file_name: "default.conf".to_string(),
+            container_file_path: PathBuf::from("/etc/my-app/default.conf"),
+            read_only: false,
+        }
+    }
+
+    fn instance_with_status(
+        status: InstanceStatus,
+        deployment: MockedDockerDeployment,
+    ) -> (DockerInstance, PathBuf) {
+        let path = testdir!();
+        let lore = Arc::new(lore::test_lore(path.clone(), &MockVarReader::new()));
+        let mut deployment = deployment;
+        deployment
+            .expect_instance_status()
+            .returning(move |_| Ok(status));
+        let instance = test_instance(
+            10,
+            lore,
+            Arc::new(deployment),
+            create_test_manifest_full(None),
+        );
+        (instance, path)
+    }
+
+    #[test]
+    fn file_locations() {
+        let (instance, _path) =
+            instance_with_status(InstanceStatus::Stopped, MockedDockerDeployment::new());
+        let locations = instance.file_locations();
+        assert_eq!(locations.config_files.len(), 3);
+        assert_eq!(
+            locations.config_files[2],
+            ConfigFileLocation {
+                name: "default.conf".to_string(),
+                container_path: PathBuf::from("/etc/my-app/default.conf"),
+                read_only: true,
+            }
+        );
+        assert_eq!(
+            locations
+                .volumes
+                .iter()
+                .map(|volume| volume.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["10-Volume#1", "10-Volume#2", "10-Volume#3"]
+        );
+        assert!(instance.config_file("default.conf").is_some());
+        assert!(instance.config_file("unknown.conf").is_none());
+        assert_eq!(
+            instance.volume_mount("10-Volume#2").unwrap().container_path,
+            PathBuf::from("/volume2")
+        );
+        assert!(instance.volume_mount("Instance#10Volume#2").is_none());
+    }
+
+    #[tokio::test]
+    async fn config_file_stopped() {
+        let (instance, _path) =
+            instance_with_status(InstanceStatus::Stopped, MockedDockerDeployment::new());
+        instance
+            .write_config_file(&config_file(), b"key=value")
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read(instance.config_path().join("default.conf")).unwrap(),
+            b"key=value"
+        );
+        assert_eq!(
+            instance.read_config_file(&config_file()).await.unwrap(),
+            b"key=value"
+        );
+    }
+
+    #[tokio::test]
+    async fn write_config_file_running() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_copy_to_instance()
+            .once()
+            .withf(|_, id, src, dst, is_dst_file_path| {
+                id.value == 10
+                    && std::fs::read(src).unwrap() == b"key=value"
+                    && dst == Path::new("/etc/my-app/default.conf")
+                    && *is_dst_file_path
+            })
+            .returning(|_, _, _, _, _| Ok(()));
+        let (instance, _path) = instance_with_status(InstanceStatus::Running, deployment);
+        instance
+            .write_config_file(&config_file(), b"key=value")
+            .await
+            .unwrap();
+        assert!(!instance.config_path().join("default.conf").exists());
+    }
+
+    #[tokio::test]
+    async fn read_file_err() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_copy_from_instance()
+            .once()
+            .returning(|_, _, _, _, _| Err(anyhow::anyhow!("TestError")));
+        let (instance, _path) = instance_with_status(InstanceStatus::Running, deployment);
+        assert!(
+            instance
+                .read_file(Path::new("/volume1/data"))
+                .await
+                .is_err()
+        );
+        let workdir = instance
+            .lore()
+            .instance_workdir_path(&instance.id.to_string());
+        assert_eq!(std::fs::read_dir(workdir).unwrap().count(), 0);
+    }
+
+    fn header(path: &str, size: u64, is_dir: bool) -> EntryHeader {
+        EntryHeader {
+            path: PathBuf::from(path),
+            size,
+            is_dir,
+        }
+    }
+
+    #[tokio::test]
+    async fn list_files_dir() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_list_instance_files()
+            .once()
+            .withf(|id, path| id.value == 10 && path == Path::new("/volume1"))
+            .returning(|_, _| {
+                Ok(vec![
+                    header("volume1/", 0, true),
+                    header("volume1/sub/b.txt", 6, false),
+                    header("volume1/a.txt", 3, false),
+                    header("volume1/sub/", 0, true),
+                ])
+            });
+        let (instance, _path) = instance_with_status(InstanceStatus::Running, deployment);
+        assert_eq!(
+            instance.list_files(Path::new("/volume1")).await.unwrap(),
+            vec![
+                FileEntry {
+                    path: PathBuf::from("a.txt"),
+                    size: 3,
+                    is_dir: false,
+                },
+                FileEntry {
+                    path: PathBuf::from("sub"),
+                    size: 0,
+                    is_dir: true,
+                },
+                FileEntry {
+                    path: PathBuf::from("sub/b.txt"),
+                    size: 6,
+                    is_dir: false,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn list_files_err() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_list_instance_files()
+            .once()
+            .returning(|_, _| Err(anyhow::anyhow!("TestError")));
+        let (instance, _path) = instance_with_status(InstanceStatus::Running, deployment);
+        assert!(instance.list_files(Path::new("/volume1")).await.is_err());
+        assert!(instance.list_files(Path::new("/")).await.is_err());
+    }
+
+    #[test]
+    fn file_entries_file() {
+        assert_eq!(
+            file_entries(vec![header("a.txt", 3, false)]),
+            vec![FileEntry {
+                path: PathBuf::from("a.txt"),
+                size: 3,
+                is_dir: false,
+            }]
+        );
+    }
+}
@@ -1,15 +1,17 @@
 pub mod config;
+pub mod files;
 use super::{InstanceCommon, InstanceId, Logs, StoredProviderReference};
 use crate::forge::bollard::BollardNetworkExtension;
 use crate::forge::ipaddr::BitComplementExt;
 use crate::forge::time::SystemTimeExt;
 use crate::jeweler::deployment::DeploymentId;
-use crate::jeweler::gem::deployment::Deployment;
 use crate::jeweler::gem::deployment::docker::DockerDeployment;
-use crate::jeweler::gem::instance::docker::config::InstancePortMapping;
+use crate::jeweler::gem::instance::docker::config::{EditorHost, InstancePortMapping};
+use crate::jeweler::gem::instance::provider_connection;
+use crate::jeweler::gem::instance::provider_connection::ProviderConnections;
 use crate::jeweler::gem::instance::status::InstanceStatus;
 use crate::jeweler::gem::manifest::single::{
-    AppManifestSingle, BindMount, ConfigFile, Mount, VolumeMount,
+    AppManifestSingle, BindMount, ConfigFile, EditorRoles, Mount, VolumeMount,
 };
 use crate::jeweler::gem::manifest::{AppManifest, DependencyKey};
 use crate::jeweler::network::NetworkId;
@@ -19,8 +21,13 @@ use crate::jeweler::volume::VolumeId;
 use crate::lore::SPECIAL_CORE_GATEWAY_HOST;
 use crate::lore::{InstanceLore, Lore};
 use crate::quest::{Quest, SyncQuest};
+use crate::relic::device::certificate::{
+    delete_instance_tls_material, ensure_instance_tls_material,
+};
 use crate::relic::device::usb::UsbDeviceReader;
-use crate::relic::floxy::{AdditionalLocationInfo, Floxy};
+use crate::relic::floxy::{AdditionalLocationInfo, EditorHostInfo, Floxy};
+use crate::relic::network::Ipv4NetworkAccess;
+use crate::relic::tls::{CertificateError, CertifiedKey, is_valid_host_name};
 use crate::vault::pouch::AppKey;
 use crate::{legacy, lore, vault};
 use async_trait::async_trait;
@@ -33,8 +40,8 @@ use flecsd_axum_server::models;
 use flecsd_axum_server::models::{AppInstance, InstancesInstanceIdGet200Response};
 use futures_util::future::{BoxFuture, join_all};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::mem::swap;
 use std::net::{IpAddr, Ipv4Addr};
 use std::path::{Path, PathBuf};
@@ -247,6 +254,18 @@ impl InstanceCommon for DockerInstance {
     ) -> Option<StoredProviderReference> {
         self.config.dependencies.insert(key, provider)
     }
+
+    fn provider_connections(&self) -> &ProviderConnections {
+        &self.config.provider_connections
+    }
+
+    fn replace_provider_connections(
+        &mut self,
+        mut connections: ProviderConnections,
+    ) -> ProviderConnections {
+        swap(&mut connections, &mut self.config.provider_connections);
+        connections
+    }
 }
 
 fn bind_mounts_to_bollard_mounts(bind_mounts: &[BindMount]) -> Vec<bollard::models::Mount> {
@@ -306,24 +325,31 @@ impl DockerInstance {
             .await
     }
 
+    pub fn instance_editor(
+        &self,
+        editor: &flecs_app_manifest::generated::manifest_3_2_0::EditorsItem,
+    ) -> models::InstanceEditor {
+        let port = editor.port.get();
+        let editor_host = self.config.editor_hosts.get(&port);
+        models::InstanceEditor {
+            name: editor.name.clone(),
+            port,
+            url: lore::FloxyLore::instance_editor_api_location(self.id, port),
+            path_prefix: self.config.editor_path_prefixes.get(&port).cloned(),
+            host_name: editor_host.map(|editor_host| editor_host.host_name.clone()),
+            host_url: editor_host.map(EditorHost::url),
+            self_signed_certificate: editor_host
+                .and_then(|editor_host| editor_host.tls.as_ref())
+                .map(|tls| tls.self_signed),
+        }
+    }
+
     fn instance_editors(&self) -> Option<models::InstanceEditors> {
         let editors: Vec<_> = self
             .manifest
             .editors()
             .iter()
-            .map(|editor| {
-                let path_prefix = self
-                    .config
-                    .editor_path_prefixes
-                    .get(&editor.port.get())
-                    .cloned();
-                models::InstanceEditor {
-                    name: editor.name.clone(),
-                    port: editor.port.get(),
-                    url: lore::FloxyLore::instance_editor_api_location(self.id, editor.port.get()),
-                    path_prefix,
-                }
-            })
+            .map(|editor| self.instance_editor(editor))
             .collect();
         if editors.is_empty() {
             None
@@ -533,9 +559,12 @@ impl DockerInstance {
             volume_mounts,
             connected_networks: HashMap::new(),
             usb_devices: HashMap::new(),
+            editor_hosts: HashMap::new(),
+            editor_roles: HashMap::new(),
             mapped_editor_ports: Default::default(),
             editor_path_prefixes: manifest.default_editor_path_prefixes(),
             dependencies: HashMap::default(),
+            provider_connections: HashMap::default(),
         };
         Ok(Self {
             hostname: format!("flecs-{instance_id}"),
@@ -549,6 +578,324 @@ impl DockerInstance {
         })
     }
 
+    /// Copy of this instance which is not part of the vault, e.g. to clone the data of the
+    /// instance without keeping the vault reserved
+    pub fn detached_copy(&self) -> Self {
+        Self {
+            id: self.id,
+            manifest: self.manifest.clone(),
+            deployment: self.deployment.clone(),
+            name: self.name.clone(),
+            hostname: self.hostname.clone(),
+            config: self.config.clone(),
+            desired: self.desired,
+            lore: self.lore.clone(),
+        }
+    }
+
+    /// Creates a new instance of the same app based on the configuration of this instance. The
+    /// clone gets its own id, name, volumes and config files. Config files and volume content are
+    /// copied from this instance if requested, otherwise they are created as for a new instance.
+    /// If anything is copied a running instance is halted during the copy and resumed afterwards.
+    /// The clone has no port mappings and is not connected to any network, as free host ports
+    /// and addresses depend on the other instances.
+    pub async fn try_create_clone(
+        &self,
+        quest: SyncQuest,
+        floxy: Arc<dyn Floxy>,
+        name: String,
+        copy_config_files: bool,
+        copy_volumes: bool,
+    ) -> anyhow::Result<Self> {
+        let is_running = (copy_config_files || copy_volumes) && self.is_running().await?;
+        if is_running {
+            self.halt().await?;
+        }
+        let result = self
+            .create_clone_data(quest, copy_config_files, copy_volumes)
+            .await;
+        if is_running {
+            if let Err(e) = self.resume(floxy).await {
+                error!(
+                    "Failed to restart instance {} after cloning its data: {e}",
+                    self.id
+                );
+            }
+        }
+        let (instance_id, volume_mounts) = result?;
+        let config = InstanceConfig {
+            port_mapping: InstancePortMapping::default(),
+            connected_networks: HashMap::new(),
+            volume_mounts,
+            mapped_editor_ports: Default::default(),
+            editor_path_prefixes: self.manifest.default_editor_path_prefixes(),
+            editor_hosts: Default::default(),
+            ..self.config.clone()
+        };
+        Ok(Self {
+            hostname: format!("flecs-{instance_id}"),
+            id: instance_id,
+            deployment: self.deployment.clone(),
+            name,
+            manifest: self.manifest.clone(),
+            config,
+            desired: InstanceStatus::Stopped,
+            lore: self.lore.clone(),
+        })
+    }
+
+    async fn create_clone_data(
+        &self,
+        quest: SyncQuest,
+        copy_config_files: bool,
+        copy_volumes: bool,
+    ) -> anyhow::Result<(InstanceId, HashMap<VolumeId, VolumeMount>)> {
+        let instance_id = InstanceId::new_random();
+        let config_path = self.lore().instance_config_path(&instance_id.to_string());
+        let result = if copy_config_files {
+            quest
+                .lock()
+                .await
+                .create_sub_quest(
+                    format!("Copy config files of instance {} to {instance_id}", self.id),
+                    |_quest| {
+                        Self::export_config_files(
+                            self.id,
+                            self.manifest.config_files.clone(),
+                            self.config_path(),
+                            config_path.clone(),
+                        )
+                    },
+                )
+                .await
+                .2
+        } else {
+            quest
+                .lock()
+                .await
+                .create_sub_quest(
+                    format!(
+                        "Create config files for instance {instance_id} of {}",
+                        self.manifest.key
+                    ),
+                    |quest| {
+                        Self::create_config_files(
+                            quest,
+                            self.deployment.clone(),
+                            config_path.clone(),
+                            self.manifest.config_files.clone(),
+                            self.manifest.clone(),
+                        )
+                    },
+                )
+                .await
+                .2
+        };
+        if let Err(e) = result.await {
+            if let Err(e) = fs::remove_dir_all(&config_path).await {
+                warn!("Could not remove config files of cloned instance {instance_id}: {e}");
+            }
+            return Err(e);
+        }
+        let volume_mounts = if copy_volumes {
+            self.clone_volumes(&quest, instance_id).await
+        } else {
+            let result = quest
+                .lock()
+                .await
+                .create_sub_quest(format!("Create volumes for {instance_id}"), |quest| {
+                    Self::create_volumes(
+                        quest,
+                        self.deployment.clone(),
+                        self.manifest.volume_mounts(),
+                        instance_id,
+                    )
+                })
+                .await
+                .2;
+            result.await
+        };
+        match volume_mounts {
+            Ok(volume_mounts) => Ok((instance_id, volume_mounts)),
+            Err(e) => {
+                if let Err(e) = fs::remove_dir_all(&config_path).await {
+                    warn!("Could not remove config files of cloned instance {instance_id}: {e}");
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Copies the content of all volumes of this instance into new volumes for the instance
+    /// with the given id via a temporary export in the working directory of the new instance
+    async fn clone_volumes(
+        &self,
+        quest: &SyncQuest,
+        instance_id: InstanceId,
+    ) -> anyhow::Result<HashMap<VolumeId, VolumeMount>> {
+        let workdir = self.lore().instance_workdir_path(&instance_id.to_string());
+        let volumes_path = workdir.join("volumes");
+        fs::create_dir_all(&volumes_path).await?;
+        let result = self
+            .clone_volumes_via(quest, instance_id, &volumes_path)
+            .await;
+        if let Err(e) = fs::remove_dir_all(&workdir).await {
+            warn!(
+                "Could not remove temporary volume exports of cloned instance {instance_id}: {e}"
+            );
+        }
+        let (volume_mounts, errors): (Vec<_>, Vec<_>) =
+            result?.into_iter().partition(Result::is_ok);
+        let volume_mounts: HashMap<VolumeId, VolumeMount> =
+            volume_mounts.into_iter().filter_map(Result::ok).collect();
+        if let Some(Err(e)) = errors.into_iter().next() {
+            let volume_ids = volume_mounts.into_keys().collect();
+            let result = quest
+                .lock()
+                .await
+                .create_sub_quest(
+                    "Delete all cloned volumes, as an error occurred".to_string(),
+                    |quest| {
+                        crate::jeweler::extension::delete_volumes(
+                            quest,
+                            self.deployment.clone(),
+                            volume_ids,
+                        )
+                    },
+                )
+                .await
+                .2;
+            result.await?;
+            return Err(e);
+        }
+        Ok(volume_mounts)
+    }
+
+    async fn clone_volumes_via(
+        &self,
+        quest: &SyncQuest,
+        instance_id: InstanceId,
+        volumes_path: &Path,
+    ) -> anyhow::Result<Vec<anyhow::Result<(VolumeId, VolumeMount)>>> {
+        let export_results = Self::export_volumes_quest(
+            quest,
+            self.config.volume_mounts.values(),
+            volumes_path.to_path_buf(),
+            self.manifest.image_with_tag(),
+            &self.deployment,
+            self.id,
+        )
+        .await;
+        for result in join_all(export_results).await {
+            result?;
+        }
+        let prefix = format!("{}-", self.id.to_docker_id());
+        let mut volume_mounts = Vec::new();
+        let mut import_results = Vec::new();
+        for volume_mount in self.config.volume_mounts.values() {
+            let name = volume_mount
+                .name
+                .strip_prefix(&prefix)
+                .unwrap_or(&volume_mount.name);
+            let new_volume_mount = VolumeMount {
+                name: format!("{}-{name}", instance_id.to_docker_id()),
+                container_path: volume_mount.container_path.clone(),
+            };
+            Self::rename_exported_volume(volumes_path, &volume_mount.name, &new_volume_mount.name)
+                .await?;
+            import_results.push(
+                self.import_volume_quest(
+                    quest,
+                    volumes_path.to_path_buf(),
+                    new_volume_mount.container_path.clone(),
+                    new_volume_mount.name.clone(),
+                )
+                .await,
+            );
+            volume_mounts.push(new_volume_mount);
+        }
+        Ok(join_all(import_results)
+            .await
+            .into_iter()
+            .zip(volume_mounts)
+            .map(|(result, volume_mount)| result.map(|id| (id, volume_mount)))
+            .collect())
+    }
+
+    /// Deployments export volumes either as directory or as (compressed) tar archive named after
+    /// the volume
+    async fn rename_exported_volume(path: &Path, from: &str, to: &str) -> anyhow::Result<()> {
+        for extension in ["", ".tar", ".tar.gz"] {
+            let src = path.join(format!("{from}{extension}"));
+            if fs::try_exists(&src).await? {
+                fs::rename(src, path.join(format!("{to}{extension}"))).await?;
+                return Ok(());
+            }
+        }
+        anyhow::bail!("Export of volume {from} not found in {}", path.display())
+    }
+
+    /// Moves an instance which is about to be imported (see [InstanceCommon::import]) to a new id,
+    /// so that it does not replace an existing instance. Hostname and volume names are derived
+    /// from the new id and the exported volumes in `volumes_path` are renamed accordingly. Host
+    /// ports and ipv4 addresses are moved to ones that are neither taken nor unavailable, the
+    /// chosen ones are added to `taken_port_mappings` and `unavailable_addresses`.
+    pub async fn renew_imported_id(
+        &mut self,
+        id: InstanceId,
+        volumes_path: &Path,
+        taken_port_mappings: &mut Vec<InstancePortMapping>,
+        unavailable_addresses: &mut HashSet<Ipv4Addr>,
+    ) -> anyhow::Result<()> {
+        let port_mapping = self
+            .config
+            .port_mapping
+            .with_fresh_host_ports(taken_port_mappings.iter())?;
+        let mut connected_networks = HashMap::new();
+        for (network_id, address) in &self.config.connected_networks {
+            if !address.is_ipv4() {
+                continue;
+            }
+            let network = self
+                .deployment
+                .network(network_id.clone())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Network {network_id} does not exist"))?;
+            let address = Ipv4NetworkAccess::try_from(network)?
+                .next_free_ipv4_address(unavailable_addresses.clone())
+                .ok_or_else(|| anyhow::anyhow!("No free ip address available in {network_id}"))?;
+            unavailable_addresses.insert(address);
+            connected_networks.insert(network_id.clone(), IpAddr::V4(address));
+        }
+        let prefix = format!("{}-", self.id.to_docker_id());
+        let mut volume_mounts = HashMap::new();
+        for (volume_id, volume_mount) in &self.config.volume_mounts {
+            let name = volume_mount
+                .name
+                .strip_prefix(&prefix)
+                .unwrap_or(&volume_mount.name);
+            let name = format!("{}-{name}", id.to_docker_id());
+            Self::rename_exported_volume(volumes_path, &volume_mount.name, &name).await?;
+            volume_mounts.insert(
+                volume_id.clone(),
+                VolumeMount {
+                    name,
+                    container_path: volume_mount.container_path.clone(),
+                },
+            );
+        }
+        taken_port_mappings.push(port_mapping.clone());
+        self.config.port_mapping = port_mapping;
+        self.config.connected_networks = connected_networks;
+        self.config.volume_mounts = volume_mounts;
+        self.config.mapped_editor_ports = Default::default();
+        // Host names and certificates of editors belong to the instance that was exported
+        self.config.editor_hosts = Default::default();
+        self.hostname = format!("flecs-{id}");
+        self.id = id;
+        Ok(())
+    }
+
     pub async fn start(&mut self, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
         self.desired = InstanceStatus::Running;
         self.resume(floxy).await
@@ -561,8 +908,10 @@ impl DockerInstance {
         if self.is_running().await? {
             self.load_reverse_proxy_config(floxy.clone()).await?;
             self.load_additional_locations_reverse_proxy_config(floxy.clone())?;
+            self.load_editor_host_configs(floxy).await?;
             return Ok(());
         }
+        self.ensure_tls_material()?;
         self.deployment
             .start_instance(
                 self.lore.clone(),
@@ -573,6 +922,37 @@ impl DockerInstance {
             .await?;
         self.load_reverse_proxy_config(floxy.clone()).await?;
         self.load_additional_locations_reverse_proxy_config(floxy.clone())?;
+        self.load_editor_host_configs(floxy).await?;
+        Ok(())
+    }
+
+    /// Host names the instance is reachable at in its networks
+    fn host_names(&self) -> Vec<String> {
+        let mut host_names = vec![self.hostname.clone()];
+        if let Some(hostname) = self.manifest.hostname() {
+            if is_valid_host_name(&hostname) && hostname != self.hostname {
+                host_names.push(hostname);
+            }
+        }
+        host_names
+    }
+
+    /// Issues the TLS material mounted into the instance if the app needs it, see
+    /// [AppManifestSingle::tls_material_path]
+    fn ensure_tls_material(&self) -> anyhow::Result<()> {
+        if self.manifest.tls_material_path().is_some() {
+            ensure_instance_tls_material(
+                &self.lore.certificate,
+                &self.id.to_string(),
+                &self.host_names(),
+            )
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "Could not provide TLS material to instance {}: {e}",
+                    self.id
+                )
+            })?;
+        }
         Ok(())
     }
 
@@ -590,8 +970,32 @@ impl DockerInstance {
             .collect()
     }
 
+    /// Roles required to access editor `port`, roles configured for the instance take precedence
+    /// over the roles declared by the manifest
+    pub fn editor_roles(&self, port: u16) -> EditorRoles {
+        match self.config.editor_roles.get(&port) {
+            Some(roles) => roles.clone(),
+            None => self
+                .manifest
+                .editor_roles()
+                .remove(&port)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Without the feature auth there is no endpoint floxy could authorize editor requests with,
+    /// restricting editors would therefore lock everyone out
+    pub fn is_editor_restricted(&self, port: u16) -> bool {
+        cfg!(feature = "auth") && self.editor_roles(port).is_restricted()
+    }
+
     pub async fn load_reverse_proxy_config(&self, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
         let editor_ports = self.get_reverse_proxy_editor_ports();
+        let restricted_ports: Vec<_> = editor_ports
+            .iter()
+            .copied()
+            .filter(|port| self.is_editor_restricted(*port))
+            .collect();
         let auth_provider_port = self
             .manifest
             .specific_providers
@@ -606,6 +1010,7 @@ impl DockerInstance {
                     self.id,
                     instance_ip,
                     &editor_ports,
+                    &restricted_ports,
                     auth_provider_port,
                 )?;
             }
@@ -649,6 +1054,216 @@ impl DockerInstance {
         )
     }
 
+    fn editor_certificate_paths(&self, port: u16) -> (PathBuf, PathBuf) {
+        let path = self.lore().instance_certificates_path(&self.id.to_string());
+        (
+            path.join(format!("editor_{port}.crt")),
+            path.join(format!("editor_{port}.key")),
+        )
+    }
+
+    /// Stores the certificate of the dedicated host of editor `port`, a stored certificate is
+    /// removed if `certified_key` is None
+    fn store_editor_certificate(
+        &self,
+        port: u16,
+        certified_key: Option<&CertifiedKey>,
+    ) -> anyhow::Result<()> {
+        let (certificate_path, private_key_path) = self.editor_certificate_paths(port);
+        match certified_key {
+            Some(certified_key) => certified_key.write_to(&certificate_path, &private_key_path)?,
+            None => {
+                for path in [certificate_path, private_key_path] {
+                    if matches!(path.try_exists(), Ok(true)) {
+                        std::fs::remove_file(path)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the stored certificate of the dedicated host of editor `port`, a missing
+    /// self-signed certificate is generated
+    fn editor_certified_key(
+        &self,
+        port: u16,
+        editor_host: &EditorHost,
+    ) -> anyhow::Result<Option<CertifiedKey>> {
+        let Some(tls) = &editor_host.tls else {
+            return Ok(None);
+        };
+        let (certificate_path, private_key_path) = self.editor_certificate_paths(port);
+        match CertifiedKey::read_from(&certificate_path, &private_key_path) {
+            Ok(certified_key) => Ok(Some(certified_key)),
+            Err(CertificateError::Io(e))
+                if tls.self_signed && e.kind() == std::io::ErrorKind::NotFound =>
+            {
+                let certified_key = CertifiedKey::generate_self_signed(
+                    &[editor_host.host_name.clone()],
+                    lore::default::floxy::SELF_SIGNED_CERTIFICATE_VALIDITY_DAYS,
+                )?;
+                self.store_editor_certificate(port, Some(&certified_key))?;
+                Ok(Some(certified_key))
+            }
+            Err(e) => anyhow::bail!(
+                "Could not load certificate of editor {port} of instance {}: {e}",
+                self.id
+            ),
+        }
+    }
+
+    pub async fn load_editor_host_configs(&self, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
+        if self.config.editor_hosts.is_empty() {
+            return Ok(());
+        }
+        let Some(instance_ip) = self.get_default_network_address().await? else {
+            return Ok(());
+        };
+        for (port, editor_host) in &self.config.editor_hosts {
+            floxy.add_instance_editor_host_config(
+                self.lore.clone(),
+                &self.app_key().name,
+                self.id,
+                instance_ip,
+                &EditorHostInfo {
+                    port: *port,
+                    host_name: editor_host.host_name.clone(),
+                    tls: self.editor_certified_key(*port, editor_host)?,
+                    restricted: self.is_editor_restricted(*port),
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Serves editor `port` under a dedicated host name using TLS if `certified_key` is set. If
+    /// the instance is running floxy is reconfigured first and nothing is changed if that fails.
+    /// Returns the previous host of the editor.
+    pub async fn set_editor_host(
+        &mut self,
+        floxy: Arc<dyn Floxy>,
+        port: u16,
+        editor_host: EditorHost,
+        certified_key: Option<CertifiedKey>,
+    ) -> anyhow::Result<Option<EditorHost>> {
+        if self.is_running().await? {
+            if let Some(instance_ip) = self.get_default_network_address().await? {
+                floxy.add_instance_editor_host_config(
+                    self.lore.clone(),
+                    &self.app_key().name,
+                    self.id,
+                    instance_ip,
+                    &EditorHostInfo {
+                        port,
+                        host_name: editor_host.host_name.clone(),
+                        tls: certified_key.clone(),
+                        restricted: self.is_editor_restricted(port),
+                    },
+                )?;
+            }
+        }
+        self.store_editor_certificate(port, certified_key.as_ref())?;
+        Ok(self.config.editor_hosts.insert(port, editor_host))
+    }
+
+    /// Overrides the roles required to access editor `port`, the roles declared by the manifest
+    /// apply again if `roles` is None. If the instance is running floxy is reconfigured and
+    /// nothing is changed if that fails. Returns the previous override of the editor.
+    pub async fn set_editor_roles(
+        &mut self,
+        floxy: Arc<dyn Floxy>,
+        port: u16,
+        roles: Option<EditorRoles>,
+    ) -> anyhow::Result<Option<EditorRoles>> {
+        let previous = match roles {
+            Some(roles) => self.config.editor_roles.insert(port, roles),
+            None => self.config.editor_roles.remove(&port),
+        };
+        if let Err(e) = self.load_editor_access_configs(floxy.clone()).await {
+            match previous.clone() {
+                Some(previous) => self.config.editor_roles.insert(port, previous),
+                None => self.config.editor_roles.remove(&port),
+            };
+            if let Err(e) = self.load_editor_access_configs(floxy).await {
+                warn!(
+                    "Could not restore floxy configs of instance {}: {e}",
+                    self.id
+                );
+            }
+            return Err(e);
+        }
+        Ok(previous)
+    }
+
+    async fn load_editor_access_configs(&self, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
+        if self.is_running().await? {
+            self.load_reverse_proxy_config(floxy.clone()).await?;
+            self.load_editor_host_configs(floxy.clone()).await?;
+            self.load_editor_server_configs(floxy).await?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the configs of the editors that are served on free ports, see
+    /// [Floxy::add_instance_editor_redirect_to_free_port]
+    async fn load_editor_server_configs(&self, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
+        if self.config.mapped_editor_ports.is_empty() {
+            return Ok(());
+        }
+        let Some(instance_ip) = self.get_default_network_address().await? else {
+            return Ok(());
+        };
+        for (dest_port, host_port) in &self.config.mapped_editor_ports {
+            floxy.add_instance_editor_server_config(
+                self.lore.clone(),
+                &self.app_key().name,
+                self.id,
+                instance_ip,
+                *host_port,
+                *dest_port,
+                self.is_editor_restricted(*dest_port),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Serves editor `port` only under the default locations again, returns the previous host of
+    /// the editor
+    pub fn remove_editor_host(
+        &mut self,
+        floxy: Arc<dyn Floxy>,
+        port: u16,
+    ) -> anyhow::Result<Option<EditorHost>> {
+        if !self.config.editor_hosts.contains_key(&port) {
+            return Ok(None);
+        }
+        floxy.delete_instance_editor_host_config(
+            self.lore.clone(),
+            &self.app_key().name,
+            self.id,
+            port,
+        )?;
+        self.store_editor_certificate(port, None)?;
+        Ok(self.config.editor_hosts.remove(&port))
+    }
+
+    fn delete_editor_host_configs(&self, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
+        for port in self.config.editor_hosts.keys() {
+            floxy.delete_instance_editor_host_config(
+                self.lore.clone(),
+                &self.app_key().name,
+                self.id,
+                *port,
+            )?;
+        }
+        let path = self.lore().instance_certificates_path(&self.id.to_string());
+        if matches!(path.try_exists(), Ok(true)) {
+            std::fs::remove_dir_all(path)?;
+        }
+        Ok(())
+    }
+
     fn delete_reverse_proxy_config(&self, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
         floxy.delete_reverse_proxy_config(self.lore.clone(), &self.app_key().name, self.id)
     }
@@ -770,9 +1385,15 @@ impl DockerInstance {
         if let Err(e) = self.delete_reverse_proxy_config(floxy.clone()) {
             warn!("Instance {}: {e}", self.id);
         }
+        if let Err(e) = self.delete_editor_host_configs(floxy.clone()) {
+            warn!("Instance {}: {e}", self.id);
+        }
         if let Err(e) = self.delete_additional_locations_reverse_proxy_config(floxy) {
             warn!("Instance {}: {e}", self.id);
         }
+        if let Err(e) = delete_instance_tls_material(&self.lore.certificate, &self.id.to_string()) {
+            warn!("Could not delete TLS material of instance {}: {e}", self.id);
+        }
         for (id, result) in volume_ids.into_iter().zip(join_all(delete_results).await) {
             if let Err(e) = result {
                 warn!("Could not delete volume {id} of instance {}: {e}", self.id);
@@ -953,10 +1574,10 @@ impl DockerInstance {
                     instance.app_key
                 )
             })?
-            .clone();
-        let Deployment::Docker(deployment) = deployment else {
-            anyhow::bail!("DockerInstances can only be created with DockerDeployments");
-        };
+            .as_docker_deployment()
+            .ok_or_else(|| {
+                anyhow::anyhow!("DockerInstances can only be created with DockerDeployments")
+            })?;
         Ok(Self::create(lore, instance, manifest, deployment))
     }
 
@@ -1203,6 +1824,21 @@ impl DockerInstance {
         }
         let mut mounts = bind_mounts_to_bollard_mounts(bind_mounts.as_slice());
         mounts.extend(self.config.generate_volume_mounts());
+        if let Some(tls_material_path) = self.manifest.tls_material_path() {
+            mounts.push(bollard::models::Mount {
+                typ: Some(MountTypeEnum::BIND),
+                source: Some(
+                    self.lore
+                        .certificate
+                        .instance_tls_path(&self.id.to_string())
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+                target: Some(tls_material_path.to_string_lossy().to_string()),
+                read_only: Some(true),
+                ..Default::default()
+            });
+        }
         let arguments = self.manifest.arguments();
         let cmd = if arguments.is_empty() {
             None
@@ -1253,11 +1889,13 @@ impl DockerInstance {
             image: Some(self.manifest.image_with_tag().to_string()),
             hostname: Some(self.hostname.clone()),
             env: Some(
-                self.config
-                    .environment_variables
-                    .iter()
-                    .map(ToString::to_string)
-                    .collect(),
+                provider_connection::inject(
+                    &self.config.environment_variables,
+                    &self.config.provider_connections,
+                )
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
             ),
             labels: Some(
                 self.manifest
@@ -1278,13 +1916,16 @@ impl DockerInstance {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::jeweler::gem::deployment::Deployment;
     use crate::jeweler::gem::deployment::docker::tests::MockedDockerDeployment;
     use crate::jeweler::gem::instance::Instance;
     use crate::jeweler::gem::instance::docker::config::UsbPathConfig;
     use crate::jeweler::gem::manifest::single::tests::{
         create_test_manifest, create_test_manifest_full, create_test_manifest_numbered,
     };
-    use crate::jeweler::gem::manifest::single::{EnvironmentVariable, PortMapping, PortRange};
+    use crate::jeweler::gem::manifest::single::{
+        EnvironmentVariable, Label, PortMapping, PortRange,
+    };
     use crate::quest::Quest;
     use crate::relic::device::usb::tests::prepare_usb_device_test_path;
     use crate::relic::floxy::MockFloxy;
@@ -1297,6 +1938,7 @@ pub mod tests {
     };
     use mockall::predicate;
     use ntest::test_case;
+    use std::collections::BTreeSet;
     use std::fs::File;
     use std::io::Write;
     use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
@@ -1467,8 +2109,11 @@ pub mod tests {
                         },
                     ),
                 ]),
+                editor_hosts: HashMap::default(),
+                editor_roles: HashMap::default(),
                 mapped_editor_ports: Default::default(),
                 dependencies: HashMap::default(),
+                provider_connections: HashMap::default(),
             },
             deployment,
             manifest,
@@ -1936,42 +2581,289 @@ pub mod tests {
         assert_eq!(&instance.config.port_mapping.tcp, &manifest.ports);
         assert_eq!(instance.desired, InstanceStatus::Stopped);
         assert_eq!(
-            &instance.config.environment_variables,
-            &manifest.environment_variables
+            &instance.config.environment_variables,
+            &manifest.environment_variables
+        );
+        assert_eq!(&instance.config.connected_networks, &HashMap::new());
+        assert_eq!(
+            &instance.config.volume_mounts,
+            &HashMap::from([(
+                "TestVolumeId".to_string(),
+                VolumeMount {
+                    name: format!("flecs-{}-my-app-etc", instance.id),
+                    container_path: PathBuf::from("/etc/my-app")
+                }
+            )])
+        )
+    }
+
+    #[tokio::test]
+    async fn create_create_config_fails() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let manifest = create_test_manifest_full(None);
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_copy_from_app_image()
+            .times(3)
+            .returning(|_, _, _, _, _| Err(anyhow::anyhow!("TestError")));
+        let deployment: Arc<dyn DockerDeployment> = Arc::new(deployment);
+        assert!(
+            DockerInstance::try_create_new(
+                Quest::new_synced("TestQuest".to_string()),
+                lore,
+                deployment,
+                manifest.clone(),
+                "TestInstance".to_string(),
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn clone_ok() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let manifest = create_test_manifest_full(None);
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_copy_from_app_image()
+            .times(3)
+            .returning(|_, _, _, _, _| Ok(()));
+        deployment
+            .expect_create_volume()
+            .times(1)
+            .returning(|_, _| Ok("TestVolumeId".to_string()));
+        deployment.expect_instance_status().never();
+        let deployment: Arc<dyn DockerDeployment> = Arc::new(deployment);
+        let instance = test_instance(1, lore, deployment, manifest);
+        let clone = instance
+            .try_create_clone(
+                Quest::new_synced("TestQuest".to_string()),
+                Arc::new(MockFloxy::new()),
+                "TestClone".to_string(),
+                false,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_ne!(clone.id, instance.id);
+        assert_eq!(clone.name, "TestClone");
+        assert_eq!(clone.hostname, format!("flecs-{}", clone.id));
+        assert_eq!(clone.desired, InstanceStatus::Stopped);
+        assert_eq!(clone.config.port_mapping, InstancePortMapping::default());
+        assert!(clone.config.connected_networks.is_empty());
+        assert_eq!(
+            clone.config.environment_variables,
+            instance.config.environment_variables
+        );
+        assert_eq!(clone.config.usb_devices, instance.config.usb_devices);
+        assert_eq!(
+            clone.config.volume_mounts,
+            HashMap::from([(
+                "TestVolumeId".to_string(),
+                VolumeMount {
+                    name: format!("flecs-{}-my-app-etc", clone.id),
+                    container_path: PathBuf::from("/etc/my-app")
+                }
+            )])
+        );
+    }
+
+    #[tokio::test]
+    async fn clone_copy_data_ok() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let manifest = create_test_manifest_full(None);
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_instance_status()
+            .times(1)
+            .returning(|_| Ok(InstanceStatus::Stopped));
+        deployment
+            .expect_export_volume()
+            .times(4)
+            .returning(|_, id, path, _, _| {
+                std::fs::create_dir_all(path.join(id)).unwrap();
+                Ok(())
+            });
+        deployment
+            .expect_import_volume()
+            .times(4)
+            .returning(|_, src, _, name, _| {
+                assert!(src.join(name).is_dir());
+                Ok(name.to_string())
+            });
+        let deployment: Arc<dyn DockerDeployment> = Arc::new(deployment);
+        let instance = test_instance(1, lore, deployment, manifest);
+        let config_path = instance.config_path();
+        std::fs::create_dir_all(&config_path).unwrap();
+        for config_file in &instance.manifest.config_files {
+            std::fs::write(config_path.join(&config_file.host_file_name), "config").unwrap();
+        }
+        let clone = instance
+            .try_create_clone(
+                Quest::new_synced("TestQuest".to_string()),
+                Arc::new(MockFloxy::new()),
+                "TestClone".to_string(),
+                true,
+                true,
+            )
+            .await
+            .unwrap();
+        for config_file in &instance.manifest.config_files {
+            assert_eq!(
+                std::fs::read_to_string(clone.config_path().join(&config_file.host_file_name))
+                    .unwrap(),
+                "config"
+            );
+        }
+        let mut volume_ids: Vec<_> = clone.config.volume_mounts.keys().cloned().collect();
+        volume_ids.sort();
+        assert_eq!(
+            volume_ids,
+            (1..=4)
+                .map(|i| format!("{}-1-Volume#{i}", clone.id.to_docker_id()))
+                .collect::<Vec<_>>()
+        );
+        assert!(
+            !clone
+                .lore()
+                .instance_workdir_path(&clone.id.to_string())
+                .try_exists()
+                .unwrap()
         );
-        assert_eq!(&instance.config.connected_networks, &HashMap::new());
-        assert_eq!(
-            &instance.config.volume_mounts,
-            &HashMap::from([(
-                "TestVolumeId".to_string(),
-                VolumeMount {
-                    name: format!("flecs-{}-my-app-etc", instance.id),
-                    container_path: PathBuf::from("/etc/my-app")
-                }
-            )])
-        )
     }
 
     #[tokio::test]
-    async fn create_create_config_fails() {
+    async fn clone_copy_volumes_import_err() {
         let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
         let manifest = create_test_manifest_full(None);
         let mut deployment = MockedDockerDeployment::new();
         deployment
             .expect_copy_from_app_image()
             .times(3)
-            .returning(|_, _, _, _, _| Err(anyhow::anyhow!("TestError")));
+            .returning(|_, _, _, _, _| Ok(()));
+        deployment
+            .expect_instance_status()
+            .times(1)
+            .returning(|_| Ok(InstanceStatus::Stopped));
+        deployment
+            .expect_export_volume()
+            .times(4)
+            .returning(|_, id, path, _, _| {
+                std::fs::write(path.join(format!("{id}.tar")), "volume").unwrap();
+                Ok(())
+            });
+        deployment
+            .expect_import_volume()
+            .times(4)
+            .returning(|_, _, container_path, name, _| {
+                if container_path == Path::new("/volume2") {
+                    Err(anyhow::anyhow!("TestError"))
+                } else {
+                    Ok(name.to_string())
+                }
+            });
+        deployment
+            .expect_delete_volume()
+            .times(3)
+            .returning(|_, _| Ok(()));
         let deployment: Arc<dyn DockerDeployment> = Arc::new(deployment);
+        let instance = test_instance(1, lore, deployment, manifest);
         assert!(
-            DockerInstance::try_create_new(
-                Quest::new_synced("TestQuest".to_string()),
-                lore,
-                deployment,
-                manifest.clone(),
-                "TestInstance".to_string(),
+            instance
+                .try_create_clone(
+                    Quest::new_synced("TestQuest".to_string()),
+                    Arc::new(MockFloxy::new()),
+                    "TestClone".to_string(),
+                    false,
+                    true,
+                )
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn renew_imported_id_ok() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_network()
+            .with(predicate::eq("TestNetwork".to_string()))
+            .times(1)
+            .returning(|_| {
+                Ok(Some(bollard::models::Network {
+                    name: Some("TestNetwork".to_string()),
+                    ipam: Some(bollard::models::Ipam {
+                        config: Some(vec![bollard::models::IpamConfig {
+                            subnet: Some("10.18.102.0/24".to_string()),
+                            gateway: Some("10.18.102.1".to_string()),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }))
+            });
+        let deployment: Arc<dyn DockerDeployment> = Arc::new(deployment);
+        let mut instance = test_instance(1, lore, deployment, create_test_manifest_full(None));
+        instance.config.volume_mounts = HashMap::from([(
+            "TestVolumeId".to_string(),
+            VolumeMount {
+                name: "flecs-00000001-data".to_string(),
+                container_path: PathBuf::from("/data"),
+            },
+        )]);
+        instance.config.connected_networks = HashMap::from([(
+            "TestNetwork".to_string(),
+            IpAddr::V4(Ipv4Addr::new(10, 18, 102, 2)),
+        )]);
+        let volumes_path = testdir!();
+        std::fs::write(volumes_path.join("flecs-00000001-data.tar"), "volume").unwrap();
+        let mut taken_port_mappings = vec![instance.config.port_mapping.clone()];
+        let mut unavailable_addresses = HashSet::from([Ipv4Addr::new(10, 18, 102, 2)]);
+        let new_id = InstanceId::new(2);
+        instance
+            .renew_imported_id(
+                new_id,
+                &volumes_path,
+                &mut taken_port_mappings,
+                &mut unavailable_addresses,
             )
             .await
-            .is_err()
+            .unwrap();
+        assert_eq!(instance.id, new_id);
+        assert_eq!(instance.hostname, "flecs-00000002");
+        assert!(volumes_path.join("flecs-00000002-data.tar").is_file());
+        assert_eq!(
+            instance.config.volume_mounts["TestVolumeId"].name,
+            "flecs-00000002-data"
+        );
+        let address = instance.config.connected_networks["TestNetwork"];
+        assert_ne!(address, IpAddr::V4(Ipv4Addr::new(10, 18, 102, 2)));
+        assert!(matches!(address, IpAddr::V4(address) if unavailable_addresses.contains(&address)));
+        assert_eq!(taken_port_mappings.len(), 2);
+        assert_ne!(instance.config.port_mapping.tcp, taken_port_mappings[0].tcp);
+    }
+
+    #[tokio::test]
+    async fn rename_exported_volume_tar() {
+        let path = testdir!();
+        std::fs::write(path.join("volume.tar"), "volume").unwrap();
+        DockerInstance::rename_exported_volume(&path, "volume", "clone")
+            .await
+            .unwrap();
+        assert!(path.join("clone.tar").is_file());
+        assert!(!path.join("volume.tar").exists());
+    }
+
+    #[tokio::test]
+    async fn rename_exported_volume_missing() {
+        let path = testdir!();
+        assert!(
+            DockerInstance::rename_exported_volume(&path, "volume", "clone")
+                .await
+                .is_err()
         );
     }
 
@@ -2011,12 +2903,18 @@ pub mod tests {
                     port: 123,
                     path_prefix: None,
                     url: "/v2/instances/00000123/editor/123".to_string(),
+                    host_name: None,
+                    host_url: None,
+                    self_signed_certificate: None,
                 },
                 models::InstanceEditor {
                     name: "Editor#2".to_string(),
                     port: 789,
                     path_prefix: None,
                     url: "/v2/instances/00000123/editor/789".to_string(),
+                    host_name: None,
+                    host_url: None,
+                    self_signed_certificate: None,
                 },
             ])),
         };
@@ -2131,12 +3029,18 @@ pub mod tests {
                     port: 123,
                     path_prefix: None,
                     url: "/v2/instances/00000123/editor/123".to_string(),
+                    host_name: None,
+                    host_url: None,
+                    self_signed_certificate: None,
                 },
                 models::InstanceEditor {
                     name: "Editor#2".to_string(),
                     port: 789,
                     path_prefix: None,
                     url: "/v2/instances/00000123/editor/789".to_string(),
+                    host_name: None,
+                    host_url: None,
+                    self_signed_certificate: None,
                 },
             ])),
         };
@@ -2328,6 +3232,70 @@ pub mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn config_from_instance_with_provider_connections() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        prepare_usb_device_test_path("test_instance_dev_1");
+        prepare_usb_device_test_path("test_instance_dev_2");
+        let mut deployment = MockedDockerDeployment::new();
+        deployment.expect_core_default_address().returning(|_| None);
+        let deployment = Arc::new(deployment);
+        let manifest = create_test_manifest_full(None);
+        let mut instance = test_instance(123, lore, deployment, manifest);
+        instance.config.environment_variables = vec![EnvironmentVariable {
+            name: "BROKER".to_string(),
+            value: Some("{{depends.mqtt.host}}:{{depends.mqtt.port}}".to_string()),
+        }];
+        instance.replace_provider_connections(provider_connection::tests::test_connections());
+        let env = instance.container_config().await.env.unwrap();
+        assert_eq!(env.len(), 7);
+        assert_eq!(env[0], "BROKER=flecs-00001234:1883");
+        assert!(env.contains(&"FLECS_DEPENDS_MQTT_ADDRESS=172.21.0.5".to_string()));
+        assert!(env.contains(&"FLECS_DEPENDS_MQTT_PROVIDER_ID=00001234".to_string()));
+    }
+
+    #[tokio::test]
+    async fn config_from_instance_with_tls_material() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let tls_path = lore.certificate.instance_tls_path(&"0000007b");
+        prepare_usb_device_test_path("test_instance_dev_1");
+        prepare_usb_device_test_path("test_instance_dev_2");
+        let mut deployment = MockedDockerDeployment::new();
+        deployment.expect_core_default_address().returning(|_| None);
+        let deployment = Arc::new(deployment);
+        let mut manifest = Arc::unwrap_or_clone(create_test_manifest_full(None));
+        manifest
+            .labels
+            .push(crate::jeweler::gem::manifest::single::Label {
+                label: crate::jeweler::gem::manifest::single::TLS_MATERIAL_LABEL.to_string(),
+                value: Some("/etc/ssl/flecs".to_string()),
+            });
+        let instance = test_instance(123, lore, deployment, Arc::new(manifest));
+        let mounts = instance
+            .container_config()
+            .await
+            .host_config
+            .unwrap()
+            .mounts
+            .unwrap();
+        assert_eq!(mounts.len(), 7);
+        assert!(mounts.contains(&bollard::models::Mount {
+            typ: Some(MountTypeEnum::BIND),
+            source: Some(tls_path.to_string_lossy().to_string()),
+            target: Some("/etc/ssl/flecs".to_string()),
+            read_only: Some(true),
+            ..Default::default()
+        }));
+        assert!(matches!(tls_path.try_exists(), Ok(false)));
+        instance.ensure_tls_material().unwrap();
+        let mut files: Vec<_> = std::fs::read_dir(&tls_path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["ca.crt", "tls.crt", "tls.key"]);
+    }
+
     #[test]
     fn instance_status_from_container_status() {
         assert_eq!(
@@ -2587,13 +3555,14 @@ pub mod tests {
         floxy
             .expect_add_instance_reverse_proxy_config()
             .once()
-            .withf(|_, app, id, ip, ports, _| {
+            .withf(|_, app, id, ip, ports, restricted_ports, _| {
                 app == "some.test.app"
                     && id == &InstanceId::new(2)
                     && ip == &IpAddr::V4(Ipv4Addr::new(125, 20, 20, 20))
                     && ports == [789]
+                    && restricted_ports.is_empty()
             })
-            .returning(|_, _, _, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _, _| Ok(()));
         let floxy = Arc::new(floxy);
         let instance = test_instance(
             2,
@@ -2604,6 +3573,220 @@ pub mod tests {
         instance.load_reverse_proxy_config(floxy).await.unwrap();
     }
 
+    fn test_editor_roles() -> EditorRoles {
+        EditorRoles {
+            read: BTreeSet::from(["operator".to_string()]),
+            write: BTreeSet::from(["engineer".to_string()]),
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn instance_load_reverse_proxy_config_restricted() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_instance_default_address()
+            .times(1)
+            .returning(|_, _| Ok(Some(IpAddr::V4(Ipv4Addr::new(125, 20, 20, 20)))));
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_add_instance_reverse_proxy_config()
+            .once()
+            .withf(|_, _, _, _, ports, restricted_ports, _| {
+                ports == [789] && restricted_ports == [789]
+            })
+            .returning(|_, _, _, _, _, _, _| Ok(()));
+        let floxy = Arc::new(floxy);
+        let mut instance = test_instance(
+            2,
+            lore,
+            Arc::new(deployment),
+            create_test_manifest_full(None),
+        );
+        instance
+            .config
+            .editor_roles
+            .insert(789, test_editor_roles());
+        instance.load_reverse_proxy_config(floxy).await.unwrap();
+    }
+
+    #[test]
+    fn instance_editor_roles() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut manifest = Arc::unwrap_or_clone(create_test_manifest_full(None));
+        manifest
+            .labels
+            .push(Label::from_str("tech.flecs.editor.789.write-roles=admin").unwrap());
+        let mut instance = test_instance(
+            2,
+            lore,
+            Arc::new(MockedDockerDeployment::new()),
+            Arc::new(manifest),
+        );
+        assert_eq!(
+            instance.editor_roles(789),
+            EditorRoles {
+                read: BTreeSet::new(),
+                write: BTreeSet::from(["admin".to_string()]),
+            }
+        );
+        instance
+            .config
+            .editor_roles
+            .insert(789, test_editor_roles());
+        assert_eq!(instance.editor_roles(789), test_editor_roles());
+        assert_eq!(instance.is_editor_restricted(789), cfg!(feature = "auth"));
+        assert!(!instance.is_editor_restricted(1000));
+    }
+
+    #[tokio::test]
+    async fn instance_set_editor_roles_stopped() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_instance_status()
+            .times(2)
+            .returning(|_| Ok(InstanceStatus::Stopped));
+        let floxy = Arc::new(MockFloxy::new());
+        let mut instance = test_instance(
+            2,
+            lore,
+            Arc::new(deployment),
+            create_test_manifest_full(None),
+        );
+        assert_eq!(
+            instance
+                .set_editor_roles(floxy.clone(), 789, Some(test_editor_roles()))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(instance.editor_roles(789), test_editor_roles());
+        assert_eq!(
+            instance.set_editor_roles(floxy, 789, None).await.unwrap(),
+            Some(test_editor_roles())
+        );
+        assert!(instance.config.editor_roles.is_empty());
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn instance_set_editor_roles_running() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_instance_status()
+            .once()
+            .returning(|_| Ok(InstanceStatus::Running));
+        deployment
+            .expect_instance_default_address()
+            .once()
+            .returning(|_, _| Ok(Some(IpAddr::V4(Ipv4Addr::new(125, 20, 20, 20)))));
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_add_instance_reverse_proxy_config()
+            .once()
+            .withf(|_, _, _, _, _, restricted_ports, _| restricted_ports == [789])
+            .returning(|_, _, _, _, _, _, _| Ok(()));
+        let mut instance = test_instance(
+            2,
+            lore,
+            Arc::new(deployment),
+            create_test_manifest_full(None),
+        );
+        assert_eq!(
+            instance
+                .set_editor_roles(Arc::new(floxy), 789, Some(test_editor_roles()))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(instance.editor_roles(789), test_editor_roles());
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn instance_set_editor_roles_floxy_error() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_instance_status()
+            .times(2)
+            .returning(|_| Ok(InstanceStatus::Running));
+        deployment
+            .expect_instance_default_address()
+            .times(2)
+            .returning(|_, _| Ok(Some(IpAddr::V4(Ipv4Addr::new(125, 20, 20, 20)))));
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_add_instance_reverse_proxy_config()
+            .once()
+            .withf(|_, _, _, _, _, restricted_ports, _| restricted_ports == [789])
+            .returning(|_, _, _, _, _, _, _| Err(anyhow::anyhow!("TestError")));
+        floxy
+            .expect_add_instance_reverse_proxy_config()
+            .once()
+            .withf(|_, _, _, _, _, restricted_ports, _| restricted_ports.is_empty())
+            .returning(|_, _, _, _, _, _, _| Ok(()));
+        let mut instance = test_instance(
+            2,
+            lore,
+            Arc::new(deployment),
+            create_test_manifest_full(None),
+        );
+        assert!(
+            instance
+                .set_editor_roles(Arc::new(floxy), 789, Some(test_editor_roles()))
+                .await
+                .is_err()
+        );
+        assert!(instance.config.editor_roles.is_empty());
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    async fn instance_set_editor_roles_running_free_port_editor() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_instance_status()
+            .once()
+            .returning(|_| Ok(InstanceStatus::Running));
+        deployment
+            .expect_instance_default_address()
+            .times(2)
+            .returning(|_, _| Ok(Some(IpAddr::V4(Ipv4Addr::new(125, 20, 20, 20)))));
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_add_instance_reverse_proxy_config()
+            .once()
+            .withf(|_, _, _, _, _, restricted_ports, _| restricted_ports.is_empty())
+            .returning(|_, _, _, _, _, _, _| Ok(()));
+        floxy
+            .expect_add_instance_editor_server_config()
+            .once()
+            .withf(|_, _, _, _, host_port, dest_port, restricted| {
+                *host_port == 4000 && *dest_port == 123 && *restricted
+            })
+            .returning(|_, _, _, _, _, _, _| Ok(()));
+        let mut instance = test_instance(
+            2,
+            lore,
+            Arc::new(deployment),
+            create_test_manifest_full(None),
+        );
+        instance.config.mapped_editor_ports = HashMap::from([(123, 4000)]);
+        assert_eq!(
+            instance
+                .set_editor_roles(Arc::new(floxy), 123, Some(test_editor_roles()))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(instance.editor_roles(123), test_editor_roles());
+    }
+
     #[tokio::test]
     async fn instance_load_reverse_proxy_config_err() {
         let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
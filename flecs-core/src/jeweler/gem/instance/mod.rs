@@ -1,13 +1,16 @@
 pub mod compose;
 pub mod docker;
 mod id;
+pub mod provider_connection;
 pub mod status;
 
 use crate::jeweler::deployment::DeploymentId;
+use crate::jeweler::gem::instance::provider_connection::ProviderConnections;
 use crate::jeweler::gem::instance::status::InstanceStatus;
 use crate::jeweler::gem::manifest::{AppManifest, DependencyKey, FeatureKey};
 use crate::lore::Lore;
 use crate::quest::SyncQuest;
+use crate::relic::docker::container::{CompletedExec, ExecInput, ExecOutputStream};
 use crate::relic::floxy::Floxy;
 use crate::vault::pouch;
 use crate::vault::pouch::AppKey;
@@ -36,6 +39,9 @@ pub enum ProviderReference {
 pub struct StoredProviderReference {
     pub provider_reference: ProviderReference,
     pub provided_feature: FeatureKey,
+    /// Providers used in this order if the provider is not available
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_providers: Vec<ProviderReference>,
 }
 
 impl ProviderReference {
@@ -68,6 +74,11 @@ impl StoredProviderReference {
     pub fn is_default(&self) -> bool {
         self.provider_reference.is_default()
     }
+
+    /// The provider followed by the fallback providers in the order they are used
+    pub fn candidates(&self) -> impl Iterator<Item = &ProviderReference> {
+        std::iter::once(&self.provider_reference).chain(self.fallback_providers.iter())
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -120,6 +131,38 @@ impl From<Logs> for flecsd_axum_server::models::InstancesInstanceIdLogsGet200Res
     }
 }
 
+/// Result of a command that was executed inside an instance
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecOutput {
+    /// Exit code of the command, not available if the command was killed
+    pub exit_code: Option<i64>,
+    pub stdout: String,
+    pub stderr: String,
+    /// The output exceeded the limit of 1 MiB per stream and was truncated
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+impl From<CompletedExec> for ExecOutput {
+    fn from(value: CompletedExec) -> Self {
+        Self {
+            exit_code: value.exit_code,
+            stdout: value.stdout,
+            stderr: value.stderr,
+            truncated: value.truncated,
+        }
+    }
+}
+
+/// Command running with an attached tty inside an instance
+pub struct ExecSession {
+    /// Id of the exec instance, necessary to resize the tty
+    pub id: String,
+    pub output: ExecOutputStream,
+    pub input: ExecInput,
+}
+
 #[async_trait]
 pub trait InstanceCommon {
     fn id(&self) -> InstanceId;
@@ -144,6 +187,11 @@ pub trait InstanceCommon {
         key: DependencyKey,
         provider: StoredProviderReference,
     ) -> Option<StoredProviderReference>;
+    fn provider_connections(&self) -> &ProviderConnections;
+    fn replace_provider_connections(
+        &mut self,
+        connections: ProviderConnections,
+    ) -> ProviderConnections;
 }
 
 impl Deref for Instance {
@@ -234,6 +282,30 @@ impl Instance {
         Ok(())
     }
 
+    /// Checks if the app of `manifest` is installed on the deployment of this instance, i.e. if an
+    /// update of this instance to `manifest` only has to replace its containers.
+    pub async fn is_staged(
+        &self,
+        quest: SyncQuest,
+        manifest: &AppManifest,
+    ) -> anyhow::Result<bool> {
+        match (manifest, self) {
+            (AppManifest::Multi(_), Instance::Compose(instance)) => {
+                instance
+                    .deployment
+                    .is_app_installed(quest, manifest.clone())
+                    .await
+            }
+            (AppManifest::Single(_), Instance::Docker(instance)) => {
+                instance
+                    .deployment
+                    .is_app_installed(quest, manifest.clone())
+                    .await
+            }
+            _ => Err(anyhow::anyhow!("Instance and manifest do not match")),
+        }
+    }
+
     pub async fn update(
         &mut self,
         quest: SyncQuest,
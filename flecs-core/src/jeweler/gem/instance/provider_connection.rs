@@ -0,0 +1,396 @@
+//! Connection details of the providers the dependencies of an instance are bound to. They are
+//! injected into the dependent instance as environment variables, either generated
+//! (`FLECS_DEPENDS_<KEY>_<FIELD>`) or via `{{depends.<key>.<field>}}` placeholders in the values
+//! of configured environment variables.
+use crate::jeweler::gem::manifest::single::EnvironmentVariable;
+use crate::jeweler::gem::manifest::{DependencyKey, FeatureKey};
+use crate::vault::pouch::provider::ProviderId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+
+const ENVIRONMENT_PREFIX: &str = "FLECS_DEPENDS";
+const PLACEHOLDER_START: &str = "{{";
+const PLACEHOLDER_END: &str = "}}";
+const PLACEHOLDER_PREFIX: &str = "depends.";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ProviderConnection {
+    pub provider_id: ProviderId,
+    pub feature: FeatureKey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<IpAddr>,
+    /// The provided config of the feature as specified in the manifest of the provider
+    #[serde(default)]
+    pub provides: serde_json::Value,
+}
+
+pub type ProviderConnections = HashMap<DependencyKey, ProviderConnection>;
+
+fn value_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+impl ProviderConnection {
+    /// The hostname if known, the address otherwise
+    pub fn host(&self) -> Option<String> {
+        self.hostname
+            .clone()
+            .or_else(|| self.address.map(|address| address.to_string()))
+    }
+
+    pub fn port(&self) -> Option<String> {
+        self.provides_property("port")
+    }
+
+    /// Looks up a property of [Self::provides], nested properties are separated by '.'
+    pub fn provides_property(&self, path: &str) -> Option<String> {
+        path.split('.')
+            .try_fold(&self.provides, |value, key| value.get(key))
+            .map(value_to_string)
+    }
+
+    /// Supported fields are `provider_id`, `feature`, `host`, `hostname`, `address`, `port`,
+    /// `provides` (as json) and `provides.<property>`
+    pub fn field(&self, field: &str) -> Option<String> {
+        match field {
+            "provider_id" => Some(self.provider_id.to_string()),
+            "feature" => Some(self.feature.to_string()),
+            "host" => self.host(),
+            "hostname" => self.hostname.clone(),
+            "address" => self.address.map(|address| address.to_string()),
+            "port" => self.port(),
+            "provides" => Some(self.provides.to_string()),
+            field => self.provides_property(field.strip_prefix("provides.")?),
+        }
+    }
+
+    pub fn environment_variables(&self, key: &DependencyKey) -> Vec<EnvironmentVariable> {
+        let prefix = environment_prefix(key);
+        [
+            ("PROVIDER_ID", self.field("provider_id")),
+            ("FEATURE", self.field("feature")),
+            ("HOST", self.host()),
+            ("ADDRESS", self.field("address")),
+            ("PORT", self.port()),
+            ("PROVIDES", self.field("provides")),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            value.map(|value| EnvironmentVariable {
+                name: format!("{prefix}_{name}"),
+                value: Some(value),
+            })
+        })
+        .collect()
+    }
+}
+
+/// E.g. `FLECS_DEPENDS_MQTT` for the dependency key `mqtt`, characters not allowed in
+/// environment variable names are replaced by '_'
+fn environment_prefix(key: &DependencyKey) -> String {
+    let key: String = key
+        .as_ref()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{ENVIRONMENT_PREFIX}_{key}")
+}
+
+/// Generated environment variables of all connections sorted by name
+pub fn environment_variables(connections: &ProviderConnections) -> Vec<EnvironmentVariable> {
+    let mut variables: Vec<_> = connections
+        .iter()
+        .flat_map(|(key, connection)| connection.environment_variables(key))
+        .collect();
+    variables.sort_by(|a, b| a.name.cmp(&b.name));
+    variables
+}
+
+fn resolve_placeholder(placeholder: &str, connections: &ProviderConnections) -> Option<String> {
+    let placeholder = placeholder.trim().strip_prefix(PLACEHOLDER_PREFIX)?;
+    // Dependency keys can contain '.', so the keys are matched instead of splitting the placeholder
+    connections.iter().find_map(|(key, connection)| {
+        let field = placeholder
+            .strip_prefix(key.as_ref().as_str())?
+            .strip_prefix('.')?;
+        connection.field(field)
+    })
+}
+
+/// Replaces all `{{depends.<key>.<field>}}` placeholders, see [ProviderConnection::field] for
+/// the supported fields. Placeholders that can not be resolved are kept as is.
+pub fn render(template: &str, connections: &ProviderConnections) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(PLACEHOLDER_START) {
+        let Some(length) = rest[start..].find(PLACEHOLDER_END) else {
+            break;
+        };
+        let end = start + length + PLACEHOLDER_END.len();
+        rendered.push_str(&rest[..start]);
+        let placeholder = &rest[start + PLACEHOLDER_START.len()..start + length];
+        match resolve_placeholder(placeholder, connections) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Renders the placeholders in the values of `variables` and appends the generated variables of
+/// `connections` that are not explicitly set
+pub fn inject(
+    variables: &[EnvironmentVariable],
+    connections: &ProviderConnections,
+) -> Vec<EnvironmentVariable> {
+    let mut injected: Vec<_> = variables
+        .iter()
+        .map(|variable| EnvironmentVariable {
+            name: variable.name.clone(),
+            value: variable
+                .value
+                .as_ref()
+                .map(|value| render(value, connections)),
+        })
+        .collect();
+    let names: HashSet<_> = variables
+        .iter()
+        .map(|variable| variable.name.clone())
+        .collect();
+    injected.extend(
+        environment_variables(connections)
+            .into_iter()
+            .filter(|variable| !names.contains(&variable.name)),
+    );
+    injected
+}
+
+/// Single quoted values are taken literally by docker compose. Values that can not be single
+/// quoted are double quoted, docker compose interpolates these and resolves escape sequences.
+fn quote_dotenv_value(value: &str) -> String {
+    if !value.contains(['\'', '\n', '\r']) {
+        return format!("'{value}'");
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '$' => quoted.push_str("$$"),
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Content of a `.env` file containing the generated variables of `connections`, used for the
+/// variable interpolation of compose files
+pub fn dotenv(connections: &ProviderConnections) -> String {
+    environment_variables(connections)
+        .into_iter()
+        .filter_map(|variable| {
+            let value = variable.value?;
+            Some(format!(
+                "{}={}\n",
+                variable.name,
+                quote_dotenv_value(&value)
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::jeweler::gem::instance::InstanceId;
+    use std::net::Ipv4Addr;
+    use std::str::FromStr;
+
+    pub fn test_connection() -> ProviderConnection {
+        ProviderConnection {
+            provider_id: InstanceId::new(0x1234),
+            feature: FeatureKey::from_str("mqtt").unwrap(),
+            hostname: Some("flecs-00001234".to_string()),
+            address: Some(IpAddr::V4(Ipv4Addr::new(172, 21, 0, 5))),
+            provides: serde_json::json!({
+                "port": 1883,
+                "protocol": "mqtt",
+                "tls": {"enabled": false}
+            }),
+        }
+    }
+
+    pub fn test_connections() -> ProviderConnections {
+        HashMap::from([(DependencyKey::new("mqtt"), test_connection())])
+    }
+
+    #[test]
+    fn connection_fields() {
+        let connection = test_connection();
+        assert_eq!(
+            connection.field("provider_id"),
+            Some("00001234".to_string())
+        );
+        assert_eq!(connection.field("feature"), Some("mqtt".to_string()));
+        assert_eq!(connection.field("host"), Some("flecs-00001234".to_string()));
+        assert_eq!(connection.field("address"), Some("172.21.0.5".to_string()));
+        assert_eq!(connection.field("port"), Some("1883".to_string()));
+        assert_eq!(
+            connection.field("provides.protocol"),
+            Some("mqtt".to_string())
+        );
+        assert_eq!(
+            connection.field("provides.tls.enabled"),
+            Some("false".to_string())
+        );
+        assert_eq!(connection.field("provides.unknown"), None);
+        assert_eq!(connection.field("unknown"), None);
+    }
+
+    #[test]
+    fn host_falls_back_to_address() {
+        let connection = ProviderConnection {
+            hostname: None,
+            ..test_connection()
+        };
+        assert_eq!(connection.host(), Some("172.21.0.5".to_string()));
+        let connection = ProviderConnection {
+            address: None,
+            ..connection
+        };
+        assert_eq!(connection.host(), None);
+    }
+
+    #[test]
+    fn environment_prefix_sanitized() {
+        assert_eq!(
+            environment_prefix(&DependencyKey::new("mqtt | amqp-1.0")),
+            "FLECS_DEPENDS_AMQP_1_0_MQTT"
+        );
+    }
+
+    #[test]
+    fn generated_environment_variables() {
+        let variables: Vec<_> = environment_variables(&test_connections())
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            variables,
+            vec![
+                "FLECS_DEPENDS_MQTT_ADDRESS=172.21.0.5",
+                "FLECS_DEPENDS_MQTT_FEATURE=mqtt",
+                "FLECS_DEPENDS_MQTT_HOST=flecs-00001234",
+                "FLECS_DEPENDS_MQTT_PORT=1883",
+                "FLECS_DEPENDS_MQTT_PROVIDER_ID=00001234",
+                r#"FLECS_DEPENDS_MQTT_PROVIDES={"port":1883,"protocol":"mqtt","tls":{"enabled":false}}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn render_placeholders() {
+        assert_eq!(
+            render(
+                "mqtt://{{depends.mqtt.host}}:{{ depends.mqtt.port }}/{{depends.mqtt.unknown}}",
+                &test_connections()
+            ),
+            "mqtt://flecs-00001234:1883/{{depends.mqtt.unknown}}"
+        );
+        assert_eq!(
+            render("{{depends.other.host}} {{unclosed", &test_connections()),
+            "{{depends.other.host}} {{unclosed"
+        );
+    }
+
+    #[test]
+    fn render_placeholders_key_with_dot() {
+        let connections = HashMap::from([(DependencyKey::new("opc.ua"), test_connection())]);
+        assert_eq!(
+            render("{{depends.opc.ua.port}}", &connections),
+            "1883".to_string()
+        );
+    }
+
+    #[test]
+    fn inject_keeps_explicit_variables() {
+        let variables = vec![
+            EnvironmentVariable {
+                name: "BROKER".to_string(),
+                value: Some("{{depends.mqtt.address}}".to_string()),
+            },
+            EnvironmentVariable {
+                name: "FLECS_DEPENDS_MQTT_PORT".to_string(),
+                value: Some("8883".to_string()),
+            },
+            EnvironmentVariable {
+                name: "FLAG".to_string(),
+                value: None,
+            },
+        ];
+        let injected = inject(&variables, &test_connections());
+        assert_eq!(injected.len(), 8);
+        assert_eq!(injected[0].to_string(), "BROKER=172.21.0.5");
+        assert_eq!(injected[1].to_string(), "FLECS_DEPENDS_MQTT_PORT=8883");
+        assert_eq!(injected[2].to_string(), "FLAG");
+        assert_eq!(
+            injected
+                .iter()
+                .filter(|variable| variable.name == "FLECS_DEPENDS_MQTT_PORT")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn dotenv_quoted() {
+        let connection = ProviderConnection {
+            hostname: None,
+            address: None,
+            provides: serde_json::json!({"name": "it's"}),
+            ..test_connection()
+        };
+        let connections = HashMap::from([(DependencyKey::new("mqtt"), connection)]);
+        assert_eq!(
+            dotenv(&connections),
+            "FLECS_DEPENDS_MQTT_FEATURE='mqtt'\n\
+             FLECS_DEPENDS_MQTT_PROVIDER_ID='00001234'\n\
+             FLECS_DEPENDS_MQTT_PROVIDES=\"{\\\"name\\\":\\\"it's\\\"}\"\n"
+        );
+    }
+
+    #[test]
+    fn quote_dotenv_value_literal() {
+        assert_eq!(quote_dotenv_value(r#"$HOME \"x\""#), r#"'$HOME \"x\"'"#);
+    }
+
+    #[test]
+    fn quote_dotenv_value_escaped() {
+        assert_eq!(
+            quote_dotenv_value(r#"it's $HOME ${USER} \ "x""#),
+            r#""it's $$HOME $${USER} \\ \"x\"""#
+        );
+        assert_eq!(
+            quote_dotenv_value("first\nsecond\r\nthird"),
+            r#""first\nsecond\r\nthird""#
+        );
+    }
+}
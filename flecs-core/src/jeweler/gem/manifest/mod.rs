@@ -166,6 +166,20 @@ impl AsRef<String> for DependencyKey {
 }
 
 impl Dependency {
+    /// Validates the required properties of typed features, see [providers::features]
+    pub fn validate(&self) -> Result<(), providers::features::FeatureSchemaError> {
+        let configs: Vec<_> = match self {
+            Self::One(feature, config) => vec![(feature, config)],
+            Self::OneOf(configs) => configs.iter().collect(),
+        };
+        for (feature, config) in configs {
+            if let Some(schema) = providers::features::feature_schema(feature) {
+                schema.validate_dependency(config)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn config_json(&self) -> serde_json::Value {
         serde_json::Value::Object(match self {
             Self::One(feature, config) => {
@@ -182,11 +196,11 @@ impl Dependency {
 
 #[derive(Debug, Clone, Error)]
 pub enum ParseDependencyError {
-    #[error("")]
+    #[error("Dependency on multiple features has to specify properties for each feature")]
     NoProperties,
-    #[error("")]
+    #[error("Number of properties does not match the number of features")]
     FeaturesNotMatchingProperties,
-    #[error("")]
+    #[error("No properties specified for feature {0}")]
     NoMatchingProperty(String),
 }
 
@@ -225,14 +239,14 @@ impl TryFrom<(&DependencyKey, &serde_json::Value)> for Dependency {
 fn parse_depends(
     flecs_app_manifest::generated::manifest_3_2_0::Depends(depends): &flecs_app_manifest::generated::manifest_3_2_0::Depends,
 ) -> Result<HashMap<DependencyKey, Dependency>, ParseDependencyError> {
-    Ok(depends
+    depends
         .iter()
         .map(|(key, value)| {
             let key = DependencyKey::new(key.deref());
-            let dependency = Dependency::try_from((&key, value)).expect("TODO");
-            (key, dependency)
+            let dependency = Dependency::try_from((&key, value))?;
+            Ok((key, dependency))
         })
-        .collect())
+        .collect()
 }
 
 impl AppManifest {
@@ -250,6 +264,13 @@ impl AppManifest {
         }
     }
 
+    pub fn minimum_flecs_version(&self) -> Option<&str> {
+        match self {
+            AppManifest::Single(single) => single.minimum_flecs_version(),
+            AppManifest::Multi(multi) => multi.minimum_flecs_version(),
+        }
+    }
+
     pub fn provides(&self) -> &HashMap<FeatureKey, serde_json::Value> {
         match self {
             AppManifest::Single(single) => single.provides(),
@@ -272,6 +293,21 @@ impl AppManifest {
         .get(key)
     }
 
+    /// Validates the typed features the manifest provides and depends on, see
+    /// [providers::features]. Only manifests which are installed are validated, stored manifests
+    /// stay loadable if a schema becomes stricter.
+    pub fn validate_features(&self) -> Result<(), providers::features::FeatureSchemaError> {
+        for (feature, config) in self.provides() {
+            if let Some(schema) = providers::features::feature_schema(feature) {
+                schema.validate_provides(config)?;
+            }
+        }
+        for dependency in self.depends().values() {
+            dependency.validate()?;
+        }
+        Ok(())
+    }
+
     pub fn specific_providers(&self) -> &providers::Providers {
         match self {
             AppManifest::Single(single) => single.specific_providers(),
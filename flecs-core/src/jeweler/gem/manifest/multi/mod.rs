@@ -96,6 +96,13 @@ impl AppManifestMulti {
         self.original.revision.as_deref()
     }
 
+    pub fn minimum_flecs_version(&self) -> Option<&str> {
+        self.original
+            .minimum_flecs_version
+            .as_ref()
+            .map(|version| version.as_str())
+    }
+
     pub fn project_name(&self) -> String {
         self.key.name.replace('.', "-")
     }
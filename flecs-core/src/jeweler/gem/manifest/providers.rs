@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use thiserror::Error;
 
 pub mod auth;
+pub mod features;
 
 #[derive(Error, Debug)]
 pub enum ProviderFromValueError {
@@ -0,0 +1,492 @@
+//! Registry of features with a known schema. The `provides` and `depends` values of typed
+//! features are validated by [crate::jeweler::gem::manifest::AppManifest::validate_features]
+//! when an app is installed, other features stay untyped.
+use crate::jeweler::gem::manifest::FeatureKey;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyType {
+    String,
+    Boolean,
+    Integer,
+    /// Integer in the range 1..=65535
+    Port,
+    StringArray,
+}
+
+impl Display for PropertyType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String => write!(f, "string"),
+            Self::Boolean => write!(f, "boolean"),
+            Self::Integer => write!(f, "integer"),
+            Self::Port => write!(f, "port"),
+            Self::StringArray => write!(f, "array of strings"),
+        }
+    }
+}
+
+impl PropertyType {
+    fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Boolean => value.is_boolean(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Port => value
+                .as_u64()
+                .is_some_and(|port| (1..=u16::MAX as u64).contains(&port)),
+            Self::StringArray => value
+                .as_array()
+                .is_some_and(|values| values.iter().all(serde_json::Value::is_string)),
+        }
+    }
+
+    /// Dependencies can use strings with alternatives separated by '|' for every type, see
+    /// [crate::sorcerer::spell::provider::set_dependency]
+    fn matches_dependency(&self, value: &serde_json::Value) -> bool {
+        match value {
+            serde_json::Value::Null | serde_json::Value::String(_) => true,
+            serde_json::Value::Array(values) => {
+                *self == Self::StringArray && values.iter().all(serde_json::Value::is_string)
+            }
+            value => self.matches(value),
+        }
+    }
+
+    fn json_schema(&self) -> serde_json::Value {
+        match self {
+            Self::String => serde_json::json!({"type": "string"}),
+            Self::Boolean => serde_json::json!({"type": "boolean"}),
+            Self::Integer => serde_json::json!({"type": "integer"}),
+            Self::Port => serde_json::json!({"type": "integer", "minimum": 1, "maximum": 65535}),
+            Self::StringArray => serde_json::json!({"type": "array", "items": {"type": "string"}}),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct PropertySchema {
+    pub name: &'static str,
+    pub property_type: PropertyType,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct FeatureSchema {
+    pub feature: &'static str,
+    pub description: &'static str,
+    pub properties: &'static [PropertySchema],
+}
+
+const fn property(
+    name: &'static str,
+    property_type: PropertyType,
+    required: bool,
+    description: &'static str,
+) -> PropertySchema {
+    PropertySchema {
+        name,
+        property_type,
+        required,
+        description,
+    }
+}
+
+pub static FEATURE_SCHEMAS: [FeatureSchema; 3] = [
+    FeatureSchema {
+        feature: "mqtt-broker",
+        description: "MQTT broker other instances can connect to",
+        properties: &[
+            property(
+                "port",
+                PropertyType::Port,
+                true,
+                "Port of the broker in the default network",
+            ),
+            property(
+                "versions",
+                PropertyType::StringArray,
+                false,
+                "Supported protocol versions, e.g. 3.1.1 and 5",
+            ),
+            property("tls", PropertyType::Boolean, false, "Connections use TLS"),
+            property(
+                "websocket_port",
+                PropertyType::Port,
+                false,
+                "Port for MQTT over websockets",
+            ),
+            property(
+                "authentication",
+                PropertyType::Boolean,
+                false,
+                "Clients have to authenticate",
+            ),
+        ],
+    },
+    FeatureSchema {
+        feature: "opcua-server",
+        description: "OPC UA server other instances can connect to",
+        properties: &[
+            property(
+                "port",
+                PropertyType::Port,
+                true,
+                "Port of the server in the default network",
+            ),
+            property(
+                "endpoint_path",
+                PropertyType::String,
+                false,
+                "Path of the endpoint url",
+            ),
+            property(
+                "security_policies",
+                PropertyType::StringArray,
+                false,
+                "Supported security policies, e.g. None and Basic256Sha256",
+            ),
+            property(
+                "anonymous",
+                PropertyType::Boolean,
+                false,
+                "Anonymous access is allowed",
+            ),
+        ],
+    },
+    FeatureSchema {
+        feature: "time-series-db",
+        description: "Database for time series other instances can store data in",
+        properties: &[
+            property(
+                "port",
+                PropertyType::Port,
+                true,
+                "Port of the database in the default network",
+            ),
+            property(
+                "kind",
+                PropertyType::String,
+                true,
+                "Kind of the database, e.g. influxdb or timescaledb",
+            ),
+            property(
+                "api_version",
+                PropertyType::String,
+                false,
+                "Version of the api of the database",
+            ),
+            property(
+                "database",
+                PropertyType::String,
+                false,
+                "Name of the database to use",
+            ),
+        ],
+    },
+];
+
+pub fn feature_schema(feature: &FeatureKey) -> Option<&'static FeatureSchema> {
+    FEATURE_SCHEMAS
+        .iter()
+        .find(|schema| schema.feature == feature.as_ref())
+}
+
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum FeatureSchemaError {
+    #[error("Expected object with properties for feature {feature}, received {value}")]
+    NotObject {
+        feature: &'static str,
+        value: serde_json::Value,
+    },
+    #[error("Required property {property} of feature {feature} is missing")]
+    MissingProperty {
+        feature: &'static str,
+        property: &'static str,
+    },
+    #[error("Property {property} of feature {feature} has to be a {expected}, received {value}")]
+    WrongType {
+        feature: &'static str,
+        property: &'static str,
+        expected: PropertyType,
+        value: serde_json::Value,
+    },
+    #[error("Feature {feature} has no property {property}")]
+    UnknownProperty {
+        feature: &'static str,
+        property: String,
+    },
+    #[error(
+        "Dependency requires {property} of feature {feature} to match {required}, but the provider offers {provided}"
+    )]
+    Unsatisfied {
+        feature: &'static str,
+        property: &'static str,
+        required: serde_json::Value,
+        provided: serde_json::Value,
+    },
+}
+
+impl FeatureSchema {
+    fn properties_of<'a>(
+        &self,
+        value: &'a serde_json::Value,
+    ) -> Result<&'a serde_json::Map<String, serde_json::Value>, FeatureSchemaError> {
+        value
+            .as_object()
+            .ok_or_else(|| FeatureSchemaError::NotObject {
+                feature: self.feature,
+                value: value.clone(),
+            })
+    }
+
+    /// Providers have to specify all required properties, additional properties are allowed
+    pub fn validate_provides(&self, value: &serde_json::Value) -> Result<(), FeatureSchemaError> {
+        let properties = self.properties_of(value)?;
+        for property in self.properties {
+            match properties.get(property.name) {
+                None if property.required => {
+                    return Err(FeatureSchemaError::MissingProperty {
+                        feature: self.feature,
+                        property: property.name,
+                    });
+                }
+                Some(value) if !property.property_type.matches(value) => {
+                    return Err(FeatureSchemaError::WrongType {
+                        feature: self.feature,
+                        property: property.name,
+                        expected: property.property_type,
+                        value: value.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Dependencies can only require known properties, a dependency without requirements is
+    /// represented as `null` or an empty object
+    pub fn validate_dependency(&self, value: &serde_json::Value) -> Result<(), FeatureSchemaError> {
+        if value.is_null() {
+            return Ok(());
+        }
+        for (name, value) in self.properties_of(value)? {
+            let Some(property) = self.properties.iter().find(|p| p.name == name) else {
+                return Err(FeatureSchemaError::UnknownProperty {
+                    feature: self.feature,
+                    property: name.clone(),
+                });
+            };
+            if !property.property_type.matches_dependency(value) {
+                return Err(FeatureSchemaError::WrongType {
+                    feature: self.feature,
+                    property: property.name,
+                    expected: property.property_type,
+                    value: value.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the first property of the dependency the provider does not satisfy, `matches`
+    /// compares a provided value with a required value
+    pub fn check_satisfied<F>(
+        &self,
+        provides: &serde_json::Value,
+        dependency: &serde_json::Value,
+        matches: F,
+    ) -> Result<(), FeatureSchemaError>
+    where
+        F: Fn(&serde_json::Value, &serde_json::Value) -> bool,
+    {
+        let Some(required) = dependency.as_object() else {
+            return Ok(());
+        };
+        for property in self.properties {
+            let Some(required) = required.get(property.name) else {
+                continue;
+            };
+            let provided = provides
+                .get(property.name)
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            if !matches(&provided, required) {
+                return Err(FeatureSchemaError::Unsatisfied {
+                    feature: self.feature,
+                    property: property.name,
+                    required: required.clone(),
+                    provided,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The schema in the JSON Schema format
+    pub fn json_schema(&self) -> serde_json::Value {
+        let properties: serde_json::Map<_, _> = self
+            .properties
+            .iter()
+            .map(|property| {
+                let mut schema = property.property_type.json_schema();
+                schema["description"] = property.description.into();
+                (property.name.to_string(), schema)
+            })
+            .collect();
+        let required: Vec<_> = self
+            .properties
+            .iter()
+            .filter(|property| property.required)
+            .map(|property| property.name)
+            .collect();
+        serde_json::json!({
+            "title": self.feature,
+            "description": self.description,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::str::FromStr;
+
+    fn mqtt_schema() -> &'static FeatureSchema {
+        feature_schema(&FeatureKey::from_str("mqtt-broker").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn feature_schema_known() {
+        for schema in FEATURE_SCHEMAS.iter() {
+            assert_eq!(
+                feature_schema(&FeatureKey::from_str(schema.feature).unwrap()),
+                Some(schema)
+            );
+        }
+        assert!(feature_schema(&FeatureKey::from_str("unknown").unwrap()).is_none());
+        assert!(feature_schema(&FeatureKey::auth()).is_none());
+    }
+
+    #[test]
+    fn validate_provides_ok() {
+        mqtt_schema()
+            .validate_provides(&json!({"port": 1883, "versions": ["3.1.1", "5"], "extra": 1}))
+            .unwrap();
+    }
+
+    #[test]
+    fn validate_provides_missing_property() {
+        assert_eq!(
+            mqtt_schema().validate_provides(&json!({"tls": true})),
+            Err(FeatureSchemaError::MissingProperty {
+                feature: "mqtt-broker",
+                property: "port",
+            })
+        );
+    }
+
+    #[test]
+    fn validate_provides_wrong_type() {
+        assert_eq!(
+            mqtt_schema().validate_provides(&json!({"port": 70000})),
+            Err(FeatureSchemaError::WrongType {
+                feature: "mqtt-broker",
+                property: "port",
+                expected: PropertyType::Port,
+                value: json!(70000),
+            })
+        );
+        assert!(matches!(
+            mqtt_schema().validate_provides(&json!({"port": 1883, "versions": [5]})),
+            Err(FeatureSchemaError::WrongType {
+                property: "versions",
+                ..
+            })
+        ));
+        assert!(matches!(
+            mqtt_schema().validate_provides(&json!("1883")),
+            Err(FeatureSchemaError::NotObject { .. })
+        ));
+    }
+
+    #[test]
+    fn validate_dependency_ok() {
+        mqtt_schema().validate_dependency(&json!(null)).unwrap();
+        mqtt_schema().validate_dependency(&json!({})).unwrap();
+        mqtt_schema()
+            .validate_dependency(&json!({"tls": true, "versions": "5|3.1.1", "port": 1883}))
+            .unwrap();
+    }
+
+    #[test]
+    fn validate_dependency_unknown_property() {
+        assert_eq!(
+            mqtt_schema().validate_dependency(&json!({"tsl": true})),
+            Err(FeatureSchemaError::UnknownProperty {
+                feature: "mqtt-broker",
+                property: "tsl".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn validate_dependency_wrong_type() {
+        assert!(matches!(
+            mqtt_schema().validate_dependency(&json!({"tls": 1})),
+            Err(FeatureSchemaError::WrongType {
+                property: "tls",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn check_satisfied() {
+        let provides = json!({"port": 1883, "tls": false});
+        mqtt_schema()
+            .check_satisfied(&provides, &json!({"port": 1883}), |a, b| a == b)
+            .unwrap();
+        assert_eq!(
+            mqtt_schema().check_satisfied(&provides, &json!({"tls": true}), |a, b| a == b),
+            Err(FeatureSchemaError::Unsatisfied {
+                feature: "mqtt-broker",
+                property: "tls",
+                required: json!(true),
+                provided: json!(false),
+            })
+        );
+        assert!(matches!(
+            mqtt_schema().check_satisfied(&provides, &json!({"versions": "5"}), |a, b| a == b),
+            Err(FeatureSchemaError::Unsatisfied {
+                provided: serde_json::Value::Null,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn json_schema() {
+        let schema = mqtt_schema().json_schema();
+        assert_eq!(schema["title"], json!("mqtt-broker"));
+        assert_eq!(schema["required"], json!(["port"]));
+        assert_eq!(
+            schema["properties"]["port"],
+            json!({
+                "type": "integer",
+                "minimum": 1,
+                "maximum": 65535,
+                "description": "Port of the broker in the default network"
+            })
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use super::Label;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// Apps declare the roles required to access editor `<port>` with the labels
+/// `tech.flecs.editor.<port>.read-roles` and `tech.flecs.editor.<port>.write-roles`, their values
+/// are comma separated lists of roles, e.g. `tech.flecs.editor.1880.write-roles=engineer`
+const EDITOR_LABEL_PREFIX: &str = "tech.flecs.editor.";
+const READ_ROLES_LABEL_SUFFIX: &str = ".read-roles";
+const WRITE_ROLES_LABEL_SUFFIX: &str = ".write-roles";
+
+/// Roles required to access an editor through floxy, users need at least one of the listed roles.
+/// Access is not restricted if both lists are empty.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Eq, PartialEq, ToSchema)]
+pub struct EditorRoles {
+    /// Roles allowed to view the editor, i.e. to send GET, HEAD and OPTIONS requests. The roles
+    /// in `write` are allowed to view the editor as well. Everyone may view the editor if empty.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub read: BTreeSet<String>,
+    /// Roles allowed to change something through the editor, i.e. to send any other request. The
+    /// roles allowed to view the editor apply if empty.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub write: BTreeSet<String>,
+}
+
+impl EditorRoles {
+    pub fn is_restricted(&self) -> bool {
+        !self.read.is_empty() || !self.write.is_empty()
+    }
+
+    /// Whether a user with the given roles may send a request with `method` to the editor
+    pub fn allows(&self, method: &http::Method, roles: &HashSet<String>) -> bool {
+        let has_any =
+            |required: &BTreeSet<String>| required.iter().any(|role| roles.contains(role));
+        let may_read = self.read.is_empty() || has_any(&self.read) || has_any(&self.write);
+        let is_read = matches!(
+            *method,
+            http::Method::GET | http::Method::HEAD | http::Method::OPTIONS
+        );
+        if is_read || self.write.is_empty() {
+            may_read
+        } else {
+            has_any(&self.write)
+        }
+    }
+
+    /// The first role that could not be declared with an editor role label, i.e. an empty role,
+    /// a role containing a comma or a role padded with whitespace
+    pub fn find_invalid_role(&self) -> Option<&str> {
+        self.read
+            .iter()
+            .chain(&self.write)
+            .map(String::as_str)
+            .find(|role| role.is_empty() || role.contains(',') || role.trim() != *role)
+    }
+
+    /// Collects the roles declared by editor role labels by editor port, labels with an invalid
+    /// port are ignored
+    pub fn from_labels(labels: &[Label]) -> HashMap<u16, Self> {
+        let mut editor_roles: HashMap<u16, Self> = HashMap::new();
+        for label in labels {
+            let Some(name) = label.label.strip_prefix(EDITOR_LABEL_PREFIX) else {
+                continue;
+            };
+            let (port, is_write) = if let Some(port) = name.strip_suffix(READ_ROLES_LABEL_SUFFIX) {
+                (port, false)
+            } else if let Some(port) = name.strip_suffix(WRITE_ROLES_LABEL_SUFFIX) {
+                (port, true)
+            } else {
+                continue;
+            };
+            let Ok(port) = port.parse::<std::num::NonZeroU16>() else {
+                continue;
+            };
+            let roles = label
+                .value
+                .iter()
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .filter(|role| !role.is_empty())
+                .map(ToString::to_string);
+            let entry = editor_roles.entry(port.get()).or_default();
+            if is_write {
+                entry.write.extend(roles);
+            } else {
+                entry.read.extend(roles);
+            }
+        }
+        editor_roles.retain(|_, roles| roles.is_restricted());
+        editor_roles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn roles(roles: &[&str]) -> BTreeSet<String> {
+        roles.iter().map(ToString::to_string).collect()
+    }
+
+    fn user(roles: &[&str]) -> HashSet<String> {
+        roles.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn allows_unrestricted() {
+        let editor_roles = EditorRoles::default();
+        assert!(!editor_roles.is_restricted());
+        assert!(editor_roles.allows(&http::Method::GET, &user(&[])));
+        assert!(editor_roles.allows(&http::Method::POST, &user(&[])));
+    }
+
+    #[test]
+    fn allows_read_and_write_roles() {
+        let editor_roles = EditorRoles {
+            read: roles(&["operator"]),
+            write: roles(&["engineer"]),
+        };
+        assert!(editor_roles.is_restricted());
+        assert!(!editor_roles.allows(&http::Method::GET, &user(&[])));
+        assert!(editor_roles.allows(&http::Method::GET, &user(&["operator"])));
+        assert!(editor_roles.allows(&http::Method::HEAD, &user(&["engineer"])));
+        assert!(!editor_roles.allows(&http::Method::POST, &user(&["operator"])));
+        assert!(editor_roles.allows(&http::Method::DELETE, &user(&["other", "engineer"])));
+    }
+
+    #[test]
+    fn allows_only_write_roles() {
+        let editor_roles = EditorRoles {
+            read: BTreeSet::new(),
+            write: roles(&["engineer"]),
+        };
+        assert!(editor_roles.allows(&http::Method::GET, &user(&[])));
+        assert!(!editor_roles.allows(&http::Method::PUT, &user(&["operator"])));
+        assert!(editor_roles.allows(&http::Method::PUT, &user(&["engineer"])));
+    }
+
+    #[test]
+    fn allows_only_read_roles() {
+        let editor_roles = EditorRoles {
+            read: roles(&["operator"]),
+            write: BTreeSet::new(),
+        };
+        assert!(!editor_roles.allows(&http::Method::GET, &user(&[])));
+        assert!(!editor_roles.allows(&http::Method::POST, &user(&["engineer"])));
+        assert!(editor_roles.allows(&http::Method::POST, &user(&["operator"])));
+    }
+
+    #[test]
+    fn find_invalid_role() {
+        let mut editor_roles = EditorRoles {
+            read: roles(&["operator"]),
+            write: roles(&["engineer"]),
+        };
+        assert_eq!(editor_roles.find_invalid_role(), None);
+        for invalid in ["", "operator,engineer", " admin"] {
+            editor_roles.write = roles(&["engineer", invalid]);
+            assert_eq!(editor_roles.find_invalid_role(), Some(invalid));
+        }
+    }
+
+    #[test]
+    fn from_labels() {
+        let labels = [
+            "tech.flecs.editor.1880.read-roles=operator, engineer",
+            "tech.flecs.editor.1880.write-roles=engineer",
+            "tech.flecs.editor.8080.write-roles=admin,,",
+            "tech.flecs.editor.9000.read-roles=",
+            "tech.flecs.editor.0.read-roles=operator",
+            "tech.flecs.editor.port.read-roles=operator",
+            "tech.flecs.editor.1880.roles=other",
+            "other.label=value",
+        ]
+        .map(|label| Label::from_str(label).unwrap());
+        assert_eq!(
+            EditorRoles::from_labels(&labels),
+            HashMap::from([
+                (
+                    1880,
+                    EditorRoles {
+                        read: roles(&["engineer", "operator"]),
+                        write: roles(&["engineer"]),
+                    }
+                ),
+                (
+                    8080,
+                    EditorRoles {
+                        read: BTreeSet::new(),
+                        write: roles(&["admin"]),
+                    }
+                ),
+            ])
+        );
+    }
+}
@@ -1,5 +1,6 @@
 mod config_file;
 mod device;
+mod editor;
 mod environment_variable;
 mod label;
 mod mount;
@@ -11,6 +12,7 @@ use crate::vault::pouch::AppKey;
 pub use crate::{Error, Result};
 pub use config_file::*;
 pub use device::*;
+pub use editor::*;
 pub use environment_variable::*;
 pub use label::*;
 pub use mount::*;
@@ -18,6 +20,12 @@ pub use port::*;
 use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// Apps declare that they need the TLS material of the device with this label, its value
+/// optionally specifies the directory the material is mounted at
+pub const TLS_MATERIAL_LABEL: &str = "tech.flecs.tls";
+const DEFAULT_TLS_MATERIAL_PATH: &str = "/flecs/tls";
 
 #[derive(Debug, Eq, PartialEq, Clone, Serialize)]
 pub struct AppManifestSingle {
@@ -119,6 +127,15 @@ impl AppManifestSingle {
         }
     }
 
+    /// Roles required to access the editors by editor port as declared by the editor role
+    /// labels, see [EditorRoles::from_labels]. Roles of ports without editor are ignored.
+    pub fn editor_roles(&self) -> HashMap<u16, EditorRoles> {
+        let mut editor_roles = EditorRoles::from_labels(&self.labels);
+        let editors = self.editors();
+        editor_roles.retain(|port, _| editors.iter().any(|editor| editor.port.get() == *port));
+        editor_roles
+    }
+
     pub fn image(&self) -> &str {
         self.original.image.as_str()
     }
@@ -160,6 +177,18 @@ impl AppManifestSingle {
         self.original.hostname.as_ref().map(ToString::to_string)
     }
 
+    /// Directory inside the container the TLS material of the device is mounted at, None if the
+    /// app does not declare [TLS_MATERIAL_LABEL]
+    pub fn tls_material_path(&self) -> Option<PathBuf> {
+        self.labels
+            .iter()
+            .find(|label| label.label == TLS_MATERIAL_LABEL)
+            .map(|label| match label.value.as_deref() {
+                Some(path) if Path::new(path).is_absolute() => PathBuf::from(path),
+                _ => PathBuf::from(DEFAULT_TLS_MATERIAL_PATH),
+            })
+    }
+
     pub fn schema(&self) -> Option<&String> {
         self.original.schema.as_deref()
     }
@@ -536,6 +565,50 @@ pub mod tests {
         )
     }
 
+    #[test]
+    fn tls_material_path() {
+        let mut manifest = Arc::unwrap_or_clone(create_test_manifest_full(None));
+        assert_eq!(manifest.tls_material_path(), None);
+        manifest.labels.push(Label {
+            label: TLS_MATERIAL_LABEL.to_string(),
+            value: None,
+        });
+        assert_eq!(
+            manifest.tls_material_path(),
+            Some(PathBuf::from("/flecs/tls"))
+        );
+        manifest.labels.last_mut().unwrap().value = Some("relative/path".to_string());
+        assert_eq!(
+            manifest.tls_material_path(),
+            Some(PathBuf::from("/flecs/tls"))
+        );
+        manifest.labels.last_mut().unwrap().value = Some("/etc/ssl/flecs".to_string());
+        assert_eq!(
+            manifest.tls_material_path(),
+            Some(PathBuf::from("/etc/ssl/flecs"))
+        );
+    }
+
+    #[test]
+    fn editor_roles() {
+        let mut manifest = Arc::unwrap_or_clone(create_test_manifest_full(None));
+        assert!(manifest.editor_roles().is_empty());
+        manifest.labels.extend([
+            Label::from_str("tech.flecs.editor.789.write-roles=engineer").unwrap(),
+            Label::from_str("tech.flecs.editor.456.write-roles=engineer").unwrap(),
+        ]);
+        assert_eq!(
+            manifest.editor_roles(),
+            HashMap::from([(
+                789,
+                EditorRoles {
+                    read: Default::default(),
+                    write: ["engineer".to_string()].into(),
+                }
+            )])
+        );
+    }
+
     #[test]
     fn ports() {
         let manifest = create_test_manifest_full(None);
@@ -722,4 +795,79 @@ pub mod tests {
         let manifest = flecs_app_manifest::AppManifest::try_from(manifest).unwrap();
         assert!(AppManifest::try_from(manifest).is_err())
     }
+
+    fn try_create_manifest_with(
+        provides: Option<serde_json::Value>,
+        depends: Option<serde_json::Value>,
+    ) -> crate::Result<AppManifest> {
+        let flecs_app_manifest::generated::manifest_3_2_0::FlecsAppManifest::Single(mut manifest) =
+            create_test_manifest_numbered_raw(0, 0, None)
+        else {
+            panic!()
+        };
+        manifest.provides = provides.map(|provides| serde_json::from_value(provides).unwrap());
+        manifest.depends = depends.map(|depends| serde_json::from_value(depends).unwrap());
+        let manifest = flecs_app_manifest::AppManifest::try_from(
+            flecs_app_manifest::AppManifestVersion::V3_2_0(
+                flecs_app_manifest::generated::manifest_3_2_0::FlecsAppManifest::Single(manifest),
+            ),
+        )
+        .unwrap();
+        AppManifest::try_from(manifest)
+    }
+
+    #[test]
+    fn typed_feature_provides() {
+        assert!(
+            try_create_manifest_with(
+                Some(serde_json::json!({"mqtt-broker": {"port": 1883}, "custom": "value"})),
+                None
+            )
+            .unwrap()
+            .validate_features()
+            .is_ok()
+        );
+        // Invalid typed features do not prevent loading stored manifests
+        assert!(
+            try_create_manifest_with(
+                Some(serde_json::json!({"mqtt-broker": {"port": "1883"}})),
+                None
+            )
+            .unwrap()
+            .validate_features()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn typed_feature_depends() {
+        assert!(
+            try_create_manifest_with(
+                None,
+                Some(serde_json::json!({"mqtt-broker|opcua-server": {
+                    "mqtt-broker": {"tls": true},
+                    "opcua-server": {"anonymous": "true|false"}
+                }}))
+            )
+            .unwrap()
+            .validate_features()
+            .is_ok()
+        );
+        assert!(
+            try_create_manifest_with(
+                None,
+                Some(serde_json::json!({"time-series-db": {"engine": "influxdb"}}))
+            )
+            .unwrap()
+            .validate_features()
+            .is_err()
+        );
+        assert!(
+            try_create_manifest_with(
+                None,
+                Some(serde_json::json!({"mqtt-broker|opcua-server": {"mqtt-broker": {}}}))
+            )
+            .is_err()
+        );
+    }
 }
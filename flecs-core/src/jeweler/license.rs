@@ -0,0 +1,362 @@
+use crate::relic::integrity::{Signature, SignatureError};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::VerifyingKey;
+use flecsd_axum_server::models::SystemInfo;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Entitlement an [OfflineLicense] requires to activate the device without the console
+pub const OFFLINE_ACTIVATION_ENTITLEMENT: &str = "offline-activation";
+
+/// License of a device that is activated without a connection to the console
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OfflineLicense {
+    pub license_key: String,
+    /// The license is only valid on the device with this serial number, if set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    /// Unix timestamp in seconds
+    pub issued_at: u64,
+    /// Unix timestamp in seconds, the license does not expire if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Features the device is entitled to use, see e.g. [OFFLINE_ACTIVATION_ENTITLEMENT]
+    #[serde(default)]
+    pub entitlements: Vec<String>,
+}
+
+impl OfflineLicense {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    pub fn is_entitled_to(&self, feature: &str) -> bool {
+        self.entitlements
+            .iter()
+            .any(|entitlement| entitlement == feature)
+    }
+}
+
+/// Signed license file as issued for a [LicenseRequest]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenseFile {
+    /// Base64 encoded json of the [OfflineLicense], the signature is created over the decoded bytes
+    pub license: String,
+    #[schema(value_type = Object)]
+    pub signature: Signature,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum LicenseFileError {
+    #[error("Malformed license file: {0}")]
+    Malformed(String),
+    #[error("No trusted keys to verify the license file with")]
+    NoTrustedKeys,
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+}
+
+impl LicenseFile {
+    /// Verifies the signature with one of the `trusted_keys` and returns the contained license
+    pub fn verify(
+        &self,
+        trusted_keys: &[VerifyingKey],
+    ) -> Result<OfflineLicense, LicenseFileError> {
+        // An empty list of trusted keys would accept any key
+        if trusted_keys.is_empty() {
+            return Err(LicenseFileError::NoTrustedKeys);
+        }
+        let license = STANDARD
+            .decode(&self.license)
+            .map_err(|e| LicenseFileError::Malformed(e.to_string()))?;
+        self.signature.verify(&license, trusted_keys)?;
+        serde_json::from_slice(&license).map_err(|e| LicenseFileError::Malformed(e.to_string()))
+    }
+
+    #[cfg(test)]
+    pub fn create(key: &ed25519_dalek::SigningKey, license: &OfflineLicense) -> Self {
+        let license = serde_json::to_vec(license).unwrap();
+        Self {
+            signature: Signature::create(key, &license),
+            license: STANDARD.encode(license),
+        }
+    }
+}
+
+/// Information about the device required to issue a [LicenseFile]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenseRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    #[schema(value_type = Object)]
+    pub system_info: SystemInfo,
+    pub core_version: String,
+    /// Unix timestamp in seconds
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseState {
+    Valid,
+    /// The console could not be reached, the license is considered valid until the grace period
+    /// ends
+    GracePeriod,
+    Invalid,
+}
+
+impl Display for LicenseState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Valid => write!(f, "valid"),
+            Self::GracePeriod => write!(f, "grace period"),
+            Self::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+/// Outcome of the latest license validation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LicenseValidation {
+    pub state: LicenseState,
+    /// Unix timestamp in seconds of the last successful validation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_validated: Option<u64>,
+    /// Unix timestamp in seconds, set if the last validation failed because the console could not
+    /// be reached
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_until: Option<u64>,
+}
+
+impl LicenseValidation {
+    pub fn is_valid(&self) -> bool {
+        matches!(self.state, LicenseState::Valid | LicenseState::GracePeriod)
+    }
+
+    /// Determines the validation following `previous` from the `result` of asking the console at
+    /// `now`. If the console could not be reached the license stays valid until `grace_period`
+    /// after the last successful validation.
+    pub fn next(
+        previous: Option<&Self>,
+        result: &anyhow::Result<bool>,
+        now: u64,
+        grace_period: Duration,
+    ) -> Self {
+        let last_validated = previous.and_then(|previous| previous.last_validated);
+        match result {
+            Ok(true) => Self {
+                state: LicenseState::Valid,
+                last_validated: Some(now),
+                grace_until: None,
+            },
+            Ok(false) => Self {
+                state: LicenseState::Invalid,
+                last_validated,
+                grace_until: None,
+            },
+            Err(_) => {
+                let grace_until = last_validated
+                    .map(|last_validated| last_validated.saturating_add(grace_period.as_secs()));
+                let state = match grace_until {
+                    Some(grace_until) if now < grace_until => LicenseState::GracePeriod,
+                    _ => LicenseState::Invalid,
+                };
+                Self {
+                    state,
+                    last_validated,
+                    grace_until,
+                }
+            }
+        }
+    }
+}
+
+/// Validation before and after revalidating the license
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseRevalidation {
+    pub previous: Option<LicenseValidation>,
+    pub current: LicenseValidation,
+}
+
+impl LicenseRevalidation {
+    pub fn is_transition(&self) -> bool {
+        self.previous.as_ref().map(|previous| previous.state) != Some(self.current.state)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+
+    pub fn test_signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; ed25519_dalek::SECRET_KEY_LENGTH])
+    }
+
+    pub fn test_license() -> OfflineLicense {
+        OfflineLicense {
+            license_key: "1234-ABCD-5678-EFGH".to_string(),
+            serial_number: Some("SN-1234".to_string()),
+            issued_at: 1_700_000_000,
+            expires_at: Some(1_800_000_000),
+            entitlements: vec![OFFLINE_ACTIVATION_ENTITLEMENT.to_string()],
+        }
+    }
+
+    #[test]
+    fn verify_ok() {
+        let key = test_signing_key(1);
+        let file = LicenseFile::create(&key, &test_license());
+        assert_eq!(file.verify(&[key.verifying_key()]).unwrap(), test_license());
+    }
+
+    #[test]
+    fn verify_untrusted_key() {
+        let file = LicenseFile::create(&test_signing_key(1), &test_license());
+        assert!(matches!(
+            file.verify(&[test_signing_key(2).verifying_key()]),
+            Err(LicenseFileError::Signature(SignatureError::UntrustedKey(_)))
+        ));
+    }
+
+    #[test]
+    fn verify_no_trusted_keys() {
+        let file = LicenseFile::create(&test_signing_key(1), &test_license());
+        assert!(matches!(
+            file.verify(&[]),
+            Err(LicenseFileError::NoTrustedKeys)
+        ));
+    }
+
+    #[test]
+    fn verify_modified_license() {
+        let key = test_signing_key(1);
+        let mut file = LicenseFile::create(&key, &test_license());
+        let modified = OfflineLicense {
+            expires_at: None,
+            ..test_license()
+        };
+        file.license = STANDARD.encode(serde_json::to_vec(&modified).unwrap());
+        assert!(matches!(
+            file.verify(&[key.verifying_key()]),
+            Err(LicenseFileError::Signature(SignatureError::Invalid(_)))
+        ));
+    }
+
+    #[test]
+    fn expiry() {
+        let license = test_license();
+        assert!(!license.is_expired(1_799_999_999));
+        assert!(license.is_expired(1_800_000_000));
+        let license = OfflineLicense {
+            expires_at: None,
+            ..license
+        };
+        assert!(!license.is_expired(u64::MAX));
+    }
+
+    #[test]
+    fn entitlements() {
+        let license = test_license();
+        assert!(license.is_entitled_to(OFFLINE_ACTIVATION_ENTITLEMENT));
+        assert!(!license.is_entitled_to("exports"));
+    }
+
+    const GRACE_PERIOD: Duration = Duration::from_secs(100);
+
+    fn validated_at(last_validated: u64) -> LicenseValidation {
+        LicenseValidation {
+            state: LicenseState::Valid,
+            last_validated: Some(last_validated),
+            grace_until: None,
+        }
+    }
+
+    #[test]
+    fn next_validation_valid() {
+        assert_eq!(
+            LicenseValidation::next(Some(&validated_at(10)), &Ok(true), 20, GRACE_PERIOD),
+            validated_at(20)
+        );
+    }
+
+    #[test]
+    fn next_validation_invalid() {
+        let validation =
+            LicenseValidation::next(Some(&validated_at(10)), &Ok(false), 20, GRACE_PERIOD);
+        assert_eq!(validation.state, LicenseState::Invalid);
+        assert_eq!(validation.last_validated, Some(10));
+        assert_eq!(validation.grace_until, None);
+        assert!(!validation.is_valid());
+    }
+
+    #[test]
+    fn next_validation_unreachable_grace_period() {
+        let validation = LicenseValidation::next(
+            Some(&validated_at(10)),
+            &Err(anyhow::anyhow!("unreachable")),
+            50,
+            GRACE_PERIOD,
+        );
+        assert_eq!(
+            validation,
+            LicenseValidation {
+                state: LicenseState::GracePeriod,
+                last_validated: Some(10),
+                grace_until: Some(110),
+            }
+        );
+        assert!(validation.is_valid());
+    }
+
+    #[test]
+    fn next_validation_unreachable_grace_period_over() {
+        let validation = LicenseValidation::next(
+            Some(&validated_at(10)),
+            &Err(anyhow::anyhow!("unreachable")),
+            110,
+            GRACE_PERIOD,
+        );
+        assert_eq!(validation.state, LicenseState::Invalid);
+        assert_eq!(validation.grace_until, Some(110));
+    }
+
+    #[test]
+    fn next_validation_unreachable_never_validated() {
+        let validation =
+            LicenseValidation::next(None, &Err(anyhow::anyhow!("unreachable")), 10, GRACE_PERIOD);
+        assert_eq!(validation.state, LicenseState::Invalid);
+        assert_eq!(validation.last_validated, None);
+        assert_eq!(validation.grace_until, None);
+    }
+
+    #[test]
+    fn revalidation_transition() {
+        let revalidation = LicenseRevalidation {
+            previous: None,
+            current: validated_at(10),
+        };
+        assert!(revalidation.is_transition());
+        let revalidation = LicenseRevalidation {
+            previous: Some(validated_at(10)),
+            current: validated_at(20),
+        };
+        assert!(!revalidation.is_transition());
+        let revalidation = LicenseRevalidation {
+            previous: Some(validated_at(10)),
+            current: LicenseValidation {
+                state: LicenseState::Invalid,
+                last_validated: Some(10),
+                grace_until: None,
+            },
+        };
+        assert!(revalidation.is_transition());
+    }
+}
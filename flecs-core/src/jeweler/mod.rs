@@ -2,7 +2,9 @@ pub mod app;
 pub mod deployment;
 pub mod extension;
 pub mod gem;
+pub mod license;
 pub mod network;
+pub mod storage;
 pub mod volume;
 pub use super::Result;
 use crate::vault::pouch::AppKey;
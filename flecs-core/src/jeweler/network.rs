@@ -1,10 +1,13 @@
 use super::Result;
+use crate::forge::bollard::BollardNetworkExtension;
 use crate::lore::NetworkLoreRef;
 use crate::quest::SyncQuest;
 use anyhow::Error;
 use async_trait::async_trait;
 use ipnet::Ipv4Net;
+use net_spider::network_adapter::NetworkAdapterReader;
 use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::net::Ipv4Addr;
@@ -69,16 +72,106 @@ impl From<&str> for NetworkKind {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[serde_as]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct NetworkConfig {
     pub kind: NetworkKind,
     pub name: String,
+    #[serde_as(as = "Option<DisplayFromStr>")]
     pub cidr_subnet: Option<Ipv4Net>,
     pub gateway: Option<Ipv4Addr>,
     pub parent_adapter: Option<String>,
     pub options: Option<HashMap<String, String>>,
 }
 
+impl NetworkConfig {
+    /// Reconstructs the config a network was created with, options which are derived from kind
+    /// and parent adapter are not part of the resulting options.
+    pub fn try_from_network(network: &Network) -> Result<Self> {
+        let Some(name) = network.name.clone() else {
+            anyhow::bail!("Network has no name");
+        };
+        let options: HashMap<String, String> = network
+            .options
+            .iter()
+            .flatten()
+            .filter(|(key, _)| !matches!(key.as_str(), "parent" | "ipvlan_mode"))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        Ok(Self {
+            kind: network.guess_network_kind(),
+            name,
+            cidr_subnet: network.subnet_ipv4()?,
+            gateway: network.gateway_ipv4()?,
+            parent_adapter: network.parent_network(),
+            options: (!options.is_empty()).then_some(options),
+        })
+    }
+
+    /// Replaces the parent adapter according to `mapping` (old name -> new name). Subnet and
+    /// gateway of ipvlan networks are cleared on replacement to be taken from the new parent.
+    /// Returns true if the parent adapter was replaced.
+    pub fn remap_parent_adapter(&mut self, mapping: &HashMap<String, String>) -> bool {
+        let Some(new_parent) = self
+            .parent_adapter
+            .as_ref()
+            .and_then(|parent| mapping.get(parent))
+        else {
+            return false;
+        };
+        self.parent_adapter = Some(new_parent.clone());
+        if matches!(self.kind, NetworkKind::IpvlanL2 | NetworkKind::IpvlanL3) {
+            self.cidr_subnet = None;
+            self.gateway = None;
+        }
+        true
+    }
+
+    /// Ipvlan networks share the subnet of their parent network adapter, missing subnet and
+    /// gateway are taken from the parent.
+    pub fn fill_from_parent_adapter(
+        &mut self,
+        network_adapter_reader: &dyn NetworkAdapterReader,
+    ) -> Result<(), CreateNetworkError> {
+        let Some(parent_adapter) = &self.parent_adapter else {
+            return Err(CreateNetworkError::NetworkConfigInvalid {
+                location: "parent_adapter".to_string(),
+                reason: "Can not create ipvlan network without parent".to_string(),
+            });
+        };
+        if self.cidr_subnet.is_some() && self.gateway.is_some() {
+            return Ok(());
+        }
+        let (parent_name, parent_adapter) = network_adapter_reader
+            .try_read_network_adapters()?
+            .remove_entry(parent_adapter)
+            .ok_or_else(|| CreateNetworkError::NetworkConfigInvalid {
+                location: "parent_adapter".to_string(),
+                reason: format!("Parent network adapter {parent_adapter} does not exist"),
+            })?;
+        if parent_adapter.ipv4_networks.is_empty() {
+            return Err(CreateNetworkError::NetworkConfigInvalid {
+                location: "parent_adapter".to_string(),
+                reason: format!("Parent network adapter {parent_name} is not ready"),
+            });
+        }
+        self.cidr_subnet = Some(
+            Ipv4Net::with_netmask(
+                parent_adapter.ipv4_networks[0].addr(),
+                parent_adapter.ipv4_networks[0].netmask(),
+            )
+            .map_err(|e| CreateNetworkError::NetworkConfigInvalid {
+                location: "parent_adapter".to_string(),
+                reason: format!(
+                    "Can not construct cidr network from parent network adapter {parent_name}: {e}"
+                ),
+            })?,
+        );
+        self.gateway = parent_adapter.gateway;
+        Ok(())
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum CreateNetworkError {
     #[error("Network config invalid at {location}: {reason}")]
@@ -124,6 +217,7 @@ pub trait NetworkDeployment {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::str::FromStr;
 
     #[test]
     fn network_kind_from_str() {
@@ -141,4 +235,97 @@ mod tests {
         assert_eq!(NetworkKind::from("IpvlanL3"), NetworkKind::IpvlanL3);
         assert_eq!(NetworkKind::from("08ih208h5"), NetworkKind::Unknown);
     }
+
+    fn ipvlan_config() -> NetworkConfig {
+        NetworkConfig {
+            kind: NetworkKind::IpvlanL2,
+            name: "ipvlan".to_string(),
+            cidr_subnet: Some(Ipv4Net::from_str("10.20.0.0/16").unwrap()),
+            gateway: Some(Ipv4Addr::new(10, 20, 0, 1)),
+            parent_adapter: Some("eth0".to_string()),
+            options: None,
+        }
+    }
+
+    #[test]
+    fn try_from_network_ok() {
+        let network = Network {
+            name: Some("ipvlan".to_string()),
+            driver: Some("ipvlan".to_string()),
+            options: Some(HashMap::from([
+                ("ipvlan_mode".to_string(), "l2".to_string()),
+                ("parent".to_string(), "eth0".to_string()),
+                ("mtu".to_string(), "1400".to_string()),
+            ])),
+            ipam: Some(bollard::models::Ipam {
+                config: Some(vec![bollard::models::IpamConfig {
+                    subnet: Some("10.20.0.0/16".to_string()),
+                    gateway: Some("10.20.0.1".to_string()),
+                    ..Default::default()
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            NetworkConfig::try_from_network(&network).unwrap(),
+            NetworkConfig {
+                options: Some(HashMap::from([("mtu".to_string(), "1400".to_string())])),
+                ..ipvlan_config()
+            }
+        );
+    }
+
+    #[test]
+    fn try_from_network_no_name() {
+        assert!(NetworkConfig::try_from_network(&Network::default()).is_err());
+    }
+
+    #[test]
+    fn network_config_serde_roundtrip() {
+        let config = ipvlan_config();
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["cidr_subnet"], "10.20.0.0/16");
+        assert_eq!(
+            serde_json::from_value::<NetworkConfig>(json).unwrap(),
+            config
+        );
+    }
+
+    #[test]
+    fn remap_parent_adapter_ipvlan() {
+        let mut config = ipvlan_config();
+        assert!(
+            config
+                .remap_parent_adapter(&HashMap::from([("eth0".to_string(), "enp1s0".to_string())]))
+        );
+        assert_eq!(config.parent_adapter.as_deref(), Some("enp1s0"));
+        assert_eq!(config.cidr_subnet, None);
+        assert_eq!(config.gateway, None);
+    }
+
+    #[test]
+    fn remap_parent_adapter_macvlan() {
+        let mut config = NetworkConfig {
+            kind: NetworkKind::MACVLAN,
+            ..ipvlan_config()
+        };
+        assert!(
+            config
+                .remap_parent_adapter(&HashMap::from([("eth0".to_string(), "enp1s0".to_string())]))
+        );
+        assert_eq!(config.parent_adapter.as_deref(), Some("enp1s0"));
+        assert!(config.cidr_subnet.is_some());
+        assert!(config.gateway.is_some());
+    }
+
+    #[test]
+    fn remap_parent_adapter_unmapped() {
+        let mut config = ipvlan_config();
+        assert!(
+            !config
+                .remap_parent_adapter(&HashMap::from([("eth1".to_string(), "enp1s0".to_string())]))
+        );
+        assert_eq!(config, ipvlan_config());
+    }
 }
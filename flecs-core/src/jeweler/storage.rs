@@ -0,0 +1,36 @@
+use super::Result;
+use async_trait::async_trait;
+
+/// Image stored by a deployment
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageUsage {
+    pub id: String,
+    pub tags: Vec<String>,
+    pub size: u64,
+    /// Number of containers using the image
+    pub containers: u64,
+}
+
+/// Volume stored by a deployment
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VolumeUsage {
+    pub name: String,
+    /// Not every volume driver reports the size of its volumes
+    pub size: Option<u64>,
+    /// Number of containers using the volume
+    pub containers: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    pub images: Vec<ImageUsage>,
+    pub volumes: Vec<VolumeUsage>,
+}
+
+#[async_trait]
+pub trait StorageDeployment {
+    async fn disk_usage(&self) -> Result<DiskUsage>;
+    /// Removes the image with the given id or tag, returns `false` if the image does not exist.
+    /// Images with multiple tags are only removed with their last tag.
+    async fn remove_image(&self, image: &str) -> Result<bool>;
+}
@@ -2,9 +2,11 @@ use crate::forge::serde::{EnvFilterWrapper, UriWrapper};
 #[cfg(feature = "auth")]
 use crate::lore::AuthLore;
 use crate::lore::{
-    AppLore, ConsoleLore, DeploymentLore, ExportLore, FloxyLore, ImportLore, InstanceLore, Lore,
-    ManifestLore, NetworkLore, ProviderLore, SecretLore, SystemLore,
+    AppLore, CertificateLore, ConsoleLore, DeploymentLore, ExportLore, ExportSchedule, FloxyLore,
+    ImportLore, InstanceLore, Lore, ManifestLore, NetworkLore, ProviderLore, SecretLore,
+    SystemLore,
 };
+use crate::relic::integrity::encode_verifying_key;
 use serde::{Deserialize, Serialize};
 use std::ffi::OsStr;
 use std::net::IpAddr;
@@ -94,6 +96,8 @@ pub struct FlecsConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider: Option<ProviderConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<CertificateConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub system: Option<SystemConfig>,
 }
 
@@ -117,6 +121,7 @@ impl Default for FlecsConfig {
             #[cfg(feature = "auth")]
             auth: None,
             provider: None,
+            certificate: None,
             system: None,
         }
     }
@@ -145,6 +150,7 @@ impl From<&Lore> for FlecsConfig {
             #[cfg(feature = "auth")]
             auth: Some((&value.auth).into()),
             provider: Some((&value.provider).into()),
+            certificate: Some((&value.certificate).into()),
             system: Some((&value.system).into()),
         }
     }
@@ -156,6 +162,15 @@ pub struct ExportConfig {
     pub base_path: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing_key_path: Option<PathBuf>,
+    /// age X25519 public keys (age1...) export archives are encrypted for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_recipients: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_passphrase_path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedules: Option<Vec<ExportSchedule>>,
 }
 
 impl From<&ExportLore> for ExportConfig {
@@ -163,6 +178,10 @@ impl From<&ExportLore> for ExportConfig {
         Self {
             base_path: Some(value.base_path.clone()),
             timeout: Some(value.timeout.as_secs()),
+            signing_key_path: value.signing_key_path.clone(),
+            encryption_recipients: Some(value.encryption_recipients.clone()),
+            encryption_passphrase_path: value.encryption_passphrase_path.clone(),
+            schedules: Some(value.schedules.clone()),
         }
     }
 }
@@ -173,6 +192,16 @@ pub struct ImportConfig {
     pub base_path: Option<PathBuf>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u64>,
+    /// Base64 encoded Ed25519 public keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted_keys: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub require_signature: Option<bool>,
+    /// age identity files used to decrypt encrypted imports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decryption_identity_paths: Option<Vec<PathBuf>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decryption_passphrase_path: Option<PathBuf>,
 }
 
 impl From<&ImportLore> for ImportConfig {
@@ -180,6 +209,16 @@ impl From<&ImportLore> for ImportConfig {
         Self {
             base_path: Some(value.base_path.clone()),
             timeout: Some(value.timeout.as_secs()),
+            trusted_keys: Some(
+                value
+                    .trusted_keys
+                    .iter()
+                    .map(encode_verifying_key)
+                    .collect(),
+            ),
+            require_signature: Some(value.require_signature),
+            decryption_identity_paths: Some(value.decryption_identity_paths.clone()),
+            decryption_passphrase_path: value.decryption_passphrase_path.clone(),
         }
     }
 }
@@ -188,12 +227,28 @@ impl From<&ImportLore> for ImportConfig {
 pub struct FloxyConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_test_command: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reload: Option<FloxyReload>,
+}
+
+/// How the reverse proxy is told to reload its configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FloxyReload {
+    /// Send SIGHUP to the process whose pid is written in the given file
+    PidFile(PathBuf),
+    /// Run the given command, the first element is the program
+    Command(Vec<String>),
 }
 
 impl From<&FloxyLore> for FloxyConfig {
     fn from(value: &FloxyLore) -> Self {
         Self {
             base_path: Some(value.base_path.clone()),
+            config_test_command: value.config_test_command.clone(),
+            reload: value.reload.clone(),
         }
     }
 }
@@ -202,12 +257,29 @@ impl From<&FloxyLore> for FloxyConfig {
 pub struct ConsoleConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub uri: Option<UriWrapper>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_revalidation_interval: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_grace_period: Option<u64>,
+    /// Base64 encoded Ed25519 public keys license files are signed with, replaces the keys of
+    /// FLECS if set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license_verifying_keys: Option<Vec<String>>,
 }
 
 impl From<&ConsoleLore> for ConsoleConfig {
     fn from(value: &ConsoleLore) -> Self {
         Self {
             uri: Some(UriWrapper(value.uri.clone())),
+            license_revalidation_interval: Some(value.license_revalidation_interval.as_secs()),
+            license_grace_period: Some(value.license_grace_period.as_secs()),
+            license_verifying_keys: Some(
+                value
+                    .license_verifying_keys
+                    .iter()
+                    .map(encode_verifying_key)
+                    .collect(),
+            ),
         }
     }
 }
@@ -336,12 +408,35 @@ impl From<&AuthLore> for AuthConfig {
 pub struct ProviderConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health_check_interval: Option<u64>,
 }
 
 impl From<&ProviderLore> for ProviderConfig {
     fn from(value: &ProviderLore) -> Self {
         Self {
             base_path: Some(value.base_path.clone()),
+            health_check_interval: Some(value.health_check_interval.as_secs()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CertificateConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation_check_interval: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renew_before: Option<u64>,
+}
+
+impl From<&CertificateLore> for CertificateConfig {
+    fn from(value: &CertificateLore) -> Self {
+        Self {
+            base_path: Some(value.base_path.clone()),
+            rotation_check_interval: Some(value.rotation_check_interval.as_secs()),
+            renew_before: Some(value.renew_before.as_secs()),
         }
     }
 }
@@ -350,12 +445,20 @@ impl From<&ProviderLore> for ProviderConfig {
 pub struct SystemConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub core_sbom_spdx_path: Option<PathBuf>,
+    /// Serial number of the device, takes precedence over the serial number read from the hardware
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
+    /// Vendor specific files containing the serial number of the device
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial_number_paths: Option<Vec<PathBuf>>,
 }
 
 impl From<&SystemLore> for SystemConfig {
     fn from(value: &SystemLore) -> Self {
         Self {
             core_sbom_spdx_path: Some(value.core_sbom_spdx_path.clone()),
+            serial_number: value.serial_number.clone(),
+            serial_number_paths: Some(value.serial_number_paths.clone()),
         }
     }
 }
@@ -396,6 +499,8 @@ impl Mergeable for FlecsConfig {
         self.manifest.merge(other.manifest);
         self.network.merge(other.network);
         self.secret.merge(other.secret);
+        self.certificate.merge(other.certificate);
+        self.system.merge(other.system);
     }
 }
 
@@ -407,7 +512,13 @@ impl Mergeable for AppConfig {
 
 impl Mergeable for ConsoleConfig {
     fn merge(&mut self, other: Self) {
-        self.uri.trivial_merge(other.uri)
+        self.uri.trivial_merge(other.uri);
+        self.license_revalidation_interval
+            .trivial_merge(other.license_revalidation_interval);
+        self.license_grace_period
+            .trivial_merge(other.license_grace_period);
+        self.license_verifying_keys
+            .trivial_merge(other.license_verifying_keys);
     }
 }
 
@@ -421,18 +532,34 @@ impl Mergeable for ExportConfig {
     fn merge(&mut self, other: Self) {
         self.base_path.trivial_merge(other.base_path);
         self.timeout.trivial_merge(other.timeout);
+        self.signing_key_path.trivial_merge(other.signing_key_path);
+        self.encryption_recipients
+            .trivial_merge(other.encryption_recipients);
+        self.encryption_passphrase_path
+            .trivial_merge(other.encryption_passphrase_path);
+        self.schedules.trivial_merge(other.schedules);
     }
 }
 
 impl Mergeable for FloxyConfig {
     fn merge(&mut self, other: Self) {
         self.base_path.trivial_merge(other.base_path);
+        self.config_test_command
+            .trivial_merge(other.config_test_command);
+        self.reload.trivial_merge(other.reload);
     }
 }
 impl Mergeable for ImportConfig {
     fn merge(&mut self, other: Self) {
         self.base_path.trivial_merge(other.base_path);
         self.timeout.trivial_merge(other.timeout);
+        self.trusted_keys.trivial_merge(other.trusted_keys);
+        self.require_signature
+            .trivial_merge(other.require_signature);
+        self.decryption_identity_paths
+            .trivial_merge(other.decryption_identity_paths);
+        self.decryption_passphrase_path
+            .trivial_merge(other.decryption_passphrase_path);
     }
 }
 impl Mergeable for InstanceConfig {
@@ -452,6 +579,16 @@ impl Mergeable for NetworkConfig {
     }
 }
 
+impl Mergeable for SystemConfig {
+    fn merge(&mut self, other: Self) {
+        self.core_sbom_spdx_path
+            .trivial_merge(other.core_sbom_spdx_path);
+        self.serial_number.trivial_merge(other.serial_number);
+        self.serial_number_paths
+            .trivial_merge(other.serial_number_paths);
+    }
+}
+
 impl Mergeable for SecretConfig {
     fn merge(&mut self, other: Self) {
         self.base_path.trivial_merge(other.base_path);
@@ -500,6 +637,17 @@ impl Mergeable for Listener {
 impl Mergeable for ProviderConfig {
     fn merge(&mut self, other: Self) {
         self.base_path.trivial_merge(other.base_path);
+        self.health_check_interval
+            .trivial_merge(other.health_check_interval);
+    }
+}
+
+impl Mergeable for CertificateConfig {
+    fn merge(&mut self, other: Self) {
+        self.base_path.trivial_merge(other.base_path);
+        self.rotation_check_interval
+            .trivial_merge(other.rotation_check_interval);
+        self.renew_before.trivial_merge(other.renew_before);
     }
 }
 
@@ -692,25 +840,118 @@ mod tests {
         const BASE_PATH: &str = "/test/base/path";
         let mut current = FloxyConfig {
             base_path: Some(PathBuf::from(BASE_PATH)),
+            ..FloxyConfig::default()
         };
         current.merge(FloxyConfig {
             base_path: Some(PathBuf::from("other")),
+            ..FloxyConfig::default()
         });
         assert_eq!(current.base_path, Some(PathBuf::from(BASE_PATH)));
     }
 
+    #[test]
+    fn merge_floxy_config_commands() {
+        let mut current = FloxyConfig {
+            config_test_command: Some(vec!["nginx".to_string(), "-t".to_string()]),
+            ..FloxyConfig::default()
+        };
+        current.merge(FloxyConfig {
+            config_test_command: Some(vec!["true".to_string()]),
+            reload: Some(FloxyReload::PidFile(PathBuf::from("/run/nginx.pid"))),
+            ..FloxyConfig::default()
+        });
+        assert_eq!(
+            current.config_test_command,
+            Some(vec!["nginx".to_string(), "-t".to_string()])
+        );
+        assert_eq!(
+            current.reload,
+            Some(FloxyReload::PidFile(PathBuf::from("/run/nginx.pid")))
+        );
+    }
+
+    #[test]
+    fn deserialize_floxy_reload() {
+        let config: FloxyConfig = serde_json::from_str(
+            r#"{"config_test_command": ["nginx", "-t"], "reload": {"command": ["nginx", "-s", "reload"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.reload,
+            Some(FloxyReload::Command(vec![
+                "nginx".to_string(),
+                "-s".to_string(),
+                "reload".to_string()
+            ]))
+        );
+        let config: FloxyConfig =
+            serde_json::from_str(r#"{"reload": {"pid_file": "/run/nginx.pid"}}"#).unwrap();
+        assert_eq!(
+            config.reload,
+            Some(FloxyReload::PidFile(PathBuf::from("/run/nginx.pid")))
+        );
+    }
+
     #[test]
     fn merge_console_config_uri_both() {
         const URI: &str = "http://some.uri";
         let mut current = ConsoleConfig {
             uri: Some(UriWrapper(http::Uri::from_static(URI))),
+            ..ConsoleConfig::default()
         };
         current.merge(ConsoleConfig {
             uri: Some(UriWrapper(http::Uri::from_static("other"))),
+            ..ConsoleConfig::default()
         });
         assert_eq!(current.uri, Some(UriWrapper(http::Uri::from_static(URI))));
     }
 
+    #[test]
+    fn merge_console_config_license_intervals() {
+        let mut current = ConsoleConfig {
+            license_revalidation_interval: Some(60),
+            ..ConsoleConfig::default()
+        };
+        current.merge(ConsoleConfig {
+            license_revalidation_interval: Some(120),
+            license_grace_period: Some(3600),
+            ..ConsoleConfig::default()
+        });
+        assert_eq!(current.license_revalidation_interval, Some(60));
+        assert_eq!(current.license_grace_period, Some(3600));
+    }
+
+    #[test]
+    fn merge_provider_config_health_check_interval() {
+        let mut current = ProviderConfig::default();
+        current.merge(ProviderConfig {
+            health_check_interval: Some(10),
+            ..ProviderConfig::default()
+        });
+        assert_eq!(current.health_check_interval, Some(10));
+        current.merge(ProviderConfig {
+            health_check_interval: Some(20),
+            ..ProviderConfig::default()
+        });
+        assert_eq!(current.health_check_interval, Some(10));
+    }
+
+    #[test]
+    fn merge_certificate_config() {
+        let mut current = CertificateConfig {
+            renew_before: Some(60),
+            ..CertificateConfig::default()
+        };
+        current.merge(CertificateConfig {
+            base_path: Some(PathBuf::from("/certs")),
+            rotation_check_interval: Some(10),
+            renew_before: Some(120),
+        });
+        assert_eq!(current.base_path, Some(PathBuf::from("/certs")));
+        assert_eq!(current.rotation_check_interval, Some(10));
+        assert_eq!(current.renew_before, Some(60));
+    }
+
     #[test]
     fn merge_network_config_default_network_name_both() {
         const DEFAULT_NETWORK_NAME: &str = "DefNet";
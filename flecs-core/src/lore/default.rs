@@ -23,15 +23,22 @@ pub mod export {
 
     pub const BASE_DIRECTORY_NAME: &str = "export";
     pub const TIMEOUT: Duration = Duration::from_secs(i64::MAX as u64);
+    pub const SCHEDULE_RETENTION: usize = 7;
+    pub const SCHEDULES_DIRECTORY_NAME: &str = "schedules";
 }
 
 pub mod floxy {
     pub const BASE_DIRECTORY: &str = "/tmp/floxy/conf.d";
     pub const SERVER_CONFIGS_DIR_NAME: &str = "servers";
     pub const INSTANCE_CONFIGS_DIR_NAME: &str = "instances";
+    pub const CERTIFICATES_DIR_NAME: &str = "certs";
+    pub const SELF_SIGNED_CERTIFICATE_VALIDITY_DAYS: u32 = 825;
+    pub const DEVICE_CERTIFICATE_NAME: &str = "device";
 }
 
 pub mod console {
+    use std::time::Duration;
+
     #[cfg(debug_assertions)]
     pub const URI: &str = "https://console-dev.flecs.tech";
     #[cfg(not(debug_assertions))]
@@ -40,6 +47,18 @@ pub mod console {
     pub fn uri() -> http::Uri {
         http::Uri::from_static(URI)
     }
+
+    pub const LICENSE_REVALIDATION_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+    pub const LICENSE_GRACE_PERIOD: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+    /// Base64 encoded Ed25519 public keys FLECS signs license files with
+    pub const LICENSE_VERIFYING_KEYS: [&str; 1] = ["gkv7G+PtTr8QwzqeKUybjKrtWVLrkaaUrMWR0Loz1uE="];
+
+    pub fn license_verifying_keys() -> Vec<String> {
+        LICENSE_VERIFYING_KEYS
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
 }
 
 pub mod instance {
@@ -67,7 +86,29 @@ pub mod network {
 }
 
 pub mod provider {
+    use std::time::Duration;
+
     pub const BASE_DIRECTORY_NAME: &str = "providers";
+    pub const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+}
+
+pub mod certificate {
+    use std::time::Duration;
+
+    pub const BASE_DIRECTORY_NAME: &str = "certificates";
+    pub const TLS_DIRECTORY_NAME: &str = "tls";
+    pub const INSTANCES_DIRECTORY_NAME: &str = "instances";
+    pub const INSTANCE_CERTIFICATE_FILE_NAME: &str = "tls.crt";
+    pub const INSTANCE_PRIVATE_KEY_FILE_NAME: &str = "tls.key";
+    pub const CA_CERTIFICATE_FILE_NAME: &str = "ca.crt";
+    pub const CA_PRIVATE_KEY_FILE_NAME: &str = "ca.key";
+    pub const SERVER_CERTIFICATE_FILE_NAME: &str = "server.crt";
+    pub const SERVER_PRIVATE_KEY_FILE_NAME: &str = "server.key";
+    pub const CA_COMMON_NAME: &str = "FLECS device CA";
+    pub const CA_VALIDITY_DAYS: u32 = 3650;
+    pub const SERVER_CERTIFICATE_VALIDITY_DAYS: u32 = 397;
+    pub const RENEW_BEFORE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+    pub const ROTATION_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
 }
 
 #[cfg(feature = "auth")]
@@ -1,12 +1,20 @@
 use crate::jeweler::gem::instance::InstanceId;
 use crate::lore::conf::Mergeable;
+use crate::relic::cron::CronSchedule;
+use crate::relic::integrity::parse_verifying_key;
+use crate::relic::upload::UploadTarget;
 use crate::relic::var::VarReader;
+use crate::vault::pouch::AppKey;
+use ed25519_dalek::VerifyingKey;
 use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
+use serde_with::{DisplayFromStr, serde_as};
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
+use tracing::warn;
 use tracing_subscriber::EnvFilter;
 
 pub mod conf;
@@ -23,6 +31,14 @@ pub enum Error {
     VarReader(#[from] var::Error),
     #[error("Error reading config file: {0}")]
     File(#[from] conf::Error),
+    #[error("Invalid trusted import key {key}: {reason}")]
+    TrustedImportKey { key: String, reason: String },
+    #[error("Import signatures are required, but no trusted keys are configured")]
+    NoTrustedImportKeys,
+    #[error("Invalid license verifying key {key}: {reason}")]
+    LicenseVerifyingKey { key: String, reason: String },
+    #[error("Config option {0} must not be zero")]
+    ZeroInterval(&'static str),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -41,6 +57,7 @@ pub type SecretLoreRef = Arc<dyn AsRef<SecretLore> + Sync + Send>;
 #[cfg(feature = "auth")]
 pub type AuthLoreRef = Arc<dyn AsRef<AuthLore> + Sync + Send>;
 pub type ProviderLoreRef = Arc<dyn AsRef<ProviderLore> + Sync + Send>;
+pub type CertificateLoreRef = Arc<dyn AsRef<CertificateLore> + Sync + Send>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Listener {
@@ -51,6 +68,24 @@ pub enum Listener {
     },
 }
 
+impl Listener {
+    /// Url the core is reachable at from the device itself, unix sockets are written in the
+    /// notation of nginx, e.g. `http://unix:/run/flecs/flecsd.sock:`
+    pub fn local_url(&self) -> String {
+        match self {
+            Self::UnixSocket(path) => format!("http://unix:{}:", path.display()),
+            Self::TCP { port, bind_address } => {
+                let address = match bind_address {
+                    Some(address) if !address.is_unspecified() => *address,
+                    Some(IpAddr::V6(_)) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+                    _ => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                };
+                format!("http://{}", SocketAddr::new(address, *port))
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Lore {
     pub tracing_filter: EnvFilter,
@@ -69,6 +104,7 @@ pub struct Lore {
     #[cfg(feature = "auth")]
     pub auth: AuthLore,
     pub provider: ProviderLore,
+    pub certificate: CertificateLore,
     pub system: SystemLore,
 }
 
@@ -140,6 +176,12 @@ impl AsRef<ProviderLore> for Lore {
     }
 }
 
+impl AsRef<CertificateLore> for Lore {
+    fn as_ref(&self) -> &CertificateLore {
+        &self.certificate
+    }
+}
+
 #[cfg(feature = "auth")]
 impl AsRef<AuthLore> for Lore {
     fn as_ref(&self) -> &AuthLore {
@@ -155,22 +197,95 @@ pub fn config_path(reader: &impl VarReader) -> PathBuf {
 pub struct ExportLore {
     pub base_path: PathBuf,
     pub timeout: Duration,
+    /// PEM encoded PKCS#8 Ed25519 key used to sign export manifests, exports are not signed if
+    /// no key is configured
+    pub signing_key_path: Option<PathBuf>,
+    /// Export archives are encrypted for these age X25519 public keys, mutually exclusive with
+    /// `encryption_passphrase_path`
+    pub encryption_recipients: Vec<String>,
+    /// File containing the passphrase export archives are encrypted with
+    pub encryption_passphrase_path: Option<PathBuf>,
+    /// Exports created periodically, names are unique
+    pub schedules: Vec<ExportSchedule>,
+}
+
+/// Export created periodically and optionally uploaded to a target
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportSchedule {
+    /// Unique name, consisting of alphanumeric characters, '-' and '_'. Used as prefix of the
+    /// uploaded files.
+    pub name: String,
+    pub cron: CronSchedule,
+    /// All installed apps are exported if not specified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apps: Option<Vec<AppKey>>,
+    /// All instances are exported if not specified
+    #[serde_as(as = "Option<Vec<DisplayFromStr>>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instances: Option<Vec<InstanceId>>,
+    /// Number of exports of this schedule which are kept, older exports are deleted locally and
+    /// from the target
+    #[serde(default = "default_export_schedule_retention")]
+    pub retention: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<UploadTarget>,
+}
+
+fn default_export_schedule_retention() -> usize {
+    default::export::SCHEDULE_RETENTION
+}
+
+impl ExportSchedule {
+    fn is_name_valid(&self) -> bool {
+        !self.name.is_empty()
+            && self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
 }
 
 #[derive(Debug)]
 pub struct ImportLore {
     pub base_path: PathBuf,
     pub timeout: Duration,
+    /// Signed imports are only accepted if they are signed with one of these keys, signatures of
+    /// imports are ignored if the list is empty as the key embedded in a signature is never trusted
+    pub trusted_keys: Vec<VerifyingKey>,
+    pub require_signature: bool,
+    /// Encrypted imports are decrypted with these age identity files in addition to the key
+    /// material supplied with the import request
+    pub decryption_identity_paths: Vec<PathBuf>,
+    /// File containing a passphrase encrypted imports are decrypted with
+    pub decryption_passphrase_path: Option<PathBuf>,
 }
 
 #[derive(Debug)]
 pub struct FloxyLore {
     pub base_path: PathBuf,
+    /// Command validating the proxy configuration after it changed, e.g. `nginx -t`. Changes
+    /// are not validated if not set.
+    pub config_test_command: Option<Vec<String>>,
+    /// How the proxy is reloaded after its configuration changed, it is not reloaded if not set
+    pub reload: Option<conf::FloxyReload>,
+    /// Url floxy reaches the core at, e.g. to authorize requests to editors
+    pub core_url: String,
 }
 
 #[derive(Debug)]
 pub struct ConsoleLore {
     pub uri: http::Uri,
+    /// Interval in which the license is validated with the console
+    pub license_revalidation_interval: Duration,
+    /// Time after the last successful validation during which the license stays valid if the
+    /// console can not be reached
+    pub license_grace_period: Duration,
+    /// License files are only accepted if they are signed with one of these keys. Defaults to
+    /// the keys FLECS signs license files with, see
+    /// [default::console::LICENSE_VERIFYING_KEYS]. Offline activation with license files is not
+    /// possible if an empty list is configured.
+    pub license_verifying_keys: Vec<VerifyingKey>,
 }
 
 #[derive(Debug)]
@@ -216,11 +331,29 @@ pub struct AuthLore {
 #[derive(Debug)]
 pub struct ProviderLore {
     pub base_path: PathBuf,
+    /// Interval in which dependencies with fallback providers are checked for unavailable
+    /// providers
+    pub health_check_interval: Duration,
+}
+
+/// Device certificate authority and the server certificate issued by it
+#[derive(Debug)]
+pub struct CertificateLore {
+    pub base_path: PathBuf,
+    /// Interval in which the device certificates are checked for upcoming expiry and changed host
+    /// names
+    pub rotation_check_interval: Duration,
+    /// Device certificates are renewed if they expire within this duration
+    pub renew_before: Duration,
 }
 
 #[derive(Debug)]
 pub struct SystemLore {
     pub core_sbom_spdx_path: PathBuf,
+    /// Configured serial number, overrides the serial number read from the hardware
+    pub serial_number: Option<String>,
+    /// Vendor specific files the serial number is read from before DMI and the device tree
+    pub serial_number_paths: Vec<PathBuf>,
 }
 
 impl Lore {
@@ -257,9 +390,9 @@ impl Lore {
             import: ImportLore::from_conf_with_defaults(
                 conf.import.unwrap_or_default(),
                 &base_path,
-            ),
-            floxy: FloxyLore::from_conf_with_defaults(conf.floxy.unwrap_or_default()),
-            console: ConsoleLore::from_conf_with_defaults(conf.console.unwrap_or_default()),
+            )?,
+            floxy: FloxyLore::from_conf_with_defaults(conf.floxy.unwrap_or_default(), &listener),
+            console: ConsoleLore::from_conf_with_defaults(conf.console.unwrap_or_default())?,
             instance: InstanceLore::from_conf_with_defaults(
                 conf.instance.unwrap_or_default(),
                 &base_path,
@@ -283,7 +416,11 @@ impl Lore {
             provider: ProviderLore::from_conf_with_defaults(
                 conf.provider.unwrap_or_default(),
                 &base_path,
-            ),
+            )?,
+            certificate: CertificateLore::from_conf_with_defaults(
+                conf.certificate.unwrap_or_default(),
+                &base_path,
+            )?,
             tracing_filter,
             base_path,
             listener,
@@ -301,12 +438,41 @@ impl ExportLore {
             .timeout
             .map(Duration::from_secs)
             .unwrap_or_else(|| default::export::TIMEOUT);
-        Self { timeout, base_path }
+        Self {
+            timeout,
+            base_path,
+            signing_key_path: conf.signing_key_path,
+            encryption_recipients: conf.encryption_recipients.unwrap_or_default(),
+            encryption_passphrase_path: conf.encryption_passphrase_path,
+            schedules: Self::valid_schedules(conf.schedules.unwrap_or_default()),
+        }
+    }
+
+    fn valid_schedules(schedules: Vec<ExportSchedule>) -> Vec<ExportSchedule> {
+        let mut names = HashSet::new();
+        schedules
+            .into_iter()
+            .filter(|schedule| {
+                if !schedule.is_name_valid() {
+                    warn!(
+                        "Ignoring export schedule with invalid name '{}'",
+                        schedule.name
+                    );
+                    false
+                } else if !names.insert(schedule.name.clone()) {
+                    warn!("Ignoring duplicate export schedule '{}'", schedule.name);
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect()
     }
 }
 
 impl ImportLore {
-    pub fn from_conf_with_defaults(conf: conf::ImportConfig, base_path: &Path) -> Self {
+    /// Fails if a trusted key is invalid or if signatures are required without any trusted key
+    pub fn from_conf_with_defaults(conf: conf::ImportConfig, base_path: &Path) -> Result<Self> {
         let base_path = conf
             .base_path
             .unwrap_or_else(|| base_path.join(default::import::BASE_DIRECTORY_NAME));
@@ -314,22 +480,54 @@ impl ImportLore {
             .timeout
             .map(Duration::from_secs)
             .unwrap_or_else(|| default::import::TIMEOUT);
-        Self { timeout, base_path }
+        let trusted_keys = conf
+            .trusted_keys
+            .unwrap_or_default()
+            .iter()
+            .map(|key| {
+                parse_verifying_key(key).map_err(|e| Error::TrustedImportKey {
+                    key: key.clone(),
+                    reason: e.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let require_signature = conf.require_signature.unwrap_or_default();
+        if require_signature && trusted_keys.is_empty() {
+            return Err(Error::NoTrustedImportKeys);
+        }
+        Ok(Self {
+            timeout,
+            base_path,
+            trusted_keys,
+            require_signature,
+            decryption_identity_paths: conf.decryption_identity_paths.unwrap_or_default(),
+            decryption_passphrase_path: conf.decryption_passphrase_path,
+        })
     }
 }
 
 impl FloxyLore {
     #[cfg(test)]
-    pub fn from_conf_with_defaults(conf: conf::FloxyConfig) -> Self {
+    pub fn from_conf_with_defaults(conf: conf::FloxyConfig, listener: &Listener) -> Self {
         let base_path = conf
             .base_path
             .unwrap_or_else(|| PathBuf::from(default::floxy::BASE_DIRECTORY));
-        Self { base_path }
+        Self {
+            base_path,
+            config_test_command: conf.config_test_command,
+            reload: conf.reload,
+            core_url: listener.local_url(),
+        }
     }
     #[cfg(not(test))]
-    pub fn from_conf_with_defaults(_conf: conf::FloxyConfig) -> Self {
+    pub fn from_conf_with_defaults(conf: conf::FloxyConfig, listener: &Listener) -> Self {
         let base_path = PathBuf::from(default::floxy::BASE_DIRECTORY);
-        Self { base_path }
+        Self {
+            base_path,
+            config_test_command: conf.config_test_command,
+            reload: conf.reload,
+            core_url: listener.local_url(),
+        }
     }
 
     pub fn instance_editor_api_location(instance_id: InstanceId, port: u16) -> String {
@@ -340,6 +538,16 @@ impl FloxyLore {
         format!("/flecs/instances/{instance_id}/editor/{port}")
     }
 
+    /// Internal location authorizing requests to an editor, see [crate::jeweler::gem::manifest::single::EditorRoles]
+    pub fn instance_editor_auth_location(instance_id: InstanceId, port: u16) -> String {
+        format!("/flecs/auth/instances/{instance_id}/editor/{port}")
+    }
+
+    /// Location of the core checking whether a request to an editor is authorized
+    pub fn instance_editor_access_api_location(instance_id: InstanceId, port: u16) -> String {
+        format!("/v2/instances/{instance_id}/editor/{port}/access")
+    }
+
     pub fn auth_provider_location(instance_id: InstanceId) -> String {
         format!("/flecs/providers/auth/{instance_id}")
     }
@@ -352,15 +560,49 @@ impl FloxyLore {
         self.base_path
             .join(default::floxy::INSTANCE_CONFIGS_DIR_NAME)
     }
+
+    /// Certificates referenced by the generated configs, relative to the proxy configuration
+    /// directory they are located at `conf.d/certs`
+    pub fn certificate_path(&self) -> PathBuf {
+        self.base_path.join(default::floxy::CERTIFICATES_DIR_NAME)
+    }
 }
 
 impl ConsoleLore {
-    pub fn from_conf_with_defaults(conf: conf::ConsoleConfig) -> Self {
+    /// Fails if a license verifying key is invalid or [Self::license_revalidation_interval] is zero
+    pub fn from_conf_with_defaults(conf: conf::ConsoleConfig) -> Result<Self> {
         let uri = conf
             .uri
             .map(|wrapper| wrapper.0)
             .unwrap_or_else(default::console::uri);
-        Self { uri }
+        let license_revalidation_interval = conf
+            .license_revalidation_interval
+            .map(Duration::from_secs)
+            .unwrap_or(default::console::LICENSE_REVALIDATION_INTERVAL);
+        if license_revalidation_interval.is_zero() {
+            return Err(Error::ZeroInterval("console.license_revalidation_interval"));
+        }
+        let license_grace_period = conf
+            .license_grace_period
+            .map(Duration::from_secs)
+            .unwrap_or(default::console::LICENSE_GRACE_PERIOD);
+        let license_verifying_keys = conf
+            .license_verifying_keys
+            .unwrap_or_else(default::console::license_verifying_keys)
+            .iter()
+            .map(|key| {
+                parse_verifying_key(key).map_err(|e| Error::LicenseVerifyingKey {
+                    key: key.clone(),
+                    reason: e.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            uri,
+            license_revalidation_interval,
+            license_grace_period,
+            license_verifying_keys,
+        })
     }
 }
 
@@ -378,6 +620,10 @@ impl InstanceLore {
     pub fn instance_workdir_path(&self, instance_id: &impl AsRef<str>) -> PathBuf {
         self.base_path.join(instance_id.as_ref()).join("work")
     }
+
+    pub fn instance_certificates_path(&self, instance_id: &impl AsRef<str>) -> PathBuf {
+        self.base_path.join(instance_id.as_ref()).join("certs")
+    }
 }
 
 impl NetworkLore {
@@ -458,11 +704,90 @@ impl AuthLore {
 }
 
 impl ProviderLore {
-    pub fn from_conf_with_defaults(conf: conf::ProviderConfig, base_path: &Path) -> Self {
+    /// Fails if [Self::health_check_interval] is zero
+    pub fn from_conf_with_defaults(conf: conf::ProviderConfig, base_path: &Path) -> Result<Self> {
         let base_path = conf
             .base_path
             .unwrap_or_else(|| base_path.join(default::provider::BASE_DIRECTORY_NAME));
-        Self { base_path }
+        let health_check_interval = conf
+            .health_check_interval
+            .map(Duration::from_secs)
+            .unwrap_or(default::provider::HEALTH_CHECK_INTERVAL);
+        if health_check_interval.is_zero() {
+            return Err(Error::ZeroInterval("provider.health_check_interval"));
+        }
+        Ok(Self {
+            base_path,
+            health_check_interval,
+        })
+    }
+}
+
+impl CertificateLore {
+    /// Fails if [Self::rotation_check_interval] is zero
+    pub fn from_conf_with_defaults(
+        conf: conf::CertificateConfig,
+        base_path: &Path,
+    ) -> Result<Self> {
+        let base_path = conf
+            .base_path
+            .unwrap_or_else(|| base_path.join(default::certificate::BASE_DIRECTORY_NAME));
+        let rotation_check_interval = conf
+            .rotation_check_interval
+            .map(Duration::from_secs)
+            .unwrap_or(default::certificate::ROTATION_CHECK_INTERVAL);
+        if rotation_check_interval.is_zero() {
+            return Err(Error::ZeroInterval("certificate.rotation_check_interval"));
+        }
+        let renew_before = conf
+            .renew_before
+            .map(Duration::from_secs)
+            .unwrap_or(default::certificate::RENEW_BEFORE);
+        Ok(Self {
+            base_path,
+            rotation_check_interval,
+            renew_before,
+        })
+    }
+
+    /// The private key of the device certificate authority is kept outside of [Self::tls_path]
+    pub fn ca_private_key_path(&self) -> PathBuf {
+        self.base_path
+            .join(default::certificate::CA_PRIVATE_KEY_FILE_NAME)
+    }
+
+    /// Directory with the public device certificate authority and the server certificate with
+    /// its key, shared with floxy
+    pub fn tls_path(&self) -> PathBuf {
+        self.base_path
+            .join(default::certificate::TLS_DIRECTORY_NAME)
+    }
+
+    /// Directory containing the TLS material of all instances, only accessible by the owner
+    pub fn instances_tls_path(&self) -> PathBuf {
+        self.base_path
+            .join(default::certificate::INSTANCES_DIRECTORY_NAME)
+    }
+
+    /// Directory mounted into instances of apps that need TLS material, contains the public
+    /// device certificate authority and a certificate with its key issued for the instance
+    pub fn instance_tls_path(&self, instance_id: &impl AsRef<str>) -> PathBuf {
+        self.instances_tls_path().join(instance_id.as_ref())
+    }
+
+    pub fn ca_certificate_path(&self) -> PathBuf {
+        self.tls_path()
+            .join(default::certificate::CA_CERTIFICATE_FILE_NAME)
+    }
+
+    pub fn server_certificate_path(&self) -> PathBuf {
+        self.tls_path()
+            .join(default::certificate::SERVER_CERTIFICATE_FILE_NAME)
+    }
+
+    pub fn server_private_key_path(&self) -> PathBuf {
+        self.tls_path()
+            .join(default::certificate::SERVER_PRIVATE_KEY_FILE_NAME)
     }
 }
 
@@ -473,6 +798,8 @@ impl SystemLore {
             .unwrap_or_else(default::system::sbom_spdx_file_path_path);
         Self {
             core_sbom_spdx_path,
+            serial_number: conf.serial_number,
+            serial_number_paths: conf.serial_number_paths.unwrap_or_default(),
         }
     }
 }
@@ -486,6 +813,7 @@ pub fn test_lore(
     conf.merge(crate::lore::conf::FlecsConfig {
         floxy: Some(crate::lore::conf::FloxyConfig {
             base_path: Some(base_path.join("floxy")),
+            ..Default::default()
         }),
         base_path: Some(base_path),
         ..crate::lore::conf::FlecsConfig::default()
@@ -495,6 +823,7 @@ pub fn test_lore(
 
 pub const MAX_SUPPORTED_APP_MANIFEST_VERSION: &str = "3.0.0";
 pub const API_VERSION: &str = env!("FLECS_API_VERSION");
+pub const FLECS_VERSION: &str = env!("FLECS_VERSION");
 pub const CORE_VERSION: &str = concat!(env!("FLECS_FULL_VERSION"), "-", env!("FLECS_GIT_SHA"));
 
 #[cfg(test)]
@@ -503,6 +832,13 @@ mod tests {
     use crate::forge::serde::{EnvFilterWrapper, UriWrapper};
     use std::str::FromStr;
 
+    fn test_listener() -> Listener {
+        Listener::TCP {
+            port: default::FLECSD_PORT,
+            bind_address: None,
+        }
+    }
+
     #[test]
     fn from_conf_unsupported_version() {
         const VERSION: u8 = 2;
@@ -623,6 +959,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn export_lore_from_conf_signing_key_path() {
+        let signing_key_path = PathBuf::from("/some/key.pem");
+        let conf = conf::ExportConfig {
+            signing_key_path: Some(signing_key_path.clone()),
+            ..conf::ExportConfig::default()
+        };
+        assert_eq!(
+            ExportLore::from_conf_with_defaults(conf, Path::new("/")).signing_key_path,
+            Some(signing_key_path)
+        );
+    }
+
+    fn export_schedule(name: &str) -> ExportSchedule {
+        ExportSchedule {
+            name: name.to_string(),
+            cron: CronSchedule::from_str("@daily").unwrap(),
+            apps: None,
+            instances: None,
+            retention: 3,
+            target: None,
+        }
+    }
+
+    #[test]
+    fn export_lore_from_conf_schedules() {
+        let conf = conf::ExportConfig {
+            schedules: Some(vec![
+                export_schedule("nightly"),
+                export_schedule("nightly"),
+                export_schedule("../invalid"),
+                export_schedule("weekly_backup-2"),
+            ]),
+            ..conf::ExportConfig::default()
+        };
+        assert_eq!(
+            ExportLore::from_conf_with_defaults(conf, Path::new("/")).schedules,
+            vec![
+                export_schedule("nightly"),
+                export_schedule("weekly_backup-2")
+            ]
+        );
+    }
+
+    #[test]
+    fn deserialize_export_schedule() {
+        let schedule: ExportSchedule = toml::from_str(
+            r#"
+            name = "nightly"
+            cron = "0 3 * * *"
+            instances = ["01234567"]
+            [target]
+            type = "local"
+            path = "/mnt/backup"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(schedule.cron.to_string(), "0 3 * * *");
+        assert_eq!(schedule.apps, None);
+        assert_eq!(schedule.instances, Some(vec![InstanceId::new(0x01234567)]));
+        assert_eq!(schedule.retention, default::export::SCHEDULE_RETENTION);
+        assert!(matches!(schedule.target, Some(UploadTarget::Local(_))));
+    }
+
+    #[test]
+    fn export_lore_from_conf_encryption() {
+        let conf = conf::ExportConfig {
+            encryption_recipients: Some(vec!["age1recipient".to_string()]),
+            encryption_passphrase_path: Some(PathBuf::from("/some/passphrase")),
+            ..conf::ExportConfig::default()
+        };
+        let lore = ExportLore::from_conf_with_defaults(conf, Path::new("/"));
+        assert_eq!(
+            lore.encryption_recipients,
+            vec!["age1recipient".to_string()]
+        );
+        assert_eq!(
+            lore.encryption_passphrase_path,
+            Some(PathBuf::from("/some/passphrase"))
+        );
+    }
+
+    #[test]
+    fn import_lore_from_conf_decryption() {
+        let conf = conf::ImportConfig {
+            decryption_identity_paths: Some(vec![PathBuf::from("/some/identity")]),
+            decryption_passphrase_path: Some(PathBuf::from("/some/passphrase")),
+            ..conf::ImportConfig::default()
+        };
+        let lore = ImportLore::from_conf_with_defaults(conf, Path::new("/")).unwrap();
+        assert_eq!(
+            lore.decryption_identity_paths,
+            vec![PathBuf::from("/some/identity")]
+        );
+        assert_eq!(
+            lore.decryption_passphrase_path,
+            Some(PathBuf::from("/some/passphrase"))
+        );
+    }
+
+    #[test]
+    fn import_lore_from_conf_trusted_keys() {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[1; 32]).verifying_key();
+        let conf = conf::ImportConfig {
+            trusted_keys: Some(vec![crate::relic::integrity::encode_verifying_key(&key)]),
+            require_signature: Some(true),
+            ..conf::ImportConfig::default()
+        };
+        let lore = ImportLore::from_conf_with_defaults(conf, Path::new("/")).unwrap();
+        assert_eq!(lore.trusted_keys, vec![key]);
+        assert!(lore.require_signature);
+    }
+
+    #[test]
+    fn import_lore_from_conf_invalid_trusted_key() {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[1; 32]).verifying_key();
+        let conf = conf::ImportConfig {
+            trusted_keys: Some(vec![
+                crate::relic::integrity::encode_verifying_key(&key),
+                "invalid".to_string(),
+            ]),
+            ..conf::ImportConfig::default()
+        };
+        assert!(matches!(
+            ImportLore::from_conf_with_defaults(conf, Path::new("/")),
+            Err(Error::TrustedImportKey { key, .. }) if key == "invalid"
+        ));
+    }
+
+    #[test]
+    fn import_lore_from_conf_require_signature_without_trusted_keys() {
+        let conf = conf::ImportConfig {
+            require_signature: Some(true),
+            ..conf::ImportConfig::default()
+        };
+        assert!(matches!(
+            ImportLore::from_conf_with_defaults(conf, Path::new("/")),
+            Err(Error::NoTrustedImportKeys)
+        ));
+    }
+
+    #[test]
+    fn import_lore_from_conf_trusted_keys_default() {
+        let lore =
+            ImportLore::from_conf_with_defaults(conf::ImportConfig::default(), Path::new("/"))
+                .unwrap();
+        assert!(lore.trusted_keys.is_empty());
+        assert!(!lore.require_signature);
+    }
+
     #[test]
     fn import_lore_from_conf_base_path() {
         let base_path = PathBuf::from("/some/base/path");
@@ -631,7 +1117,9 @@ mod tests {
             ..conf::ImportConfig::default()
         };
         assert_eq!(
-            ImportLore::from_conf_with_defaults(conf, Path::new("/")).base_path,
+            ImportLore::from_conf_with_defaults(conf, Path::new("/"))
+                .unwrap()
+                .base_path,
             base_path
         );
     }
@@ -641,7 +1129,9 @@ mod tests {
         let base_path = PathBuf::from("/some/base/path");
         let conf = conf::ImportConfig::default();
         assert_eq!(
-            ImportLore::from_conf_with_defaults(conf, &base_path).base_path,
+            ImportLore::from_conf_with_defaults(conf, &base_path)
+                .unwrap()
+                .base_path,
             base_path.join(default::import::BASE_DIRECTORY_NAME)
         );
     }
@@ -654,7 +1144,9 @@ mod tests {
             ..conf::ImportConfig::default()
         };
         assert_eq!(
-            ImportLore::from_conf_with_defaults(conf, Path::new("/")).timeout,
+            ImportLore::from_conf_with_defaults(conf, Path::new("/"))
+                .unwrap()
+                .timeout,
             Duration::from_secs(TIMEOUT)
         );
     }
@@ -663,7 +1155,9 @@ mod tests {
     fn import_lore_from_conf_timeout_default() {
         let conf = conf::ImportConfig::default();
         assert_eq!(
-            ImportLore::from_conf_with_defaults(conf, Path::new("/")).timeout,
+            ImportLore::from_conf_with_defaults(conf, Path::new("/"))
+                .unwrap()
+                .timeout,
             default::import::TIMEOUT,
         );
     }
@@ -673,9 +1167,10 @@ mod tests {
         let base_path = PathBuf::from("/some/base/path");
         let conf = conf::FloxyConfig {
             base_path: Some(base_path.clone()),
+            ..conf::FloxyConfig::default()
         };
         assert_eq!(
-            FloxyLore::from_conf_with_defaults(conf).base_path,
+            FloxyLore::from_conf_with_defaults(conf, &test_listener()).base_path,
             base_path
         );
     }
@@ -685,11 +1180,67 @@ mod tests {
         let base_path = PathBuf::from("/some/base/path");
         let conf = conf::FloxyConfig::default();
         assert_eq!(
-            FloxyLore::from_conf_with_defaults(conf).base_path,
+            FloxyLore::from_conf_with_defaults(conf, &test_listener()).base_path,
             base_path.join(default::floxy::BASE_DIRECTORY)
         );
     }
 
+    #[test]
+    fn floxy_lore_from_conf_commands() {
+        let conf = conf::FloxyConfig {
+            config_test_command: Some(vec!["nginx".to_string(), "-t".to_string()]),
+            reload: Some(conf::FloxyReload::PidFile(PathBuf::from("/run/nginx.pid"))),
+            ..conf::FloxyConfig::default()
+        };
+        let lore = FloxyLore::from_conf_with_defaults(conf, &test_listener());
+        assert_eq!(
+            lore.config_test_command,
+            Some(vec!["nginx".to_string(), "-t".to_string()])
+        );
+        assert_eq!(
+            lore.reload,
+            Some(conf::FloxyReload::PidFile(PathBuf::from("/run/nginx.pid")))
+        );
+    }
+
+    #[test]
+    fn floxy_lore_from_conf_commands_default() {
+        let lore =
+            FloxyLore::from_conf_with_defaults(conf::FloxyConfig::default(), &test_listener());
+        assert!(lore.config_test_command.is_none());
+        assert!(lore.reload.is_none());
+    }
+
+    #[test]
+    fn floxy_lore_from_conf_core_url() {
+        let lore = FloxyLore::from_conf_with_defaults(
+            conf::FloxyConfig::default(),
+            &Listener::UnixSocket(PathBuf::from("/run/flecs/flecsd.sock")),
+        );
+        assert_eq!(lore.core_url, "http://unix:/run/flecs/flecsd.sock:");
+    }
+
+    #[test]
+    fn listener_local_url() {
+        assert_eq!(test_listener().local_url(), "http://127.0.0.1:8951");
+        assert_eq!(
+            Listener::TCP {
+                port: 80,
+                bind_address: Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+            }
+            .local_url(),
+            "http://[::1]:80"
+        );
+        assert_eq!(
+            Listener::TCP {
+                port: 8080,
+                bind_address: Some(IpAddr::V4(Ipv4Addr::new(10, 20, 30, 40))),
+            }
+            .local_url(),
+            "http://10.20.30.40:8080"
+        );
+    }
+
     #[test]
     fn instance_lore_from_conf_base_path() {
         let base_path = PathBuf::from("/some/base/path");
@@ -805,19 +1356,178 @@ mod tests {
         let uri = http::Uri::from_static("http://cloud.my/console");
         let conf = conf::ConsoleConfig {
             uri: Some(UriWrapper(uri.clone())),
+            ..conf::ConsoleConfig::default()
         };
-        assert_eq!(ConsoleLore::from_conf_with_defaults(conf).uri, uri);
+        assert_eq!(ConsoleLore::from_conf_with_defaults(conf).unwrap().uri, uri);
     }
 
     #[test]
     fn console_lore_from_conf_uri_default() {
         let conf = conf::ConsoleConfig::default();
         assert_eq!(
-            ConsoleLore::from_conf_with_defaults(conf).uri,
+            ConsoleLore::from_conf_with_defaults(conf).unwrap().uri,
             default::console::uri()
         );
     }
 
+    #[test]
+    fn console_lore_from_conf_license_intervals() {
+        let conf = conf::ConsoleConfig {
+            license_revalidation_interval: Some(60),
+            license_grace_period: Some(3600),
+            ..conf::ConsoleConfig::default()
+        };
+        let lore = ConsoleLore::from_conf_with_defaults(conf).unwrap();
+        assert_eq!(lore.license_revalidation_interval, Duration::from_secs(60));
+        assert_eq!(lore.license_grace_period, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn console_lore_from_conf_license_intervals_default() {
+        let lore = ConsoleLore::from_conf_with_defaults(conf::ConsoleConfig::default()).unwrap();
+        assert_eq!(
+            lore.license_revalidation_interval,
+            default::console::LICENSE_REVALIDATION_INTERVAL
+        );
+        assert_eq!(
+            lore.license_grace_period,
+            default::console::LICENSE_GRACE_PERIOD
+        );
+        assert_eq!(
+            lore.license_verifying_keys.len(),
+            default::console::LICENSE_VERIFYING_KEYS.len()
+        );
+    }
+
+    #[test]
+    fn console_lore_from_conf_license_verifying_keys() {
+        let key = ed25519_dalek::SigningKey::from_bytes(&[1; 32]).verifying_key();
+        let conf = conf::ConsoleConfig {
+            license_verifying_keys: Some(vec![crate::relic::integrity::encode_verifying_key(&key)]),
+            ..conf::ConsoleConfig::default()
+        };
+        let lore = ConsoleLore::from_conf_with_defaults(conf).unwrap();
+        assert_eq!(lore.license_verifying_keys, vec![key]);
+    }
+
+    #[test]
+    fn console_lore_from_conf_invalid_license_verifying_key() {
+        let conf = conf::ConsoleConfig {
+            license_verifying_keys: Some(vec!["invalid".to_string()]),
+            ..conf::ConsoleConfig::default()
+        };
+        assert!(matches!(
+            ConsoleLore::from_conf_with_defaults(conf),
+            Err(Error::LicenseVerifyingKey { key, .. }) if key == "invalid"
+        ));
+    }
+
+    #[test]
+    fn console_lore_from_conf_zero_license_revalidation_interval() {
+        let conf = conf::ConsoleConfig {
+            license_revalidation_interval: Some(0),
+            ..conf::ConsoleConfig::default()
+        };
+        assert!(matches!(
+            ConsoleLore::from_conf_with_defaults(conf),
+            Err(Error::ZeroInterval("console.license_revalidation_interval"))
+        ));
+    }
+
+    #[test]
+    fn provider_lore_from_conf_health_check_interval() {
+        let conf = conf::ProviderConfig {
+            health_check_interval: Some(10),
+            ..conf::ProviderConfig::default()
+        };
+        let lore = ProviderLore::from_conf_with_defaults(conf, Path::new("/base")).unwrap();
+        assert_eq!(lore.health_check_interval, Duration::from_secs(10));
+        assert_eq!(lore.base_path, PathBuf::from("/base/providers"));
+    }
+
+    #[test]
+    fn provider_lore_from_conf_health_check_interval_default() {
+        let lore =
+            ProviderLore::from_conf_with_defaults(conf::ProviderConfig::default(), Path::new("/"))
+                .unwrap();
+        assert_eq!(
+            lore.health_check_interval,
+            default::provider::HEALTH_CHECK_INTERVAL
+        );
+    }
+
+    #[test]
+    fn provider_lore_from_conf_zero_health_check_interval() {
+        let conf = conf::ProviderConfig {
+            health_check_interval: Some(0),
+            ..conf::ProviderConfig::default()
+        };
+        assert!(matches!(
+            ProviderLore::from_conf_with_defaults(conf, Path::new("/base")),
+            Err(Error::ZeroInterval("provider.health_check_interval"))
+        ));
+    }
+
+    #[test]
+    fn certificate_lore_from_conf() {
+        let conf = conf::CertificateConfig {
+            base_path: Some(PathBuf::from("/certs")),
+            rotation_check_interval: Some(10),
+            renew_before: Some(20),
+        };
+        let lore = CertificateLore::from_conf_with_defaults(conf, Path::new("/base")).unwrap();
+        assert_eq!(lore.base_path, PathBuf::from("/certs"));
+        assert_eq!(lore.rotation_check_interval, Duration::from_secs(10));
+        assert_eq!(lore.renew_before, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn certificate_lore_from_conf_default() {
+        let lore = CertificateLore::from_conf_with_defaults(
+            conf::CertificateConfig::default(),
+            Path::new("/base"),
+        )
+        .unwrap();
+        assert_eq!(lore.base_path, PathBuf::from("/base/certificates"));
+        assert_eq!(
+            lore.rotation_check_interval,
+            default::certificate::ROTATION_CHECK_INTERVAL
+        );
+        assert_eq!(lore.renew_before, default::certificate::RENEW_BEFORE);
+        assert_eq!(
+            lore.ca_private_key_path(),
+            PathBuf::from("/base/certificates/ca.key")
+        );
+        assert_eq!(
+            lore.ca_certificate_path(),
+            PathBuf::from("/base/certificates/tls/ca.crt")
+        );
+        assert_eq!(
+            lore.server_certificate_path(),
+            PathBuf::from("/base/certificates/tls/server.crt")
+        );
+        assert_eq!(
+            lore.server_private_key_path(),
+            PathBuf::from("/base/certificates/tls/server.key")
+        );
+        assert_eq!(
+            lore.instance_tls_path(&"00001234"),
+            PathBuf::from("/base/certificates/instances/00001234")
+        );
+    }
+
+    #[test]
+    fn certificate_lore_from_conf_zero_interval() {
+        let conf = conf::CertificateConfig {
+            rotation_check_interval: Some(0),
+            ..conf::CertificateConfig::default()
+        };
+        assert!(matches!(
+            CertificateLore::from_conf_with_defaults(conf, Path::new("/base")),
+            Err(Error::ZeroInterval(_))
+        ));
+    }
+
     #[test]
     fn network_lore_from_conf_default_network_name() {
         const NETWORK_NAME: &str = "TESTNET";
@@ -2,9 +2,9 @@ use crate::forge::serde::EnvFilterWrapper;
 #[cfg(feature = "auth")]
 use crate::lore::conf::AuthConfig;
 use crate::lore::conf::{
-    AppConfig, ConsoleConfig, DeploymentConfig, ExportConfig, FlecsConfig, FloxyConfig,
-    ImportConfig, InstanceConfig, Listener, ManifestConfig, NetworkConfig, ProviderConfig,
-    SecretConfig, SystemConfig,
+    AppConfig, CertificateConfig, ConsoleConfig, DeploymentConfig, ExportConfig, FlecsConfig,
+    FloxyConfig, ImportConfig, InstanceConfig, Listener, ManifestConfig, NetworkConfig,
+    ProviderConfig, SecretConfig, SystemConfig,
 };
 use crate::relic::var;
 use crate::relic::var::VarReader;
@@ -94,7 +94,8 @@ impl FlecsConfig {
             #[cfg(feature = "auth")]
             auth: AuthConfig::from_var_reader(reader)?,
             provider: ProviderConfig::from_var_reader(reader),
-            system: SystemConfig::from_var_reader(reader),
+            certificate: CertificateConfig::from_var_reader(reader),
+            system: SystemConfig::from_var_reader(reader)?,
         })
     }
 }
@@ -122,7 +123,11 @@ pub mod export {
             let base_path = base_path(reader);
             let timeout = timeout(reader)?.as_ref().map(Duration::as_secs);
             if base_path.is_some() && timeout.is_some() {
-                Ok(Some(Self { base_path, timeout }))
+                Ok(Some(Self {
+                    base_path,
+                    timeout,
+                    ..Self::default()
+                }))
             } else {
                 Ok(None)
             }
@@ -152,7 +157,11 @@ pub mod import {
             let base_path = base_path(reader);
             let timeout = timeout(reader)?.as_ref().map(Duration::as_secs);
             if base_path.is_some() && timeout.is_some() {
-                Ok(Some(Self { base_path, timeout }))
+                Ok(Some(Self {
+                    base_path,
+                    timeout,
+                    ..Self::default()
+                }))
             } else {
                 Ok(None)
             }
@@ -175,6 +184,7 @@ pub mod floxy {
         pub fn from_var_reader(reader: &impl VarReader) -> Option<Self> {
             base_path(reader).map(|base_path| Self {
                 base_path: Some(base_path),
+                ..Self::default()
             })
         }
     }
@@ -195,7 +205,10 @@ pub mod console {
     impl ConsoleConfig {
         pub fn from_var_reader(reader: &impl VarReader) -> Result<Option<Self>> {
             let uri = uri(reader)?.map(UriWrapper);
-            Ok(uri.map(|uri| Self { uri: Some(uri) }))
+            Ok(uri.map(|uri| Self {
+                uri: Some(uri),
+                ..Self::default()
+            }))
         }
     }
 }
@@ -383,27 +396,82 @@ pub mod provider {
             let base_path = base_path(reader);
             base_path.map(|base_path| Self {
                 base_path: Some(base_path),
+                ..Self::default()
+            })
+        }
+    }
+}
+
+pub mod certificate {
+    use crate::lore::conf::CertificateConfig;
+    use crate::relic::var::VarReader;
+    use std::path::PathBuf;
+
+    const BASE_PATH: &str = "FLECS_CORE_CERTIFICATE_BASE_PATH";
+
+    fn base_path(reader: &impl VarReader) -> Option<PathBuf> {
+        reader.read_path(BASE_PATH)
+    }
+
+    impl CertificateConfig {
+        pub fn from_var_reader(reader: &impl VarReader) -> Option<Self> {
+            let base_path = base_path(reader);
+            base_path.map(|base_path| Self {
+                base_path: Some(base_path),
+                ..Self::default()
             })
         }
     }
 }
 
 pub mod system {
+    use super::Result;
     use crate::lore::conf::SystemConfig;
     use crate::relic::var::VarReader;
     use std::path::PathBuf;
     const SBOM_SPDX_PATH: &str = "FLECS_CORE_SBOM_SPDX_PATH";
+    const SERIAL_NUMBER: &str = "FLECS_CORE_SERIAL_NUMBER";
 
     fn sbom_spdx_path(reader: &impl VarReader) -> Option<PathBuf> {
         reader.read_path(SBOM_SPDX_PATH)
     }
 
+    fn serial_number(reader: &impl VarReader) -> Result<Option<String>> {
+        Ok(reader.read_var(SERIAL_NUMBER)?)
+    }
+
     impl SystemConfig {
-        pub fn from_var_reader(reader: &impl VarReader) -> Option<Self> {
-            let core_sbom_spdx_path = sbom_spdx_path(reader)?;
-            Some(Self {
-                core_sbom_spdx_path: Some(core_sbom_spdx_path),
-            })
+        pub fn from_var_reader(reader: &impl VarReader) -> Result<Option<Self>> {
+            let core_sbom_spdx_path = sbom_spdx_path(reader);
+            let serial_number = serial_number(reader)?;
+            if core_sbom_spdx_path.is_none() && serial_number.is_none() {
+                return Ok(None);
+            }
+            Ok(Some(Self {
+                core_sbom_spdx_path,
+                serial_number,
+                serial_number_paths: None,
+            }))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::relic::var::test::MockVarReader;
+
+        #[test]
+        fn from_var_reader_none() {
+            let reader = &MockVarReader::new();
+            assert!(SystemConfig::from_var_reader(reader).unwrap().is_none());
+        }
+
+        #[test]
+        fn from_var_reader_serial_number() {
+            let reader = &MockVarReader::from_var((SERIAL_NUMBER, "SN-1234"));
+            let config = SystemConfig::from_var_reader(reader).unwrap().unwrap();
+            assert_eq!(config.serial_number.as_deref(), Some("SN-1234"));
+            assert!(config.core_sbom_spdx_path.is_none());
         }
     }
 }
@@ -1,4 +1,6 @@
 pub use super::{Error, Result};
+use crate::sorcerer::importius::ImportPlan;
+use crate::sorcerer::systemus::PruneReport;
 use crate::vault::pouch::instance::InstanceId;
 use futures_util::StreamExt;
 use futures_util::future::BoxFuture;
@@ -35,6 +37,8 @@ pub enum QuestResult {
     None,
     InstanceId(InstanceId),
     ExportId(String),
+    ImportPlan(ImportPlan),
+    PruneReport(PruneReport),
 }
 
 impl QuestResult {
@@ -43,6 +47,8 @@ impl QuestResult {
             Self::None => None,
             Self::InstanceId(id) => Some(id.to_string()),
             Self::ExportId(id) => Some(id.clone()),
+            Self::ImportPlan(plan) => serde_json::to_string(plan).ok(),
+            Self::PruneReport(report) => serde_json::to_string(report).ok(),
         }
     }
 }
@@ -1,8 +1,53 @@
 pub use super::Result;
+use crate::relic::integrity::encode_hex;
 use async_compression::tokio::bufread::GzipDecoder;
 use flecstract::tar::{archive, archive_single_file_as, extract, extract_single_file_as};
+use futures_util::StreamExt;
+use futures_util::future::BoxFuture;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tokio::io::BufReader;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream};
+
+/// Size of the buffer between a streamed archive entry and its [EntryTarget::Consumer]
+const CONSUMER_BUFFER_SIZE: usize = 64 * 1024;
+
+/// File entry of an archive which was not extracted, see [extract_deferring]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeferredEntry {
+    /// Path of the entry inside the archive
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Receives the content of a streamed archive entry, see [stream_entries]
+pub type EntryConsumer = Box<dyn FnOnce(DuplexStream) -> BoxFuture<'static, Result<()>> + Send>;
+
+/// File entry of an archive, see [list_entries]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedEntry {
+    /// Path of the entry inside the archive
+    pub path: PathBuf,
+    pub size: u64,
+    /// Content of the entry if it was requested
+    pub content: Option<Vec<u8>>,
+}
+
+/// Header of an archive entry, see [read_headers]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryHeader {
+    /// Path of the entry inside the archive
+    pub path: PathBuf,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+pub enum EntryTarget {
+    /// The entry is written to the given path
+    File(PathBuf),
+    /// The entry is piped into the consumer
+    Consumer(EntryConsumer),
+}
 
 pub async fn archive_to_file(src: &Path, dst: &Path, follow_symlinks: bool) -> Result<()> {
     let dst = dst.to_path_buf();
@@ -60,6 +105,162 @@ pub async fn decompress_from_file(src: impl Into<PathBuf>, dst: impl Into<PathBu
     Ok(())
 }
 
+async fn open_archive(src: &Path) -> Result<tokio_tar::Archive<Box<dyn AsyncRead + Send + Unpin>>> {
+    let file = BufReader::new(tokio::fs::File::open(src).await?);
+    let reader: Box<dyn AsyncRead + Send + Unpin> = if src.extension() == Some("gz".as_ref()) {
+        Box::new(GzipDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+    Ok(tokio_tar::Archive::new(reader))
+}
+
+/// Extracts the tar archive at `src`, which is decompressed if its extension is 'gz', to `dst`
+/// except the file entries `defer` returns true for. The deferred entries are returned and can be
+/// processed later on with [stream_entries] without extracting them.
+pub async fn extract_deferring(
+    src: &Path,
+    dst: &Path,
+    defer: impl Fn(&Path) -> bool,
+) -> Result<Vec<DeferredEntry>> {
+    tokio::fs::create_dir_all(dst).await?;
+    let mut archive = open_archive(src).await?;
+    let mut entries = archive.entries()?;
+    let mut deferred = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        if entry.header().entry_type().is_file() && defer(&path) {
+            deferred.push(DeferredEntry {
+                path,
+                size: entry.header().size()?,
+            });
+        } else {
+            entry.unpack_in(dst).await?;
+        }
+    }
+    Ok(deferred)
+}
+
+/// Lists the file entries of the archive at `src`, which is decompressed if its extension is
+/// 'gz', without extracting it. The content of the entries `read` returns true for (called with
+/// the path and size of the entry) is read into memory.
+pub async fn list_entries(
+    src: &Path,
+    read: impl Fn(&Path, u64) -> bool,
+) -> Result<Vec<ListedEntry>> {
+    let mut archive = open_archive(src).await?;
+    let mut entries = archive.entries()?;
+    let mut listed = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_path_buf();
+        let size = entry.header().size()?;
+        let content = if read(&path, size) {
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).await?;
+            Some(content)
+        } else {
+            None
+        };
+        listed.push(ListedEntry {
+            path,
+            size,
+            content,
+        });
+    }
+    Ok(listed)
+}
+
+/// Reads the headers of all entries of the tar archive read from `src`. The content of the entries
+/// is skipped without being buffered.
+pub async fn read_headers(src: impl AsyncRead + Unpin + Send) -> Result<Vec<EntryHeader>> {
+    let mut archive = tokio_tar::Archive::new(src);
+    let mut entries = archive.entries()?;
+    let mut headers = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        let is_dir = entry.header().entry_type().is_dir();
+        headers.push(EntryHeader {
+            path: entry.path()?.to_path_buf(),
+            size: if is_dir { 0 } else { entry.header().size()? },
+            is_dir,
+        });
+    }
+    Ok(headers)
+}
+
+/// Streams the file entries of the archive at `src` which are contained in `targets` to their
+/// target, all other entries are skipped. Consumers are started when their entry is reached and
+/// awaited before the next entry is processed. Returns the hex encoded sha256 of every streamed
+/// entry.
+pub async fn stream_entries(
+    src: &Path,
+    mut targets: HashMap<PathBuf, EntryTarget>,
+) -> Result<HashMap<PathBuf, String>> {
+    let mut archive = open_archive(src).await?;
+    let mut entries = archive.entries()?;
+    let mut checksums = HashMap::new();
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_path_buf();
+        let Some(target) = targets.remove(&path) else {
+            continue;
+        };
+        let checksum = match target {
+            EntryTarget::File(dst) => {
+                if let Some(parent) = dst.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                let mut file = tokio::fs::File::create(&dst).await?;
+                let checksum = copy_with_checksum(&mut entry, &mut file).await?;
+                file.flush().await?;
+                checksum
+            }
+            EntryTarget::Consumer(consumer) => {
+                let (mut writer, reader) = tokio::io::duplex(CONSUMER_BUFFER_SIZE);
+                let consumer = tokio::spawn(consumer(reader));
+                let copy_result = copy_with_checksum(&mut entry, &mut writer).await;
+                let shutdown_result = writer.shutdown().await;
+                drop(writer);
+                // A failing consumer closes its end early, its error is more meaningful
+                consumer.await??;
+                shutdown_result?;
+                copy_result?
+            }
+        };
+        checksums.insert(path, checksum);
+    }
+    if let Some(missing) = targets.keys().next() {
+        anyhow::bail!("Entry {missing:?} not found in archive {src:?}");
+    }
+    Ok(checksums)
+}
+
+async fn copy_with_checksum<R, W>(reader: &mut R, writer: &mut W) -> Result<String>
+where
+    R: AsyncRead + Unpin,
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let len = reader.read(&mut buffer).await?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+        writer.write_all(&buffer[..len]).await?;
+    }
+    Ok(encode_hex(&hasher.finalize()))
+}
+
 pub async fn extract_from_memory(src: Vec<u8>, dst: &Path) -> Result<()> {
     let dst = dst.to_path_buf();
     // Potentially long synchronously blocking calls should be wrapped with tokio::task::spawn_blocking
@@ -73,3 +274,193 @@ pub async fn extract_single_file_from_memory_as(src: Vec<u8>, dst: &Path) -> Res
     tokio::task::spawn_blocking(move || extract_single_file_as(src.as_slice(), dst)).await??;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relic::integrity::sha256_file;
+    use futures_util::FutureExt;
+    use testdir::testdir;
+
+    async fn prepare_archive(path: &Path) -> PathBuf {
+        let src = path.join("src");
+        std::fs::create_dir_all(src.join("apps")).unwrap();
+        std::fs::write(src.join("manifest.json"), b"{}").unwrap();
+        std::fs::write(src.join("apps/app.json"), b"{}").unwrap();
+        std::fs::write(src.join("apps/app.tar"), b"image data").unwrap();
+        std::fs::write(src.join("apps/other.tar"), b"other image data").unwrap();
+        let archive_path = path.join("archive.tar");
+        archive_to_file(&src, &archive_path, false).await.unwrap();
+        archive_path
+    }
+
+    fn is_tar(path: &Path) -> bool {
+        path.extension() == Some("tar".as_ref())
+    }
+
+    #[tokio::test]
+    async fn extract_deferring_ok() {
+        let path = testdir!();
+        let archive_path = prepare_archive(&path).await;
+        let dst = path.join("dst");
+        let mut deferred = extract_deferring(&archive_path, &dst, is_tar)
+            .await
+            .unwrap();
+        deferred.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            deferred,
+            vec![
+                DeferredEntry {
+                    path: PathBuf::from("apps/app.tar"),
+                    size: 10,
+                },
+                DeferredEntry {
+                    path: PathBuf::from("apps/other.tar"),
+                    size: 16,
+                },
+            ]
+        );
+        assert!(dst.join("manifest.json").is_file());
+        assert!(dst.join("apps/app.json").is_file());
+        assert!(!dst.join("apps/app.tar").exists());
+        assert!(!dst.join("apps/other.tar").exists());
+    }
+
+    #[tokio::test]
+    async fn list_entries_ok() {
+        let path = testdir!();
+        let archive_path = prepare_archive(&path).await;
+        let mut entries = list_entries(&archive_path, |path, _| path.ends_with("manifest.json"))
+            .await
+            .unwrap();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(
+            entries,
+            vec![
+                ListedEntry {
+                    path: PathBuf::from("apps/app.json"),
+                    size: 2,
+                    content: None,
+                },
+                ListedEntry {
+                    path: PathBuf::from("apps/app.tar"),
+                    size: 10,
+                    content: None,
+                },
+                ListedEntry {
+                    path: PathBuf::from("apps/other.tar"),
+                    size: 16,
+                    content: None,
+                },
+                ListedEntry {
+                    path: PathBuf::from("manifest.json"),
+                    size: 2,
+                    content: Some(b"{}".to_vec()),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn read_headers_ok() {
+        let src = testdir!().join("src");
+        std::fs::create_dir_all(src.join("sub")).unwrap();
+        std::fs::write(src.join("a.txt"), b"abc").unwrap();
+        std::fs::write(src.join("sub/b.txt"), b"abcdef").unwrap();
+        let archive = archive_to_memory(&src, false).await.unwrap();
+        let headers = read_headers(archive.as_slice()).await.unwrap();
+        assert!(headers.contains(&EntryHeader {
+            path: PathBuf::from("a.txt"),
+            size: 3,
+            is_dir: false,
+        }));
+        assert!(headers.contains(&EntryHeader {
+            path: PathBuf::from("sub"),
+            size: 0,
+            is_dir: true,
+        }));
+        assert!(headers.contains(&EntryHeader {
+            path: PathBuf::from("sub/b.txt"),
+            size: 6,
+            is_dir: false,
+        }));
+    }
+
+    #[tokio::test]
+    async fn read_headers_invalid() {
+        assert!(read_headers(&[1u8; 1024][..]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_entries_ok() {
+        let path = testdir!();
+        let archive_path = prepare_archive(&path).await;
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let consumer: EntryConsumer = Box::new(move |mut reader| {
+            async move {
+                let mut data = Vec::new();
+                reader.read_to_end(&mut data).await?;
+                _ = sender.send(data);
+                Ok(())
+            }
+            .boxed()
+        });
+        let dst = path.join("dst/apps/app.tar");
+        let checksums = stream_entries(
+            &archive_path,
+            HashMap::from([
+                (
+                    PathBuf::from("apps/app.tar"),
+                    EntryTarget::File(dst.clone()),
+                ),
+                (
+                    PathBuf::from("apps/other.tar"),
+                    EntryTarget::Consumer(consumer),
+                ),
+            ]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(&dst).unwrap(), b"image data");
+        assert_eq!(receiver.await.unwrap(), b"other image data");
+        assert_eq!(
+            checksums.get(Path::new("apps/app.tar")),
+            Some(&sha256_file(&dst).await.unwrap())
+        );
+        assert_eq!(checksums.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn stream_entries_consumer_err() {
+        let path = testdir!();
+        let archive_path = prepare_archive(&path).await;
+        let consumer: EntryConsumer =
+            Box::new(|_reader| async { Err(anyhow::anyhow!("TestError")) }.boxed());
+        let result = stream_entries(
+            &archive_path,
+            HashMap::from([(
+                PathBuf::from("apps/app.tar"),
+                EntryTarget::Consumer(consumer),
+            )]),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().to_string(), "TestError");
+    }
+
+    #[tokio::test]
+    async fn stream_entries_missing() {
+        let path = testdir!();
+        let archive_path = prepare_archive(&path).await;
+        assert!(
+            stream_entries(
+                &archive_path,
+                HashMap::from([(
+                    PathBuf::from("apps/missing.tar"),
+                    EntryTarget::File(path.join("missing.tar")),
+                )]),
+            )
+            .await
+            .is_err()
+        );
+    }
+}
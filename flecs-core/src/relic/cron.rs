@@ -0,0 +1,313 @@
+//! Cron expressions with the five standard fields `minute hour day-of-month month day-of-week`.
+//! Every field supports `*`, single values, ranges `a-b`, steps `*/n` or `a-b/n` and comma
+//! separated lists. The shorthands `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are
+//! supported as well.
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Upper bound of the search for the next matching point in time, a valid expression matches at
+/// least once every four years (e.g. February 29th)
+const MAX_SEARCH_DAYS: i64 = 5 * 366;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum CronError {
+    #[error("Expected 5 fields, got {0}")]
+    FieldCount(usize),
+    #[error("Invalid {name} field '{field}': {reason}")]
+    Field {
+        name: &'static str,
+        field: String,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, DeserializeFromStr, SerializeDisplay)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// If day of month and day of week are both restricted a day matches if either matches
+    days_restricted: (bool, bool),
+}
+
+struct FieldSpec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+}
+
+const MINUTE: FieldSpec = FieldSpec {
+    name: "minute",
+    min: 0,
+    max: 59,
+};
+const HOUR: FieldSpec = FieldSpec {
+    name: "hour",
+    min: 0,
+    max: 23,
+};
+const DAY_OF_MONTH: FieldSpec = FieldSpec {
+    name: "day of month",
+    min: 1,
+    max: 31,
+};
+const MONTH: FieldSpec = FieldSpec {
+    name: "month",
+    min: 1,
+    max: 12,
+};
+/// 0 and 7 are both sunday
+const DAY_OF_WEEK: FieldSpec = FieldSpec {
+    name: "day of week",
+    min: 0,
+    max: 7,
+};
+
+impl FieldSpec {
+    fn error(&self, field: &str, reason: impl Into<String>) -> CronError {
+        CronError::Field {
+            name: self.name,
+            field: field.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    fn value(&self, field: &str, value: &str) -> Result<u32, CronError> {
+        let value = u32::from_str(value).map_err(|e| self.error(field, e.to_string()))?;
+        if value < self.min || value > self.max {
+            return Err(self.error(
+                field,
+                format!("{value} is not in {}-{}", self.min, self.max),
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Returns the bitmask of all matching values and whether the field is restricted
+    fn parse(&self, field: &str) -> Result<(u64, bool), CronError> {
+        let mut mask = 0;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step = u32::from_str(step).map_err(|e| self.error(field, e.to_string()))?;
+                    if step == 0 {
+                        return Err(self.error(field, "step must not be 0"));
+                    }
+                    (range, step)
+                }
+                None => (part, 1),
+            };
+            let (start, end) = match range {
+                "*" => (self.min, self.max),
+                range => match range.split_once('-') {
+                    Some((start, end)) => (self.value(field, start)?, self.value(field, end)?),
+                    None if step > 1 => (self.value(field, range)?, self.max),
+                    None => {
+                        let value = self.value(field, range)?;
+                        (value, value)
+                    }
+                },
+            };
+            if start > end {
+                return Err(self.error(field, format!("{start} is greater than {end}")));
+            }
+            for value in (start..=end).step_by(step as usize) {
+                mask |= 1 << value;
+            }
+        }
+        Ok((mask, field != "*"))
+    }
+}
+
+fn contains(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+impl CronSchedule {
+    fn matches_day<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> bool {
+        if !contains(self.months, time.month()) {
+            return false;
+        }
+        let day_of_month = contains(self.days_of_month, time.day());
+        let day_of_week = contains(self.days_of_week, time.weekday().num_days_from_sunday());
+        match self.days_restricted {
+            (true, true) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+
+    /// Returns the first point in time strictly after `after` matching the expression
+    pub fn next_after<Tz: TimeZone>(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let mut time = after.clone().with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = after.clone() + Duration::days(MAX_SEARCH_DAYS);
+        while time < limit {
+            if !self.matches_day(&time) {
+                time = time.clone() + Duration::days(1)
+                    - Duration::hours(time.hour() as i64)
+                    - Duration::minutes(time.minute() as i64);
+            } else if !contains(self.hours, time.hour()) {
+                time = time.clone() + Duration::hours(1) - Duration::minutes(time.minute() as i64);
+            } else if !contains(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = match s.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            expression => expression,
+        };
+        let fields: Vec<_> = expression.split_whitespace().collect();
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(CronError::FieldCount(fields.len()));
+        };
+        let (days_of_month, dom_restricted) = DAY_OF_MONTH.parse(days_of_month)?;
+        let (mut days_of_week, dow_restricted) = DAY_OF_WEEK.parse(days_of_week)?;
+        if contains(days_of_week, 7) {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            expression: s.trim().to_string(),
+            minutes: MINUTE.parse(minutes)?.0,
+            hours: HOUR.parse(hours)?.0,
+            days_of_month,
+            months: MONTH.parse(months)?.0,
+            days_of_week,
+            days_restricted: (dom_restricted, dow_restricted),
+        })
+    }
+}
+
+impl Display for CronSchedule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.expression)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn time(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expression: &str, after: &str) -> Option<DateTime<Utc>> {
+        CronSchedule::from_str(expression)
+            .unwrap()
+            .next_after(&time(after))
+    }
+
+    #[test]
+    fn parse_err() {
+        assert_eq!(
+            CronSchedule::from_str("* * * *"),
+            Err(CronError::FieldCount(4))
+        );
+        assert!(matches!(
+            CronSchedule::from_str("60 * * * *"),
+            Err(CronError::Field { name: "minute", .. })
+        ));
+        assert!(matches!(
+            CronSchedule::from_str("* * * * 1-8"),
+            Err(CronError::Field {
+                name: "day of week",
+                ..
+            })
+        ));
+        assert!(CronSchedule::from_str("*/0 * * * *").is_err());
+        assert!(CronSchedule::from_str("5-1 * * * *").is_err());
+        assert!(CronSchedule::from_str("a * * * *").is_err());
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        let schedule = CronSchedule::from_str(" 0 3 * * 1-5 ").unwrap();
+        assert_eq!(schedule.to_string(), "0 3 * * 1-5");
+        assert_eq!(CronSchedule::from_str("0 3 * * 1-5").unwrap(), schedule);
+    }
+
+    #[test]
+    fn next_every_minute() {
+        assert_eq!(
+            next("* * * * *", "2024-03-01T10:15:30Z"),
+            Some(time("2024-03-01T10:16:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_daily() {
+        assert_eq!(
+            next("@daily", "2024-03-01T10:15:00Z"),
+            Some(time("2024-03-02T00:00:00Z"))
+        );
+        assert_eq!(
+            next("30 2 * * *", "2024-03-01T02:30:00Z"),
+            Some(time("2024-03-02T02:30:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_steps_and_lists() {
+        assert_eq!(
+            next("*/15 8,20 * * *", "2024-03-01T08:50:00Z"),
+            Some(time("2024-03-01T20:00:00Z"))
+        );
+        assert_eq!(
+            next("10-20/5 * * * *", "2024-03-01T08:12:00Z"),
+            Some(time("2024-03-01T08:15:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_day_of_week() {
+        // 2024-03-01 is a friday
+        assert_eq!(
+            next("0 0 * * 7", "2024-03-01T10:00:00Z"),
+            Some(time("2024-03-03T00:00:00Z"))
+        );
+        assert_eq!(
+            next("0 0 * * 1-5", "2024-03-01T10:00:00Z"),
+            Some(time("2024-03-04T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_day_of_month_or_week() {
+        assert_eq!(
+            next("0 0 15 * 0", "2024-03-01T10:00:00Z"),
+            Some(time("2024-03-03T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_leap_day() {
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00:00Z"),
+            Some(time("2028-02-29T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_never() {
+        assert_eq!(next("0 0 31 2 *", "2024-03-01T00:00:00Z"), None);
+    }
+}
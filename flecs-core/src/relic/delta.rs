@@ -0,0 +1,450 @@
+use crate::relic::integrity::{Checksums, encode_hex, sha256_directory};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::io::SeekFrom;
+use std::path::{Component, Path, PathBuf};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tracing::debug;
+
+/// Extension appended to the file name of tar archives which are assembled from chunks, the file
+/// contains all chunks which are not taken from the base, e.g. app_1.0.0.tar.delta
+pub const EXTENSION: &str = "delta";
+const BLOCK_SIZE: u64 = 512;
+
+/// Consecutive bytes of a tar archive consisting of one header block and the data of the entry.
+/// The blocks after the last entry form the last chunk.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+    pub sha256: String,
+    pub offset: u64,
+    pub len: u64,
+}
+
+/// Checksums of all files of a directory and the chunks of all contained tar archives
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Index {
+    pub files: Checksums,
+    /// Chunks of all files with extension 'tar' which are valid tar archives
+    #[serde(default)]
+    pub chunks: BTreeMap<String, Vec<Chunk>>,
+    /// Id of the export the indexed export is an increment of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "camelCase")]
+pub enum ChunkSource {
+    /// Chunk taken from a file of the base directory
+    Base { path: String, offset: u64, len: u64 },
+    /// Next chunk of the delta file
+    Delta { len: u64 },
+}
+
+/// Difference of a directory to a base directory, see [create_delta] and [apply_delta]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Delta {
+    /// Files which are taken unchanged from the base
+    pub unchanged: Vec<String>,
+    /// Tar archives which are assembled from chunks of the base and the delta file next to them
+    pub assembled: BTreeMap<String, Vec<ChunkSource>>,
+}
+
+fn delta_file_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(format!(".{EXTENSION}"));
+    PathBuf::from(path)
+}
+
+/// Joins a path relative to `root` as contained in [Index] or [Delta], paths leaving `root` are
+/// rejected
+fn join_relative(root: &Path, path: &str) -> anyhow::Result<PathBuf> {
+    anyhow::ensure!(
+        !path.is_empty()
+            && Path::new(path)
+                .components()
+                .all(|component| matches!(component, Component::Normal(_))),
+        "Invalid relative path {path}"
+    );
+    Ok(root.join(path))
+}
+
+fn parse_octal(field: &[u8]) -> anyhow::Result<u64> {
+    let field = std::str::from_utf8(field)?.trim_matches(|c: char| c == '\0' || c == ' ');
+    if field.is_empty() {
+        return Ok(0);
+    }
+    Ok(u64::from_str_radix(field, 8)?)
+}
+
+/// Returns the size of the entry data of a tar header block, validating the header checksum
+fn entry_size(header: &[u8; BLOCK_SIZE as usize]) -> anyhow::Result<u64> {
+    let checksum = parse_octal(&header[148..156]).context("Invalid tar header checksum")?;
+    let actual: u64 = header
+        .iter()
+        .enumerate()
+        .map(|(index, byte)| {
+            if (148..156).contains(&index) {
+                b' ' as u64
+            } else {
+                *byte as u64
+            }
+        })
+        .sum();
+    anyhow::ensure!(checksum == actual, "Tar header checksum does not match");
+    if header[124] & 0x80 != 0 {
+        // Base-256 encoding for large entries
+        Ok(header[125..136]
+            .iter()
+            .fold(0, |size, byte| (size << 8) | *byte as u64))
+    } else {
+        parse_octal(&header[124..136]).context("Invalid tar entry size")
+    }
+}
+
+async fn hash_exact<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    hasher: &mut Sha256,
+    len: u64,
+) -> anyhow::Result<()> {
+    let mut reader = reader.take(len);
+    let mut buffer = vec![0; 64 * 1024];
+    let mut read = 0;
+    loop {
+        let count = reader.read(&mut buffer).await?;
+        if count == 0 {
+            break;
+        }
+        hasher.update(&buffer[..count]);
+        read += count as u64;
+    }
+    anyhow::ensure!(read == len, "Unexpected end of file");
+    Ok(())
+}
+
+async fn copy_exact<R: AsyncRead + Unpin, W: AsyncWrite + Unpin>(
+    reader: &mut R,
+    writer: &mut W,
+    len: u64,
+) -> anyhow::Result<()> {
+    let copied = tokio::io::copy(&mut reader.take(len), writer).await?;
+    anyhow::ensure!(copied == len, "Unexpected end of file");
+    Ok(())
+}
+
+/// Splits the tar archive at `path` into chunks, see [Chunk]
+pub async fn tar_chunks(path: &Path) -> anyhow::Result<Vec<Chunk>> {
+    let file = tokio::fs::File::open(path).await?;
+    let total = file.metadata().await?.len();
+    anyhow::ensure!(total % BLOCK_SIZE == 0, "{path:?} is not a tar archive");
+    let mut reader = tokio::io::BufReader::new(file);
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    let mut header = [0; BLOCK_SIZE as usize];
+    while offset < total {
+        reader.read_exact(&mut header).await?;
+        let mut hasher = Sha256::new();
+        hasher.update(header);
+        let len = if header.iter().all(|byte| *byte == 0) {
+            let len = total - offset;
+            hash_exact(&mut reader, &mut hasher, len - BLOCK_SIZE).await?;
+            len
+        } else {
+            let data_len = entry_size(&header)?.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+            hash_exact(&mut reader, &mut hasher, data_len).await?;
+            BLOCK_SIZE + data_len
+        };
+        chunks.push(Chunk {
+            sha256: encode_hex(&hasher.finalize()),
+            offset,
+            len,
+        });
+        offset += len;
+    }
+    Ok(chunks)
+}
+
+/// Creates the index of all files below `root` except the excluded ones
+pub async fn create_index(root: &Path, exclude: &[&str]) -> anyhow::Result<Index> {
+    let files = sha256_directory(root, exclude).await?;
+    let mut chunks = BTreeMap::new();
+    for path in files.keys().filter(|path| path.ends_with(".tar")) {
+        match tar_chunks(&root.join(path)).await {
+            Ok(file_chunks) => {
+                chunks.insert(path.clone(), file_chunks);
+            }
+            Err(e) => debug!("{path} is not split into chunks: {e}"),
+        }
+    }
+    Ok(Index {
+        files,
+        chunks,
+        base: None,
+    })
+}
+
+async fn write_delta_file(
+    path: &Path,
+    chunks: &[Chunk],
+    base_chunks: &HashMap<&str, (&str, &Chunk)>,
+) -> anyhow::Result<Vec<ChunkSource>> {
+    let mut src = tokio::fs::File::open(path).await?;
+    let mut dst = tokio::io::BufWriter::new(tokio::fs::File::create(delta_file_path(path)).await?);
+    let mut sources = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        match base_chunks.get(chunk.sha256.as_str()) {
+            Some((base_path, base_chunk)) => {
+                src.seek(SeekFrom::Current(chunk.len as i64)).await?;
+                sources.push(ChunkSource::Base {
+                    path: base_path.to_string(),
+                    offset: base_chunk.offset,
+                    len: base_chunk.len,
+                });
+            }
+            None => {
+                copy_exact(&mut src, &mut dst, chunk.len).await?;
+                sources.push(ChunkSource::Delta { len: chunk.len });
+            }
+        }
+    }
+    dst.flush().await?;
+    tokio::fs::remove_file(path).await?;
+    Ok(sources)
+}
+
+/// Reduces the files below `root` described by `index` to their difference to the directory
+/// described by `base`. Unchanged files are removed, tar archives with chunks contained in `base`
+/// are replaced by a delta file with the remaining chunks.
+pub async fn create_delta(root: &Path, base: &Index, index: &Index) -> anyhow::Result<Delta> {
+    let base_chunks: HashMap<&str, (&str, &Chunk)> = base
+        .chunks
+        .iter()
+        .flat_map(|(path, chunks)| {
+            chunks
+                .iter()
+                .map(move |chunk| (chunk.sha256.as_str(), (path.as_str(), chunk)))
+        })
+        .collect();
+    let mut delta = Delta::default();
+    for (path, checksum) in &index.files {
+        if base.files.get(path) == Some(checksum) {
+            tokio::fs::remove_file(join_relative(root, path)?).await?;
+            delta.unchanged.push(path.clone());
+            continue;
+        }
+        let Some(chunks) = index.chunks.get(path) else {
+            continue;
+        };
+        if chunks
+            .iter()
+            .any(|chunk| base_chunks.contains_key(chunk.sha256.as_str()))
+        {
+            let sources =
+                write_delta_file(&join_relative(root, path)?, chunks, &base_chunks).await?;
+            delta.assembled.insert(path.clone(), sources);
+        }
+    }
+    Ok(delta)
+}
+
+/// Restores the files below `root` which were reduced by [create_delta] from the complete base
+/// directory `base_root`
+pub async fn apply_delta(root: &Path, base_root: &Path, delta: &Delta) -> anyhow::Result<()> {
+    for path in &delta.unchanged {
+        let dst = join_relative(root, path)?;
+        if let Some(parent) = dst.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::copy(join_relative(base_root, path)?, &dst)
+            .await
+            .with_context(|| format!("Could not take {path} from base"))?;
+    }
+    for (path, sources) in &delta.assembled {
+        let dst = join_relative(root, path)?;
+        let delta_path = delta_file_path(&dst);
+        let mut delta_file = tokio::io::BufReader::new(
+            tokio::fs::File::open(&delta_path)
+                .await
+                .with_context(|| format!("Could not open delta file of {path}"))?,
+        );
+        let mut file = tokio::io::BufWriter::new(tokio::fs::File::create(&dst).await?);
+        for source in sources {
+            match source {
+                ChunkSource::Base {
+                    path: base_path,
+                    offset,
+                    len,
+                } => {
+                    let mut base_file = tokio::fs::File::open(join_relative(base_root, base_path)?)
+                        .await
+                        .with_context(|| format!("Could not take chunk from {base_path}"))?;
+                    base_file.seek(SeekFrom::Start(*offset)).await?;
+                    copy_exact(&mut base_file, &mut file, *len).await?;
+                }
+                ChunkSource::Delta { len } => copy_exact(&mut delta_file, &mut file, *len).await?,
+            }
+        }
+        file.flush().await?;
+        tokio::fs::remove_file(&delta_path).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testdir::testdir;
+
+    fn tar_entry(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut header = [0u8; BLOCK_SIZE as usize];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[156] = b'0';
+        header[257..262].copy_from_slice(b"ustar");
+        header[148..156].fill(b' ');
+        let checksum: u64 = header.iter().map(|byte| *byte as u64).sum();
+        header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+        let mut entry = header.to_vec();
+        entry.extend_from_slice(data);
+        entry.resize(
+            entry.len().div_ceil(BLOCK_SIZE as usize) * BLOCK_SIZE as usize,
+            0,
+        );
+        entry
+    }
+
+    fn tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut tar: Vec<u8> = entries
+            .iter()
+            .flat_map(|(name, data)| tar_entry(name, data))
+            .collect();
+        tar.extend_from_slice(&[0; 2 * BLOCK_SIZE as usize]);
+        tar
+    }
+
+    fn write(root: &Path, path: &str, content: &[u8]) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tar_chunks_ok() {
+        let path = testdir!().join("image.tar");
+        std::fs::write(&path, tar(&[("a", b"layer a"), ("b", &[1; 600])])).unwrap();
+        let chunks = tar_chunks(&path).await.unwrap();
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| (chunk.offset, chunk.len))
+                .collect::<Vec<_>>(),
+            vec![(0, 1024), (1024, 1536), (2560, 1024)]
+        );
+    }
+
+    #[tokio::test]
+    async fn tar_chunks_no_tar() {
+        let path = testdir!().join("image.tar");
+        std::fs::write(&path, [1; 1024]).unwrap();
+        assert!(tar_chunks(&path).await.is_err());
+        std::fs::write(&path, b"no tar").unwrap();
+        assert!(tar_chunks(&path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn delta_roundtrip() {
+        let path = testdir!();
+        let base = path.join("base");
+        let current = path.join("current");
+        let image = tar(&[("layer1", b"base layer"), ("layer2", b"app layer")]);
+        let new_image = tar(&[("layer1", b"base layer"), ("layer2", b"new app layer")]);
+        write(&base, "apps/app/app.tar", &image);
+        write(&base, "instances/1/volumes/data/file", b"unchanged");
+        write(&base, "instances/1/instance.json", b"{}");
+        write(&current, "apps/app/app.tar", &new_image);
+        write(&current, "apps/other/other.tar", &image);
+        write(&current, "instances/1/volumes/data/file", b"unchanged");
+        write(&current, "instances/1/instance.json", b"{\"changed\":true}");
+        write(&current, "manifest.json", b"{}");
+        let base_index = create_index(&base, &[]).await.unwrap();
+        let index = create_index(&current, &["manifest.json"]).await.unwrap();
+        assert_eq!(index.chunks.len(), 2);
+        let delta = create_delta(&current, &base_index, &index).await.unwrap();
+        assert_eq!(
+            delta.unchanged,
+            vec!["instances/1/volumes/data/file".to_string()]
+        );
+        let base_chunk = |offset| ChunkSource::Base {
+            path: "apps/app/app.tar".to_string(),
+            offset,
+            len: 1024,
+        };
+        assert_eq!(
+            delta.assembled,
+            BTreeMap::from([
+                (
+                    "apps/app/app.tar".to_string(),
+                    vec![
+                        base_chunk(0),
+                        ChunkSource::Delta { len: 1024 },
+                        base_chunk(2048)
+                    ]
+                ),
+                (
+                    "apps/other/other.tar".to_string(),
+                    vec![base_chunk(0), base_chunk(1024), base_chunk(2048)]
+                ),
+            ])
+        );
+        assert!(!current.join("apps/app/app.tar").exists());
+        assert!(!current.join("instances/1/volumes/data/file").exists());
+        assert_eq!(
+            std::fs::metadata(current.join("apps/app/app.tar.delta"))
+                .unwrap()
+                .len(),
+            1024
+        );
+        apply_delta(&current, &base, &delta).await.unwrap();
+        assert_eq!(
+            std::fs::read(current.join("apps/app/app.tar")).unwrap(),
+            new_image
+        );
+        assert_eq!(
+            std::fs::read(current.join("apps/other/other.tar")).unwrap(),
+            image
+        );
+        assert!(!current.join("apps/app/app.tar.delta").exists());
+        assert_eq!(
+            create_index(&current, &["manifest.json"]).await.unwrap(),
+            index
+        );
+    }
+
+    #[tokio::test]
+    async fn apply_delta_invalid_path() {
+        let path = testdir!();
+        let delta = Delta {
+            unchanged: vec!["../../etc/shadow".to_string()],
+            assembled: BTreeMap::new(),
+        };
+        assert!(
+            apply_delta(&path.join("current"), &path.join("base"), &delta)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn join_relative_ok() {
+        assert_eq!(
+            join_relative(Path::new("/root"), "apps/app.tar").unwrap(),
+            PathBuf::from("/root/apps/app.tar")
+        );
+        assert!(join_relative(Path::new("/root"), "/etc/shadow").is_err());
+        assert!(join_relative(Path::new("/root"), "apps/../../x").is_err());
+        assert!(join_relative(Path::new("/root"), "").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Device certificate authority and the TLS material of instances issued by it, see
+//! [crate::lore::CertificateLore]
+use crate::lore::CertificateLore;
+use crate::lore::default::certificate::{
+    CA_CERTIFICATE_FILE_NAME, CA_COMMON_NAME, CA_VALIDITY_DAYS, INSTANCE_CERTIFICATE_FILE_NAME,
+    INSTANCE_PRIVATE_KEY_FILE_NAME, SERVER_CERTIFICATE_VALIDITY_DAYS,
+};
+use crate::relic::tls::{CertificateError, CertifiedKey};
+use std::collections::BTreeSet;
+use std::io::ErrorKind;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+/// Reads the stored certificate, invalid certificates are treated like missing ones so that
+/// they are replaced
+pub fn read_certified_key(
+    certificate_path: &Path,
+    private_key_path: &Path,
+) -> Result<Option<CertifiedKey>, CertificateError> {
+    match CertifiedKey::read_from(certificate_path, private_key_path) {
+        Ok(certified_key) => Ok(Some(certified_key)),
+        Err(CertificateError::Io(e)) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(
+            e @ (CertificateError::InvalidCertificate(_)
+            | CertificateError::InvalidPrivateKey(_)
+            | CertificateError::KeyMismatch),
+        ) => {
+            warn!("Replacing invalid certificate {certificate_path:?}: {e}");
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Checks if `certificate` has to be reissued because it was not issued by `ca`, expires within
+/// `renew_before` or does not match `host_names`
+pub fn certificate_outdated(
+    certificate: &CertifiedKey,
+    ca: &CertifiedKey,
+    host_names: &[String],
+    renew_before: Duration,
+) -> Result<bool, CertificateError> {
+    Ok(!certificate.is_issued_by(ca)?
+        || certificate.expires_within(renew_before)?
+        || certificate.host_names()?.iter().collect::<BTreeSet<_>>()
+            != host_names.iter().collect::<BTreeSet<_>>())
+}
+
+/// Serializes changes of the device certificate authority between the certificate rotation and
+/// instances requesting TLS material
+static CERTIFICATE_AUTHORITY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Creates the device certificate authority if it is missing or expires within
+/// [CertificateLore::renew_before]. A replaced certificate authority stays published in the
+/// certificate file until it expires, so that clients can switch to the new one in the meantime.
+/// Returns whether the certificate authority was created.
+pub fn ensure_certificate_authority(
+    lore: &CertificateLore,
+) -> Result<(CertifiedKey, bool), CertificateError> {
+    let _lock = CERTIFICATE_AUTHORITY_LOCK
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let ca_certificate_path = lore.ca_certificate_path();
+    let ca_private_key_path = lore.ca_private_key_path();
+    match read_certified_key(&ca_certificate_path, &ca_private_key_path)? {
+        Some(ca) if !ca.expires_within(lore.renew_before)? => {
+            match ca.without_expired_previous()? {
+                Some(pruned) => {
+                    pruned.write_to(&ca_certificate_path, &ca_private_key_path)?;
+                    Ok((pruned, false))
+                }
+                None => Ok((ca, false)),
+            }
+        }
+        previous => {
+            let mut ca =
+                CertifiedKey::generate_certificate_authority(CA_COMMON_NAME, CA_VALIDITY_DAYS)?;
+            if let Some(previous) = previous {
+                ca = ca.with_previous(&previous)?;
+            }
+            ca.write_to(&ca_certificate_path, &ca_private_key_path)?;
+            Ok((ca, true))
+        }
+    }
+}
+
+/// Provides the TLS material for instance `instance_id` in [CertificateLore::instance_tls_path]:
+/// the public device certificate authority and a certificate for `host_names` issued by it. The
+/// private key of the instance certificate is readable by any user of the container, the
+/// material of all instances is only accessible by the owner on the host. The instance
+/// certificate is reissued if it was issued by a previous certificate authority, expires within
+/// [CertificateLore::renew_before] or `host_names` changed. Returns the directory containing the
+/// material.
+pub fn ensure_instance_tls_material(
+    lore: &CertificateLore,
+    instance_id: &impl AsRef<str>,
+    host_names: &[String],
+) -> Result<PathBuf, CertificateError> {
+    let (ca, _) = ensure_certificate_authority(lore)?;
+    let instances_path = lore.instances_tls_path();
+    std::fs::create_dir_all(&instances_path)?;
+    std::fs::set_permissions(&instances_path, std::fs::Permissions::from_mode(0o700))?;
+    let path = lore.instance_tls_path(instance_id);
+    std::fs::create_dir_all(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    std::fs::write(
+        path.join(CA_CERTIFICATE_FILE_NAME),
+        ca.certificate.as_bytes(),
+    )?;
+    let certificate_path = path.join(INSTANCE_CERTIFICATE_FILE_NAME);
+    let private_key_path = path.join(INSTANCE_PRIVATE_KEY_FILE_NAME);
+    match read_certified_key(&certificate_path, &private_key_path)? {
+        Some(certificate)
+            if !certificate_outdated(&certificate, &ca, host_names, lore.renew_before)? => {}
+        _ => CertifiedKey::generate_issued_by(host_names, SERVER_CERTIFICATE_VALIDITY_DAYS, &ca)?
+            .write_readable_to(&certificate_path, &private_key_path)?,
+    }
+    Ok(path)
+}
+
+/// Removes the TLS material of instance `instance_id`, see [ensure_instance_tls_material]
+pub fn delete_instance_tls_material(
+    lore: &CertificateLore,
+    instance_id: &impl AsRef<str>,
+) -> Result<(), std::io::Error> {
+    match std::fs::remove_dir_all(lore.instance_tls_path(instance_id)) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore::conf::CertificateConfig;
+    use testdir::testdir;
+
+    fn test_lore() -> CertificateLore {
+        CertificateLore::from_conf_with_defaults(CertificateConfig::default(), &testdir!()).unwrap()
+    }
+
+    #[test]
+    fn ensure_certificate_authority_keeps_previous() {
+        let lore = test_lore();
+        let (previous, created) = ensure_certificate_authority(&lore).unwrap();
+        assert!(created);
+        let (ca, created) = ensure_certificate_authority(&lore).unwrap();
+        assert!(!created);
+        assert_eq!(ca, previous);
+        let lore = CertificateLore {
+            renew_before: Duration::from_secs(u64::from(CA_VALIDITY_DAYS + 1) * 24 * 60 * 60),
+            ..lore
+        };
+        let (ca, created) = ensure_certificate_authority(&lore).unwrap();
+        assert!(created);
+        assert_ne!(ca.private_key, previous.private_key);
+        assert!(ca.certificate.ends_with(&previous.certificate));
+        assert_eq!(
+            std::fs::read_to_string(lore.ca_certificate_path()).unwrap(),
+            ca.certificate
+        );
+    }
+
+    #[test]
+    fn ensure_certificate_authority_prunes_expired() {
+        let lore = test_lore();
+        let expired = CertifiedKey::generate_certificate_authority(CA_COMMON_NAME, 0).unwrap();
+        let ca = CertifiedKey::generate_certificate_authority(CA_COMMON_NAME, 20).unwrap();
+        CertifiedKey {
+            certificate: format!("{}{}", ca.certificate, expired.certificate),
+            private_key: ca.private_key.clone(),
+        }
+        .write_to(&lore.ca_certificate_path(), &lore.ca_private_key_path())
+        .unwrap();
+        assert_eq!(
+            ensure_certificate_authority(&lore).unwrap(),
+            (ca.clone(), false)
+        );
+        assert_eq!(
+            std::fs::read_to_string(lore.ca_certificate_path()).unwrap(),
+            ca.certificate
+        );
+    }
+
+    #[test]
+    fn ensure_instance_tls_material_ok() {
+        let lore = test_lore();
+        let host_names = vec!["flecs-00001234".to_string()];
+        let path = ensure_instance_tls_material(&lore, &"00001234", &host_names).unwrap();
+        assert_eq!(path, lore.instance_tls_path(&"00001234"));
+        assert_eq!(
+            std::fs::read_to_string(path.join(CA_CERTIFICATE_FILE_NAME)).unwrap(),
+            std::fs::read_to_string(lore.ca_certificate_path()).unwrap()
+        );
+        let certificate = CertifiedKey::read_from(
+            &path.join(INSTANCE_CERTIFICATE_FILE_NAME),
+            &path.join(INSTANCE_PRIVATE_KEY_FILE_NAME),
+        )
+        .unwrap();
+        let ca = CertifiedKey::read_from(&lore.ca_certificate_path(), &lore.ca_private_key_path())
+            .unwrap();
+        assert!(certificate.is_issued_by(&ca).unwrap());
+        assert_eq!(certificate.host_names().unwrap(), host_names);
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&lore.instances_tls_path()), 0o700);
+        assert_eq!(mode(&path), 0o755);
+        assert_eq!(mode(&path.join(INSTANCE_PRIVATE_KEY_FILE_NAME)), 0o644);
+        // Only the public certificate authority and the instance certificate are provided
+        let mut files: Vec<_> = std::fs::read_dir(&path)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["ca.crt", "tls.crt", "tls.key"]);
+        ensure_instance_tls_material(&lore, &"00001234", &host_names).unwrap();
+        assert_eq!(
+            CertifiedKey::read_from(
+                &path.join(INSTANCE_CERTIFICATE_FILE_NAME),
+                &path.join(INSTANCE_PRIVATE_KEY_FILE_NAME),
+            )
+            .unwrap(),
+            certificate
+        );
+        delete_instance_tls_material(&lore, &"00001234").unwrap();
+        assert!(matches!(path.try_exists(), Ok(false)));
+        delete_instance_tls_material(&lore, &"00001234").unwrap();
+    }
+}
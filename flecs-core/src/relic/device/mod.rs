@@ -1 +1,2 @@
+pub mod certificate;
 pub mod usb;
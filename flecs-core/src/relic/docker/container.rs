@@ -1,8 +1,8 @@
 pub use super::{Error, Result};
 use crate::quest::{Progress, SyncQuest};
 use crate::relic::async_flecstract::{
-    archive_single_file_to_memory, archive_to_memory, extract_from_memory,
-    extract_single_file_from_memory_as,
+    EntryHeader, archive_single_file_to_memory, archive_to_memory, extract_from_memory,
+    extract_single_file_from_memory_as, read_headers,
 };
 use crate::relic::docker::{map_bollard_error, write_stream_to_file, write_stream_to_memory};
 use async_compression::tokio::bufread::{GzipDecoder, GzipEncoder};
@@ -12,9 +12,10 @@ use bollard::container::{
     Config, CreateContainerOptions, DownloadFromContainerOptions, ListContainersOptions, LogOutput,
     LogsOptions, RemoveContainerOptions, StopContainerOptions, UploadToContainerOptions,
 };
-use bollard::exec::{CreateExecOptions, StartExecOptions, StartExecResults};
+use bollard::exec::{CreateExecOptions, ResizeExecOptions, StartExecOptions, StartExecResults};
 use bollard::models::{ContainerInspectResponse, ContainerSummary};
 use futures::Stream;
+use futures_util::TryStreamExt;
 use futures_util::stream::StreamExt;
 use serde::Serialize;
 use std::hash::Hash;
@@ -23,10 +24,12 @@ use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 use tokio::fs::File;
-use tokio::io::{AsyncRead, AsyncWriteExt, BufReader, ReadBuf};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf};
 use tokio::join;
 use tokio_util::codec;
+use tokio_util::compat::FuturesAsyncReadCompatExt;
 use tracing::{error, warn};
 
 /// # Example
@@ -88,6 +91,191 @@ where
         .map_err(map_bollard_error)
 }
 
+pub type ExecOutputStream = Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>;
+pub type ExecInput = Pin<Box<dyn AsyncWrite + Send>>;
+
+/// Number of bytes of stdout and stderr each which are kept of a command executed with
+/// [exec_to_completion], further output is discarded
+pub const EXEC_OUTPUT_LIMIT: usize = 1024 * 1024;
+
+/// Output of a command executed with [exec_to_completion]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CompletedExec {
+    pub exit_code: Option<i64>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Stdout or stderr exceeded [EXEC_OUTPUT_LIMIT] and was truncated
+    pub truncated: bool,
+}
+
+/// Collects the output of a command up to [EXEC_OUTPUT_LIMIT] bytes per stream
+#[derive(Debug, Default)]
+pub(crate) struct ExecOutputCollector {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    truncated: bool,
+}
+
+impl ExecOutputCollector {
+    fn push(buffer: &mut Vec<u8>, truncated: &mut bool, data: &[u8]) {
+        let len = data.len().min(EXEC_OUTPUT_LIMIT - buffer.len());
+        buffer.extend_from_slice(&data[..len]);
+        *truncated |= len < data.len();
+    }
+
+    pub(crate) fn push_stdout(&mut self, data: &[u8]) {
+        Self::push(&mut self.stdout, &mut self.truncated, data)
+    }
+
+    pub(crate) fn push_stderr(&mut self, data: &[u8]) {
+        Self::push(&mut self.stderr, &mut self.truncated, data)
+    }
+
+    pub(crate) fn complete(self, exit_code: Option<i64>) -> CompletedExec {
+        CompletedExec {
+            exit_code,
+            stdout: String::from_utf8_lossy(&self.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&self.stderr).to_string(),
+            truncated: self.truncated,
+        }
+    }
+}
+
+/// Executes the command in the container and waits at most `timeout` for it to finish. The
+/// command is not killed if the timeout elapses, the returned error contains the
+/// [tokio::time::error::Elapsed] in that case.
+pub async fn exec_to_completion(
+    docker_client: Arc<Docker>,
+    container_name: &str,
+    cmd: Vec<String>,
+    timeout: Duration,
+) -> Result<CompletedExec> {
+    tokio::time::timeout(
+        timeout,
+        exec_to_completion_unbounded(docker_client, container_name, cmd),
+    )
+    .await
+    .map_err(|elapsed| {
+        anyhow::Error::new(elapsed).context(format!(
+            "Command in container {container_name} did not finish within {timeout:?}"
+        ))
+    })?
+}
+
+async fn exec_to_completion_unbounded(
+    docker_client: Arc<Docker>,
+    container_name: &str,
+    cmd: Vec<String>,
+) -> Result<CompletedExec> {
+    let create_options = CreateExecOptions {
+        attach_stderr: Some(true),
+        attach_stdout: Some(true),
+        cmd: Some(cmd),
+        ..CreateExecOptions::default()
+    };
+    let exec = docker_client
+        .create_exec(container_name, create_options)
+        .await
+        .map_err(map_bollard_error)?;
+    let StartExecResults::Attached { mut output, .. } = docker_client
+        .start_exec(&exec.id, None)
+        .await
+        .map_err(map_bollard_error)?
+    else {
+        anyhow::bail!(
+            "Exec {} in container {container_name} is not attached",
+            exec.id
+        );
+    };
+    let mut collector = ExecOutputCollector::default();
+    while let Some(data) = output.next().await {
+        match data.map_err(map_bollard_error)? {
+            LogOutput::StdErr { message } => collector.push_stderr(&message),
+            LogOutput::StdOut { message } | LogOutput::Console { message } => {
+                collector.push_stdout(&message)
+            }
+            LogOutput::StdIn { .. } => {}
+        }
+    }
+    let exit_code = docker_client
+        .inspect_exec(&exec.id)
+        .await
+        .map_err(map_bollard_error)?
+        .exit_code;
+    Ok(collector.complete(exit_code))
+}
+
+/// Starts the command with an attached tty in the container. Returns the id of the exec instance
+/// which can be used to [resize_exec] the tty, the output of the tty and the input of the command.
+pub async fn exec_tty(
+    docker_client: Arc<Docker>,
+    container_name: &str,
+    cmd: Vec<String>,
+) -> Result<(String, ExecOutputStream, ExecInput)> {
+    let create_options = CreateExecOptions {
+        attach_stdin: Some(true),
+        attach_stderr: Some(true),
+        attach_stdout: Some(true),
+        tty: Some(true),
+        cmd: Some(cmd),
+        ..CreateExecOptions::default()
+    };
+    let exec = docker_client
+        .create_exec(container_name, create_options)
+        .await
+        .map_err(map_bollard_error)?;
+    let start_options = StartExecOptions {
+        tty: true,
+        ..StartExecOptions::default()
+    };
+    match docker_client
+        .start_exec(&exec.id, Some(start_options))
+        .await
+        .map_err(map_bollard_error)?
+    {
+        StartExecResults::Attached { output, input } => Ok((
+            exec.id,
+            Box::pin(
+                output.map(|output| output.map(LogOutput::into_bytes).map_err(map_bollard_error)),
+            ),
+            input,
+        )),
+        StartExecResults::Detached => {
+            anyhow::bail!(
+                "Exec {} in container {container_name} is not attached",
+                exec.id
+            )
+        }
+    }
+}
+
+/// Resizes the tty of the exec instance, fails if the exec instance was not started in the
+/// specified container
+pub async fn resize_exec(
+    docker_client: Arc<Docker>,
+    container_name: &str,
+    exec_id: &str,
+    width: u16,
+    height: u16,
+) -> Result<()> {
+    let exec = docker_client
+        .inspect_exec(exec_id)
+        .await
+        .map_err(map_bollard_error)?;
+    let container = docker_client
+        .inspect_container(container_name, None)
+        .await
+        .map_err(map_bollard_error)?;
+    anyhow::ensure!(
+        exec.container_id.is_some() && exec.container_id == container.id,
+        "Exec {exec_id} does not belong to container {container_name}"
+    );
+    docker_client
+        .resize_exec(exec_id, ResizeExecOptions { height, width })
+        .await
+        .map_err(map_bollard_error)
+}
+
 /// # Example
 /// ```no_run
 /// use bollard::Docker;
@@ -450,6 +638,22 @@ pub async fn copy_archive_from(
     result.await
 }
 
+/// Lists the entries of the archive of `src` in the container. Only the headers are read while
+/// the archive is downloaded, the content is neither kept in memory nor written to disk.
+pub async fn list_archive_entries(
+    docker_client: Arc<Docker>,
+    src: &Path,
+    container_name: &str,
+) -> Result<Vec<EntryHeader>> {
+    let options = Some(DownloadFromContainerOptions {
+        path: src.to_string_lossy().to_string(),
+    });
+    let stream = docker_client
+        .download_from_container(container_name, options)
+        .map_err(std::io::Error::other);
+    read_headers(Box::pin(stream).into_async_read().compat()).await
+}
+
 /// Copies the content in the container at the given path into an archive at the specified location.
 pub async fn copy_archive_to_file(
     quest: SyncQuest,
@@ -1620,4 +1824,107 @@ mod tests {
         assert!(inspect(client, "12345678").await.unwrap().is_none());
         mock.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn exec_to_completion_err() {
+        let (mut mock_server, client) = create_test_server_and_config().await;
+        let body = serde_json::to_vec(&serde_json::json!({
+            "message": "Container test_container is not running"
+        }))
+        .unwrap();
+        let mock = mock_server
+            .mock("POST", "/containers/test_container/exec")
+            .with_status(409)
+            .with_body(&body)
+            .create_async()
+            .await;
+        assert!(
+            exec_to_completion(
+                client,
+                "test_container",
+                vec!["ls".to_string()],
+                Duration::from_secs(10)
+            )
+            .await
+            .is_err()
+        );
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn exec_output_collector_truncates() {
+        let mut collector = ExecOutputCollector::default();
+        collector.push_stdout(b"out");
+        collector.push_stderr(&vec![b'e'; EXEC_OUTPUT_LIMIT - 1]);
+        assert!(!collector.truncated);
+        collector.push_stderr(b"rr");
+        collector.push_stderr(b"more");
+        let exec = collector.complete(Some(1));
+        assert_eq!(exec.exit_code, Some(1));
+        assert_eq!(exec.stdout, "out");
+        assert_eq!(exec.stderr.len(), EXEC_OUTPUT_LIMIT);
+        assert!(exec.stderr.ends_with("er"));
+        assert!(exec.truncated);
+    }
+
+    #[tokio::test]
+    async fn resize_exec_ok() {
+        let (mut mock_server, client) = create_test_server_and_config().await;
+        let exec_mock = mock_server
+            .mock("GET", "/exec/12345678/json")
+            .with_status(200)
+            .with_body(r#"{"ContainerID": "c0ffee"}"#)
+            .create_async()
+            .await;
+        let container_mock = mock_server
+            .mock("GET", "/containers/test/json")
+            .with_status(200)
+            .with_body(r#"{"Id": "c0ffee"}"#)
+            .create_async()
+            .await;
+        let mock = mock_server
+            .mock("POST", "/exec/12345678/resize")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("h".to_string(), "24".to_string()),
+                mockito::Matcher::UrlEncoded("w".to_string(), "80".to_string()),
+            ]))
+            .with_status(200)
+            .create_async()
+            .await;
+        resize_exec(client, "test", "12345678", 80, 24)
+            .await
+            .unwrap();
+        exec_mock.assert_async().await;
+        container_mock.assert_async().await;
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn resize_exec_other_container() {
+        let (mut mock_server, client) = create_test_server_and_config().await;
+        mock_server
+            .mock("GET", "/exec/12345678/json")
+            .with_status(200)
+            .with_body(r#"{"ContainerID": "c0ffee"}"#)
+            .create_async()
+            .await;
+        mock_server
+            .mock("GET", "/containers/test/json")
+            .with_status(200)
+            .with_body(r#"{"Id": "decaf"}"#)
+            .create_async()
+            .await;
+        let mock = mock_server
+            .mock("POST", "/exec/12345678/resize")
+            .match_query(mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        assert!(
+            resize_exec(client, "test", "12345678", 80, 24)
+                .await
+                .is_err()
+        );
+        mock.assert_async().await;
+    }
 }
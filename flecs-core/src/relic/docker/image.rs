@@ -13,6 +13,7 @@ use std::default::Default;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::fs::File;
+use tokio::io::AsyncRead;
 use tokio_util::codec;
 use tracing::{debug, trace};
 
@@ -392,9 +393,19 @@ pub async fn load(
 ) -> Result<()> {
     debug!("Import image from {path:?}");
     let file = File::open(path).await?;
+    load_from_reader(quest, docker_client, file, options, credentials).await
+}
 
+/// Loads an image archive from `reader`, see [load]
+pub async fn load_from_reader<R: AsyncRead + Send + Unpin + 'static>(
+    quest: SyncQuest,
+    docker_client: Arc<Docker>,
+    reader: R,
+    options: ImportImageOptions,
+    credentials: Option<HashMap<String, DockerCredentials>>,
+) -> Result<()> {
     let byte_stream =
-        codec::FramedRead::new(file, codec::BytesCodec::new()).map(|r| r.unwrap().freeze());
+        codec::FramedRead::new(reader, codec::BytesCodec::new()).map(|r| r.unwrap().freeze());
 
     let mut stream = docker_client.import_image_stream(options, byte_stream, credentials);
 
@@ -2,6 +2,7 @@
 pub mod container;
 pub mod image;
 pub mod network;
+pub mod system;
 pub mod volume;
 
 pub use super::{Error, Result};
@@ -0,0 +1,23 @@
+pub use super::Result;
+use crate::relic::docker::map_bollard_error;
+use bollard::Docker;
+use bollard::models::SystemDataUsageResponse;
+use std::sync::Arc;
+
+/// # Example
+/// ```no_run
+/// use bollard::Docker;
+/// use flecs_core::relic::docker::system::disk_usage;
+/// use std::sync::Arc;
+///
+/// # tokio_test::block_on(
+/// async {
+///     let docker_client = Arc::new(Docker::connect_with_local_defaults().unwrap());
+///     let usage = disk_usage(docker_client).await.unwrap();
+///     println!("{:#?}", usage.images);
+/// }
+/// # )
+/// ```
+pub async fn disk_usage(docker_client: Arc<Docker>) -> Result<SystemDataUsageResponse> {
+    docker_client.df().await.map_err(map_bollard_error)
+}
@@ -0,0 +1,374 @@
+use age::secrecy::SecretString;
+use anyhow::Context;
+use std::fmt::{Debug, Formatter};
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::io::AsyncReadExt;
+
+/// Extension appended to the file name of encrypted files, e.g. 1234.tar.age
+pub const EXTENSION: &str = "age";
+/// Every age encrypted file starts with this header
+const HEADER: &[u8] = b"age-encryption.org/v1";
+
+#[derive(thiserror::Error, Debug)]
+pub enum DecryptionError {
+    #[error("File is encrypted but no passphrase or identity was supplied")]
+    NoKeyMaterial,
+    #[error("File could not be decrypted with any of the supplied passphrases or identities")]
+    NoMatchingKey,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Key material used to encrypt a file with [age](https://age-encryption.org)
+#[derive(Clone)]
+pub enum Encryption {
+    Passphrase(String),
+    /// X25519 public keys (age1...), every recipient can decrypt the file
+    Recipients(Vec<String>),
+}
+
+impl Debug for Encryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passphrase(_) => f.write_str("Passphrase(***)"),
+            Self::Recipients(recipients) => f.debug_tuple("Recipients").field(recipients).finish(),
+        }
+    }
+}
+
+/// Key material which is tried to decrypt a file encrypted with [age](https://age-encryption.org)
+#[derive(Clone, Default)]
+pub struct Decryption {
+    pub passphrases: Vec<String>,
+    /// Content of age identity files, i.e. one or more X25519 secret keys (AGE-SECRET-KEY-1...)
+    pub identities: Vec<String>,
+}
+
+impl Debug for Decryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Decryption")
+            .field("passphrases", &self.passphrases.len())
+            .field("identities", &self.identities.len())
+            .finish()
+    }
+}
+
+impl Decryption {
+    pub fn is_empty(&self) -> bool {
+        self.passphrases.is_empty() && self.identities.is_empty()
+    }
+
+    pub fn extend(&mut self, other: Decryption) {
+        self.passphrases.extend(other.passphrases);
+        self.identities.extend(other.identities);
+    }
+
+    /// Reads the passphrase from `passphrase_path` and the identities from `identity_paths`
+    pub async fn from_files(
+        passphrase_path: Option<&Path>,
+        identity_paths: &[PathBuf],
+    ) -> anyhow::Result<Self> {
+        let mut decryption = Self::default();
+        if let Some(passphrase_path) = passphrase_path {
+            decryption
+                .passphrases
+                .push(read_passphrase(passphrase_path).await?);
+        }
+        for identity_path in identity_paths {
+            decryption.identities.push(
+                tokio::fs::read_to_string(identity_path)
+                    .await
+                    .with_context(|| format!("Could not read identity file {identity_path:?}"))?,
+            );
+        }
+        Ok(decryption)
+    }
+}
+
+/// Reads a passphrase from the first line of the file at `path`
+pub async fn read_passphrase(path: &Path) -> anyhow::Result<String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Could not read passphrase file {path:?}"))?;
+    let passphrase = content.lines().next().unwrap_or_default();
+    anyhow::ensure!(!passphrase.is_empty(), "Passphrase file {path:?} is empty");
+    Ok(passphrase.to_string())
+}
+
+/// Checks if the file at `path` starts with the age header
+pub async fn is_encrypted(path: &Path) -> std::io::Result<bool> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut header = [0; HEADER.len()];
+    match file.read_exact(&mut header).await {
+        Ok(_) => Ok(header == HEADER),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn encrypt_file(
+    src: PathBuf,
+    dst: PathBuf,
+    encryption: Encryption,
+) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || encrypt_file_blocking(&src, &dst, encryption)).await?
+}
+
+fn encrypt_file_blocking(src: &Path, dst: &Path, encryption: Encryption) -> anyhow::Result<()> {
+    let encryptor = match encryption {
+        Encryption::Passphrase(passphrase) => {
+            age::Encryptor::with_user_passphrase(SecretString::from(passphrase))
+        }
+        Encryption::Recipients(recipients) => {
+            let recipients = recipients
+                .iter()
+                .map(|recipient| {
+                    age::x25519::Recipient::from_str(recipient.trim())
+                        .map_err(|e| anyhow::anyhow!("Invalid recipient {recipient}: {e}"))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            age::Encryptor::with_recipients(
+                recipients
+                    .iter()
+                    .map(|recipient| recipient as &dyn age::Recipient),
+            )?
+        }
+    };
+    let mut src = std::fs::File::open(src)?;
+    let dst = BufWriter::new(std::fs::File::create(dst)?);
+    let mut writer = encryptor.wrap_output(dst)?;
+    std::io::copy(&mut src, &mut writer)?;
+    writer.finish()?.flush()?;
+    Ok(())
+}
+
+pub async fn decrypt_file(
+    src: PathBuf,
+    dst: PathBuf,
+    decryption: Decryption,
+) -> Result<(), DecryptionError> {
+    tokio::task::spawn_blocking(move || decrypt_file_blocking(&src, &dst, decryption))
+        .await
+        .map_err(anyhow::Error::from)?
+}
+
+fn open_decryptor(src: &Path) -> anyhow::Result<age::Decryptor<BufReader<std::fs::File>>> {
+    Ok(age::Decryptor::new_buffered(BufReader::new(
+        std::fs::File::open(src)?,
+    ))?)
+}
+
+fn decrypt_file_blocking(
+    src: &Path,
+    dst: &Path,
+    decryption: Decryption,
+) -> Result<(), DecryptionError> {
+    if decryption.is_empty() {
+        return Err(DecryptionError::NoKeyMaterial);
+    }
+    let decryptor = open_decryptor(src)?;
+    let mut reader = if decryptor.is_scrypt() {
+        // A wrong passphrase aborts the decryption, so every passphrase is tried separately
+        let mut decryptor = Some(decryptor);
+        let mut result = None;
+        for passphrase in decryption.passphrases {
+            let identity = age::scrypt::Identity::new(SecretString::from(passphrase));
+            let decryptor = match decryptor.take() {
+                Some(decryptor) => decryptor,
+                None => open_decryptor(src)?,
+            };
+            if let Ok(reader) = decryptor.decrypt(std::iter::once(&identity as &dyn age::Identity))
+            {
+                result = Some(reader);
+                break;
+            }
+        }
+        result.ok_or(DecryptionError::NoMatchingKey)?
+    } else {
+        let mut identities = Vec::new();
+        for identity in &decryption.identities {
+            identities.extend(
+                age::IdentityFile::from_buffer(identity.as_bytes())
+                    .context("Invalid identity")?
+                    .into_identities()
+                    .context("Invalid identity")?,
+            );
+        }
+        match decryptor.decrypt(
+            identities
+                .iter()
+                .map(|identity| identity.as_ref() as &dyn age::Identity),
+        ) {
+            Ok(reader) => reader,
+            Err(age::DecryptError::NoMatchingKeys) => return Err(DecryptionError::NoMatchingKey),
+            Err(e) => return Err(anyhow::Error::from(e).into()),
+        }
+    };
+    let mut dst = BufWriter::new(std::fs::File::create(dst).context("Could not create file")?);
+    std::io::copy(&mut reader, &mut dst).context("Could not decrypt file")?;
+    dst.flush().context("Could not write decrypted file")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use age::secrecy::ExposeSecret;
+    use testdir::testdir;
+
+    const DATA: &[u8] = b"some secret data";
+
+    fn prepare_file() -> (PathBuf, PathBuf, PathBuf) {
+        let path = testdir!();
+        let src = path.join("data.tar");
+        std::fs::write(&src, DATA).unwrap();
+        (src, path.join("data.tar.age"), path.join("decrypted.tar"))
+    }
+
+    #[tokio::test]
+    async fn passphrase_roundtrip() {
+        let (src, encrypted, decrypted) = prepare_file();
+        encrypt_file(
+            src.clone(),
+            encrypted.clone(),
+            Encryption::Passphrase("correct horse".to_string()),
+        )
+        .await
+        .unwrap();
+        assert!(!is_encrypted(&src).await.unwrap());
+        assert!(is_encrypted(&encrypted).await.unwrap());
+        decrypt_file(
+            encrypted,
+            decrypted.clone(),
+            Decryption {
+                passphrases: vec!["wrong".to_string(), "correct horse".to_string()],
+                identities: Vec::new(),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(decrypted).unwrap(), DATA);
+    }
+
+    #[tokio::test]
+    async fn recipients_roundtrip() {
+        let (src, encrypted, decrypted) = prepare_file();
+        let identity = age::x25519::Identity::generate();
+        encrypt_file(
+            src,
+            encrypted.clone(),
+            Encryption::Recipients(vec![identity.to_public().to_string()]),
+        )
+        .await
+        .unwrap();
+        decrypt_file(
+            encrypted,
+            decrypted.clone(),
+            Decryption {
+                passphrases: Vec::new(),
+                identities: vec![format!(
+                    "# created: test\n{}\n",
+                    identity.to_string().expose_secret()
+                )],
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(decrypted).unwrap(), DATA);
+    }
+
+    #[tokio::test]
+    async fn decrypt_no_matching_key() {
+        let (src, encrypted, decrypted) = prepare_file();
+        encrypt_file(
+            src,
+            encrypted.clone(),
+            Encryption::Recipients(vec![
+                age::x25519::Identity::generate().to_public().to_string(),
+            ]),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            decrypt_file(
+                encrypted,
+                decrypted,
+                Decryption {
+                    passphrases: vec!["passphrase".to_string()],
+                    identities: vec![
+                        age::x25519::Identity::generate()
+                            .to_string()
+                            .expose_secret()
+                            .to_string()
+                    ],
+                },
+            )
+            .await,
+            Err(DecryptionError::NoMatchingKey)
+        ));
+    }
+
+    #[tokio::test]
+    async fn decrypt_no_key_material() {
+        let (src, encrypted, decrypted) = prepare_file();
+        encrypt_file(
+            src,
+            encrypted.clone(),
+            Encryption::Passphrase("passphrase".to_string()),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            decrypt_file(encrypted, decrypted, Decryption::default()).await,
+            Err(DecryptionError::NoKeyMaterial)
+        ));
+    }
+
+    #[tokio::test]
+    async fn encrypt_invalid_recipient() {
+        let (src, encrypted, _) = prepare_file();
+        assert!(
+            encrypt_file(
+                src,
+                encrypted,
+                Encryption::Recipients(vec!["age1invalid".to_string()])
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn read_passphrase_ok() {
+        let path = testdir!().join("passphrase");
+        std::fs::write(&path, b"correct horse\n").unwrap();
+        assert_eq!(read_passphrase(&path).await.unwrap(), "correct horse");
+    }
+
+    #[tokio::test]
+    async fn read_passphrase_empty() {
+        let path = testdir!().join("passphrase");
+        std::fs::write(&path, b"\n").unwrap();
+        assert!(read_passphrase(&path).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn decryption_from_files() {
+        let path = testdir!();
+        std::fs::write(path.join("passphrase"), b"pass").unwrap();
+        std::fs::write(path.join("identity"), b"AGE-SECRET-KEY-1").unwrap();
+        let decryption =
+            Decryption::from_files(Some(&path.join("passphrase")), &[path.join("identity")])
+                .await
+                .unwrap();
+        assert_eq!(decryption.passphrases, vec!["pass".to_string()]);
+        assert_eq!(decryption.identities, vec!["AGE-SECRET-KEY-1".to_string()]);
+        assert!(
+            Decryption::from_files(None, &[path.join("missing")])
+                .await
+                .is_err()
+        );
+    }
+}
@@ -2,6 +2,7 @@ mod floxy_impl;
 
 use crate::jeweler::gem::instance::InstanceId;
 use crate::lore::FloxyLoreRef;
+use crate::relic::tls::CertifiedKey;
 pub use floxy_impl::FloxyImpl;
 #[cfg(test)]
 use mockall::{automock, predicate::*};
@@ -13,8 +14,22 @@ pub struct AdditionalLocationInfo {
     pub location: String,
 }
 
+/// Dedicated host name an editor is served under
+pub struct EditorHostInfo {
+    pub port: u16,
+    pub host_name: String,
+    /// TLS is terminated with this certificate, plain http is used if not set
+    pub tls: Option<CertifiedKey>,
+    /// Requests are only forwarded if the core authorizes them, see
+    /// [crate::jeweler::gem::manifest::single::EditorRoles]
+    pub restricted: bool,
+}
+
 #[cfg_attr(test, automock)]
 pub trait Floxy: Send + Sync + Display {
+    /// Serves the editors with the given `dest_ports`, requests to the editors in
+    /// `restricted_ports` are only forwarded if the core authorizes them
+    #[allow(clippy::too_many_arguments)]
     fn add_instance_reverse_proxy_config(
         &self,
         lore: FloxyLoreRef,
@@ -22,6 +37,7 @@ pub trait Floxy: Send + Sync + Display {
         instance_id: InstanceId,
         instance_ip: IpAddr,
         dest_ports: &[u16],
+        restricted_ports: &[u16],
         auth_provider_port: Option<u16>,
     ) -> crate::Result<()>;
 
@@ -63,6 +79,8 @@ pub trait Floxy: Send + Sync + Display {
         host_ports: &[u16],
     ) -> crate::Result<()>;
 
+    /// Serves the editor with the given `dest_port` on a free host port which is returned,
+    /// requests are only forwarded if the core authorizes them if `restricted` is set
     fn add_instance_editor_redirect_to_free_port(
         &self,
         lore: FloxyLoreRef,
@@ -70,8 +88,23 @@ pub trait Floxy: Send + Sync + Display {
         instance_id: InstanceId,
         instance_ip: IpAddr,
         dest_port: u16,
+        restricted: bool,
     ) -> crate::Result<u16>;
 
+    /// Serves the editor with the given `dest_port` on `host_port`, requests are only forwarded
+    /// if the core authorizes them if `restricted` is set
+    #[allow(clippy::too_many_arguments)]
+    fn add_instance_editor_server_config(
+        &self,
+        lore: FloxyLoreRef,
+        app_name: &str,
+        instance_id: InstanceId,
+        instance_ip: IpAddr,
+        host_port: u16,
+        dest_port: u16,
+        restricted: bool,
+    ) -> crate::Result<()>;
+
     fn add_instance_redirect(
         &self,
         lore: FloxyLoreRef,
@@ -82,6 +115,32 @@ pub trait Floxy: Send + Sync + Display {
         dest_port: u16,
     ) -> crate::Result<()>;
 
+    /// Serves the editor of the instance on its own server for the given host name
+    fn add_instance_editor_host_config(
+        &self,
+        lore: FloxyLoreRef,
+        app_name: &str,
+        instance_id: InstanceId,
+        instance_ip: IpAddr,
+        editor_host: &EditorHostInfo,
+    ) -> crate::Result<()>;
+
+    fn delete_instance_editor_host_config(
+        &self,
+        lore: FloxyLoreRef,
+        app_name: &str,
+        instance_id: InstanceId,
+        port: u16,
+    ) -> crate::Result<()>;
+
+    /// Installs the device server certificate at `conf.d/certs/device.crt` and
+    /// `conf.d/certs/device.key` and reloads the proxy
+    fn set_device_certificate(
+        &self,
+        lore: FloxyLoreRef,
+        certified_key: &CertifiedKey,
+    ) -> crate::Result<()>;
+
     fn clear_server_configs(&self, lore: FloxyLoreRef) -> crate::Result<()>;
     fn clear_instance_configs(&self, lore: FloxyLoreRef) -> crate::Result<()>;
 }
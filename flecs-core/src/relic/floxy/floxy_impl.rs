@@ -1,14 +1,27 @@
 use crate::jeweler::gem::instance::InstanceId;
+use crate::lore::conf::FloxyReload;
+use crate::lore::default::floxy::{CERTIFICATES_DIR_NAME, DEVICE_CERTIFICATE_NAME};
 use crate::lore::{FloxyLore, FloxyLoreRef};
-use crate::relic::floxy::{AdditionalLocationInfo, Floxy};
+use crate::relic::floxy::{AdditionalLocationInfo, EditorHostInfo, Floxy};
 use crate::relic::network::get_random_free_port;
+use crate::relic::process::{send_signal, signal};
+use crate::relic::tls::CertifiedKey;
+use anyhow::Context;
 use std::fmt::{Display, Formatter};
 use std::fs::DirEntry;
+use std::io::Read;
 use std::net::IpAddr;
 use std::path::{Path, PathBuf};
-use tracing::{debug, error, info};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+/// Config test and reload commands are killed if they do not finish within this time
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
 
 const CONFIG_EXTENSION: &str = "conf";
+const HTTP_PORT: u16 = 80;
+const HTTPS_PORT: u16 = 443;
 #[derive(Default)]
 pub struct FloxyImpl;
 
@@ -20,12 +33,15 @@ impl Floxy for FloxyImpl {
         instance_id: InstanceId,
         instance_ip: IpAddr,
         dest_ports: &[u16],
+        restricted_ports: &[u16],
         auth_provider_port: Option<u16>,
     ) -> anyhow::Result<()> {
         let config_content = Self::create_instance_reverse_proxy_config(
+            &lore.as_ref().as_ref().core_url,
             instance_id,
             instance_ip,
             dest_ports.iter(),
+            restricted_ports,
             auth_provider_port,
         );
         let config_path = Self::build_instance_config_path(&lore, app_name, instance_id);
@@ -66,7 +82,7 @@ impl Floxy for FloxyImpl {
                 debug!(
                     "Removed additional locations reverse proxy config for instance {instance_id} at {config_path:?}."
                 );
-                Ok(())
+                Self::reload(&lore)
             }
             Err(e) => Err(anyhow::anyhow!("Error deleting {config_path:?}: {e}")),
         }
@@ -87,7 +103,7 @@ impl Floxy for FloxyImpl {
                 debug!(
                     "Removed reverse proxy config for instance {instance_id} at {config_path:?}."
                 );
-                Ok(())
+                Self::reload(&lore)
             }
             Err(e) => Err(anyhow::anyhow!("Error deleting {config_path:?}: {e}")),
         }
@@ -109,7 +125,7 @@ impl Floxy for FloxyImpl {
                 debug!(
                     "Removed server config for instance {instance_id} and port {host_port} at {config_path:?}."
                 );
-                Ok(())
+                Self::reload(&lore)
             }
             Err(e) => Err(anyhow::anyhow!("Error deleting {config_path:?}: {e}")),
         }
@@ -149,19 +165,47 @@ impl Floxy for FloxyImpl {
         instance_id: InstanceId,
         instance_ip: IpAddr,
         dest_port: u16,
+        restricted: bool,
     ) -> crate::Result<u16> {
         let free_port = get_random_free_port()?;
-        self.add_instance_redirect(
+        self.add_instance_editor_server_config(
             lore,
             app_name,
             instance_id,
             instance_ip,
             free_port,
             dest_port,
+            restricted,
         )?;
         Ok(free_port)
     }
 
+    fn add_instance_editor_server_config(
+        &self,
+        lore: FloxyLoreRef,
+        app_name: &str,
+        instance_id: InstanceId,
+        instance_ip: IpAddr,
+        host_port: u16,
+        dest_port: u16,
+        restricted: bool,
+    ) -> anyhow::Result<()> {
+        let auth_core_url = restricted.then_some(lore.as_ref().as_ref().core_url.as_str());
+        let config_content = Self::create_editor_server_config(
+            instance_id,
+            instance_ip,
+            host_port,
+            dest_port,
+            auth_core_url,
+        );
+        let config_path = Self::build_server_config_path(&lore, app_name, instance_id, host_port);
+        Self::add_reverse_proxy_config(lore, &config_content, &config_path)?;
+        debug!(
+            "Added server config for editor {dest_port} of instance {instance_id} at {config_path:?}: host:{host_port} -> {instance_ip}:{dest_port}"
+        );
+        Ok(())
+    }
+
     fn add_instance_redirect(
         &self,
         lore: FloxyLoreRef,
@@ -180,6 +224,94 @@ impl Floxy for FloxyImpl {
         Ok(())
     }
 
+    fn add_instance_editor_host_config(
+        &self,
+        lore: FloxyLoreRef,
+        app_name: &str,
+        instance_id: InstanceId,
+        instance_ip: IpAddr,
+        editor_host: &EditorHostInfo,
+    ) -> anyhow::Result<()> {
+        let port = editor_host.port;
+        let auth_core_url = editor_host
+            .restricted
+            .then_some(lore.as_ref().as_ref().core_url.as_str());
+        let config_content = match &editor_host.tls {
+            None => Self::create_editor_host_config(
+                &editor_host.host_name,
+                instance_id,
+                instance_ip,
+                port,
+                auth_core_url,
+            ),
+            Some(certified_key) => {
+                let (certificate_path, private_key_path) =
+                    Self::build_editor_certificate_paths(&lore, instance_id, port);
+                certified_key.write_to(&certificate_path, &private_key_path)?;
+                Self::create_editor_host_tls_config(
+                    &editor_host.host_name,
+                    instance_id,
+                    instance_ip,
+                    port,
+                    &Self::editor_certificate_name(instance_id, port),
+                    auth_core_url,
+                )
+            }
+        };
+        let config_path = Self::build_editor_host_config_path(&lore, app_name, instance_id, port);
+        Self::add_reverse_proxy_config(lore, &config_content, &config_path)?;
+        debug!(
+            "Added host config for editor {port} of instance {instance_id} at {config_path:?}: {} -> {instance_ip}:{port}",
+            editor_host.host_name
+        );
+        Ok(())
+    }
+
+    fn delete_instance_editor_host_config(
+        &self,
+        lore: FloxyLoreRef,
+        app_name: &str,
+        instance_id: InstanceId,
+        port: u16,
+    ) -> anyhow::Result<()> {
+        let config_path = Self::build_editor_host_config_path(&lore, app_name, instance_id, port);
+        if matches!(config_path.try_exists(), Ok(true)) {
+            std::fs::remove_file(&config_path)
+                .map_err(|e| anyhow::anyhow!("Error deleting {config_path:?}: {e}"))?;
+            debug!(
+                "Removed host config for editor {port} of instance {instance_id} at {config_path:?}."
+            );
+            Self::reload(&lore)?;
+        }
+        let (certificate_path, private_key_path) =
+            Self::build_editor_certificate_paths(&lore, instance_id, port);
+        for path in [certificate_path, private_key_path] {
+            if matches!(path.try_exists(), Ok(true)) {
+                std::fs::remove_file(&path)
+                    .map_err(|e| anyhow::anyhow!("Error deleting {path:?}: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_device_certificate(
+        &self,
+        lore: FloxyLoreRef,
+        certified_key: &CertifiedKey,
+    ) -> anyhow::Result<()> {
+        let certificate_path = lore.as_ref().as_ref().certificate_path();
+        certified_key
+            .write_to(
+                &certificate_path.join(format!("{DEVICE_CERTIFICATE_NAME}.crt")),
+                &certificate_path.join(format!("{DEVICE_CERTIFICATE_NAME}.key")),
+            )
+            .with_context(|| {
+                format!("Could not write device certificate to {certificate_path:?}")
+            })?;
+        debug!("Installed device certificate at {certificate_path:?}");
+        Self::reload(&lore)
+    }
+
     fn clear_server_configs(&self, lore: FloxyLoreRef) -> anyhow::Result<()> {
         let server_dir = lore.as_ref().as_ref().server_config_path();
         Self::clear_configs(&lore, &server_dir)
@@ -199,24 +331,35 @@ impl Display for FloxyImpl {
 
 impl FloxyImpl {
     fn create_instance_reverse_proxy_config<'a, I: Iterator<Item = &'a u16>>(
+        core_url: &str,
         instance_id: InstanceId,
         instance_ip: IpAddr,
         dest_ports: I,
+        restricted_ports: &[u16],
         auth_provider_port: Option<u16>,
     ) -> String {
         dest_ports
             .map(|port| {
-                Self::create_instance_config(
-                    instance_ip,
-                    *port,
-                    &FloxyLore::instance_editor_location(instance_id, *port),
-                )
+                let location = FloxyLore::instance_editor_location(instance_id, *port);
+                if restricted_ports.contains(port) {
+                    let auth_location =
+                        FloxyLore::instance_editor_auth_location(instance_id, *port);
+                    Self::create_instance_config(
+                        instance_ip,
+                        *port,
+                        &location,
+                        Some(&auth_location),
+                    ) + &Self::create_editor_auth_location_config(core_url, instance_id, *port, "")
+                } else {
+                    Self::create_instance_config(instance_ip, *port, &location, None)
+                }
             })
             .chain(auth_provider_port.map(|auth_provider_port| {
                 Self::create_instance_config(
                     instance_ip,
                     auth_provider_port,
                     &FloxyLore::auth_provider_location(instance_id),
+                    None,
                 )
             }))
             .collect::<String>()
@@ -246,7 +389,9 @@ impl FloxyImpl {
     }
 
     /// Creates a config with the given content at the given path. Returns Ok(true) if the file
-    /// was created and Ok(false) if the file with the exact content already exists.
+    /// was created and Ok(false) if the file with the exact content already exists. If the
+    /// changed configuration fails the configured test the previous config is restored and an
+    /// error is returned, otherwise the proxy is reloaded.
     fn add_reverse_proxy_config(
         lore: FloxyLoreRef,
         config: &str,
@@ -256,18 +401,122 @@ impl FloxyImpl {
             config_path.starts_with(&lore.as_ref().as_ref().base_path),
             "The config path ({config_path:?}) has to be inside the floxy base directory"
         );
-        if let Ok(true) = config_path.try_exists() {
-            if std::fs::read_to_string(config_path)? == config {
-                return Ok(false);
-            }
+        let previous_config = match config_path.try_exists() {
+            Ok(true) => Some(std::fs::read_to_string(config_path)?),
+            _ => None,
+        };
+        if previous_config.as_deref() == Some(config) {
+            return Ok(false);
         }
         if let Some(parent) = config_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
         std::fs::write(config_path, config.as_bytes())?;
+        if let Err(e) = Self::test_config(&lore) {
+            let rollback = match &previous_config {
+                Some(previous_config) => std::fs::write(config_path, previous_config.as_bytes()),
+                None => std::fs::remove_file(config_path),
+            };
+            return Err(match rollback {
+                Ok(()) => e.context(format!("Reverted invalid proxy config {config_path:?}")),
+                Err(rollback_error) => e.context(format!(
+                    "Could not revert invalid proxy config {config_path:?}: {rollback_error}"
+                )),
+            });
+        }
+        Self::reload(&lore)?;
         Ok(true)
     }
 
+    /// Runs the configured config test command, succeeds if none is configured
+    fn test_config(lore: &FloxyLoreRef) -> crate::Result<()> {
+        match &lore.as_ref().as_ref().config_test_command {
+            None => Ok(()),
+            Some(command) => Self::run_command(command).context("Proxy config test failed"),
+        }
+    }
+
+    /// Tells the proxy to reload its configuration as configured, does nothing if no reload is
+    /// configured
+    fn reload(lore: &FloxyLoreRef) -> crate::Result<()> {
+        let result = match &lore.as_ref().as_ref().reload {
+            None => return Ok(()),
+            Some(FloxyReload::Command(command)) => Self::run_command(command),
+            Some(FloxyReload::PidFile(pid_file)) => {
+                Self::read_pid_file(pid_file).and_then(|pid| send_signal(pid, signal::SIGHUP))
+            }
+        };
+        match result {
+            Ok(()) => {
+                debug!("Reloaded proxy configuration");
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Could not reload proxy configuration: {e}");
+                Err(e.context("Could not reload proxy configuration"))
+            }
+        }
+    }
+
+    fn read_pid_file(pid_file: &Path) -> crate::Result<i32> {
+        let content = std::fs::read_to_string(pid_file)
+            .with_context(|| format!("Could not read pid file {pid_file:?}"))?;
+        content
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid pid in {pid_file:?}: {content:?}"))
+    }
+
+    fn run_command(command: &[String]) -> crate::Result<()> {
+        Self::run_command_with_timeout(command, COMMAND_TIMEOUT)
+    }
+
+    /// Runs the given command and returns an error containing its output if it does not succeed.
+    /// The command is killed if it does not finish within `timeout`.
+    fn run_command_with_timeout(command: &[String], timeout: Duration) -> crate::Result<()> {
+        let Some((program, args)) = command.split_first() else {
+            anyhow::bail!("Empty command");
+        };
+        let mut child = std::process::Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Could not run {command:?}"))?;
+        // Stderr is read concurrently, otherwise a command filling the pipe would never finish
+        let mut stderr = child.stderr.take().expect("Stderr of the command is piped");
+        let reader = std::thread::spawn(move || {
+            let mut output = Vec::new();
+            _ = stderr.read_to_end(&mut output);
+            output
+        });
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child
+                .try_wait()
+                .with_context(|| format!("Could not wait for {command:?}"))?
+            {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                if let Err(e) = child.kill() {
+                    warn!("Could not kill {command:?}: {e}");
+                }
+                _ = child.wait();
+                anyhow::bail!("{command:?} did not finish within {timeout:?}");
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let stderr = reader.join().unwrap_or_default();
+        anyhow::ensure!(
+            status.success(),
+            "{command:?} failed with {status}: {}",
+            String::from_utf8_lossy(&stderr).trim()
+        );
+        Ok(())
+    }
+
     fn build_server_config_path(
         lore: &FloxyLoreRef,
         app_name: &str,
@@ -279,6 +528,34 @@ impl FloxyImpl {
         ))
     }
 
+    fn build_editor_host_config_path(
+        lore: &FloxyLoreRef,
+        app_name: &str,
+        instance_id: InstanceId,
+        port: u16,
+    ) -> PathBuf {
+        lore.as_ref().as_ref().server_config_path().join(format!(
+            "{app_name}-{instance_id}-editor_{port}.{CONFIG_EXTENSION}"
+        ))
+    }
+
+    fn editor_certificate_name(instance_id: InstanceId, port: u16) -> String {
+        format!("editor-{instance_id}_{port}")
+    }
+
+    fn build_editor_certificate_paths(
+        lore: &FloxyLoreRef,
+        instance_id: InstanceId,
+        port: u16,
+    ) -> (PathBuf, PathBuf) {
+        let name = Self::editor_certificate_name(instance_id, port);
+        let certificate_path = lore.as_ref().as_ref().certificate_path();
+        (
+            certificate_path.join(format!("{name}.crt")),
+            certificate_path.join(format!("{name}.key")),
+        )
+    }
+
     fn build_instance_config_path(
         lore: &FloxyLoreRef,
         app_name: &str,
@@ -317,10 +594,19 @@ impl FloxyImpl {
         Ok(())
     }
 
-    fn create_instance_config(instance_ip: IpAddr, dest_port: u16, location: &str) -> String {
+    /// Requests are only forwarded if the subrequest to `auth_location` succeeds if it is set
+    fn create_instance_config(
+        instance_ip: IpAddr,
+        dest_port: u16,
+        location: &str,
+        auth_location: Option<&str>,
+    ) -> String {
+        let auth_request = auth_location
+            .map(|auth_location| format!("\n  auth_request {auth_location};\n"))
+            .unwrap_or_default();
         format!(
             "
-location {location}/ {{
+location {location}/ {{{auth_request}
   proxy_pass http://{instance_ip}:{dest_port}/;
   proxy_redirect / {location}/;
 
@@ -345,18 +631,150 @@ location ~ ^{additional_location}/(.*) {{
     }
 
     fn create_server_config(instance_ip: IpAddr, host_port: u16, dest_port: u16) -> String {
+        Self::format_server_config(instance_ip, host_port, dest_port, "", "")
+    }
+
+    /// Server config of an editor served on `host_port`, requests are authorized by the core at
+    /// `auth_core_url` if it is set
+    fn create_editor_server_config(
+        instance_id: InstanceId,
+        instance_ip: IpAddr,
+        host_port: u16,
+        dest_port: u16,
+        auth_core_url: Option<&str>,
+    ) -> String {
+        let (auth_request, auth_location) =
+            Self::create_editor_host_auth_configs(instance_id, dest_port, auth_core_url);
+        Self::format_server_config(
+            instance_ip,
+            host_port,
+            dest_port,
+            &auth_request,
+            &auth_location,
+        )
+    }
+
+    fn format_server_config(
+        instance_ip: IpAddr,
+        host_port: u16,
+        dest_port: u16,
+        auth_request: &str,
+        auth_location: &str,
+    ) -> String {
         format!(
             "
 server {{
   listen {host_port};
-  location / {{
+  location / {{{auth_request}
     proxy_pass http://{instance_ip}:{dest_port}/;
 
     include conf.d/include/proxy_headers.conf;
 
     client_max_body_size 0;
     client_body_timeout 30m;
-  }}
+  }}{auth_location}
+}}"
+        )
+    }
+
+    /// Internal location asking the core at `core_url` whether a request to editor `port` is
+    /// authorized, the body of the original request is not passed on
+    fn create_editor_auth_location_config(
+        core_url: &str,
+        instance_id: InstanceId,
+        port: u16,
+        indent: &str,
+    ) -> String {
+        let auth_location = FloxyLore::instance_editor_auth_location(instance_id, port);
+        let access_location = FloxyLore::instance_editor_access_api_location(instance_id, port);
+        format!(
+            "
+{indent}location = {auth_location} {{
+{indent}  internal;
+{indent}  proxy_pass {core_url}{access_location};
+{indent}  proxy_pass_request_body off;
+{indent}  proxy_set_header Content-Length \"\";
+{indent}  proxy_set_header X-Original-Method $request_method;
+{indent}  proxy_set_header X-Original-URI $request_uri;
+{indent}}}"
+        )
+    }
+
+    /// The `auth_request` directive and the internal location it refers to for editors on a
+    /// dedicated server, empty if `auth_core_url` is not set
+    fn create_editor_host_auth_configs(
+        instance_id: InstanceId,
+        dest_port: u16,
+        auth_core_url: Option<&str>,
+    ) -> (String, String) {
+        match auth_core_url {
+            None => Default::default(),
+            Some(core_url) => (
+                format!(
+                    "\n    auth_request {};\n",
+                    FloxyLore::instance_editor_auth_location(instance_id, dest_port)
+                ),
+                Self::create_editor_auth_location_config(core_url, instance_id, dest_port, "  "),
+            ),
+        }
+    }
+
+    fn create_editor_host_config(
+        host_name: &str,
+        instance_id: InstanceId,
+        instance_ip: IpAddr,
+        dest_port: u16,
+        auth_core_url: Option<&str>,
+    ) -> String {
+        let (auth_request, auth_location) =
+            Self::create_editor_host_auth_configs(instance_id, dest_port, auth_core_url);
+        format!(
+            "
+server {{
+  listen {HTTP_PORT};
+  server_name {host_name};
+  location / {{{auth_request}
+    proxy_pass http://{instance_ip}:{dest_port}/;
+
+    include conf.d/include/proxy_headers.conf;
+
+    client_max_body_size 0;
+    client_body_timeout 30m;
+  }}{auth_location}
+}}"
+        )
+    }
+
+    fn create_editor_host_tls_config(
+        host_name: &str,
+        instance_id: InstanceId,
+        instance_ip: IpAddr,
+        dest_port: u16,
+        certificate_name: &str,
+        auth_core_url: Option<&str>,
+    ) -> String {
+        let (auth_request, auth_location) =
+            Self::create_editor_host_auth_configs(instance_id, dest_port, auth_core_url);
+        format!(
+            "
+server {{
+  listen {HTTP_PORT};
+  server_name {host_name};
+  return 301 https://$host$request_uri;
+}}
+server {{
+  listen {HTTPS_PORT} ssl;
+  server_name {host_name};
+  ssl_certificate conf.d/{CERTIFICATES_DIR_NAME}/{certificate_name}.crt;
+  ssl_certificate_key conf.d/{CERTIFICATES_DIR_NAME}/{certificate_name}.key;
+  location / {{{auth_request}
+    proxy_pass http://{instance_ip}:{dest_port}/;
+
+    include conf.d/include/proxy_headers.conf;
+
+    client_max_body_size 0;
+    client_body_timeout 30m;
+  }}{auth_location}
 }}"
         )
     }
@@ -375,7 +793,7 @@ server {{
         }
         if failed_deletes.is_empty() {
             info!("All floxy configs deleted from {path:?} {}", Self);
-            Ok(())
+            Self::reload(lore)
         } else {
             Err(anyhow::anyhow!(
                 "Could not delete all floxy configs from {path:?} ({})",
@@ -434,14 +852,58 @@ location /flecs/instances/1234abcd/editor/7000/ {
     #[test]
     fn create_instance_reverse_proxy_config_test() {
         let config = FloxyImpl::create_instance_reverse_proxy_config(
+            "http://127.0.0.1:8951",
             InstanceId::new(0x1234abcd),
             IpAddr::V4(Ipv4Addr::new(123, 123, 234, 234)),
             TRIPLE_DEST_PORTS.iter(),
+            &[],
             None,
         );
         assert_eq!(config, EXPECTED_TRIPLE_CONFIG);
     }
 
+    #[test]
+    fn create_instance_reverse_proxy_config_restricted() {
+        const EXPECTED_CONFIG: &str = "
+location /flecs/instances/1234abcd/editor/5000/ {
+  proxy_pass http://123.123.234.234:5000/;
+  proxy_redirect / /flecs/instances/1234abcd/editor/5000/;
+
+  include conf.d/include/proxy_headers.conf;
+
+  client_max_body_size 0;
+  client_body_timeout 30m;
+}
+location /flecs/instances/1234abcd/editor/6000/ {
+  auth_request /flecs/auth/instances/1234abcd/editor/6000;
+
+  proxy_pass http://123.123.234.234:6000/;
+  proxy_redirect / /flecs/instances/1234abcd/editor/6000/;
+
+  include conf.d/include/proxy_headers.conf;
+
+  client_max_body_size 0;
+  client_body_timeout 30m;
+}
+location = /flecs/auth/instances/1234abcd/editor/6000 {
+  internal;
+  proxy_pass http://127.0.0.1:8951/v2/instances/1234abcd/editor/6000/access;
+  proxy_pass_request_body off;
+  proxy_set_header Content-Length \"\";
+  proxy_set_header X-Original-Method $request_method;
+  proxy_set_header X-Original-URI $request_uri;
+}";
+        let config = FloxyImpl::create_instance_reverse_proxy_config(
+            "http://127.0.0.1:8951",
+            InstanceId::new(0x1234abcd),
+            IpAddr::V4(Ipv4Addr::new(123, 123, 234, 234)),
+            [5000, 6000].iter(),
+            &[6000, 7000],
+            None,
+        );
+        assert_eq!(config, EXPECTED_CONFIG);
+    }
+
     #[test]
     fn add_instance_reverse_proxy_config_new() {
         let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
@@ -456,6 +918,7 @@ location /flecs/instances/1234abcd/editor/7000/ {
                 InstanceId::new(0x1234abcd),
                 IpAddr::V4(Ipv4Addr::new(123, 123, 234, 234)),
                 &TRIPLE_DEST_PORTS,
+                &[],
                 None
             ),
             Ok(())
@@ -482,6 +945,7 @@ location /flecs/instances/1234abcd/editor/7000/ {
                 InstanceId::new(0x1234abcd),
                 IpAddr::V4(Ipv4Addr::new(123, 123, 234, 234)),
                 &TRIPLE_DEST_PORTS,
+                &[],
                 None
             ),
             Ok(())
@@ -508,6 +972,7 @@ location /flecs/instances/1234abcd/editor/7000/ {
                 InstanceId::new(0x1234abcd),
                 IpAddr::V4(Ipv4Addr::new(123, 123, 234, 234)),
                 &TRIPLE_DEST_PORTS,
+                &[],
                 None
             ),
             Ok(())
@@ -559,6 +1024,7 @@ location TEST_LOCATION/ {
                 IpAddr::V4(Ipv4Addr::new(30, 60, 120, 240)),
                 7799,
                 "TEST_LOCATION",
+                None,
             ),
             EXPECTED_CONFIG
         )
@@ -873,6 +1339,361 @@ location TEST_LOCATION/ {
         assert!(matches!(config_path.try_exists(), Ok(false)));
     }
 
+    fn sh_command(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn create_reverse_proxy_config_test_ok() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.floxy.config_test_command = Some(vec!["true".to_string()]);
+        let lore = Arc::new(lore);
+        let config_path = lore.floxy.base_path.join("test.conf");
+        assert!(matches!(
+            FloxyImpl::add_reverse_proxy_config(lore, "test content", &config_path),
+            Ok(true)
+        ));
+        assert_eq!(
+            "test content",
+            std::fs::read_to_string(config_path).unwrap()
+        );
+    }
+
+    #[test]
+    fn create_reverse_proxy_config_test_failed_restores_previous() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.floxy.config_test_command = Some(sh_command("echo invalid config >&2; false"));
+        let lore = Arc::new(lore);
+        fs::create_dir_all(&lore.floxy.base_path).unwrap();
+        let config_path = lore.floxy.base_path.join("test.conf");
+        std::fs::write(&config_path, "old test content").unwrap();
+        let error =
+            FloxyImpl::add_reverse_proxy_config(lore, "test content", &config_path).unwrap_err();
+        assert!(format!("{error:#}").contains("invalid config"));
+        assert_eq!(
+            "old test content",
+            std::fs::read_to_string(config_path).unwrap()
+        );
+    }
+
+    #[test]
+    fn create_reverse_proxy_config_test_failed_removes_new() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.floxy.config_test_command = Some(vec!["false".to_string()]);
+        let lore = Arc::new(lore);
+        let config_path = lore.floxy.base_path.join("test.conf");
+        assert!(FloxyImpl::add_reverse_proxy_config(lore, "test content", &config_path).is_err());
+        assert!(matches!(config_path.try_exists(), Ok(false)));
+    }
+
+    #[test]
+    fn create_reverse_proxy_config_no_changes_no_reload() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.floxy.reload = Some(FloxyReload::Command(vec!["false".to_string()]));
+        let lore = Arc::new(lore);
+        fs::create_dir_all(&lore.floxy.base_path).unwrap();
+        let config_path = lore.floxy.base_path.join("test.conf");
+        std::fs::write(&config_path, "test content").unwrap();
+        assert!(matches!(
+            FloxyImpl::add_reverse_proxy_config(lore, "test content", &config_path),
+            Ok(false)
+        ));
+    }
+
+    #[test]
+    fn create_reverse_proxy_config_reload_command() {
+        let path = testdir!();
+        let reloaded = path.join("reloaded");
+        let mut lore = lore::test_lore(path, &MockVarReader::new());
+        lore.floxy.reload = Some(FloxyReload::Command(sh_command(&format!(
+            "touch {}",
+            reloaded.display()
+        ))));
+        let lore = Arc::new(lore);
+        let config_path = lore.floxy.base_path.join("test.conf");
+        assert!(matches!(
+            FloxyImpl::add_reverse_proxy_config(lore, "test content", &config_path),
+            Ok(true)
+        ));
+        assert!(matches!(reloaded.try_exists(), Ok(true)));
+    }
+
+    #[test]
+    fn create_reverse_proxy_config_reload_failed_keeps_config() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.floxy.config_test_command = Some(vec!["true".to_string()]);
+        lore.floxy.reload = Some(FloxyReload::Command(vec!["false".to_string()]));
+        let lore = Arc::new(lore);
+        let config_path = lore.floxy.base_path.join("test.conf");
+        assert!(FloxyImpl::add_reverse_proxy_config(lore, "test content", &config_path).is_err());
+        assert_eq!(
+            "test content",
+            std::fs::read_to_string(config_path).unwrap()
+        );
+    }
+
+    #[test]
+    fn reload_pid_file() {
+        let path = testdir!();
+        let pid_file = path.join("nginx.pid");
+        let mut child = crate::relic::process::tests::sleepy_child();
+        fs::write(&pid_file, format!("{}\n", child.id())).unwrap();
+        let mut lore = lore::test_lore(path, &MockVarReader::new());
+        lore.floxy.reload = Some(FloxyReload::PidFile(pid_file));
+        let lore: FloxyLoreRef = Arc::new(lore);
+        FloxyImpl::reload(&lore).unwrap();
+        let status = child.wait().unwrap();
+        assert_eq!(
+            std::os::unix::process::ExitStatusExt::signal(&status),
+            Some(signal::SIGHUP)
+        );
+    }
+
+    #[test]
+    fn reload_pid_file_invalid() {
+        let path = testdir!();
+        let pid_file = path.join("nginx.pid");
+        fs::write(&pid_file, "no pid").unwrap();
+        let mut lore = lore::test_lore(path, &MockVarReader::new());
+        lore.floxy.reload = Some(FloxyReload::PidFile(pid_file));
+        let lore: FloxyLoreRef = Arc::new(lore);
+        assert!(FloxyImpl::reload(&lore).is_err());
+    }
+
+    #[test]
+    fn reload_pid_file_missing() {
+        let path = testdir!();
+        let mut lore = lore::test_lore(path.clone(), &MockVarReader::new());
+        lore.floxy.reload = Some(FloxyReload::PidFile(path.join("nginx.pid")));
+        let lore: FloxyLoreRef = Arc::new(lore);
+        assert!(FloxyImpl::reload(&lore).is_err());
+    }
+
+    #[test]
+    fn add_instance_editor_host_config_plain() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let config_path = lore
+            .floxy
+            .server_config_path()
+            .join("test_app-12345678-editor_5000.conf");
+        FloxyImpl
+            .add_instance_editor_host_config(
+                lore.clone(),
+                "test_app",
+                InstanceId::new(0x12345678),
+                IpAddr::V4(Ipv4Addr::new(123, 123, 123, 123)),
+                &EditorHostInfo {
+                    port: 5000,
+                    host_name: "editor.flecs.local".to_string(),
+                    tls: None,
+                    restricted: false,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(config_path).unwrap(),
+            "
+server {
+  listen 80;
+  server_name editor.flecs.local;
+  location / {
+    proxy_pass http://123.123.123.123:5000/;
+
+    include conf.d/include/proxy_headers.conf;
+
+    client_max_body_size 0;
+    client_body_timeout 30m;
+  }
+}"
+        );
+        assert!(matches!(
+            lore.floxy.certificate_path().try_exists(),
+            Ok(false)
+        ));
+    }
+
+    #[test]
+    fn add_instance_editor_host_config_restricted() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.floxy.core_url = "http://127.0.0.1:8951".to_string();
+        let lore = Arc::new(lore);
+        let config_path = lore
+            .floxy
+            .server_config_path()
+            .join("test_app-12345678-editor_5000.conf");
+        FloxyImpl
+            .add_instance_editor_host_config(
+                lore.clone(),
+                "test_app",
+                InstanceId::new(0x12345678),
+                IpAddr::V4(Ipv4Addr::new(123, 123, 123, 123)),
+                &EditorHostInfo {
+                    port: 5000,
+                    host_name: "editor.flecs.local".to_string(),
+                    tls: None,
+                    restricted: true,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(config_path).unwrap(),
+            "
+server {
+  listen 80;
+  server_name editor.flecs.local;
+  location / {
+    auth_request /flecs/auth/instances/12345678/editor/5000;
+
+    proxy_pass http://123.123.123.123:5000/;
+
+    include conf.d/include/proxy_headers.conf;
+
+    client_max_body_size 0;
+    client_body_timeout 30m;
+  }
+  location = /flecs/auth/instances/12345678/editor/5000 {
+    internal;
+    proxy_pass http://127.0.0.1:8951/v2/instances/12345678/editor/5000/access;
+    proxy_pass_request_body off;
+    proxy_set_header Content-Length \"\";
+    proxy_set_header X-Original-Method $request_method;
+    proxy_set_header X-Original-URI $request_uri;
+  }
+}"
+        );
+    }
+
+    #[test]
+    fn create_editor_host_tls_config_restricted() {
+        let config = FloxyImpl::create_editor_host_tls_config(
+            "editor.flecs.local",
+            InstanceId::new(0x12345678),
+            IpAddr::V4(Ipv4Addr::new(123, 123, 123, 123)),
+            5000,
+            "editor-12345678_5000",
+            Some("http://127.0.0.1:8951"),
+        );
+        let (http, https) = config.split_once("listen 443 ssl;").unwrap();
+        assert!(!http.contains("auth_request"));
+        assert!(https.contains("auth_request /flecs/auth/instances/12345678/editor/5000;"));
+        assert!(https.contains(
+            "proxy_pass http://127.0.0.1:8951/v2/instances/12345678/editor/5000/access;"
+        ));
+    }
+
+    #[test]
+    fn add_and_delete_instance_editor_host_config_tls() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let certified_key = crate::relic::tls::CertifiedKey::generate_self_signed(
+            &["editor.flecs.local".to_string()],
+            10,
+        )
+        .unwrap();
+        let config_path = lore
+            .floxy
+            .server_config_path()
+            .join("test_app-12345678-editor_5000.conf");
+        let certificate_path = lore
+            .floxy
+            .certificate_path()
+            .join("editor-12345678_5000.crt");
+        let private_key_path = lore
+            .floxy
+            .certificate_path()
+            .join("editor-12345678_5000.key");
+        FloxyImpl
+            .add_instance_editor_host_config(
+                lore.clone(),
+                "test_app",
+                InstanceId::new(0x12345678),
+                IpAddr::V4(Ipv4Addr::new(123, 123, 123, 123)),
+                &EditorHostInfo {
+                    port: 5000,
+                    host_name: "editor.flecs.local".to_string(),
+                    tls: Some(certified_key.clone()),
+                    restricted: false,
+                },
+            )
+            .unwrap();
+        let config = fs::read_to_string(&config_path).unwrap();
+        assert!(config.contains("listen 443 ssl;"));
+        assert!(config.contains("return 301 https://$host$request_uri;"));
+        assert!(config.contains("ssl_certificate conf.d/certs/editor-12345678_5000.crt;"));
+        assert!(config.contains("ssl_certificate_key conf.d/certs/editor-12345678_5000.key;"));
+        assert_eq!(
+            crate::relic::tls::CertifiedKey::read_from(&certificate_path, &private_key_path)
+                .unwrap(),
+            certified_key
+        );
+        FloxyImpl
+            .delete_instance_editor_host_config(lore, "test_app", InstanceId::new(0x12345678), 5000)
+            .unwrap();
+        assert!(matches!(config_path.try_exists(), Ok(false)));
+        assert!(matches!(certificate_path.try_exists(), Ok(false)));
+        assert!(matches!(private_key_path.try_exists(), Ok(false)));
+    }
+
+    #[test]
+    fn set_device_certificate_ok() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let certificate_path = lore.floxy.certificate_path().join("device.crt");
+        let private_key_path = lore.floxy.certificate_path().join("device.key");
+        let first = CertifiedKey::generate_self_signed(&["flecs".to_string()], 10).unwrap();
+        FloxyImpl
+            .set_device_certificate(lore.clone(), &first)
+            .unwrap();
+        assert_eq!(
+            CertifiedKey::read_from(&certificate_path, &private_key_path).unwrap(),
+            first
+        );
+        let second = CertifiedKey::generate_self_signed(&["flecs".to_string()], 10).unwrap();
+        FloxyImpl.set_device_certificate(lore, &second).unwrap();
+        assert_eq!(
+            CertifiedKey::read_from(&certificate_path, &private_key_path).unwrap(),
+            second
+        );
+    }
+
+    #[test]
+    fn delete_instance_editor_host_config_not_existing() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        assert!(
+            FloxyImpl
+                .delete_instance_editor_host_config(
+                    lore,
+                    "test_app",
+                    InstanceId::new(0x12345678),
+                    5000,
+                )
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn run_command_empty() {
+        assert!(FloxyImpl::run_command(&[]).is_err());
+    }
+
+    #[test]
+    fn run_command_stderr() {
+        let error = FloxyImpl::run_command(&sh_command("echo invalid config >&2; exit 1"))
+            .unwrap_err()
+            .to_string();
+        assert!(error.ends_with(": invalid config"), "{error}");
+    }
+
+    #[test]
+    fn run_command_timeout() {
+        let start = Instant::now();
+        let error = FloxyImpl::run_command_with_timeout(
+            &["sleep".to_string(), "10".to_string()],
+            Duration::from_millis(100),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("did not finish"), "{error}");
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     fn create_instance_editor_redirect_to_free_port_ok() {
         let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
@@ -884,6 +1705,7 @@ location TEST_LOCATION/ {
                 InstanceId::new(0x12345678),
                 IpAddr::V4(Ipv4Addr::new(123, 123, 123, 123)),
                 50000,
+                false,
             )
             .unwrap();
         let config_path = server_config_path.join(format!("test app-12345678_{port}.conf"));
@@ -909,4 +1731,51 @@ server {{
         std::net::TcpListener::bind(std::net::SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port))
             .unwrap();
     }
+
+    #[test]
+    fn add_instance_editor_server_config_restricted() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.floxy.core_url = "http://127.0.0.1:8951".to_string();
+        let lore = Arc::new(lore);
+        let config_path = lore
+            .floxy
+            .server_config_path()
+            .join("test_app-12345678_4000.conf");
+        FloxyImpl
+            .add_instance_editor_server_config(
+                lore,
+                "test_app",
+                InstanceId::new(0x12345678),
+                IpAddr::V4(Ipv4Addr::new(123, 123, 123, 123)),
+                4000,
+                5000,
+                true,
+            )
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(config_path).unwrap(),
+            "
+server {
+  listen 4000;
+  location / {
+    auth_request /flecs/auth/instances/12345678/editor/5000;
+
+    proxy_pass http://123.123.123.123:5000/;
+
+    include conf.d/include/proxy_headers.conf;
+
+    client_max_body_size 0;
+    client_body_timeout 30m;
+  }
+  location = /flecs/auth/instances/12345678/editor/5000 {
+    internal;
+    proxy_pass http://127.0.0.1:8951/v2/instances/12345678/editor/5000/access;
+    proxy_pass_request_body off;
+    proxy_set_header Content-Length \"\";
+    proxy_set_header X-Original-Method $request_method;
+    proxy_set_header X-Original-URI $request_uri;
+  }
+}"
+        );
+    }
 }
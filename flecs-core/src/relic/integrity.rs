@@ -0,0 +1,348 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ed25519_dalek::pkcs8::DecodePrivateKey;
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+/// Maps paths relative to a directory, separated by '/', to the hex encoded sha256 of the file
+pub type Checksums = BTreeMap<String, String>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ChecksumError {
+    #[error("Checksum of {0} does not match")]
+    Mismatch(String),
+    #[error("File {0} has no checksum")]
+    Unlisted(String),
+    #[error("File {0} has a checksum but does not exist")]
+    Missing(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error("Malformed signature: {0}")]
+    Malformed(String),
+    #[error("Signature was created with untrusted key {0}")]
+    UntrustedKey(String),
+    #[error("No trusted keys to verify the signature with")]
+    NoTrustedKeys,
+    #[error("Signature does not match: {0}")]
+    Invalid(String),
+}
+
+/// Detached Ed25519 signature together with the public key of the signer
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signature {
+    /// Base64 encoded public key
+    pub public_key: String,
+    /// Base64 encoded signature
+    pub signature: String,
+}
+
+impl Signature {
+    pub fn create(key: &SigningKey, data: &[u8]) -> Self {
+        Self {
+            public_key: encode_verifying_key(&key.verifying_key()),
+            signature: STANDARD.encode(key.sign(data).to_bytes()),
+        }
+    }
+
+    /// Verifies the signature of `data`, the signature has to be created with one of the
+    /// `trusted_keys`. The public key embedded in the signature is never trusted on its own, i.e.
+    /// verification fails if `trusted_keys` is empty. Returns the key the signature was created
+    /// with.
+    pub fn verify(
+        &self,
+        data: &[u8],
+        trusted_keys: &[VerifyingKey],
+    ) -> Result<VerifyingKey, SignatureError> {
+        if trusted_keys.is_empty() {
+            return Err(SignatureError::NoTrustedKeys);
+        }
+        let key = parse_verifying_key(&self.public_key)
+            .map_err(|e| SignatureError::Malformed(e.to_string()))?;
+        if !trusted_keys.contains(&key) {
+            return Err(SignatureError::UntrustedKey(self.public_key.clone()));
+        }
+        let signature = STANDARD
+            .decode(&self.signature)
+            .map_err(|e| SignatureError::Malformed(e.to_string()))?;
+        let signature = ed25519_dalek::Signature::from_slice(&signature)
+            .map_err(|e| SignatureError::Malformed(e.to_string()))?;
+        key.verify_strict(data, &signature)
+            .map_err(|e| SignatureError::Invalid(e.to_string()))?;
+        Ok(key)
+    }
+}
+
+pub fn encode_verifying_key(key: &VerifyingKey) -> String {
+    STANDARD.encode(key.as_bytes())
+}
+
+/// Parses a base64 encoded Ed25519 public key
+pub fn parse_verifying_key(key: &str) -> anyhow::Result<VerifyingKey> {
+    let key = STANDARD.decode(key.trim())?;
+    let key: [u8; ed25519_dalek::PUBLIC_KEY_LENGTH] = key
+        .try_into()
+        .map_err(|key: Vec<u8>| anyhow::anyhow!("Invalid public key length {}", key.len()))?;
+    Ok(VerifyingKey::from_bytes(&key)?)
+}
+
+/// Reads a PEM encoded PKCS#8 Ed25519 private key
+pub async fn read_signing_key(path: &Path) -> anyhow::Result<SigningKey> {
+    let pem = tokio::fs::read_to_string(path).await?;
+    SigningKey::from_pkcs8_pem(&pem)
+        .map_err(|e| anyhow::anyhow!("Could not read signing key from {path:?}: {e}"))
+}
+
+pub async fn sha256_file(path: &Path) -> anyhow::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let len = file.read(&mut buffer).await?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+    }
+    Ok(encode_hex(&hasher.finalize()))
+}
+
+/// Lower case hex encoding as used in [Checksums]
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Collects all files below `root` except the excluded ones, paths are relative to `root`
+async fn collect_files(root: &Path, exclude: &[&str]) -> anyhow::Result<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative_path = path
+                .strip_prefix(root)?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if exclude.contains(&relative_path.as_str()) {
+                continue;
+            }
+            anyhow::ensure!(file_type.is_file(), "{path:?} is not a regular file");
+            files.insert(relative_path, path);
+        }
+    }
+    Ok(files)
+}
+
+/// Calculates the checksums of all files below `root` except the excluded ones
+pub async fn sha256_directory(root: &Path, exclude: &[&str]) -> anyhow::Result<Checksums> {
+    let mut checksums = Checksums::new();
+    for (relative_path, path) in collect_files(root, exclude).await? {
+        checksums.insert(relative_path, sha256_file(&path).await?);
+    }
+    Ok(checksums)
+}
+
+/// Verifies that the files below `root` except the excluded ones match exactly the `expected`
+/// checksums
+pub async fn verify_directory_checksums(
+    root: &Path,
+    exclude: &[&str],
+    expected: &Checksums,
+) -> Result<(), ChecksumError> {
+    let files = collect_files(root, exclude).await?;
+    if let Some(missing) = expected.keys().find(|path| !files.contains_key(*path)) {
+        return Err(ChecksumError::Missing(missing.clone()));
+    }
+    for (relative_path, path) in files {
+        let Some(expected) = expected.get(&relative_path) else {
+            return Err(ChecksumError::Unlisted(relative_path));
+        };
+        if !sha256_file(&path).await?.eq_ignore_ascii_case(expected) {
+            return Err(ChecksumError::Mismatch(relative_path));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::EncodePrivateKey;
+    use ed25519_dalek::pkcs8::LineEnding;
+    use testdir::testdir;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn prepare_directory() -> PathBuf {
+        let path = testdir!();
+        std::fs::create_dir_all(path.join("apps/app_1.0.0")).unwrap();
+        std::fs::write(path.join("apps/app_1.0.0/app.json"), b"abc").unwrap();
+        std::fs::write(path.join("deployment.json"), b"").unwrap();
+        std::fs::write(path.join("manifest.json"), b"{}").unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn sha256_file_ok() {
+        let path = testdir!().join("file");
+        std::fs::write(&path, b"abc").unwrap();
+        assert_eq!(sha256_file(&path).await.unwrap(), ABC_SHA256);
+    }
+
+    #[tokio::test]
+    async fn sha256_directory_ok() {
+        let path = prepare_directory();
+        let checksums = sha256_directory(&path, &["manifest.json"]).await.unwrap();
+        assert_eq!(
+            checksums,
+            Checksums::from([
+                (
+                    "apps/app_1.0.0/app.json".to_string(),
+                    ABC_SHA256.to_string()
+                ),
+                (
+                    "deployment.json".to_string(),
+                    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string()
+                ),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_directory_checksums_ok() {
+        let path = prepare_directory();
+        let checksums = sha256_directory(&path, &["manifest.json"]).await.unwrap();
+        verify_directory_checksums(&path, &["manifest.json"], &checksums)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_directory_checksums_mismatch() {
+        let path = prepare_directory();
+        let checksums = sha256_directory(&path, &["manifest.json"]).await.unwrap();
+        std::fs::write(path.join("apps/app_1.0.0/app.json"), b"abd").unwrap();
+        assert!(matches!(
+            verify_directory_checksums(&path, &["manifest.json"], &checksums).await,
+            Err(ChecksumError::Mismatch(path)) if path == "apps/app_1.0.0/app.json"
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_directory_checksums_unlisted() {
+        let path = prepare_directory();
+        let checksums = sha256_directory(&path, &["manifest.json"]).await.unwrap();
+        std::fs::write(path.join("injected.json"), b"{}").unwrap();
+        assert!(matches!(
+            verify_directory_checksums(&path, &["manifest.json"], &checksums).await,
+            Err(ChecksumError::Unlisted(path)) if path == "injected.json"
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_directory_checksums_missing() {
+        let path = prepare_directory();
+        let checksums = sha256_directory(&path, &["manifest.json"]).await.unwrap();
+        std::fs::remove_file(path.join("deployment.json")).unwrap();
+        assert!(matches!(
+            verify_directory_checksums(&path, &["manifest.json"], &checksums).await,
+            Err(ChecksumError::Missing(path)) if path == "deployment.json"
+        ));
+    }
+
+    #[test]
+    fn signature_ok() {
+        let key = signing_key(1);
+        let signature = Signature::create(&key, b"data");
+        assert_eq!(
+            signature.verify(b"data", &[key.verifying_key()]),
+            Ok(key.verifying_key())
+        );
+    }
+
+    #[test]
+    fn signature_no_trusted_keys() {
+        let signature = Signature::create(&signing_key(1), b"data");
+        assert_eq!(
+            signature.verify(b"data", &[]),
+            Err(SignatureError::NoTrustedKeys)
+        );
+    }
+
+    #[test]
+    fn signature_untrusted() {
+        let signature = Signature::create(&signing_key(1), b"data");
+        assert!(matches!(
+            signature.verify(b"data", &[signing_key(2).verifying_key()]),
+            Err(SignatureError::UntrustedKey(_))
+        ));
+    }
+
+    #[test]
+    fn signature_invalid() {
+        let key = signing_key(1);
+        let signature = Signature::create(&key, b"data");
+        assert!(matches!(
+            signature.verify(b"other data", &[key.verifying_key()]),
+            Err(SignatureError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn signature_malformed() {
+        let key = signing_key(1);
+        let signature = Signature {
+            signature: "not base64".to_string(),
+            ..Signature::create(&key, b"data")
+        };
+        assert!(matches!(
+            signature.verify(b"data", &[key.verifying_key()]),
+            Err(SignatureError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn verifying_key_roundtrip() {
+        let key = signing_key(3).verifying_key();
+        assert_eq!(
+            parse_verifying_key(&encode_verifying_key(&key)).unwrap(),
+            key
+        );
+        assert!(parse_verifying_key("AAAA").is_err());
+    }
+
+    #[tokio::test]
+    async fn read_signing_key_ok() {
+        let path = testdir!().join("key.pem");
+        let key = signing_key(4);
+        std::fs::write(&path, key.to_pkcs8_pem(LineEnding::LF).unwrap().as_bytes()).unwrap();
+        assert_eq!(read_signing_key(&path).await.unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn read_signing_key_err() {
+        let path = testdir!().join("key.pem");
+        std::fs::write(&path, b"no key").unwrap();
+        assert!(read_signing_key(&path).await.is_err());
+    }
+}
@@ -12,10 +12,17 @@ use std::sync::Arc;
 
 /// Helper functions that provide async versions of [flecstract::tar::extract] and [flecstract::tar::archive]
 pub mod async_flecstract;
+pub mod cron;
+pub mod delta;
 pub mod docker;
 pub mod docker_cli;
+pub mod encryption;
+pub mod integrity;
+pub mod podman;
 pub mod process;
 pub mod serde;
+pub mod tls;
+pub mod upload;
 
 pub struct Relics<UDR: UsbDeviceReader, NAR: NetworkAdapterReader, NDR: NetDeviceReader> {
     pub usb_device_reader: Arc<UDR>,
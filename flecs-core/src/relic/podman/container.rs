@@ -0,0 +1,828 @@
+pub use super::Result;
+use crate::quest::{Progress, SyncQuest};
+use crate::relic::async_flecstract::{
+    EntryHeader, archive_single_file_to_memory, archive_to_memory, extract_from_memory,
+    extract_single_file_from_memory_as, read_headers,
+};
+use crate::relic::docker::container::{CompletedExec, ExecOutputCollector};
+use crate::relic::podman::{
+    PodmanClient, body_stream, json_body, json_headers, tar_headers, write_stream_to_memory,
+};
+use axum::body::Body;
+use bollard::models::ContainerStateStatusEnum;
+use futures_util::TryStreamExt;
+use futures_util::stream::StreamExt;
+use http::{HeaderMap, Method, StatusCode};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs::File;
+use tokio_util::codec;
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CreateResponse {
+    id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ExecInspect {
+    #[serde(default)]
+    exit_code: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerState {
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub running: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct ContainerNetwork {
+    #[serde(rename = "IPAddress", default)]
+    pub ip_address: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerNetworkSettings {
+    #[serde(default)]
+    pub networks: HashMap<String, ContainerNetwork>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerMount {
+    #[serde(rename = "Type", default)]
+    pub typ: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub destination: String,
+}
+
+/// Subset of the libpod container inspect response
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerInspect {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub state: ContainerState,
+    #[serde(default)]
+    pub network_settings: ContainerNetworkSettings,
+    #[serde(default)]
+    pub mounts: Vec<ContainerMount>,
+}
+
+impl ContainerState {
+    /// Maps the libpod container status onto the docker equivalent
+    pub fn docker_status(&self) -> ContainerStateStatusEnum {
+        match self.status.as_str() {
+            "configured" | "created" | "initialized" => ContainerStateStatusEnum::CREATED,
+            "running" => ContainerStateStatusEnum::RUNNING,
+            "paused" => ContainerStateStatusEnum::PAUSED,
+            "stopping" | "removing" => ContainerStateStatusEnum::REMOVING,
+            "exited" | "stopped" => ContainerStateStatusEnum::EXITED,
+            _ => ContainerStateStatusEnum::EMPTY,
+        }
+    }
+}
+
+/// Creates a container from a libpod `SpecGenerator` and returns the id of the new container
+pub async fn create(client: Arc<PodmanClient>, spec: &serde_json::Value) -> Result<String> {
+    let response: CreateResponse = client
+        .post_json("/containers/create", &[], spec)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Image of container does not exist"))?;
+    Ok(response.id)
+}
+
+pub async fn start(client: Arc<PodmanClient>, container_name: &str) -> Result<()> {
+    let response = client
+        .send(
+            Method::POST,
+            &format!("/containers/{container_name}/start"),
+            &[],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await?;
+    match response.status() {
+        // 304: Container already started
+        status if status.is_success() || status == StatusCode::NOT_MODIFIED => Ok(()),
+        StatusCode::NOT_FOUND => anyhow::bail!("Container {container_name} does not exist"),
+        status => Err(super::error_from_response(status, response).await),
+    }
+}
+
+/// Returns false if the container did not exist
+pub async fn stop(
+    client: Arc<PodmanClient>,
+    container_name: &str,
+    timeout: Option<u32>,
+) -> Result<bool> {
+    let timeout = timeout.map(|timeout| timeout.to_string());
+    let query: Vec<(&str, &str)> = timeout
+        .as_deref()
+        .map(|timeout| ("timeout", timeout))
+        .into_iter()
+        .collect();
+    let response = client
+        .send(
+            Method::POST,
+            &format!("/containers/{container_name}/stop"),
+            &query,
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await?;
+    match response.status() {
+        // 304: Container already stopped
+        status if status.is_success() || status == StatusCode::NOT_MODIFIED => Ok(true),
+        StatusCode::NOT_FOUND => Ok(false),
+        status => Err(super::error_from_response(status, response).await),
+    }
+}
+
+/// Returns false if the container did not exist
+pub async fn remove(client: Arc<PodmanClient>, container_name: &str, force: bool) -> Result<bool> {
+    client
+        .request_no_content(
+            Method::DELETE,
+            &format!("/containers/{container_name}"),
+            &[("force", if force { "true" } else { "false" })],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await
+}
+
+pub async fn inspect(
+    client: Arc<PodmanClient>,
+    container_name: &str,
+) -> Result<Option<ContainerInspect>> {
+    client
+        .get_json(&format!("/containers/{container_name}/json"), &[])
+        .await
+}
+
+/// Splits the multiplexed log stream of a container without tty into stdout and stderr. Each
+/// frame starts with an 8 byte header: stream type (1 = stdout, 2 = stderr), three bytes padding
+/// and the big endian length of the payload.
+fn demultiplex_logs(mut data: &[u8]) -> Result<(String, String)> {
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    while !data.is_empty() {
+        anyhow::ensure!(data.len() >= 8, "Incomplete log frame header");
+        let size = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        anyhow::ensure!(data.len() >= 8 + size, "Incomplete log frame");
+        let message = String::from_utf8_lossy(&data[8..8 + size]).to_string();
+        match data[0] {
+            2 => stderr.push(message),
+            _ => stdout.push(message),
+        }
+        data = &data[8 + size..];
+    }
+    Ok((stdout.concat(), stderr.concat()))
+}
+
+pub async fn logs(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    container_name: &str,
+) -> Result<(String, String)> {
+    let response = client
+        .send_checked(
+            Method::GET,
+            &format!("/containers/{container_name}/logs"),
+            &[("stdout", "true"), ("stderr", "true")],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Container {container_name} does not exist"))?;
+    let data = write_stream_to_memory(quest, body_stream(response)).await?;
+    demultiplex_logs(&data)
+}
+
+/// Appends the complete frames of multiplexed output at the start of `data` to the collector,
+/// returns the number of consumed bytes
+fn collect_frames(data: &[u8], collector: &mut ExecOutputCollector) -> usize {
+    let mut consumed = 0;
+    while let Some(header) = data.get(consumed..consumed + 8) {
+        let size = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let Some(message) = data.get(consumed + 8..consumed + 8 + size) else {
+            break;
+        };
+        match header[0] {
+            2 => collector.push_stderr(message),
+            _ => collector.push_stdout(message),
+        }
+        consumed += 8 + size;
+    }
+    consumed
+}
+
+/// See [crate::relic::docker::container::exec_to_completion]
+pub async fn exec_to_completion(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    container_name: &str,
+    cmd: Vec<String>,
+    timeout: Duration,
+) -> Result<CompletedExec> {
+    tokio::time::timeout(
+        timeout,
+        exec_to_completion_unbounded(client, quest, container_name, cmd),
+    )
+    .await
+    .map_err(|elapsed| {
+        anyhow::Error::new(elapsed).context(format!(
+            "Command in container {container_name} did not finish within {timeout:?}"
+        ))
+    })?
+}
+
+async fn exec_to_completion_unbounded(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    container_name: &str,
+    cmd: Vec<String>,
+) -> Result<CompletedExec> {
+    let exec: CreateResponse = client
+        .post_json(
+            &format!("/containers/{container_name}/exec"),
+            &[],
+            &serde_json::json!({
+                "AttachStdout": true,
+                "AttachStderr": true,
+                "Cmd": cmd,
+            }),
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Container {container_name} does not exist"))?;
+    let response = client
+        .send_checked(
+            Method::POST,
+            &format!("/exec/{}/start", exec.id),
+            &[],
+            json_headers(),
+            json_body(&serde_json::json!({"Detach": false, "Tty": false}))?,
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Exec {} does not exist", exec.id))?;
+    // The output is demultiplexed while it is received as only a limited amount of it is kept
+    let mut collector = ExecOutputCollector::default();
+    let mut pending = Vec::new();
+    let mut total_bytes = 0;
+    let mut stream = std::pin::pin!(body_stream(response));
+    while let Some(data) = stream.next().await {
+        let data = data?;
+        total_bytes += data.len();
+        pending.extend_from_slice(&data);
+        let consumed = collect_frames(&pending, &mut collector);
+        pending.drain(..consumed);
+        quest.lock().await.progress = Some(Progress {
+            current: total_bytes as u64,
+            total: None,
+        });
+    }
+    anyhow::ensure!(pending.is_empty(), "Incomplete log frame");
+    let inspect: ExecInspect = client
+        .get_json(&format!("/exec/{}/json", exec.id), &[])
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Exec {} does not exist", exec.id))?;
+    Ok(collector.complete(inspect.exit_code))
+}
+
+/// Uploads a tar archive and extracts it at `extract_path` in the container
+pub async fn copy_archive_to(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    archive: Body,
+    total: Option<u64>,
+    extract_path: &Path,
+    container_name: &str,
+) -> Result<()> {
+    quest.lock().await.progress = Some(Progress { total, current: 0 });
+    let extract_path = extract_path.to_string_lossy();
+    if !client
+        .request_no_content(
+            Method::PUT,
+            &format!("/containers/{container_name}/archive"),
+            &[("path", extract_path.as_ref())],
+            tar_headers(),
+            archive,
+        )
+        .await?
+    {
+        anyhow::bail!("Container {container_name} or path {extract_path} does not exist");
+    }
+    if let Some(total) = total {
+        quest.lock().await.progress = Some(Progress {
+            total: Some(total),
+            current: total,
+        });
+    }
+    Ok(())
+}
+
+pub async fn copy_archive_file_to(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    archive_path: &Path,
+    extract_path: &Path,
+    container_name: &str,
+) -> Result<()> {
+    let file = File::open(archive_path).await?;
+    let total = file.metadata().await.map(|meta| meta.len()).ok();
+    let byte_stream =
+        codec::FramedRead::new(file, codec::BytesCodec::new()).map(|r| r.map(|b| b.freeze()));
+    copy_archive_to(
+        client,
+        quest,
+        Body::from_stream(byte_stream),
+        total,
+        extract_path,
+        container_name,
+    )
+    .await
+}
+
+/// See [crate::relic::docker::container::copy_to]
+pub async fn copy_to(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    src_path: &Path,
+    dst_path: &Path,
+    container_name: &str,
+    follow_symlinks: bool,
+    is_dst_file_path: bool,
+) -> Result<()> {
+    let (archive, extract_path) = if is_dst_file_path {
+        let file_name = dst_path
+            .file_name()
+            .ok_or_else(|| {
+                anyhow::anyhow!("Expected destination path '{dst_path:?}' to be a file")
+            })?
+            .to_string_lossy()
+            .to_string();
+        let parent = dst_path.parent().ok_or_else(|| {
+            anyhow::anyhow!("Expected destination path '{dst_path:?}' to have a parent directory")
+        })?;
+        (
+            archive_single_file_to_memory(src_path, file_name, true).await?,
+            parent,
+        )
+    } else {
+        (
+            archive_to_memory(src_path, follow_symlinks).await?,
+            dst_path,
+        )
+    };
+    let total = archive.len() as u64;
+    copy_archive_to(
+        client,
+        quest,
+        Body::from(archive),
+        Some(total),
+        extract_path,
+        container_name,
+    )
+    .await
+}
+
+/// Downloads the content at `src` in the container as tar archive
+pub async fn copy_archive_from(
+    quest: SyncQuest,
+    client: Arc<PodmanClient>,
+    src: &Path,
+    container_name: &str,
+) -> Result<Vec<u8>> {
+    let src = src.to_string_lossy();
+    let response = client
+        .send_checked(
+            Method::GET,
+            &format!("/containers/{container_name}/archive"),
+            &[("path", src.as_ref())],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!("Container {container_name} or path {src} does not exist")
+        })?;
+    write_stream_to_memory(quest, body_stream(response)).await
+}
+
+/// See [crate::relic::docker::container::list_archive_entries]
+pub async fn list_archive_entries(
+    client: Arc<PodmanClient>,
+    src: &Path,
+    container_name: &str,
+) -> Result<Vec<EntryHeader>> {
+    let src = src.to_string_lossy();
+    let response = client
+        .send_checked(
+            Method::GET,
+            &format!("/containers/{container_name}/archive"),
+            &[("path", src.as_ref())],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!("Container {container_name} or path {src} does not exist")
+        })?;
+    let stream = body_stream(response).map_err(std::io::Error::other);
+    read_headers(Box::pin(stream).into_async_read().compat()).await
+}
+
+/// See [crate::relic::docker::container::copy_from]
+pub async fn copy_from(
+    quest: SyncQuest,
+    client: Arc<PodmanClient>,
+    src: &Path,
+    dst: &Path,
+    container_name: &str,
+    is_dst_file_path: bool,
+) -> Result<()> {
+    let container_name = container_name.to_string();
+    let src = src.to_path_buf();
+    let dst = dst.to_path_buf();
+    let archive = quest
+        .lock()
+        .await
+        .create_sub_quest(
+            format!("Download archive {src:?} from {container_name}"),
+            |quest| async move { copy_archive_from(quest, client, &src, &container_name).await },
+        )
+        .await
+        .2;
+    let result = quest
+        .lock()
+        .await
+        .create_sub_quest(format!("Extract archive to {dst:?}"), |_quest| async move {
+            if is_dst_file_path {
+                extract_single_file_from_memory_as(archive.await?, &dst).await
+            } else {
+                extract_from_memory(archive.await?, &dst).await
+            }
+        })
+        .await
+        .2;
+    result.await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quest::Quest;
+    use crate::relic::podman::tests::{StubPodman, StubResponse};
+
+    fn log_frame(stream: u8, message: &str) -> Vec<u8> {
+        let mut frame = vec![stream, 0, 0, 0];
+        frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        frame.extend_from_slice(message.as_bytes());
+        frame
+    }
+
+    #[test]
+    fn demultiplex_logs_ok() {
+        let data = [
+            log_frame(1, "out 1\n"),
+            log_frame(2, "err 1\n"),
+            log_frame(1, "out 2\n"),
+        ]
+        .concat();
+        assert_eq!(
+            demultiplex_logs(&data).unwrap(),
+            ("out 1\nout 2\n".to_string(), "err 1\n".to_string())
+        );
+    }
+
+    #[test]
+    fn collect_frames_partial() {
+        let data = [log_frame(1, "out"), log_frame(2, "err")].concat();
+        let mut collector = ExecOutputCollector::default();
+        assert_eq!(collect_frames(&data[..data.len() - 1], &mut collector), 11);
+        assert_eq!(collect_frames(&data[11..], &mut collector), 11);
+        assert_eq!(
+            collector.complete(None),
+            CompletedExec {
+                exit_code: None,
+                stdout: "out".to_string(),
+                stderr: "err".to_string(),
+                truncated: false,
+            }
+        );
+    }
+
+    #[test]
+    fn demultiplex_logs_empty() {
+        assert_eq!(
+            demultiplex_logs(&[]).unwrap(),
+            (String::new(), String::new())
+        );
+    }
+
+    #[test]
+    fn demultiplex_logs_incomplete() {
+        let mut data = log_frame(1, "out 1\n");
+        data.pop();
+        assert!(demultiplex_logs(&data).is_err());
+        assert!(demultiplex_logs(&[1, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn docker_status() {
+        for (status, expected) in [
+            ("configured", ContainerStateStatusEnum::CREATED),
+            ("created", ContainerStateStatusEnum::CREATED),
+            ("running", ContainerStateStatusEnum::RUNNING),
+            ("paused", ContainerStateStatusEnum::PAUSED),
+            ("stopping", ContainerStateStatusEnum::REMOVING),
+            ("exited", ContainerStateStatusEnum::EXITED),
+            ("stopped", ContainerStateStatusEnum::EXITED),
+            ("unknown", ContainerStateStatusEnum::EMPTY),
+        ] {
+            let state = ContainerState {
+                status: status.to_string(),
+                running: false,
+            };
+            assert_eq!(state.docker_status(), expected, "{status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn create_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::POST, "/containers/create"),
+                StubResponse::json(
+                    StatusCode::CREATED,
+                    serde_json::json!({"Id": "6f2a", "Warnings": []}),
+                ),
+            )],
+        );
+        let spec = serde_json::json!({"name": "test", "image": "alpine:3.21"});
+        assert_eq!(create(stub.client(), &spec).await.unwrap(), "6f2a");
+        assert_eq!(stub.requests()[0].json_body(), spec);
+    }
+
+    #[tokio::test]
+    async fn start_already_started() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::POST, "/containers/test/start"),
+                StubResponse::empty(StatusCode::NOT_MODIFIED),
+            )],
+        );
+        start(stub.client(), "test").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn start_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(start(stub.client(), "test").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stop_with_timeout() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::POST, "/containers/test/stop"),
+                StubResponse::empty(StatusCode::NO_CONTENT),
+            )],
+        );
+        assert!(stop(stub.client(), "test", Some(20)).await.unwrap());
+        assert_eq!(stub.requests()[0].query.as_deref(), Some("timeout=20"));
+    }
+
+    #[tokio::test]
+    async fn stop_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(!stop(stub.client(), "test", None).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn inspect_some() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/containers/test/json"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({
+                        "Id": "6f2a",
+                        "Name": "test",
+                        "State": {"Status": "running", "Running": true, "Pid": 1234},
+                        "NetworkSettings": {
+                            "Networks": {"flecs": {"IPAddress": "172.21.0.3"}}
+                        },
+                        "Mounts": [{
+                            "Type": "volume",
+                            "Name": "data",
+                            "Destination": "/data",
+                            "Source": "/var/lib/containers/storage/volumes/data/_data"
+                        }]
+                    }),
+                ),
+            )],
+        );
+        let inspect = inspect(stub.client(), "test").await.unwrap().unwrap();
+        assert_eq!(
+            inspect.state.docker_status(),
+            ContainerStateStatusEnum::RUNNING
+        );
+        assert_eq!(
+            inspect.network_settings.networks["flecs"].ip_address,
+            "172.21.0.3"
+        );
+        assert_eq!(inspect.mounts[0].name.as_deref(), Some("data"));
+    }
+
+    #[tokio::test]
+    async fn logs_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/containers/test/logs"),
+                StubResponse {
+                    status: StatusCode::OK,
+                    body: [log_frame(1, "out"), log_frame(2, "err")].concat(),
+                },
+            )],
+        );
+        assert_eq!(
+            logs(stub.client(), Quest::new_synced("Logs".to_string()), "test")
+                .await
+                .unwrap(),
+            ("out".to_string(), "err".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_to_completion_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [
+                (
+                    (Method::POST, "/containers/test/exec"),
+                    StubResponse::json(StatusCode::CREATED, serde_json::json!({"Id": "e1"})),
+                ),
+                (
+                    (Method::POST, "/exec/e1/start"),
+                    StubResponse {
+                        status: StatusCode::OK,
+                        body: [log_frame(1, "out"), log_frame(2, "err")].concat(),
+                    },
+                ),
+                (
+                    (Method::GET, "/exec/e1/json"),
+                    StubResponse::json(StatusCode::OK, serde_json::json!({"ExitCode": 3})),
+                ),
+            ],
+        );
+        assert_eq!(
+            exec_to_completion(
+                stub.client(),
+                Quest::new_synced("Exec".to_string()),
+                "test",
+                vec!["ls".to_string(), "-la".to_string()],
+                Duration::from_secs(10)
+            )
+            .await
+            .unwrap(),
+            CompletedExec {
+                exit_code: Some(3),
+                stdout: "out".to_string(),
+                stderr: "err".to_string(),
+                truncated: false,
+            }
+        );
+        assert_eq!(
+            stub.requests()[0].json_body()["Cmd"],
+            serde_json::json!(["ls", "-la"])
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_to_completion_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(
+            exec_to_completion(
+                stub.client(),
+                Quest::new_synced("Exec".to_string()),
+                "test",
+                vec!["ls".to_string()],
+                Duration::from_secs(10)
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn copy_to_file_path() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::PUT, "/containers/test/archive"),
+                StubResponse::empty(StatusCode::OK),
+            )],
+        );
+        let src = path.join("config.json");
+        std::fs::write(&src, "{}").unwrap();
+        copy_to(
+            stub.client(),
+            Quest::new_synced("Copy".to_string()),
+            &src,
+            Path::new("/etc/app/app.json"),
+            "test",
+            true,
+            true,
+        )
+        .await
+        .unwrap();
+        let request = &stub.requests()[0];
+        assert_eq!(request.query.as_deref(), Some("path=%2Fetc%2Fapp"));
+        let extracted = path.join("extracted");
+        extract_from_memory(request.body.clone(), &extracted)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(extracted.join("app.json")).unwrap(), b"{}");
+    }
+
+    #[tokio::test]
+    async fn copy_from_missing() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(path.join("podman.sock"), []);
+        assert!(
+            copy_from(
+                Quest::new_synced("Copy".to_string()),
+                stub.client(),
+                Path::new("/etc/app/app.json"),
+                &path.join("app.json"),
+                "test",
+                true,
+            )
+            .await
+            .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn list_archive_entries_ok() {
+        let path = testdir::testdir!();
+        let src = path.join("app");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("app.json"), b"{}").unwrap();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::GET, "/containers/test/archive"),
+                StubResponse {
+                    status: StatusCode::OK,
+                    body: archive_to_memory(&src, false).await.unwrap(),
+                },
+            )],
+        );
+        let entries = list_archive_entries(stub.client(), Path::new("/etc/app"), "test")
+            .await
+            .unwrap();
+        assert!(entries.contains(&EntryHeader {
+            path: "app.json".into(),
+            size: 2,
+            is_dir: false,
+        }));
+        assert_eq!(
+            stub.requests()[0].query.as_deref(),
+            Some("path=%2Fetc%2Fapp")
+        );
+    }
+
+    #[tokio::test]
+    async fn list_archive_entries_missing() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(path.join("podman.sock"), []);
+        assert!(
+            list_archive_entries(stub.client(), Path::new("/etc/app"), "test")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,426 @@
+pub use super::Result;
+use crate::quest::{State, SyncQuest};
+use crate::relic::podman::{
+    PodmanClient, body_stream, tar_headers, write_stream_to_file, write_stream_to_memory,
+};
+use axum::body::Body;
+use base64::Engine;
+use bollard::auth::DockerCredentials;
+use bollard::models::ImageInspect;
+use futures_util::stream::StreamExt;
+use http::{HeaderMap, HeaderValue, Method};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio::io::AsyncRead;
+use tokio_util::codec;
+use tracing::{debug, trace};
+
+/// Subset of the libpod image inspect response which is converted into an [ImageInspect]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LibpodImageInspect {
+    id: Option<String>,
+    repo_tags: Option<Vec<String>>,
+    repo_digests: Option<Vec<String>>,
+    parent: Option<String>,
+    comment: Option<String>,
+    author: Option<String>,
+    architecture: Option<String>,
+    os: Option<String>,
+    size: Option<i64>,
+    virtual_size: Option<i64>,
+}
+
+impl From<LibpodImageInspect> for ImageInspect {
+    fn from(value: LibpodImageInspect) -> Self {
+        Self {
+            id: value.id,
+            repo_tags: value.repo_tags,
+            repo_digests: value.repo_digests,
+            parent: value.parent,
+            comment: value.comment,
+            author: value.author,
+            architecture: value.architecture,
+            os: value.os,
+            size: value.size,
+            virtual_size: value.virtual_size,
+            ..ImageInspect::default()
+        }
+    }
+}
+
+/// Progress report of libpod while pulling or loading images, one json object per line
+#[derive(Debug, Default, Deserialize)]
+struct PullReport {
+    #[serde(default)]
+    stream: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    id: Option<String>,
+}
+
+fn registry_auth_header(credentials: Option<DockerCredentials>) -> Result<HeaderMap> {
+    let mut headers = HeaderMap::new();
+    if let Some(credentials) = credentials {
+        let credentials =
+            base64::engine::general_purpose::URL_SAFE.encode(serde_json::to_vec(&credentials)?);
+        headers.insert("X-Registry-Auth", HeaderValue::from_str(&credentials)?);
+    }
+    Ok(headers)
+}
+
+/// Parses the newline separated pull reports, updates the quest and returns the id of the pulled
+/// image if it was reported.
+async fn process_pull_reports(quest: &SyncQuest, data: &[u8]) -> Result<Option<String>> {
+    let mut id = None;
+    for line in data.split(|c| *c == b'\n').filter(|line| !line.is_empty()) {
+        let report: PullReport = serde_json::from_slice(line)?;
+        if let Some(error) = report.error {
+            quest.lock().await.state = State::Failing;
+            anyhow::bail!("Podman reported error: {error}");
+        }
+        if let Some(stream) = report.stream {
+            let detail = stream.trim().to_string();
+            trace!("{detail}");
+            quest.lock().await.detail = Some(detail);
+        }
+        if report.id.is_some() {
+            id = report.id;
+        }
+    }
+    Ok(id)
+}
+
+/// # Example
+/// ```no_run
+/// use flecs_core::quest::Quest;
+/// use flecs_core::relic::podman::PodmanClient;
+/// use flecs_core::relic::podman::image::pull;
+/// use std::path::PathBuf;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// # tokio_test::block_on(
+/// async {
+///     let client = Arc::new(PodmanClient::new(
+///         PathBuf::from("/run/podman/podman.sock"),
+///         Duration::from_secs(120),
+///     ));
+///     let quest = Quest::new_synced("Podman pull".to_string());
+///     let id = pull(quest, client, None, "docker.io/library/alpine", "3.21")
+///         .await
+///         .unwrap();
+///     println!("{id}");
+/// }
+/// # )
+/// ```
+pub async fn pull(
+    quest: SyncQuest,
+    client: Arc<PodmanClient>,
+    credentials: Option<DockerCredentials>,
+    image: &str,
+    tag: &str,
+) -> Result<String> {
+    let reference = format!("{image}:{tag}");
+    let response = client
+        .send_checked(
+            Method::POST,
+            "/images/pull",
+            &[("reference", reference.as_str()), ("policy", "always")],
+            registry_auth_header(credentials)?,
+            Body::empty(),
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Image {reference} not found"))?;
+    let mut stream = body_stream(response);
+    let mut id = None;
+    // Reports can be split across multiple chunks, only complete lines are processed
+    let mut pending = Vec::new();
+    while let Some(data) = stream.next().await {
+        pending.extend_from_slice(&data?);
+        if let Some(end) = pending.iter().rposition(|c| *c == b'\n') {
+            let complete: Vec<u8> = pending.drain(..=end).collect();
+            if let Some(reported_id) = process_pull_reports(&quest, &complete).await? {
+                id = Some(reported_id);
+            }
+        }
+    }
+    if let Some(reported_id) = process_pull_reports(&quest, &pending).await? {
+        id = Some(reported_id);
+    }
+    match id {
+        Some(id) => Ok(id),
+        None => inspect(client, &reference)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Could not get image for {reference}"))?
+            .id
+            .ok_or_else(|| anyhow::anyhow!("Could not get image id for {reference}")),
+    }
+}
+
+/// # Example
+/// ```no_run
+/// use flecs_core::relic::podman::PodmanClient;
+/// use flecs_core::relic::podman::image::inspect;
+/// use std::path::PathBuf;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// # tokio_test::block_on(
+/// async {
+///     let client = Arc::new(PodmanClient::new(
+///         PathBuf::from("/run/podman/podman.sock"),
+///         Duration::from_secs(120),
+///     ));
+///     let result = inspect(client, "docker.io/library/alpine:3.21")
+///         .await
+///         .unwrap();
+///     println!("{:#?}", result);
+/// }
+/// # )
+/// ```
+pub async fn inspect(client: Arc<PodmanClient>, image: &str) -> Result<Option<ImageInspect>> {
+    Ok(client
+        .get_json::<LibpodImageInspect>(&format!("/images/{image}/json"), &[])
+        .await?
+        .map(ImageInspect::from))
+}
+
+/// Returns false if the image did not exist
+pub async fn remove(client: Arc<PodmanClient>, image: &str, force: bool) -> Result<bool> {
+    client
+        .request_no_content(
+            Method::DELETE,
+            &format!("/images/{image}"),
+            &[("force", if force { "true" } else { "false" })],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await
+}
+
+/// Loads an image from a docker archive, see [crate::relic::docker::image::load]
+pub async fn load(quest: SyncQuest, client: Arc<PodmanClient>, path: &Path) -> Result<()> {
+    debug!("Import image from {path:?}");
+    let file = File::open(path).await?;
+    load_from_reader(quest, client, file).await
+}
+
+/// Loads an image archive from `reader`, see [load]
+pub async fn load_from_reader<R: AsyncRead + Send + Unpin + 'static>(
+    quest: SyncQuest,
+    client: Arc<PodmanClient>,
+    reader: R,
+) -> Result<()> {
+    let byte_stream =
+        codec::FramedRead::new(reader, codec::BytesCodec::new()).map(|r| r.map(|b| b.freeze()));
+    let response = client
+        .send_checked(
+            Method::POST,
+            "/images/load",
+            &[],
+            tar_headers(),
+            Body::from_stream(byte_stream),
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Podman does not support loading images"))?;
+    let report = write_stream_to_memory(quest, body_stream(response)).await?;
+    trace!("Loaded image: {}", String::from_utf8_lossy(&report));
+    Ok(())
+}
+
+/// Saves an image as docker archive, which allows importing the result into docker deployments
+/// as well, see [crate::relic::docker::image::save]
+pub async fn save(
+    quest: SyncQuest,
+    client: Arc<PodmanClient>,
+    path: &Path,
+    image: &str,
+) -> Result<()> {
+    let image = image.to_string();
+    let path = path.to_path_buf();
+    let result = quest
+        .lock()
+        .await
+        .create_sub_quest(
+            format!("Writing image {image} to {path:?}"),
+            |quest| async move {
+                let response = client
+                    .send_checked(
+                        Method::GET,
+                        &format!("/images/{image}/get"),
+                        &[("format", "docker-archive")],
+                        HeaderMap::new(),
+                        Body::empty(),
+                    )
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Image {image} does not exist"))?;
+                write_stream_to_file(quest, body_stream(response), &path).await
+            },
+        )
+        .await
+        .2;
+    result.await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quest::Quest;
+    use crate::relic::podman::tests::{StubPodman, StubResponse};
+    use http::StatusCode;
+
+    #[tokio::test]
+    async fn inspect_some() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/images/alpine:3.21/json"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({
+                        "Id": "aded1e1a5b37",
+                        "RepoTags": ["docker.io/library/alpine:3.21"],
+                        "Architecture": "amd64",
+                        "Os": "linux",
+                        "Size": 8123456,
+                        "Config": {"Cmd": ["/bin/sh"]},
+                        "Created": "2025-01-08T12:07:30.123456Z",
+                    }),
+                ),
+            )],
+        );
+        let image = inspect(stub.client(), "alpine:3.21")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(image.id.as_deref(), Some("aded1e1a5b37"));
+        assert_eq!(image.size, Some(8123456));
+        assert_eq!(
+            image.repo_tags,
+            Some(vec!["docker.io/library/alpine:3.21".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn inspect_none() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(
+            inspect(stub.client(), "alpine:3.21")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn pull_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::POST, "/images/pull"),
+                StubResponse {
+                    status: StatusCode::OK,
+                    body: b"{\"stream\":\"Trying to pull docker.io/library/alpine:3.21...\\n\"}\n{\"images\":[\"aded1e1a5b37\"],\"id\":\"aded1e1a5b37\"}\n".to_vec(),
+                },
+            )],
+        );
+        let credentials = DockerCredentials {
+            username: Some("user".to_string()),
+            password: Some("password".to_string()),
+            ..DockerCredentials::default()
+        };
+        let id = pull(
+            Quest::new_synced("Pull".to_string()),
+            stub.client(),
+            Some(credentials),
+            "docker.io/library/alpine",
+            "3.21",
+        )
+        .await
+        .unwrap();
+        assert_eq!(id, "aded1e1a5b37");
+        let requests = stub.requests();
+        assert_eq!(
+            requests[0].query.as_deref(),
+            Some("reference=docker.io%2Flibrary%2Falpine%3A3.21&policy=always")
+        );
+        assert!(requests[0].headers.contains_key("X-Registry-Auth"));
+    }
+
+    #[tokio::test]
+    async fn pull_error_report() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::POST, "/images/pull"),
+                StubResponse {
+                    status: StatusCode::OK,
+                    body: b"{\"error\":\"unauthorized\"}\n".to_vec(),
+                },
+            )],
+        );
+        let quest = Quest::new_synced("Pull".to_string());
+        assert!(
+            pull(quest.clone(), stub.client(), None, "registry.io/app", "1.0")
+                .await
+                .is_err()
+        );
+        assert_eq!(quest.lock().await.state, State::Failing);
+    }
+
+    #[tokio::test]
+    async fn remove_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(!remove(stub.client(), "alpine:3.21", true).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn save_to_file() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::GET, "/images/alpine:3.21/get"),
+                StubResponse {
+                    status: StatusCode::OK,
+                    body: b"image archive".to_vec(),
+                },
+            )],
+        );
+        let file = path.join("alpine.tar");
+        save(
+            Quest::new_synced("Save".to_string()),
+            stub.client(),
+            &file,
+            "alpine:3.21",
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(file).unwrap(), b"image archive");
+    }
+
+    #[tokio::test]
+    async fn load_from_file() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::POST, "/images/load"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({"Names": ["docker.io/library/alpine:3.21"]}),
+                ),
+            )],
+        );
+        let file = path.join("alpine.tar");
+        std::fs::write(&file, b"image archive").unwrap();
+        load(Quest::new_synced("Load".to_string()), stub.client(), &file)
+            .await
+            .unwrap();
+        assert_eq!(stub.requests()[0].body, b"image archive");
+    }
+}
@@ -0,0 +1,504 @@
+//! Thin client for the [libpod REST API](https://docs.podman.io/en/latest/_static/api.html) of
+//! Podman which is served on a unix socket. The functions in the submodules mirror the ones in
+//! [crate::relic::docker] but take an [Arc]<[PodmanClient]> instead of a bollard client.
+pub mod container;
+pub mod image;
+pub mod network;
+pub mod system;
+pub mod volume;
+
+pub use super::{Error, Result};
+use crate::quest::{Progress, SyncQuest};
+use axum::body::{Body, Bytes};
+use futures_util::{Stream, StreamExt};
+use http::{HeaderMap, HeaderValue, Method, Request, Response, StatusCode};
+use hyper::body::Incoming;
+use hyper_util::rt::TokioIo;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::UnixStream;
+use tracing::{trace, warn};
+
+/// The libpod API is versioned independently of the docker compatible API, all endpoints we use
+/// are available since Podman 4.0.
+pub const LIBPOD_API_VERSION: &str = "v4.0.0";
+
+/// The host header is required by HTTP/1.1 but ignored by Podman.
+const HOST: &str = "podman";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PodmanClient {
+    socket_path: PathBuf,
+    timeout: Duration,
+}
+
+/// Error body returned by libpod for all non successful requests
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    #[serde(default)]
+    cause: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+impl PodmanClient {
+    /// # Example
+    /// ```no_run
+    /// use flecs_core::relic::podman::{PodmanClient, image};
+    /// use std::path::PathBuf;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// # tokio_test::block_on(
+    /// async {
+    ///     let client = Arc::new(PodmanClient::new(
+    ///         PathBuf::from("/run/podman/podman.sock"),
+    ///         Duration::from_secs(120),
+    ///     ));
+    ///     let image = image::inspect(client, "docker.io/library/alpine:3.21")
+    ///         .await
+    ///         .unwrap();
+    ///     println!("{image:#?}");
+    /// }
+    /// # )
+    /// ```
+    pub fn new(socket_path: PathBuf, timeout: Duration) -> Self {
+        Self {
+            socket_path,
+            timeout,
+        }
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    fn uri(path: &str, query: &[(&str, &str)]) -> Result<String> {
+        let mut url =
+            reqwest::Url::parse(&format!("http://{HOST}/{LIBPOD_API_VERSION}/libpod{path}"))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        Ok(match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        })
+    }
+
+    /// Sends a request to the libpod API and returns the response regardless of its status code.
+    /// The timeout only applies to establishing the connection and receiving the response head,
+    /// the body of the response can be streamed for an arbitrary amount of time.
+    pub async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: HeaderMap,
+        body: Body,
+    ) -> Result<Response<Incoming>> {
+        let uri = Self::uri(path, query)?;
+        trace!("Sending podman request {method} {uri}");
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(http::header::HOST, HOST)
+            .body(body)?;
+        request.headers_mut().extend(headers);
+        tokio::time::timeout(self.timeout, async {
+            let stream = UnixStream::connect(&self.socket_path).await.map_err(|e| {
+                anyhow::anyhow!(
+                    "Could not connect to podman socket {}: {e}",
+                    self.socket_path.display()
+                )
+            })?;
+            let (mut sender, connection) =
+                hyper::client::conn::http1::handshake(TokioIo::new(stream)).await?;
+            tokio::spawn(async move {
+                if let Err(e) = connection.await {
+                    warn!("Podman connection failed: {e}");
+                }
+            });
+            Ok::<_, crate::Error>(sender.send_request(request).await?)
+        })
+        .await
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Request to podman socket {} timed out after {:?}",
+                self.socket_path.display(),
+                self.timeout
+            )
+        })?
+    }
+
+    /// Sends a request and fails if the response has a non successful status code. `Ok(None)` is
+    /// returned if the requested resource does not exist.
+    pub async fn send_checked(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: HeaderMap,
+        body: Body,
+    ) -> Result<Option<Response<Incoming>>> {
+        let response = self.send(method, path, query, headers, body).await?;
+        match response.status() {
+            status if status.is_success() => Ok(Some(response)),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => Err(error_from_response(status, response).await),
+        }
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Option<T>> {
+        match self
+            .send_checked(Method::GET, path, query, HeaderMap::new(), Body::empty())
+            .await?
+        {
+            None => Ok(None),
+            Some(response) => Ok(Some(serde_json::from_slice(
+                &collect_body(response).await?,
+            )?)),
+        }
+    }
+
+    pub async fn post_json<B: serde::Serialize, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        body: &B,
+    ) -> Result<Option<T>> {
+        match self
+            .send_checked(Method::POST, path, query, json_headers(), json_body(body)?)
+            .await?
+        {
+            None => Ok(None),
+            Some(response) => Ok(Some(serde_json::from_slice(
+                &collect_body(response).await?,
+            )?)),
+        }
+    }
+
+    /// Sends a request without expecting any content in the response. Returns `false` if the
+    /// resource does not exist.
+    pub async fn request_no_content(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: HeaderMap,
+        body: Body,
+    ) -> Result<bool> {
+        Ok(self
+            .send_checked(method, path, query, headers, body)
+            .await?
+            .is_some())
+    }
+}
+
+fn json_headers() -> HeaderMap {
+    HeaderMap::from_iter([(
+        http::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    )])
+}
+
+fn json_body<B: serde::Serialize>(body: &B) -> Result<Body> {
+    Ok(Body::from(serde_json::to_vec(body)?))
+}
+
+fn tar_headers() -> HeaderMap {
+    HeaderMap::from_iter([(
+        http::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/x-tar"),
+    )])
+}
+
+async fn collect_body(response: Response<Incoming>) -> Result<Bytes> {
+    Ok(axum::body::to_bytes(Body::new(response.into_body()), usize::MAX).await?)
+}
+
+fn body_stream(response: Response<Incoming>) -> impl Stream<Item = Result<Bytes>> {
+    Body::new(response.into_body())
+        .into_data_stream()
+        .map(|data| data.map_err(anyhow::Error::from))
+}
+
+async fn error_from_response(status: StatusCode, response: Response<Incoming>) -> Error {
+    match collect_body(response).await {
+        Err(e) => anyhow::anyhow!("Podman responded with {status}, failed to read body: {e}"),
+        Ok(body) => match serde_json::from_slice::<ErrorResponse>(&body) {
+            Ok(ErrorResponse {
+                message: Some(message),
+                ..
+            }) => anyhow::anyhow!("Podman responded with {status}: {message}"),
+            Ok(ErrorResponse {
+                cause: Some(cause), ..
+            }) => anyhow::anyhow!("Podman responded with {status}: {cause}"),
+            _ => anyhow::anyhow!(
+                "Podman responded with {status}: {}",
+                String::from_utf8_lossy(&body)
+            ),
+        },
+    }
+}
+
+async fn write_stream_to_writer<T, W>(quest: SyncQuest, mut stream: T, writer: &mut W) -> Result<()>
+where
+    T: Stream<Item = Result<Bytes>> + Unpin,
+    W: AsyncWriteExt + Unpin,
+{
+    let mut total_bytes = 0;
+    while let Some(data) = stream.next().await {
+        let data = data?;
+        writer.write_all(data.as_ref()).await?;
+        total_bytes += data.len();
+        quest.lock().await.progress = Some(Progress {
+            current: total_bytes as u64,
+            total: None,
+        });
+    }
+    writer.flush().await?;
+    Ok(())
+}
+
+async fn write_stream_to_file<T>(quest: SyncQuest, stream: T, path: &Path) -> Result<()>
+where
+    T: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .await?;
+    write_stream_to_writer(quest, stream, &mut file).await
+}
+
+async fn write_stream_to_memory<T>(quest: SyncQuest, stream: T) -> Result<Vec<u8>>
+where
+    T: Stream<Item = Result<Bytes>> + Unpin,
+{
+    let mut buffer = Vec::new();
+    write_stream_to_writer(quest, stream, &mut buffer).await?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use hyper::service::service_fn;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::UnixListener;
+    use tokio::task::JoinHandle;
+
+    #[derive(Debug, Clone)]
+    pub struct StubResponse {
+        pub status: StatusCode,
+        pub body: Vec<u8>,
+    }
+
+    impl StubResponse {
+        pub fn json(status: StatusCode, body: serde_json::Value) -> Self {
+            Self {
+                status,
+                body: serde_json::to_vec(&body).unwrap(),
+            }
+        }
+
+        pub fn empty(status: StatusCode) -> Self {
+            Self {
+                status,
+                body: Vec::new(),
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct RecordedRequest {
+        pub method: Method,
+        pub path: String,
+        pub query: Option<String>,
+        pub headers: HeaderMap,
+        pub body: Vec<u8>,
+    }
+
+    impl RecordedRequest {
+        pub fn json_body(&self) -> serde_json::Value {
+            serde_json::from_slice(&self.body).unwrap()
+        }
+    }
+
+    /// Serves canned responses on a unix socket in place of a podman service. Requests for routes
+    /// that were not registered are answered with 404.
+    pub struct StubPodman {
+        socket_path: PathBuf,
+        requests: Arc<Mutex<Vec<RecordedRequest>>>,
+        handle: JoinHandle<()>,
+    }
+
+    impl Drop for StubPodman {
+        fn drop(&mut self) {
+            self.handle.abort();
+        }
+    }
+
+    impl StubPodman {
+        /// Routes are given as (method, path below /libpod) e.g. (GET, "/images/alpine/json")
+        pub fn spawn(
+            socket_path: PathBuf,
+            routes: impl IntoIterator<Item = ((Method, &'static str), StubResponse)>,
+        ) -> Self {
+            let routes: Arc<HashMap<(Method, String), StubResponse>> = Arc::new(
+                routes
+                    .into_iter()
+                    .map(|((method, path), response)| {
+                        (
+                            (method, format!("/{LIBPOD_API_VERSION}/libpod{path}")),
+                            response,
+                        )
+                    })
+                    .collect(),
+            );
+            let _ = std::fs::remove_file(&socket_path);
+            let listener = UnixListener::bind(&socket_path).unwrap();
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = requests.clone();
+            let handle = tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let routes = routes.clone();
+                    let recorded = recorded.clone();
+                    tokio::spawn(async move {
+                        let service = service_fn(move |request: Request<Incoming>| {
+                            let routes = routes.clone();
+                            let recorded = recorded.clone();
+                            async move {
+                                let (parts, body) = request.into_parts();
+                                let body = axum::body::to_bytes(Body::new(body), usize::MAX)
+                                    .await
+                                    .unwrap();
+                                let path = parts.uri.path().to_string();
+                                recorded.lock().unwrap().push(RecordedRequest {
+                                    method: parts.method.clone(),
+                                    path: path.clone(),
+                                    query: parts.uri.query().map(ToString::to_string),
+                                    headers: parts.headers,
+                                    body: body.to_vec(),
+                                });
+                                let response = routes
+                                    .get(&(parts.method, path))
+                                    .cloned()
+                                    .unwrap_or(StubResponse::empty(StatusCode::NOT_FOUND));
+                                Response::builder()
+                                    .status(response.status)
+                                    .body(Body::from(response.body))
+                            }
+                        });
+                        let _ = hyper::server::conn::http1::Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .await;
+                    });
+                }
+            });
+            Self {
+                socket_path,
+                requests,
+                handle,
+            }
+        }
+
+        pub fn client(&self) -> Arc<PodmanClient> {
+            Arc::new(PodmanClient::new(
+                self.socket_path.clone(),
+                Duration::from_secs(10),
+            ))
+        }
+
+        pub fn requests(&self) -> Vec<RecordedRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn uri_without_query() {
+        assert_eq!(
+            PodmanClient::uri("/images/alpine/json", &[]).unwrap(),
+            format!("/{LIBPOD_API_VERSION}/libpod/images/alpine/json")
+        );
+    }
+
+    #[test]
+    fn uri_with_query() {
+        assert_eq!(
+            PodmanClient::uri(
+                "/images/pull",
+                &[("reference", "registry.io/app:1.0"), ("quiet", "true")]
+            )
+            .unwrap(),
+            format!(
+                "/{LIBPOD_API_VERSION}/libpod/images/pull?reference=registry.io%2Fapp%3A1.0&quiet=true"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn get_json_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/info"),
+                StubResponse::json(StatusCode::OK, serde_json::json!({"value": 10})),
+            )],
+        );
+        let value: serde_json::Value = stub.client().get_json("/info", &[]).await.unwrap().unwrap();
+        assert_eq!(value, serde_json::json!({"value": 10}));
+        assert_eq!(stub.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn get_json_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        let value: Option<serde_json::Value> = stub.client().get_json("/info", &[]).await.unwrap();
+        assert!(value.is_none());
+    }
+
+    #[tokio::test]
+    async fn get_json_error_message() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/info"),
+                StubResponse::json(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    serde_json::json!({"cause": "some cause", "message": "some message", "response": 500}),
+                ),
+            )],
+        );
+        let error = stub
+            .client()
+            .get_json::<serde_json::Value>("/info", &[])
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("some message"));
+    }
+
+    #[tokio::test]
+    async fn send_no_socket() {
+        let client = PodmanClient::new(
+            testdir::testdir!().join("missing.sock"),
+            Duration::from_secs(1),
+        );
+        assert!(
+            client
+                .send(Method::GET, "/info", &[], HeaderMap::new(), Body::empty())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,301 @@
+pub use super::Result;
+use crate::relic::podman::{PodmanClient, json_body, json_headers};
+use bollard::models::{Ipam, IpamConfig, Network};
+use http::{HeaderMap, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subnet {
+    pub subnet: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gateway: Option<String>,
+}
+
+/// Network as created and returned by libpod
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LibpodNetwork {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    pub driver: String,
+    /// Parent interface of macvlan and ipvlan networks, bridge name of bridge networks
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub network_interface: String,
+    #[serde(default)]
+    pub subnets: Vec<Subnet>,
+    #[serde(default)]
+    pub internal: bool,
+    #[serde(default)]
+    pub options: HashMap<String, String>,
+}
+
+impl From<LibpodNetwork> for Network {
+    /// The options are translated to the docker equivalents so that
+    /// [crate::forge::bollard::BollardNetworkExtension] works for podman networks as well
+    fn from(value: LibpodNetwork) -> Self {
+        let mut options = value.options;
+        if value.driver == "ipvlan" {
+            if let Some(mode) = options.remove("mode") {
+                options.insert("ipvlan_mode".to_string(), mode);
+            }
+        }
+        if matches!(value.driver.as_str(), "ipvlan" | "macvlan")
+            && !value.network_interface.is_empty()
+        {
+            options.insert("parent".to_string(), value.network_interface);
+        }
+        Network {
+            name: Some(value.name),
+            id: (!value.id.is_empty()).then_some(value.id),
+            driver: Some(value.driver),
+            internal: Some(value.internal),
+            ipam: Some(Ipam {
+                driver: Some("default".to_string()),
+                config: Some(
+                    value
+                        .subnets
+                        .into_iter()
+                        .map(|subnet| IpamConfig {
+                            subnet: Some(subnet.subnet),
+                            gateway: subnet.gateway,
+                            ..IpamConfig::default()
+                        })
+                        .collect(),
+                ),
+                ..Ipam::default()
+            }),
+            options: Some(options),
+            ..Network::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct ConnectRequest<'a> {
+    container: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    static_ips: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct DisconnectRequest<'a> {
+    container: &'a str,
+    force: bool,
+}
+
+/// Fails if a network with the same name already exists
+pub async fn create(client: Arc<PodmanClient>, network: &LibpodNetwork) -> Result<Network> {
+    let network: LibpodNetwork = client
+        .post_json("/networks/create", &[], network)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Podman does not support creating networks"))?;
+    Ok(network.into())
+}
+
+pub async fn inspect(client: Arc<PodmanClient>, network_name: &str) -> Result<Option<Network>> {
+    Ok(client
+        .get_json::<LibpodNetwork>(&format!("/networks/{network_name}/json"), &[])
+        .await?
+        .map(Network::from))
+}
+
+pub async fn list(client: Arc<PodmanClient>) -> Result<Vec<Network>> {
+    Ok(client
+        .get_json::<Vec<LibpodNetwork>>("/networks/json", &[])
+        .await?
+        .unwrap_or_default()
+        .into_iter()
+        .map(Network::from)
+        .collect())
+}
+
+pub async fn remove(client: Arc<PodmanClient>, network_name: &str) -> Result<()> {
+    let response = client
+        .send(
+            Method::DELETE,
+            &format!("/networks/{network_name}"),
+            &[],
+            HeaderMap::new(),
+            axum::body::Body::empty(),
+        )
+        .await?;
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        StatusCode::NOT_FOUND => anyhow::bail!("Network {network_name} does not exist"),
+        status => Err(super::error_from_response(status, response).await),
+    }
+}
+
+pub async fn connect(
+    client: Arc<PodmanClient>,
+    network_name: &str,
+    container_name: &str,
+    address: Option<String>,
+) -> Result<()> {
+    let body = ConnectRequest {
+        container: container_name,
+        static_ips: address.into_iter().collect(),
+    };
+    if !client
+        .request_no_content(
+            Method::POST,
+            &format!("/networks/{network_name}/connect"),
+            &[],
+            json_headers(),
+            json_body(&body)?,
+        )
+        .await?
+    {
+        anyhow::bail!("Network {network_name} or container {container_name} does not exist");
+    }
+    Ok(())
+}
+
+pub async fn disconnect(
+    client: Arc<PodmanClient>,
+    network_name: &str,
+    container_name: &str,
+    force: bool,
+) -> Result<()> {
+    let body = DisconnectRequest {
+        container: container_name,
+        force,
+    };
+    if !client
+        .request_no_content(
+            Method::POST,
+            &format!("/networks/{network_name}/disconnect"),
+            &[],
+            json_headers(),
+            json_body(&body)?,
+        )
+        .await?
+    {
+        anyhow::bail!("Network {network_name} or container {container_name} does not exist");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forge::bollard::BollardNetworkExtension;
+    use crate::jeweler::network::NetworkKind;
+    use crate::relic::podman::tests::{StubPodman, StubResponse};
+
+    fn ipvlan_network() -> serde_json::Value {
+        serde_json::json!({
+            "name": "flecs-ipvlan_l2-eth0",
+            "id": "1e3a",
+            "driver": "ipvlan",
+            "network_interface": "eth0",
+            "created": "2025-04-02T09:51:24.130529017Z",
+            "subnets": [{"subnet": "192.168.2.0/24", "gateway": "192.168.2.1"}],
+            "ipv6_enabled": false,
+            "internal": false,
+            "dns_enabled": false,
+            "options": {"mode": "l2"},
+            "ipam_options": {"driver": "host-local"}
+        })
+    }
+
+    #[test]
+    fn convert_ipvlan_network() {
+        let network: LibpodNetwork = serde_json::from_value(ipvlan_network()).unwrap();
+        let network = Network::from(network);
+        assert_eq!(network.guess_network_kind(), NetworkKind::IpvlanL2);
+        assert_eq!(network.parent_network().as_deref(), Some("eth0"));
+        assert_eq!(
+            network.gateways().unwrap(),
+            vec!["192.168.2.1".parse::<std::net::IpAddr>().unwrap()]
+        );
+        assert_eq!(network.id.as_deref(), Some("1e3a"));
+    }
+
+    #[test]
+    fn convert_bridge_network() {
+        let network = Network::from(LibpodNetwork {
+            name: "flecs".to_string(),
+            driver: "bridge".to_string(),
+            network_interface: "podman1".to_string(),
+            subnets: vec![Subnet {
+                subnet: "172.21.0.0/16".to_string(),
+                gateway: Some("172.21.0.1".to_string()),
+            }],
+            ..LibpodNetwork::default()
+        });
+        assert_eq!(network.guess_network_kind(), NetworkKind::Bridge);
+        assert_eq!(network.parent_network(), None);
+        assert!(network.id.is_none());
+    }
+
+    #[tokio::test]
+    async fn inspect_some() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/networks/flecs-ipvlan_l2-eth0/json"),
+                StubResponse::json(StatusCode::OK, ipvlan_network()),
+            )],
+        );
+        let network = inspect(stub.client(), "flecs-ipvlan_l2-eth0")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(network.name.as_deref(), Some("flecs-ipvlan_l2-eth0"));
+    }
+
+    #[tokio::test]
+    async fn list_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/networks/json"),
+                StubResponse::json(StatusCode::OK, serde_json::json!([ipvlan_network()])),
+            )],
+        );
+        assert_eq!(list(stub.client()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn remove_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(remove(stub.client(), "flecs").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn connect_with_address() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::POST, "/networks/flecs/connect"),
+                StubResponse::empty(StatusCode::OK),
+            )],
+        );
+        connect(
+            stub.client(),
+            "flecs",
+            "flecs-1234abcd",
+            Some("172.21.0.5".to_string()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            stub.requests()[0].json_body(),
+            serde_json::json!({"container": "flecs-1234abcd", "static_ips": ["172.21.0.5"]})
+        );
+    }
+
+    #[tokio::test]
+    async fn disconnect_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(
+            disconnect(stub.client(), "flecs", "flecs-1234abcd", false)
+                .await
+                .is_err()
+        );
+    }
+}
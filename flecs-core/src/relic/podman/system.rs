@@ -0,0 +1,166 @@
+pub use super::Result;
+use crate::relic::podman::PodmanClient;
+use serde::Deserialize;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct Security {
+    #[serde(default)]
+    pub rootless: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct Host {
+    #[serde(default)]
+    pub arch: String,
+    #[serde(default)]
+    pub security: Security,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Version {
+    #[serde(default)]
+    pub version: String,
+}
+
+/// Subset of the information libpod provides about the host and the podman service
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct Info {
+    #[serde(default)]
+    pub host: Host,
+    #[serde(default)]
+    pub version: Version,
+}
+
+/// Image entry of the libpod disk usage report, images with multiple tags are listed once per tag
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct DiskUsageImage {
+    #[serde(default)]
+    pub repository: String,
+    #[serde(default)]
+    pub tag: String,
+    #[serde(rename = "ImageID")]
+    pub image_id: String,
+    #[serde(default)]
+    pub size: i64,
+    #[serde(default)]
+    pub containers: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct DiskUsageVolume {
+    pub volume_name: String,
+    #[serde(default)]
+    pub links: i64,
+    #[serde(default)]
+    pub size: i64,
+}
+
+/// Subset of the disk usage report of libpod
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct DiskUsage {
+    #[serde(default)]
+    pub images: Vec<DiskUsageImage>,
+    #[serde(default)]
+    pub volumes: Vec<DiskUsageVolume>,
+}
+
+pub async fn info(client: Arc<PodmanClient>) -> Result<Info> {
+    client
+        .get_json("/info", &[])
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Podman does not provide system info"))
+}
+
+pub async fn disk_usage(client: Arc<PodmanClient>) -> Result<DiskUsage> {
+    client
+        .get_json("/system/df", &[])
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Podman does not provide disk usage"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relic::podman::tests::{StubPodman, StubResponse};
+    use http::{Method, StatusCode};
+
+    #[tokio::test]
+    async fn info_rootless() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/info"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({
+                        "host": {
+                            "arch": "arm64",
+                            "security": {"rootless": true, "selinuxEnabled": false}
+                        },
+                        "version": {"APIVersion": "5.4.1", "Version": "5.4.1"}
+                    }),
+                ),
+            )],
+        );
+        let info = info(stub.client()).await.unwrap();
+        assert!(info.host.security.rootless);
+        assert_eq!(info.host.arch, "arm64");
+        assert_eq!(info.version.version, "5.4.1");
+    }
+
+    #[tokio::test]
+    async fn disk_usage_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/system/df"),
+                StubResponse::json(
+                    StatusCode::OK,
+                    serde_json::json!({
+                        "ImagesSize": 1000,
+                        "Images": [{
+                            "Repository": "flecs.azurecr.io/tech.flecs.mqtt-bridge",
+                            "Tag": "3.0.0",
+                            "ImageID": "9a0c4d0b2f5e",
+                            "Created": "2025-01-01T00:00:00Z",
+                            "Size": 1000,
+                            "SharedSize": 0,
+                            "UniqueSize": 1000,
+                            "Containers": 2
+                        }],
+                        "Containers": [],
+                        "Volumes": [{
+                            "VolumeName": "flecs-01234567-data",
+                            "Links": 1,
+                            "Size": 4096,
+                            "ReclaimableSize": 0
+                        }]
+                    }),
+                ),
+            )],
+        );
+        let usage = disk_usage(stub.client()).await.unwrap();
+        assert_eq!(
+            usage,
+            DiskUsage {
+                images: vec![DiskUsageImage {
+                    repository: "flecs.azurecr.io/tech.flecs.mqtt-bridge".to_string(),
+                    tag: "3.0.0".to_string(),
+                    image_id: "9a0c4d0b2f5e".to_string(),
+                    size: 1000,
+                    containers: 2,
+                }],
+                volumes: vec![DiskUsageVolume {
+                    volume_name: "flecs-01234567-data".to_string(),
+                    links: 1,
+                    size: 4096,
+                }],
+            }
+        );
+    }
+}
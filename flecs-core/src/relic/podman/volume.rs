@@ -0,0 +1,286 @@
+pub use super::Result;
+use crate::quest::{Progress, SyncQuest};
+use crate::relic::podman::{PodmanClient, body_stream, tar_headers, write_stream_to_file};
+use axum::body::Body;
+use bollard::models::Volume;
+use futures_util::stream::StreamExt;
+use http::{HeaderMap, Method};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::File;
+use tokio_util::codec;
+use tracing::error;
+
+/// Volume as returned by libpod
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LibpodVolume {
+    name: String,
+    #[serde(default)]
+    driver: String,
+    #[serde(default)]
+    mountpoint: String,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    #[serde(default)]
+    options: HashMap<String, String>,
+}
+
+impl From<LibpodVolume> for Volume {
+    fn from(value: LibpodVolume) -> Self {
+        Self {
+            name: value.name,
+            driver: value.driver,
+            mountpoint: value.mountpoint,
+            labels: value.labels,
+            options: value.options,
+            ..Volume::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct CreateRequest<'a> {
+    name: &'a str,
+    driver: &'a str,
+}
+
+pub async fn create(client: Arc<PodmanClient>, name: &str) -> Result<Volume> {
+    let volume: LibpodVolume = client
+        .post_json(
+            "/volumes/create",
+            &[],
+            &CreateRequest {
+                name,
+                driver: "local",
+            },
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Podman does not support creating volumes"))?;
+    Ok(volume.into())
+}
+
+pub async fn inspect(client: Arc<PodmanClient>, name: &str) -> Result<Option<Volume>> {
+    Ok(client
+        .get_json::<LibpodVolume>(&format!("/volumes/{name}/json"), &[])
+        .await?
+        .map(Volume::from))
+}
+
+/// Returns false if the volume did not exist
+pub async fn remove(client: Arc<PodmanClient>, name: &str) -> Result<bool> {
+    client
+        .request_no_content(
+            Method::DELETE,
+            &format!("/volumes/{name}"),
+            &[],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await
+}
+
+/// Replaces the content of the volume with the content of the given uncompressed tar archive
+pub async fn import_archive(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    name: &str,
+    archive: Body,
+    total: Option<u64>,
+) -> Result<()> {
+    quest.lock().await.progress = Some(Progress { total, current: 0 });
+    if !client
+        .request_no_content(
+            Method::POST,
+            &format!("/volumes/{name}/import"),
+            &[],
+            tar_headers(),
+            archive,
+        )
+        .await?
+    {
+        anyhow::bail!("Volume {name} does not exist");
+    }
+    if let Some(total) = total {
+        quest.lock().await.progress = Some(Progress {
+            total: Some(total),
+            current: total,
+        });
+    }
+    Ok(())
+}
+
+pub async fn import_archive_file(
+    client: Arc<PodmanClient>,
+    quest: SyncQuest,
+    name: &str,
+    path: &Path,
+) -> Result<()> {
+    let file = File::open(path).await?;
+    let total = file.metadata().await.map(|meta| meta.len()).ok();
+    let byte_stream =
+        codec::FramedRead::new(file, codec::BytesCodec::new()).map(|r| r.map(|b| b.freeze()));
+    import_archive(client, quest, name, Body::from_stream(byte_stream), total).await
+}
+
+/// Writes the content of the volume as tar archive to `dst`
+pub async fn export_to_file(
+    quest: SyncQuest,
+    client: Arc<PodmanClient>,
+    name: &str,
+    dst: &Path,
+) -> Result<()> {
+    let response = client
+        .send_checked(
+            Method::GET,
+            &format!("/volumes/{name}/export"),
+            &[],
+            HeaderMap::new(),
+            Body::empty(),
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Volume {name} does not exist"))?;
+    match write_stream_to_file(quest, body_stream(response), dst).await {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            if let Err(e) = tokio::fs::remove_file(dst).await {
+                error!("Failed to remove {dst:?} after failing to export volume {name}: {e}");
+            }
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quest::Quest;
+    use crate::relic::podman::tests::{StubPodman, StubResponse};
+    use http::StatusCode;
+
+    fn volume() -> serde_json::Value {
+        serde_json::json!({
+            "Name": "data",
+            "Driver": "local",
+            "Mountpoint": "/var/lib/containers/storage/volumes/data/_data",
+            "CreatedAt": "2025-04-02T09:51:24.130529017Z",
+            "Labels": {},
+            "Scope": "local",
+            "Options": {},
+            "UID": 0,
+            "GID": 0,
+            "MountCount": 0
+        })
+    }
+
+    #[tokio::test]
+    async fn create_ok() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::POST, "/volumes/create"),
+                StubResponse::json(StatusCode::CREATED, volume()),
+            )],
+        );
+        let volume = create(stub.client(), "data").await.unwrap();
+        assert_eq!(volume.name, "data");
+        assert_eq!(
+            stub.requests()[0].json_body(),
+            serde_json::json!({"Name": "data", "Driver": "local"})
+        );
+    }
+
+    #[tokio::test]
+    async fn inspect_some() {
+        let stub = StubPodman::spawn(
+            testdir::testdir!().join("podman.sock"),
+            [(
+                (Method::GET, "/volumes/data/json"),
+                StubResponse::json(StatusCode::OK, volume()),
+            )],
+        );
+        let volume = inspect(stub.client(), "data").await.unwrap().unwrap();
+        assert_eq!(
+            volume.mountpoint,
+            "/var/lib/containers/storage/volumes/data/_data"
+        );
+    }
+
+    #[tokio::test]
+    async fn remove_not_found() {
+        let stub = StubPodman::spawn(testdir::testdir!().join("podman.sock"), []);
+        assert!(!remove(stub.client(), "data").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn import_archive_file_ok() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::POST, "/volumes/data/import"),
+                StubResponse::empty(StatusCode::NO_CONTENT),
+            )],
+        );
+        let archive = path.join("data.tar");
+        std::fs::write(&archive, b"volume archive").unwrap();
+        let quest = Quest::new_synced("Import".to_string());
+        import_archive_file(stub.client(), quest.clone(), "data", &archive)
+            .await
+            .unwrap();
+        assert_eq!(stub.requests()[0].body, b"volume archive");
+        assert_eq!(
+            quest.lock().await.progress,
+            Some(Progress {
+                current: 14,
+                total: Some(14)
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn export_to_file_missing_volume() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(path.join("podman.sock"), []);
+        let dst = path.join("data.tar");
+        assert!(
+            export_to_file(
+                Quest::new_synced("Export".to_string()),
+                stub.client(),
+                "data",
+                &dst
+            )
+            .await
+            .is_err()
+        );
+        assert!(!dst.exists());
+    }
+
+    #[tokio::test]
+    async fn export_to_file_ok() {
+        let path = testdir::testdir!();
+        let stub = StubPodman::spawn(
+            path.join("podman.sock"),
+            [(
+                (Method::GET, "/volumes/data/export"),
+                StubResponse {
+                    status: StatusCode::OK,
+                    body: b"volume archive".to_vec(),
+                },
+            )],
+        );
+        let dst = path.join("data.tar");
+        export_to_file(
+            Quest::new_synced("Export".to_string()),
+            stub.client(),
+            "data",
+            &dst,
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(dst).unwrap(), b"volume archive");
+    }
+}
@@ -14,6 +14,7 @@ pub fn try_create_system_info() -> Result<SystemInfo> {
         distro: read_distro(),
         kernel: read_kernel(&info),
         platform: platform_from_version(info.version().to_str().unwrap_or_default()).to_string(),
+        serial_number: None,
     })
 }
 
@@ -1,9 +1,12 @@
 pub mod info;
+pub mod serial;
 
 pub use super::{Error, Result};
 use libc::c_char;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
 
 use thiserror::Error;
 #[derive(Error, Debug)]
@@ -28,3 +31,72 @@ pub fn hostname() -> Result<String, ReadHostnameError> {
         Ok(hostname.to_string())
     }
 }
+
+/// Returns the number of bytes available to unprivileged users on the filesystem containing
+/// `path`
+pub fn available_space(path: &Path) -> Result<u64> {
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: MaybeUninit<libc::statvfs> = MaybeUninit::uninit();
+    if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return Err(anyhow::anyhow!(
+            "Call to 'statvfs' for {path:?} failed: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    let stat = unsafe { stat.assume_init() };
+    // The field types differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    let available = stat.f_bavail as u64 * stat.f_frsize as u64;
+    Ok(available)
+}
+
+/// Returns the accumulated size of all regular files below `path`, symlinks are neither followed
+/// nor counted. The size of a missing path is zero.
+pub async fn directory_size(path: &Path) -> Result<u64> {
+    let mut size = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let metadata = match tokio::fs::symlink_metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        if metadata.is_dir() {
+            let mut entries = tokio::fs::read_dir(&path).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                pending.push(entry.path());
+            }
+        } else if metadata.is_file() {
+            size += metadata.len();
+        }
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testdir::testdir;
+
+    #[test]
+    fn available_space_ok() {
+        assert!(available_space(&testdir!()).unwrap() > 0);
+    }
+
+    #[test]
+    fn available_space_err() {
+        assert!(available_space(Path::new("/path/which/does/not/exist")).is_err());
+    }
+
+    #[tokio::test]
+    async fn directory_size_ok() {
+        let path = testdir!();
+        std::fs::create_dir_all(path.join("a/b")).unwrap();
+        std::fs::write(path.join("a/file"), [0; 100]).unwrap();
+        std::fs::write(path.join("a/b/file"), [0; 20]).unwrap();
+        std::os::unix::fs::symlink(path.join("a"), path.join("a/b/link")).unwrap();
+        assert_eq!(directory_size(&path.join("a")).await.unwrap(), 120);
+        assert_eq!(directory_size(&path.join("a/file")).await.unwrap(), 100);
+        assert_eq!(directory_size(&path.join("missing")).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::lore::SystemLore;
+#[cfg(test)]
+use mockall::automock;
+use std::path::PathBuf;
+use tracing::debug;
+
+const DMI_PRODUCT_SERIAL_PATH: &str = "/sys/class/dmi/id/product_serial";
+const DEVICE_TREE_SERIAL_NUMBER_PATH: &str = "/proc/device-tree/serial-number";
+
+/// Values some firmwares report instead of an actual serial number
+const PLACEHOLDER_SERIAL_NUMBERS: [&str; 8] = [
+    "0",
+    "none",
+    "default string",
+    "not specified",
+    "not applicable",
+    "to be filled by o.e.m.",
+    "system serial number",
+    "0123456789",
+];
+
+#[cfg_attr(test, automock)]
+pub trait SerialNumberReader: Sync + Send {
+    fn read_serial_number(&self) -> Option<String>;
+}
+
+/// Reads the serial number from a file, e.g. from sysfs or procfs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSerialNumberReader {
+    pub path: PathBuf,
+}
+
+impl FileSerialNumberReader {
+    pub fn dmi() -> Self {
+        Self {
+            path: PathBuf::from(DMI_PRODUCT_SERIAL_PATH),
+        }
+    }
+
+    pub fn device_tree() -> Self {
+        Self {
+            path: PathBuf::from(DEVICE_TREE_SERIAL_NUMBER_PATH),
+        }
+    }
+}
+
+impl SerialNumberReader for FileSerialNumberReader {
+    fn read_serial_number(&self) -> Option<String> {
+        match std::fs::read(&self.path) {
+            Ok(content) => sanitize_serial_number(&String::from_utf8_lossy(&content)),
+            Err(e) => {
+                debug!("Could not read serial number from {:?}: {e}", self.path);
+                None
+            }
+        }
+    }
+}
+
+/// Returns a configured serial number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedSerialNumberReader(pub String);
+
+impl SerialNumberReader for FixedSerialNumberReader {
+    fn read_serial_number(&self) -> Option<String> {
+        sanitize_serial_number(&self.0)
+    }
+}
+
+/// Tries multiple [SerialNumberReader]s in order and returns the first serial number found
+pub struct SerialNumberReaderImpl {
+    readers: Vec<Box<dyn SerialNumberReader>>,
+}
+
+impl SerialNumberReaderImpl {
+    pub fn new(readers: Vec<Box<dyn SerialNumberReader>>) -> Self {
+        Self { readers }
+    }
+}
+
+impl Default for SerialNumberReaderImpl {
+    fn default() -> Self {
+        Self::new(vec![
+            Box::new(FileSerialNumberReader::dmi()),
+            Box::new(FileSerialNumberReader::device_tree()),
+        ])
+    }
+}
+
+/// A configured serial number takes precedence over vendor specific files, which in turn take
+/// precedence over DMI and the device tree
+impl From<&SystemLore> for SerialNumberReaderImpl {
+    fn from(lore: &SystemLore) -> Self {
+        let mut readers: Vec<Box<dyn SerialNumberReader>> = Vec::new();
+        if let Some(serial_number) = &lore.serial_number {
+            readers.push(Box::new(FixedSerialNumberReader(serial_number.clone())));
+        }
+        for path in &lore.serial_number_paths {
+            readers.push(Box::new(FileSerialNumberReader { path: path.clone() }));
+        }
+        readers.push(Box::new(FileSerialNumberReader::dmi()));
+        readers.push(Box::new(FileSerialNumberReader::device_tree()));
+        Self::new(readers)
+    }
+}
+
+impl SerialNumberReader for SerialNumberReaderImpl {
+    fn read_serial_number(&self) -> Option<String> {
+        self.readers
+            .iter()
+            .find_map(|reader| reader.read_serial_number())
+    }
+}
+
+/// Strips whitespace and the trailing nul of device tree strings, empty values and placeholders
+/// result in `None`
+fn sanitize_serial_number(value: &str) -> Option<String> {
+    let value = value.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    if value.is_empty()
+        || PLACEHOLDER_SERIAL_NUMBERS
+            .iter()
+            .any(|placeholder| value.eq_ignore_ascii_case(placeholder))
+    {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testdir::testdir;
+
+    #[test]
+    fn sanitize_serial_number_ok() {
+        assert_eq!(
+            sanitize_serial_number(" SN-1234\n"),
+            Some("SN-1234".to_string())
+        );
+        assert_eq!(
+            sanitize_serial_number("SN-1234\0"),
+            Some("SN-1234".to_string())
+        );
+    }
+
+    #[test]
+    fn sanitize_serial_number_placeholder() {
+        assert_eq!(sanitize_serial_number(""), None);
+        assert_eq!(sanitize_serial_number("\0"), None);
+        assert_eq!(sanitize_serial_number("To Be Filled By O.E.M.\n"), None);
+        assert_eq!(sanitize_serial_number("Default string"), None);
+    }
+
+    #[test]
+    fn file_serial_number_reader_ok() {
+        let path = testdir!().join("serial-number");
+        std::fs::write(&path, b"00000000c0ffee00\0").unwrap();
+        assert_eq!(
+            FileSerialNumberReader { path }.read_serial_number(),
+            Some("00000000c0ffee00".to_string())
+        );
+    }
+
+    #[test]
+    fn file_serial_number_reader_missing() {
+        let path = testdir!().join("serial-number");
+        assert_eq!(FileSerialNumberReader { path }.read_serial_number(), None);
+    }
+
+    #[test]
+    fn serial_number_reader_impl_first_match() {
+        let mut first = MockSerialNumberReader::new();
+        first.expect_read_serial_number().once().return_const(None);
+        let mut second = MockSerialNumberReader::new();
+        second
+            .expect_read_serial_number()
+            .once()
+            .return_const(Some("SN-2".to_string()));
+        let mut third = MockSerialNumberReader::new();
+        third.expect_read_serial_number().never();
+        let reader =
+            SerialNumberReaderImpl::new(vec![Box::new(first), Box::new(second), Box::new(third)]);
+        assert_eq!(reader.read_serial_number(), Some("SN-2".to_string()));
+    }
+
+    #[test]
+    fn serial_number_reader_impl_from_lore() {
+        let path = testdir!().join("vendor-serial");
+        std::fs::write(&path, "VENDOR-1234\n").unwrap();
+        let lore = SystemLore {
+            core_sbom_spdx_path: PathBuf::default(),
+            serial_number: None,
+            serial_number_paths: vec![path.clone()],
+        };
+        assert_eq!(
+            SerialNumberReaderImpl::from(&lore).read_serial_number(),
+            Some("VENDOR-1234".to_string())
+        );
+        let lore = SystemLore {
+            serial_number: Some("OVERRIDE-1234".to_string()),
+            ..lore
+        };
+        assert_eq!(
+            SerialNumberReaderImpl::from(&lore).read_serial_number(),
+            Some("OVERRIDE-1234".to_string())
+        );
+    }
+}
@@ -0,0 +1,591 @@
+use openssl::asn1::Asn1Time;
+use openssl::bn::{BigNum, MsbOption};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{
+    AuthorityKeyIdentifier, BasicConstraints, ExtendedKeyUsage, KeyUsage, SubjectAlternativeName,
+    SubjectKeyIdentifier,
+};
+use openssl::x509::{
+    X509, X509Builder, X509Extension, X509Name, X509NameBuilder, X509NameRef, X509v3Context,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[derive(thiserror::Error, Debug)]
+pub enum CertificateError {
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(String),
+    #[error("Invalid private key: {0}")]
+    InvalidPrivateKey(String),
+    #[error("The private key does not belong to the certificate")]
+    KeyMismatch,
+    #[error("Invalid host name {0:?}")]
+    InvalidHostName(String),
+    #[error(transparent)]
+    OpenSsl(#[from] ErrorStack),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// PEM encoded certificate chain together with the PEM encoded private key of the first
+/// certificate
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertifiedKey {
+    pub certificate: String,
+    pub private_key: String,
+}
+
+impl Debug for CertifiedKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CertifiedKey")
+            .field("certificate", &self.certificate)
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl CertifiedKey {
+    /// Parses the given certificate chain and private key and checks that they belong together
+    pub fn new(certificate: String, private_key: String) -> Result<Self, CertificateError> {
+        let certified_key = Self {
+            certificate,
+            private_key,
+        };
+        let leaf = certified_key.leaf_certificate()?;
+        let key = PKey::private_key_from_pem(certified_key.private_key.as_bytes())
+            .map_err(|e| CertificateError::InvalidPrivateKey(e.to_string()))?;
+        if !leaf.public_key()?.public_eq(&key) {
+            return Err(CertificateError::KeyMismatch);
+        }
+        Ok(certified_key)
+    }
+
+    /// Generates a new key and a self-signed certificate valid for `validity_days` for all given
+    /// host names, which may also be ip addresses. The first host name is used as common name.
+    pub fn generate_self_signed(
+        host_names: &[String],
+        validity_days: u32,
+    ) -> Result<Self, CertificateError> {
+        let key = generate_private_key()?;
+        let subject = subject_name(host_names)?;
+        let mut builder = certificate_builder(&subject, &key, validity_days)?;
+        builder.set_issuer_name(&subject)?;
+        builder.append_extension(BasicConstraints::new().critical().build()?)?;
+        builder.append_extension(KeyUsage::new().critical().digital_signature().build()?)?;
+        builder.append_extension(ExtendedKeyUsage::new().server_auth().build()?)?;
+        let subject_alternative_name =
+            subject_alternative_name(host_names, &builder.x509v3_context(None, None))?;
+        builder.append_extension(subject_alternative_name)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+        Ok(Self {
+            certificate: String::from_utf8_lossy(&builder.build().to_pem()?).into_owned(),
+            private_key: String::from_utf8_lossy(&key.private_key_to_pem_pkcs8()?).into_owned(),
+        })
+    }
+
+    /// Generates a new key and a self-signed certificate authority valid for `validity_days`
+    pub fn generate_certificate_authority(
+        common_name: &str,
+        validity_days: u32,
+    ) -> Result<Self, CertificateError> {
+        let key = generate_private_key()?;
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_nid(Nid::COMMONNAME, common_name)?;
+        let subject = name.build();
+        let mut builder = certificate_builder(&subject, &key, validity_days)?;
+        builder.set_issuer_name(&subject)?;
+        builder.append_extension(BasicConstraints::new().critical().ca().pathlen(0).build()?)?;
+        builder.append_extension(
+            KeyUsage::new()
+                .critical()
+                .key_cert_sign()
+                .crl_sign()
+                .build()?,
+        )?;
+        let subject_key_identifier =
+            SubjectKeyIdentifier::new().build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(subject_key_identifier)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+        Ok(Self {
+            certificate: String::from_utf8_lossy(&builder.build().to_pem()?).into_owned(),
+            private_key: String::from_utf8_lossy(&key.private_key_to_pem_pkcs8()?).into_owned(),
+        })
+    }
+
+    /// Generates a new key and a server certificate valid for `validity_days` for all given host
+    /// names, which may also be ip addresses. The certificate is signed by `issuer`, which has to
+    /// be a certificate authority.
+    pub fn generate_issued_by(
+        host_names: &[String],
+        validity_days: u32,
+        issuer: &CertifiedKey,
+    ) -> Result<Self, CertificateError> {
+        let issuer_certificate = issuer.leaf_certificate()?;
+        let issuer_key = PKey::private_key_from_pem(issuer.private_key.as_bytes())
+            .map_err(|e| CertificateError::InvalidPrivateKey(e.to_string()))?;
+        let key = generate_private_key()?;
+        let subject = subject_name(host_names)?;
+        let mut builder = certificate_builder(&subject, &key, validity_days)?;
+        builder.set_issuer_name(issuer_certificate.subject_name())?;
+        builder.append_extension(BasicConstraints::new().critical().build()?)?;
+        builder.append_extension(KeyUsage::new().critical().digital_signature().build()?)?;
+        builder.append_extension(ExtendedKeyUsage::new().server_auth().build()?)?;
+        let context = builder.x509v3_context(Some(&*issuer_certificate), None);
+        let authority_key_identifier = AuthorityKeyIdentifier::new()
+            .keyid(false)
+            .issuer(false)
+            .build(&context)?;
+        let subject_alternative_name = subject_alternative_name(host_names, &context)?;
+        builder.append_extension(authority_key_identifier)?;
+        builder.append_extension(subject_alternative_name)?;
+        builder.sign(&issuer_key, MessageDigest::sha256())?;
+        Ok(Self {
+            certificate: String::from_utf8_lossy(&builder.build().to_pem()?).into_owned(),
+            private_key: String::from_utf8_lossy(&key.private_key_to_pem_pkcs8()?).into_owned(),
+        })
+    }
+
+    pub fn leaf_certificate(&self) -> Result<X509, CertificateError> {
+        X509::stack_from_pem(self.certificate.as_bytes())
+            .map_err(|e| CertificateError::InvalidCertificate(e.to_string()))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| CertificateError::InvalidCertificate("No certificate found".to_string()))
+    }
+
+    /// Reads and validates the certificate chain and private key from the given files
+    pub fn read_from(
+        certificate_path: &Path,
+        private_key_path: &Path,
+    ) -> Result<Self, CertificateError> {
+        Self::new(
+            std::fs::read_to_string(certificate_path)?,
+            std::fs::read_to_string(private_key_path)?,
+        )
+    }
+
+    /// Writes the certificate chain and private key to the given files, the private key is only
+    /// accessible by the owner
+    pub fn write_to(
+        &self,
+        certificate_path: &Path,
+        private_key_path: &Path,
+    ) -> std::io::Result<()> {
+        self.write_with_key_mode(certificate_path, private_key_path, 0o600)
+    }
+
+    /// Writes the certificate chain and private key to the given files, the private key is
+    /// readable by everyone so that containers running as any user can use it. Access has to be
+    /// restricted by the permissions of a parent directory.
+    pub fn write_readable_to(
+        &self,
+        certificate_path: &Path,
+        private_key_path: &Path,
+    ) -> std::io::Result<()> {
+        self.write_with_key_mode(certificate_path, private_key_path, 0o644)
+    }
+
+    fn write_with_key_mode(
+        &self,
+        certificate_path: &Path,
+        private_key_path: &Path,
+        mode: u32,
+    ) -> std::io::Result<()> {
+        for path in [certificate_path, private_key_path] {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        std::fs::write(certificate_path, self.certificate.as_bytes())?;
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(mode)
+            .open(private_key_path)?;
+        file.set_permissions(std::fs::Permissions::from_mode(mode))?;
+        file.write_all(self.private_key.as_bytes())
+    }
+
+    /// Appends the leaf certificate of `previous` to the certificate chain unless it is expired,
+    /// used to keep publishing a replaced certificate authority until it expires
+    pub fn with_previous(self, previous: &CertifiedKey) -> Result<Self, CertificateError> {
+        let previous = previous.leaf_certificate()?;
+        if is_expired(&previous)? {
+            return Ok(self);
+        }
+        let mut certificate = self.certificate;
+        certificate.push_str(&String::from_utf8_lossy(&previous.to_pem()?));
+        Ok(Self {
+            certificate,
+            private_key: self.private_key,
+        })
+    }
+
+    /// Removes expired certificates following the leaf certificate, see [Self::with_previous].
+    /// Returns None if no certificate expired.
+    pub fn without_expired_previous(&self) -> Result<Option<Self>, CertificateError> {
+        let certificates = X509::stack_from_pem(self.certificate.as_bytes())
+            .map_err(|e| CertificateError::InvalidCertificate(e.to_string()))?;
+        let mut certificate = String::new();
+        let mut removed = false;
+        for (index, current) in certificates.iter().enumerate() {
+            if index > 0 && is_expired(current)? {
+                removed = true;
+                continue;
+            }
+            certificate.push_str(&String::from_utf8_lossy(&current.to_pem()?));
+        }
+        Ok(removed.then(|| Self {
+            certificate,
+            private_key: self.private_key.clone(),
+        }))
+    }
+
+    /// End of the validity of the leaf certificate, e.g. "Jan  1 00:00:00 2030 GMT"
+    pub fn not_after(&self) -> Result<String, CertificateError> {
+        Ok(self.leaf_certificate()?.not_after().to_string())
+    }
+
+    /// Checks if the leaf certificate is not valid anymore in `duration`
+    pub fn expires_within(&self, duration: Duration) -> Result<bool, CertificateError> {
+        // Largest time representable in a certificate, 9999-12-31T23:59:59Z
+        const MAX_UNIX_TIME: u64 = 253_402_300_799;
+        let limit = SystemTime::now()
+            .checked_add(duration)
+            .and_then(|limit| limit.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(MAX_UNIX_TIME, |limit| limit.as_secs().min(MAX_UNIX_TIME));
+        let limit =
+            Asn1Time::from_unix(libc::time_t::try_from(limit).unwrap_or(libc::time_t::MAX))?;
+        Ok(self.leaf_certificate()?.not_after().compare(&limit)? != Ordering::Greater)
+    }
+
+    /// Checks if the leaf certificate was signed by the leaf certificate of `issuer`
+    pub fn is_issued_by(&self, issuer: &CertifiedKey) -> Result<bool, CertificateError> {
+        let issuer_key = issuer.leaf_certificate()?.public_key()?;
+        Ok(self.leaf_certificate()?.verify(&issuer_key)?)
+    }
+
+    /// Dns names and ip addresses in the subject alternative names of the leaf certificate
+    pub fn host_names(&self) -> Result<Vec<String>, CertificateError> {
+        let Some(names) = self.leaf_certificate()?.subject_alt_names() else {
+            return Ok(Vec::new());
+        };
+        Ok(names
+            .iter()
+            .filter_map(|name| {
+                if let Some(dns_name) = name.dnsname() {
+                    return Some(dns_name.to_string());
+                }
+                let ip_address = match name.ipaddress()? {
+                    [a, b, c, d] => IpAddr::from(Ipv4Addr::new(*a, *b, *c, *d)),
+                    address => IpAddr::from(Ipv6Addr::from(<[u8; 16]>::try_from(address).ok()?)),
+                };
+                Some(ip_address.to_string())
+            })
+            .collect())
+    }
+}
+
+fn is_expired(certificate: &X509) -> Result<bool, ErrorStack> {
+    Ok(certificate
+        .not_after()
+        .compare(&Asn1Time::days_from_now(0)?)?
+        != Ordering::Greater)
+}
+
+/// Checks that `host_name` is a valid dns name as described in RFC 1123
+pub fn is_valid_host_name(host_name: &str) -> bool {
+    !host_name.is_empty()
+        && host_name.len() <= 253
+        && host_name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn generate_private_key() -> Result<PKey<Private>, ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    PKey::from_ec_key(EcKey::generate(&group)?)
+}
+
+fn subject_name(host_names: &[String]) -> Result<X509Name, CertificateError> {
+    let common_name = host_names
+        .first()
+        .ok_or_else(|| CertificateError::InvalidHostName(String::new()))?;
+    let mut name = X509NameBuilder::new()?;
+    name.append_entry_by_nid(Nid::COMMONNAME, common_name)?;
+    Ok(name.build())
+}
+
+fn certificate_builder(
+    subject: &X509NameRef,
+    key: &PKey<Private>,
+    validity_days: u32,
+) -> Result<X509Builder, ErrorStack> {
+    let mut serial_number = BigNum::new()?;
+    serial_number.rand(127, MsbOption::MAYBE_ZERO, false)?;
+    let mut builder = X509::builder()?;
+    builder.set_version(2)?;
+    builder.set_serial_number(&serial_number.to_asn1_integer()?)?;
+    builder.set_subject_name(subject)?;
+    builder.set_pubkey(key)?;
+    builder.set_not_before(&Asn1Time::days_from_now(0)?)?;
+    builder.set_not_after(&Asn1Time::days_from_now(validity_days)?)?;
+    Ok(builder)
+}
+
+fn subject_alternative_name(
+    host_names: &[String],
+    context: &X509v3Context<'_>,
+) -> Result<X509Extension, CertificateError> {
+    let mut subject_alternative_name = SubjectAlternativeName::new();
+    for host_name in host_names {
+        if host_name.parse::<IpAddr>().is_ok() {
+            subject_alternative_name.ip(host_name);
+        } else if is_valid_host_name(host_name) {
+            subject_alternative_name.dns(host_name);
+        } else {
+            return Err(CertificateError::InvalidHostName(host_name.clone()));
+        }
+    }
+    Ok(subject_alternative_name.build(context)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testdir::testdir;
+
+    #[test]
+    fn host_names() {
+        assert!(is_valid_host_name("flecs.local"));
+        assert!(is_valid_host_name("editor-1.example.com"));
+        assert!(is_valid_host_name("localhost"));
+        assert!(!is_valid_host_name(""));
+        assert!(!is_valid_host_name("-flecs.local"));
+        assert!(!is_valid_host_name("flecs..local"));
+        assert!(!is_valid_host_name("flecs.local; include /etc/passwd"));
+        assert!(!is_valid_host_name(&"a".repeat(64)));
+    }
+
+    #[test]
+    fn generate_self_signed_ok() {
+        let host_names = vec!["editor.flecs.local".to_string(), "192.168.0.10".to_string()];
+        let certified_key = CertifiedKey::generate_self_signed(&host_names, 10).unwrap();
+        let certified_key =
+            CertifiedKey::new(certified_key.certificate, certified_key.private_key).unwrap();
+        let certificate = certified_key.leaf_certificate().unwrap();
+        let alt_names: Vec<_> = certificate
+            .subject_alt_names()
+            .unwrap()
+            .iter()
+            .map(|name| {
+                name.dnsname()
+                    .map(str::to_string)
+                    .or_else(|| name.ipaddress().map(|ip| format!("{ip:?}")))
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(alt_names, ["editor.flecs.local", "[192, 168, 0, 10]"]);
+        let key = PKey::private_key_from_pem(certified_key.private_key.as_bytes()).unwrap();
+        assert!(certificate.verify(&key).unwrap());
+        assert!(certified_key.not_after().is_ok());
+    }
+
+    #[test]
+    fn generate_self_signed_invalid_host_name() {
+        assert!(matches!(
+            CertifiedKey::generate_self_signed(&["in valid".to_string()], 10),
+            Err(CertificateError::InvalidHostName(_))
+        ));
+        assert!(matches!(
+            CertifiedKey::generate_self_signed(&[], 10),
+            Err(CertificateError::InvalidHostName(_))
+        ));
+    }
+
+    #[test]
+    fn generate_issued_by_ok() {
+        let ca = CertifiedKey::generate_certificate_authority("FLECS device CA", 20).unwrap();
+        let host_names = vec![
+            "flecs-device".to_string(),
+            "192.168.0.10".to_string(),
+            "fd00::10".to_string(),
+        ];
+        let server = CertifiedKey::generate_issued_by(&host_names, 10, &ca).unwrap();
+        let server = CertifiedKey::new(server.certificate, server.private_key).unwrap();
+        assert!(server.is_issued_by(&ca).unwrap());
+        assert!(!ca.is_issued_by(&server).unwrap());
+        assert_eq!(server.host_names().unwrap(), host_names);
+        assert!(ca.host_names().unwrap().is_empty());
+        let certificate = server.leaf_certificate().unwrap();
+        assert_eq!(
+            certificate.issuer_name().to_der().unwrap(),
+            ca.leaf_certificate()
+                .unwrap()
+                .subject_name()
+                .to_der()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn generate_issued_by_other_ca() {
+        let ca = CertifiedKey::generate_certificate_authority("FLECS device CA", 20).unwrap();
+        let other_ca = CertifiedKey::generate_certificate_authority("FLECS device CA", 20).unwrap();
+        let server =
+            CertifiedKey::generate_issued_by(&["flecs-device".to_string()], 10, &ca).unwrap();
+        assert!(!server.is_issued_by(&other_ca).unwrap());
+    }
+
+    #[test]
+    fn expires_within() {
+        let certified_key =
+            CertifiedKey::generate_self_signed(&["flecs.local".to_string()], 10).unwrap();
+        assert!(
+            !certified_key
+                .expires_within(Duration::from_secs(9 * 24 * 60 * 60))
+                .unwrap()
+        );
+        assert!(
+            certified_key
+                .expires_within(Duration::from_secs(11 * 24 * 60 * 60))
+                .unwrap()
+        );
+        assert!(certified_key.expires_within(Duration::MAX).unwrap());
+    }
+
+    #[test]
+    fn certified_key_key_mismatch() {
+        let host_names = vec!["flecs.local".to_string()];
+        let first = CertifiedKey::generate_self_signed(&host_names, 10).unwrap();
+        let second = CertifiedKey::generate_self_signed(&host_names, 10).unwrap();
+        assert!(matches!(
+            CertifiedKey::new(first.certificate, second.private_key),
+            Err(CertificateError::KeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn certified_key_invalid() {
+        assert!(matches!(
+            CertifiedKey::new("no certificate".to_string(), "no key".to_string()),
+            Err(CertificateError::InvalidCertificate(_))
+        ));
+        let certified_key =
+            CertifiedKey::generate_self_signed(&["flecs.local".to_string()], 10).unwrap();
+        assert!(matches!(
+            CertifiedKey::new(certified_key.certificate, "no key".to_string()),
+            Err(CertificateError::InvalidPrivateKey(_))
+        ));
+    }
+
+    #[test]
+    fn certified_key_write_read() {
+        let path = testdir!();
+        let certificate_path = path.join("certs/editor.crt");
+        let private_key_path = path.join("certs/editor.key");
+        let certified_key =
+            CertifiedKey::generate_self_signed(&["flecs.local".to_string()], 10).unwrap();
+        certified_key
+            .write_to(&certificate_path, &private_key_path)
+            .unwrap();
+        assert_eq!(
+            std::fs::metadata(&private_key_path)
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o600
+        );
+        assert_eq!(
+            CertifiedKey::read_from(&certificate_path, &private_key_path).unwrap(),
+            certified_key
+        );
+    }
+
+    #[test]
+    fn certified_key_write_readable() {
+        let path = testdir!();
+        let certificate_path = path.join("tls.crt");
+        let private_key_path = path.join("tls.key");
+        let certified_key =
+            CertifiedKey::generate_self_signed(&["flecs.local".to_string()], 10).unwrap();
+        certified_key
+            .write_readable_to(&certificate_path, &private_key_path)
+            .unwrap();
+        assert_eq!(
+            std::fs::metadata(&private_key_path)
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o777,
+            0o644
+        );
+        assert_eq!(
+            CertifiedKey::read_from(&certificate_path, &private_key_path).unwrap(),
+            certified_key
+        );
+    }
+
+    #[test]
+    fn with_previous() {
+        let previous = CertifiedKey::generate_certificate_authority("FLECS device CA", 20).unwrap();
+        let ca = CertifiedKey::generate_certificate_authority("FLECS device CA", 20).unwrap();
+        let published = ca.clone().with_previous(&previous).unwrap();
+        let published = CertifiedKey::new(published.certificate, published.private_key).unwrap();
+        assert_eq!(
+            published.certificate,
+            format!("{}{}", ca.certificate, previous.certificate)
+        );
+        assert_eq!(published.without_expired_previous().unwrap(), None);
+        let server =
+            CertifiedKey::generate_issued_by(&["flecs-device".to_string()], 10, &published)
+                .unwrap();
+        assert!(server.is_issued_by(&ca).unwrap());
+    }
+
+    #[test]
+    fn with_previous_expired() {
+        let previous = CertifiedKey::generate_certificate_authority("FLECS device CA", 0).unwrap();
+        let ca = CertifiedKey::generate_certificate_authority("FLECS device CA", 20).unwrap();
+        assert_eq!(ca.clone().with_previous(&previous).unwrap(), ca);
+        let published = CertifiedKey {
+            certificate: format!("{}{}", ca.certificate, previous.certificate),
+            private_key: ca.private_key.clone(),
+        };
+        assert_eq!(published.without_expired_previous().unwrap(), Some(ca));
+    }
+
+    #[test]
+    fn certified_key_read_missing() {
+        let path = testdir!();
+        assert!(matches!(
+            CertifiedKey::read_from(&path.join("editor.crt"), &path.join("editor.key")),
+            Err(CertificateError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn certified_key_debug_redacts_private_key() {
+        let certified_key = CertifiedKey {
+            certificate: "certificate".to_string(),
+            private_key: "secret".to_string(),
+        };
+        assert!(!format!("{certified_key:?}").contains("secret"));
+    }
+}
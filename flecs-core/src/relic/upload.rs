@@ -0,0 +1,487 @@
+//! Upload targets files (e.g. export archives) are copied to so they leave the device
+use crate::relic::integrity::{encode_hex, sha256_file};
+use anyhow::Context;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Body, StatusCode, Url, header};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+#[async_trait]
+pub trait Uploader: Send + Sync {
+    /// Uploads the file at `src` as `name`, an existing file of the same name is replaced
+    async fn upload(&self, src: &Path, name: &str) -> anyhow::Result<()>;
+    /// Deletes the previously uploaded file `name`, deleting a file that does not exist succeeds
+    async fn delete(&self, name: &str) -> anyhow::Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UploadTarget {
+    Local(LocalTarget),
+    Http(HttpTarget),
+    S3(S3Target),
+}
+
+impl UploadTarget {
+    pub fn uploader(&self) -> &dyn Uploader {
+        match self {
+            Self::Local(target) => target,
+            Self::Http(target) => target,
+            Self::S3(target) => target,
+        }
+    }
+}
+
+/// Directory on the device, e.g. a mounted network share
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalTarget {
+    pub path: PathBuf,
+}
+
+/// WebDAV or any other server accepting HTTP PUT, files are stored as `{url}/{name}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpTarget {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// File containing the password for basic authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_path: Option<PathBuf>,
+}
+
+/// S3 compatible object storage using path style addressing, files are stored as
+/// `{endpoint}/{bucket}/{prefix}{name}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct S3Target {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    pub access_key_id: String,
+    /// File containing the secret access key
+    pub secret_access_key_path: PathBuf,
+}
+
+async fn read_secret(path: &Path) -> anyhow::Result<String> {
+    let secret = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Could not read secret from {}", path.display()))?;
+    Ok(secret.trim_end().to_string())
+}
+
+async fn file_body(src: &Path) -> anyhow::Result<(Body, u64)> {
+    let file = tokio::fs::File::open(src).await?;
+    let len = file.metadata().await?.len();
+    Ok((Body::from(file), len))
+}
+
+/// Appends the path segments to the url, `segments` are percent encoded as necessary
+fn join_url<'a>(base: &str, segments: impl IntoIterator<Item = &'a str>) -> anyhow::Result<Url> {
+    let mut url = Url::parse(base)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("{base} can not be used as base url"))?
+        .pop_if_empty()
+        .extend(segments);
+    Ok(url)
+}
+
+fn check_status(response: reqwest::Response, allowed: &[StatusCode]) -> anyhow::Result<()> {
+    let status = response.status();
+    if status.is_success() || allowed.contains(&status) {
+        Ok(())
+    } else {
+        anyhow::bail!("{} returned {status}", response.url())
+    }
+}
+
+#[async_trait]
+impl Uploader for LocalTarget {
+    async fn upload(&self, src: &Path, name: &str) -> anyhow::Result<()> {
+        tokio::fs::create_dir_all(&self.path).await?;
+        // Copy to a temporary file first so incomplete uploads are never visible under `name`
+        let partial = self.path.join(format!(".{name}.partial"));
+        tokio::fs::copy(src, &partial).await?;
+        tokio::fs::rename(&partial, self.path.join(name)).await?;
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> anyhow::Result<()> {
+        match tokio::fs::remove_file(self.path.join(name)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl HttpTarget {
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        name: &str,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let request = reqwest::Client::new().request(method, join_url(&self.url, [name])?);
+        Ok(match &self.username {
+            Some(username) => {
+                let password = match &self.password_path {
+                    Some(path) => Some(read_secret(path).await?),
+                    None => None,
+                };
+                request.basic_auth(username, password)
+            }
+            None => request,
+        })
+    }
+}
+
+#[async_trait]
+impl Uploader for HttpTarget {
+    async fn upload(&self, src: &Path, name: &str) -> anyhow::Result<()> {
+        let (body, len) = file_body(src).await?;
+        let response = self
+            .request(reqwest::Method::PUT, name)
+            .await?
+            .header(header::CONTENT_LENGTH, len)
+            .body(body)
+            .send()
+            .await?;
+        check_status(response, &[])
+    }
+
+    async fn delete(&self, name: &str) -> anyhow::Result<()> {
+        let response = self
+            .request(reqwest::Method::DELETE, name)
+            .await?
+            .send()
+            .await?;
+        check_status(response, &[StatusCode::NOT_FOUND])
+    }
+}
+
+const S3_SERVICE: &str = "s3";
+const S3_ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const S3_SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+/// Sha256 of an empty payload
+const EMPTY_PAYLOAD_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("Hmac accepts keys of any size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Key used to sign requests with AWS signature version 4
+fn signing_key(secret_access_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let key = hmac_sha256(format!("AWS4{secret_access_key}").as_bytes(), date);
+    let key = hmac_sha256(&key, region);
+    let key = hmac_sha256(&key, service);
+    hmac_sha256(&key, "aws4_request")
+}
+
+impl S3Target {
+    fn object_url(&self, name: &str) -> anyhow::Result<Url> {
+        let key = format!("{}{name}", self.prefix.as_deref().unwrap_or_default());
+        join_url(
+            &self.endpoint,
+            std::iter::once(self.bucket.as_str()).chain(key.split('/')),
+        )
+    }
+
+    /// Returns the headers authenticating the request according to AWS signature version 4
+    fn sign(
+        &self,
+        secret_access_key: &str,
+        method: &reqwest::Method,
+        url: &Url,
+        payload_hash: &str,
+        time: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<(&'static str, String)>> {
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            (None, _) => anyhow::bail!("{url} has no host"),
+        };
+        let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = time.format("%Y%m%d").to_string();
+        let canonical_request = format!(
+            "{method}\n{}\n{}\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{S3_SIGNED_HEADERS}\n{payload_hash}",
+            url.path(),
+            url.query().unwrap_or_default(),
+        );
+        let scope = format!("{date}/{}/{S3_SERVICE}/aws4_request", self.region);
+        let string_to_sign = format!(
+            "{S3_ALGORITHM}\n{amz_date}\n{scope}\n{}",
+            encode_hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signature = encode_hex(&hmac_sha256(
+            &signing_key(secret_access_key, &date, &self.region, S3_SERVICE),
+            &string_to_sign,
+        ));
+        Ok(vec![
+            ("x-amz-content-sha256", payload_hash.to_string()),
+            ("x-amz-date", amz_date),
+            (
+                "authorization",
+                format!(
+                    "{S3_ALGORITHM} Credential={}/{scope}, SignedHeaders={S3_SIGNED_HEADERS}, Signature={signature}",
+                    self.access_key_id
+                ),
+            ),
+        ])
+    }
+
+    async fn request(
+        &self,
+        method: reqwest::Method,
+        name: &str,
+        payload_hash: &str,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let secret_access_key = read_secret(&self.secret_access_key_path).await?;
+        let url = self.object_url(name)?;
+        let headers = self.sign(&secret_access_key, &method, &url, payload_hash, Utc::now())?;
+        let mut request = reqwest::Client::new().request(method, url);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        Ok(request)
+    }
+}
+
+#[async_trait]
+impl Uploader for S3Target {
+    async fn upload(&self, src: &Path, name: &str) -> anyhow::Result<()> {
+        let payload_hash = sha256_file(src).await?;
+        let (body, len) = file_body(src).await?;
+        let response = self
+            .request(reqwest::Method::PUT, name, &payload_hash)
+            .await?
+            .header(header::CONTENT_LENGTH, len)
+            .body(body)
+            .send()
+            .await?;
+        check_status(response, &[])
+    }
+
+    async fn delete(&self, name: &str) -> anyhow::Result<()> {
+        let response = self
+            .request(reqwest::Method::DELETE, name, EMPTY_PAYLOAD_HASH)
+            .await?
+            .send()
+            .await?;
+        check_status(response, &[StatusCode::NOT_FOUND])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testdir::testdir;
+
+    fn s3_target(endpoint: &str, secret_path: PathBuf) -> S3Target {
+        S3Target {
+            endpoint: endpoint.to_string(),
+            bucket: "backups".to_string(),
+            region: "us-east-1".to_string(),
+            prefix: Some("device/".to_string()),
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key_path: secret_path,
+        }
+    }
+
+    #[test]
+    fn deserialize_target() {
+        let target: UploadTarget =
+            serde_json::from_str(r#"{"type": "local", "path": "/mnt/backup"}"#).unwrap();
+        assert_eq!(
+            target,
+            UploadTarget::Local(LocalTarget {
+                path: PathBuf::from("/mnt/backup")
+            })
+        );
+        let target: UploadTarget = serde_json::from_str(
+            r#"{"type": "http", "url": "https://dav.example.com/backups/", "username": "flecs"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            target,
+            UploadTarget::Http(HttpTarget {
+                username: Some(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn join_url_ok() {
+        assert_eq!(
+            join_url("http://example.com/backups/", ["1234.tar"])
+                .unwrap()
+                .as_str(),
+            "http://example.com/backups/1234.tar"
+        );
+        assert_eq!(
+            join_url("http://example.com/backups", ["a b.tar"])
+                .unwrap()
+                .as_str(),
+            "http://example.com/backups/a%20b.tar"
+        );
+        assert!(join_url("no url", ["1234.tar"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn local_upload_delete() {
+        let path = testdir!();
+        let src = path.join("1234.tar");
+        std::fs::write(&src, b"archive").unwrap();
+        let target = LocalTarget {
+            path: path.join("target"),
+        };
+        target.upload(&src, "backup.tar").await.unwrap();
+        assert_eq!(
+            std::fs::read(path.join("target/backup.tar")).unwrap(),
+            b"archive"
+        );
+        target.delete("backup.tar").await.unwrap();
+        assert!(!path.join("target/backup.tar").exists());
+        target.delete("backup.tar").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn http_upload() {
+        let path = testdir!();
+        let src = path.join("1234.tar");
+        std::fs::write(&src, b"archive").unwrap();
+        std::fs::write(path.join("password"), b"secret\n").unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/dav/backup.tar")
+            // flecs:secret
+            .match_header("authorization", "Basic ZmxlY3M6c2VjcmV0")
+            .match_body("archive")
+            .with_status(201)
+            .create_async()
+            .await;
+        let target = HttpTarget {
+            url: format!("{}/dav/", server.url()),
+            username: Some("flecs".to_string()),
+            password_path: Some(path.join("password")),
+        };
+        target.upload(&src, "backup.tar").await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn http_upload_error() {
+        let path = testdir!();
+        let src = path.join("1234.tar");
+        std::fs::write(&src, b"archive").unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/backup.tar")
+            .with_status(507)
+            .create_async()
+            .await;
+        let target = HttpTarget {
+            url: server.url(),
+            username: None,
+            password_path: None,
+        };
+        assert!(target.upload(&src, "backup.tar").await.is_err());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn http_delete_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("DELETE", "/backup.tar")
+            .with_status(404)
+            .create_async()
+            .await;
+        let target = HttpTarget {
+            url: server.url(),
+            username: None,
+            password_path: None,
+        };
+        target.delete("backup.tar").await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn signing_key_aws_example() {
+        // Example from the AWS signature version 4 documentation
+        assert_eq!(
+            encode_hex(&signing_key(
+                "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+                "20120215",
+                "us-east-1",
+                "iam"
+            )),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn s3_object_url() {
+        let target = s3_target("http://minio.local:9000", PathBuf::new());
+        assert_eq!(
+            target.object_url("1234.tar").unwrap().as_str(),
+            "http://minio.local:9000/backups/device/1234.tar"
+        );
+    }
+
+    #[tokio::test]
+    async fn s3_upload() {
+        let path = testdir!();
+        let src = path.join("1234.tar");
+        std::fs::write(&src, b"archive").unwrap();
+        std::fs::write(
+            path.join("secret"),
+            b"wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+        )
+        .unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PUT", "/backups/device/backup.tar")
+            .match_header("x-amz-content-sha256", sha256_file(&src).await.unwrap().as_str())
+            .match_header(
+                "authorization",
+                mockito::Matcher::Regex(
+                    r"^AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/\d{8}/us-east-1/s3/aws4_request, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature=[0-9a-f]{64}$".to_string(),
+                ),
+            )
+            .match_body("archive")
+            .with_status(200)
+            .create_async()
+            .await;
+        let target = s3_target(&server.url(), path.join("secret"));
+        target.upload(&src, "backup.tar").await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn s3_delete() {
+        let path = testdir!();
+        std::fs::write(path.join("secret"), b"secret").unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("DELETE", "/backups/device/backup.tar")
+            .match_header("x-amz-content-sha256", EMPTY_PAYLOAD_HASH)
+            .with_status(204)
+            .create_async()
+            .await;
+        let target = s3_target(&server.url(), path.join("secret"));
+        target.delete("backup.tar").await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn s3_secret_missing() {
+        let path = testdir!();
+        let target = s3_target("http://127.0.0.1:1", path.join("secret"));
+        assert!(target.delete("backup.tar").await.is_err());
+    }
+}
@@ -9,6 +9,7 @@ use crate::relic::floxy::Floxy;
 use crate::sorcerer::{Sorcerer, spell};
 use crate::vault::pouch::{AppKey, Pouch};
 use crate::vault::{GrabbedPouches, Vault};
+use anyhow::Context;
 use async_trait::async_trait;
 use flecsd_axum_server::models::InstalledApp;
 use futures_util::TryFutureExt;
@@ -186,6 +187,9 @@ impl AppRaiser for AppraiserImpl {
         config: ConsoleClient,
     ) -> anyhow::Result<()> {
         let app_key = manifest.key().clone();
+        manifest
+            .validate_features()
+            .with_context(|| format!("Manifest of {app_key} is invalid"))?;
         let result = quest
             .lock()
             .await
@@ -287,6 +291,92 @@ impl AppRaiser for AppraiserImpl {
         self.install_app_from_manifest(quest, vault, manifest, config)
             .await
     }
+
+    async fn stage_app(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        app_key: AppKey,
+        config: ConsoleClient,
+    ) -> anyhow::Result<()> {
+        let manifest = quest
+            .lock()
+            .await
+            .create_sub_quest("Obtain manifest".to_string(), |_quest| {
+                download_manifest(vault.clone(), app_key.clone(), config.clone())
+            })
+            .await
+            .2;
+        let manifest = manifest.await?;
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest(format!("Verify manifest of {app_key}"), |_quest| {
+                verify_staged_manifest(vault.clone(), app_key.clone(), manifest.clone())
+            })
+            .await
+            .2;
+        result.await?;
+        self.install_app_from_manifest(quest.clone(), vault.clone(), manifest, config)
+            .await?;
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest(format!("Verify installation of {app_key}"), |quest| {
+                verify_installation(quest, vault, app_key)
+            })
+            .await
+            .2;
+        result.await
+    }
+}
+
+/// Checks that the manifest belongs to `app_key` and that instances of installed versions of the
+/// app can be updated to it
+async fn verify_staged_manifest(
+    vault: Arc<Vault>,
+    app_key: AppKey,
+    manifest: AppManifest,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        manifest.key() == &app_key,
+        "Received manifest for {} instead of {app_key}",
+        manifest.key()
+    );
+    let is_multi_image_app = matches!(manifest, AppManifest::Multi(_));
+    let grab = vault.reservation().reserve_manifest_pouch().grab().await;
+    let manifests = grab
+        .manifest_pouch
+        .as_ref()
+        .expect("Reservations should never fail");
+    for (key, installed) in manifests.gems() {
+        anyhow::ensure!(
+            key.name != app_key.name
+                || is_multi_image_app == matches!(installed, AppManifest::Multi(_)),
+            "Instances of {key} can not be updated to {app_key}, the kind of the app changed"
+        );
+    }
+    Ok(())
+}
+
+async fn verify_installation(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    app_key: AppKey,
+) -> anyhow::Result<()> {
+    vault
+        .reservation()
+        .reserve_app_pouch()
+        .grab()
+        .await
+        .app_pouch
+        .as_ref()
+        .expect("Reservation failed")
+        .gems()
+        .get(&app_key)
+        .ok_or_else(|| anyhow::anyhow!("App {app_key} was unexpectedly removed"))?
+        .verify_installation(quest)
+        .await
 }
 
 async fn download_manifest(
@@ -927,6 +1017,101 @@ pub mod tests {
         token_mock.assert();
     }
 
+    #[tokio::test]
+    async fn stage_app_ok() {
+        let key = AppKey {
+            name: NO_MANIFEST_APP_NAME.to_string(),
+            version: NO_MANIFEST_APP_VERSION.to_string(),
+        };
+        let (mut server, config) = crate::tests::create_test_server_and_config().await;
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_deployment_id()
+            .return_const("MockedDeployment".to_string());
+        let mut installed = false;
+        deployment
+            .expect_is_app_installed()
+            .times(2)
+            .returning(move |_, _| {
+                let result = installed;
+                installed = true;
+                Ok(result)
+            });
+        deployment
+            .expect_install_app()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = create_test_vault(HashMap::new(), HashMap::new(), Some(deployment));
+        let manifest_mock = manifest_mock_ok(&mut server, no_manifest(), &key).await;
+        let token_mock = token_mock_ok(&mut server).await;
+        AppraiserImpl::default()
+            .stage_app(Quest::new_synced("TestQuest"), vault, key, config)
+            .await
+            .unwrap();
+        manifest_mock.assert();
+        token_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn stage_app_not_installed() {
+        let key = AppKey {
+            name: NO_MANIFEST_APP_NAME.to_string(),
+            version: NO_MANIFEST_APP_VERSION.to_string(),
+        };
+        let (mut server, config) = crate::tests::create_test_server_and_config().await;
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_deployment_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_is_app_installed()
+            .times(2)
+            .returning(|_, _| Ok(false));
+        deployment
+            .expect_install_app()
+            .once()
+            .returning(|_, _, _| Ok(()));
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = create_test_vault(HashMap::new(), HashMap::new(), Some(deployment));
+        let manifest_mock = manifest_mock_ok(&mut server, no_manifest(), &key).await;
+        let token_mock = token_mock_ok(&mut server).await;
+        assert!(
+            AppraiserImpl::default()
+                .stage_app(Quest::new_synced("TestQuest"), vault, key, config)
+                .await
+                .is_err()
+        );
+        manifest_mock.assert();
+        token_mock.assert();
+    }
+
+    #[tokio::test]
+    async fn stage_app_wrong_manifest() {
+        let key = AppKey {
+            name: NO_MANIFEST_APP_NAME.to_string(),
+            version: NO_MANIFEST_APP_VERSION.to_string(),
+        };
+        let (mut server, config) = crate::tests::create_test_server_and_config().await;
+        let vault = create_empty_test_vault();
+        let manifest_mock = manifest_mock_ok(&mut server, editor_manifest(), &key).await;
+        let token_mock = token_mock_uncalled(&mut server).await;
+        assert!(
+            AppraiserImpl::default()
+                .stage_app(Quest::new_synced("TestQuest"), vault, key, config)
+                .await
+                .is_err()
+        );
+        manifest_mock.assert();
+        token_mock.assert();
+    }
+
     #[tokio::test]
     async fn test_sideload_token_error() {
         let manifest = no_manifest();
@@ -59,6 +59,17 @@ pub trait AppRaiser: Sorcerer {
         app_key: AppKey,
         config: ConsoleClient,
     ) -> Result<()>;
+
+    /// Installs the app and verifies that it is available on all of its deployments, so that
+    /// instances of other versions of the app can be updated without pulling any images.
+    /// Existing instances are not touched.
+    async fn stage_app(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        app_key: AppKey,
+        config: ConsoleClient,
+    ) -> Result<()>;
 }
 
 #[cfg(test)]
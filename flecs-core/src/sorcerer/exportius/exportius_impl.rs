@@ -3,8 +3,9 @@ use crate::lore::ExportLoreRef;
 use crate::quest::SyncQuest;
 use crate::relic::floxy::Floxy;
 use crate::sorcerer::Sorcerer;
+use crate::sorcerer::exportius::manifest::v3::NetworkKey;
 use crate::sorcerer::exportius::{
-    ExportAppError, ExportDeploymentError, ExportInstanceError, Exportius,
+    ExportAppError, ExportDeploymentError, ExportInstanceError, ExportNetworkError, Exportius,
 };
 use crate::vault::Vault;
 use crate::vault::pouch::AppKey;
@@ -60,4 +61,12 @@ impl Exportius for ExportiusImpl {
     ) -> Result<(), ExportDeploymentError> {
         crate::sorcerer::spell::flecsport::export_deployments(quest, vault, path).await
     }
+
+    async fn export_networks(
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        path: PathBuf,
+    ) -> Result<Vec<NetworkKey>, ExportNetworkError> {
+        crate::sorcerer::spell::flecsport::export_networks(quest, vault, path).await
+    }
 }
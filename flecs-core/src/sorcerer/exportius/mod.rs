@@ -1,13 +1,20 @@
 mod exportius_impl;
+pub mod schedule;
 use crate::forge::time::SystemTimeExt;
 use crate::jeweler::gem::instance::InstanceId;
-use crate::lore::ExportLoreRef;
+use crate::lore::{ExportLore, ExportLoreRef};
 use crate::quest::SyncQuest;
 use crate::relic::async_flecstract::archive_to_file;
+use crate::relic::delta::{create_delta, create_index};
+use crate::relic::encryption;
+use crate::relic::encryption::{Encryption, encrypt_file, read_passphrase};
 use crate::relic::floxy::Floxy;
+use crate::relic::integrity::{Signature, read_signing_key, sha256_directory};
 use crate::sorcerer::Sorcerer;
+use crate::sorcerer::exportius::manifest::v3::NetworkKey;
 use crate::sorcerer::spell::flecsport::{
-    ExportAppError, ExportDeploymentError, ExportInstanceError,
+    ExportAppError, ExportDeploymentError, ExportInstanceError, ExportNetworkError,
+    export_index_path, read_export_index,
 };
 use crate::vault::Vault;
 use crate::vault::pouch::{AppKey, Pouch};
@@ -15,7 +22,6 @@ use async_trait::async_trait;
 pub use exportius_impl::*;
 #[cfg(test)]
 use mockall::automock;
-use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::warn;
@@ -25,6 +31,12 @@ pub mod manifest {
     use serde::{Deserialize, Serialize};
     use std::str::FromStr;
 
+    pub const FILE_NAME: &str = "manifest.json";
+    /// Detached signature of the manifest file, see [crate::relic::integrity::Signature]
+    pub const SIGNATURE_FILE_NAME: &str = "manifest.sig";
+    /// Difference of an incremental export to its base, see [crate::relic::delta::Delta]
+    pub const DELTA_FILE_NAME: &str = "delta.json";
+
     #[derive(Serialize, Deserialize, Debug, Clone)]
     #[serde(tag = "_schemaVersion")]
     pub enum Manifest {
@@ -107,6 +119,8 @@ pub mod manifest {
     pub mod v3 {
         pub use super::v2::{Device, Version};
         use crate::jeweler::deployment::DeploymentId;
+        use crate::jeweler::network::NetworkId;
+        use crate::relic::integrity::Checksums;
         use crate::vault::pouch::AppKey;
         use crate::vault::pouch::instance::InstanceId;
         use serde::{Deserialize, Serialize};
@@ -119,6 +133,15 @@ pub mod manifest {
             pub contents: Contents,
             pub device: Device,
             pub version: Version,
+            /// Sha256 of every file in the export except the manifest and its signature, missing
+            /// in exports created by older versions
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub checksums: Option<Checksums>,
+            /// Id of the export this export is an increment of. The checksums cover the complete
+            /// content, files unchanged since the base are missing in the archive, see
+            /// [super::DELTA_FILE_NAME].
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            pub base: Option<String>,
         }
 
         #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -126,6 +149,17 @@ pub mod manifest {
             pub apps: Vec<AppKey>,
             pub instances: Vec<InstanceId>,
             pub deployments: Vec<DeploymentId>,
+            /// Custom networks of the deployments, missing in exports created by older versions
+            #[serde(default, skip_serializing_if = "Vec::is_empty")]
+            pub networks: Vec<NetworkKey>,
+        }
+
+        /// A network is stored in `networks/{deployment}/{network}.json` as
+        /// [crate::jeweler::network::NetworkConfig]
+        #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct NetworkKey {
+            pub deployment: DeploymentId,
+            pub network: NetworkId,
         }
     }
 }
@@ -138,10 +172,18 @@ pub enum CreateExportError {
     Instance(#[from] ExportInstanceError),
     #[error("Failed to export deployments: {0}")]
     Deployment(#[from] ExportDeploymentError),
+    #[error("Failed to export networks: {0}")]
+    Network(#[from] ExportNetworkError),
     #[error("Failed to create export manifest: {0}")]
     Manifest(String),
     #[error("Failed to archive export: {0}")]
     Archive(#[from] ArchiveError),
+    #[error("Failed to encrypt export: {0}")]
+    Encryption(String),
+    #[error("Base export {base} can not be used: {error}")]
+    Base { base: String, error: String },
+    #[error("Failed to index export: {0}")]
+    Index(String),
     #[error("I/O Error during export: {0}")]
     IO(#[from] std::io::Error),
     #[error("Failed to get system info: {0}")]
@@ -156,11 +198,61 @@ pub enum ArchiveError {
     Any { path: PathBuf, error: String },
 }
 
+/// Writes the manifest to `export_dir` and signs it with the key at `signing_key_path` if
+/// present
+async fn write_manifest(
+    export_dir: &Path,
+    manifest: &manifest::Manifest,
+    signing_key_path: Option<&Path>,
+) -> Result<(), CreateExportError> {
+    let manifest =
+        serde_json::to_vec_pretty(manifest).expect("Manifest should always be serializable");
+    if let Some(signing_key_path) = signing_key_path {
+        let key = read_signing_key(signing_key_path)
+            .await
+            .map_err(|e| CreateExportError::Manifest(e.to_string()))?;
+        let signature = Signature::create(&key, &manifest);
+        tokio::fs::write(
+            export_dir.join(manifest::SIGNATURE_FILE_NAME),
+            serde_json::to_vec_pretty(&signature).expect("Signature should always be serializable"),
+        )
+        .await?;
+    }
+    tokio::fs::write(export_dir.join(manifest::FILE_NAME), manifest).await?;
+    Ok(())
+}
+
+/// Returns the encryption configured for export archives, exports are not encrypted if neither a
+/// passphrase nor recipients are configured
+async fn export_encryption(lore: &ExportLore) -> Result<Option<Encryption>, CreateExportError> {
+    match (
+        &lore.encryption_passphrase_path,
+        lore.encryption_recipients.is_empty(),
+    ) {
+        (Some(_), false) => Err(CreateExportError::Encryption(
+            "Either an encryption passphrase or encryption recipients can be configured, not both"
+                .to_string(),
+        )),
+        (Some(passphrase_path), true) => read_passphrase(passphrase_path)
+            .await
+            .map(|passphrase| Some(Encryption::Passphrase(passphrase)))
+            .map_err(|e| CreateExportError::Encryption(e.to_string())),
+        (None, false) => Ok(Some(Encryption::Recipients(
+            lore.encryption_recipients.clone(),
+        ))),
+        (None, true) => Ok(None),
+    }
+}
+
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait Exportius: Sorcerer + 'static {
     /// Creates an export as a tar archive at the exports base path (default /var/lib/flecs/exports)
-    /// with the current time as the filename.
+    /// with the current time as the filename. If encryption is configured the archive is
+    /// encrypted with age and stored as {timestamp}.tar.age instead. The index of the content is
+    /// stored next to the archive as {timestamp}.index.json. If a `base` export is specified an
+    /// incremental export is created which omits all files and image layers unchanged since the
+    /// base, see [crate::relic::delta].
     async fn create_export_archive(
         &self,
         quest: SyncQuest,
@@ -169,16 +261,63 @@ pub trait Exportius: Sorcerer + 'static {
         lore: ExportLoreRef,
         apps: Vec<AppKey>,
         instances: Vec<InstanceId>,
+        base: Option<String>,
     ) -> Result<String, CreateExportError> {
+        let archive_encryption = export_encryption(lore.as_ref().as_ref()).await?;
+        let export_base_path = lore.as_ref().as_ref().base_path.clone();
+        let base_index =
+            match &base {
+                Some(base) => Some(read_export_index(&export_base_path, base).await.map_err(
+                    |e| CreateExportError::Base {
+                        base: base.clone(),
+                        error: e.to_string(),
+                    },
+                )?),
+                None => None,
+            };
         let path = quest
             .lock()
             .await
             .create_sub_quest("Create export".to_string(), |quest| {
-                Self::create_export(quest, vault, floxy, lore, apps, instances)
+                Self::create_export(quest, vault, floxy, lore, apps, instances, base.clone())
             })
             .await
             .2;
         let path = path.await?;
+        let index_result = quest
+            .lock()
+            .await
+            .create_sub_quest("Index export".to_string(), |_quest| {
+                let path = path.clone();
+                async move {
+                    let mut index =
+                        create_index(&path, &[manifest::FILE_NAME, manifest::SIGNATURE_FILE_NAME])
+                            .await
+                            .map_err(|e| CreateExportError::Index(e.to_string()))?;
+                    index.base = base;
+                    if let Some(base_index) = base_index {
+                        let delta = create_delta(&path, &base_index, &index)
+                            .await
+                            .map_err(|e| CreateExportError::Index(e.to_string()))?;
+                        tokio::fs::write(
+                            path.join(manifest::DELTA_FILE_NAME),
+                            serde_json::to_vec_pretty(&delta)
+                                .expect("Delta should always be serializable"),
+                        )
+                        .await?;
+                    }
+                    Ok::<_, CreateExportError>(index)
+                }
+            })
+            .await
+            .2;
+        let index = match index_result.await {
+            Ok(index) => index,
+            Err(e) => {
+                _ = tokio::fs::remove_dir_all(&path).await;
+                return Err(e);
+            }
+        };
         let mut archive_path = path.clone();
         archive_path.set_extension("tar");
         let result = archive_path
@@ -192,26 +331,57 @@ pub trait Exportius: Sorcerer + 'static {
             .lock()
             .await
             .create_sub_quest("Archive export".to_string(), |quest| {
-                Self::archive_export(quest, path.clone(), archive_path)
+                Self::archive_export(quest, path.clone(), archive_path.clone())
             })
             .await
             .2;
         let archive_result = archive_result.await;
         tokio::fs::remove_dir_all(path).await?;
         archive_result?;
+        if let Some(archive_encryption) = archive_encryption {
+            let mut encrypted_path = archive_path.clone().into_os_string();
+            encrypted_path.push(format!(".{}", encryption::EXTENSION));
+            let encrypted_path = PathBuf::from(encrypted_path);
+            let encrypt_result = quest
+                .lock()
+                .await
+                .create_sub_quest("Encrypt export".to_string(), |_quest| {
+                    encrypt_file(
+                        archive_path.clone(),
+                        encrypted_path.clone(),
+                        archive_encryption,
+                    )
+                })
+                .await
+                .2;
+            let encrypt_result = encrypt_result.await;
+            tokio::fs::remove_file(&archive_path).await?;
+            if let Err(e) = encrypt_result {
+                _ = tokio::fs::remove_file(&encrypted_path).await;
+                return Err(CreateExportError::Encryption(e.to_string()));
+            }
+        }
+        tokio::fs::write(
+            export_index_path(&export_base_path, &result),
+            serde_json::to_vec(&index).expect("Index should always be serializable"),
+        )
+        .await?;
         Ok(result)
     }
 
     /// Creates an export in a directory at the exports base path (default /var/lib/flecs/exports)
     /// with the current time as the directory name. The export consists of an export manifest and
-    /// the specified content. See [Exportius::export_content] for details.
+    /// the specified content. See [Exportius::export_content] for details. The manifest contains
+    /// the checksums of all exported files and is signed if a signing key is configured.
     /// Structure:
     ///     /var/lib/flecs/exports
     ///         /{timestamp}
     ///             /manifest.json
+    ///             /manifest.sig (optional)
     ///             /apps
     ///             /instances
     ///             /deployments
+    ///             /networks
     async fn create_export(
         quest: SyncQuest,
         vault: Arc<Vault>,
@@ -219,6 +389,7 @@ pub trait Exportius: Sorcerer + 'static {
         lore: ExportLoreRef,
         apps: Vec<AppKey>,
         instances: Vec<InstanceId>,
+        base: Option<String>,
     ) -> Result<PathBuf, CreateExportError> {
         let now = std::time::SystemTime::now();
         let export_dir = lore
@@ -268,7 +439,7 @@ pub trait Exportius: Sorcerer + 'static {
             }
         };
         let readable_time: chrono::DateTime<chrono::offset::Utc> = now.into();
-        let manifest = manifest::Manifest::V3(manifest::v3::Manifest {
+        let mut manifest = manifest::v3::Manifest {
             time: now,
             human_readable_time: Some(
                 readable_time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
@@ -277,6 +448,7 @@ pub trait Exportius: Sorcerer + 'static {
                 apps: apps.clone(),
                 instances: instances.clone(),
                 deployments,
+                networks: Vec::new(),
             },
             device: manifest::v3::Device {
                 sysinfo: crate::relic::system::info::try_create_system_info()
@@ -284,17 +456,10 @@ pub trait Exportius: Sorcerer + 'static {
                 hostname,
             },
             version: Default::default(),
-        });
-
-        if let Err(e) = tokio::fs::write(
-            export_dir.join("manifest.json"),
-            serde_json::to_vec_pretty(&manifest).expect("Manifest should always be serializable"),
-        )
-        .await
-        {
-            _ = tokio::fs::remove_dir_all(&export_dir).await;
-            return Err(e.into());
+            checksums: None,
+            base,
         };
+        let signing_key_path = lore.as_ref().as_ref().signing_key_path.clone();
 
         let result = quest
             .lock()
@@ -312,6 +477,38 @@ pub trait Exportius: Sorcerer + 'static {
             })
             .await
             .2;
+        match result.await {
+            Ok(networks) => manifest.contents.networks = networks,
+            Err(e) => {
+                _ = tokio::fs::remove_dir_all(&export_dir).await;
+                return Err(e);
+            }
+        };
+
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest("Write export manifest".to_string(), |_quest| {
+                let export_dir = export_dir.clone();
+                async move {
+                    manifest.checksums = Some(
+                        sha256_directory(
+                            &export_dir,
+                            &[manifest::FILE_NAME, manifest::SIGNATURE_FILE_NAME],
+                        )
+                        .await
+                        .map_err(|e| CreateExportError::Manifest(e.to_string()))?,
+                    );
+                    write_manifest(
+                        &export_dir,
+                        &manifest::Manifest::V3(manifest),
+                        signing_key_path.as_deref(),
+                    )
+                    .await
+                }
+            })
+            .await
+            .2;
         if let Err(e) = result.await {
             _ = tokio::fs::remove_dir_all(&export_dir).await;
             return Err(e);
@@ -320,13 +517,15 @@ pub trait Exportius: Sorcerer + 'static {
     }
 
     /// Exports the specified content to the given 'export_dir'. The content consists of apps,
-    /// instances and deployments taken from the 'vault'. See [Exportius::export_apps],
-    /// [Exportius::export_instances] and [Exportius::export_deployments] for details.
+    /// instances, deployments and networks taken from the 'vault'. See [Exportius::export_apps],
+    /// [Exportius::export_instances], [Exportius::export_deployments] and
+    /// [Exportius::export_networks] for details. Returns the exported networks.
     /// Structure:
     ///     export_dir
     ///         /apps
     ///         /instances
     ///         /deployments
+    ///         /networks
     async fn export_content(
         quest: SyncQuest,
         vault: Arc<Vault>,
@@ -335,7 +534,7 @@ pub trait Exportius: Sorcerer + 'static {
         apps: Vec<AppKey>,
         instances: Vec<InstanceId>,
         export_dir: PathBuf,
-    ) -> Result<(), CreateExportError> {
+    ) -> Result<Vec<NetworkKey>, CreateExportError> {
         let apps_result = quest
             .lock()
             .await
@@ -366,14 +565,28 @@ pub trait Exportius: Sorcerer + 'static {
             })
             .await
             .2;
-        let (apps_result, instances_result, deployments_result) =
-            futures::join!(apps_result, instances_result, deployments_result);
+        let networks_result = quest
+            .lock()
+            .await
+            .create_sub_quest(format!("Export networks to {export_dir:?}"), |quest| {
+                Self::export_networks(quest, vault.clone(), export_dir.join("networks"))
+            })
+            .await
+            .2;
+        let (apps_result, instances_result, deployments_result, networks_result) = futures::join!(
+            apps_result,
+            instances_result,
+            deployments_result,
+            networks_result
+        );
         apps_result?;
         instances_result?;
         deployments_result?;
-        Ok(())
+        Ok(networks_result?)
     }
 
+    /// Deletes the export and all incremental exports based on it as they can not be imported
+    /// without their base
     async fn delete_export(
         &self,
         export_dir: &Path,
@@ -395,11 +608,12 @@ pub trait Exportius: Sorcerer + 'static {
         let mut entries = tokio::fs::read_dir(export_dir).await?;
         while let Some(entry) = entries.next_entry().await? {
             if entry.metadata().await?.is_file() {
-                let path = entry.path();
-                if path.extension() == Some(OsStr::new("tar")) {
-                    if let Some(file_name) = path.file_stem() {
-                        exports.push(file_name.to_string_lossy().to_string());
-                    }
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if let Some(export_id) = file_name
+                    .strip_suffix(&format!(".tar.{}", encryption::EXTENSION))
+                    .or_else(|| file_name.strip_suffix(".tar"))
+                {
+                    exports.push(export_id.to_string());
                 }
             }
         }
@@ -440,7 +654,74 @@ pub trait Exportius: Sorcerer + 'static {
         vault: Arc<Vault>,
         path_buf: PathBuf,
     ) -> Result<(), ExportDeploymentError>;
+
+    async fn export_networks(
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        path_buf: PathBuf,
+    ) -> Result<Vec<NetworkKey>, ExportNetworkError>;
 }
 
 #[cfg(test)]
 impl Sorcerer for MockExportius {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore;
+    use crate::relic::var::test::MockVarReader;
+    use testdir::testdir;
+
+    #[tokio::test]
+    async fn export_encryption_none() {
+        let lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        assert!(export_encryption(&lore.export).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn export_encryption_passphrase() {
+        let path = testdir!();
+        std::fs::write(path.join("passphrase"), b"correct horse\n").unwrap();
+        let mut lore = lore::test_lore(path.clone(), &MockVarReader::new());
+        lore.export.encryption_passphrase_path = Some(path.join("passphrase"));
+        assert!(matches!(
+            export_encryption(&lore.export).await,
+            Ok(Some(Encryption::Passphrase(passphrase))) if passphrase == "correct horse"
+        ));
+    }
+
+    #[tokio::test]
+    async fn export_encryption_recipients() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.export.encryption_recipients = vec!["age1recipient".to_string()];
+        assert!(matches!(
+            export_encryption(&lore.export).await,
+            Ok(Some(Encryption::Recipients(recipients))) if recipients == ["age1recipient"]
+        ));
+    }
+
+    #[tokio::test]
+    async fn export_encryption_ambiguous() {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.export.encryption_recipients = vec!["age1recipient".to_string()];
+        lore.export.encryption_passphrase_path = Some(PathBuf::from("/some/passphrase"));
+        assert!(matches!(
+            export_encryption(&lore.export).await,
+            Err(CreateExportError::Encryption(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_exports_encrypted() {
+        let path = testdir!();
+        let lore = lore::test_lore(path, &MockVarReader::new());
+        let export_dir = lore.export.base_path.clone();
+        std::fs::create_dir_all(&export_dir).unwrap();
+        std::fs::write(export_dir.join("1.tar"), b"").unwrap();
+        std::fs::write(export_dir.join("2.tar.age"), b"").unwrap();
+        std::fs::write(export_dir.join("3.json"), b"").unwrap();
+        let mut exports = ExportiusImpl.get_exports(Arc::new(lore)).await.unwrap();
+        exports.sort();
+        assert_eq!(exports, vec!["1".to_string(), "2".to_string()]);
+    }
+}
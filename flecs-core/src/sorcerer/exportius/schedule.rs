@@ -0,0 +1,444 @@
+//! Periodic exports configured in [crate::lore::ExportLore::schedules]
+use crate::enchantment::quest_master::QuestMaster;
+use crate::jeweler::gem::instance::InstanceId;
+use crate::lore::{ExportSchedule, Lore, default};
+use crate::quest::SyncQuest;
+use crate::relic::floxy::Floxy;
+use crate::relic::upload::UploadTarget;
+use crate::sorcerer::exportius::Exportius;
+use crate::vault::Vault;
+use crate::vault::pouch::{AppKey, Pouch};
+use chrono::Local;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// Exports created by a schedule, oldest first
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleHistory {
+    pub exports: Vec<ScheduledExport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledExport {
+    pub export_id: String,
+    /// Name of the file at the upload target, None if the export was not uploaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uploaded_as: Option<String>,
+}
+
+/// The history of a schedule is stored as {export base path}/schedules/{schedule name}.json
+pub fn history_path(export_dir: &Path, schedule_name: &str) -> PathBuf {
+    export_dir
+        .join(default::export::SCHEDULES_DIRECTORY_NAME)
+        .join(format!("{schedule_name}.json"))
+}
+
+pub async fn read_history(path: &Path) -> anyhow::Result<ScheduleHistory> {
+    match tokio::fs::read(path).await {
+        Ok(content) => Ok(serde_json::from_slice(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ScheduleHistory::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn write_history(path: &Path, history: &ScheduleHistory) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, serde_json::to_vec_pretty(history)?).await?;
+    Ok(())
+}
+
+/// Apps and instances exported by the schedule, everything present in the vault if not specified
+async fn schedule_content(
+    vault: &Vault,
+    schedule: &ExportSchedule,
+) -> (Vec<AppKey>, Vec<InstanceId>) {
+    let grab = vault
+        .reservation()
+        .reserve_app_pouch()
+        .reserve_instance_pouch()
+        .grab()
+        .await;
+    let apps = match &schedule.apps {
+        Some(apps) => apps.clone(),
+        None => grab
+            .app_pouch
+            .as_ref()
+            .expect("Vault reservations should never fail")
+            .gems()
+            .keys()
+            .cloned()
+            .collect(),
+    };
+    let instances = match &schedule.instances {
+        Some(instances) => instances.clone(),
+        None => grab
+            .instance_pouch
+            .as_ref()
+            .expect("Vault reservations should never fail")
+            .gems()
+            .keys()
+            .cloned()
+            .collect(),
+    };
+    (apps, instances)
+}
+
+/// Removes the oldest exports of the history until at most `retention` exports remain, the
+/// exports are deleted locally and from the upload target
+async fn apply_retention<E: Exportius>(
+    exportius: &E,
+    export_dir: &Path,
+    target: Option<&UploadTarget>,
+    history: &mut ScheduleHistory,
+    retention: usize,
+) {
+    let excess = history.exports.len().saturating_sub(retention.max(1));
+    for export in history.exports.drain(..excess) {
+        if let (Some(target), Some(name)) = (target, &export.uploaded_as) {
+            if let Err(e) = target.uploader().delete(name).await {
+                warn!("Could not delete expired export {name} from upload target: {e}");
+            }
+        }
+        match exportius
+            .delete_export(export_dir, export.export_id.clone())
+            .await
+        {
+            Ok(_) => info!("Deleted expired export {}", export.export_id),
+            Err(e) => warn!("Could not delete expired export {}: {e}", export.export_id),
+        }
+    }
+}
+
+/// Creates an export as specified by the schedule, uploads it to the target of the schedule and
+/// deletes exports exceeding the retention of the schedule
+pub async fn run_scheduled_export<E: Exportius>(
+    quest: SyncQuest,
+    exportius: Arc<E>,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+    lore: Arc<Lore>,
+    schedule: ExportSchedule,
+) -> anyhow::Result<String> {
+    let (apps, instances) = schedule_content(&vault, &schedule).await;
+    let export_dir = lore.export.base_path.clone();
+    let export_id = exportius
+        .create_export_archive(
+            quest.clone(),
+            vault,
+            floxy,
+            lore.clone(),
+            apps,
+            instances,
+            None,
+        )
+        .await?;
+    let history_path = history_path(&export_dir, &schedule.name);
+    let mut history = read_history(&history_path).await?;
+    let upload_result = match &schedule.target {
+        None => Ok(None),
+        Some(target) => {
+            let archive = exportius
+                .get_export(&export_dir, export_id.clone())
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Export {export_id} not found"))?;
+            let name = format!(
+                "{}-{}",
+                schedule.name,
+                archive.file_name().unwrap_or_default().to_string_lossy()
+            );
+            let target = target.clone();
+            let result = quest
+                .lock()
+                .await
+                .create_sub_quest(format!("Upload export as {name}"), |_quest| async move {
+                    target.uploader().upload(&archive, &name).await?;
+                    Ok::<_, anyhow::Error>(name)
+                })
+                .await
+                .2;
+            result.await.map(Some)
+        }
+    };
+    // Failed uploads are recorded as well so the local export is subject to the retention
+    history.exports.push(ScheduledExport {
+        export_id: export_id.clone(),
+        uploaded_as: upload_result.as_ref().ok().cloned().flatten(),
+    });
+    apply_retention(
+        exportius.as_ref(),
+        &export_dir,
+        schedule.target.as_ref(),
+        &mut history,
+        schedule.retention,
+    )
+    .await;
+    write_history(&history_path, &history).await?;
+    upload_result?;
+    Ok(export_id)
+}
+
+/// Runs all export schedules configured in [crate::lore::ExportLore::schedules], never returns
+/// unless no schedule is configured
+pub async fn run_export_schedules<E: Exportius>(
+    quest_master: QuestMaster,
+    exportius: Arc<E>,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+    lore: Arc<Lore>,
+) {
+    join_all(lore.export.schedules.iter().cloned().map(|schedule| {
+        run_export_schedule(
+            quest_master.clone(),
+            exportius.clone(),
+            vault.clone(),
+            floxy.clone(),
+            lore.clone(),
+            schedule,
+        )
+    }))
+    .await;
+}
+
+async fn run_export_schedule<E: Exportius>(
+    quest_master: QuestMaster,
+    exportius: Arc<E>,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+    lore: Arc<Lore>,
+    schedule: ExportSchedule,
+) {
+    // Runs of the same schedule are not executed concurrently as they share the history
+    let running = Arc::new(Mutex::new(()));
+    loop {
+        let Some(next) = schedule.cron.next_after(&Local::now()) else {
+            warn!(
+                "Export schedule {} ({}) never matches",
+                schedule.name, schedule.cron
+            );
+            return;
+        };
+        info!("Next export of schedule {} at {next}", schedule.name);
+        tokio::time::sleep((next - Local::now()).to_std().unwrap_or_default()).await;
+        let exportius = exportius.clone();
+        let vault = vault.clone();
+        let floxy = floxy.clone();
+        let lore = lore.clone();
+        let running = running.clone();
+        let run_schedule = schedule.clone();
+        if let Err(e) = quest_master
+            .lock()
+            .await
+            .schedule_quest(
+                format!("Scheduled export {}", schedule.name),
+                |quest| async move {
+                    let _running = running.lock().await;
+                    let export_id =
+                        run_scheduled_export(quest, exportius, vault, floxy, lore, run_schedule)
+                            .await?;
+                    info!("Created scheduled export {export_id}");
+                    Ok(())
+                },
+            )
+            .await
+        {
+            error!("Could not schedule export {}: {e}", schedule.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore;
+    use crate::quest::Quest;
+    use crate::relic::cron::CronSchedule;
+    use crate::relic::floxy::MockFloxy;
+    use crate::relic::upload::LocalTarget;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::exportius::MockExportius;
+    use crate::vault::pouch::instance::tests::MINIMAL_INSTANCE;
+    use crate::vault::tests::create_test_vault;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use testdir::testdir;
+
+    fn schedule(target: Option<UploadTarget>) -> ExportSchedule {
+        ExportSchedule {
+            name: "nightly".to_string(),
+            cron: CronSchedule::from_str("@daily").unwrap(),
+            apps: Some(Vec::new()),
+            instances: Some(vec![MINIMAL_INSTANCE]),
+            retention: 2,
+            target,
+        }
+    }
+
+    fn history(ids: &[&str]) -> ScheduleHistory {
+        ScheduleHistory {
+            exports: ids
+                .iter()
+                .map(|id| ScheduledExport {
+                    export_id: id.to_string(),
+                    uploaded_as: Some(format!("nightly-{id}.tar")),
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn read_history_missing() {
+        assert_eq!(
+            read_history(&testdir!().join("nightly.json"))
+                .await
+                .unwrap(),
+            ScheduleHistory::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn history_roundtrip() {
+        let path = history_path(&testdir!(), "nightly");
+        write_history(&path, &history(&["1", "2"])).await.unwrap();
+        assert_eq!(read_history(&path).await.unwrap(), history(&["1", "2"]));
+    }
+
+    #[tokio::test]
+    async fn schedule_content_all() {
+        let vault = create_test_vault(HashMap::new(), HashMap::new(), None);
+        let schedule = ExportSchedule {
+            apps: None,
+            instances: None,
+            ..schedule(None)
+        };
+        let (_, instances) = schedule_content(&vault, &schedule).await;
+        assert!(instances.contains(&MINIMAL_INSTANCE));
+        let (apps, instances) = schedule_content(&vault, &self::schedule(None)).await;
+        assert!(apps.is_empty());
+        assert_eq!(instances, vec![MINIMAL_INSTANCE]);
+    }
+
+    #[tokio::test]
+    async fn apply_retention_deletes_oldest() {
+        let path = testdir!();
+        let target_path = path.join("target");
+        std::fs::create_dir_all(&target_path).unwrap();
+        for id in ["1", "2", "3"] {
+            std::fs::write(target_path.join(format!("nightly-{id}.tar")), id).unwrap();
+        }
+        let target = UploadTarget::Local(LocalTarget {
+            path: target_path.clone(),
+        });
+        let mut exportius = MockExportius::new();
+        exportius
+            .expect_delete_export()
+            .withf(|_, id| id == "1")
+            .once()
+            .returning(|_, _| Ok(true));
+        let mut history = history(&["1", "2", "3"]);
+        apply_retention(&exportius, &path, Some(&target), &mut history, 2).await;
+        assert_eq!(history, self::history(&["2", "3"]));
+        assert!(!target_path.join("nightly-1.tar").exists());
+        assert!(target_path.join("nightly-2.tar").exists());
+    }
+
+    #[tokio::test]
+    async fn run_scheduled_export_upload() {
+        let path = testdir!();
+        let lore = Arc::new(lore::test_lore(path.clone(), &MockVarReader::new()));
+        let export_dir = lore.export.base_path.clone();
+        std::fs::create_dir_all(&export_dir).unwrap();
+        let archive = export_dir.join("3000.tar");
+        std::fs::write(&archive, b"archive").unwrap();
+        let history_path = history_path(&export_dir, "nightly");
+        write_history(&history_path, &history(&["1000", "2000"]))
+            .await
+            .unwrap();
+        let mut exportius = MockExportius::new();
+        exportius
+            .expect_create_export_archive()
+            .withf(|_, _, _, _, apps, instances, base| {
+                apps.is_empty() && instances == &[MINIMAL_INSTANCE] && base.is_none()
+            })
+            .once()
+            .returning(|_, _, _, _, _, _, _| Ok("3000".to_string()));
+        exportius
+            .expect_get_export()
+            .once()
+            .returning(move |_, _| Ok(Some(archive.clone())));
+        exportius
+            .expect_delete_export()
+            .withf(|_, id| id == "1000")
+            .once()
+            .returning(|_, _| Ok(true));
+        let target = UploadTarget::Local(LocalTarget {
+            path: path.join("target"),
+        });
+        let export_id = run_scheduled_export(
+            Quest::new_synced("Test"),
+            Arc::new(exportius),
+            create_test_vault(HashMap::new(), HashMap::new(), None),
+            Arc::new(MockFloxy::new()),
+            lore,
+            schedule(Some(target)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(export_id, "3000");
+        assert_eq!(
+            std::fs::read(path.join("target/nightly-3000.tar")).unwrap(),
+            b"archive"
+        );
+        assert_eq!(
+            read_history(&history_path).await.unwrap(),
+            history(&["2000", "3000"])
+        );
+    }
+
+    #[tokio::test]
+    async fn run_scheduled_export_upload_error() {
+        let path = testdir!();
+        let lore = Arc::new(lore::test_lore(path.clone(), &MockVarReader::new()));
+        let export_dir = lore.export.base_path.clone();
+        let mut exportius = MockExportius::new();
+        exportius
+            .expect_create_export_archive()
+            .once()
+            .returning(|_, _, _, _, _, _, _| Ok("3000".to_string()));
+        exportius
+            .expect_get_export()
+            .once()
+            .returning(|_, _| Ok(Some(PathBuf::from("/does/not/exist/3000.tar"))));
+        let target = UploadTarget::Local(LocalTarget {
+            path: path.join("target"),
+        });
+        assert!(
+            run_scheduled_export(
+                Quest::new_synced("Test"),
+                Arc::new(exportius),
+                create_test_vault(HashMap::new(), HashMap::new(), None),
+                Arc::new(MockFloxy::new()),
+                lore,
+                schedule(Some(target)),
+            )
+            .await
+            .is_err()
+        );
+        assert_eq!(
+            read_history(&history_path(&export_dir, "nightly"))
+                .await
+                .unwrap(),
+            ScheduleHistory {
+                exports: vec![ScheduledExport {
+                    export_id: "3000".to_string(),
+                    uploaded_as: None
+                }]
+            }
+        );
+    }
+}
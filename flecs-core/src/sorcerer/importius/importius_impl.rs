@@ -1,20 +1,38 @@
+use crate::forge::path::PathExtension;
 use crate::forge::time::SystemTimeExt;
 use crate::lore::Lore;
 use crate::quest::SyncQuest;
-use crate::relic::async_flecstract::{decompress_from_file, extract_from_file};
+use crate::relic::async_flecstract::{
+    DeferredEntry, EntryTarget, decompress_from_file, extract_deferring, extract_from_file,
+    stream_entries,
+};
+use crate::relic::delta::{Delta, apply_delta};
 use crate::relic::device::usb::UsbDeviceReader;
+use crate::relic::encryption;
+use crate::relic::encryption::{Decryption, is_encrypted};
 use crate::relic::floxy::Floxy;
-use crate::sorcerer::exportius::manifest::Manifest;
-use crate::sorcerer::importius::{ImportError, ImportPathInfo, Importius};
+use crate::relic::system::available_space;
+use crate::sorcerer::exportius::manifest;
+use crate::sorcerer::exportius::manifest::{Manifest, v3};
+use crate::sorcerer::importius::{
+    DeferredImportContent, ImportError, ImportInspection, ImportOptions, ImportPathInfo,
+    ImportPlan, Importius, InspectImportError,
+};
+use crate::sorcerer::spell::flimport::ImportDirectoryConfig;
 use crate::sorcerer::spell::instance::start_all_instances_as_desired;
 use crate::sorcerer::{Sorcerer, spell};
 use crate::vault::Vault;
 use async_trait::async_trait;
+use futures_util::FutureExt;
 use futures_util::future::BoxFuture;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tracing::warn;
 
+/// Maximum number of base exports an incremental import may depend on
+const MAX_INCREMENT_CHAIN_LENGTH: usize = 64;
+
 #[derive(Default)]
 pub struct ImportiusImpl;
 
@@ -30,17 +48,50 @@ impl Importius for ImportiusImpl {
         lore: Arc<Lore>,
         usb_device_reader: Arc<U>,
         mut path_info: ImportPathInfo,
-    ) -> Result<(), ImportError> {
+        options: ImportOptions,
+    ) -> Result<ImportPlan, ImportError> {
         let now = std::time::SystemTime::now();
         path_info.temp_path = path_info.temp_path.join(now.unix_millis().to_string());
         tokio::fs::create_dir_all(&path_info.temp_path).await?;
         let temp_path = path_info.temp_path.clone();
-        let result = import_archive(quest, vault, floxy, lore, usb_device_reader, path_info).await;
+        let decrypted_path = decrypted_archive_path(&path_info);
+        let bases_path = bases_path(&temp_path);
+        let result = import_archive(
+            quest,
+            vault,
+            floxy,
+            lore,
+            usb_device_reader,
+            path_info,
+            options,
+        )
+        .await;
         if let Err(e) = tokio::fs::remove_dir_all(&temp_path).await {
             warn!("Could not remove temporary import directory {temp_path:?}: {e}")
         }
+        match tokio::fs::remove_dir_all(&bases_path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Could not remove temporary base import directory {bases_path:?}: {e}")
+            }
+            _ => {}
+        }
+        match tokio::fs::remove_file(&decrypted_path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Could not remove decrypted import archive {decrypted_path:?}: {e}")
+            }
+            _ => {}
+        }
         result
     }
+
+    async fn inspect_archive(
+        &self,
+        lore: Arc<Lore>,
+        archive_path: PathBuf,
+        decryption: Decryption,
+    ) -> Result<ImportInspection, InspectImportError> {
+        spell::flimport::inspect_import_archive(&lore.import, &archive_path, decryption).await
+    }
 }
 
 async fn import_archive<U: UsbDeviceReader + 'static>(
@@ -50,20 +101,73 @@ async fn import_archive<U: UsbDeviceReader + 'static>(
     lore: Arc<Lore>,
     usb_device_reader: Arc<U>,
     path_info: ImportPathInfo,
-) -> Result<(), ImportError> {
-    extract_quest(&quest, &path_info.archive_path, &path_info.temp_path)
+    options: ImportOptions,
+) -> Result<ImportPlan, ImportError> {
+    let archive_path = if is_encrypted(&path_info.archive_path).await? {
+        let decrypted_path = decrypted_archive_path(&path_info);
+        decrypt_quest(
+            &quest,
+            lore.clone(),
+            &path_info.archive_path,
+            &decrypted_path,
+            path_info.decryption.clone(),
+        )
         .await
         .await?;
-    import(
+        decrypted_path
+    } else {
+        path_info.archive_path
+    };
+    let deferred = extract_metadata_quest(&quest, &archive_path, &path_info.temp_path)
+        .await
+        .await?;
+    let import_path = export_data_path(path_info.temp_path.clone()).await?;
+    let mut deferred =
+        deferred_import_content(archive_path, &path_info.temp_path, &import_path, deferred)?;
+    if requires_full_extraction(&import_path).await? {
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest("Extract import content", |_quest| {
+                extract_deferred(deferred, import_path.clone())
+            })
+            .await
+            .2;
+        result.await?;
+        deferred = DeferredImportContent::default();
+    }
+    let result = quest
+        .lock()
+        .await
+        .create_sub_quest("Reconstruct incremental import", |_quest| {
+            reconstruct_increment(
+                lore.clone(),
+                import_path.clone(),
+                bases_path(&path_info.temp_path),
+                Arc::new(path_info.base_archives),
+                path_info.decryption,
+                0,
+            )
+        })
+        .await
+        .2;
+    result.await.map_err(ImportError::Increment)?;
+    let dry_run = options.dry_run;
+    let plan = import(
         quest.clone(),
         vault.clone(),
         floxy.clone(),
         usb_device_reader,
         lore,
-        path_info.temp_path,
+        import_path,
+        deferred,
         path_info.base_path,
+        options,
     )
     .await?;
+    if dry_run {
+        return Ok(plan);
+    }
     let result = quest
         .lock()
         .await
@@ -73,37 +177,305 @@ async fn import_archive<U: UsbDeviceReader + 'static>(
         .await
         .2;
     result.await.map_err(ImportError::InstanceStart)?;
-    Ok(())
+    Ok(plan)
 }
 
-async fn extract_quest(
+/// The decrypted archive is placed next to the temporary import directory as the content of the
+/// import directory is inspected after extraction
+fn decrypted_archive_path(path_info: &ImportPathInfo) -> PathBuf {
+    decrypted_path(&path_info.archive_path, &path_info.temp_path)
+}
+
+/// Path with the name of `path` and the extension of the decrypted `archive_path`
+fn decrypted_path(archive_path: &Path, path: &Path) -> PathBuf {
+    let file_name = archive_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let file_name = file_name
+        .strip_suffix(&format!(".{}", encryption::EXTENSION))
+        .unwrap_or(&file_name);
+    if file_name.ends_with(".gz") {
+        path.with_extension("tar.gz")
+    } else {
+        path.with_extension("tar")
+    }
+}
+
+/// Base exports of incremental imports are extracted next to the temporary import directory
+fn bases_path(temp_path: &Path) -> PathBuf {
+    temp_path.with_extension("bases")
+}
+
+/// Export id of an archive named {export_id}.tar, {export_id}.tar.gz or {export_id}.tar.age
+fn archive_export_id(archive_path: &Path) -> Option<&str> {
+    let file_name = archive_path.file_name()?.to_str()?;
+    let file_name = file_name
+        .strip_suffix(&format!(".{}", encryption::EXTENSION))
+        .unwrap_or(file_name);
+    file_name
+        .strip_suffix(".tar")
+        .or_else(|| file_name.strip_suffix(".tar.gz"))
+}
+
+/// Searches the archive of the export with the given id in `base_archives` and in the exports of
+/// the device
+async fn find_base_archive(
+    lore: &Lore,
+    base_archives: &[PathBuf],
+    export_id: &str,
+) -> std::io::Result<Option<PathBuf>> {
+    if let Some(archive) = base_archives
+        .iter()
+        .find(|archive| archive_export_id(archive) == Some(export_id))
+    {
+        return Ok(Some(archive.clone()));
+    }
+    spell::flecsport::get_export(&lore.export.base_path, export_id.to_string()).await
+}
+
+/// Restores the content omitted from the incremental export in `data_path` from its chain of
+/// base exports, see [crate::relic::delta]. Nothing is done if the export is not incremental.
+fn reconstruct_increment(
+    lore: Arc<Lore>,
+    data_path: PathBuf,
+    bases_path: PathBuf,
+    base_archives: Arc<Vec<PathBuf>>,
+    decryption: Decryption,
+    depth: usize,
+) -> BoxFuture<'static, anyhow::Result<()>> {
+    async move {
+        let delta_path = data_path.join(manifest::DELTA_FILE_NAME);
+        if !tokio::fs::try_exists(&delta_path).await? {
+            return Ok(());
+        }
+        anyhow::ensure!(
+            depth < MAX_INCREMENT_CHAIN_LENGTH,
+            "Chain of incremental exports is longer than {MAX_INCREMENT_CHAIN_LENGTH}"
+        );
+        // The base is taken from the manifest, which has to be verified before it is used
+        let manifest = tokio::fs::read(data_path.join(manifest::FILE_NAME)).await?;
+        spell::flimport::verify_import_signature(&lore.import, &manifest, &data_path).await?;
+        let Manifest::V3(v3::Manifest {
+            base: Some(base), ..
+        }) = serde_json::from_slice(&manifest)?
+        else {
+            anyhow::bail!("Incremental export does not specify its base");
+        };
+        anyhow::ensure!(
+            spell::flecsport::is_export_id(&base),
+            "Invalid base export {base:?}"
+        );
+        let base_archive = find_base_archive(&lore, &base_archives, &base)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Base export {base} not found"))?;
+        let base_path = bases_path.join(&base);
+        let base_archive = if is_encrypted(&base_archive).await? {
+            let decrypted_path = decrypted_path(&base_archive, &base_path);
+            decrypt_archive(&lore, &base_archive, &decrypted_path, decryption.clone()).await?;
+            decrypted_path
+        } else {
+            base_archive
+        };
+        extract_archive(base_archive, base_path.clone()).await?;
+        let base_data_path = export_data_path(base_path.clone()).await?;
+        reconstruct_increment(
+            lore,
+            base_data_path.clone(),
+            bases_path,
+            base_archives,
+            decryption,
+            depth + 1,
+        )
+        .await?;
+        let delta: Delta = serde_json::from_slice(&tokio::fs::read(&delta_path).await?)?;
+        apply_delta(&data_path, &base_data_path, &delta).await?;
+        tokio::fs::remove_file(delta_path).await?;
+        tokio::fs::remove_dir_all(base_path).await?;
+        Ok(())
+    }
+    .boxed()
+}
+
+/// See [spell::flimport::decrypt_import_archive]
+async fn decrypt_archive(
+    lore: &Lore,
+    archive_path: &Path,
+    decrypted_path: &Path,
+    decryption: Decryption,
+) -> Result<(), ImportError> {
+    spell::flimport::decrypt_import_archive(&lore.import, archive_path, decrypted_path, decryption)
+        .await
+        .map_err(|error| ImportError::Decrypt {
+            import: archive_path.to_path_buf(),
+            error,
+        })
+}
+
+/// See [decrypt_archive]
+async fn decrypt_quest(
     quest: &SyncQuest,
+    lore: Arc<Lore>,
     archive_path: &Path,
-    temp_path: &Path,
+    decrypted_path: &Path,
+    decryption: Decryption,
 ) -> BoxFuture<'static, Result<(), ImportError>> {
+    let decrypt_closure = {
+        let archive_path = archive_path.to_path_buf();
+        let decrypted_path = decrypted_path.to_path_buf();
+        move |_quest: SyncQuest| async move {
+            decrypt_archive(&lore, &archive_path, &decrypted_path, decryption).await
+        }
+    };
+    quest
+        .lock()
+        .await
+        .create_sub_quest("Decrypt import archive", decrypt_closure)
+        .await
+        .2
+}
+
+async fn extract_archive(archive_path: PathBuf, dst: PathBuf) -> Result<(), ImportError> {
+    let result = if archive_path.extension() == Some("gz".as_ref()) {
+        decompress_from_file(archive_path.clone(), dst).await
+    } else {
+        extract_from_file(archive_path.clone(), dst).await
+    };
+    result.map_err(|error| ImportError::Extract {
+        import: archive_path,
+        error,
+    })
+}
+
+/// Images and volumes are the bulk of an import and are only streamed from the archive once the
+/// import is validated, see [DeferredImportContent]
+fn is_deferred(path: &Path) -> bool {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    file_name.ends_with(".tar") || file_name.ends_with(".tar.gz")
+}
+
+/// Extracts everything except the deferred entries of the archive, see [is_deferred]
+async fn extract_metadata_quest(
+    quest: &SyncQuest,
+    archive_path: &Path,
+    temp_path: &Path,
+) -> BoxFuture<'static, Result<Vec<DeferredEntry>, ImportError>> {
     let extract_closure = {
         let archive_path = archive_path.to_path_buf();
         let temp_path = temp_path.to_path_buf();
         move |_quest: SyncQuest| async move {
-            let result = if archive_path.extension() == Some("gz".as_ref()) {
-                decompress_from_file(archive_path.clone(), temp_path).await
-            } else {
-                extract_from_file(archive_path.clone(), temp_path).await
-            };
-            result.map_err(|error| ImportError::Extract {
-                import: archive_path,
-                error,
-            })
+            extract_deferring(&archive_path, &temp_path, is_deferred)
+                .await
+                .map_err(|error| ImportError::Extract {
+                    import: archive_path,
+                    error,
+                })
         }
     };
     quest
         .lock()
         .await
-        .create_sub_quest("Extract import archive", extract_closure)
+        .create_sub_quest("Extract import metadata", extract_closure)
         .await
         .2
 }
 
+/// Assigns the deferred entries extracted to `temp_path` to their path relative to `import_path`,
+/// entries outside of `import_path` are not part of the export and dropped. Entries whose path
+/// could leave `import_path`, e.g. 'export/../../etc/passwd', are rejected.
+fn deferred_import_content(
+    archive_path: PathBuf,
+    temp_path: &Path,
+    import_path: &Path,
+    deferred: Vec<DeferredEntry>,
+) -> Result<DeferredImportContent, ImportError> {
+    let mut entries = BTreeMap::new();
+    for entry in deferred {
+        let path = temp_path.join(&entry.path);
+        let Ok(relative_path) = path.strip_prefix(import_path) else {
+            continue;
+        };
+        if !relative_path.is_contained_relative() {
+            return Err(ImportError::Extract {
+                import: archive_path,
+                error: anyhow::anyhow!("Invalid entry path {:?}", entry.path),
+            });
+        }
+        let relative_path = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        entries.insert(relative_path, entry);
+    }
+    Ok(DeferredImportContent {
+        archive_path,
+        entries,
+    })
+}
+
+/// Legacy and incremental imports are processed on the extracted directory
+async fn requires_full_extraction(import_path: &Path) -> Result<bool, std::io::Error> {
+    if tokio::fs::try_exists(import_path.join(manifest::DELTA_FILE_NAME)).await? {
+        return Ok(true);
+    }
+    let manifest = match tokio::fs::read(import_path.join(manifest::FILE_NAME)).await {
+        Ok(manifest) => manifest,
+        // Reported when the manifest is read
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    Ok(matches!(
+        serde_json::from_slice(&manifest),
+        Ok(Manifest::V2(_))
+    ))
+}
+
+/// Extracts the deferred entries to `import_path` after checking that enough space is available
+async fn extract_deferred(
+    content: DeferredImportContent,
+    import_path: PathBuf,
+) -> Result<(), ImportError> {
+    let required = content.required_space();
+    let available = available_space(&import_path).map_err(ImportError::Stream)?;
+    if required > available {
+        return Err(ImportError::InsufficientSpace {
+            required,
+            available,
+        });
+    }
+    let targets = content
+        .entries
+        .into_iter()
+        .map(|(relative_path, entry)| {
+            (
+                entry.path,
+                EntryTarget::File(import_path.join(relative_path)),
+            )
+        })
+        .collect();
+    stream_entries(&content.archive_path, targets)
+        .await
+        .map_err(ImportError::Stream)?;
+    Ok(())
+}
+
+/// Export data is either in the root of the archive or there is exactly one directory containing
+/// the data
+async fn export_data_path(extracted_path: PathBuf) -> Result<PathBuf, std::io::Error> {
+    let mut entries = Vec::new();
+    let mut dir = tokio::fs::read_dir(&extracted_path).await?;
+    while let Some(entry) = dir.next_entry().await? {
+        entries.push(entry);
+    }
+    // Exactly one directory
+    if entries.len() == 1 && entries[0].file_type().await?.is_dir() {
+        Ok(entries[0].path())
+    } else {
+        Ok(extracted_path)
+    }
+}
+
 async fn import<U: UsbDeviceReader + 'static>(
     quest: SyncQuest,
     vault: Arc<Vault>,
@@ -111,32 +483,51 @@ async fn import<U: UsbDeviceReader + 'static>(
     usb_device_reader: Arc<U>,
     lore: Arc<Lore>,
     import_path: PathBuf,
+    deferred: DeferredImportContent,
     base_path: PathBuf,
-) -> Result<(), ImportError> {
-    // Export data is either in the root of the archive or there is exactly one directory containing the data
-    let import_path = {
-        let mut entries = Vec::new();
-        let mut dir = tokio::fs::read_dir(&import_path).await?;
-        while let Some(entry) = dir.next_entry().await? {
-            entries.push(entry);
-        }
-        // Exactly one directory
-        if entries.len() == 1 && entries[0].file_type().await?.is_dir() {
-            entries[0].path()
-        } else {
-            import_path
-        }
-    };
+    options: ImportOptions,
+) -> Result<ImportPlan, ImportError> {
     let manifest = quest
         .lock()
         .await
         .create_sub_quest(
             format!("Read import manifest from {import_path:?}"),
-            |quest| spell::flimport::read_import_manifest(quest, import_path.clone()),
+            |quest| {
+                spell::flimport::read_import_manifest(
+                    quest,
+                    lore.clone(),
+                    import_path.clone(),
+                    deferred.entries.keys().cloned().collect(),
+                )
+            },
         )
         .await
         .2;
     let manifest = manifest.await?;
+    let dry_run = options.dry_run;
+    let parent_adapters = options.parent_adapters.clone();
+    let plan = quest
+        .lock()
+        .await
+        .create_sub_quest("Plan import", |_quest| {
+            let vault = vault.clone();
+            let import_path = import_path.clone();
+            async move {
+                spell::flimport::plan_import(vault, &manifest, &import_path, &options)
+                    .await
+                    .map(|plan| (manifest, plan))
+            }
+        })
+        .await
+        .2;
+    let (manifest, plan) = plan.await?;
+    if dry_run {
+        return Ok(plan);
+    }
+    if !plan.incompatibilities.is_empty() {
+        return Err(ImportError::Incompatible(plan.incompatibilities));
+    }
+    let manifest = spell::flimport::restrict_to_plan(manifest, &plan);
     let stop_result = quest
         .lock()
         .await
@@ -145,15 +536,48 @@ async fn import<U: UsbDeviceReader + 'static>(
                 quest,
                 vault.clone(),
                 floxy.clone(),
-                manifest.instance_ids(),
+                plan.replace.instances.clone(),
             )
         })
         .await
         .2;
     stop_result.await.map_err(ImportError::InstanceStop)?;
+    let loaded_apps = match &manifest {
+        Manifest::V3(manifest) if !deferred.entries.is_empty() => {
+            let result = quest
+                .lock()
+                .await
+                .create_sub_quest("Stream import content", |quest| {
+                    let vault = vault.clone();
+                    let lore = lore.clone();
+                    let manifest = manifest.clone();
+                    let import_path = import_path.clone();
+                    async move {
+                        spell::flimport::stream_import_content(
+                            quest,
+                            vault,
+                            lore,
+                            &manifest,
+                            deferred,
+                            import_path,
+                        )
+                        .await
+                    }
+                })
+                .await
+                .2;
+            result.await?
+        }
+        _ => HashSet::new(),
+    };
     let import_closure = {
         let import_path = import_path.clone();
         let vault = vault.clone();
+        let config = ImportDirectoryConfig {
+            new_instance_ids: plan.new_instance_ids.clone(),
+            parent_adapters,
+            loaded_apps,
+        };
         |quest: SyncQuest| async move {
             match manifest {
                 Manifest::V2(manifest) => {
@@ -174,6 +598,7 @@ async fn import<U: UsbDeviceReader + 'static>(
                         vault,
                         lore,
                         manifest,
+                        config,
                         import_path,
                         base_path,
                     )
@@ -188,5 +613,368 @@ async fn import<U: UsbDeviceReader + 'static>(
         .create_sub_quest("Import", import_closure)
         .await
         .2;
-    result.await
+    result.await?;
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore;
+    use crate::quest::Quest;
+    use crate::relic::delta::{create_delta, create_index};
+    use crate::relic::encryption::{DecryptionError, Encryption, encrypt_file};
+    use crate::relic::var::test::MockVarReader;
+    use testdir::testdir;
+
+    fn path_info(archive_path: &str) -> ImportPathInfo {
+        ImportPathInfo {
+            archive_path: PathBuf::from(archive_path),
+            temp_path: PathBuf::from("/tmp/imports/1234"),
+            base_path: PathBuf::from("/var/lib/flecs"),
+            decryption: Decryption::default(),
+            base_archives: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn decrypted_archive_path_tar() {
+        assert_eq!(
+            decrypted_archive_path(&path_info("/uploads/1234.tar.age")),
+            PathBuf::from("/tmp/imports/1234.tar")
+        );
+    }
+
+    #[test]
+    fn archive_export_id_ok() {
+        assert_eq!(
+            archive_export_id(Path::new("/exports/1234.tar")),
+            Some("1234")
+        );
+        assert_eq!(
+            archive_export_id(Path::new("/exports/1234.tar.gz")),
+            Some("1234")
+        );
+        assert_eq!(
+            archive_export_id(Path::new("/exports/1234.tar.age")),
+            Some("1234")
+        );
+        assert_eq!(archive_export_id(Path::new("/exports/1234.zip")), None);
+    }
+
+    #[test]
+    fn is_deferred_ok() {
+        assert!(is_deferred(Path::new("apps/app_1.0.0/app_1.0.0.tar")));
+        assert!(is_deferred(Path::new("instances/1234/volumes/data.tar.gz")));
+        assert!(!is_deferred(Path::new("apps/app_1.0.0/app_1.0.0.json")));
+        assert!(!is_deferred(Path::new("manifest.json")));
+    }
+
+    #[test]
+    fn deferred_import_content_ok() {
+        let entry = |path: &str| DeferredEntry {
+            path: PathBuf::from(path),
+            size: 10,
+        };
+        let content = deferred_import_content(
+            PathBuf::from("/uploads/export.tar"),
+            Path::new("/tmp/imports/1234"),
+            Path::new("/tmp/imports/1234/export"),
+            vec![
+                entry("export/apps/app_1.0.0/app_1.0.0.tar"),
+                entry("./export/instances/1234/volumes/data.tar"),
+                entry("other.tar"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(content.archive_path, PathBuf::from("/uploads/export.tar"));
+        assert_eq!(
+            content.entries.keys().collect::<Vec<_>>(),
+            vec![
+                "apps/app_1.0.0/app_1.0.0.tar",
+                "instances/1234/volumes/data.tar"
+            ]
+        );
+        assert_eq!(content.required_space(), 20);
+    }
+
+    #[test]
+    fn deferred_import_content_traversal() {
+        let result = deferred_import_content(
+            PathBuf::from("/uploads/export.tar"),
+            Path::new("/tmp/imports/1234"),
+            Path::new("/tmp/imports/1234/export"),
+            vec![DeferredEntry {
+                path: PathBuf::from("export/../../../etc/x.tar"),
+                size: 10,
+            }],
+        );
+        assert!(matches!(result, Err(ImportError::Extract { .. })));
+    }
+
+    #[tokio::test]
+    async fn requires_full_extraction_ok() {
+        let path = testdir!();
+        assert!(!requires_full_extraction(&path).await.unwrap());
+        write(&path, manifest::FILE_NAME, &increment_manifest(None));
+        assert!(!requires_full_extraction(&path).await.unwrap());
+        let legacy_manifest = Manifest::V2(manifest::v2::Manifest {
+            time: String::new(),
+            contents: manifest::v2::Contents {
+                apps: Vec::new(),
+                instances: Vec::new(),
+            },
+            device: v3::Device {
+                sysinfo: crate::relic::system::info::try_create_system_info().unwrap(),
+                hostname: None,
+            },
+            version: Default::default(),
+        });
+        write(
+            &path,
+            manifest::FILE_NAME,
+            &serde_json::to_vec(&legacy_manifest).unwrap(),
+        );
+        assert!(requires_full_extraction(&path).await.unwrap());
+        write(&path, manifest::FILE_NAME, &increment_manifest(None));
+        write(&path, manifest::DELTA_FILE_NAME, b"{}");
+        assert!(requires_full_extraction(&path).await.unwrap());
+    }
+
+    #[test]
+    fn decrypted_archive_path_gz() {
+        assert_eq!(
+            decrypted_archive_path(&path_info("/uploads/export.tar.gz.age")),
+            PathBuf::from("/tmp/imports/1234.tar.gz")
+        );
+    }
+
+    #[tokio::test]
+    async fn decrypt_quest_configured_passphrase() {
+        let path = testdir!();
+        let archive_path = path.join("export.tar");
+        let encrypted_path = path.join("export.tar.age");
+        let decrypted_path = path.join("decrypted.tar");
+        std::fs::write(&archive_path, b"archive").unwrap();
+        std::fs::write(path.join("passphrase"), b"correct horse\n").unwrap();
+        encrypt_file(
+            archive_path,
+            encrypted_path.clone(),
+            Encryption::Passphrase("correct horse".to_string()),
+        )
+        .await
+        .unwrap();
+        let mut lore = lore::test_lore(path.clone(), &MockVarReader::new());
+        lore.import.decryption_passphrase_path = Some(path.join("passphrase"));
+        let quest = Quest::new_synced("TestQuest");
+        decrypt_quest(
+            &quest,
+            Arc::new(lore),
+            &encrypted_path,
+            &decrypted_path,
+            Decryption::default(),
+        )
+        .await
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(decrypted_path).unwrap(), b"archive");
+    }
+
+    #[tokio::test]
+    async fn decrypt_quest_no_key_material() {
+        let path = testdir!();
+        let archive_path = path.join("export.tar");
+        let encrypted_path = path.join("export.tar.age");
+        std::fs::write(&archive_path, b"archive").unwrap();
+        encrypt_file(
+            archive_path,
+            encrypted_path.clone(),
+            Encryption::Passphrase("correct horse".to_string()),
+        )
+        .await
+        .unwrap();
+        let lore = lore::test_lore(path.clone(), &MockVarReader::new());
+        let quest = Quest::new_synced("TestQuest");
+        assert!(matches!(
+            decrypt_quest(
+                &quest,
+                Arc::new(lore),
+                &encrypted_path,
+                &path.join("decrypted.tar"),
+                Decryption::default(),
+            )
+            .await
+            .await,
+            Err(ImportError::Decrypt {
+                error: DecryptionError::NoKeyMaterial,
+                ..
+            })
+        ));
+    }
+
+    fn write(root: &Path, path: &str, content: &[u8]) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn increment_manifest(base: Option<&str>) -> Vec<u8> {
+        serde_json::to_vec(&Manifest::V3(v3::Manifest {
+            time: std::time::SystemTime::now(),
+            human_readable_time: None,
+            contents: v3::Contents {
+                apps: Vec::new(),
+                instances: Vec::new(),
+                deployments: Vec::new(),
+                networks: Vec::new(),
+            },
+            device: v3::Device {
+                sysinfo: crate::relic::system::info::try_create_system_info().unwrap(),
+                hostname: None,
+            },
+            version: Default::default(),
+            checksums: None,
+            base: base.map(str::to_string),
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reconstruct_increment_not_incremental() {
+        let path = testdir!();
+        let lore = Arc::new(lore::test_lore(path.clone(), &MockVarReader::new()));
+        write(&path, "export/file", b"content");
+        reconstruct_increment(
+            lore,
+            path.join("export"),
+            path.join("bases"),
+            Arc::new(Vec::new()),
+            Decryption::default(),
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(std::fs::read(path.join("export/file")).unwrap(), b"content");
+    }
+
+    #[tokio::test]
+    async fn reconstruct_increment_ok() {
+        let path = testdir!();
+        let lore = Arc::new(lore::test_lore(path.clone(), &MockVarReader::new()));
+        let base = path.join("base").join("1000");
+        let increment = path.join("increment");
+        write(&base, manifest::FILE_NAME, &increment_manifest(None));
+        write(&base, "instances/1/volumes/data/file", b"unchanged");
+        write(&base, "instances/1/instance.json", b"{}");
+        write(
+            &increment,
+            manifest::FILE_NAME,
+            &increment_manifest(Some("1000")),
+        );
+        write(&increment, "instances/1/volumes/data/file", b"unchanged");
+        write(
+            &increment,
+            "instances/1/instance.json",
+            b"{\"changed\":true}",
+        );
+        let base_index = create_index(&base, &[manifest::FILE_NAME]).await.unwrap();
+        let index = create_index(&increment, &[manifest::FILE_NAME])
+            .await
+            .unwrap();
+        let delta = create_delta(&increment, &base_index, &index).await.unwrap();
+        write(
+            &increment,
+            manifest::DELTA_FILE_NAME,
+            &serde_json::to_vec(&delta).unwrap(),
+        );
+        assert!(!increment.join("instances/1/volumes/data/file").exists());
+        let base_archive = path.join("uploads").join("1000.tar");
+        std::fs::create_dir_all(base_archive.parent().unwrap()).unwrap();
+        crate::relic::async_flecstract::archive_to_file(&base, &base_archive, false)
+            .await
+            .unwrap();
+        reconstruct_increment(
+            lore,
+            increment.clone(),
+            path.join("bases"),
+            Arc::new(vec![base_archive]),
+            Decryption::default(),
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            std::fs::read(increment.join("instances/1/volumes/data/file")).unwrap(),
+            b"unchanged"
+        );
+        assert_eq!(
+            std::fs::read(increment.join("instances/1/instance.json")).unwrap(),
+            b"{\"changed\":true}"
+        );
+        assert!(!increment.join(manifest::DELTA_FILE_NAME).exists());
+        assert!(!path.join("bases").join("1000").exists());
+    }
+
+    #[tokio::test]
+    async fn reconstruct_increment_invalid_base() {
+        let path = testdir!();
+        let lore = Arc::new(lore::test_lore(path.clone(), &MockVarReader::new()));
+        let increment = path.join("import").join("increment");
+        write(&path, "outside/file", b"content");
+        write(
+            &increment,
+            manifest::FILE_NAME,
+            &increment_manifest(Some("../../outside")),
+        );
+        write(
+            &increment,
+            manifest::DELTA_FILE_NAME,
+            b"{\"unchanged\":[],\"assembled\":{}}",
+        );
+        assert!(
+            reconstruct_increment(
+                lore,
+                increment,
+                path.join("import").join("bases"),
+                Arc::new(Vec::new()),
+                Decryption::default(),
+                0,
+            )
+            .await
+            .is_err()
+        );
+        assert_eq!(
+            std::fs::read(path.join("outside/file")).unwrap(),
+            b"content"
+        );
+    }
+
+    #[tokio::test]
+    async fn reconstruct_increment_base_missing() {
+        let path = testdir!();
+        let lore = Arc::new(lore::test_lore(path.clone(), &MockVarReader::new()));
+        let increment = path.join("increment");
+        write(
+            &increment,
+            manifest::FILE_NAME,
+            &increment_manifest(Some("1000")),
+        );
+        write(
+            &increment,
+            manifest::DELTA_FILE_NAME,
+            b"{\"unchanged\":[],\"assembled\":{}}",
+        );
+        assert!(
+            reconstruct_increment(
+                lore,
+                increment,
+                path.join("bases"),
+                Arc::new(Vec::new()),
+                Decryption::default(),
+                0,
+            )
+            .await
+            .is_err()
+        );
+    }
 }
@@ -1,21 +1,31 @@
 mod importius_impl;
 
-use crate::jeweler::gem::instance::CreateInstanceError;
+use crate::jeweler::deployment::DeploymentId;
 use crate::jeweler::gem::instance::docker::TransferIpError;
+use crate::jeweler::gem::instance::{CreateInstanceError, InstanceId};
+use crate::jeweler::network::CreateNetworkError;
 use crate::lore::Lore;
 use crate::quest::SyncQuest;
+use crate::relic::async_flecstract::DeferredEntry;
 use crate::relic::device::usb::UsbDeviceReader;
+use crate::relic::encryption::{Decryption, DecryptionError};
 use crate::relic::floxy::Floxy;
+use crate::relic::integrity::{ChecksumError, SignatureError};
 use crate::sorcerer::Sorcerer;
+use crate::sorcerer::exportius::manifest::v2::Device;
 use crate::vault::Vault;
 use crate::vault::pouch::AppKey;
 use async_trait::async_trait;
 pub use importius_impl::*;
 #[cfg(test)]
 use mockall::automock;
+use serde::{Deserialize, Serialize};
+use serde_with::{DisplayFromStr, serde_as};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::oneshot::error::RecvError;
+use utoipa::ToSchema;
 
 #[derive(thiserror::Error, Debug)]
 pub enum ReadImportManifestError {
@@ -27,11 +37,14 @@ pub enum ReadImportManifestError {
     IO(#[from] std::io::Error),
     #[error("Error during deserialization: {0}")]
     Ser(#[from] serde_json::Error),
-    #[error("Import has different architecture than device ({device_arch}): {import_arch}")]
-    ArchitectureMismatch {
-        device_arch: String,
-        import_arch: String,
-    },
+    #[error("Import manifest is not signed but a signature is required")]
+    MissingSignature,
+    #[error("Import manifest contains no checksums but a signature is required")]
+    MissingChecksums,
+    #[error("Invalid signature of import manifest: {0}")]
+    Signature(#[from] SignatureError),
+    #[error("Integrity check of import failed: {0}")]
+    Checksum(#[from] ChecksumError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -44,6 +57,25 @@ pub enum ImportDeploymentError {
     Ser(#[from] serde_json::Error),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ImportNetworkError {
+    #[error("Failed to create network {network} in deployment {deployment}: {error}")]
+    Create {
+        deployment: DeploymentId,
+        network: String,
+        error: CreateNetworkError,
+    },
+    #[error("Invalid network {network} of deployment {deployment}")]
+    InvalidKey {
+        deployment: DeploymentId,
+        network: String,
+    },
+    #[error("IO error during import: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Error during deserialization: {0}")]
+    Ser(#[from] serde_json::Error),
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum ImportAppError {
     #[error(transparent)]
@@ -86,14 +118,29 @@ pub enum ImportInstanceError {
 
 #[derive(thiserror::Error, Debug)]
 pub enum ImportError {
+    #[error("Failed to decrypt import {import:?}: {error}")]
+    Decrypt {
+        import: PathBuf,
+        error: DecryptionError,
+    },
     #[error("Failed to extract import {import:?}: {error}")]
     Extract {
         import: PathBuf,
         error: anyhow::Error,
     },
+    #[error("Failed to reconstruct incremental import: {0}")]
+    Increment(anyhow::Error),
+    #[error(
+        "Not enough free space for import: {required} bytes required, {available} bytes available"
+    )]
+    InsufficientSpace { required: u64, available: u64 },
+    #[error("Failed to stream import content: {0}")]
+    Stream(anyhow::Error),
     #[error(transparent)]
     Deployment(#[from] ImportDeploymentError),
     #[error(transparent)]
+    Network(#[from] ImportNetworkError),
+    #[error(transparent)]
     Manifest(#[from] ImportManifestError),
     #[error(transparent)]
     ImportManifest(#[from] ReadImportManifestError),
@@ -101,6 +148,12 @@ pub enum ImportError {
     App(#[from] ImportAppError),
     #[error(transparent)]
     Instance(#[from] ImportInstanceError),
+    #[error("Invalid import selection: {0}")]
+    InvalidSelection(String),
+    #[error("Import is incompatible with this device: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    Incompatible(Vec<ImportIncompatibility>),
+    #[error("Failed to plan import: {0}")]
+    Plan(anyhow::Error),
     #[error("Internal logic error {0}")]
     Logic(&'static str),
     #[error("Failed to stop instances before import")]
@@ -113,11 +166,193 @@ pub enum ImportError {
     RecvError(#[from] RecvError),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum InspectImportError {
+    #[error("Failed to decrypt import {import:?}: {error}")]
+    Decrypt {
+        import: PathBuf,
+        error: DecryptionError,
+    },
+    #[error("Failed to read import {import:?}: {error}")]
+    Read {
+        import: PathBuf,
+        error: anyhow::Error,
+    },
+    #[error("Import contains no manifest")]
+    MissingManifest,
+    #[error("Error reading manifest: {0}")]
+    Ser(#[from] serde_json::Error),
+    #[error("IO error during inspection: {0}")]
+    IO(#[from] std::io::Error),
+}
+
 #[derive(Debug, Clone)]
 pub struct ImportPathInfo {
     pub archive_path: PathBuf,
     pub temp_path: PathBuf,
     pub base_path: PathBuf,
+    /// Key material supplied with the import, used in addition to the configured key material
+    /// if the archive is encrypted
+    pub decryption: Decryption,
+    /// Archives which may contain the base exports if the import is incremental, the exports of
+    /// the device are searched as well
+    pub base_archives: Vec<PathBuf>,
+}
+
+/// How parts of an import are handled which already exist on the device
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Existing apps, instances and deployments are kept, the conflicting parts of the import are
+    /// skipped
+    Skip,
+    /// Existing apps, instances and deployments are replaced
+    #[default]
+    Replace,
+    /// Conflicting instances are imported with a new instance id, existing apps and deployments
+    /// are kept. Only supported for docker instances in exports with schema version 3.0.0,
+    /// other conflicting instances are skipped.
+    NewInstanceId,
+}
+
+/// Selects which parts of an import are applied and how, everything contained in the import is
+/// selected if a selection is missing
+#[serde_as]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub apps: Option<Vec<AppKey>>,
+    #[serde_as(as = "Option<Vec<DisplayFromStr>>")]
+    #[serde(default)]
+    pub instances: Option<Vec<InstanceId>>,
+    #[serde(default)]
+    pub deployments: Option<Vec<DeploymentId>>,
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Replaces parent network adapters of imported networks (name in the import -> name on this
+    /// device), e.g. if the device names its interfaces differently
+    #[serde(default)]
+    pub parent_adapters: HashMap<String, String>,
+    /// Only determine what the import would do without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Large entries of an import archive (images and volumes) which are not extracted with the
+/// metadata but streamed from the archive once the import is validated
+#[derive(Debug, Default)]
+pub struct DeferredImportContent {
+    pub archive_path: PathBuf,
+    /// Deferred entries by their '/' separated path relative to the export data
+    pub entries: BTreeMap<String, DeferredEntry>,
+}
+
+impl DeferredImportContent {
+    pub fn required_space(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize)]
+pub struct ImportPlanContents {
+    pub apps: Vec<AppKey>,
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub instances: Vec<InstanceId>,
+    pub deployments: Vec<DeploymentId>,
+}
+
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ImportIncompatibility {
+    #[error("Import has different architecture than device ({device_arch}): {import_arch}")]
+    #[serde(rename_all = "camelCase")]
+    Architecture {
+        device_arch: String,
+        import_arch: String,
+    },
+    #[error("App {app_key} requires at least FLECS {minimum_version}, device runs {flecs_version}")]
+    #[serde(rename_all = "camelCase")]
+    FlecsVersion {
+        app_key: AppKey,
+        minimum_version: String,
+        flecs_version: String,
+    },
+}
+
+/// What an import does, or would do in a dry run
+#[serde_as]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPlan {
+    pub create: ImportPlanContents,
+    pub replace: ImportPlanContents,
+    pub skip: ImportPlanContents,
+    /// Instances which are imported with a new id, mapped from the id in the import to the new id
+    #[serde_as(as = "HashMap<DisplayFromStr, DisplayFromStr>")]
+    pub new_instance_ids: HashMap<InstanceId, InstanceId>,
+    pub incompatibilities: Vec<ImportIncompatibility>,
+}
+
+impl ImportPlan {
+    /// Apps which are taken from the import
+    pub fn imported_apps(&self) -> Vec<AppKey> {
+        [&self.create.apps[..], &self.replace.apps[..]].concat()
+    }
+
+    /// Instances which are taken from the import, identified by their id in the import
+    pub fn imported_instances(&self) -> Vec<InstanceId> {
+        [&self.create.instances[..], &self.replace.instances[..]]
+            .concat()
+            .into_iter()
+            .chain(self.new_instance_ids.keys().copied())
+            .collect()
+    }
+
+    /// Deployments which are taken from the import
+    pub fn imported_deployments(&self) -> Vec<DeploymentId> {
+        [&self.create.deployments[..], &self.replace.deployments[..]].concat()
+    }
+}
+
+/// Sizes of the files in an import archive in bytes
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportSizes {
+    pub total: u64,
+    /// App images and manifests
+    pub apps: u64,
+    /// Instance configurations, config files and volumes
+    pub instances: u64,
+    /// Deployments and their networks
+    pub deployments: u64,
+    /// Manifest, signature and unknown files
+    pub other: u64,
+}
+
+/// Content of an import archive according to its manifest, see
+/// [crate::sorcerer::spell::flimport::inspect_import_archive]
+#[serde_as]
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportInspection {
+    pub schema_version: String,
+    pub apps: Vec<AppKey>,
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[schema(value_type = Vec<String>)]
+    pub instances: Vec<InstanceId>,
+    pub deployments: Vec<DeploymentId>,
+    /// Device the export was created on
+    #[schema(value_type = Object)]
+    pub device: Device,
+    /// Version of FLECS the export was created with
+    pub core_version: String,
+    /// Id of the base export if the export is incremental
+    pub base: Option<String>,
+    pub signed: bool,
+    pub encrypted: bool,
+    pub sizes: ImportSizes,
 }
 
 #[cfg_attr(test, automock)]
@@ -131,7 +366,15 @@ pub trait Importius: Sorcerer + 'static {
         lore: Arc<Lore>,
         usb_device_reader: Arc<U>,
         path_info: ImportPathInfo,
-    ) -> Result<(), ImportError>;
+        options: ImportOptions,
+    ) -> Result<ImportPlan, ImportError>;
+
+    async fn inspect_archive(
+        &self,
+        lore: Arc<Lore>,
+        archive_path: PathBuf,
+        decryption: Decryption,
+    ) -> Result<ImportInspection, InspectImportError>;
 }
 
 #[cfg(test)]
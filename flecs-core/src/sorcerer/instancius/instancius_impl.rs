@@ -6,13 +6,15 @@ use crate::jeweler::gem::deployment::Deployment;
 use crate::jeweler::gem::deployment::compose::ComposeDeployment;
 use crate::jeweler::gem::deployment::docker::DockerDeployment;
 use crate::jeweler::gem::instance::docker::config::{
-    InstanceConfig, InstancePortMapping, TransportProtocol, UsbPathConfig,
+    EditorHost, EditorTls, InstanceConfig, InstancePortMapping, TransportProtocol, UsbPathConfig,
 };
-use crate::jeweler::gem::instance::{Instance, InstanceId, Logs};
+use crate::jeweler::gem::instance::docker::files::{FileEntry, FileLocations};
+use crate::jeweler::gem::instance::{ExecOutput, ExecSession, Instance, InstanceId, Logs};
 use crate::jeweler::gem::manifest::AppManifest;
 use crate::jeweler::gem::manifest::multi::AppManifestMulti;
 use crate::jeweler::gem::manifest::single::{
-    AppManifestSingle, BindMount, EnvironmentVariable, Label, PortMapping, PortRange, VolumeMount,
+    AppManifestSingle, BindMount, EditorRoles, EnvironmentVariable, Label, PortMapping, PortRange,
+    VolumeMount,
 };
 use crate::jeweler::network::{Network, NetworkId};
 use crate::jeweler::volume::VolumeId;
@@ -21,15 +23,18 @@ use crate::quest::SyncQuest;
 use crate::relic::device::usb::{UsbDevice, UsbDeviceReader};
 use crate::relic::floxy::Floxy;
 use crate::relic::network::Ipv4NetworkAccess;
+use crate::relic::tls::{CertifiedKey, is_valid_host_name};
 use crate::sorcerer::instancius::{
-    ConnectInstanceConfigNetworkError, DisconnectInstanceError, GetInstanceConfigBindMountError,
+    CloneInstanceError, ConnectInstanceConfigNetworkError, DisconnectInstanceError,
+    EditorCertificate, ExecInstanceError, GetInstanceConfigBindMountError,
     GetInstanceConfigNetworkResult, GetInstanceConfigVolumeMountError, GetInstanceUsbDeviceResult,
-    InstanceEditorPathPrefixError, Instancius, PutInstanceUsbDeviceResult,
-    RedirectEditorRequestResult,
+    InstanceEditorHostError, InstanceEditorPathPrefixError, InstanceEditorRolesError,
+    InstanceFilesError, Instancius, PutInstanceUsbDeviceResult, RedirectEditorRequestResult,
 };
 use crate::sorcerer::spell::instance::{QueryInstanceConfigError, UpdateInstanceError};
 use crate::sorcerer::spell::provider::set_default_dependencies;
 use crate::sorcerer::{Sorcerer, spell};
+use crate::vault::pouch::deployment::DeploymentId;
 use crate::vault::pouch::{AppKey, Pouch};
 use crate::vault::{GrabbedPouches, Vault};
 use async_trait::async_trait;
@@ -37,8 +42,9 @@ use flecsd_axum_server::models::{AppInstance, InstanceEditor, InstancesInstanceI
 use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr};
 use std::num::NonZeroU16;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Default)]
 pub struct InstanciusImpl {}
@@ -110,6 +116,7 @@ impl InstanciusImpl {
         _quest: SyncQuest,
         vault: Arc<Vault>,
         app_key: AppKey,
+        deployment_id: Option<DeploymentId>,
     ) -> anyhow::Result<(
         Option<(Arc<AppManifestSingle>, Arc<dyn DockerDeployment>)>,
         Option<(Arc<AppManifestMulti>, Arc<dyn ComposeDeployment>)>,
@@ -153,16 +160,25 @@ impl InstanciusImpl {
                 {
                     anyhow::bail!("Can not create multiple instances for {app_key}");
                 }
-                let deployment = deployments.default_docker_deployment().ok_or_else(|| {
-                    anyhow::anyhow!("No deployment present to create instance in")
-                })?;
-                let Deployment::Docker(deployment) = deployment else {
+                let deployment = match deployment_id {
+                    Some(deployment_id) => deployments
+                        .gems()
+                        .get(&deployment_id)
+                        .cloned()
+                        .ok_or_else(|| {
+                            anyhow::anyhow!("Deployment {deployment_id} does not exist")
+                        })?,
+                    None => deployments.default_docker_deployment().ok_or_else(|| {
+                        anyhow::anyhow!("No deployment present to create instance in")
+                    })?,
+                };
+                let Some(docker_deployment) = deployment.as_docker_deployment() else {
                     anyhow::bail!(
-                        "Can only create single image app ({app_key}) with DockerDeployment, not with {}",
+                        "Can only create single image app ({app_key}) with DockerDeployment or PodmanDeployment, not with {}",
                         deployment.id()
                     );
                 };
-                Ok((Some((manifest, deployment)), None))
+                Ok((Some((manifest, docker_deployment)), None))
             }
             AppManifest::Multi(manifest) => {
                 anyhow::ensure!(
@@ -322,13 +338,21 @@ impl Instancius for InstanciusImpl {
         lore: Arc<Lore>,
         app_key: AppKey,
         name: String,
+        deployment_id: Option<DeploymentId>,
     ) -> anyhow::Result<InstanceId> {
         let result = quest
             .lock()
             .await
             .create_sub_quest(
                 format!("Validate request for creation of instance '{name}' of {app_key}"),
-                |quest| Self::validate_instance_creation(quest, vault.clone(), app_key.clone()),
+                |quest| {
+                    Self::validate_instance_creation(
+                        quest,
+                        vault.clone(),
+                        app_key.clone(),
+                        deployment_id,
+                    )
+                },
             )
             .await
             .2;
@@ -419,6 +443,23 @@ impl Instancius for InstanciusImpl {
         spell::instance::get_instance_config_part_with(vault, id, |config| config.clone()).await
     }
 
+    async fn put_instance_config(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        config: InstanceConfig,
+    ) -> Result<(), QueryInstanceConfigError> {
+        spell::instance::modify_instance_config_with(vault, id, |current| {
+            *current = InstanceConfig {
+                volume_mounts: std::mem::take(&mut current.volume_mounts),
+                mapped_editor_ports: std::mem::take(&mut current.mapped_editor_ports),
+                provider_connections: std::mem::take(&mut current.provider_connections),
+                ..config
+            }
+        })
+        .await
+    }
+
     async fn get_instance_editor(
         &self,
         vault: Arc<Vault>,
@@ -444,13 +485,7 @@ impl Instancius for InstanciusImpl {
             .iter()
             .find(|editor| editor.port.get() == port)
             .ok_or_else(|| InstanceEditorPathPrefixError::EditorNotFound(id, port))?;
-        let path_prefix = instance.config.editor_path_prefixes.get(&port).cloned();
-        Ok(InstanceEditor {
-            name: editor.name.clone(),
-            port,
-            url: crate::lore::FloxyLore::instance_editor_api_location(id, port),
-            path_prefix,
-        })
+        Ok(instance.instance_editor(editor))
     }
 
     async fn get_instance_editors(
@@ -476,16 +511,7 @@ impl Instancius for InstanciusImpl {
             .manifest
             .editors()
             .iter()
-            .map(|editor| {
-                let port = editor.port.get();
-                let path_prefix = instance.config.editor_path_prefixes.get(&port).cloned();
-                InstanceEditor {
-                    name: editor.name.clone(),
-                    port,
-                    url: crate::lore::FloxyLore::instance_editor_api_location(id, port),
-                    path_prefix,
-                }
-            })
+            .map(|editor| instance.instance_editor(editor))
             .collect();
         Ok(editors)
     }
@@ -558,6 +584,211 @@ impl Instancius for InstanciusImpl {
         Ok(previous_path_prefix)
     }
 
+    async fn put_instance_editor_host(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+        host_name: String,
+        certificate: Option<EditorCertificate>,
+    ) -> Result<Option<EditorHost>, InstanceEditorHostError> {
+        if !is_valid_host_name(&host_name) {
+            return Err(InstanceEditorHostError::InvalidHostName(host_name));
+        }
+        let mut grab = vault
+            .reservation()
+            .reserve_instance_pouch_mut()
+            .grab()
+            .await;
+        let instances = grab
+            .instance_pouch_mut
+            .as_mut()
+            .expect("Reservations should never fail")
+            .gems_mut();
+        let host_name_in_use = instances.values().find_map(|instance| match instance {
+            Instance::Compose(_) => None,
+            Instance::Docker(instance) => instance
+                .config
+                .editor_hosts
+                .iter()
+                .find(|(editor_port, editor_host)| {
+                    (instance.id, **editor_port) != (id, port)
+                        && editor_host.host_name.eq_ignore_ascii_case(&host_name)
+                })
+                .map(|(editor_port, _)| (instance.id, *editor_port)),
+        });
+        if let Some((instance_id, port)) = host_name_in_use {
+            return Err(InstanceEditorHostError::HostNameInUse {
+                host_name,
+                instance_id,
+                port,
+            });
+        }
+        let instance = match instances.get_mut(&id) {
+            None => return Err(InstanceEditorHostError::InstanceNotFound(id)),
+            Some(Instance::Compose(_)) => return Err(InstanceEditorHostError::NotSupported(id)),
+            Some(Instance::Docker(instance)) => instance,
+        };
+        if !instance
+            .manifest
+            .editors()
+            .iter()
+            .any(|editor| editor.port.get() == port)
+        {
+            return Err(InstanceEditorHostError::EditorNotFound(id, port));
+        }
+        let (tls, certified_key) = match certificate {
+            None => (None, None),
+            Some(EditorCertificate::SelfSigned) => (
+                Some(EditorTls { self_signed: true }),
+                Some(CertifiedKey::generate_self_signed(
+                    std::slice::from_ref(&host_name),
+                    crate::lore::default::floxy::SELF_SIGNED_CERTIFICATE_VALIDITY_DAYS,
+                )?),
+            ),
+            Some(EditorCertificate::Custom {
+                certificate,
+                private_key,
+            }) => (
+                Some(EditorTls { self_signed: false }),
+                Some(CertifiedKey::new(certificate, private_key)?),
+            ),
+        };
+        let editor_host = EditorHost { host_name, tls };
+        Ok(instance
+            .set_editor_host(floxy, port, editor_host, certified_key)
+            .await?)
+    }
+
+    async fn delete_instance_editor_host(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+    ) -> Result<Option<EditorHost>, InstanceEditorHostError> {
+        let mut grab = vault
+            .reservation()
+            .reserve_instance_pouch_mut()
+            .grab()
+            .await;
+        let instance = match grab
+            .instance_pouch_mut
+            .as_mut()
+            .expect("Reservations should never fail")
+            .gems_mut()
+            .get_mut(&id)
+        {
+            None => return Err(InstanceEditorHostError::InstanceNotFound(id)),
+            Some(Instance::Compose(_)) => return Err(InstanceEditorHostError::NotSupported(id)),
+            Some(Instance::Docker(instance)) => instance,
+        };
+        Ok(instance.remove_editor_host(floxy, port)?)
+    }
+
+    async fn get_instance_editor_roles(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        port: u16,
+    ) -> Result<EditorRoles, InstanceEditorRolesError> {
+        let grab = vault.reservation().reserve_instance_pouch().grab().await;
+        let instance = match grab
+            .instance_pouch
+            .as_ref()
+            .expect("Reservations should never fail")
+            .gems()
+            .get(&id)
+        {
+            None => return Err(InstanceEditorRolesError::InstanceNotFound(id)),
+            Some(Instance::Compose(_)) => {
+                return Err(InstanceEditorRolesError::NotSupported(id));
+            }
+            Some(Instance::Docker(instance)) => instance,
+        };
+        if !instance
+            .manifest
+            .editors()
+            .iter()
+            .any(|editor| editor.port.get() == port)
+        {
+            return Err(InstanceEditorRolesError::EditorNotFound(id, port));
+        }
+        Ok(instance.editor_roles(port))
+    }
+
+    async fn put_instance_editor_roles(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+        roles: EditorRoles,
+    ) -> Result<Option<EditorRoles>, InstanceEditorRolesError> {
+        let mut grab = vault
+            .reservation()
+            .reserve_instance_pouch_mut()
+            .grab()
+            .await;
+        let instance = match grab
+            .instance_pouch_mut
+            .as_mut()
+            .expect("Reservations should never fail")
+            .gems_mut()
+            .get_mut(&id)
+        {
+            None => return Err(InstanceEditorRolesError::InstanceNotFound(id)),
+            Some(Instance::Compose(_)) => {
+                return Err(InstanceEditorRolesError::NotSupported(id));
+            }
+            Some(Instance::Docker(instance)) => instance,
+        };
+        if !instance
+            .manifest
+            .editors()
+            .iter()
+            .any(|editor| editor.port.get() == port)
+        {
+            return Err(InstanceEditorRolesError::EditorNotFound(id, port));
+        }
+        if let Some(role) = roles.find_invalid_role() {
+            return Err(InstanceEditorRolesError::InvalidRole(role.to_string()));
+        }
+        Ok(instance.set_editor_roles(floxy, port, Some(roles)).await?)
+    }
+
+    async fn delete_instance_editor_roles(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+    ) -> Result<Option<EditorRoles>, InstanceEditorRolesError> {
+        let mut grab = vault
+            .reservation()
+            .reserve_instance_pouch_mut()
+            .grab()
+            .await;
+        let instance = match grab
+            .instance_pouch_mut
+            .as_mut()
+            .expect("Reservations should never fail")
+            .gems_mut()
+            .get_mut(&id)
+        {
+            None => return Err(InstanceEditorRolesError::InstanceNotFound(id)),
+            Some(Instance::Compose(_)) => {
+                return Err(InstanceEditorRolesError::NotSupported(id));
+            }
+            Some(Instance::Docker(instance)) => instance,
+        };
+        if !instance.config.editor_roles.contains_key(&port) {
+            return Ok(None);
+        }
+        Ok(instance.set_editor_roles(floxy, port, None).await?)
+    }
+
     async fn get_instance_usb_devices<U: UsbDeviceReader>(
         &self,
         vault: Arc<Vault>,
@@ -1143,6 +1374,101 @@ impl Instancius for InstanciusImpl {
         }
     }
 
+    async fn exec_instance(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        cmd: Vec<String>,
+        timeout: Duration,
+    ) -> Result<ExecOutput, ExecInstanceError> {
+        spell::instance::exec_instance(vault, id, cmd, timeout).await
+    }
+
+    async fn check_instance_exec(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+    ) -> Result<(), ExecInstanceError> {
+        spell::instance::check_instance_exec(vault, id).await
+    }
+
+    async fn exec_instance_tty(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        cmd: Vec<String>,
+    ) -> Result<ExecSession, ExecInstanceError> {
+        spell::instance::exec_instance_tty(vault, id, cmd).await
+    }
+
+    async fn resize_instance_exec(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        exec_id: &str,
+        width: u16,
+        height: u16,
+    ) -> Result<(), ExecInstanceError> {
+        spell::instance::resize_instance_exec(vault, id, exec_id, width, height).await
+    }
+
+    async fn get_instance_file_locations(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+    ) -> Result<FileLocations, InstanceFilesError> {
+        spell::instance::get_instance_file_locations(vault, id).await
+    }
+
+    async fn get_instance_config_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        file_name: &str,
+    ) -> Result<Vec<u8>, InstanceFilesError> {
+        spell::instance::get_instance_config_file(vault, id, file_name).await
+    }
+
+    async fn put_instance_config_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        file_name: &str,
+        data: &[u8],
+    ) -> Result<(), InstanceFilesError> {
+        spell::instance::put_instance_config_file(vault, id, file_name, data).await
+    }
+
+    async fn get_instance_volume_files(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        volume_name: &str,
+    ) -> Result<Vec<FileEntry>, InstanceFilesError> {
+        spell::instance::get_instance_volume_files(vault, id, volume_name).await
+    }
+
+    async fn get_instance_volume_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        volume_name: &str,
+        path: &Path,
+    ) -> Result<Vec<u8>, InstanceFilesError> {
+        spell::instance::get_instance_volume_file(vault, id, volume_name, path).await
+    }
+
+    async fn put_instance_volume_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        volume_name: &str,
+        path: &Path,
+        data: &[u8],
+    ) -> Result<(), InstanceFilesError> {
+        spell::instance::put_instance_volume_file(vault, id, volume_name, path, data).await
+    }
+
     async fn get_instance_labels(&self, vault: Arc<Vault>, id: InstanceId) -> Option<Vec<Label>> {
         spell::instance::query_instance(vault, id, |instance| match instance {
             Instance::Docker(instance) => instance.manifest.labels.clone(),
@@ -1224,6 +1550,7 @@ impl Instancius for InstanciusImpl {
             instance_id,
             network_address,
             port.get(),
+            instance.is_editor_restricted(port.get()),
         )?;
         instance
             .config
@@ -1275,6 +1602,28 @@ impl Instancius for InstanciusImpl {
             .2;
         update_result.await
     }
+
+    async fn clone_instance(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        instance_id: InstanceId,
+        name: Option<String>,
+        copy_config_files: bool,
+        copy_volumes: bool,
+    ) -> Result<InstanceId, CloneInstanceError> {
+        spell::instance::clone_instance(
+            quest,
+            vault,
+            floxy,
+            instance_id,
+            name,
+            copy_config_files,
+            copy_volumes,
+        )
+        .await
+    }
 }
 
 fn network_access_from_network(
@@ -1589,6 +1938,7 @@ pub mod tests {
                 lore,
                 app_key,
                 "TestInstance".to_string(),
+                None,
             )
             .await
             .unwrap();
@@ -1607,6 +1957,44 @@ pub mod tests {
         assert!(instance.config.connected_networks.is_empty());
     }
 
+    #[tokio::test]
+    async fn create_instance_unknown_deployment() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let app_key = AppKey {
+            name: MINIMAL_APP_NAME.to_string(),
+            version: MINIMAL_APP_VERSION.to_string(),
+        };
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_deployment_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_is_app_installed()
+            .returning(|_, _| Ok(true));
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = vault::tests::create_test_vault(
+            HashMap::new(),
+            HashMap::from([(app_key.clone(), deployment.clone())]),
+            Some(deployment.clone()),
+        );
+        assert!(
+            InstanciusImpl::default()
+                .create_instance(
+                    Quest::new_synced("TestQuest".to_string()),
+                    vault,
+                    lore,
+                    app_key,
+                    "TestInstance".to_string(),
+                    Some("UnknownDeployment".to_string()),
+                )
+                .await
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn create_instance_err() {
         let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
@@ -1662,6 +2050,7 @@ pub mod tests {
                 lore,
                 app_key,
                 "TestInstance".to_string(),
+                None,
             )
             .await;
         assert!(result.is_err());
@@ -1711,6 +2100,7 @@ pub mod tests {
                 lore.clone(),
                 app_key.clone(),
                 "TestInstance1".to_string(),
+                None,
             )
             .await
             .unwrap();
@@ -1721,6 +2111,7 @@ pub mod tests {
                 lore,
                 app_key,
                 "TestInstance2".to_string(),
+                None,
             )
             .await
             .unwrap();
@@ -1773,6 +2164,7 @@ pub mod tests {
                 lore.clone(),
                 app_key.clone(),
                 "TestInstance1".to_string(),
+                None,
             )
             .await
             .unwrap();
@@ -1784,6 +2176,7 @@ pub mod tests {
                     lore,
                     app_key,
                     "TestInstance2".to_string(),
+                    None,
                 )
                 .await
                 .is_err()
@@ -1833,6 +2226,7 @@ pub mod tests {
                     lore,
                     app_key,
                     "TestInstance".to_string(),
+                    None,
                 )
                 .await
                 .is_err()
@@ -1866,6 +2260,7 @@ pub mod tests {
                     lore,
                     app_key,
                     "TestInstance".to_string(),
+                    None,
                 )
                 .await
                 .is_err()
@@ -1903,6 +2298,7 @@ pub mod tests {
                     lore,
                     app_key,
                     "TestInstance".to_string(),
+                    None,
                 )
                 .await
                 .is_err()
@@ -1950,6 +2346,7 @@ pub mod tests {
                     lore,
                     app_key,
                     "TestInstance".to_string(),
+                    None,
                 )
                 .await
                 .is_err()
@@ -2888,6 +3285,49 @@ pub mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn put_instance_config_unknown() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .put_instance_config(vault, UNKNOWN_INSTANCE_1, InstanceConfig::default())
+                .await,
+            Err(QueryInstanceConfigError::NotFound(UNKNOWN_INSTANCE_1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_instance_config_keeps_volume_mounts() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        let volume_mounts = InstanciusImpl::default()
+            .get_instance_config(vault.clone(), MOUNT_INSTANCE)
+            .await
+            .unwrap()
+            .volume_mounts;
+        assert!(!volume_mounts.is_empty());
+        let config = InstanceConfig {
+            environment_variables: vec![EnvironmentVariable {
+                name: "VAR_1".to_string(),
+                value: None,
+            }],
+            ..InstanceConfig::default()
+        };
+        InstanciusImpl::default()
+            .put_instance_config(vault.clone(), MOUNT_INSTANCE, config.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            InstanciusImpl::default()
+                .get_instance_config(vault, MOUNT_INSTANCE)
+                .await
+                .unwrap(),
+            InstanceConfig {
+                volume_mounts,
+                ..config
+            }
+        );
+    }
+
     #[tokio::test]
     async fn put_instance_config_environment_variable_value_none() {
         let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
@@ -4059,6 +4499,355 @@ pub mod tests {
         );
     }
 
+    fn stopped_editor_deployment(status_checks: usize) -> Deployment {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_deployment_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_instance_status()
+            .times(status_checks)
+            .returning(|_| Ok(InstanceStatus::Stopped));
+        Deployment::Docker(Arc::new(deployment))
+    }
+
+    #[tokio::test]
+    async fn put_instance_editor_host_invalid_host_name() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .put_instance_editor_host(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234,
+                    "server_name evil".to_string(),
+                    None,
+                )
+                .await,
+            Err(InstanceEditorHostError::InvalidHostName(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_instance_editor_host_instance_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .put_instance_editor_host(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    UNKNOWN_INSTANCE_1,
+                    1234,
+                    "editor.flecs.local".to_string(),
+                    None,
+                )
+                .await,
+            Err(InstanceEditorHostError::InstanceNotFound(
+                UNKNOWN_INSTANCE_1
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_instance_editor_host_editor_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .put_instance_editor_host(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    60,
+                    "editor.flecs.local".to_string(),
+                    None,
+                )
+                .await,
+            Err(InstanceEditorHostError::EditorNotFound(EDITOR_INSTANCE, 60))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_instance_editor_host_invalid_certificate() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .put_instance_editor_host(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234,
+                    "editor.flecs.local".to_string(),
+                    Some(EditorCertificate::Custom {
+                        certificate: "no certificate".to_string(),
+                        private_key: "no key".to_string(),
+                    }),
+                )
+                .await,
+            Err(InstanceEditorHostError::Certificate(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_and_delete_instance_editor_host_stopped() {
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(EDITOR_INSTANCE, stopped_editor_deployment(1))]),
+            HashMap::new(),
+            None,
+        );
+        let instancius = InstanciusImpl::default();
+        assert_eq!(
+            instancius
+                .put_instance_editor_host(
+                    vault.clone(),
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234,
+                    "editor.flecs.local".to_string(),
+                    Some(EditorCertificate::SelfSigned),
+                )
+                .await
+                .unwrap(),
+            None
+        );
+        let editor = instancius
+            .get_instance_editor(vault.clone(), EDITOR_INSTANCE, 1234)
+            .await
+            .unwrap();
+        assert_eq!(editor.host_name.as_deref(), Some("editor.flecs.local"));
+        assert_eq!(
+            editor.host_url.as_deref(),
+            Some("https://editor.flecs.local/")
+        );
+        assert_eq!(editor.self_signed_certificate, Some(true));
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_delete_instance_editor_host_config()
+            .once()
+            .withf(|_, _, id, port| *id == EDITOR_INSTANCE && *port == 1234)
+            .returning(|_, _, _, _| Ok(()));
+        assert_eq!(
+            instancius
+                .delete_instance_editor_host(vault.clone(), Arc::new(floxy), EDITOR_INSTANCE, 1234)
+                .await
+                .unwrap(),
+            Some(EditorHost {
+                host_name: "editor.flecs.local".to_string(),
+                tls: Some(EditorTls { self_signed: true }),
+            })
+        );
+        let editor = instancius
+            .get_instance_editor(vault, EDITOR_INSTANCE, 1234)
+            .await
+            .unwrap();
+        assert_eq!(editor.host_name, None);
+    }
+
+    #[tokio::test]
+    async fn put_instance_editor_host_in_use() {
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(EDITOR_INSTANCE, stopped_editor_deployment(1))]),
+            HashMap::new(),
+            None,
+        );
+        let instancius = InstanciusImpl::default();
+        instancius
+            .put_instance_editor_host(
+                vault.clone(),
+                Arc::new(MockFloxy::new()),
+                EDITOR_INSTANCE,
+                1234,
+                "editor.flecs.local".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            instancius
+                .put_instance_editor_host(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    5678,
+                    "Editor.flecs.local".to_string(),
+                    None,
+                )
+                .await,
+            Err(InstanceEditorHostError::HostNameInUse {
+                instance_id: EDITOR_INSTANCE,
+                port: 1234,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn delete_instance_editor_host_not_configured() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert_eq!(
+            InstanciusImpl::default()
+                .delete_instance_editor_host(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234
+                )
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    fn test_editor_roles() -> EditorRoles {
+        EditorRoles {
+            read: ["operator".to_string()].into(),
+            write: ["engineer".to_string()].into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn get_instance_editor_roles_instance_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .get_instance_editor_roles(vault, UNKNOWN_INSTANCE_1, 1234)
+                .await,
+            Err(InstanceEditorRolesError::InstanceNotFound(
+                UNKNOWN_INSTANCE_1
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_instance_editor_roles_editor_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .get_instance_editor_roles(vault, EDITOR_INSTANCE, 60)
+                .await,
+            Err(InstanceEditorRolesError::EditorNotFound(
+                EDITOR_INSTANCE,
+                60
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_instance_editor_roles_editor_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .put_instance_editor_roles(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    60,
+                    test_editor_roles(),
+                )
+                .await,
+            Err(InstanceEditorRolesError::EditorNotFound(
+                EDITOR_INSTANCE,
+                60
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_instance_editor_roles_invalid_role() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            InstanciusImpl::default()
+                .put_instance_editor_roles(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234,
+                    EditorRoles {
+                        read: ["operator,engineer".to_string()].into(),
+                        write: Default::default(),
+                    },
+                )
+                .await,
+            Err(InstanceEditorRolesError::InvalidRole(role)) if role == "operator,engineer"
+        ));
+    }
+
+    #[tokio::test]
+    async fn put_and_delete_instance_editor_roles_stopped() {
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(EDITOR_INSTANCE, stopped_editor_deployment(2))]),
+            HashMap::new(),
+            None,
+        );
+        let instancius = InstanciusImpl::default();
+        assert_eq!(
+            instancius
+                .get_instance_editor_roles(vault.clone(), EDITOR_INSTANCE, 1234)
+                .await
+                .unwrap(),
+            EditorRoles::default()
+        );
+        assert_eq!(
+            instancius
+                .put_instance_editor_roles(
+                    vault.clone(),
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234,
+                    test_editor_roles(),
+                )
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            instancius
+                .get_instance_editor_roles(vault.clone(), EDITOR_INSTANCE, 1234)
+                .await
+                .unwrap(),
+            test_editor_roles()
+        );
+        assert_eq!(
+            instancius
+                .delete_instance_editor_roles(
+                    vault.clone(),
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234
+                )
+                .await
+                .unwrap(),
+            Some(test_editor_roles())
+        );
+        assert_eq!(
+            instancius
+                .get_instance_editor_roles(vault, EDITOR_INSTANCE, 1234)
+                .await
+                .unwrap(),
+            EditorRoles::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_instance_editor_roles_not_configured() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert_eq!(
+            InstanciusImpl::default()
+                .delete_instance_editor_roles(
+                    vault,
+                    Arc::new(MockFloxy::new()),
+                    EDITOR_INSTANCE,
+                    1234
+                )
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
     #[tokio::test]
     async fn redirect_editor_request_instance_not_found() {
         let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
@@ -4125,7 +4914,8 @@ pub mod tests {
         floxy
             .expect_add_instance_editor_redirect_to_free_port()
             .times(1)
-            .returning(|_, _, _, _, _| Ok(125));
+            .withf(|_, _, _, _, port, restricted| *port == 1234 && !*restricted)
+            .returning(|_, _, _, _, _, _| Ok(125));
         let floxy = Arc::new(floxy);
         assert_eq!(
             InstanciusImpl::default()
@@ -2,11 +2,12 @@ mod instancius_impl;
 pub use super::Result;
 use crate::jeweler::gem;
 use crate::jeweler::gem::instance::docker::config::{
-    InstancePortMapping, TransportProtocol, UsbPathConfig,
+    EditorHost, InstancePortMapping, TransportProtocol, UsbPathConfig,
 };
-use crate::jeweler::gem::instance::{InstanceId, Logs};
+use crate::jeweler::gem::instance::docker::files::{FileEntry, FileLocations};
+use crate::jeweler::gem::instance::{ExecOutput, ExecSession, InstanceId, Logs};
 use crate::jeweler::gem::manifest::single::{
-    BindMount, EnvironmentVariable, Label, PortMapping, PortRange, VolumeMount,
+    BindMount, EditorRoles, EnvironmentVariable, Label, PortMapping, PortRange, VolumeMount,
 };
 use crate::jeweler::network::NetworkId;
 use crate::jeweler::volume::VolumeId;
@@ -14,22 +15,30 @@ use crate::lore::{FloxyLoreRef, Lore};
 use crate::quest::SyncQuest;
 use crate::relic::device::usb::{UsbDevice, UsbDeviceReader};
 use crate::relic::floxy::Floxy;
+use crate::relic::tls::CertificateError;
 use crate::sorcerer::Sorcerer;
+pub use crate::sorcerer::spell::instance::CloneInstanceError;
 pub use crate::sorcerer::spell::instance::DisconnectInstanceError;
+pub use crate::sorcerer::spell::instance::ExecInstanceError;
+pub use crate::sorcerer::spell::instance::InstanceFilesError;
 pub use crate::sorcerer::spell::instance::QueryInstanceConfigError;
 use crate::sorcerer::spell::instance::UpdateInstanceError;
 use crate::vault::Vault;
 use crate::vault::pouch::AppKey;
+use crate::vault::pouch::deployment::DeploymentId;
 use anyhow::Error;
 use async_trait::async_trait;
 pub use instancius_impl::InstanciusImpl;
 #[cfg(test)]
 use mockall::automock;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr};
 use std::num::NonZeroU16;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
 
 pub type UsbDevices = Vec<(UsbPathConfig, Option<UsbDevice>)>;
 
@@ -128,6 +137,57 @@ pub enum InstanceEditorPathPrefixError {
     Other(#[from] anyhow::Error),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum InstanceEditorRolesError {
+    #[error("Instance {0} not found")]
+    InstanceNotFound(InstanceId),
+    #[error("Instance {0} has no editor with port {1}")]
+    EditorNotFound(InstanceId, u16),
+    #[error("Instance {0} does not support configuring")]
+    NotSupported(InstanceId),
+    #[error(
+        "Invalid role {0:?}, roles must not be empty, contain commas or be padded with whitespace"
+    )]
+    InvalidRole(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InstanceEditorHostError {
+    #[error("Instance {0} not found")]
+    InstanceNotFound(InstanceId),
+    #[error("Instance {0} has no editor with port {1}")]
+    EditorNotFound(InstanceId, u16),
+    #[error("Instance {0} does not support configuring")]
+    NotSupported(InstanceId),
+    #[error("Invalid host name {0:?}")]
+    InvalidHostName(String),
+    #[error("Host name {host_name} is already used by editor {port} of instance {instance_id}")]
+    HostNameInUse {
+        host_name: String,
+        instance_id: InstanceId,
+        port: u16,
+    },
+    #[error(transparent)]
+    Certificate(#[from] CertificateError),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Certificate the dedicated host of an editor is served with
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EditorCertificate {
+    /// Generate a self-signed certificate for the host name
+    SelfSigned,
+    /// Use the given PEM encoded certificate chain and private key
+    Custom {
+        certificate: String,
+        private_key: String,
+    },
+}
+
 impl From<anyhow::Error> for ConnectInstanceConfigNetworkError {
     fn from(value: Error) -> Self {
         Self::Other(value.to_string())
@@ -202,6 +262,8 @@ pub trait Instancius: Sorcerer {
         floxy: Arc<dyn Floxy>,
     ) -> Result<()>;
 
+    /// Single image apps are created in the deployment with the given id if specified, otherwise
+    /// the default deployment for the type of app is used
     async fn create_instance(
         &self,
         quest: SyncQuest,
@@ -209,6 +271,7 @@ pub trait Instancius: Sorcerer {
         lore: Arc<Lore>,
         app_key: AppKey,
         name: String,
+        deployment_id: Option<DeploymentId>,
     ) -> Result<InstanceId>;
 
     async fn does_instance_exist(&self, vault: Arc<Vault>, id: InstanceId) -> bool;
@@ -219,6 +282,15 @@ pub trait Instancius: Sorcerer {
         id: InstanceId,
     ) -> Result<gem::instance::docker::config::InstanceConfig, QueryInstanceConfigError>;
 
+    /// Replaces the config of the instance, volume mounts and mapped editor ports belong to the
+    /// instance and are kept
+    async fn put_instance_config(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        config: gem::instance::docker::config::InstanceConfig,
+    ) -> Result<(), QueryInstanceConfigError>;
+
     async fn get_instance_editor(
         &self,
         vault: Arc<Vault>,
@@ -249,6 +321,54 @@ pub trait Instancius: Sorcerer {
         port: u16,
     ) -> Result<Option<String>, InstanceEditorPathPrefixError>;
 
+    /// Serves the editor under a dedicated host name, TLS is terminated with `certificate` if
+    /// set. Returns the previous host of the editor.
+    async fn put_instance_editor_host(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+        host_name: String,
+        certificate: Option<EditorCertificate>,
+    ) -> Result<Option<EditorHost>, InstanceEditorHostError>;
+
+    async fn delete_instance_editor_host(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+    ) -> Result<Option<EditorHost>, InstanceEditorHostError>;
+
+    /// Roles required to access the editor, see [gem::instance::docker::DockerInstance::editor_roles]
+    async fn get_instance_editor_roles(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        port: u16,
+    ) -> Result<EditorRoles, InstanceEditorRolesError>;
+
+    /// Overrides the roles required to access the editor, returns the previous override
+    async fn put_instance_editor_roles(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+        roles: EditorRoles,
+    ) -> Result<Option<EditorRoles>, InstanceEditorRolesError>;
+
+    /// Removes the override of the roles required to access the editor so that the roles declared
+    /// by the manifest apply again, returns the removed override
+    async fn delete_instance_editor_roles(
+        &self,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        id: InstanceId,
+        port: u16,
+    ) -> Result<Option<EditorRoles>, InstanceEditorRolesError>;
+
     async fn get_instance_usb_devices<U: UsbDeviceReader + 'static>(
         &self,
         vault: Arc<Vault>,
@@ -469,6 +589,89 @@ pub trait Instancius: Sorcerer {
 
     async fn get_instance_logs(&self, vault: Arc<Vault>, id: InstanceId) -> Result<Logs>;
 
+    /// Executes the command inside the running instance and waits at most `timeout` for it to
+    /// finish
+    async fn exec_instance(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        cmd: Vec<String>,
+        timeout: Duration,
+    ) -> Result<ExecOutput, ExecInstanceError>;
+
+    /// Checks that commands can be executed inside the instance, i.e. that it exists, supports
+    /// executing commands and is running
+    async fn check_instance_exec(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+    ) -> Result<(), ExecInstanceError>;
+
+    /// Starts the command with an attached tty inside the running instance
+    async fn exec_instance_tty(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        cmd: Vec<String>,
+    ) -> Result<ExecSession, ExecInstanceError>;
+
+    async fn resize_instance_exec(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        exec_id: &str,
+        width: u16,
+        height: u16,
+    ) -> Result<(), ExecInstanceError>;
+
+    async fn get_instance_file_locations(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+    ) -> Result<FileLocations, InstanceFilesError>;
+
+    async fn get_instance_config_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        file_name: &str,
+    ) -> Result<Vec<u8>, InstanceFilesError>;
+
+    async fn put_instance_config_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        file_name: &str,
+        data: &[u8],
+    ) -> Result<(), InstanceFilesError>;
+
+    /// Recursively lists the content of the volume, requires a running instance
+    async fn get_instance_volume_files(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        volume_name: &str,
+    ) -> Result<Vec<FileEntry>, InstanceFilesError>;
+
+    /// Reads a file inside the volume, the path is relative to the volume
+    async fn get_instance_volume_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        volume_name: &str,
+        path: &Path,
+    ) -> Result<Vec<u8>, InstanceFilesError>;
+
+    /// Writes a file inside the volume, the path is relative to the volume
+    async fn put_instance_volume_file(
+        &self,
+        vault: Arc<Vault>,
+        id: InstanceId,
+        volume_name: &str,
+        path: &Path,
+        data: &[u8],
+    ) -> Result<(), InstanceFilesError>;
+
     async fn get_instance_labels(&self, vault: Arc<Vault>, id: InstanceId) -> Option<Vec<Label>>;
 
     async fn get_instance_label_value(
@@ -496,6 +699,20 @@ pub trait Instancius: Sorcerer {
         new_version: String,
         base_path: PathBuf,
     ) -> Result<(), UpdateInstanceError>;
+
+    /// Creates a new instance of the same app with the configuration of the given instance. If
+    /// no name is given the name of the original instance with the suffix '-clone' is used.
+    #[allow(clippy::too_many_arguments)]
+    async fn clone_instance(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        instance_id: InstanceId,
+        name: Option<String>,
+        copy_config_files: bool,
+        copy_volumes: bool,
+    ) -> Result<InstanceId, CloneInstanceError>;
 }
 
 #[cfg(test)]
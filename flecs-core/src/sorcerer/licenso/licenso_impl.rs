@@ -1,11 +1,19 @@
+use crate::forge::time::SystemTimeExt;
 use crate::fsm::console_client::ConsoleClient;
-use crate::sorcerer::licenso::Licenso;
+use crate::jeweler::license::{
+    LicenseFile, LicenseRequest, LicenseRevalidation, LicenseValidation, OfflineLicense,
+};
+use crate::relic::system::serial::SerialNumberReader;
+use crate::sorcerer::licenso::{ImportLicenseError, Licenso};
 use crate::sorcerer::spell::license::ActivationResult;
 use crate::sorcerer::{Sorcerer, spell};
 use crate::vault::pouch::Pouch;
 use crate::vault::{GrabbedPouches, Vault};
 use anyhow::{Context, anyhow};
 use async_trait::async_trait;
+use ed25519_dalek::VerifyingKey;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
 
 #[derive(Default)]
 pub struct LicensoImpl {}
@@ -17,13 +25,14 @@ impl Licenso for LicensoImpl {
     async fn activate_license(
         &self,
         vault: &Vault,
+        serial_number_reader: &dyn SerialNumberReader,
         configuration: ConsoleClient,
     ) -> anyhow::Result<()> {
         let secrets = vault.get_secrets().await;
 
         let activation_result = match (
             secrets.license_key.as_ref(),
-            spell::license::read_serial_number(),
+            serial_number_reader.read_serial_number(),
         ) {
             (Some(&ref key), _) | (None, Some(ref key)) => {
                 spell::license::activate_via_license_key(key, secrets.get_session_id(), configuration)
@@ -82,6 +91,9 @@ impl Licenso for LicensoImpl {
         vault: &Vault,
         configuration: ConsoleClient,
     ) -> anyhow::Result<bool> {
+        if spell::license::has_valid_offline_license(vault, SystemTime::now().unix_secs()).await {
+            return Ok(true);
+        }
         let session_id = vault
             .reservation()
             .reserve_secret_pouch()
@@ -95,11 +107,61 @@ impl Licenso for LicensoImpl {
             .id;
         spell::license::validate_license(session_id, configuration).await
     }
+
+    async fn revalidate_license(
+        &self,
+        vault: &Vault,
+        configuration: ConsoleClient,
+        grace_period: Duration,
+    ) -> LicenseRevalidation {
+        let previous = vault.get_secrets().await.license_validation;
+        let result = self.validate_license(vault, configuration).await;
+        if let Err(e) = &result {
+            warn!("Could not validate license: {e:#}");
+        }
+        let current = LicenseValidation::next(
+            previous.as_ref(),
+            &result,
+            SystemTime::now().unix_secs(),
+            grace_period,
+        );
+        spell::license::store_license_validation(vault, current.clone()).await;
+        LicenseRevalidation { previous, current }
+    }
+
+    async fn create_license_request(
+        &self,
+        serial_number_reader: &dyn SerialNumberReader,
+    ) -> anyhow::Result<LicenseRequest> {
+        spell::license::create_license_request(
+            serial_number_reader.read_serial_number(),
+            SystemTime::now().unix_secs(),
+        )
+    }
+
+    async fn import_license_file(
+        &self,
+        vault: &Vault,
+        serial_number_reader: &dyn SerialNumberReader,
+        trusted_keys: &[VerifyingKey],
+        license_file: LicenseFile,
+    ) -> Result<OfflineLicense, ImportLicenseError> {
+        let license = spell::license::verify_license_file(
+            &license_file,
+            trusted_keys,
+            serial_number_reader.read_serial_number().as_deref(),
+            SystemTime::now().unix_secs(),
+        )?;
+        spell::license::store_offline_license(vault, license.clone()).await;
+        Ok(license)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::jeweler::license::LicenseState;
+    use crate::relic::system::serial::MockSerialNumberReader;
     use crate::vault::pouch::secret::Secrets;
     use crate::vault::tests::create_empty_test_vault;
     use flecs_console_client::models::SessionId;
@@ -110,6 +172,12 @@ mod tests {
     const SESSION_ID: &str = "74c3b620-6048-4bfd-9bf7-c9857a001694";
     const TIMESTAMP: u64 = 17243237291234u64;
 
+    fn no_serial_number() -> MockSerialNumberReader {
+        let mut reader = MockSerialNumberReader::new();
+        reader.expect_read_serial_number().return_const(None);
+        reader
+    }
+
     async fn setup_secrets(vault: &Vault, secrets: Secrets) {
         let mut pouches = vault.reservation().reserve_secret_pouch_mut().grab().await;
         let secret_pouch = pouches.secret_pouch_mut.as_mut().unwrap();
@@ -182,7 +250,7 @@ mod tests {
             .create_async()
             .await;
         LicensoImpl::default()
-            .activate_license(&vault, config)
+            .activate_license(&vault, &no_serial_number(), config)
             .await
             .unwrap();
         mock.assert();
@@ -226,7 +294,7 @@ mod tests {
             .create_async()
             .await;
         LicensoImpl::default()
-            .activate_license(&vault, config)
+            .activate_license(&vault, &no_serial_number(), config)
             .await
             .unwrap();
         mock.assert();
@@ -236,6 +304,46 @@ mod tests {
         assert_eq!(secrets.gems().license_key, Some(LICENSE_KEY.to_string()));
     }
 
+    #[tokio::test]
+    async fn activate_via_serial_number_test() {
+        const SERIAL_NUMBER: &str = "00000000c0ffee00";
+        let vault = create_empty_test_vault();
+        let (mut server, config) = crate::tests::create_test_server_and_config().await;
+        let body = serde_json::json!({
+            "statusCode": 200,
+            "statusTest": "OK",
+            "data": {
+                "sessionId": {
+                    "id": SESSION_ID,
+                    "timestamp": TIMESTAMP
+                },
+                "licenseKey": LICENSE_KEY
+            }
+        });
+        let body = serde_json::to_string(&body).unwrap();
+        let mock = server
+            .mock("POST", "/api/v2/device/license/activate")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(&body)
+            .match_header("Authorization", Matcher::Missing)
+            .create_async()
+            .await;
+        let mut reader = MockSerialNumberReader::new();
+        reader
+            .expect_read_serial_number()
+            .once()
+            .return_const(Some(SERIAL_NUMBER.to_string()));
+        LicensoImpl::default()
+            .activate_license(&vault, &reader, config)
+            .await
+            .unwrap();
+        mock.assert();
+        let mut secrets = vault.reservation().reserve_secret_pouch_mut().grab().await;
+        let secrets = secrets.secret_pouch_mut.as_mut().unwrap();
+        assert_eq!(secrets.gems().license_key, Some(LICENSE_KEY.to_string()));
+    }
+
     #[tokio::test]
     async fn activate_already_active_test() {
         let vault = create_empty_test_vault();
@@ -262,7 +370,7 @@ mod tests {
             .create_async()
             .await;
         LicensoImpl::default()
-            .activate_license(&vault, config)
+            .activate_license(&vault, &no_serial_number(), config)
             .await
             .unwrap();
         mock.assert();
@@ -294,7 +402,7 @@ mod tests {
             format!(
                 "{:#}",
                 LicensoImpl::default()
-                    .activate_license(&vault, config)
+                    .activate_license(&vault, &no_serial_number(), config)
                     .await
                     .err()
                     .unwrap()
@@ -335,7 +443,7 @@ mod tests {
             format!(
                 "{:#}",
                 LicensoImpl::default()
-                    .activate_license(&vault, config)
+                    .activate_license(&vault, &no_serial_number(), config)
                     .await
                     .err()
                     .unwrap()
@@ -373,7 +481,7 @@ mod tests {
             format!(
                 "{:#}",
                 LicensoImpl::default()
-                    .activate_license(&vault, config)
+                    .activate_license(&vault, &no_serial_number(), config)
                     .await
                     .err()
                     .unwrap()
@@ -401,7 +509,7 @@ mod tests {
             .await;
         assert!(
             LicensoImpl::default()
-                .activate_license(&vault, config)
+                .activate_license(&vault, &no_serial_number(), config)
                 .await
                 .err()
                 .unwrap()
@@ -417,4 +525,97 @@ mod tests {
         assert_eq!(secrets.gems().license_key, None);
         assert_eq!(secrets.gems().authentication, None);
     }
+
+    #[tokio::test]
+    async fn validate_offline_license_test() {
+        let vault = create_empty_test_vault();
+        let license = OfflineLicense {
+            expires_at: None,
+            ..crate::jeweler::license::tests::test_license()
+        };
+        spell::license::store_offline_license(&vault, license).await;
+        let (mut server, config) = crate::tests::create_test_server_and_config().await;
+        let mock = server
+            .mock("POST", "/api/v2/device/license/validate")
+            .expect(0)
+            .create_async()
+            .await;
+        assert!(
+            LicensoImpl::default()
+                .validate_license(&vault, config)
+                .await
+                .unwrap()
+        );
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn import_untrusted_license_file_test() {
+        let vault = create_empty_test_vault();
+        let license_file = LicenseFile::create(
+            &crate::jeweler::license::tests::test_signing_key(3),
+            &crate::jeweler::license::tests::test_license(),
+        );
+        assert!(matches!(
+            LicensoImpl::default()
+                .import_license_file(
+                    &vault,
+                    &no_serial_number(),
+                    &[crate::jeweler::license::tests::test_signing_key(4).verifying_key()],
+                    license_file
+                )
+                .await,
+            Err(ImportLicenseError::File(_))
+        ));
+        let grab = vault.reservation().reserve_secret_pouch().grab().await;
+        let secrets = grab.secret_pouch.as_ref().unwrap();
+        assert_eq!(secrets.gems().offline_license, None);
+    }
+
+    #[tokio::test]
+    async fn revalidate_unreachable_console_test() {
+        let vault = create_empty_test_vault();
+        setup_secrets(
+            &vault,
+            Secrets::new(
+                None,
+                SessionId {
+                    id: Some(SESSION_ID.to_string()),
+                    timestamp: Some(TIMESTAMP),
+                },
+                None,
+            ),
+        )
+        .await;
+        let last_validated = SystemTime::now().unix_secs() - 60;
+        let previous = LicenseValidation {
+            state: LicenseState::Valid,
+            last_validated: Some(last_validated),
+            grace_until: None,
+        };
+        spell::license::store_license_validation(&vault, previous.clone()).await;
+        let (mut server, config) = crate::tests::create_test_server_and_config().await;
+        let mock = server
+            .mock("POST", "/api/v2/device/license/validate")
+            .with_status(500)
+            .create_async()
+            .await;
+        let revalidation = LicensoImpl::default()
+            .revalidate_license(&vault, config, Duration::from_secs(3600))
+            .await;
+        mock.assert();
+        let expected = LicenseValidation {
+            state: LicenseState::GracePeriod,
+            last_validated: Some(last_validated),
+            grace_until: Some(last_validated + 3600),
+        };
+        assert_eq!(
+            revalidation,
+            LicenseRevalidation {
+                previous: Some(previous),
+                current: expected.clone(),
+            }
+        );
+        assert_eq!(vault.get_secrets().await.license_validation, Some(expected));
+    }
 }
@@ -1,19 +1,57 @@
 mod licenso_impl;
+pub mod revalidation;
 pub use super::Result;
 use super::Sorcerer;
 use crate::fsm::console_client::ConsoleClient;
+use crate::jeweler::license::{LicenseFile, LicenseRequest, LicenseRevalidation, OfflineLicense};
+use crate::relic::system::serial::SerialNumberReader;
+pub use crate::sorcerer::spell::license::ImportLicenseError;
 use crate::vault::Vault;
 use async_trait::async_trait;
+use ed25519_dalek::VerifyingKey;
 pub use licenso_impl::LicensoImpl;
 #[cfg(test)]
 use mockall::automock;
+use std::time::Duration;
 
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait Licenso: Sorcerer {
-    async fn activate_license(&self, vault: &Vault, configuration: ConsoleClient) -> Result<()>;
+    /// Activates the license with the stored license key, the serial number of the device or the
+    /// license of the authenticated user, in this order
+    async fn activate_license(
+        &self,
+        vault: &Vault,
+        serial_number_reader: &dyn SerialNumberReader,
+        configuration: ConsoleClient,
+    ) -> Result<()>;
 
+    /// A stored offline license that is not expired is valid without asking the console
     async fn validate_license(&self, vault: &Vault, configuration: ConsoleClient) -> Result<bool>;
+
+    /// Validates the license and stores the outcome. If the console can not be reached the
+    /// license stays valid until `grace_period` after the last successful validation.
+    async fn revalidate_license(
+        &self,
+        vault: &Vault,
+        configuration: ConsoleClient,
+        grace_period: Duration,
+    ) -> LicenseRevalidation;
+
+    /// Describes this device for issuing a [LicenseFile] without a connection to the console
+    async fn create_license_request(
+        &self,
+        serial_number_reader: &dyn SerialNumberReader,
+    ) -> Result<LicenseRequest>;
+
+    /// Verifies the license file against the `trusted_keys` and stores the contained license
+    async fn import_license_file(
+        &self,
+        vault: &Vault,
+        serial_number_reader: &dyn SerialNumberReader,
+        trusted_keys: &[VerifyingKey],
+        license_file: LicenseFile,
+    ) -> Result<OfflineLicense, ImportLicenseError>;
 }
 
 #[cfg(test)]
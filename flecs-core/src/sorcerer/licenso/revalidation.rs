@@ -0,0 +1,166 @@
+//! Periodic license validation, see [crate::lore::ConsoleLore::license_revalidation_interval]
+use crate::enchantment::quest_master::QuestMaster;
+use crate::fsm::console_client::{self, ConsoleClient};
+use crate::jeweler::license::{LicenseRevalidation, LicenseState, LicenseValidation};
+use crate::lore::Lore;
+use crate::sorcerer::licenso::Licenso;
+use crate::vault::Vault;
+use anyhow::anyhow;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// Revalidates the license and records changes of the license state as quests
+pub async fn revalidate_license<L: Licenso + ?Sized>(
+    quest_master: &QuestMaster,
+    licenso: &L,
+    vault: &Vault,
+    configuration: ConsoleClient,
+    grace_period: Duration,
+) -> LicenseValidation {
+    let revalidation = licenso
+        .revalidate_license(vault, configuration, grace_period)
+        .await;
+    if revalidation.is_transition() {
+        record_transition(quest_master, &revalidation).await;
+    }
+    revalidation.current
+}
+
+fn transition_description(revalidation: &LicenseRevalidation) -> String {
+    let previous = revalidation
+        .previous
+        .as_ref()
+        .map(|previous| previous.state.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    format!(
+        "License state changed from {previous} to {}",
+        revalidation.current.state
+    )
+}
+
+/// The quest of a transition to [LicenseState::Invalid] fails
+async fn record_transition(quest_master: &QuestMaster, revalidation: &LicenseRevalidation) {
+    let description = transition_description(revalidation);
+    info!("{description}");
+    let current = revalidation.current.clone();
+    if let Err(e) = quest_master
+        .lock()
+        .await
+        .schedule_quest(description, |_quest| async move {
+            match current.state {
+                LicenseState::Valid => Ok(()),
+                LicenseState::GracePeriod => {
+                    warn!(
+                        "Console not reachable, license stays valid until {:?}",
+                        current.grace_until
+                    );
+                    Ok(())
+                }
+                LicenseState::Invalid => Err(anyhow!("License is invalid")),
+            }
+        })
+        .await
+    {
+        error!("Could not record license state transition: {e}");
+    }
+}
+
+/// Revalidates the license in the configured interval, never returns
+pub async fn run_license_revalidation<L: Licenso>(
+    quest_master: QuestMaster,
+    licenso: Arc<L>,
+    vault: Arc<Vault>,
+    lore: Arc<Lore>,
+) {
+    let configuration = console_client::create_default(vault.clone(), lore.clone());
+    let mut interval = tokio::time::interval(lore.console.license_revalidation_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        revalidate_license(
+            &quest_master,
+            licenso.as_ref(),
+            &vault,
+            configuration.clone(),
+            lore.console.license_grace_period,
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::licenso::MockLicenso;
+    use crate::vault::tests::create_empty_test_vault;
+
+    fn validation(state: LicenseState) -> LicenseValidation {
+        LicenseValidation {
+            state,
+            last_validated: Some(1_750_000_000),
+            grace_until: None,
+        }
+    }
+
+    #[test]
+    fn transition_description_ok() {
+        let revalidation = LicenseRevalidation {
+            previous: None,
+            current: validation(LicenseState::Valid),
+        };
+        assert_eq!(
+            transition_description(&revalidation),
+            "License state changed from unknown to valid"
+        );
+        let revalidation = LicenseRevalidation {
+            previous: Some(validation(LicenseState::Valid)),
+            current: validation(LicenseState::GracePeriod),
+        };
+        assert_eq!(
+            transition_description(&revalidation),
+            "License state changed from valid to grace period"
+        );
+    }
+
+    async fn revalidate_with(revalidation: LicenseRevalidation) -> usize {
+        let quest_master = QuestMaster::default();
+        let mut licenso = MockLicenso::new();
+        let expected = revalidation.current.clone();
+        licenso
+            .expect_revalidate_license()
+            .once()
+            .return_const(revalidation);
+        let (_server, configuration) = crate::tests::create_test_server_and_config().await;
+        assert_eq!(
+            revalidate_license(
+                &quest_master,
+                &licenso,
+                &create_empty_test_vault(),
+                configuration,
+                Duration::from_secs(60),
+            )
+            .await,
+            expected
+        );
+        quest_master.lock().await.get_quests().len()
+    }
+
+    #[tokio::test]
+    async fn revalidate_license_transition() {
+        let revalidation = LicenseRevalidation {
+            previous: Some(validation(LicenseState::Valid)),
+            current: validation(LicenseState::Invalid),
+        };
+        assert_eq!(revalidate_with(revalidation).await, 1);
+    }
+
+    #[tokio::test]
+    async fn revalidate_license_no_transition() {
+        let revalidation = LicenseRevalidation {
+            previous: Some(validation(LicenseState::Valid)),
+            current: validation(LicenseState::Valid),
+        };
+        assert_eq!(revalidate_with(revalidation).await, 0);
+    }
+}
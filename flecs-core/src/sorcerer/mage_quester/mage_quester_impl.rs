@@ -85,6 +85,8 @@ async fn job_from_quest(quest: SyncQuest) -> models::Job {
         QuestResult::None => String::new(),
         QuestResult::InstanceId(id) => id.to_string(),
         QuestResult::ExportId(id) => id.clone(),
+        QuestResult::ImportPlan(plan) => serde_json::to_string(plan).unwrap_or_default(),
+        QuestResult::PruneReport(report) => serde_json::to_string(report).unwrap_or_default(),
     };
     models::Job {
         id: quest.id.0 as u32,
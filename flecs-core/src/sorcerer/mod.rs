@@ -9,6 +9,7 @@ pub mod mage_quester;
 pub mod manifesto;
 pub mod providius;
 mod spell;
+pub mod statius;
 pub mod systemus;
 
 pub use super::{Error, Result};
@@ -23,6 +24,8 @@ use crate::sorcerer::mage_quester::{MageQuester, MageQuesterImpl};
 use crate::sorcerer::manifesto::{Manifesto, ManifestoImpl};
 use crate::sorcerer::providius::Providius;
 use crate::sorcerer::providius::providius_impl::ProvidiusImpl;
+use crate::sorcerer::statius::Statius;
+use crate::sorcerer::statius::statius_impl::StatiusImpl;
 use crate::sorcerer::systemus::{Systemus, SystemusImpl};
 use std::sync::Arc;
 
@@ -53,6 +56,7 @@ impl Default for FlecsSorcerers {
             exportius: Default::default(),
             importius: Default::default(),
             providius: Arc::new(ProvidiusImpl),
+            statius: Arc::new(StatiusImpl),
         }
     }
 }
@@ -80,6 +84,7 @@ pub struct Sorcerers<
     pub exportius: Arc<E>,
     pub importius: Arc<IMP>,
     pub providius: Arc<dyn Providius>,
+    pub statius: Arc<dyn Statius>,
 }
 
 impl<
@@ -108,6 +113,7 @@ impl<
             exportius: self.exportius.clone(),
             importius: self.importius.clone(),
             providius: self.providius.clone(),
+            statius: self.statius.clone(),
         }
     }
 }
@@ -141,6 +147,7 @@ impl Default for MockSorcerers {
             exportius: Arc::new(crate::sorcerer::exportius::MockExportius::default()),
             importius: Arc::new(crate::sorcerer::importius::MockImportius::default()),
             providius: Arc::new(crate::sorcerer::providius::MockProvidius::default()),
+            statius: Arc::new(crate::sorcerer::statius::MockStatius::default()),
         }
     }
 }
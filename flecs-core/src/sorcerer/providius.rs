@@ -2,8 +2,8 @@ use super::spell;
 use crate::jeweler::gem::instance::{InstanceId, ProviderReference, StoredProviderReference};
 use crate::jeweler::gem::manifest::providers::auth::AuthProvider;
 use crate::jeweler::gem::manifest::{DependencyKey, FeatureKey};
-#[cfg(feature = "auth")]
 use crate::quest::SyncQuest;
+use crate::relic::floxy::Floxy;
 use crate::sorcerer::Sorcerer;
 #[cfg(feature = "auth")]
 use crate::sorcerer::spell::provider::BuildWatchConfigError;
@@ -77,6 +77,7 @@ pub struct Dependency {
     pub config: serde_json::Value,
 }
 
+pub mod failover;
 pub mod providius_impl;
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
@@ -164,7 +165,27 @@ pub trait Providius: Sorcerer {
         feature: FeatureKey,
         id: InstanceId,
         provider_reference: ProviderReference,
+        fallback_providers: Vec<ProviderReference>,
     ) -> Result<Option<ProviderReference>, SetDependencyError>;
+    /// Injects the current provider connection details into all dependent instances, running
+    /// instances are restarted if their connection details changed
+    async fn update_dependents(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+    ) -> anyhow::Result<()>;
+    /// Instances with dependencies that have to fail over to another provider
+    async fn get_pending_failovers(&self, vault: Arc<Vault>) -> Vec<InstanceId>;
+    /// Binds the dependencies of the instances to their first available provider, running
+    /// instances are restarted if their connection details changed
+    async fn fail_over_dependents(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        ids: Vec<InstanceId>,
+    ) -> anyhow::Result<()>;
     #[cfg(feature = "auth")]
     async fn build_watch_config_from_auth_provider(
         &self,
@@ -0,0 +1,118 @@
+//! Periodic check of dependencies with fallback providers, see
+//! [crate::lore::ProviderLore::health_check_interval]
+use crate::enchantment::quest_master::QuestMaster;
+use crate::lore::Lore;
+use crate::relic::floxy::Floxy;
+use crate::sorcerer::providius::Providius;
+use crate::vault::Vault;
+use std::sync::Arc;
+use tracing::error;
+
+/// Schedules a quest failing over the dependencies whose provider became unavailable or whose
+/// preferred provider became available again, returns true if a quest was scheduled
+pub async fn check_provider_health(
+    quest_master: &QuestMaster,
+    providius: Arc<dyn Providius>,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+) -> bool {
+    let pending = providius.get_pending_failovers(vault.clone()).await;
+    if pending.is_empty() {
+        return false;
+    }
+    match quest_master
+        .lock()
+        .await
+        .schedule_quest(
+            "Fail over providers of dependent instances".to_string(),
+            |quest| async move {
+                providius
+                    .fail_over_dependents(quest, vault, floxy, pending)
+                    .await
+            },
+        )
+        .await
+    {
+        Ok(_) => true,
+        Err(e) => {
+            error!("Could not schedule provider failover: {e}");
+            false
+        }
+    }
+}
+
+/// Checks the providers of dependencies in the configured interval, never returns
+pub async fn run_provider_health_checks(
+    quest_master: QuestMaster,
+    providius: Arc<dyn Providius>,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+    lore: Arc<Lore>,
+) {
+    let mut interval = tokio::time::interval(lore.provider.health_check_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        check_provider_health(
+            &quest_master,
+            providius.clone(),
+            vault.clone(),
+            floxy.clone(),
+        )
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::instance::InstanceId;
+    use crate::relic::floxy::MockFloxy;
+    use crate::sorcerer::providius::MockProvidius;
+    use crate::vault::tests::create_empty_test_vault;
+
+    #[tokio::test]
+    async fn check_provider_health_nothing_pending() {
+        let quest_master = QuestMaster::default();
+        let mut providius = MockProvidius::new();
+        providius
+            .expect_get_pending_failovers()
+            .once()
+            .returning(|_| Vec::new());
+        providius.expect_fail_over_dependents().never();
+        assert!(
+            !check_provider_health(
+                &quest_master,
+                Arc::new(providius),
+                create_empty_test_vault(),
+                Arc::new(MockFloxy::new()),
+            )
+            .await
+        );
+        assert!(quest_master.lock().await.get_quests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn check_provider_health_pending() {
+        let quest_master = QuestMaster::default();
+        let mut providius = MockProvidius::new();
+        providius
+            .expect_get_pending_failovers()
+            .once()
+            .returning(|_| vec![InstanceId::new(1)]);
+        providius
+            .expect_fail_over_dependents()
+            .withf(|_, _, _, ids| *ids == [InstanceId::new(1)])
+            .returning(|_, _, _, _| Ok(()));
+        assert!(
+            check_provider_health(
+                &quest_master,
+                Arc::new(providius),
+                create_empty_test_vault(),
+                Arc::new(MockFloxy::new()),
+            )
+            .await
+        );
+        assert_eq!(quest_master.lock().await.get_quests().len(), 1);
+    }
+}
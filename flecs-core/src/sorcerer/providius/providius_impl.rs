@@ -3,8 +3,8 @@ use crate::jeweler::gem::manifest::{DependencyKey, FeatureKey};
 use crate::lore::FloxyLore;
 #[cfg(feature = "auth")]
 use crate::quest;
-#[cfg(feature = "auth")]
 use crate::quest::SyncQuest;
+use crate::relic::floxy::Floxy;
 use crate::sorcerer::Sorcerer;
 #[cfg(feature = "auth")]
 use crate::sorcerer::providius::AuthProvidersAndDefaults;
@@ -19,9 +19,10 @@ use crate::sorcerer::spell::provider::{
 use crate::sorcerer::spell::provider::{
     DeleteDefaultProviderError, GetDependencyError, GetProviderError, SetCoreAuthProviderError,
     SetDefaultProviderError, SetDependencyError, clear_dependency, delete_default_provider,
-    get_core_providers, get_default_provider_id, get_default_provider_ids, get_dependencies,
-    get_dependency, get_feature_provides, get_provider, get_providers, get_provides,
-    set_core_auth_provider, set_default_provider, set_dependency,
+    fail_over_dependents, get_core_providers, get_default_provider_id, get_default_provider_ids,
+    get_dependencies, get_dependency, get_feature_provides, get_pending_failovers, get_provider,
+    get_providers, get_provides, set_core_auth_provider, set_default_provider, set_dependency,
+    update_dependents,
 };
 use crate::vault::pouch::Pouch;
 use crate::vault::pouch::provider::{CoreProviders, ProviderId};
@@ -374,6 +375,7 @@ impl Providius for ProvidiusImpl {
         feature: FeatureKey,
         id: InstanceId,
         provider_reference: ProviderReference,
+        fallback_providers: Vec<ProviderReference>,
     ) -> Result<Option<ProviderReference>, SetDependencyError> {
         let GrabbedPouches {
             provider_pouch: Some(ref providers),
@@ -395,10 +397,47 @@ impl Providius for ProvidiusImpl {
             feature,
             id,
             provider_reference,
+            fallback_providers,
         )
         .await
     }
 
+    async fn update_dependents(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+    ) -> anyhow::Result<()> {
+        update_dependents(quest, vault, floxy).await
+    }
+
+    async fn get_pending_failovers(&self, vault: Arc<Vault>) -> Vec<InstanceId> {
+        let GrabbedPouches {
+            provider_pouch: Some(ref providers),
+            instance_pouch: Some(ref instances),
+            ..
+        } = vault
+            .reservation()
+            .reserve_provider_pouch()
+            .reserve_instance_pouch()
+            .grab()
+            .await
+        else {
+            unreachable!("Reservation should never fail");
+        };
+        get_pending_failovers(instances.gems(), providers.gems()).await
+    }
+
+    async fn fail_over_dependents(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        floxy: Arc<dyn Floxy>,
+        ids: Vec<InstanceId>,
+    ) -> anyhow::Result<()> {
+        fail_over_dependents(quest, vault, floxy, ids).await
+    }
+
     #[cfg(feature = "auth")]
     async fn build_watch_config_from_auth_provider(
         &self,
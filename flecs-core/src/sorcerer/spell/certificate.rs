@@ -0,0 +1,238 @@
+//! Device certificate authority and the server certificate issued by it, see
+//! [crate::lore::CertificateLore]
+use crate::lore::CertificateLore;
+use crate::lore::default::certificate::SERVER_CERTIFICATE_VALIDITY_DAYS;
+use crate::relic::device::certificate::{
+    certificate_outdated, ensure_certificate_authority, read_certified_key,
+};
+use crate::relic::tls::{CertificateError, CertifiedKey, is_valid_host_name};
+use crate::sorcerer::systemus::DeviceCertificates;
+use net_spider::network_adapter::NetworkAdapter;
+use std::collections::{BTreeSet, HashMap};
+use std::io::ErrorKind;
+
+const LOCALHOST: &str = "localhost";
+
+/// Host names and ip addresses the device is reachable at, the hostname comes first
+pub fn device_host_names(
+    hostname: Option<&str>,
+    network_adapters: &HashMap<String, NetworkAdapter>,
+) -> Vec<String> {
+    let mut host_names = Vec::new();
+    if let Some(hostname) = hostname.filter(|hostname| is_valid_host_name(hostname)) {
+        host_names.push(hostname.to_string());
+        if hostname != LOCALHOST && !hostname.contains('.') {
+            host_names.push(format!("{hostname}.local"));
+        }
+    }
+    if !host_names.iter().any(|host_name| host_name == LOCALHOST) {
+        host_names.push(LOCALHOST.to_string());
+    }
+    let ip_addresses: BTreeSet<_> = network_adapters
+        .values()
+        .flat_map(|adapter| adapter.ip_addresses.iter())
+        .collect();
+    host_names.extend(ip_addresses.into_iter().map(ToString::to_string));
+    host_names
+}
+
+/// Creates the device certificate authority and the server certificate for `host_names` if they
+/// are missing or expire within [CertificateLore::renew_before]. The server certificate is also
+/// reissued if it was issued by a previous certificate authority or `host_names` changed.
+pub fn ensure_device_certificates(
+    lore: &CertificateLore,
+    host_names: &[String],
+) -> Result<DeviceCertificates, CertificateError> {
+    let (ca, ca_renewed) = ensure_certificate_authority(lore)?;
+    let server_certificate_path = lore.server_certificate_path();
+    let server_private_key_path = lore.server_private_key_path();
+    let (server, server_renewed) =
+        match read_certified_key(&server_certificate_path, &server_private_key_path)? {
+            Some(server) if !certificate_outdated(&server, &ca, host_names, lore.renew_before)? => {
+                (server, false)
+            }
+            _ => {
+                let server = CertifiedKey::generate_issued_by(
+                    host_names,
+                    SERVER_CERTIFICATE_VALIDITY_DAYS,
+                    &ca,
+                )?;
+                server.write_to(&server_certificate_path, &server_private_key_path)?;
+                (server, true)
+            }
+        };
+    Ok(DeviceCertificates {
+        server,
+        ca_renewed,
+        server_renewed,
+    })
+}
+
+/// PEM encoded certificate of the device certificate authority followed by a replaced certificate
+/// authority that did not expire yet, None if it was not created yet
+pub fn device_ca_certificate(lore: &CertificateLore) -> Result<Option<String>, std::io::Error> {
+    match std::fs::read_to_string(lore.ca_certificate_path()) {
+        Ok(certificate) => Ok(Some(certificate)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lore::conf::CertificateConfig;
+    use crate::lore::default::certificate::CA_VALIDITY_DAYS;
+    use crate::relic::network::tests::test_adapters;
+    use std::time::Duration;
+    use testdir::testdir;
+
+    fn test_lore(renew_before: Duration) -> CertificateLore {
+        CertificateLore::from_conf_with_defaults(
+            CertificateConfig {
+                renew_before: Some(renew_before.as_secs()),
+                ..CertificateConfig::default()
+            },
+            &testdir!(),
+        )
+        .unwrap()
+    }
+
+    fn host_names() -> Vec<String> {
+        vec!["flecs-device".to_string(), "192.168.0.10".to_string()]
+    }
+
+    #[test]
+    fn device_host_names_full() {
+        assert_eq!(
+            device_host_names(Some("flecs-device"), &test_adapters()),
+            [
+                "flecs-device",
+                "flecs-device.local",
+                "localhost",
+                "22.41.12.11",
+                "22.41.87.55",
+                "15a1:b1ac::12",
+                "d5f0:bf0f:7ec0::1:100",
+            ]
+        );
+    }
+
+    #[test]
+    fn device_host_names_minimal() {
+        assert_eq!(
+            device_host_names(None, &HashMap::new()),
+            [LOCALHOST.to_string()]
+        );
+        assert_eq!(
+            device_host_names(Some("invalid_hostname"), &HashMap::new()),
+            [LOCALHOST.to_string()]
+        );
+        assert_eq!(
+            device_host_names(Some("localhost"), &HashMap::new()),
+            [LOCALHOST.to_string()]
+        );
+        assert_eq!(
+            device_host_names(Some("device.example.com"), &HashMap::new()),
+            ["device.example.com", "localhost"]
+        );
+    }
+
+    #[test]
+    fn ensure_device_certificates_create() {
+        let lore = test_lore(Duration::from_secs(60));
+        let certificates = ensure_device_certificates(&lore, &host_names()).unwrap();
+        assert!(certificates.ca_renewed);
+        assert!(certificates.server_renewed);
+        assert_eq!(certificates.server.host_names().unwrap(), host_names());
+        let ca = CertifiedKey::read_from(&lore.ca_certificate_path(), &lore.ca_private_key_path())
+            .unwrap();
+        assert!(certificates.server.is_issued_by(&ca).unwrap());
+        assert_eq!(
+            CertifiedKey::read_from(
+                &lore.server_certificate_path(),
+                &lore.server_private_key_path()
+            )
+            .unwrap(),
+            certificates.server
+        );
+        assert!(matches!(
+            lore.tls_path().join("ca.key").try_exists(),
+            Ok(false)
+        ));
+        assert_eq!(device_ca_certificate(&lore).unwrap(), Some(ca.certificate));
+    }
+
+    #[test]
+    fn ensure_device_certificates_unchanged() {
+        let lore = test_lore(Duration::from_secs(60));
+        let created = ensure_device_certificates(&lore, &host_names()).unwrap();
+        let mut reordered = host_names();
+        reordered.reverse();
+        let ensured = ensure_device_certificates(&lore, &reordered).unwrap();
+        assert!(!ensured.ca_renewed);
+        assert!(!ensured.server_renewed);
+        assert_eq!(ensured.server, created.server);
+    }
+
+    #[test]
+    fn ensure_device_certificates_host_names_changed() {
+        let lore = test_lore(Duration::from_secs(60));
+        let ca = device_ca_certificate(&lore).unwrap();
+        assert_eq!(ca, None);
+        ensure_device_certificates(&lore, &host_names()).unwrap();
+        let ca = device_ca_certificate(&lore).unwrap();
+        let host_names = vec!["flecs-device".to_string(), "192.168.0.11".to_string()];
+        let ensured = ensure_device_certificates(&lore, &host_names).unwrap();
+        assert!(!ensured.ca_renewed);
+        assert!(ensured.server_renewed);
+        assert_eq!(ensured.server.host_names().unwrap(), host_names);
+        assert_eq!(device_ca_certificate(&lore).unwrap(), ca);
+    }
+
+    #[test]
+    fn ensure_device_certificates_expiring() {
+        let lore = test_lore(Duration::from_secs(60));
+        ensure_device_certificates(&lore, &host_names()).unwrap();
+        let ca = device_ca_certificate(&lore).unwrap();
+        let lore = CertificateLore {
+            renew_before: Duration::from_secs(
+                u64::from(SERVER_CERTIFICATE_VALIDITY_DAYS + 1) * 24 * 60 * 60,
+            ),
+            ..lore
+        };
+        let ensured = ensure_device_certificates(&lore, &host_names()).unwrap();
+        assert!(!ensured.ca_renewed);
+        assert!(ensured.server_renewed);
+        assert_eq!(device_ca_certificate(&lore).unwrap(), ca);
+        let lore = CertificateLore {
+            renew_before: Duration::from_secs(u64::from(CA_VALIDITY_DAYS + 1) * 24 * 60 * 60),
+            ..lore
+        };
+        let ensured = ensure_device_certificates(&lore, &host_names()).unwrap();
+        assert!(ensured.ca_renewed);
+        assert!(ensured.server_renewed);
+        let published = device_ca_certificate(&lore).unwrap().unwrap();
+        let ca = ca.unwrap();
+        assert_ne!(published, ca);
+        // The replaced certificate authority stays published until it expires
+        assert!(published.ends_with(&ca));
+        let new_ca =
+            CertifiedKey::read_from(&lore.ca_certificate_path(), &lore.ca_private_key_path())
+                .unwrap();
+        assert!(ensured.server.is_issued_by(&new_ca).unwrap());
+    }
+
+    #[test]
+    fn ensure_device_certificates_ca_replaced() {
+        let lore = test_lore(Duration::from_secs(60));
+        ensure_device_certificates(&lore, &host_names()).unwrap();
+        std::fs::write(lore.ca_certificate_path(), "invalid").unwrap();
+        let ensured = ensure_device_certificates(&lore, &host_names()).unwrap();
+        assert!(ensured.ca_renewed);
+        assert!(ensured.server_renewed);
+        let ca = CertifiedKey::read_from(&lore.ca_certificate_path(), &lore.ca_private_key_path())
+            .unwrap();
+        assert!(ensured.server.is_issued_by(&ca).unwrap());
+    }
+}
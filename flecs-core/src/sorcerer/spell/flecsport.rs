@@ -1,14 +1,27 @@
+use crate::forge::bollard::BollardNetworkExtension;
+use crate::jeweler::deployment::DeploymentId;
 use crate::jeweler::gem::deployment::Deployment;
 use crate::jeweler::gem::instance::InstanceId;
+use crate::jeweler::network::{Network, NetworkConfig, NetworkKind};
 use crate::lore::ExportLoreRef;
 use crate::quest::SyncQuest;
+use crate::relic::delta::Index;
+use crate::relic::encryption;
 use crate::relic::floxy::Floxy;
+use crate::sorcerer::exportius::manifest::v3::NetworkKey;
 use crate::vault::Vault;
 use crate::vault::pouch::{AppKey, Pouch};
+use anyhow::Context;
 use futures_util::future::join_all;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tracing::warn;
+
+/// Networks which are present on every docker host and can not be created
+const BUILTIN_NETWORKS: [&str; 3] = ["bridge", "host", "none"];
+/// Networks created by docker compose are recreated together with their instance
+const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
 
 #[derive(thiserror::Error, Debug)]
 pub enum ExportInstanceError {
@@ -34,6 +47,19 @@ pub enum ExportDeploymentError {
     IO(#[from] std::io::Error),
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ExportNetworkError {
+    #[error("Failed to read networks of deployment {deployment}: {error}")]
+    Read {
+        deployment: DeploymentId,
+        error: anyhow::Error,
+    },
+    #[error("Failed to serialize network with serde_json: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("IO error serializing network: {0}")]
+    IO(#[from] std::io::Error),
+}
+
 pub async fn export_instance(
     quest: SyncQuest,
     vault: Arc<Vault>,
@@ -92,25 +118,106 @@ pub async fn export_instances(
     Ok(())
 }
 
+/// Possible paths of the export archive with the given id, plain and encrypted
+fn export_paths(export_dir: &Path, export_id: &str) -> [PathBuf; 2] {
+    [
+        export_dir.join(format!("{export_id}.tar")),
+        export_dir.join(format!("{export_id}.tar.{}", encryption::EXTENSION)),
+    ]
+}
+
+/// Export ids are the creation time of the export in unix milliseconds
+pub fn is_export_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Path of the index of the export with the given id, see [crate::relic::delta::Index]
+pub fn export_index_path(export_dir: &Path, export_id: &str) -> PathBuf {
+    export_dir.join(format!("{export_id}.index.json"))
+}
+
+pub async fn read_export_index(export_dir: &Path, export_id: &str) -> anyhow::Result<Index> {
+    let path = export_index_path(export_dir, export_id);
+    let index = tokio::fs::read(&path)
+        .await
+        .with_context(|| format!("Could not read index {path:?}"))?;
+    Ok(serde_json::from_slice(&index)?)
+}
+
 pub async fn get_export(
     export_dir: &Path,
     export_id: String,
 ) -> Result<Option<PathBuf>, std::io::Error> {
-    let path = export_dir.join(format!("{export_id}.tar"));
-    match tokio::fs::try_exists(&path).await {
-        Ok(true) => Ok(Some(path)),
-        Ok(false) => Ok(None),
-        Err(e) => Err(e),
+    for path in export_paths(export_dir, &export_id) {
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(Some(path));
+        }
     }
+    Ok(None)
 }
 
+/// Returns the ids of all exports which are increments of the export with the given id
+pub async fn export_increments(
+    export_dir: &Path,
+    export_id: &str,
+) -> Result<Vec<String>, std::io::Error> {
+    let mut entries = match tokio::fs::read_dir(export_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut increments = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(id) = file_name.strip_suffix(".index.json") else {
+            continue;
+        };
+        match read_export_index(export_dir, id).await {
+            Ok(index) if index.base.as_deref() == Some(export_id) => {
+                increments.push(id.to_string())
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Could not determine base of export {id}: {e}"),
+        }
+    }
+    Ok(increments)
+}
+
+/// Deletes the export with the given id and all exports based on it, returns false if the export
+/// does not exist
 pub async fn delete_export(export_dir: &Path, export_id: String) -> Result<bool, std::io::Error> {
-    let path = export_dir.join(format!("{export_id}.tar"));
-    match tokio::fs::remove_file(path).await {
-        Ok(_) => Ok(true),
-        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
-        Err(e) => Err(e),
+    let mut dependents = Vec::new();
+    let mut bases = vec![export_id.clone()];
+    while let Some(base) = bases.pop() {
+        for increment in export_increments(export_dir, &base).await? {
+            if increment != export_id && !dependents.contains(&increment) {
+                bases.push(increment.clone());
+                dependents.push(increment);
+            }
+        }
+    }
+    // Increments of increments are deleted first, an interrupted deletion leaves no increment
+    // without its base
+    for dependent in dependents.into_iter().rev() {
+        delete_export_files(export_dir, &dependent).await?;
+    }
+    delete_export_files(export_dir, &export_id).await
+}
+
+async fn delete_export_files(export_dir: &Path, export_id: &str) -> Result<bool, std::io::Error> {
+    let mut deleted = false;
+    for path in export_paths(export_dir, &export_id) {
+        match tokio::fs::remove_file(path).await {
+            Ok(_) => deleted = true,
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    match tokio::fs::remove_file(export_index_path(export_dir, export_id)).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+        _ => {}
     }
+    Ok(deleted)
 }
 
 pub async fn export_apps(
@@ -200,6 +307,106 @@ pub async fn export_deployments(
     Ok(())
 }
 
+/// Exports the custom networks of all deployments to `path/{deployment}/{network}.json`.
+/// Builtin networks, networks created by docker compose and networks of unknown kind are skipped.
+pub async fn export_networks(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    path: PathBuf,
+) -> Result<Vec<NetworkKey>, ExportNetworkError> {
+    let deployments: Vec<_> = vault
+        .reservation()
+        .reserve_deployment_pouch()
+        .grab()
+        .await
+        .deployment_pouch
+        .as_ref()
+        .expect("Vault reservations should never fail")
+        .gems()
+        .values()
+        .cloned()
+        .collect();
+    let mut results = Vec::new();
+    {
+        let mut quest = quest.lock().await;
+        for deployment in deployments {
+            let result = quest
+                .create_sub_quest(
+                    format!("Export networks of deployment {}", deployment.id()),
+                    |quest| export_deployment_networks(quest, deployment, path.clone()),
+                )
+                .await
+                .2;
+            results.push(result);
+        }
+    }
+    let mut keys = Vec::new();
+    for result in join_all(results).await {
+        keys.extend(result?);
+    }
+    Ok(keys)
+}
+
+fn is_exported_network(network: &Network) -> bool {
+    let Some(name) = network.name.as_deref() else {
+        return false;
+    };
+    if BUILTIN_NETWORKS.contains(&name) {
+        return false;
+    }
+    if network
+        .labels
+        .as_ref()
+        .is_some_and(|labels| labels.contains_key(COMPOSE_PROJECT_LABEL))
+    {
+        return false;
+    }
+    !matches!(
+        network.guess_network_kind(),
+        NetworkKind::None | NetworkKind::Unknown
+    )
+}
+
+pub async fn export_deployment_networks(
+    quest: SyncQuest,
+    deployment: Deployment,
+    path: PathBuf,
+) -> Result<Vec<NetworkKey>, ExportNetworkError> {
+    let networks = deployment
+        .networks(quest)
+        .await
+        .map_err(|error| ExportNetworkError::Read {
+            deployment: deployment.id().clone(),
+            error,
+        })?;
+    let path = path.join(deployment.id());
+    let mut keys = Vec::new();
+    for network in networks
+        .iter()
+        .filter(|network| is_exported_network(network))
+    {
+        let config = match NetworkConfig::try_from_network(network) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!(
+                    "Skipping export of network {:?} of deployment {}: {e}",
+                    network.name,
+                    deployment.id()
+                );
+                continue;
+            }
+        };
+        tokio::fs::create_dir_all(&path).await?;
+        let data = serde_json::to_vec_pretty(&config)?;
+        tokio::fs::write(path.join(format!("{}.json", config.name)), &data).await?;
+        keys.push(NetworkKey {
+            deployment: deployment.id().clone(),
+            network: config.name,
+        });
+    }
+    Ok(keys)
+}
+
 pub async fn export_deployment(
     deployment: Deployment,
     path: PathBuf,
@@ -227,11 +434,22 @@ mod tests {
     use crate::vault::pouch::instance::tests::{
         ENV_INSTANCE, MINIMAL_INSTANCE, PORT_MAPPING_INSTANCE, UNKNOWN_INSTANCE_2, USB_DEV_INSTANCE,
     };
-    use crate::vault::tests::{create_empty_test_vault, create_test_vault};
+    use crate::vault::tests::{
+        create_empty_test_vault, create_test_vault, create_test_vault_with_deployment,
+    };
     use mockall::predicate;
     use std::collections::HashMap;
     use testdir::testdir;
 
+    #[test]
+    fn is_export_id_ok() {
+        assert!(is_export_id("1700000000000"));
+        assert!(!is_export_id(""));
+        assert!(!is_export_id(".."));
+        assert!(!is_export_id("../1700000000000"));
+        assert!(!is_export_id("1700000000000/"));
+    }
+
     #[tokio::test]
     async fn export_instances_ok() {
         const INSTANCE_IDS: [InstanceId; 4] = [
@@ -526,6 +744,79 @@ mod tests {
         );
     }
 
+    fn network(name: &str, driver: &str) -> Network {
+        Network {
+            name: Some(name.to_string()),
+            driver: Some(driver.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn export_networks_ok() {
+        const DEPLOYMENT_ID: &str = "ExportedMockDeployment";
+        let path = testdir!().join("networks");
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const(DEPLOYMENT_ID.to_string());
+        deployment.expect_is_default().return_const(true);
+        deployment.expect_networks().once().returning(|_| {
+            Ok(vec![
+                network("bridge", "bridge"),
+                network("host", "host"),
+                Network {
+                    labels: Some(HashMap::from([(
+                        COMPOSE_PROJECT_LABEL.to_string(),
+                        "project".to_string(),
+                    )])),
+                    ..network("project_default", "bridge")
+                },
+                network("overlay", "overlay"),
+                Network {
+                    options: Some(HashMap::from([("parent".to_string(), "eth0".to_string())])),
+                    ..network("macvlan", "macvlan")
+                },
+            ])
+        });
+        let vault = create_test_vault_with_deployment(Deployment::Docker(Arc::new(deployment)));
+        let keys = export_networks(Quest::new_synced("TestQuest"), vault, path.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            keys,
+            vec![NetworkKey {
+                deployment: DEPLOYMENT_ID.to_string(),
+                network: "macvlan".to_string(),
+            }]
+        );
+        let config: NetworkConfig = serde_json::from_slice(
+            &std::fs::read(path.join(DEPLOYMENT_ID).join("macvlan.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.kind, NetworkKind::MACVLAN);
+        assert_eq!(config.parent_adapter.as_deref(), Some("eth0"));
+        assert_eq!(config.options, None);
+    }
+
+    #[tokio::test]
+    async fn export_networks_err_read() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("ExportedMockDeployment".to_string());
+        deployment.expect_is_default().return_const(true);
+        deployment
+            .expect_networks()
+            .once()
+            .returning(|_| Err(anyhow::anyhow!("TestError")));
+        let vault = create_test_vault_with_deployment(Deployment::Docker(Arc::new(deployment)));
+        assert!(matches!(
+            export_networks(Quest::new_synced("TestQuest"), vault, testdir!()).await,
+            Err(ExportNetworkError::Read { .. })
+        ));
+    }
+
     #[tokio::test]
     async fn export_deployment_err() {
         const DEPLOYMENT_ID: &str = "ExportedMockDeployment";
@@ -557,6 +848,21 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn get_export_ok_encrypted() {
+        const EXPORT_ID: &str = "1234tasf236zt";
+        let path = testdir!();
+        let expected_file_path = path.join(format!("{EXPORT_ID}.tar.age"));
+        std::fs::write(&expected_file_path, b"age-encryption.org/v1").unwrap();
+        assert_eq!(
+            get_export(&path, EXPORT_ID.to_string())
+                .await
+                .unwrap()
+                .unwrap(),
+            expected_file_path
+        );
+    }
+
     #[tokio::test]
     async fn get_export_ok_none() {
         const EXPORT_ID: &str = "1234tasf236zt";
@@ -578,6 +884,16 @@ mod tests {
         assert!(!expected_file_path.try_exists().unwrap());
     }
 
+    #[tokio::test]
+    async fn delete_export_ok_encrypted() {
+        const EXPORT_ID: &str = "1234tasf236zt";
+        let path = testdir!();
+        let expected_file_path = path.join(format!("{EXPORT_ID}.tar.age"));
+        std::fs::write(&expected_file_path, b"age-encryption.org/v1").unwrap();
+        assert!(delete_export(&path, EXPORT_ID.to_string()).await.unwrap());
+        assert!(!expected_file_path.try_exists().unwrap());
+    }
+
     #[tokio::test]
     async fn delete_export_ok_none() {
         const EXPORT_ID: &str = "1234tasf236zt";
@@ -585,6 +901,59 @@ mod tests {
         assert!(!delete_export(&path, EXPORT_ID.to_string()).await.unwrap());
     }
 
+    fn write_export_with_base(path: &Path, export_id: &str, base: Option<&str>) {
+        std::fs::write(path.join(format!("{export_id}.tar")), b"data").unwrap();
+        std::fs::write(
+            export_index_path(path, export_id),
+            serde_json::to_vec(&Index {
+                base: base.map(str::to_string),
+                ..Index::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn export_increments_ok() {
+        let path = testdir!();
+        write_export_with_base(&path, "1", None);
+        write_export_with_base(&path, "2", Some("1"));
+        write_export_with_base(&path, "3", Some("1"));
+        write_export_with_base(&path, "4", Some("2"));
+        std::fs::write(path.join("5.index.json"), b"invalid").unwrap();
+        let mut increments = export_increments(&path, "1").await.unwrap();
+        increments.sort();
+        assert_eq!(increments, vec!["2".to_string(), "3".to_string()]);
+        assert!(export_increments(&path, "4").await.unwrap().is_empty());
+        assert!(
+            export_increments(&path.join("missing"), "1")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_export_cascades_to_increments() {
+        let path = testdir!();
+        write_export_with_base(&path, "1", None);
+        write_export_with_base(&path, "2", Some("1"));
+        write_export_with_base(&path, "3", Some("2"));
+        write_export_with_base(&path, "4", None);
+        assert!(delete_export(&path, "2".to_string()).await.unwrap());
+        for id in ["2", "3"] {
+            assert!(!path.join(format!("{id}.tar")).exists());
+            assert!(!export_index_path(&path, id).exists());
+        }
+        for id in ["1", "4"] {
+            assert!(path.join(format!("{id}.tar")).exists());
+            assert!(export_index_path(&path, id).exists());
+        }
+        assert!(delete_export(&path, "1".to_string()).await.unwrap());
+        assert!(!path.join("1.tar").exists());
+    }
+
     #[tokio::test]
     async fn delete_export_err() {
         const EXPORT_ID: &str = "1234tasf236zt";
@@ -1,40 +1,86 @@
+use crate::forge::path::PathExtension;
+use crate::forge::time::SystemTimeExt;
+use crate::jeweler::app::AppDeployment;
 use crate::jeweler::deployment::DeploymentId;
 use crate::jeweler::gem::app::{App, AppDeserializable, try_create_app, try_create_legacy_app};
 use crate::jeweler::gem::deployment::{Deployment, SerializedDeployment};
 use crate::jeweler::gem::instance::compose::ComposeInstance;
 use crate::jeweler::gem::instance::docker::DockerInstance;
+use crate::jeweler::gem::instance::docker::config::InstancePortMapping;
 use crate::jeweler::gem::instance::{
     CreateInstanceError, Instance, InstanceDeserializable, InstanceId,
 };
 use crate::jeweler::gem::manifest::AppManifest;
+use crate::jeweler::network::{CreateNetworkError, NetworkConfig};
 use crate::legacy;
-use crate::lore::Lore;
+use crate::lore::{ImportLore, ImportLoreRef, Lore};
 use crate::quest::SyncQuest;
+use crate::relic::async_flecstract::{EntryTarget, list_entries, stream_entries};
 use crate::relic::device::usb::UsbDeviceReader;
+use crate::relic::encryption;
+use crate::relic::encryption::{Decryption, DecryptionError, decrypt_file, is_encrypted};
+use crate::relic::integrity::{ChecksumError, Checksums, Signature, verify_directory_checksums};
+use crate::relic::system::available_space;
 use crate::relic::system::info::try_create_system_info;
+use crate::sorcerer::exportius::manifest;
+use crate::sorcerer::exportius::manifest::v3::NetworkKey;
 use crate::sorcerer::exportius::manifest::{Manifest, v2, v3};
 use crate::sorcerer::importius::{
-    ImportAppError, ImportDeploymentError, ImportError, ImportInstanceError, ImportManifestError,
+    ConflictPolicy, DeferredImportContent, ImportAppError, ImportDeploymentError, ImportError,
+    ImportIncompatibility, ImportInspection, ImportInstanceError, ImportManifestError,
+    ImportNetworkError, ImportOptions, ImportPlan, ImportSizes, InspectImportError,
     ReadImportManifestError,
 };
 use crate::vault::pouch::deployment::DefaultDeployments;
 use crate::vault::pouch::{AppKey, Pouch};
 use crate::vault::{GrabbedPouches, Vault, pouch};
+use futures_util::FutureExt;
 use futures_util::future::{BoxFuture, join_all};
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::net::Ipv4Addr;
+use std::path::{Component, Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::oneshot::error::RecvError;
 use tracing::{debug, error, warn};
 
+/// Held shared while importing into the vault and exclusively while pruning volumes, volumes of
+/// instances which are imported but not yet inserted into the vault are not seen as orphaned
+pub static IMPORT_LOCK: tokio::sync::RwLock<()> = tokio::sync::RwLock::const_new(());
+
+/// Manifests larger than this are not read during inspection, see [inspect_import_archive]
+const MAX_MANIFEST_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Reads, verifies and validates the manifest of the import in `src`. The checksums of the
+/// `deferred` files, which are not yet extracted, are verified once they are streamed, see
+/// [stream_import_content].
 pub async fn read_import_manifest(
     quest: SyncQuest,
+    lore: ImportLoreRef,
     src: PathBuf,
+    deferred: Vec<String>,
 ) -> Result<Manifest, ReadImportManifestError> {
-    let manifest_path = src.join("manifest.json");
-    let manifest = tokio::fs::read(&manifest_path).await?;
-    let manifest: Manifest = serde_json::from_slice(&manifest)?;
+    let manifest_path = src.join(manifest::FILE_NAME);
+    let manifest_content = tokio::fs::read(&manifest_path).await?;
+    let manifest: Manifest = serde_json::from_slice(&manifest_content)?;
+    let checksums = match &manifest {
+        Manifest::V2(_) => None,
+        Manifest::V3(manifest) => manifest.checksums.clone().map(|mut checksums| {
+            checksums.retain(|path, _| !deferred.contains(path));
+            checksums
+        }),
+    };
+    let result = quest
+        .lock()
+        .await
+        .create_sub_quest("Verify import integrity", |_quest| {
+            let src = src.clone();
+            async move { verify_import_integrity(lore, &manifest_content, checksums, &src).await }
+        })
+        .await
+        .2;
+    result.await?;
     let manifest = quest
         .lock()
         .await
@@ -46,6 +92,438 @@ pub async fn read_import_manifest(
     manifest.await
 }
 
+/// Verifies the signature of the import manifest and the checksums of all files in the import
+/// according to the import lore
+async fn verify_import_integrity(
+    lore: ImportLoreRef,
+    manifest_content: &[u8],
+    checksums: Option<Checksums>,
+    src: &Path,
+) -> Result<(), ReadImportManifestError> {
+    let lore = lore.as_ref().as_ref();
+    verify_import_signature(lore, manifest_content, src).await?;
+    match checksums {
+        Some(checksums) => {
+            verify_directory_checksums(
+                src,
+                &[manifest::FILE_NAME, manifest::SIGNATURE_FILE_NAME],
+                &checksums,
+            )
+            .await?
+        }
+        None if lore.require_signature => return Err(ReadImportManifestError::MissingChecksums),
+        None => warn!("Import manifest contains no checksums, skipping integrity check"),
+    }
+    Ok(())
+}
+
+/// Verifies the signature of the import manifest in `src` according to the import lore
+pub async fn verify_import_signature(
+    lore: &ImportLore,
+    manifest_content: &[u8],
+    src: &Path,
+) -> Result<(), ReadImportManifestError> {
+    let signature_path = src.join(manifest::SIGNATURE_FILE_NAME);
+    if tokio::fs::try_exists(&signature_path).await? {
+        let signature: Signature =
+            serde_json::from_slice(&tokio::fs::read(&signature_path).await?)?;
+        if lore.trusted_keys.is_empty() && !lore.require_signature {
+            // The key embedded in the signature is not trusted on its own, without trusted keys
+            // the import is treated like an unsigned one
+            warn!(
+                "Import manifest is signed with {}, but no trusted keys are configured, ignoring signature",
+                signature.public_key
+            );
+        } else {
+            signature.verify(manifest_content, &lore.trusted_keys)?;
+        }
+    } else if lore.require_signature {
+        return Err(ReadImportManifestError::MissingSignature);
+    }
+    Ok(())
+}
+
+/// Streams the deferred content of the import in `src` from its archive, see
+/// [DeferredImportContent]. Images of apps with exactly one deployment which are not yet
+/// installed are loaded directly from the archive while their checksum is calculated, all other
+/// content of the selected apps and instances is extracted to `src` and content of apps and
+/// instances which are not imported is skipped. If streaming fails or a checksum does not match
+/// the manifest the loaded images are removed again and the import fails before any app is
+/// created. Returns the apps whose images were loaded.
+pub async fn stream_import_content(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    lore: Arc<Lore>,
+    manifest: &v3::Manifest,
+    content: DeferredImportContent,
+    src: PathBuf,
+) -> Result<HashSet<AppKey>, ImportError> {
+    let image_deployments = image_deployments(quest.clone(), &vault, manifest, &src).await?;
+    let app_directories: HashMap<String, &AppKey> = manifest
+        .contents
+        .apps
+        .iter()
+        .map(|app_key| (app_directory_name(app_key), app_key))
+        .collect();
+    let instance_directories: HashSet<String> = manifest
+        .contents
+        .instances
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let mut loaded_apps = HashSet::new();
+    let mut relative_paths = HashMap::new();
+    let mut targets = HashMap::new();
+    let mut required_space = 0;
+    for (relative_path, entry) in content.entries {
+        if !Path::new(&relative_path).is_contained_relative() {
+            return Err(ImportError::Stream(anyhow::anyhow!(
+                "Invalid entry path {:?}",
+                entry.path
+            )));
+        }
+        let target = match relative_path.split('/').collect::<Vec<_>>()[..] {
+            ["apps", app_directory, file] => match app_directories.get(app_directory) {
+                None => continue,
+                Some(app_key) => match image_deployments.get(*app_key) {
+                    Some((deployment, _)) => {
+                        loaded_apps.insert((*app_key).clone());
+                        image_consumer(
+                            quest.clone(),
+                            lore.clone(),
+                            deployment.clone(),
+                            file.to_string(),
+                        )
+                    }
+                    None => EntryTarget::File(src.join(&relative_path)),
+                },
+            },
+            ["apps", app_directory, ..] if !app_directories.contains_key(app_directory) => {
+                continue;
+            }
+            ["instances", instance, ..] if !instance_directories.contains(instance) => continue,
+            _ => EntryTarget::File(src.join(&relative_path)),
+        };
+        if matches!(target, EntryTarget::File(_)) {
+            required_space += entry.size;
+        }
+        relative_paths.insert(entry.path.clone(), relative_path);
+        targets.insert(entry.path, target);
+    }
+    let available_space = available_space(&src).map_err(ImportError::Stream)?;
+    if required_space > available_space {
+        return Err(ImportError::InsufficientSpace {
+            required: required_space,
+            available: available_space,
+        });
+    }
+    let result = match stream_entries(&content.archive_path, targets).await {
+        Err(e) => Err(ImportError::Stream(e)),
+        Ok(checksums) => match &manifest.checksums {
+            None => Ok(()),
+            Some(expected) => {
+                let checksums = checksums
+                    .into_iter()
+                    .filter_map(|(path, checksum)| {
+                        relative_paths
+                            .remove(&path)
+                            .map(|relative_path| (relative_path, checksum))
+                    })
+                    .collect();
+                verify_streamed_checksums(expected, &checksums)
+                    .map_err(|e| ReadImportManifestError::from(e).into())
+            }
+        },
+    };
+    if let Err(e) = result {
+        remove_loaded_images(quest, &image_deployments, &loaded_apps).await;
+        return Err(e);
+    }
+    Ok(loaded_apps)
+}
+
+/// Removes the images of the `loaded_apps` which were loaded by [stream_import_content] before
+/// the import failed
+async fn remove_loaded_images(
+    quest: SyncQuest,
+    image_deployments: &HashMap<AppKey, (Deployment, AppManifest)>,
+    loaded_apps: &HashSet<AppKey>,
+) {
+    for app_key in loaded_apps {
+        let Some((deployment, manifest)) = image_deployments.get(app_key) else {
+            continue;
+        };
+        let deployment = deployment.clone();
+        let manifest = manifest.clone();
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest(
+                format!("Remove streamed image of {app_key}"),
+                |quest| async move { deployment.uninstall_app(quest, manifest).await },
+            )
+            .await
+            .2;
+        if let Err(e) = result.await {
+            warn!("Could not remove streamed image of {app_key}: {e}");
+        }
+    }
+}
+
+/// Deployments the images of the imported apps can be loaded into directly together with the
+/// manifest of the app, i.e. the deployment of every imported app with exactly one deployment
+/// which is not yet installed. Images which were installed before the import are never loaded
+/// directly, so removing the images of a failed import does not remove them.
+async fn image_deployments(
+    quest: SyncQuest,
+    vault: &Vault,
+    manifest: &v3::Manifest,
+    src: &Path,
+) -> Result<HashMap<AppKey, (Deployment, AppManifest)>, ImportError> {
+    let mut image_deployments = HashMap::new();
+    for app_key in &manifest.contents.apps {
+        // Invalid apps are not loaded directly, their import fails in [import_directory]
+        let Ok(app) = read_app(&src.join("apps").join(app_directory_name(app_key)), app_key).await
+        else {
+            continue;
+        };
+        let [data] = &app.deployments[..] else {
+            continue;
+        };
+        let Ok(app_manifest) =
+            import_manifest(quest.clone(), app_key.clone(), src.join("apps")).await
+        else {
+            continue;
+        };
+        let deployment = if manifest.contents.deployments.contains(&data.deployment_id) {
+            Some(
+                import_deployment(
+                    quest.clone(),
+                    data.deployment_id.clone(),
+                    src.join("deployments"),
+                )
+                .await?,
+            )
+        } else {
+            vault
+                .reservation()
+                .reserve_deployment_pouch()
+                .grab()
+                .await
+                .deployment_pouch
+                .as_ref()
+                .expect("Vault reservations should never fail")
+                .gems()
+                .get(&data.deployment_id)
+                .cloned()
+        };
+        let Some(deployment) = deployment else {
+            continue;
+        };
+        if !matches!(
+            deployment
+                .is_app_installed(quest.clone(), app_manifest.clone())
+                .await,
+            Ok(false)
+        ) {
+            continue;
+        }
+        image_deployments.insert(app_key.clone(), (deployment, app_manifest));
+    }
+    Ok(image_deployments)
+}
+
+fn image_consumer(
+    quest: SyncQuest,
+    lore: Arc<Lore>,
+    deployment: Deployment,
+    file: String,
+) -> EntryTarget {
+    EntryTarget::Consumer(Box::new(move |image| {
+        async move {
+            let result = quest
+                .lock()
+                .await
+                .create_sub_quest(format!("Load image {file}"), |quest| async move {
+                    deployment
+                        .import_app_image(quest, lore, Box::new(image))
+                        .await
+                })
+                .await
+                .2;
+            result.await
+        }
+        .boxed()
+    }))
+}
+
+/// Verifies the checksums of streamed files, in contrast to [verify_directory_checksums] files
+/// which were not streamed are not considered missing
+fn verify_streamed_checksums(
+    expected: &Checksums,
+    streamed: &Checksums,
+) -> Result<(), ChecksumError> {
+    for (path, checksum) in streamed {
+        let Some(expected) = expected.get(path) else {
+            return Err(ChecksumError::Unlisted(path.clone()));
+        };
+        if !checksum.eq_ignore_ascii_case(expected) {
+            return Err(ChecksumError::Mismatch(path.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Decrypts the import archive with the supplied key material and the key material configured
+/// in [ImportLore]
+pub async fn decrypt_import_archive(
+    lore: &ImportLore,
+    archive_path: &Path,
+    decrypted_path: &Path,
+    mut decryption: Decryption,
+) -> Result<(), DecryptionError> {
+    let configured = Decryption::from_files(
+        lore.decryption_passphrase_path.as_deref(),
+        &lore.decryption_identity_paths,
+    )
+    .await?;
+    decryption.extend(configured);
+    decrypt_file(
+        archive_path.to_path_buf(),
+        decrypted_path.to_path_buf(),
+        decryption,
+    )
+    .await
+}
+
+/// Describes the content of the import archive by reading only its manifest, nothing is
+/// extracted. Encrypted archives are temporarily decrypted to the import directory.
+pub async fn inspect_import_archive(
+    lore: &ImportLore,
+    archive_path: &Path,
+    decryption: Decryption,
+) -> Result<ImportInspection, InspectImportError> {
+    if !is_encrypted(archive_path).await? {
+        return inspect_archive(archive_path, false).await;
+    }
+    let file_name = archive_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let file_name = file_name
+        .strip_suffix(&format!(".{}", encryption::EXTENSION))
+        .unwrap_or(&file_name);
+    tokio::fs::create_dir_all(&lore.base_path).await?;
+    let decrypted_path = lore.base_path.join(format!(
+        "inspect_{}_{file_name}",
+        std::time::SystemTime::now().unix_millis()
+    ));
+    let result = match decrypt_import_archive(lore, archive_path, &decrypted_path, decryption).await
+    {
+        Ok(()) => inspect_archive(&decrypted_path, true).await,
+        Err(error) => Err(InspectImportError::Decrypt {
+            import: archive_path.to_path_buf(),
+            error,
+        }),
+    };
+    match tokio::fs::remove_file(&decrypted_path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            warn!("Could not remove decrypted archive {decrypted_path:?}: {e}")
+        }
+        _ => {}
+    }
+    result
+}
+
+/// Manifests are expected in the root of the archive or in a single top level directory, see
+/// [crate::sorcerer::importius]
+fn is_manifest_candidate(path: &Path, size: u64) -> bool {
+    size <= MAX_MANIFEST_SIZE
+        && path.file_name() == Some(manifest::FILE_NAME.as_ref())
+        && normal_components(path).len() <= 2
+}
+
+fn normal_components(path: &Path) -> Vec<&std::ffi::OsStr> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+async fn inspect_archive(
+    archive_path: &Path,
+    encrypted: bool,
+) -> Result<ImportInspection, InspectImportError> {
+    let entries = list_entries(archive_path, is_manifest_candidate)
+        .await
+        .map_err(|error| InspectImportError::Read {
+            import: archive_path.to_path_buf(),
+            error,
+        })?;
+    // The manifest closest to the root determines where the export data is located
+    let (data_path, manifest) = entries
+        .iter()
+        .filter_map(|entry| {
+            let content = entry.content.as_ref()?;
+            let components = normal_components(&entry.path);
+            Some((components[..components.len() - 1].to_vec(), content))
+        })
+        .min_by_key(|(data_path, _)| data_path.len())
+        .ok_or(InspectImportError::MissingManifest)?;
+    let manifest: Manifest = serde_json::from_slice(manifest)?;
+    let instances = manifest.instance_ids();
+    let mut sizes = ImportSizes::default();
+    let mut signed = false;
+    for entry in &entries {
+        let components = normal_components(&entry.path);
+        let Some(relative) = components.strip_prefix(&data_path[..]) else {
+            sizes.other += entry.size;
+            sizes.total += entry.size;
+            continue;
+        };
+        let size = match relative.first().and_then(|name| name.to_str()) {
+            Some("apps") => &mut sizes.apps,
+            Some("instances") => &mut sizes.instances,
+            Some("deployments") | Some("networks") => &mut sizes.deployments,
+            Some(manifest::SIGNATURE_FILE_NAME) if relative.len() == 1 => {
+                signed = true;
+                &mut sizes.other
+            }
+            _ => &mut sizes.other,
+        };
+        *size += entry.size;
+        sizes.total += entry.size;
+    }
+    Ok(match manifest {
+        Manifest::V2(manifest) => ImportInspection {
+            schema_version: "2.0.0".to_string(),
+            apps: manifest.contents.apps,
+            instances,
+            deployments: Vec::new(),
+            device: manifest.device,
+            core_version: manifest.version.core,
+            base: None,
+            signed,
+            encrypted,
+            sizes,
+        },
+        Manifest::V3(manifest) => ImportInspection {
+            schema_version: "3.0.0".to_string(),
+            apps: manifest.contents.apps,
+            instances,
+            deployments: manifest.contents.deployments,
+            device: manifest.device,
+            core_version: manifest.version.core,
+            base: manifest.base,
+            signed,
+            encrypted,
+            sizes,
+        },
+    })
+}
+
 async fn merged_apps(vault: &Arc<Vault>, new_apps: &pouch::app::Gems) -> Arc<pouch::app::Gems> {
     let merged_apps: HashMap<_, _> = vault
         .reservation()
@@ -103,14 +581,34 @@ async fn merged_manifests(
     Arc::new(merged_manifests)
 }
 
+/// Decisions which are taken before the content of an import directory is imported
+#[derive(Debug, Default, Clone)]
+pub struct ImportDirectoryConfig {
+    /// See [ImportPlan::new_instance_ids]
+    pub new_instance_ids: HashMap<InstanceId, InstanceId>,
+    /// See [ImportOptions::parent_adapters]
+    pub parent_adapters: HashMap<String, String>,
+    /// Apps whose images were already loaded while the import was streamed, see
+    /// [stream_import_content]
+    pub loaded_apps: HashSet<AppKey>,
+}
+
 pub async fn import_directory(
     quest: SyncQuest,
     vault: Arc<Vault>,
     lore: Arc<Lore>,
     manifest: v3::Manifest,
+    config: ImportDirectoryConfig,
     src: PathBuf,
     dst: PathBuf,
 ) -> Result<(), ImportError> {
+    let _import_guard = IMPORT_LOCK.read().await;
+    let ImportDirectoryConfig {
+        new_instance_ids,
+        parent_adapters,
+        loaded_apps,
+    } = config;
+    let renewal = Arc::new(InstanceRenewal::new(&vault, new_instance_ids).await);
     let deployments = quest
         .lock()
         .await
@@ -137,6 +635,7 @@ pub async fn import_directory(
         manifest.contents.apps.clone(),
         lore.clone(),
         apps_input_receiver,
+        Arc::new(loaded_apps),
         src.join("apps"),
     )
     .await;
@@ -151,6 +650,7 @@ pub async fn import_directory(
         manifest.contents.instances,
         instances_input_receiver,
         config,
+        renewal,
     )
     .await;
     // Deployments and manifests can be imported concurrently as there are no dependencies
@@ -161,8 +661,25 @@ pub async fn import_directory(
     // e.g. a flecsport with an app but no deployments or manifests
     let merged_deployments = merged_deployments(&vault, &new_deployments).await;
     let merged_manifests = merged_manifests(&vault, &new_manifests).await;
+    // Networks have to exist before instances are connected to them on start
+    let networks = quest
+        .lock()
+        .await
+        .create_sub_quest("Import networks", |quest| {
+            import_networks(
+                quest,
+                merged_deployments.clone(),
+                manifest.contents.networks,
+                parent_adapters,
+                src.join("networks"),
+            )
+        })
+        .await
+        .2;
     _ = apps_input_sender.send((merged_manifests.clone(), merged_deployments.clone()));
-    let new_apps = apps.await?;
+    let (new_apps, networks) = tokio::join!(apps, networks);
+    networks?;
+    let new_apps = new_apps?;
 
     // We need to take all apps into account, not just the new ones
     // e.g. a flecsport with an instance but without the app
@@ -182,6 +699,7 @@ pub async fn import_legacy_directory<U: UsbDeviceReader + 'static>(
     src: PathBuf,
     dst: PathBuf,
 ) -> Result<(), ImportError> {
+    let _import_guard = IMPORT_LOCK.read().await;
     let default_docker_deployments = vault
         .reservation()
         .reserve_deployment_pouch()
@@ -272,6 +790,91 @@ pub async fn import_deployment(
     Ok(deployment.into())
 }
 
+/// Creates the networks in their deployment, the parent adapters are replaced according to
+/// `parent_adapters`. Networks which already exist are kept as they are, networks of unknown
+/// deployments are skipped.
+pub async fn import_networks(
+    quest: SyncQuest,
+    deployments: Arc<pouch::deployment::Gems>,
+    networks: Vec<NetworkKey>,
+    parent_adapters: HashMap<String, String>,
+    path: PathBuf,
+) -> Result<(), ImportNetworkError> {
+    let parent_adapters = Arc::new(parent_adapters);
+    let mut results = Vec::new();
+    {
+        let mut quest = quest.lock().await;
+        for key in networks {
+            let Some(deployment) = deployments.get(&key.deployment).cloned() else {
+                warn!(
+                    "Skipping import of network {} as deployment {} does not exist",
+                    key.network, key.deployment
+                );
+                continue;
+            };
+            let result = quest
+                .create_sub_quest(format!("Import network {}", key.network), |quest| {
+                    import_network(
+                        quest,
+                        deployment,
+                        key,
+                        parent_adapters.clone(),
+                        path.clone(),
+                    )
+                })
+                .await
+                .2;
+            results.push(result);
+        }
+    }
+    join_all(results).await.into_iter().collect()
+}
+
+pub async fn import_network(
+    quest: SyncQuest,
+    deployment: Deployment,
+    key: NetworkKey,
+    parent_adapters: Arc<HashMap<String, String>>,
+    path: PathBuf,
+) -> Result<(), ImportNetworkError> {
+    if !Path::new(&key.deployment).is_contained_relative()
+        || !Path::new(&key.network).is_contained_relative()
+    {
+        return Err(ImportNetworkError::InvalidKey {
+            deployment: key.deployment,
+            network: key.network,
+        });
+    }
+    let network_path = path
+        .join(&key.deployment)
+        .join(format!("{}.json", key.network));
+    let network = tokio::fs::read(&network_path).await?;
+    let mut config: NetworkConfig = serde_json::from_slice(&network)?;
+    let original_parent = config.parent_adapter.clone();
+    if config.remap_parent_adapter(&parent_adapters) {
+        debug!(
+            "Replaced parent adapter {original_parent:?} of network {} with {:?}",
+            key.network, config.parent_adapter
+        );
+    }
+    match deployment.create_network(quest, config).await {
+        Ok(_) | Err(CreateNetworkError::ExactNetworkExists(_)) => Ok(()),
+        Err(CreateNetworkError::DifferentNetworkExists(_)) => {
+            warn!(
+                "Network {} already exists in deployment {} with a different config, \
+                keeping the existing network",
+                key.network, key.deployment
+            );
+            Ok(())
+        }
+        Err(error) => Err(ImportNetworkError::Create {
+            deployment: key.deployment,
+            network: key.network,
+            error,
+        }),
+    }
+}
+
 pub async fn import_manifests(
     quest: SyncQuest,
     app_keys: Vec<AppKey>,
@@ -379,6 +982,7 @@ async fn import_apps_quest(
         Arc<pouch::manifest::Gems>,
         Arc<pouch::deployment::Gems>,
     )>,
+    loaded_apps: Arc<HashSet<AppKey>>,
     path: PathBuf,
 ) -> BoxFuture<'static, Result<HashMap<AppKey, App>, RecvError>> {
     quest
@@ -386,7 +990,16 @@ async fn import_apps_quest(
         .await
         .create_sub_quest("Import apps", |quest| async move {
             let (manifests, deployments) = input_recv.await?;
-            Ok(import_apps(quest, app_keys, manifests, deployments, lore, path).await)
+            Ok(import_apps(
+                quest,
+                app_keys,
+                manifests,
+                deployments,
+                lore,
+                loaded_apps,
+                path,
+            )
+            .await)
         })
         .await
         .2
@@ -420,6 +1033,7 @@ pub async fn import_apps(
     manifests: Arc<pouch::manifest::Gems>,
     deployments: Arc<pouch::deployment::Gems>,
     lore: Arc<Lore>,
+    loaded_apps: Arc<HashSet<AppKey>>,
     path: PathBuf,
 ) -> pouch::app::Gems {
     let mut results = Vec::new();
@@ -434,6 +1048,7 @@ pub async fn import_apps(
                         manifests.clone(),
                         deployments.clone(),
                         lore.clone(),
+                        loaded_apps.contains(app_key),
                         path.clone(),
                     )
                 })
@@ -456,23 +1071,37 @@ pub async fn import_apps(
     apps
 }
 
+/// Creates the app from the import, its images are loaded unless `image_loaded` is set
 pub async fn import_app(
     quest: SyncQuest,
     app_key: AppKey,
     manifests: Arc<pouch::manifest::Gems>,
     deployments: Arc<pouch::deployment::Gems>,
     lore: Arc<Lore>,
+    image_loaded: bool,
     path: PathBuf,
 ) -> Result<App, ImportAppError> {
-    let path = path.join(format!("{}_{}", app_key.name, app_key.version));
-    let app_path = path.join(format!("{}_{}.json", app_key.name, app_key.version));
-    let app = tokio::fs::read(&app_path).await?;
-    let app: AppDeserializable = serde_json::from_slice(&app)?;
+    let path = path.join(app_directory_name(&app_key));
+    let app = read_app(&path, &app_key).await?;
     let app = try_create_app(app, &manifests, &deployments)?;
-    app.import(quest, lore, path).await?;
+    if image_loaded {
+        debug!("Image of {app_key} was loaded while streaming the import");
+    } else {
+        app.import(quest, lore, path).await?;
+    }
     Ok(app)
 }
 
+fn app_directory_name(app_key: &AppKey) -> String {
+    format!("{}_{}", app_key.name, app_key.version)
+}
+
+async fn read_app(path: &Path, app_key: &AppKey) -> Result<AppDeserializable, ImportAppError> {
+    let app_path = path.join(format!("{}.json", app_directory_name(app_key)));
+    let app = tokio::fs::read(&app_path).await?;
+    Ok(serde_json::from_slice(&app)?)
+}
+
 pub async fn import_legacy_apps(
     quest: SyncQuest,
     app_keys: Vec<AppKey>,
@@ -660,19 +1289,24 @@ pub async fn import_legacy_instance<U: UsbDeviceReader>(
         (
             AppManifest::Single(manifest),
             DefaultDeployments {
-                docker: Some(Deployment::Docker(deployment)),
+                docker: Some(deployment),
                 ..
             },
-        ) => Instance::Docker(
-            DockerInstance::try_create_from_legacy(
-                lore,
-                instance,
-                usb_device_reader.as_ref(),
-                manifest,
-                deployment,
+        ) => {
+            let Some(deployment) = deployment.as_docker_deployment() else {
+                return Err(CreateInstanceError::NoFittingDeployment.into());
+            };
+            Instance::Docker(
+                DockerInstance::try_create_from_legacy(
+                    lore,
+                    instance,
+                    usb_device_reader.as_ref(),
+                    manifest,
+                    deployment,
+                )
+                .await?,
             )
-            .await?,
-        ),
+        }
         (
             AppManifest::Multi(manifest),
             DefaultDeployments {
@@ -697,13 +1331,23 @@ async fn import_instances_quest(
         Arc<pouch::app::Gems>,
     )>,
     config: ImportInstanceConfig,
+    renewal: Arc<InstanceRenewal>,
 ) -> BoxFuture<'static, Result<HashMap<InstanceId, Instance>, RecvError>> {
     quest
         .lock()
         .await
         .create_sub_quest("Import instances", |quest| async move {
             let (manifests, deployments, apps) = input_recv.await?;
-            Ok(import_instances(quest, instances, manifests, deployments, apps, config).await)
+            Ok(import_instances(
+                quest,
+                instances,
+                manifests,
+                deployments,
+                apps,
+                config,
+                renewal,
+            )
+            .await)
         })
         .await
         .2
@@ -716,6 +1360,7 @@ pub async fn import_instances(
     deployments: Arc<pouch::deployment::Gems>,
     apps: Arc<pouch::app::Gems>,
     config: ImportInstanceConfig,
+    renewal: Arc<InstanceRenewal>,
 ) -> pouch::instance::Gems {
     let mut results = Vec::new();
     {
@@ -730,6 +1375,7 @@ pub async fn import_instances(
                         apps.clone(),
                         *instance_id,
                         config.clone(),
+                        renewal.clone(),
                     )
                 })
                 .await
@@ -742,7 +1388,7 @@ pub async fn import_instances(
         match instance {
             Err(e) => error!("Failed to import instance {instance_id}: {e}"),
             Ok(instance) => {
-                if let Some(replaced_instance) = instances.insert(instance_id, instance) {
+                if let Some(replaced_instance) = instances.insert(instance.id(), instance) {
                     warn!("Replaced instance {} during import", replaced_instance.id())
                 }
             }
@@ -758,22 +1404,81 @@ pub async fn import_instance(
     apps: Arc<pouch::app::Gems>,
     id: InstanceId,
     ImportInstanceConfig { lore, src, dst }: ImportInstanceConfig,
+    renewal: Arc<InstanceRenewal>,
 ) -> Result<Instance, ImportInstanceError> {
     let src = src.join(id.to_string());
-    let dst = dst.join(id.to_string());
-    let instance_path = src.join("instance.json");
-    let instance = tokio::fs::read(&instance_path).await?;
-    let instance: InstanceDeserializable = serde_json::from_slice(&instance)?;
+    let instance = read_instance(&src).await?;
     if !apps.contains_key(instance.app_key()) {
         return Err(ImportInstanceError::AppNotPresent(
             instance.app_key().clone(),
         ));
     }
     let mut instance = Instance::try_create_with_state(lore, instance, &manifests, &deployments)?;
+    if let Some(new_id) = renewal.new_ids.get(&id) {
+        let Instance::Docker(docker_instance) = &mut instance else {
+            return Err(
+                anyhow::anyhow!("Only docker instances can be imported with a new id").into(),
+            );
+        };
+        let mut reserved = renewal.reserved.lock().await;
+        let (taken_port_mappings, unavailable_addresses) = &mut *reserved;
+        docker_instance
+            .renew_imported_id(
+                *new_id,
+                &src.join("volumes"),
+                taken_port_mappings,
+                unavailable_addresses,
+            )
+            .await?;
+    }
+    let dst = dst.join(instance.id().to_string());
     instance.import(quest, src, dst).await?;
     Ok(instance)
 }
 
+async fn read_instance(src: &Path) -> Result<InstanceDeserializable, ImportInstanceError> {
+    let instance = tokio::fs::read(src.join("instance.json")).await?;
+    Ok(serde_json::from_slice(&instance)?)
+}
+
+/// Instances which are imported with a new id instead of replacing an existing instance, see
+/// [DockerInstance::renew_imported_id]
+#[derive(Default)]
+pub struct InstanceRenewal {
+    /// Maps the id in the import to the new id
+    pub new_ids: HashMap<InstanceId, InstanceId>,
+    /// Host ports and ipv4 addresses in use, shared by all renewed instances of an import
+    reserved: tokio::sync::Mutex<(Vec<InstancePortMapping>, HashSet<Ipv4Addr>)>,
+}
+
+impl InstanceRenewal {
+    pub async fn new(vault: &Vault, new_ids: HashMap<InstanceId, InstanceId>) -> Self {
+        if new_ids.is_empty() {
+            return Self::default();
+        }
+        let grab = vault.reservation().reserve_instance_pouch().grab().await;
+        let instances = grab
+            .instance_pouch
+            .as_ref()
+            .expect("Vault reservations should never fail");
+        let taken_port_mappings = instances
+            .gems()
+            .values()
+            .filter_map(|instance| match instance {
+                Instance::Docker(instance) => Some(instance.config.port_mapping.clone()),
+                Instance::Compose(_) => None,
+            })
+            .collect();
+        Self {
+            new_ids,
+            reserved: tokio::sync::Mutex::new((
+                taken_port_mappings,
+                instances.unavailable_ipv4_addresses(),
+            )),
+        }
+    }
+}
+
 pub async fn validate_import(
     manifest: Manifest,
     path: PathBuf,
@@ -784,23 +1489,10 @@ pub async fn validate_import(
     })
 }
 
-fn validate_architecture_match(arch: &str) -> Result<(), ReadImportManifestError> {
-    let sys_info = try_create_system_info()?;
-    if sys_info.arch != arch {
-        Err(ReadImportManifestError::ArchitectureMismatch {
-            device_arch: sys_info.arch,
-            import_arch: arch.to_string(),
-        })
-    } else {
-        Ok(())
-    }
-}
-
 async fn validate_v2_import(
     manifest: v2::Manifest,
     path: PathBuf,
 ) -> Result<v2::Manifest, ReadImportManifestError> {
-    validate_architecture_match(&manifest.device.sysinfo.arch)?;
     for app_key in &manifest.contents.apps {
         let manifest_path = path.join(format!("apps/{}_{}.json", app_key.name, app_key.version));
         if !tokio::fs::try_exists(&manifest_path).await? {
@@ -817,7 +1509,6 @@ async fn validate_v3_import(
     path: PathBuf,
 ) -> Result<v3::Manifest, ReadImportManifestError> {
     // TODO: Check that everything has unique ids (manifest -> AppKey, app -> AppKey, instance -> InstanceId, deployment -> DeploymentId
-    validate_architecture_match(&manifest.device.sysinfo.arch)?;
     for deployment in &manifest.contents.deployments {
         if !tokio::fs::try_exists(path.join(format!("deployments/{deployment}.json"))).await? {
             return Err(ReadImportManifestError::Invalid(anyhow::anyhow!(
@@ -862,6 +1553,226 @@ async fn validate_v3_import(
     Ok(manifest)
 }
 
+/// Returns the entries of `available` which are selected, everything is selected if there is no
+/// selection. Selecting entries which are not available is an error.
+fn select<T: PartialEq + Display>(
+    kind: &str,
+    available: Vec<T>,
+    selection: Option<&[T]>,
+) -> Result<Vec<T>, ImportError> {
+    let Some(selection) = selection else {
+        return Ok(available);
+    };
+    if let Some(missing) = selection.iter().find(|entry| !available.contains(entry)) {
+        return Err(ImportError::InvalidSelection(format!(
+            "{kind} {missing} is not contained in the import"
+        )));
+    }
+    Ok(available
+        .into_iter()
+        .filter(|entry| selection.contains(entry))
+        .collect())
+}
+
+fn is_flecs_version_sufficient(minimum_version: &str, flecs_version: &str) -> bool {
+    match (
+        semver::Version::parse(minimum_version),
+        semver::Version::parse(flecs_version),
+    ) {
+        (Ok(minimum_version), Ok(flecs_version)) => flecs_version >= minimum_version,
+        (Err(e), _) | (_, Err(e)) => {
+            warn!(
+                "Could not compare minimum FLECS version {minimum_version} to {flecs_version}: {e}"
+            );
+            true
+        }
+    }
+}
+
+async fn app_incompatibility(
+    manifest_path: &Path,
+    app_key: &AppKey,
+) -> Result<Option<ImportIncompatibility>, ImportError> {
+    let manifest = read_manifest(manifest_path).await?;
+    Ok(match manifest.minimum_flecs_version() {
+        Some(minimum_version)
+            if !is_flecs_version_sufficient(minimum_version, crate::lore::FLECS_VERSION) =>
+        {
+            Some(ImportIncompatibility::FlecsVersion {
+                app_key: app_key.clone(),
+                minimum_version: minimum_version.to_string(),
+                flecs_version: crate::lore::FLECS_VERSION.to_string(),
+            })
+        }
+        _ => None,
+    })
+}
+
+/// Determines what importing the export in `src` does with the given options: What is created,
+/// replaced or skipped, which instances get a new id and why the import is incompatible with this
+/// device, if it is
+pub async fn plan_import(
+    vault: Arc<Vault>,
+    manifest: &Manifest,
+    src: &Path,
+    options: &ImportOptions,
+) -> Result<ImportPlan, ImportError> {
+    let (apps, deployments, import_arch) = match manifest {
+        Manifest::V2(manifest) => (
+            manifest.contents.apps.clone(),
+            Vec::new(),
+            &manifest.device.sysinfo.arch,
+        ),
+        Manifest::V3(manifest) => (
+            manifest.contents.apps.clone(),
+            manifest.contents.deployments.clone(),
+            &manifest.device.sysinfo.arch,
+        ),
+    };
+    let instances = manifest.instance_ids();
+    let apps = select("App", apps, options.apps.as_deref())?;
+    let deployments = select("Deployment", deployments, options.deployments.as_deref())?;
+    let selected_instances = select("Instance", instances.clone(), options.instances.as_deref())?;
+    let (existing_apps, existing_instances, existing_deployments) = {
+        let grab = vault
+            .reservation()
+            .reserve_app_pouch()
+            .reserve_instance_pouch()
+            .reserve_deployment_pouch()
+            .grab()
+            .await;
+        let (Some(app_pouch), Some(instance_pouch), Some(deployment_pouch)) = (
+            grab.app_pouch.as_ref(),
+            grab.instance_pouch.as_ref(),
+            grab.deployment_pouch.as_ref(),
+        ) else {
+            unreachable!("Vault reservations should never fail")
+        };
+        (
+            app_pouch.gems().keys().cloned().collect::<HashSet<_>>(),
+            instance_pouch
+                .gems()
+                .keys()
+                .copied()
+                .collect::<HashSet<_>>(),
+            deployment_pouch
+                .gems()
+                .keys()
+                .cloned()
+                .collect::<HashSet<_>>(),
+        )
+    };
+    let policy = options.conflict_policy;
+    let mut plan = ImportPlan::default();
+    for deployment in deployments {
+        if !existing_deployments.contains(&deployment) {
+            plan.create.deployments.push(deployment);
+        } else if policy == ConflictPolicy::Replace {
+            plan.replace.deployments.push(deployment);
+        } else {
+            plan.skip.deployments.push(deployment);
+        }
+    }
+    for app in apps {
+        if !existing_apps.contains(&app) {
+            plan.create.apps.push(app);
+        } else if policy == ConflictPolicy::Replace {
+            plan.replace.apps.push(app);
+        } else {
+            plan.skip.apps.push(app);
+        }
+    }
+    for instance_id in selected_instances {
+        if !existing_instances.contains(&instance_id) {
+            plan.create.instances.push(instance_id);
+            continue;
+        }
+        match policy {
+            ConflictPolicy::Replace => plan.replace.instances.push(instance_id),
+            ConflictPolicy::Skip => plan.skip.instances.push(instance_id),
+            ConflictPolicy::NewInstanceId => {
+                let is_docker_instance = matches!(manifest, Manifest::V3(_))
+                    && matches!(
+                        read_instance(&src.join("instances").join(instance_id.to_string()))
+                            .await
+                            .map_err(|e| ImportError::Plan(e.into()))?,
+                        InstanceDeserializable::Docker(_)
+                    );
+                if is_docker_instance {
+                    let new_id = loop {
+                        let new_id = InstanceId::new_random();
+                        if !existing_instances.contains(&new_id)
+                            && !instances.contains(&new_id)
+                            && !plan.new_instance_ids.values().any(|id| *id == new_id)
+                        {
+                            break new_id;
+                        }
+                    };
+                    plan.new_instance_ids.insert(instance_id, new_id);
+                } else {
+                    plan.skip.instances.push(instance_id);
+                }
+            }
+        }
+    }
+    let device_arch = try_create_system_info().map_err(ImportError::Plan)?.arch;
+    if device_arch != *import_arch {
+        plan.incompatibilities
+            .push(ImportIncompatibility::Architecture {
+                device_arch,
+                import_arch: import_arch.clone(),
+            });
+    }
+    for app_key in plan.imported_apps() {
+        let manifest_path = match manifest {
+            Manifest::V2(_) => src
+                .join("apps")
+                .join(format!("{}_{}.json", app_key.name, app_key.version)),
+            Manifest::V3(_) => src
+                .join("apps")
+                .join(format!("{}_{}", app_key.name, app_key.version))
+                .join(format!(
+                    "{}_{}.manifest.json",
+                    app_key.name, app_key.version
+                )),
+        };
+        if let Some(incompatibility) = app_incompatibility(&manifest_path, &app_key).await? {
+            plan.incompatibilities.push(incompatibility);
+        }
+    }
+    Ok(plan)
+}
+
+/// Removes everything from the contents of the manifest which is not imported according to the
+/// plan
+pub fn restrict_to_plan(manifest: Manifest, plan: &ImportPlan) -> Manifest {
+    let apps = plan.imported_apps();
+    let instances = plan.imported_instances();
+    match manifest {
+        Manifest::V2(mut manifest) => {
+            manifest.contents.apps.retain(|app| apps.contains(app));
+            manifest.contents.instances.retain(|instance| {
+                InstanceId::from_str(&instance.instance_id)
+                    .map(|id| instances.contains(&id))
+                    .unwrap_or_default()
+            });
+            Manifest::V2(manifest)
+        }
+        Manifest::V3(mut manifest) => {
+            manifest.contents.apps = apps;
+            manifest.contents.instances = instances;
+            manifest.contents.deployments = plan.imported_deployments();
+            // Networks of deployments which are kept are imported as well as they might be
+            // required by imported instances
+            manifest.contents.networks.retain(|network| {
+                manifest.contents.deployments.contains(&network.deployment)
+                    || plan.skip.deployments.contains(&network.deployment)
+            });
+            Manifest::V3(manifest)
+        }
+    }
+}
+
 pub async fn import_to_vault(
     vault: Arc<Vault>,
     deployments: pouch::deployment::Gems,
@@ -917,3 +1828,863 @@ pub async fn import_to_vault(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::deployment::docker::tests::MockedDockerDeployment;
+    use crate::jeweler::network::{Network, NetworkKind};
+    use crate::lore;
+    use crate::quest::Quest;
+    use crate::relic::integrity::{SignatureError, sha256_directory};
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::importius::ImportPlanContents;
+    use crate::vault::pouch::app::tests::{
+        MINIMAL_APP_WITH_INSTANCE_NAME, MINIMAL_APP_WITH_INSTANCE_VERSION, UNKNOWN_APP_NAME,
+    };
+    use crate::vault::pouch::instance::tests::MINIMAL_INSTANCE;
+    use crate::vault::tests::{create_test_vault, create_test_vault_with_deployment};
+    use ed25519_dalek::SigningKey;
+    use testdir::testdir;
+
+    const MANIFEST: &[u8] = br#"{"_schemaVersion":"3.0.0"}"#;
+
+    fn import_lore(trusted_keys: Vec<SigningKey>, require_signature: bool) -> ImportLoreRef {
+        let mut lore = lore::test_lore(testdir!(), &MockVarReader::new());
+        lore.import.trusted_keys = trusted_keys.iter().map(SigningKey::verifying_key).collect();
+        lore.import.require_signature = require_signature;
+        Arc::new(lore)
+    }
+
+    async fn prepare_import(path: &Path, key: Option<&SigningKey>) -> Checksums {
+        tokio::fs::create_dir_all(path.join("apps")).await.unwrap();
+        tokio::fs::write(path.join("apps/app.json"), b"{}")
+            .await
+            .unwrap();
+        tokio::fs::write(path.join(manifest::FILE_NAME), MANIFEST)
+            .await
+            .unwrap();
+        if let Some(key) = key {
+            tokio::fs::write(
+                path.join(manifest::SIGNATURE_FILE_NAME),
+                serde_json::to_vec(&Signature::create(key, MANIFEST)).unwrap(),
+            )
+            .await
+            .unwrap();
+        }
+        sha256_directory(path, &[manifest::FILE_NAME, manifest::SIGNATURE_FILE_NAME])
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn verify_import_integrity_signed_ok() {
+        let path = testdir!().join("import");
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let checksums = prepare_import(&path, Some(&key)).await;
+        verify_import_integrity(
+            import_lore(vec![key], true),
+            MANIFEST,
+            Some(checksums),
+            &path,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_import_integrity_untrusted_key() {
+        let path = testdir!().join("import");
+        let checksums = prepare_import(&path, Some(&SigningKey::from_bytes(&[1; 32]))).await;
+        assert!(matches!(
+            verify_import_integrity(
+                import_lore(vec![SigningKey::from_bytes(&[2; 32])], false),
+                MANIFEST,
+                Some(checksums),
+                &path,
+            )
+            .await,
+            Err(ReadImportManifestError::Signature(
+                SignatureError::UntrustedKey(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_import_integrity_missing_signature() {
+        let path = testdir!().join("import");
+        let checksums = prepare_import(&path, None).await;
+        assert!(matches!(
+            verify_import_integrity(
+                import_lore(Vec::new(), true),
+                MANIFEST,
+                Some(checksums),
+                &path
+            )
+            .await,
+            Err(ReadImportManifestError::MissingSignature)
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_import_integrity_tampered() {
+        let path = testdir!().join("import");
+        let checksums = prepare_import(&path, None).await;
+        tokio::fs::write(path.join("apps/app.json"), b"{\"tampered\":true}")
+            .await
+            .unwrap();
+        assert!(matches!(
+            verify_import_integrity(
+                import_lore(Vec::new(), false),
+                MANIFEST,
+                Some(checksums),
+                &path
+            )
+            .await,
+            Err(ReadImportManifestError::Checksum(ChecksumError::Mismatch(
+                _
+            )))
+        ));
+    }
+
+    #[tokio::test]
+    async fn verify_import_integrity_unsigned_without_checksums() {
+        let path = testdir!().join("import");
+        prepare_import(&path, None).await;
+        verify_import_integrity(import_lore(Vec::new(), false), MANIFEST, None, &path)
+            .await
+            .unwrap();
+    }
+
+    fn checksums(entries: &[(&str, &str)]) -> Checksums {
+        entries
+            .iter()
+            .map(|(path, checksum)| (path.to_string(), checksum.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn verify_streamed_checksums_ok() {
+        let expected = checksums(&[("apps/a_1/a_1.tar", "ab12"), ("apps/b_1/b_1.tar", "cd34")]);
+        verify_streamed_checksums(&expected, &checksums(&[("apps/a_1/a_1.tar", "AB12")])).unwrap();
+    }
+
+    #[test]
+    fn verify_streamed_checksums_mismatch() {
+        let expected = checksums(&[("apps/a_1/a_1.tar", "ab12")]);
+        assert!(matches!(
+            verify_streamed_checksums(&expected, &checksums(&[("apps/a_1/a_1.tar", "ab13")])),
+            Err(ChecksumError::Mismatch(path)) if path == "apps/a_1/a_1.tar"
+        ));
+    }
+
+    #[test]
+    fn verify_streamed_checksums_unlisted() {
+        let expected = checksums(&[("apps/a_1/a_1.tar", "ab12")]);
+        assert!(matches!(
+            verify_streamed_checksums(&expected, &checksums(&[("apps/c_1/c_1.tar", "ab12")])),
+            Err(ChecksumError::Unlisted(path)) if path == "apps/c_1/c_1.tar"
+        ));
+    }
+
+    #[tokio::test]
+    async fn read_import_manifest_deferred() {
+        let path = testdir!().join("import");
+        let mut checksums = prepare_import(&path, None).await;
+        checksums.insert("apps/app.tar".to_string(), "ab12".to_string());
+        let Manifest::V3(mut manifest) = import_manifest(Vec::new()) else {
+            panic!()
+        };
+        manifest.contents = v3::Contents {
+            apps: Vec::new(),
+            instances: Vec::new(),
+            deployments: Vec::new(),
+            networks: Vec::new(),
+        };
+        manifest.checksums = Some(checksums);
+        tokio::fs::write(
+            path.join(manifest::FILE_NAME),
+            serde_json::to_vec(&Manifest::V3(manifest)).unwrap(),
+        )
+        .await
+        .unwrap();
+        let lore = import_lore(Vec::new(), false);
+        assert!(matches!(
+            read_import_manifest(
+                Quest::new_synced("Test"),
+                lore.clone(),
+                path.clone(),
+                Vec::new()
+            )
+            .await,
+            Err(ReadImportManifestError::Checksum(ChecksumError::Missing(_)))
+        ));
+        read_import_manifest(
+            Quest::new_synced("Test"),
+            lore,
+            path,
+            vec!["apps/app.tar".to_string()],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn select_everything() {
+        assert_eq!(select("App", vec![1, 2, 3], None).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn select_some() {
+        assert_eq!(
+            select("App", vec![1, 2, 3], Some(&[3, 1][..])).unwrap(),
+            vec![1, 3]
+        );
+        assert!(
+            select("App", vec![1, 2, 3], Some(&[][..]))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn select_missing() {
+        assert!(matches!(
+            select("App", vec![1, 2, 3], Some(&[4][..])),
+            Err(ImportError::InvalidSelection(_))
+        ));
+    }
+
+    #[test]
+    fn flecs_version_sufficient() {
+        assert!(is_flecs_version_sufficient("5.2.0", "5.2.0"));
+        assert!(is_flecs_version_sufficient("4.1.0", "5.2.0"));
+        assert!(is_flecs_version_sufficient("5.2.0-beta.1", "5.2.0"));
+        assert!(!is_flecs_version_sufficient("5.2.1", "5.2.0"));
+        assert!(!is_flecs_version_sufficient("6.0.0-beta.1", "5.2.0"));
+        assert!(is_flecs_version_sufficient("invalid", "5.2.0"));
+    }
+
+    const NEW_INSTANCE: InstanceId = InstanceId::new(0xabcd);
+
+    fn existing_app() -> AppKey {
+        AppKey {
+            name: MINIMAL_APP_WITH_INSTANCE_NAME.to_string(),
+            version: MINIMAL_APP_WITH_INSTANCE_VERSION.to_string(),
+        }
+    }
+
+    fn new_app() -> AppKey {
+        AppKey {
+            name: UNKNOWN_APP_NAME.to_string(),
+            version: "2.0.0".to_string(),
+        }
+    }
+
+    fn network_key(deployment: &str, network: &str) -> NetworkKey {
+        NetworkKey {
+            deployment: deployment.to_string(),
+            network: network.to_string(),
+        }
+    }
+
+    fn import_manifest(apps: Vec<AppKey>) -> Manifest {
+        Manifest::V3(v3::Manifest {
+            time: std::time::SystemTime::now(),
+            human_readable_time: None,
+            contents: v3::Contents {
+                apps,
+                instances: vec![MINIMAL_INSTANCE, NEW_INSTANCE],
+                deployments: vec![
+                    "DefaultMockedDeploymentId".to_string(),
+                    "NewDeployment".to_string(),
+                ],
+                networks: vec![
+                    network_key("DefaultMockedDeploymentId", "kept"),
+                    network_key("NewDeployment", "new"),
+                    network_key("UnselectedDeployment", "unselected"),
+                ],
+            },
+            device: v3::Device {
+                sysinfo: try_create_system_info().unwrap(),
+                hostname: None,
+            },
+            version: Default::default(),
+            checksums: None,
+            base: None,
+        })
+    }
+
+    fn write_app_manifest(src: &Path, app_key: &AppKey, minimum_flecs_version: &str) {
+        let dir = src
+            .join("apps")
+            .join(format!("{}_{}", app_key.name, app_key.version));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest = serde_json::json!({
+            "_schemaVersion": "3.0.0",
+            "_minimumFlecsVersion": minimum_flecs_version,
+            "app": app_key.name,
+            "version": app_key.version,
+            "image": format!("flecs.azurecr.io/{}", app_key.name),
+        });
+        std::fs::write(
+            dir.join(format!(
+                "{}_{}.manifest.json",
+                app_key.name, app_key.version
+            )),
+            manifest.to_string(),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn plan_import_skip() {
+        let src = testdir!();
+        write_app_manifest(&src, &new_app(), "99.0.0");
+        let vault = create_test_vault(HashMap::new(), HashMap::new(), None);
+        let plan = plan_import(
+            vault,
+            &import_manifest(vec![existing_app(), new_app()]),
+            &src,
+            &ImportOptions {
+                conflict_policy: ConflictPolicy::Skip,
+                dry_run: true,
+                ..ImportOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(plan.create.apps, vec![new_app()]);
+        assert_eq!(plan.create.instances, vec![NEW_INSTANCE]);
+        assert_eq!(plan.create.deployments, vec!["NewDeployment".to_string()]);
+        assert_eq!(plan.replace, Default::default());
+        assert_eq!(plan.skip.apps, vec![existing_app()]);
+        assert_eq!(plan.skip.instances, vec![MINIMAL_INSTANCE]);
+        assert_eq!(
+            plan.skip.deployments,
+            vec!["DefaultMockedDeploymentId".to_string()]
+        );
+        assert!(plan.new_instance_ids.is_empty());
+        assert_eq!(
+            plan.incompatibilities,
+            vec![ImportIncompatibility::FlecsVersion {
+                app_key: new_app(),
+                minimum_version: "99.0.0".to_string(),
+                flecs_version: crate::lore::FLECS_VERSION.to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn plan_import_replace_selection() {
+        let src = testdir!();
+        write_app_manifest(&src, &existing_app(), "1.0.0");
+        let vault = create_test_vault(HashMap::new(), HashMap::new(), None);
+        let plan = plan_import(
+            vault,
+            &import_manifest(vec![existing_app(), new_app()]),
+            &src,
+            &ImportOptions {
+                apps: Some(vec![existing_app()]),
+                instances: Some(vec![MINIMAL_INSTANCE]),
+                deployments: Some(Vec::new()),
+                ..ImportOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(plan.create, Default::default());
+        assert_eq!(plan.replace.apps, vec![existing_app()]);
+        assert_eq!(plan.replace.instances, vec![MINIMAL_INSTANCE]);
+        assert!(plan.replace.deployments.is_empty());
+        assert_eq!(plan.skip, Default::default());
+        assert!(plan.incompatibilities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn plan_import_invalid_selection() {
+        let vault = create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            plan_import(
+                vault,
+                &import_manifest(vec![existing_app()]),
+                &testdir!(),
+                &ImportOptions {
+                    instances: Some(vec![InstanceId::new(0x1234)]),
+                    ..ImportOptions::default()
+                },
+            )
+            .await,
+            Err(ImportError::InvalidSelection(_))
+        ));
+    }
+
+    #[test]
+    fn restrict_to_plan_v3() {
+        let plan = ImportPlan {
+            create: ImportPlanContents {
+                apps: vec![new_app()],
+                instances: vec![NEW_INSTANCE],
+                deployments: Vec::new(),
+            },
+            replace: ImportPlanContents {
+                apps: Vec::new(),
+                instances: Vec::new(),
+                deployments: vec!["NewDeployment".to_string()],
+            },
+            skip: ImportPlanContents {
+                apps: vec![existing_app()],
+                instances: Vec::new(),
+                deployments: vec!["DefaultMockedDeploymentId".to_string()],
+            },
+            new_instance_ids: HashMap::from([(MINIMAL_INSTANCE, InstanceId::new(0x5678))]),
+            incompatibilities: Vec::new(),
+        };
+        let Manifest::V3(manifest) =
+            restrict_to_plan(import_manifest(vec![existing_app(), new_app()]), &plan)
+        else {
+            panic!("Expected manifest v3");
+        };
+        assert_eq!(manifest.contents.apps, vec![new_app()]);
+        assert_eq!(
+            manifest.contents.instances,
+            vec![NEW_INSTANCE, MINIMAL_INSTANCE]
+        );
+        assert_eq!(
+            manifest.contents.deployments,
+            vec!["NewDeployment".to_string()]
+        );
+        assert_eq!(
+            manifest.contents.networks,
+            vec![
+                network_key("DefaultMockedDeploymentId", "kept"),
+                network_key("NewDeployment", "new"),
+            ]
+        );
+    }
+
+    fn write_network_config(path: &Path, deployment: &str) -> NetworkConfig {
+        let config = NetworkConfig {
+            kind: NetworkKind::IpvlanL2,
+            name: "ipvlan".to_string(),
+            cidr_subnet: Some(ipnet::Ipv4Net::from_str("10.20.0.0/16").unwrap()),
+            gateway: Some(Ipv4Addr::new(10, 20, 0, 1)),
+            parent_adapter: Some("eth0".to_string()),
+            options: None,
+        };
+        let path = path.join(deployment);
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(
+            path.join("ipvlan.json"),
+            serde_json::to_vec(&config).unwrap(),
+        )
+        .unwrap();
+        config
+    }
+
+    #[tokio::test]
+    async fn import_network_remapped() {
+        let path = testdir!();
+        write_network_config(&path, "MockedDeployment");
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_create_network()
+            .once()
+            .withf(|_, config| {
+                config.parent_adapter.as_deref() == Some("enp1s0")
+                    && config.cidr_subnet.is_none()
+                    && config.gateway.is_none()
+            })
+            .returning(|_, _| Ok(Network::default()));
+        import_network(
+            Quest::new_synced("TestQuest"),
+            Deployment::Docker(Arc::new(deployment)),
+            network_key("MockedDeployment", "ipvlan"),
+            Arc::new(HashMap::from([("eth0".to_string(), "enp1s0".to_string())])),
+            path,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn import_network_exists() {
+        let path = testdir!();
+        let expected_config = write_network_config(&path, "MockedDeployment");
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_create_network()
+            .once()
+            .withf(move |_, config| *config == expected_config)
+            .returning(|_, _| {
+                Err(CreateNetworkError::DifferentNetworkExists(
+                    Network::default(),
+                ))
+            });
+        import_network(
+            Quest::new_synced("TestQuest"),
+            Deployment::Docker(Arc::new(deployment)),
+            network_key("MockedDeployment", "ipvlan"),
+            Arc::new(HashMap::new()),
+            path,
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn import_network_err_create() {
+        let path = testdir!();
+        write_network_config(&path, "MockedDeployment");
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_create_network()
+            .once()
+            .returning(|_, _| Err(CreateNetworkError::Other("test error".to_string())));
+        assert!(matches!(
+            import_network(
+                Quest::new_synced("TestQuest"),
+                Deployment::Docker(Arc::new(deployment)),
+                network_key("MockedDeployment", "ipvlan"),
+                Arc::new(HashMap::new()),
+                path,
+            )
+            .await,
+            Err(ImportNetworkError::Create { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn import_network_invalid_key() {
+        let path = testdir!();
+        let mut deployment = MockedDockerDeployment::new();
+        deployment.expect_create_network().never();
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        for key in [
+            network_key("MockedDeployment", "../../ipvlan"),
+            network_key("../MockedDeployment", "ipvlan"),
+            network_key("MockedDeployment", "/ipvlan"),
+        ] {
+            assert!(matches!(
+                import_network(
+                    Quest::new_synced("TestQuest"),
+                    deployment.clone(),
+                    key,
+                    Arc::new(HashMap::new()),
+                    path.clone(),
+                )
+                .await,
+                Err(ImportNetworkError::InvalidKey { .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn import_networks_unknown_deployment() {
+        import_networks(
+            Quest::new_synced("TestQuest"),
+            Arc::new(HashMap::new()),
+            vec![network_key("UnknownDeployment", "ipvlan")],
+            HashMap::new(),
+            testdir!(),
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn prepare_inspection(path: &Path) -> PathBuf {
+        let src = path.join("src");
+        let export = src.join("export");
+        std::fs::create_dir_all(export.join("apps/app_1.0.0")).unwrap();
+        std::fs::create_dir_all(export.join("instances/0000abcd")).unwrap();
+        std::fs::create_dir_all(export.join("deployments")).unwrap();
+        let manifest = import_manifest(vec![AppKey {
+            name: "app".to_string(),
+            version: "1.0.0".to_string(),
+        }]);
+        std::fs::write(
+            export.join(manifest::FILE_NAME),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(export.join(manifest::SIGNATURE_FILE_NAME), [0; 4]).unwrap();
+        std::fs::write(export.join("apps/app_1.0.0/app_1.0.0.tar"), [0; 100]).unwrap();
+        std::fs::write(export.join("instances/0000abcd/instance.json"), [0; 20]).unwrap();
+        std::fs::write(export.join("deployments/docker.json"), [0; 10]).unwrap();
+        let archive_path = path.join("export.tar");
+        crate::relic::async_flecstract::archive_to_file(&src, &archive_path, false)
+            .await
+            .unwrap();
+        archive_path
+    }
+
+    #[tokio::test]
+    async fn inspect_import_archive_ok() {
+        let path = testdir!();
+        let archive_path = prepare_inspection(&path).await;
+        let lore = import_lore(Vec::new(), false);
+        let inspection =
+            inspect_import_archive(lore.as_ref().as_ref(), &archive_path, Decryption::default())
+                .await
+                .unwrap();
+        assert_eq!(inspection.schema_version, "3.0.0");
+        assert_eq!(
+            inspection.apps,
+            vec![AppKey {
+                name: "app".to_string(),
+                version: "1.0.0".to_string(),
+            }]
+        );
+        assert_eq!(inspection.instances, vec![MINIMAL_INSTANCE, NEW_INSTANCE]);
+        assert_eq!(inspection.deployments.len(), 2);
+        assert!(inspection.signed);
+        assert!(!inspection.encrypted);
+        assert_eq!(inspection.sizes.apps, 100);
+        assert_eq!(inspection.sizes.instances, 20);
+        assert_eq!(inspection.sizes.deployments, 10);
+        assert_eq!(inspection.sizes.total, inspection.sizes.other + 130);
+    }
+
+    #[tokio::test]
+    async fn inspect_import_archive_encrypted() {
+        let path = testdir!();
+        let archive_path = prepare_inspection(&path).await;
+        let encrypted_path = path.join("export.tar.age");
+        crate::relic::encryption::encrypt_file(
+            archive_path,
+            encrypted_path.clone(),
+            crate::relic::encryption::Encryption::Passphrase("secret".to_string()),
+        )
+        .await
+        .unwrap();
+        let lore = import_lore(Vec::new(), false);
+        let lore = lore.as_ref().as_ref();
+        assert!(matches!(
+            inspect_import_archive(lore, &encrypted_path, Decryption::default()).await,
+            Err(InspectImportError::Decrypt {
+                error: DecryptionError::NoKeyMaterial,
+                ..
+            })
+        ));
+        let inspection = inspect_import_archive(
+            lore,
+            &encrypted_path,
+            Decryption {
+                passphrases: vec!["secret".to_string()],
+                identities: Vec::new(),
+            },
+        )
+        .await
+        .unwrap();
+        assert!(inspection.encrypted);
+        assert_eq!(inspection.sizes.apps, 100);
+        let mut remaining = std::fs::read_dir(&lore.base_path).unwrap();
+        assert!(remaining.all(|entry| {
+            !entry
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with("inspect_")
+        }));
+    }
+
+    /// Archive containing the image of [new_app] and a volume of an instance which is not
+    /// imported, the returned manifest lists the checksum of the image if `image_checksum` is set
+    async fn prepare_streamed_import(
+        path: &Path,
+        image_checksum: Option<&str>,
+    ) -> (v3::Manifest, DeferredImportContent) {
+        let src = path.join("src");
+        let image_path = format!("apps/{}/image.tar", app_directory_name(&new_app()));
+        std::fs::create_dir_all(src.join(&image_path).parent().unwrap()).unwrap();
+        std::fs::write(src.join(&image_path), b"image data").unwrap();
+        std::fs::create_dir_all(src.join("instances/ffffffff/volumes")).unwrap();
+        std::fs::write(src.join("instances/ffffffff/volumes/data.tar"), b"volume").unwrap();
+        let archive_path = path.join("export.tar");
+        crate::relic::async_flecstract::archive_to_file(&src, &archive_path, false)
+            .await
+            .unwrap();
+        let Manifest::V3(mut manifest) = import_manifest(vec![new_app()]) else {
+            panic!()
+        };
+        manifest.checksums = image_checksum
+            .map(|checksum| Checksums::from([(image_path.clone(), checksum.to_string())]));
+        let entries = [
+            (image_path, 10),
+            ("instances/ffffffff/volumes/data.tar".to_string(), 6),
+        ]
+        .into_iter()
+        .map(|(relative_path, size)| {
+            let entry = crate::relic::async_flecstract::DeferredEntry {
+                path: PathBuf::from(&relative_path),
+                size,
+            };
+            (relative_path, entry)
+        })
+        .collect();
+        (
+            manifest,
+            DeferredImportContent {
+                archive_path,
+                entries,
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn stream_import_content_ok() {
+        let path = testdir!();
+        let checksum = crate::relic::integrity::encode_hex(
+            &<sha2::Sha256 as sha2::Digest>::digest(b"image data"),
+        );
+        let (manifest, content) = prepare_streamed_import(&path, Some(&checksum)).await;
+        let dst = path.join("dst");
+        std::fs::create_dir_all(&dst).unwrap();
+        let loaded_apps = stream_import_content(
+            Quest::new_synced("TestQuest"),
+            create_test_vault(HashMap::new(), HashMap::new(), None),
+            Arc::new(lore::test_lore(path.clone(), &MockVarReader::new())),
+            &manifest,
+            content,
+            dst.clone(),
+        )
+        .await
+        .unwrap();
+        assert!(loaded_apps.is_empty());
+        assert_eq!(
+            std::fs::read(
+                dst.join("apps")
+                    .join(app_directory_name(&new_app()))
+                    .join("image.tar")
+            )
+            .unwrap(),
+            b"image data"
+        );
+        assert!(!dst.join("instances/ffffffff").exists());
+    }
+
+    #[tokio::test]
+    async fn stream_import_content_checksum_mismatch() {
+        let path = testdir!();
+        let (manifest, content) = prepare_streamed_import(&path, Some("00")).await;
+        let dst = path.join("dst");
+        std::fs::create_dir_all(&dst).unwrap();
+        assert!(matches!(
+            stream_import_content(
+                Quest::new_synced("TestQuest"),
+                create_test_vault(HashMap::new(), HashMap::new(), None),
+                Arc::new(lore::test_lore(path.clone(), &MockVarReader::new())),
+                &manifest,
+                content,
+                dst,
+            )
+            .await,
+            Err(ImportError::ImportManifest(
+                ReadImportManifestError::Checksum(ChecksumError::Mismatch(_))
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn stream_import_content_checksum_mismatch_removes_loaded_image() {
+        let path = testdir!();
+        let (manifest, content) = prepare_streamed_import(&path, Some("00")).await;
+        let dst = path.join("dst");
+        write_app_manifest(&dst, &new_app(), "3.0.0");
+        let app_directory = app_directory_name(&new_app());
+        std::fs::write(
+            dst.join("apps")
+                .join(&app_directory)
+                .join(format!("{app_directory}.json")),
+            serde_json::json!({
+                "key": new_app(),
+                "deployments": [{"desired": "Installed", "deployment_id": "TestDeployment"}],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let images = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut deployment = MockedDockerDeployment::new();
+        deployment.expect_is_default().return_const(true);
+        deployment
+            .expect_id()
+            .return_const("TestDeployment".to_string());
+        deployment
+            .expect_is_app_installed()
+            .once()
+            .returning(|_, _| Ok(false));
+        {
+            let images = images.clone();
+            deployment
+                .expect_import_app_image()
+                .once()
+                .returning(move |_, _, image| {
+                    images.lock().unwrap().push(image);
+                    Ok(())
+                });
+        }
+        deployment
+            .expect_uninstall_app()
+            .once()
+            .returning(|_, _| Ok(()));
+        let vault = create_test_vault_with_deployment(Deployment::Docker(Arc::new(deployment)));
+        assert!(matches!(
+            stream_import_content(
+                Quest::new_synced("TestQuest"),
+                vault,
+                Arc::new(lore::test_lore(path.clone(), &MockVarReader::new())),
+                &manifest,
+                content,
+                dst,
+            )
+            .await,
+            Err(ImportError::ImportManifest(
+                ReadImportManifestError::Checksum(ChecksumError::Mismatch(_))
+            ))
+        ));
+        assert_eq!(images.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn stream_import_content_traversal() {
+        let path = testdir!();
+        let (manifest, mut content) = prepare_streamed_import(&path, None).await;
+        let (_, entry) = content.entries.pop_first().unwrap();
+        content
+            .entries
+            .insert("apps/../../../etc/image.tar".to_string(), entry);
+        let dst = path.join("dst");
+        std::fs::create_dir_all(&dst).unwrap();
+        assert!(matches!(
+            stream_import_content(
+                Quest::new_synced("TestQuest"),
+                create_test_vault(HashMap::new(), HashMap::new(), None),
+                Arc::new(lore::test_lore(path.clone(), &MockVarReader::new())),
+                &manifest,
+                content,
+                dst,
+            )
+            .await,
+            Err(ImportError::Stream(_))
+        ));
+        assert!(!path.join("etc").exists());
+    }
+
+    #[tokio::test]
+    async fn inspect_import_archive_missing_manifest() {
+        let path = testdir!();
+        let src = path.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::write(src.join("data.json"), b"{}").unwrap();
+        let archive_path = path.join("export.tar");
+        crate::relic::async_flecstract::archive_to_file(&src, &archive_path, false)
+            .await
+            .unwrap();
+        let lore = import_lore(Vec::new(), false);
+        assert!(matches!(
+            inspect_import_archive(lore.as_ref().as_ref(), &archive_path, Decryption::default())
+                .await,
+            Err(InspectImportError::MissingManifest)
+        ));
+    }
+}
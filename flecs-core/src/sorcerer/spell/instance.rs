@@ -1,11 +1,15 @@
 pub use super::{Error, Result};
+use crate::forge::path::PathExtension;
 use crate::jeweler::gem::deployment::compose::ComposeDeployment;
 use crate::jeweler::gem::deployment::docker::DockerDeployment;
 use crate::jeweler::gem::instance::compose::ComposeInstance;
 use crate::jeweler::gem::instance::docker::DockerInstance;
 use crate::jeweler::gem::instance::docker::config::InstanceConfig;
+use crate::jeweler::gem::instance::docker::files::{FileEntry, FileLocations};
 use crate::jeweler::gem::instance::status::InstanceStatus;
-use crate::jeweler::gem::instance::{Instance, InstanceId, ProviderReference};
+use crate::jeweler::gem::instance::{
+    ExecOutput, ExecSession, Instance, InstanceId, ProviderReference,
+};
 use crate::jeweler::gem::manifest::FeatureKey;
 use crate::jeweler::gem::manifest::multi::AppManifestMulti;
 use crate::jeweler::gem::manifest::single::AppManifestSingle;
@@ -14,14 +18,17 @@ use crate::lore::Lore;
 use crate::quest::{State, SyncQuest};
 use crate::relic::floxy::Floxy;
 use crate::relic::network::Ipv4NetworkAccess;
+use crate::sorcerer::spell;
 use crate::vault::pouch::provider::ProviderId;
 use crate::vault::pouch::{AppKey, Pouch};
 use crate::vault::{GrabbedPouches, Vault, pouch};
 use futures_util::future::join_all;
+use std::collections::HashMap;
 use std::net::{IpAddr, Ipv4Addr};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::error;
+use std::time::Duration;
+use tracing::{error, warn};
 
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
 pub enum DisconnectInstanceError {
@@ -46,6 +53,63 @@ pub enum UpdateInstanceError {
     NoManifest(AppKey),
     #[error("Instance {0} does not exist")]
     NotFound(InstanceId),
+    #[error("App {0} is not staged on the deployment of the instance")]
+    NotStaged(AppKey),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CloneInstanceError {
+    #[error("Instance {0} does not exist")]
+    NotFound(InstanceId),
+    #[error("Instance {0} does not support cloning")]
+    Unsupported(InstanceId),
+    #[error("App {0} does not support multiple instances")]
+    NotMultiInstance(AppKey),
+    #[error("Instance name {0} is already used by another instance")]
+    NameTaken(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExecInstanceError {
+    #[error("Instance {0} does not exist")]
+    NotFound(InstanceId),
+    #[error("Instance {0} does not support executing commands")]
+    Unsupported(InstanceId),
+    #[error("Instance {0} is not running")]
+    NotRunning(InstanceId),
+    #[error("Command in instance {instance_id} did not finish within {timeout:?}")]
+    Timeout {
+        instance_id: InstanceId,
+        timeout: Duration,
+    },
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum InstanceFilesError {
+    #[error("Instance {0} does not exist")]
+    NotFound(InstanceId),
+    #[error("Instance {0} does not support browsing files")]
+    Unsupported(InstanceId),
+    #[error("Instance {0} is not running")]
+    NotRunning(InstanceId),
+    #[error("Instance {instance_id} has no config file {name}")]
+    UnknownConfigFile {
+        instance_id: InstanceId,
+        name: String,
+    },
+    #[error("Instance {instance_id} has no volume {name}")]
+    UnknownVolume {
+        instance_id: InstanceId,
+        name: String,
+    },
+    #[error("Invalid path {0:?}, expected a relative path inside the volume")]
+    InvalidPath(PathBuf),
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -70,28 +134,64 @@ pub async fn create_compose_instance(
     Ok(ComposeInstance::try_create_new(quest, lore, deployment, manifest, name).await?)
 }
 
+/// Injects the current connection details of its providers into the instance before it is
+/// started. Running instances depending on the started instance are restarted if the connection
+/// details changed, e.g. because the started instance got a new address.
 pub async fn start_instance(
-    _quest: SyncQuest,
+    quest: SyncQuest,
     vault: Arc<Vault>,
     floxy: Arc<dyn Floxy>,
     instance_id: InstanceId,
 ) -> Result<()> {
-    let mut grab = vault
+    let GrabbedPouches {
+        provider_pouch: Some(ref providers),
+        instance_pouch_mut: Some(ref mut instances),
+        ..
+    } = vault
         .reservation()
+        .reserve_provider_pouch()
         .reserve_instance_pouch_mut()
         .grab()
-        .await;
-    let instance = grab
-        .instance_pouch_mut
-        .as_mut()
-        .expect("Vault reservations should never fail")
-        .gems_mut()
+        .await
+    else {
+        unreachable!("Vault reservations should never fail")
+    };
+    let providers = providers.gems();
+    let instances = instances.gems_mut();
+    if let Some(rebindings) =
+        spell::provider::refresh_provider_connections(instances, providers, instance_id).await
+    {
+        spell::provider::record_rebindings(&quest, rebindings).await;
+    }
+    let instance = instances
         .get_mut(&instance_id)
         .ok_or_else(|| anyhow::anyhow!("Instance {instance_id} does not exist"))?;
     match instance {
-        Instance::Docker(instance) => instance.start(floxy).await,
-        Instance::Compose(instance) => instance.start().await,
+        Instance::Docker(instance) => instance.start(floxy.clone()).await?,
+        Instance::Compose(instance) => instance.start().await?,
     }
+    let dependents = spell::provider::get_dependents(instances, providers, instance_id);
+    let results = spell::provider::restart_changed_dependents(
+        &quest, instances, providers, floxy, dependents,
+    )
+    .await;
+    describe_dependent_restarts(&quest, instance_id, results).await;
+    Ok(())
+}
+
+async fn describe_dependent_restarts(
+    quest: &SyncQuest,
+    instance_id: InstanceId,
+    results: Vec<(InstanceId, Result<()>)>,
+) {
+    if results.is_empty() {
+        return;
+    }
+    let (detail, failed) = spell::provider::describe_restarts(results);
+    if !failed.is_empty() {
+        warn!("Failed to restart dependent instances {failed:?} of instance {instance_id}");
+    }
+    quest.lock().await.detail = Some(detail);
 }
 
 pub async fn resume_instance(
@@ -114,8 +214,10 @@ pub async fn resume_instance(
     }
 }
 
+/// Dependent instances with fallback providers are bound to their next available provider after
+/// the instance stopped
 pub async fn stop_instance(
-    _quest: SyncQuest,
+    quest: SyncQuest,
     vault: Arc<Vault>,
     floxy: Arc<dyn Floxy>,
     instance_id: InstanceId,
@@ -147,14 +249,21 @@ pub async fn stop_instance(
         }
         _ => {}
     }
+    let instances = instances.gems_mut();
     let instance = instances
-        .gems_mut()
         .get_mut(&instance_id)
         .ok_or_else(|| anyhow::anyhow!("Instance {instance_id} does not exist"))?;
     match instance {
-        Instance::Docker(instance) => instance.stop(floxy).await,
-        Instance::Compose(instance) => instance.stop().await,
+        Instance::Docker(instance) => instance.stop(floxy.clone()).await?,
+        Instance::Compose(instance) => instance.stop().await?,
     }
+    let dependents = spell::provider::get_failover_dependents(instances, providers, instance_id);
+    let results = spell::provider::restart_changed_dependents(
+        &quest, instances, providers, floxy, dependents,
+    )
+    .await;
+    describe_dependent_restarts(&quest, instance_id, results).await;
+    Ok(())
 }
 
 pub async fn stop_instances(
@@ -335,8 +444,21 @@ pub async fn start_all_instances_as_desired(
             start_results.push(result);
         }
     }
-    join_all(start_results)
+    let start_results = join_all(start_results).await;
+    // The addresses of providers can change when they are started
+    let update_result = quest
+        .lock()
         .await
+        .create_sub_quest(
+            "Update provider connections of dependent instances".to_string(),
+            |quest| spell::provider::update_dependents(quest, vault.clone(), floxy.clone()),
+        )
+        .await
+        .2;
+    if let Err(e) = update_result.await {
+        warn!("{e}");
+    }
+    start_results
         .into_iter()
         .try_for_each(|result| match result {
             Err(e) => Err(anyhow::anyhow!(e)),
@@ -519,12 +641,15 @@ pub fn validate_no_dependents(
         .collect();
     for (dependent_instance_id, instance) in instances {
         for dependency in instance.dependencies().values() {
-            match dependency.provider_reference {
-                ProviderReference::Provider(id) if id == provider_id => usages.push(format!(
+            // Dependencies with other candidates fail over to them
+            let only_candidate = dependency.candidates().all(|candidate| {
+                matches!(candidate, ProviderReference::Provider(id) if *id == provider_id)
+            });
+            if only_candidate {
+                usages.push(format!(
                     "providing {} for instance {dependent_instance_id}",
                     dependency.provided_feature
-                )),
-                _ => {}
+                ));
             }
         }
     }
@@ -568,11 +693,12 @@ pub async fn delete_instance(
     let providers = providers.gems();
     let instances = instances.gems_mut();
     validate_no_dependents(providers, instances, id)?;
+    let dependents = spell::provider::get_failover_dependents(instances, providers, id);
     match instances.remove(&id) {
         Some(instance) => {
             let result = match instance {
                 Instance::Docker(instance) => instance
-                    .stop_and_delete(quest, floxy)
+                    .stop_and_delete(quest.clone(), floxy.clone())
                     .await
                     .map_err(|(e, instance)| (e, Instance::Docker(instance))),
                 Instance::Compose(instance) => instance
@@ -582,10 +708,14 @@ pub async fn delete_instance(
             };
             if let Err((e, instance)) = result {
                 instances.insert(id, instance);
-                Err(e)
-            } else {
-                Ok(())
+                return Err(e);
             }
+            let results = spell::provider::restart_changed_dependents(
+                &quest, instances, providers, floxy, dependents,
+            )
+            .await;
+            describe_dependent_restarts(&quest, id, results).await;
+            Ok(())
         }
         None => anyhow::bail!("Instance {id} not found"),
     }
@@ -756,6 +886,11 @@ pub async fn update_instance(
     {
         None => Err(UpdateInstanceError::NotFound(instance_id)),
         Some(instance) => {
+            // The new version has to be staged beforehand, so that the instance is not stopped
+            // before all images are available
+            if !instance.is_staged(quest.clone(), &new_manifest).await? {
+                return Err(UpdateInstanceError::NotStaged(new_version));
+            }
             let base_path = base_path.join(instance_id.to_string());
             instance
                 .update(quest, floxy, new_manifest, &base_path)
@@ -765,15 +900,350 @@ pub async fn update_instance(
     }
 }
 
+/// Creates a copy of the instance with the given id. The clone is connected to the same networks
+/// with new addresses and gets free host ports for all port mappings of the original instance.
+/// The vault is not kept reserved while the data of the instance is copied.
+#[allow(clippy::too_many_arguments)]
+pub async fn clone_instance(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+    instance_id: InstanceId,
+    name: Option<String>,
+    copy_config_files: bool,
+    copy_volumes: bool,
+) -> Result<InstanceId, CloneInstanceError> {
+    let (source, name) = {
+        let grab = vault.reservation().reserve_instance_pouch().grab().await;
+        let instances = grab
+            .instance_pouch
+            .as_ref()
+            .expect("Vault reservations should never fail");
+        let instance = match instances.gems().get(&instance_id) {
+            None => return Err(CloneInstanceError::NotFound(instance_id)),
+            Some(Instance::Compose(_)) => return Err(CloneInstanceError::Unsupported(instance_id)),
+            Some(Instance::Docker(instance)) => instance,
+        };
+        if !instance.manifest.multi_instance() {
+            return Err(CloneInstanceError::NotMultiInstance(instance.app_key()));
+        }
+        let name = name.unwrap_or_else(|| format!("{}-clone", instance.name));
+        ensure_unique_instance_name(instances.gems(), &name)?;
+        (instance.detached_copy(), name)
+    };
+    let mut clone = source
+        .try_create_clone(
+            quest.clone(),
+            floxy.clone(),
+            name,
+            copy_config_files,
+            copy_volumes,
+        )
+        .await?;
+    let mut grab = vault
+        .reservation()
+        .reserve_instance_pouch_mut()
+        .grab()
+        .await;
+    let instances = grab
+        .instance_pouch_mut
+        .as_mut()
+        .expect("Vault reservations should never fail");
+    if let Err(e) = connect_clone(instances, &source, &mut clone).await {
+        let clone_id = clone.id;
+        if let Err((delete_error, _)) = clone.delete(quest, floxy).await {
+            warn!("Could not delete cloned instance {clone_id}: {delete_error}");
+        }
+        return Err(e);
+    }
+    let clone_id = clone.id;
+    instances
+        .gems_mut()
+        .insert(clone_id, Instance::Docker(clone));
+    Ok(clone_id)
+}
+
+fn ensure_unique_instance_name(
+    instances: &pouch::instance::Gems,
+    name: &str,
+) -> Result<(), CloneInstanceError> {
+    if instances.values().any(|instance| instance.name() == name) {
+        Err(CloneInstanceError::NameTaken(name.to_string()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Assigns free host ports and addresses in the networks of `source` to the clone. Ports and
+/// addresses are determined with the vault reserved, as other instances might have been created
+/// while the data of the clone was copied.
+async fn connect_clone(
+    instances: &pouch::instance::InstancePouch,
+    source: &DockerInstance,
+    clone: &mut DockerInstance,
+) -> Result<(), CloneInstanceError> {
+    ensure_unique_instance_name(instances.gems(), &clone.name)?;
+    clone.config.port_mapping =
+        source
+            .config
+            .port_mapping
+            .with_fresh_host_ports(instances.gems().values().filter_map(
+                |instance| match instance {
+                    Instance::Docker(instance) => Some(&instance.config.port_mapping),
+                    Instance::Compose(_) => None,
+                },
+            ))?;
+    let mut unavailable_addresses = instances.unavailable_ipv4_addresses();
+    for (network_id, address) in &source.config.connected_networks {
+        if !address.is_ipv4() {
+            continue;
+        }
+        let network = source
+            .deployment
+            .network(network_id.clone())
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Network {network_id} does not exist"))?;
+        let address = Ipv4NetworkAccess::try_from(network)?
+            .next_free_ipv4_address(unavailable_addresses.clone())
+            .ok_or_else(|| anyhow::anyhow!("No free ip address available in {network_id}"))?;
+        unavailable_addresses.insert(address);
+        clone
+            .config
+            .connected_networks
+            .insert(network_id.clone(), IpAddr::V4(address));
+    }
+    Ok(())
+}
+
+/// Returns the deployment of the instance if it is a running docker instance. The vault is not
+/// kept reserved, so that long-running commands do not block other operations.
+async fn running_instance_deployment(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+) -> Result<Arc<dyn DockerDeployment>, ExecInstanceError> {
+    let deployment = match query_instance(vault, instance_id, |instance| match instance {
+        Instance::Docker(instance) => Some(instance.deployment.clone()),
+        Instance::Compose(_) => None,
+    })
+    .await
+    {
+        None => return Err(ExecInstanceError::NotFound(instance_id)),
+        Some(None) => return Err(ExecInstanceError::Unsupported(instance_id)),
+        Some(Some(deployment)) => deployment,
+    };
+    if deployment.instance_status(instance_id).await? != InstanceStatus::Running {
+        return Err(ExecInstanceError::NotRunning(instance_id));
+    }
+    Ok(deployment)
+}
+
+pub async fn exec_instance(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    cmd: Vec<String>,
+    timeout: Duration,
+) -> Result<ExecOutput, ExecInstanceError> {
+    let deployment = running_instance_deployment(vault, instance_id).await?;
+    match deployment.exec_in_instance(instance_id, cmd, timeout).await {
+        Err(e) if e.downcast_ref::<tokio::time::error::Elapsed>().is_some() => {
+            Err(ExecInstanceError::Timeout {
+                instance_id,
+                timeout,
+            })
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Fails if no command can be executed in the instance, i.e. if the instance does not exist,
+/// does not support executing commands or is not running
+pub async fn check_instance_exec(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+) -> Result<(), ExecInstanceError> {
+    running_instance_deployment(vault, instance_id).await?;
+    Ok(())
+}
+
+pub async fn exec_instance_tty(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    cmd: Vec<String>,
+) -> Result<ExecSession, ExecInstanceError> {
+    let deployment = running_instance_deployment(vault, instance_id).await?;
+    Ok(deployment.exec_tty_in_instance(instance_id, cmd).await?)
+}
+
+pub async fn resize_instance_exec(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    exec_id: &str,
+    width: u16,
+    height: u16,
+) -> Result<(), ExecInstanceError> {
+    let deployment = running_instance_deployment(vault, instance_id).await?;
+    Ok(deployment
+        .resize_instance_exec(instance_id, exec_id, width, height)
+        .await?)
+}
+
+fn files_instance(
+    gems: &pouch::instance::Gems,
+    instance_id: InstanceId,
+) -> Result<&DockerInstance, InstanceFilesError> {
+    match gems.get(&instance_id) {
+        None => Err(InstanceFilesError::NotFound(instance_id)),
+        Some(Instance::Compose(_)) => Err(InstanceFilesError::Unsupported(instance_id)),
+        Some(Instance::Docker(instance)) => Ok(instance),
+    }
+}
+
+/// Resolves the path inside the given volume, volumes can only be accessed while the instance is
+/// running as the container is removed on stop
+async fn volume_file_path(
+    instance: &DockerInstance,
+    volume_name: &str,
+    path: Option<&Path>,
+) -> Result<PathBuf, InstanceFilesError> {
+    let volume =
+        instance
+            .volume_mount(volume_name)
+            .ok_or_else(|| InstanceFilesError::UnknownVolume {
+                instance_id: instance.id,
+                name: volume_name.to_string(),
+            })?;
+    let container_path = match path {
+        None => volume.container_path.clone(),
+        Some(path) if path.is_contained_relative() => volume.container_path.join(path),
+        Some(path) => return Err(InstanceFilesError::InvalidPath(path.to_path_buf())),
+    };
+    if !instance.is_running().await? {
+        return Err(InstanceFilesError::NotRunning(instance.id));
+    }
+    Ok(container_path)
+}
+
+pub async fn get_instance_file_locations(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+) -> Result<FileLocations, InstanceFilesError> {
+    let grab = vault.reservation().reserve_instance_pouch().grab().await;
+    let gems = grab
+        .instance_pouch
+        .as_ref()
+        .expect("Reservations should never fail")
+        .gems();
+    Ok(files_instance(gems, instance_id)?.file_locations())
+}
+
+pub async fn get_instance_config_file(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    file_name: &str,
+) -> Result<Vec<u8>, InstanceFilesError> {
+    let grab = vault.reservation().reserve_instance_pouch().grab().await;
+    let gems = grab
+        .instance_pouch
+        .as_ref()
+        .expect("Reservations should never fail")
+        .gems();
+    let instance = files_instance(gems, instance_id)?;
+    let config_file =
+        instance
+            .config_file(file_name)
+            .ok_or_else(|| InstanceFilesError::UnknownConfigFile {
+                instance_id,
+                name: file_name.to_string(),
+            })?;
+    Ok(instance.read_config_file(config_file).await?)
+}
+
+pub async fn put_instance_config_file(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    file_name: &str,
+    data: &[u8],
+) -> Result<(), InstanceFilesError> {
+    let grab = vault.reservation().reserve_instance_pouch().grab().await;
+    let gems = grab
+        .instance_pouch
+        .as_ref()
+        .expect("Reservations should never fail")
+        .gems();
+    let instance = files_instance(gems, instance_id)?;
+    let config_file =
+        instance
+            .config_file(file_name)
+            .ok_or_else(|| InstanceFilesError::UnknownConfigFile {
+                instance_id,
+                name: file_name.to_string(),
+            })?;
+    Ok(instance.write_config_file(config_file, data).await?)
+}
+
+pub async fn get_instance_volume_files(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    volume_name: &str,
+) -> Result<Vec<FileEntry>, InstanceFilesError> {
+    let grab = vault.reservation().reserve_instance_pouch().grab().await;
+    let gems = grab
+        .instance_pouch
+        .as_ref()
+        .expect("Reservations should never fail")
+        .gems();
+    let instance = files_instance(gems, instance_id)?;
+    let path = volume_file_path(instance, volume_name, None).await?;
+    Ok(instance.list_files(&path).await?)
+}
+
+pub async fn get_instance_volume_file(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    volume_name: &str,
+    path: &Path,
+) -> Result<Vec<u8>, InstanceFilesError> {
+    let grab = vault.reservation().reserve_instance_pouch().grab().await;
+    let gems = grab
+        .instance_pouch
+        .as_ref()
+        .expect("Reservations should never fail")
+        .gems();
+    let instance = files_instance(gems, instance_id)?;
+    let path = volume_file_path(instance, volume_name, Some(path)).await?;
+    Ok(instance.read_file(&path).await?)
+}
+
+pub async fn put_instance_volume_file(
+    vault: Arc<Vault>,
+    instance_id: InstanceId,
+    volume_name: &str,
+    path: &Path,
+    data: &[u8],
+) -> Result<(), InstanceFilesError> {
+    let grab = vault.reservation().reserve_instance_pouch().grab().await;
+    let gems = grab
+        .instance_pouch
+        .as_ref()
+        .expect("Reservations should never fail")
+        .gems();
+    let instance = files_instance(gems, instance_id)?;
+    let path = volume_file_path(instance, volume_name, Some(path)).await?;
+    Ok(instance.write_file(&path, data).await?)
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::jeweler::gem::deployment::Deployment;
     use crate::jeweler::gem::deployment::docker::tests::MockedDockerDeployment;
-    use crate::jeweler::gem::manifest::single::EnvironmentVariable;
+    use crate::jeweler::gem::manifest::single::{EnvironmentVariable, PortMapping, PortRange};
     use crate::quest::Quest;
     use crate::relic::floxy::MockFloxy;
     use crate::vault;
+    use crate::vault::pouch::app::tests::{
+        MINIMAL_APP_WITH_INSTANCE_NAME, MINIMAL_APP_WITH_INSTANCE_VERSION,
+    };
     use crate::vault::pouch::instance::tests::{
         EDITOR_INSTANCE, ENV_INSTANCE, LABEL_INSTANCE, MINIMAL_INSTANCE, MOUNT_INSTANCE,
         NETWORK_INSTANCE, PORT_MAPPING_INSTANCE, RUNNING_INSTANCE, UNKNOWN_INSTANCE_1,
@@ -1259,7 +1729,7 @@ pub mod tests {
         floxy
             .expect_add_instance_reverse_proxy_config()
             .times(2)
-            .returning(|_, _, _, _, _, _| Ok(()));
+            .returning(|_, _, _, _, _, _, _| Ok(()));
         let floxy = Arc::new(floxy);
         start_all_instances_as_desired(Quest::new_synced("TestQuest".to_string()), vault, floxy)
             .await
@@ -1338,4 +1808,443 @@ pub mod tests {
             .is_err()
         );
     }
+
+    #[tokio::test]
+    async fn clone_instance_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            clone_instance(
+                Quest::new_synced("TestQuest".to_string()),
+                vault,
+                Arc::new(MockFloxy::new()),
+                UNKNOWN_INSTANCE_1,
+                None,
+                false,
+                false,
+            )
+            .await,
+            Err(CloneInstanceError::NotFound(UNKNOWN_INSTANCE_1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn clone_instance_not_multi_instance() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            clone_instance(
+                Quest::new_synced("TestQuest".to_string()),
+                vault,
+                Arc::new(MockFloxy::new()),
+                MINIMAL_INSTANCE,
+                None,
+                false,
+                false,
+            )
+            .await,
+            Err(CloneInstanceError::NotMultiInstance(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn clone_instance_name_taken() {
+        let lore = Arc::new(crate::lore::test_lore(
+            testdir::testdir!(),
+            &crate::relic::var::test::MockVarReader::new(),
+        ));
+        let instance = crate::jeweler::gem::instance::docker::tests::test_instance(
+            1,
+            lore,
+            Arc::new(MockedDockerDeployment::new()),
+            crate::jeweler::gem::manifest::single::tests::create_test_manifest_full(Some(true)),
+        );
+        let vault = vault::tests::create_empty_test_vault();
+        vault
+            .reservation()
+            .reserve_instance_pouch_mut()
+            .grab()
+            .await
+            .instance_pouch_mut
+            .as_mut()
+            .unwrap()
+            .gems_mut()
+            .insert(instance.id, Instance::Docker(instance));
+        assert!(matches!(
+            clone_instance(
+                Quest::new_synced("TestQuest".to_string()),
+                vault,
+                Arc::new(MockFloxy::new()),
+                InstanceId::new(1),
+                Some("TestInstance".to_string()),
+                false,
+                false,
+            )
+            .await,
+            Err(CloneInstanceError::NameTaken(name)) if name == "TestInstance"
+        ));
+    }
+
+    #[tokio::test]
+    async fn clone_instance_ok() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_network()
+            .with(predicate::eq("TestNetwork".to_string()))
+            .times(1)
+            .returning(|_| {
+                Ok(Some(bollard::models::Network {
+                    name: Some("TestNetwork".to_string()),
+                    ipam: Some(bollard::models::Ipam {
+                        config: Some(vec![bollard::models::IpamConfig {
+                            subnet: Some("10.18.102.0/24".to_string()),
+                            gateway: Some("10.18.102.1".to_string()),
+                            ..Default::default()
+                        }]),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }))
+            });
+        deployment
+            .expect_copy_from_app_image()
+            .times(3)
+            .returning(|_, _, _, _, _| Ok(()));
+        deployment
+            .expect_create_volume()
+            .times(1)
+            .returning(|_, _| Ok("TestVolumeId".to_string()));
+        let lore = Arc::new(crate::lore::test_lore(
+            testdir::testdir!(),
+            &crate::relic::var::test::MockVarReader::new(),
+        ));
+        let mut instance = crate::jeweler::gem::instance::docker::tests::test_instance(
+            1,
+            lore,
+            Arc::new(deployment),
+            crate::jeweler::gem::manifest::single::tests::create_test_manifest_full(Some(true)),
+        );
+        instance.config.connected_networks = HashMap::from([(
+            "TestNetwork".to_string(),
+            IpAddr::V4(Ipv4Addr::new(10, 18, 102, 2)),
+        )]);
+        let vault = vault::tests::create_empty_test_vault();
+        vault
+            .reservation()
+            .reserve_instance_pouch_mut()
+            .grab()
+            .await
+            .instance_pouch_mut
+            .as_mut()
+            .unwrap()
+            .gems_mut()
+            .insert(instance.id, Instance::Docker(instance));
+        let clone_id = clone_instance(
+            Quest::new_synced("TestQuest".to_string()),
+            vault.clone(),
+            Arc::new(MockFloxy::new()),
+            InstanceId::new(1),
+            None,
+            false,
+            false,
+        )
+        .await
+        .unwrap();
+        let grab = vault.reservation().reserve_instance_pouch().grab().await;
+        let Some(Instance::Docker(clone)) =
+            grab.instance_pouch.as_ref().unwrap().gems().get(&clone_id)
+        else {
+            panic!("Expected cloned docker instance");
+        };
+        assert_eq!(clone.name, "TestInstance-clone");
+        assert_eq!(
+            clone.config.connected_networks,
+            HashMap::from([(
+                "TestNetwork".to_string(),
+                IpAddr::V4(Ipv4Addr::new(10, 18, 102, 3)),
+            )])
+        );
+        assert_eq!(
+            clone.config.port_mapping.tcp,
+            vec![
+                PortMapping::Single(1003, 2002),
+                PortMapping::Range {
+                    from: PortRange::try_new(9001, 10001).unwrap(),
+                    to: PortRange::try_new(9500, 10500).unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn exec_instance_ok() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_instance_status()
+            .returning(|_| Ok(InstanceStatus::Running));
+        deployment
+            .expect_exec_in_instance()
+            .once()
+            .withf(|id, cmd, timeout| {
+                *id == RUNNING_INSTANCE
+                    && cmd == &["ls".to_string()]
+                    && *timeout == Duration::from_secs(5)
+            })
+            .returning(|_, _, _| {
+                Ok(ExecOutput {
+                    exit_code: Some(0),
+                    stdout: "config.json".to_string(),
+                    ..ExecOutput::default()
+                })
+            });
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(RUNNING_INSTANCE, deployment)]),
+            HashMap::new(),
+            None,
+        );
+        let output = exec_instance(
+            vault,
+            RUNNING_INSTANCE,
+            vec!["ls".to_string()],
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert_eq!(output.exit_code, Some(0));
+        assert_eq!(output.stdout, "config.json");
+    }
+
+    #[tokio::test]
+    async fn exec_instance_not_running() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_instance_status()
+            .returning(|_| Ok(InstanceStatus::Stopped));
+        deployment.expect_exec_in_instance().never();
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(RUNNING_INSTANCE, deployment)]),
+            HashMap::new(),
+            None,
+        );
+        assert!(matches!(
+            exec_instance(
+                vault,
+                RUNNING_INSTANCE,
+                vec!["ls".to_string()],
+                Duration::from_secs(5)
+            )
+            .await,
+            Err(ExecInstanceError::NotRunning(RUNNING_INSTANCE))
+        ));
+    }
+
+    #[tokio::test]
+    async fn exec_instance_timeout() {
+        let elapsed = tokio::time::timeout(Duration::ZERO, std::future::pending::<()>())
+            .await
+            .unwrap_err();
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_instance_status()
+            .returning(|_| Ok(InstanceStatus::Running));
+        deployment
+            .expect_exec_in_instance()
+            .once()
+            .return_once(move |_, _, timeout| {
+                Err(anyhow::Error::new(elapsed).context(format!("Timeout after {timeout:?}")))
+            });
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(RUNNING_INSTANCE, deployment)]),
+            HashMap::new(),
+            None,
+        );
+        assert!(matches!(
+            exec_instance(
+                vault,
+                RUNNING_INSTANCE,
+                vec!["sleep".to_string(), "10".to_string()],
+                Duration::from_secs(5)
+            )
+            .await,
+            Err(ExecInstanceError::Timeout {
+                instance_id: RUNNING_INSTANCE,
+                ..
+            })
+        ));
+    }
+
+    fn files_vault(status: InstanceStatus, mut deployment: MockedDockerDeployment) -> Arc<Vault> {
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_instance_status()
+            .returning(move |_| Ok(status));
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        vault::tests::create_test_vault(
+            HashMap::from([(MOUNT_INSTANCE, deployment)]),
+            HashMap::new(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn get_instance_volume_file_ok() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_copy_from_instance()
+            .once()
+            .withf(|_, id, src, _, is_dst_file_path| {
+                *id == MOUNT_INSTANCE
+                    && src == Path::new("/data/v2/sub/file.txt")
+                    && *is_dst_file_path
+            })
+            .returning(|_, _, _, dst, _| {
+                std::fs::write(dst, b"content").unwrap();
+                Ok(())
+            });
+        let vault = files_vault(InstanceStatus::Running, deployment);
+        assert_eq!(
+            get_instance_volume_file(vault, MOUNT_INSTANCE, "volume-2", Path::new("sub/file.txt"))
+                .await
+                .unwrap(),
+            b"content"
+        );
+    }
+
+    #[tokio::test]
+    async fn put_instance_volume_file_invalid_path() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment.expect_copy_to_instance().never();
+        let vault = files_vault(InstanceStatus::Running, deployment);
+        assert!(matches!(
+            put_instance_volume_file(
+                vault,
+                MOUNT_INSTANCE,
+                "volume-2",
+                Path::new("../../etc/passwd"),
+                b"content"
+            )
+            .await,
+            Err(InstanceFilesError::InvalidPath(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_instance_volume_files_not_running() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment.expect_copy_from_instance().never();
+        let vault = files_vault(InstanceStatus::Stopped, deployment);
+        assert!(matches!(
+            get_instance_volume_files(vault, MOUNT_INSTANCE, "volume-1").await,
+            Err(InstanceFilesError::NotRunning(MOUNT_INSTANCE))
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_instance_volume_files_unknown_volume() {
+        let vault = files_vault(InstanceStatus::Running, MockedDockerDeployment::new());
+        assert!(matches!(
+            get_instance_volume_files(vault, MOUNT_INSTANCE, "volume-3").await,
+            Err(InstanceFilesError::UnknownVolume { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_instance_config_file_unknown() {
+        let vault = files_vault(InstanceStatus::Running, MockedDockerDeployment::new());
+        assert!(matches!(
+            get_instance_config_file(vault, MOUNT_INSTANCE, "unknown.conf").await,
+            Err(InstanceFilesError::UnknownConfigFile { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn get_instance_file_locations_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            get_instance_file_locations(vault, UNKNOWN_INSTANCE_1).await,
+            Err(InstanceFilesError::NotFound(UNKNOWN_INSTANCE_1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn check_instance_exec_not_running() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_instance_status()
+            .returning(|_| Ok(InstanceStatus::Stopped));
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(RUNNING_INSTANCE, deployment)]),
+            HashMap::new(),
+            None,
+        );
+        assert!(matches!(
+            check_instance_exec(vault, RUNNING_INSTANCE).await,
+            Err(ExecInstanceError::NotRunning(RUNNING_INSTANCE))
+        ));
+    }
+
+    #[tokio::test]
+    async fn exec_instance_not_found() {
+        let vault = vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        assert!(matches!(
+            exec_instance(
+                vault,
+                UNKNOWN_INSTANCE_1,
+                vec!["ls".to_string()],
+                Duration::from_secs(5)
+            )
+            .await,
+            Err(ExecInstanceError::NotFound(UNKNOWN_INSTANCE_1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_instance_not_staged() {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment
+            .expect_id()
+            .return_const("MockedDeployment".to_string());
+        deployment
+            .expect_is_app_installed()
+            .once()
+            .returning(|_, _| Ok(false));
+        let deployment = Deployment::Docker(Arc::new(deployment));
+        let vault = vault::tests::create_test_vault(
+            HashMap::from([(MINIMAL_INSTANCE, deployment)]),
+            HashMap::new(),
+            None,
+        );
+        let app_key = AppKey {
+            name: MINIMAL_APP_WITH_INSTANCE_NAME.to_string(),
+            version: MINIMAL_APP_WITH_INSTANCE_VERSION.to_string(),
+        };
+        assert!(matches!(
+            update_instance(
+                Quest::new_synced("TestQuest"),
+                vault,
+                Arc::new(MockFloxy::new()),
+                MINIMAL_INSTANCE,
+                app_key.clone(),
+                testdir::testdir!(),
+            )
+            .await,
+            Err(UpdateInstanceError::NotStaged(key)) if key == app_key
+        ));
+    }
 }
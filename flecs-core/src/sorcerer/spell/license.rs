@@ -1,6 +1,13 @@
 pub use super::Result;
 use crate::fsm::console_client::ConsoleClient;
+use crate::jeweler::license::{
+    LicenseFile, LicenseFileError, LicenseRequest, LicenseValidation,
+    OFFLINE_ACTIVATION_ENTITLEMENT, OfflineLicense,
+};
+use crate::vault::pouch::Pouch;
+use crate::vault::{GrabbedPouches, Vault};
 use anyhow::{Context, anyhow};
+use ed25519_dalek::VerifyingKey;
 use flecs_console_client::apis::device_api::{
     PostApiV2DeviceLicenseActivateError, PostApiV2DeviceLicenseActivateSuccess,
     PostApiV2DeviceLicenseValidateSuccess,
@@ -42,10 +49,6 @@ pub async fn validate_license(
     .with_context(|| format!("Could not validate session id: {session_id:?}"))
 }
 
-pub fn read_serial_number() -> Option<String> {
-    None
-}
-
 #[derive(Debug, PartialEq)]
 pub enum ActivationResult {
     Activated(ActivationData),
@@ -103,6 +106,106 @@ pub async fn activate_via_license_key(
         .with_context(|| format!("Could not activate via license key {license_key}"))
 }
 
+#[derive(thiserror::Error, Debug)]
+pub enum ImportLicenseError {
+    #[error(transparent)]
+    File(#[from] LicenseFileError),
+    #[error(
+        "License was issued for device {expected}, but the serial number of this device is {}",
+        actual.as_deref().unwrap_or("unknown")
+    )]
+    SerialNumberMismatch {
+        expected: String,
+        actual: Option<String>,
+    },
+    #[error("License expired at {0}")]
+    Expired(u64),
+    #[error("License does not entitle the device to {0}")]
+    NotEntitled(&'static str),
+}
+
+pub fn create_license_request(serial_number: Option<String>, now: u64) -> Result<LicenseRequest> {
+    let mut system_info = crate::relic::system::info::try_create_system_info()?;
+    system_info.serial_number = serial_number.clone();
+    Ok(LicenseRequest {
+        serial_number,
+        system_info,
+        core_version: crate::lore::CORE_VERSION.to_string(),
+        created_at: now,
+    })
+}
+
+/// Verifies the signature of the license file and that the contained license is valid for this
+/// device at `now`
+pub fn verify_license_file(
+    license_file: &LicenseFile,
+    trusted_keys: &[VerifyingKey],
+    serial_number: Option<&str>,
+    now: u64,
+) -> Result<OfflineLicense, ImportLicenseError> {
+    let license = license_file.verify(trusted_keys)?;
+    if let Some(expected) = license
+        .serial_number
+        .as_ref()
+        .filter(|expected| serial_number != Some(expected.as_str()))
+    {
+        return Err(ImportLicenseError::SerialNumberMismatch {
+            expected: expected.clone(),
+            actual: serial_number.map(str::to_string),
+        });
+    }
+    if let Some(expires_at) = license.expires_at.filter(|_| license.is_expired(now)) {
+        return Err(ImportLicenseError::Expired(expires_at));
+    }
+    if !license.is_entitled_to(OFFLINE_ACTIVATION_ENTITLEMENT) {
+        return Err(ImportLicenseError::NotEntitled(
+            OFFLINE_ACTIVATION_ENTITLEMENT,
+        ));
+    }
+    Ok(license)
+}
+
+/// Stores the license and its license key, which replaces any license key obtained online
+pub async fn store_offline_license(vault: &Vault, license: OfflineLicense) {
+    let GrabbedPouches {
+        secret_pouch_mut: Some(ref mut secret_pouch),
+        ..
+    } = vault.reservation().reserve_secret_pouch_mut().grab().await
+    else {
+        unreachable!("Reservation should never fail");
+    };
+    secret_pouch.gems_mut().license_key = Some(license.license_key.clone());
+    secret_pouch.gems_mut().offline_license = Some(license);
+}
+
+/// Stores the outcome of a license validation and returns the previous one
+pub async fn store_license_validation(
+    vault: &Vault,
+    validation: LicenseValidation,
+) -> Option<LicenseValidation> {
+    let GrabbedPouches {
+        secret_pouch_mut: Some(ref mut secret_pouch),
+        ..
+    } = vault.reservation().reserve_secret_pouch_mut().grab().await
+    else {
+        unreachable!("Reservation should never fail");
+    };
+    secret_pouch
+        .gems_mut()
+        .license_validation
+        .replace(validation)
+}
+
+pub async fn has_valid_offline_license(vault: &Vault, now: u64) -> bool {
+    vault
+        .get_secrets()
+        .await
+        .offline_license
+        .is_some_and(|license| {
+            !license.is_expired(now) && license.is_entitled_to(OFFLINE_ACTIVATION_ENTITLEMENT)
+        })
+}
+
 pub async fn activate_via_user_license(
     configuration: ConsoleClient,
     authorization_token: &str,
@@ -128,10 +231,132 @@ mod tests {
     };
     use http::StatusCode;
 
+    use crate::jeweler::license::LicenseState;
+    use crate::jeweler::license::tests::{test_license, test_signing_key};
+    use crate::vault::tests::create_empty_test_vault;
+
     const LICENSE_KEY: &str = "1234-ABCD-5678-EFGH";
     const SESSION_ID: &str = "74c3b620-6048-4bfd-9bf7-c9857a001694";
     const TIMESTAMP: u64 = 17243237291234u64;
 
+    #[test]
+    fn verify_license_file_ok() {
+        let key = test_signing_key(3);
+        let file = LicenseFile::create(&key, &test_license());
+        assert_eq!(
+            verify_license_file(
+                &file,
+                &[key.verifying_key()],
+                Some("SN-1234"),
+                1_750_000_000
+            )
+            .unwrap(),
+            test_license()
+        );
+    }
+
+    #[test]
+    fn verify_license_file_serial_number_mismatch() {
+        let key = test_signing_key(3);
+        let file = LicenseFile::create(&key, &test_license());
+        assert!(matches!(
+            verify_license_file(&file, &[key.verifying_key()], None, 1_750_000_000),
+            Err(ImportLicenseError::SerialNumberMismatch { actual: None, .. })
+        ));
+        assert!(matches!(
+            verify_license_file(
+                &file,
+                &[key.verifying_key()],
+                Some("SN-5678"),
+                1_750_000_000
+            ),
+            Err(ImportLicenseError::SerialNumberMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn verify_license_file_expired() {
+        let key = test_signing_key(3);
+        let file = LicenseFile::create(&key, &test_license());
+        assert!(matches!(
+            verify_license_file(
+                &file,
+                &[key.verifying_key()],
+                Some("SN-1234"),
+                1_800_000_001
+            ),
+            Err(ImportLicenseError::Expired(1_800_000_000))
+        ));
+    }
+
+    #[test]
+    fn verify_license_file_not_entitled() {
+        let key = test_signing_key(3);
+        let license = OfflineLicense {
+            entitlements: vec!["exports".to_string()],
+            ..test_license()
+        };
+        let file = LicenseFile::create(&key, &license);
+        assert!(matches!(
+            verify_license_file(
+                &file,
+                &[key.verifying_key()],
+                Some("SN-1234"),
+                1_750_000_000
+            ),
+            Err(ImportLicenseError::NotEntitled(
+                OFFLINE_ACTIVATION_ENTITLEMENT
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn has_valid_offline_license_not_entitled() {
+        let vault = create_empty_test_vault();
+        let license = OfflineLicense {
+            entitlements: Vec::new(),
+            ..test_license()
+        };
+        store_offline_license(&vault, license).await;
+        assert!(!has_valid_offline_license(&vault, 1_750_000_000).await);
+    }
+
+    #[tokio::test]
+    async fn store_offline_license_ok() {
+        let vault = create_empty_test_vault();
+        assert!(!has_valid_offline_license(&vault, 1_750_000_000).await);
+        store_offline_license(&vault, test_license()).await;
+        let secrets = vault.get_secrets().await;
+        assert_eq!(secrets.license_key.as_deref(), Some("1234-ABCD-5678-EFGH"));
+        assert_eq!(secrets.offline_license, Some(test_license()));
+        assert!(has_valid_offline_license(&vault, 1_750_000_000).await);
+        assert!(!has_valid_offline_license(&vault, 1_800_000_000).await);
+    }
+
+    #[tokio::test]
+    async fn store_license_validation_ok() {
+        let vault = create_empty_test_vault();
+        let validation = LicenseValidation {
+            state: LicenseState::Valid,
+            last_validated: Some(1_750_000_000),
+            grace_until: None,
+        };
+        assert_eq!(
+            store_license_validation(&vault, validation.clone()).await,
+            None
+        );
+        let next = LicenseValidation {
+            state: LicenseState::GracePeriod,
+            grace_until: Some(1_750_086_400),
+            ..validation.clone()
+        };
+        assert_eq!(
+            store_license_validation(&vault, next.clone()).await,
+            Some(validation)
+        );
+        assert_eq!(vault.get_secrets().await.license_validation, Some(next));
+    }
+
     #[tokio::test]
     async fn activate_via_license_key_already_active_test() {
         let session = SessionId {
@@ -1,5 +1,6 @@
 pub(super) mod app;
 pub(super) mod auth;
+pub(super) mod certificate;
 pub(super) mod deployment;
 pub(super) mod flecsport;
 pub(super) mod flimport;
@@ -7,5 +8,7 @@ pub(super) mod instance;
 pub(super) mod license;
 pub(super) mod manifest;
 pub(super) mod provider;
+pub(super) mod state;
+pub(super) mod storage;
 
 pub use super::{Error, Result};
@@ -1,12 +1,14 @@
 use crate::jeweler::gem;
-#[cfg(feature = "auth")]
 use crate::jeweler::gem::instance::Instance;
+use crate::jeweler::gem::instance::provider_connection::{ProviderConnection, ProviderConnections};
 use crate::jeweler::gem::instance::status::InstanceStatus;
 use crate::jeweler::gem::instance::{InstanceId, ProviderReference, StoredProviderReference};
 #[cfg(feature = "auth")]
 use crate::jeweler::gem::manifest::providers::auth::AuthProvider;
+use crate::jeweler::gem::manifest::providers::features;
 use crate::jeweler::gem::manifest::{DependencyKey, FeatureKey};
 use crate::quest::{State, SyncQuest};
+use crate::relic::floxy::Floxy;
 use crate::sorcerer::instancius::QueryInstanceConfigError;
 use crate::sorcerer::providius::{Dependency, Provider};
 use crate::vault::pouch::provider::{CoreProviders, ProviderId};
@@ -16,9 +18,11 @@ use crate::vault::{GrabbedPouches, Vault, pouch};
 use crate::wall::watch;
 use anyhow::Context;
 use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use std::sync::Arc;
 use thiserror::Error;
+use tracing::{info, warn};
 
 #[derive(Error, Debug)]
 pub enum DeleteDefaultProviderError {
@@ -554,6 +558,58 @@ fn config_matches(
     }
 }
 
+/// Typed features report the first unsatisfied property, see [features::FeatureSchema]
+fn satisfies_dependency(
+    feature: &FeatureKey,
+    provider_config: &serde_json::Value,
+    dependency_config: &serde_json::Value,
+) -> Result<(), anyhow::Error> {
+    if let Some(schema) = features::feature_schema(feature) {
+        schema.check_satisfied(provider_config, dependency_config, |provided, required| {
+            config_matches(provided, required).is_ok()
+        })?;
+    }
+    config_matches(provider_config, dependency_config)
+}
+
+fn validate_candidate(
+    instances: &pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+    feature: &FeatureKey,
+    dependency_config: &serde_json::Value,
+    provider_reference: ProviderReference,
+) -> Result<(), SetDependencyError> {
+    let provider_id = match provider_reference {
+        ProviderReference::Provider(id) => id,
+        ProviderReference::Default => providers
+            .default_providers
+            .get(feature)
+            .cloned()
+            .ok_or_else(|| SetDependencyError::NoDefaultProvider {
+                feature: feature.clone(),
+            })?,
+    };
+    let Some(provider) = instances.get(&provider_id) else {
+        return Err(SetDependencyError::ProviderDoesNotExist(provider_id));
+    };
+    let manifest = provider.manifest();
+    let Some(provider_config) = manifest.provides().get(feature) else {
+        return Err(SetDependencyError::ProviderDoesNotProvideFeature {
+            feature: feature.clone(),
+            provider_id,
+        });
+    };
+    satisfies_dependency(feature, provider_config, dependency_config).map_err(|error| {
+        SetDependencyError::FeatureConfigNotMatching {
+            provider_id,
+            error,
+            feature: feature.clone(),
+        }
+    })
+}
+
+/// Binds the dependency to the provider, the fallback providers are used in the given order if
+/// the provider is not available, see [resolve_available_provider_id]
 pub async fn set_dependency(
     instances: &mut pouch::instance::Gems,
     providers: &pouch::provider::Gems,
@@ -561,6 +617,7 @@ pub async fn set_dependency(
     feature: FeatureKey,
     id: InstanceId,
     provider_reference: ProviderReference,
+    fallback_providers: Vec<ProviderReference>,
 ) -> Result<Option<ProviderReference>, SetDependencyError> {
     let Some(instance) = instances.get(&id) else {
         return Err(SetDependencyError::InstanceNotFound(id));
@@ -590,33 +647,15 @@ pub async fn set_dependency(
         }
     };
 
-    let provider_id = match provider_reference {
-        ProviderReference::Provider(id) => id,
-        ProviderReference::Default => providers
-            .default_providers
-            .get(&feature)
-            .cloned()
-            .ok_or_else(|| SetDependencyError::NoDefaultProvider {
-                feature: feature.clone(),
-            })?,
-    };
-    let Some(provider) = instances.get(&provider_id) else {
-        return Err(SetDependencyError::ProviderDoesNotExist(provider_id));
-    };
-    let manifest = provider.manifest();
-    let Some(provider_config) = manifest.provides().get(&feature) else {
-        return Err(SetDependencyError::ProviderDoesNotProvideFeature {
-            feature: feature.clone(),
-            provider_id,
-        });
-    };
-    config_matches(provider_config, dependency_config).map_err(|error| {
-        SetDependencyError::FeatureConfigNotMatching {
-            provider_id,
-            error,
-            feature: feature.clone(),
-        }
-    })?;
+    for candidate in std::iter::once(&provider_reference).chain(fallback_providers.iter()) {
+        validate_candidate(
+            instances,
+            providers,
+            &feature,
+            dependency_config,
+            *candidate,
+        )?;
+    }
     if instance.status().await? == InstanceStatus::Running {
         return Err(SetDependencyError::InstanceRunning { instance_id: id });
     }
@@ -626,6 +665,7 @@ pub async fn set_dependency(
     let provider_reference = StoredProviderReference {
         provider_reference,
         provided_feature: feature,
+        fallback_providers,
     };
     Ok(instance
         .set_dependency(dependency_key, provider_reference)
@@ -705,6 +745,7 @@ pub async fn set_default_dependency(
             feature.clone(),
             id,
             ProviderReference::Default,
+            Vec::new(),
         )
         .await
         {
@@ -716,6 +757,385 @@ pub async fn set_default_dependency(
     Err(errors)
 }
 
+fn resolve_provider_reference(
+    providers: &pouch::provider::Gems,
+    feature: &FeatureKey,
+    provider_reference: &ProviderReference,
+) -> Option<ProviderId> {
+    match provider_reference {
+        ProviderReference::Provider(id) => Some(*id),
+        ProviderReference::Default => providers.default_providers.get(feature).copied(),
+    }
+}
+
+/// Resolves the first candidate of the dependency whose provider is running, see
+/// [StoredProviderReference::candidates]. Dependencies without fallback providers and
+/// dependencies without any running provider resolve to their first existing provider.
+pub async fn resolve_available_provider_id(
+    instances: &pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+    dependency: &StoredProviderReference,
+) -> Option<ProviderId> {
+    let feature = &dependency.provided_feature;
+    if dependency.fallback_providers.is_empty() {
+        return resolve_provider_reference(providers, feature, &dependency.provider_reference)
+            .filter(|id| instances.contains_key(id));
+    }
+    let mut first_existing = None;
+    for candidate in dependency.candidates() {
+        let Some(id) = resolve_provider_reference(providers, feature, candidate) else {
+            continue;
+        };
+        let Some(provider) = instances.get(&id) else {
+            continue;
+        };
+        match provider.status().await {
+            Ok(InstanceStatus::Running) => return Some(id),
+            Ok(_) => {}
+            Err(e) => warn!("Could not get status of provider {id}: {e}"),
+        }
+        first_existing.get_or_insert(id);
+    }
+    first_existing
+}
+
+/// Resolves the connection details of the providers the dependencies of the instance are bound
+/// to, dependencies without an existing provider are skipped
+pub async fn resolve_provider_connections(
+    instances: &pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+    id: InstanceId,
+) -> ProviderConnections {
+    let mut connections = ProviderConnections::new();
+    let Some(instance) = instances.get(&id) else {
+        return connections;
+    };
+    for (key, dependency) in instance.dependencies() {
+        let Some(provider_id) =
+            resolve_available_provider_id(instances, providers, dependency).await
+        else {
+            continue;
+        };
+        let Some(provider) = instances.get(&provider_id) else {
+            continue;
+        };
+        let provides = provider
+            .manifest()
+            .provides()
+            .get(&dependency.provided_feature)
+            .cloned()
+            .unwrap_or_default();
+        let (hostname, address) = match provider {
+            Instance::Docker(provider) => {
+                let address = provider
+                    .get_default_network_address()
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Could not get address of provider {provider_id}: {e}");
+                        None
+                    });
+                (Some(provider.hostname.clone()), address)
+            }
+            Instance::Compose(_) => (None, None),
+        };
+        connections.insert(
+            key.clone(),
+            ProviderConnection {
+                provider_id,
+                feature: dependency.provided_feature.clone(),
+                hostname,
+                address,
+                provides,
+            },
+        );
+    }
+    connections
+}
+
+/// A dependency of an instance was bound to another provider than before
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rebinding {
+    pub instance_id: InstanceId,
+    pub key: DependencyKey,
+    pub previous: ProviderId,
+    /// None if no provider of the dependency exists anymore
+    pub current: Option<ProviderId>,
+}
+
+impl Display for Rebinding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.current {
+            Some(current) => write!(
+                f,
+                "Rebound dependency {} of instance {} from provider {} to provider {current}",
+                self.key, self.instance_id, self.previous
+            ),
+            None => write!(
+                f,
+                "Dependency {} of instance {} is no longer bound to provider {}",
+                self.key, self.instance_id, self.previous
+            ),
+        }
+    }
+}
+
+fn rebindings(
+    instance_id: InstanceId,
+    previous: &ProviderConnections,
+    current: &ProviderConnections,
+) -> Vec<Rebinding> {
+    let mut rebindings: Vec<_> = previous
+        .iter()
+        .filter_map(|(key, previous)| {
+            let current = current.get(key).map(|connection| connection.provider_id);
+            (current != Some(previous.provider_id)).then(|| Rebinding {
+                instance_id,
+                key: key.clone(),
+                previous: previous.provider_id,
+                current,
+            })
+        })
+        .collect();
+    rebindings.sort_by_key(|rebinding| rebinding.key.to_string());
+    rebindings
+}
+
+/// Records each rebinding as sub-quest of the quest
+pub async fn record_rebindings(quest: &SyncQuest, rebindings: Vec<Rebinding>) {
+    for rebinding in rebindings {
+        let description = rebinding.to_string();
+        info!("{description}");
+        let result = quest
+            .lock()
+            .await
+            .create_infallible_sub_quest(description, |_quest| async {})
+            .await
+            .2;
+        result.await;
+    }
+}
+
+/// Updates the stored provider connections of the instance. Returns None if they did not change,
+/// otherwise the dependencies that were bound to another provider than before.
+pub async fn refresh_provider_connections(
+    instances: &mut pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+    id: InstanceId,
+) -> Option<Vec<Rebinding>> {
+    let connections = resolve_provider_connections(instances, providers, id).await;
+    let instance = instances.get_mut(&id)?;
+    if *instance.provider_connections() == connections {
+        return None;
+    }
+    let rebindings = rebindings(id, instance.provider_connections(), &connections);
+    instance.replace_provider_connections(connections);
+    Some(rebindings)
+}
+
+/// Instances with a dependency that is or was bound to the specified provider
+pub fn get_dependents(
+    instances: &pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+    provider_id: ProviderId,
+) -> Vec<InstanceId> {
+    instances
+        .iter()
+        .filter(|(_, instance)| {
+            instance.dependencies().values().any(|dependency| {
+                dependency.candidates().any(|candidate| {
+                    resolve_provider_reference(providers, &dependency.provided_feature, candidate)
+                        == Some(provider_id)
+                })
+            }) || instance
+                .provider_connections()
+                .values()
+                .any(|connection| connection.provider_id == provider_id)
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// Instances with a dependency with fallback providers that is or was bound to the specified
+/// provider, i.e. instances which fail over if the provider becomes unavailable
+pub fn get_failover_dependents(
+    instances: &pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+    provider_id: ProviderId,
+) -> Vec<InstanceId> {
+    instances
+        .iter()
+        .filter(|(_, instance)| {
+            instance.dependencies().iter().any(|(key, dependency)| {
+                !dependency.fallback_providers.is_empty()
+                    && (dependency.candidates().any(|candidate| {
+                        resolve_provider_reference(
+                            providers,
+                            &dependency.provided_feature,
+                            candidate,
+                        ) == Some(provider_id)
+                    }) || instance
+                        .provider_connections()
+                        .get(key)
+                        .is_some_and(|connection| connection.provider_id == provider_id))
+            })
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// Instances with a dependency with fallback providers that is currently bound to another
+/// provider than the one it resolves to, e.g. because the provider stopped
+pub async fn get_pending_failovers(
+    instances: &pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+) -> Vec<InstanceId> {
+    let mut pending = Vec::new();
+    for (id, instance) in instances {
+        for (key, dependency) in instance.dependencies() {
+            if dependency.fallback_providers.is_empty() {
+                continue;
+            }
+            let Some(connection) = instance.provider_connections().get(key) else {
+                continue;
+            };
+            if resolve_available_provider_id(instances, providers, dependency).await
+                != Some(connection.provider_id)
+            {
+                pending.push(*id);
+                break;
+            }
+        }
+    }
+    pending
+}
+
+async fn restart_instance(instance: &Instance, floxy: Arc<dyn Floxy>) -> anyhow::Result<()> {
+    instance.halt().await?;
+    match instance {
+        Instance::Docker(instance) => instance.resume(floxy).await,
+        Instance::Compose(instance) => instance.resume().await,
+    }
+}
+
+/// Refreshes the provider connections of the specified instances and restarts the running ones
+/// whose connections changed, returns the results of the restarts. Rebindings are recorded as
+/// sub-quests of the quest.
+pub async fn restart_changed_dependents(
+    quest: &SyncQuest,
+    instances: &mut pouch::instance::Gems,
+    providers: &pouch::provider::Gems,
+    floxy: Arc<dyn Floxy>,
+    ids: Vec<InstanceId>,
+) -> Vec<(InstanceId, anyhow::Result<()>)> {
+    let mut results = Vec::new();
+    for id in ids {
+        let Some(rebindings) = refresh_provider_connections(instances, providers, id).await else {
+            continue;
+        };
+        record_rebindings(quest, rebindings).await;
+        let Some(instance) = instances.get(&id) else {
+            continue;
+        };
+        let result = match instance.status().await {
+            Ok(InstanceStatus::Running) => restart_instance(instance, floxy.clone()).await,
+            Ok(_) => continue,
+            Err(e) => Err(e),
+        };
+        results.push((id, result));
+    }
+    results
+}
+
+/// Summarizes the results of [restart_changed_dependents] as quest detail, returns the ids of the
+/// instances that could not be restarted
+pub fn describe_restarts(
+    results: Vec<(InstanceId, anyhow::Result<()>)>,
+) -> (String, Vec<InstanceId>) {
+    let mut failed = Vec::new();
+    let detail = results
+        .into_iter()
+        .map(|(id, result)| match result {
+            Ok(()) => format!("Restarted dependent instance {id}"),
+            Err(e) => {
+                failed.push(id);
+                format!("Failed to restart dependent instance {id}: {e}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    (detail, failed)
+}
+
+async fn restart_changed_dependents_matching<F>(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+    filter: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(&InstanceId, &Instance) -> bool,
+{
+    let GrabbedPouches {
+        provider_pouch: Some(ref providers),
+        instance_pouch_mut: Some(ref mut instances),
+        ..
+    } = vault
+        .reservation()
+        .reserve_provider_pouch()
+        .reserve_instance_pouch_mut()
+        .grab()
+        .await
+    else {
+        unreachable!("Reservation should never fail");
+    };
+    let ids = instances
+        .gems()
+        .iter()
+        .filter(|(id, instance)| filter(id, instance))
+        .map(|(id, _)| *id)
+        .collect();
+    let results =
+        restart_changed_dependents(&quest, instances.gems_mut(), providers.gems(), floxy, ids)
+            .await;
+    let mut quest = quest.lock().await;
+    if results.is_empty() {
+        quest.state = State::Skipped;
+        quest.detail = Some("No dependent instance had to be restarted".to_string());
+        return Ok(());
+    }
+    let (detail, failed) = describe_restarts(results);
+    quest.detail = Some(detail);
+    anyhow::ensure!(
+        failed.is_empty(),
+        "Failed to restart dependent instances {failed:?}"
+    );
+    Ok(())
+}
+
+/// Refreshes the provider connections of all instances with dependencies and restarts running
+/// instances whose connections changed, e.g. after the default provider of a feature changed
+pub async fn update_dependents(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+) -> anyhow::Result<()> {
+    restart_changed_dependents_matching(quest, vault, floxy, |_, instance| {
+        !instance.dependencies().is_empty() || !instance.provider_connections().is_empty()
+    })
+    .await
+}
+
+/// Binds the dependencies of the specified instances to their first available provider and
+/// restarts running instances whose connections changed, see [get_pending_failovers]
+pub async fn fail_over_dependents(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    floxy: Arc<dyn Floxy>,
+    ids: Vec<InstanceId>,
+) -> anyhow::Result<()> {
+    restart_changed_dependents_matching(quest, vault, floxy, |id, _| ids.contains(id)).await
+}
+
 #[cfg(feature = "auth")]
 pub async fn build_watch_config_from_auth_provider(
     instances: &pouch::instance::Gems,
@@ -973,4 +1393,190 @@ mod tests {
     fn split_escaped_trailing_slash() {
         assert_eq!(split_escaped("1234\\"), str_vec(&["1234\\"]));
     }
+
+    #[test]
+    fn satisfies_dependency_typed_feature() {
+        let feature = FeatureKey::from_str("mqtt-broker").unwrap();
+        let provider_config = json!({"port": 1883, "versions": ["3.1.1", "5"], "tls": false});
+        satisfies_dependency(&feature, &provider_config, &json!({"versions": "5"})).unwrap();
+        let error =
+            satisfies_dependency(&feature, &provider_config, &json!({"tls": true})).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Dependency requires tls of feature mqtt-broker to match true, but the provider offers false"
+        );
+    }
+
+    #[test]
+    fn satisfies_dependency_untyped_feature() {
+        let feature = FeatureKey::from_str("custom").unwrap();
+        satisfies_dependency(&feature, &json!({"a": 1}), &json!({"a": 1})).unwrap();
+        assert!(satisfies_dependency(&feature, &json!({"a": 1}), &json!({"a": 2})).is_err());
+    }
+
+    #[test]
+    fn describe_restarts_ok() {
+        let (detail, failed) = describe_restarts(vec![
+            (InstanceId::new(1), Ok(())),
+            (InstanceId::new(2), Err(anyhow::anyhow!("TestError"))),
+        ]);
+        assert_eq!(
+            detail,
+            "Restarted dependent instance 00000001\n\
+             Failed to restart dependent instance 00000002: TestError"
+        );
+        assert_eq!(failed, vec![InstanceId::new(2)]);
+    }
+
+    #[tokio::test]
+    async fn update_dependents_no_dependents() {
+        let vault = crate::vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        let quest = crate::quest::Quest::new_synced("TestQuest");
+        update_dependents(
+            quest.clone(),
+            vault,
+            Arc::new(crate::relic::floxy::MockFloxy::new()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(quest.lock().await.state, State::Skipped);
+    }
+
+    #[tokio::test]
+    async fn fail_over_dependents_unknown_instance() {
+        let vault = crate::vault::tests::create_test_vault(HashMap::new(), HashMap::new(), None);
+        let quest = crate::quest::Quest::new_synced("TestQuest");
+        fail_over_dependents(
+            quest.clone(),
+            vault,
+            Arc::new(crate::relic::floxy::MockFloxy::new()),
+            vec![InstanceId::new(1)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(quest.lock().await.state, State::Skipped);
+    }
+
+    fn test_dependency(fallback_providers: Vec<ProviderReference>) -> StoredProviderReference {
+        StoredProviderReference {
+            provider_reference: ProviderReference::Provider(InstanceId::new(1)),
+            provided_feature: FeatureKey::from_str("mqtt").unwrap(),
+            fallback_providers,
+        }
+    }
+
+    #[test]
+    fn dependency_candidates() {
+        let dependency = test_dependency(vec![
+            ProviderReference::Default,
+            ProviderReference::Provider(InstanceId::new(2)),
+        ]);
+        assert_eq!(
+            dependency.candidates().copied().collect::<Vec<_>>(),
+            vec![
+                ProviderReference::Provider(InstanceId::new(1)),
+                ProviderReference::Default,
+                ProviderReference::Provider(InstanceId::new(2)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_available_provider_id_no_existing_provider() {
+        let instances = pouch::instance::Gems::new();
+        let providers = pouch::provider::Gems::default();
+        assert_eq!(
+            resolve_available_provider_id(&instances, &providers, &test_dependency(Vec::new()))
+                .await,
+            None
+        );
+        let dependency = test_dependency(vec![
+            ProviderReference::Default,
+            ProviderReference::Provider(InstanceId::new(2)),
+        ]);
+        assert_eq!(
+            resolve_available_provider_id(&instances, &providers, &dependency).await,
+            None
+        );
+    }
+
+    fn connection_to(provider_id: u32) -> ProviderConnection {
+        ProviderConnection {
+            provider_id: InstanceId::new(provider_id),
+            ..gem::instance::provider_connection::tests::test_connection()
+        }
+    }
+
+    #[test]
+    fn rebindings_ok() {
+        let instance_id = InstanceId::new(10);
+        let previous = HashMap::from([
+            (DependencyKey::new("a"), connection_to(1)),
+            (DependencyKey::new("b"), connection_to(2)),
+            (DependencyKey::new("c"), connection_to(3)),
+        ]);
+        let current = HashMap::from([
+            (DependencyKey::new("a"), connection_to(1)),
+            (DependencyKey::new("b"), connection_to(4)),
+            (DependencyKey::new("d"), connection_to(5)),
+        ]);
+        assert_eq!(
+            rebindings(instance_id, &previous, &current),
+            vec![
+                Rebinding {
+                    instance_id,
+                    key: DependencyKey::new("b"),
+                    previous: InstanceId::new(2),
+                    current: Some(InstanceId::new(4)),
+                },
+                Rebinding {
+                    instance_id,
+                    key: DependencyKey::new("c"),
+                    previous: InstanceId::new(3),
+                    current: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rebinding_display() {
+        let mut rebinding = Rebinding {
+            instance_id: InstanceId::new(10),
+            key: DependencyKey::new("mqtt"),
+            previous: InstanceId::new(1),
+            current: Some(InstanceId::new(2)),
+        };
+        assert_eq!(
+            rebinding.to_string(),
+            "Rebound dependency mqtt of instance 0000000a from provider 00000001 to provider 00000002"
+        );
+        rebinding.current = None;
+        assert_eq!(
+            rebinding.to_string(),
+            "Dependency mqtt of instance 0000000a is no longer bound to provider 00000001"
+        );
+    }
+
+    #[tokio::test]
+    async fn record_rebindings_sub_quests() {
+        let quest = crate::quest::Quest::new_synced("TestQuest");
+        let rebinding = Rebinding {
+            instance_id: InstanceId::new(10),
+            key: DependencyKey::new("mqtt"),
+            previous: InstanceId::new(1),
+            current: Some(InstanceId::new(2)),
+        };
+        record_rebindings(&quest, vec![rebinding.clone()]).await;
+        let sub_quests = crate::quest::Quest::create_model(quest)
+            .await
+            .subquests
+            .unwrap();
+        assert_eq!(sub_quests.len(), 1);
+        assert_eq!(sub_quests[0].description, rebinding.to_string());
+        assert_eq!(
+            sub_quests[0].state,
+            flecsd_axum_server::models::QuestState::Success
+        );
+    }
 }
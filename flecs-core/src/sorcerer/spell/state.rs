@@ -0,0 +1,697 @@
+use crate::forge::bollard::BollardNetworkExtension;
+use crate::jeweler::deployment::DeploymentId;
+use crate::jeweler::gem::instance::docker::config::InstanceConfig;
+use crate::jeweler::gem::instance::status::InstanceStatus;
+use crate::jeweler::gem::instance::{Instance, InstanceId};
+use crate::jeweler::gem::manifest::FeatureKey;
+use crate::jeweler::network::Network;
+use crate::quest::Quest;
+use crate::sorcerer::statius::{
+    DesiredStatus, DeviceState, InstanceState, NetworkState, StateAction,
+};
+use crate::vault::pouch::provider::ProviderId;
+use crate::vault::pouch::{AppKey, Pouch};
+use crate::vault::{GrabbedPouches, Vault};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+/// Networks every deployment creates on its own, they are neither exported nor created
+const BUILTIN_NETWORKS: [&str; 4] = ["bridge", "host", "none", "podman"];
+/// Options that are derived from the network kind and parent adapter on creation
+const DERIVED_NETWORK_OPTIONS: [&str; 2] = ["parent", "ipvlan_mode"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum PlanStateError {
+    #[error("Instance name '{0}' is used more than once in the desired state")]
+    DuplicateInstanceName(String),
+    #[error("Network '{0}' is defined more than once in the desired state")]
+    DuplicateNetworkName(String),
+    #[error("Instance name '{0}' is ambiguous, multiple instances with this name exist")]
+    AmbiguousInstanceName(String),
+    #[error("Instance '{name}' is an instance of app {current}, not of app {desired}")]
+    AppMismatch {
+        name: String,
+        current: String,
+        desired: String,
+    },
+    #[error("Default provider '{name}' for feature {feature} is not an instance of the state")]
+    UnknownProvider { feature: FeatureKey, name: String },
+    #[error("No deployment to create networks in found")]
+    NoNetworkDeployment,
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CurrentState {
+    pub apps: Vec<AppKey>,
+    pub instances: Vec<(InstanceId, InstanceState)>,
+    pub networks: Vec<NetworkState>,
+    /// Deployment in which the networks were found and new networks are created
+    pub network_deployment: Option<DeploymentId>,
+    pub default_providers: HashMap<FeatureKey, ProviderId>,
+}
+
+impl CurrentState {
+    pub fn to_device_state(&self) -> DeviceState {
+        let names: HashMap<InstanceId, &str> = self
+            .instances
+            .iter()
+            .map(|(id, instance)| (*id, instance.name.as_str()))
+            .collect();
+        DeviceState {
+            apps: self.apps.clone(),
+            networks: self.networks.clone(),
+            instances: self
+                .instances
+                .iter()
+                .map(|(_, instance)| instance.clone())
+                .collect(),
+            default_providers: self
+                .default_providers
+                .iter()
+                .filter_map(|(feature, id)| Some((feature.clone(), names.get(id)?.to_string())))
+                .collect(),
+        }
+    }
+
+    fn find_instance(
+        &self,
+        name: &str,
+    ) -> Result<Option<&(InstanceId, InstanceState)>, PlanStateError> {
+        let mut matching = self
+            .instances
+            .iter()
+            .filter(|(_, instance)| instance.name == name);
+        match (matching.next(), matching.next()) {
+            (Some(_), Some(_)) => Err(PlanStateError::AmbiguousInstanceName(name.to_string())),
+            (instance, _) => Ok(instance),
+        }
+    }
+}
+
+fn instance_state(instance: &Instance) -> InstanceState {
+    InstanceState {
+        name: instance.name().to_string(),
+        app_key: instance.app_key().clone(),
+        desired: match instance.desired_status() {
+            InstanceStatus::Running => DesiredStatus::Running,
+            _ => DesiredStatus::Stopped,
+        },
+        config: match instance {
+            Instance::Docker(instance) => Some(instance.config.clone()),
+            Instance::Compose(_) => None,
+        },
+    }
+}
+
+fn network_state(network: &Network) -> anyhow::Result<Option<NetworkState>> {
+    let Some(name) = network.name.clone() else {
+        return Ok(None);
+    };
+    if BUILTIN_NETWORKS.contains(&name.as_str()) {
+        return Ok(None);
+    }
+    let options = network
+        .options
+        .as_ref()
+        .map(|options| {
+            options
+                .iter()
+                .filter(|(key, _)| !DERIVED_NETWORK_OPTIONS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<HashMap<_, _>>()
+        })
+        .filter(|options| !options.is_empty());
+    Ok(Some(NetworkState {
+        kind: network.guess_network_kind(),
+        subnet: network.subnet_ipv4()?,
+        gateway: network.gateway_ipv4()?,
+        parent_adapter: network.parent_network(),
+        options,
+        name,
+    }))
+}
+
+pub async fn get_current_state(vault: Arc<Vault>) -> anyhow::Result<CurrentState> {
+    let (mut state, deployment) = {
+        let GrabbedPouches {
+            app_pouch: Some(ref apps),
+            instance_pouch: Some(ref instances),
+            provider_pouch: Some(ref providers),
+            deployment_pouch: Some(ref deployments),
+            ..
+        } = vault
+            .reservation()
+            .reserve_app_pouch()
+            .reserve_instance_pouch()
+            .reserve_provider_pouch()
+            .reserve_deployment_pouch()
+            .grab()
+            .await
+        else {
+            unreachable!("Reservation should never fail");
+        };
+        let mut app_keys: Vec<AppKey> = apps.gems().keys().cloned().collect();
+        app_keys.sort();
+        let mut instances: Vec<(InstanceId, InstanceState)> = instances
+            .gems()
+            .iter()
+            .map(|(id, instance)| (*id, instance_state(instance)))
+            .collect();
+        instances.sort_by(|(id_1, instance_1), (id_2, instance_2)| {
+            (&instance_1.name, id_1.value).cmp(&(&instance_2.name, id_2.value))
+        });
+        let deployment = deployments.default_docker_deployment();
+        (
+            CurrentState {
+                apps: app_keys,
+                instances,
+                networks: Vec::new(),
+                network_deployment: deployment
+                    .as_ref()
+                    .map(|deployment| deployment.id().clone()),
+                default_providers: providers.gems().default_providers.clone(),
+            },
+            deployment,
+        )
+    };
+    if let Some(deployment) = deployment {
+        let networks = deployment
+            .networks(Quest::new_synced(format!(
+                "Get networks of {}",
+                deployment.id()
+            )))
+            .await?;
+        for network in networks.iter() {
+            if let Some(network) = network_state(network)? {
+                state.networks.push(network);
+            }
+        }
+        state.networks.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    Ok(state)
+}
+
+/// Volume mounts, mapped editor ports and provider connections belong to the specific instance and
+/// are not compared
+fn config_matches(current: Option<&InstanceConfig>, desired: &InstanceConfig) -> bool {
+    current.is_some_and(|current| {
+        *current
+            == InstanceConfig {
+                volume_mounts: current.volume_mounts.clone(),
+                mapped_editor_ports: current.mapped_editor_ports.clone(),
+                provider_connections: current.provider_connections.clone(),
+                ..desired.clone()
+            }
+    })
+}
+
+/// The actions are ordered so that every action only depends on previous actions: Apps and
+/// networks are created first, instances are stopped before they are updated or reconfigured,
+/// pruned instances and apps are removed before new instances are created and instances are
+/// started last after all default providers are set.
+pub fn plan_state(
+    current: &CurrentState,
+    desired: &DeviceState,
+    prune: bool,
+) -> Result<Vec<StateAction>, PlanStateError> {
+    let mut desired_names = HashSet::new();
+    for instance in desired.instances.iter() {
+        if !desired_names.insert(instance.name.as_str()) {
+            return Err(PlanStateError::DuplicateInstanceName(instance.name.clone()));
+        }
+    }
+    let mut desired_networks = HashSet::new();
+    for network in desired.networks.iter() {
+        if !desired_networks.insert(network.name.as_str()) {
+            return Err(PlanStateError::DuplicateNetworkName(network.name.clone()));
+        }
+    }
+    let desired_apps: BTreeSet<&AppKey> = desired
+        .apps
+        .iter()
+        .chain(desired.instances.iter().map(|instance| &instance.app_key))
+        .collect();
+    let mut actions: Vec<StateAction> = desired_apps
+        .iter()
+        .filter(|app_key| !current.apps.contains(app_key))
+        .map(|app_key| StateAction::InstallApp {
+            app_key: (*app_key).clone(),
+        })
+        .collect();
+    for network in desired.networks.iter() {
+        if current
+            .networks
+            .iter()
+            .any(|existing| existing.name == network.name)
+        {
+            continue;
+        }
+        actions.push(StateAction::CreateNetwork {
+            deployment_id: current
+                .network_deployment
+                .clone()
+                .ok_or(PlanStateError::NoNetworkDeployment)?,
+            network: network.clone(),
+        });
+    }
+    let mut stops = Vec::new();
+    let mut updates = Vec::new();
+    let mut creates = Vec::new();
+    let mut configures = Vec::new();
+    let mut starts = Vec::new();
+    for instance in desired.instances.iter() {
+        let name = instance.name.clone();
+        let Some((id, existing)) = current.find_instance(&instance.name)? else {
+            creates.push(StateAction::CreateInstance {
+                name: name.clone(),
+                app_key: instance.app_key.clone(),
+            });
+            if let Some(config) = &instance.config {
+                configures.push(StateAction::ConfigureInstance {
+                    name: name.clone(),
+                    id: None,
+                    config: config.clone(),
+                });
+            }
+            if instance.desired == DesiredStatus::Running {
+                starts.push(StateAction::StartInstance { name, id: None });
+            }
+            continue;
+        };
+        if existing.app_key.name != instance.app_key.name {
+            return Err(PlanStateError::AppMismatch {
+                name,
+                current: existing.app_key.name.clone(),
+                desired: instance.app_key.name.clone(),
+            });
+        }
+        let instance_id = *id;
+        let id = Some(instance_id);
+        let mut running = existing.desired == DesiredStatus::Running;
+        if existing.app_key.version != instance.app_key.version {
+            updates.push(StateAction::UpdateInstance {
+                name: name.clone(),
+                id: instance_id,
+                app_key: instance.app_key.clone(),
+            });
+        }
+        if let Some(config) = &instance.config {
+            if !config_matches(existing.config.as_ref(), config) {
+                if running {
+                    stops.push(StateAction::StopInstance {
+                        name: name.clone(),
+                        id,
+                    });
+                    running = false;
+                }
+                configures.push(StateAction::ConfigureInstance {
+                    name: name.clone(),
+                    id,
+                    config: config.clone(),
+                });
+            }
+        }
+        match (instance.desired, running) {
+            (DesiredStatus::Running, false) => starts.push(StateAction::StartInstance { name, id }),
+            (DesiredStatus::Stopped, true) => stops.push(StateAction::StopInstance { name, id }),
+            _ => {}
+        }
+    }
+    let mut providers = Vec::new();
+    let mut default_providers: Vec<_> = desired.default_providers.iter().collect();
+    default_providers.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+    for (feature, name) in default_providers {
+        let existing = current.find_instance(name)?;
+        if existing.is_none() && !desired_names.contains(name.as_str()) {
+            return Err(PlanStateError::UnknownProvider {
+                feature: feature.clone(),
+                name: name.clone(),
+            });
+        }
+        let id = existing.map(|(id, _)| *id);
+        if id.is_some() && current.default_providers.get(feature) == id.as_ref() {
+            continue;
+        }
+        providers.push(StateAction::SetDefaultProvider {
+            feature: feature.clone(),
+            name: name.clone(),
+            id,
+        });
+    }
+    actions.append(&mut stops);
+    actions.append(&mut updates);
+    if prune {
+        actions.extend(
+            current
+                .instances
+                .iter()
+                .filter(|(_, instance)| !desired_names.contains(instance.name.as_str()))
+                .map(|(id, instance)| StateAction::DeleteInstance {
+                    name: instance.name.clone(),
+                    id: *id,
+                }),
+        );
+        actions.extend(
+            current
+                .apps
+                .iter()
+                .filter(|app_key| !desired_apps.contains(app_key))
+                .map(|app_key| StateAction::UninstallApp {
+                    app_key: app_key.clone(),
+                }),
+        );
+    }
+    actions.append(&mut creates);
+    actions.append(&mut configures);
+    actions.append(&mut providers);
+    actions.append(&mut starts);
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::manifest::single::EnvironmentVariable;
+    use crate::jeweler::network::NetworkKind;
+    use ipnet::Ipv4Net;
+    use std::str::FromStr;
+
+    fn app_key(name: &str, version: &str) -> AppKey {
+        AppKey {
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    fn instance(name: &str, app_key: AppKey, desired: DesiredStatus) -> InstanceState {
+        InstanceState {
+            name: name.to_string(),
+            app_key,
+            desired,
+            config: Some(InstanceConfig::default()),
+        }
+    }
+
+    fn network(name: &str) -> NetworkState {
+        NetworkState {
+            name: name.to_string(),
+            kind: NetworkKind::Bridge,
+            subnet: None,
+            gateway: None,
+            parent_adapter: None,
+            options: None,
+        }
+    }
+
+    fn current_state() -> CurrentState {
+        CurrentState {
+            apps: vec![app_key("tech.flecs.app-1", "1.0.0")],
+            instances: vec![(
+                InstanceId::new(1),
+                instance(
+                    "app-1",
+                    app_key("tech.flecs.app-1", "1.0.0"),
+                    DesiredStatus::Running,
+                ),
+            )],
+            networks: vec![network("flecs")],
+            network_deployment: Some("docker".to_string()),
+            default_providers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn plan_state_unchanged() {
+        let current = current_state();
+        let desired = current.to_device_state();
+        assert!(plan_state(&current, &desired, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn plan_state_create() {
+        let current = current_state();
+        let mut desired = current.to_device_state();
+        desired.networks.push(network("data"));
+        desired.instances.push(instance(
+            "app-2",
+            app_key("tech.flecs.app-2", "2.0.0"),
+            DesiredStatus::Running,
+        ));
+        desired
+            .default_providers
+            .insert(FeatureKey::from_str("mqtt").unwrap(), "app-2".to_string());
+        assert_eq!(
+            plan_state(&current, &desired, false).unwrap(),
+            vec![
+                StateAction::InstallApp {
+                    app_key: app_key("tech.flecs.app-2", "2.0.0")
+                },
+                StateAction::CreateNetwork {
+                    deployment_id: "docker".to_string(),
+                    network: network("data"),
+                },
+                StateAction::CreateInstance {
+                    name: "app-2".to_string(),
+                    app_key: app_key("tech.flecs.app-2", "2.0.0")
+                },
+                StateAction::ConfigureInstance {
+                    name: "app-2".to_string(),
+                    id: None,
+                    config: InstanceConfig::default(),
+                },
+                StateAction::SetDefaultProvider {
+                    feature: FeatureKey::from_str("mqtt").unwrap(),
+                    name: "app-2".to_string(),
+                    id: None,
+                },
+                StateAction::StartInstance {
+                    name: "app-2".to_string(),
+                    id: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_state_reconfigure_and_update() {
+        let current = current_state();
+        let mut desired = current.to_device_state();
+        desired.apps.clear();
+        desired.instances[0].app_key.version = "1.1.0".to_string();
+        let config = InstanceConfig {
+            environment_variables: vec![EnvironmentVariable {
+                name: "VAR".to_string(),
+                value: None,
+            }],
+            ..InstanceConfig::default()
+        };
+        desired.instances[0].config = Some(config.clone());
+        let id = Some(InstanceId::new(1));
+        assert_eq!(
+            plan_state(&current, &desired, true).unwrap(),
+            vec![
+                StateAction::InstallApp {
+                    app_key: app_key("tech.flecs.app-1", "1.1.0")
+                },
+                StateAction::StopInstance {
+                    name: "app-1".to_string(),
+                    id
+                },
+                StateAction::UpdateInstance {
+                    name: "app-1".to_string(),
+                    id: InstanceId::new(1),
+                    app_key: app_key("tech.flecs.app-1", "1.1.0")
+                },
+                StateAction::UninstallApp {
+                    app_key: app_key("tech.flecs.app-1", "1.0.0")
+                },
+                StateAction::ConfigureInstance {
+                    name: "app-1".to_string(),
+                    id,
+                    config,
+                },
+                StateAction::StartInstance {
+                    name: "app-1".to_string(),
+                    id
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_state_stop_and_prune() {
+        let mut current = current_state();
+        current.instances.push((
+            InstanceId::new(2),
+            instance(
+                "app-1-b",
+                app_key("tech.flecs.app-1", "1.0.0"),
+                DesiredStatus::Stopped,
+            ),
+        ));
+        let mut desired = current.to_device_state();
+        desired.instances.truncate(1);
+        desired.instances[0].desired = DesiredStatus::Stopped;
+        assert_eq!(
+            plan_state(&current, &desired, false).unwrap(),
+            vec![StateAction::StopInstance {
+                name: "app-1".to_string(),
+                id: Some(InstanceId::new(1))
+            }]
+        );
+        assert_eq!(
+            plan_state(&current, &desired, true).unwrap(),
+            vec![
+                StateAction::StopInstance {
+                    name: "app-1".to_string(),
+                    id: Some(InstanceId::new(1))
+                },
+                StateAction::DeleteInstance {
+                    name: "app-1-b".to_string(),
+                    id: InstanceId::new(2)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn plan_state_ignores_volume_mounts() {
+        let mut current = current_state();
+        let mut desired = current.to_device_state();
+        current.instances[0]
+            .1
+            .config
+            .as_mut()
+            .unwrap()
+            .mapped_editor_ports
+            .insert(80, 4000);
+        desired.instances[0].config = Some(InstanceConfig::default());
+        assert!(plan_state(&current, &desired, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn plan_state_duplicate_name() {
+        let current = current_state();
+        let mut desired = current.to_device_state();
+        desired.instances.push(desired.instances[0].clone());
+        assert!(matches!(
+            plan_state(&current, &desired, false),
+            Err(PlanStateError::DuplicateInstanceName(name)) if name == "app-1"
+        ));
+    }
+
+    #[test]
+    fn plan_state_ambiguous_name() {
+        let mut current = current_state();
+        current
+            .instances
+            .push((InstanceId::new(2), current.instances[0].1.clone()));
+        let desired = current.to_device_state();
+        assert!(matches!(
+            plan_state(&current, &desired, false),
+            Err(PlanStateError::DuplicateInstanceName(_))
+        ));
+        let desired = DeviceState {
+            instances: vec![current.instances[0].1.clone()],
+            ..DeviceState::default()
+        };
+        assert!(matches!(
+            plan_state(&current, &desired, false),
+            Err(PlanStateError::AmbiguousInstanceName(name)) if name == "app-1"
+        ));
+    }
+
+    #[test]
+    fn plan_state_app_mismatch() {
+        let current = current_state();
+        let mut desired = current.to_device_state();
+        desired.instances[0].app_key = app_key("tech.flecs.app-2", "1.0.0");
+        assert!(matches!(
+            plan_state(&current, &desired, false),
+            Err(PlanStateError::AppMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn plan_state_unknown_provider() {
+        let current = current_state();
+        let mut desired = current.to_device_state();
+        desired
+            .default_providers
+            .insert(FeatureKey::from_str("mqtt").unwrap(), "unknown".to_string());
+        assert!(matches!(
+            plan_state(&current, &desired, false),
+            Err(PlanStateError::UnknownProvider { .. })
+        ));
+    }
+
+    #[test]
+    fn plan_state_no_network_deployment() {
+        let mut current = current_state();
+        current.network_deployment = None;
+        let mut desired = current.to_device_state();
+        desired.networks.push(network("data"));
+        assert!(matches!(
+            plan_state(&current, &desired, false),
+            Err(PlanStateError::NoNetworkDeployment)
+        ));
+    }
+
+    #[test]
+    fn to_device_state_maps_providers_to_names() {
+        let mut current = current_state();
+        current
+            .default_providers
+            .insert(FeatureKey::from_str("mqtt").unwrap(), InstanceId::new(1));
+        current
+            .default_providers
+            .insert(FeatureKey::from_str("auth").unwrap(), InstanceId::new(9));
+        assert_eq!(
+            current.to_device_state().default_providers,
+            HashMap::from([(FeatureKey::from_str("mqtt").unwrap(), "app-1".to_string())])
+        );
+    }
+
+    #[test]
+    fn network_state_builtin() {
+        let network = Network {
+            name: Some("bridge".to_string()),
+            ..Network::default()
+        };
+        assert!(network_state(&network).unwrap().is_none());
+    }
+
+    #[test]
+    fn network_state_ipvlan() {
+        let network = Network {
+            name: Some("ipvlan".to_string()),
+            driver: Some("ipvlan".to_string()),
+            ipam: Some(bollard::models::Ipam {
+                config: Some(vec![bollard::models::IpamConfig {
+                    subnet: Some("10.18.0.0/16".to_string()),
+                    gateway: Some("10.18.0.1".to_string()),
+                    ..bollard::models::IpamConfig::default()
+                }]),
+                ..bollard::models::Ipam::default()
+            }),
+            options: Some(HashMap::from([
+                ("parent".to_string(), "eth0".to_string()),
+                ("ipvlan_mode".to_string(), "l2".to_string()),
+            ])),
+            ..Network::default()
+        };
+        assert_eq!(
+            network_state(&network).unwrap(),
+            Some(NetworkState {
+                name: "ipvlan".to_string(),
+                kind: NetworkKind::IpvlanL2,
+                subnet: Some(Ipv4Net::from_str("10.18.0.0/16").unwrap()),
+                gateway: Some(std::net::Ipv4Addr::new(10, 18, 0, 1)),
+                parent_adapter: Some("eth0".to_string()),
+                options: None,
+            })
+        );
+    }
+}
@@ -0,0 +1,845 @@
+use crate::jeweler::deployment::DeploymentId;
+use crate::jeweler::gem::app::App;
+use crate::jeweler::gem::deployment::Deployment;
+use crate::jeweler::gem::instance::InstanceId;
+use crate::jeweler::gem::manifest::AppManifest;
+use crate::lore::Lore;
+use crate::quest::SyncQuest;
+use crate::relic;
+use crate::relic::encryption;
+use crate::sorcerer::spell::{flecsport, flimport};
+use crate::sorcerer::systemus::{
+    AppStorage, BackupStorage, ExportStorage, ImageStorage, PruneOptions, PruneReport,
+    StorageUsage, VolumeStorage,
+};
+use crate::vault::Vault;
+use crate::vault::pouch::Pouch;
+use futures_util::future::join_all;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Apps, deployments and the images and instances referenced by the vault
+struct References {
+    apps: Vec<App>,
+    deployments: HashMap<DeploymentId, Deployment>,
+    images: HashSet<String>,
+    /// Repositories of the images of all manifests known to the vault, images of other
+    /// repositories were not installed by FLECS
+    repositories: HashSet<String>,
+    instances: HashSet<InstanceId>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Backup {
+    instance: InstanceId,
+    path: PathBuf,
+    /// Unix timestamp in milliseconds
+    created: u64,
+    size: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Export {
+    id: String,
+    /// Export this export is an increment of, see [crate::relic::delta::Index::base]
+    base: Option<String>,
+    modified: SystemTime,
+    size: u64,
+}
+
+pub async fn storage_usage(vault: Arc<Vault>, lore: Arc<Lore>) -> crate::Result<StorageUsage> {
+    let references = read_references(&vault).await;
+    let mut apps = join_all(references.apps.iter().map(|app| async {
+        AppStorage {
+            app: app.key.clone(),
+            size: app.installed_size().await.ok().map(|size| size as u64),
+        }
+    }))
+    .await;
+    apps.sort_by(|a, b| a.app.cmp(&b.app));
+    let (images, volumes) = deployment_storage(&references).await;
+    let backups = backup_storage(read_backups(&lore.instance.base_path).await?);
+    let exports = read_exports(&lore.export.base_path).await?;
+    let available = match relic::system::available_space(&lore.base_path) {
+        Ok(available) => Some(available),
+        Err(e) => {
+            warn!("Could not determine available space: {e}");
+            None
+        }
+    };
+    Ok(StorageUsage {
+        apps,
+        images,
+        volumes,
+        backups,
+        exports: ExportStorage {
+            count: exports.len(),
+            size: exports.iter().map(|export| export.size).sum(),
+        },
+        available,
+    })
+}
+
+pub async fn prune_storage(
+    quest: SyncQuest,
+    vault: Arc<Vault>,
+    lore: Arc<Lore>,
+    options: PruneOptions,
+) -> crate::Result<PruneReport> {
+    // Volumes of instances which are currently imported are not yet referenced by the vault
+    let _import_guard = if options.volumes {
+        Some(flimport::IMPORT_LOCK.write().await)
+    } else {
+        None
+    };
+    let references = read_references(&vault).await;
+    let mut report = PruneReport::default();
+    if options.images || options.volumes {
+        let (images, volumes) = deployment_storage(&references).await;
+        if options.images {
+            let deployments = references.deployments.clone();
+            let repositories = references.repositories.clone();
+            let result = quest
+                .lock()
+                .await
+                .create_sub_quest("Remove unreferenced images", |quest| {
+                    prune_images(quest, deployments, repositories, images)
+                })
+                .await
+                .2;
+            let (removed, freed) = result.await?;
+            report.images = removed;
+            report.freed += freed;
+        }
+        if options.volumes {
+            let deployments = references.deployments.clone();
+            let result = quest
+                .lock()
+                .await
+                .create_sub_quest("Remove orphaned volumes", |quest| {
+                    prune_volumes(quest, deployments, volumes)
+                })
+                .await
+                .2;
+            let (removed, freed) = result.await?;
+            report.volumes = removed;
+            report.freed += freed;
+        }
+    }
+    if let Some(keep) = options.keep_exports {
+        let export_dir = lore.export.base_path.clone();
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest("Remove old exports", |quest| {
+                prune_exports(quest, export_dir, keep)
+            })
+            .await
+            .2;
+        let (removed, freed) = result.await?;
+        report.exports = removed;
+        report.freed += freed;
+    }
+    if let Some(keep) = options.keep_backups {
+        let instance_dir = lore.instance.base_path.clone();
+        let result = quest
+            .lock()
+            .await
+            .create_sub_quest("Remove old backups", |quest| {
+                prune_backups(quest, instance_dir, keep)
+            })
+            .await
+            .2;
+        let (removed, freed) = result.await?;
+        report.backups = removed;
+        report.freed += freed;
+    }
+    Ok(report)
+}
+
+async fn read_references(vault: &Vault) -> References {
+    let grab = vault
+        .reservation()
+        .reserve_manifest_pouch()
+        .reserve_app_pouch()
+        .reserve_instance_pouch()
+        .reserve_deployment_pouch()
+        .grab()
+        .await;
+    let manifests = grab
+        .manifest_pouch
+        .as_ref()
+        .expect("Vault reservations should never fail")
+        .gems();
+    let apps = grab
+        .app_pouch
+        .as_ref()
+        .expect("Vault reservations should never fail")
+        .gems();
+    let instances = grab
+        .instance_pouch
+        .as_ref()
+        .expect("Vault reservations should never fail")
+        .gems();
+    let mut images = HashSet::new();
+    for app in apps.values() {
+        images.extend(manifest_images(app.manifest()));
+    }
+    for instance in instances.values() {
+        images.extend(manifest_images(&instance.manifest()));
+    }
+    let mut repositories: HashSet<String> = images
+        .iter()
+        .map(|image| image_repository(image).to_string())
+        .collect();
+    for manifest in manifests.values() {
+        repositories.extend(
+            manifest_images(manifest)
+                .iter()
+                .map(|image| image_repository(image).to_string()),
+        );
+    }
+    References {
+        apps: apps.values().cloned().collect(),
+        deployments: grab
+            .deployment_pouch
+            .as_ref()
+            .expect("Vault reservations should never fail")
+            .gems()
+            .clone(),
+        images,
+        repositories,
+        instances: instances.keys().copied().collect(),
+    }
+}
+
+fn manifest_images(manifest: &AppManifest) -> Vec<String> {
+    match manifest {
+        AppManifest::Single(single) => vec![normalize_image(&single.image_with_tag())],
+        AppManifest::Multi(multi) => multi
+            .images()
+            .iter()
+            .map(|image| normalize_image(image))
+            .collect(),
+    }
+}
+
+/// Normalizes image references to compare the images of manifests with the tags reported by
+/// docker and podman, e.g. 'docker.io/library/alpine' and 'alpine:latest' are equal
+fn normalize_image(image: &str) -> String {
+    let image = image
+        .strip_prefix("docker.io/library/")
+        .or_else(|| image.strip_prefix("docker.io/"))
+        .unwrap_or(image);
+    let name = image.rsplit('/').next().unwrap_or(image);
+    if name.contains(':') {
+        image.to_string()
+    } else {
+        format!("{image}:latest")
+    }
+}
+
+/// Returns the repository of a normalized image, i.e. the image without its tag, see
+/// [normalize_image]
+fn image_repository(image: &str) -> &str {
+    image
+        .rsplit_once(':')
+        .map_or(image, |(repository, _)| repository)
+}
+
+/// Parses instance ids in their 8 digit hex representation, see [InstanceId::to_string]
+fn parse_instance_id(id: &str) -> Option<InstanceId> {
+    if id.len() != 8 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    id.parse().ok()
+}
+
+/// Returns the instance of volumes named like instance volumes, i.e. 'flecs-{instance_id}-{name}'
+fn volume_instance(name: &str) -> Option<InstanceId> {
+    let (id, _) = name.strip_prefix("flecs-")?.split_once('-')?;
+    parse_instance_id(id)
+}
+
+/// Collects the images and volumes of all deployments. Deployments sharing a daemon report the
+/// same images and volumes, these are only listed for the first deployment.
+async fn deployment_storage(references: &References) -> (Vec<ImageStorage>, Vec<VolumeStorage>) {
+    let mut deployments: Vec<_> = references.deployments.iter().collect();
+    deployments.sort_by(|a, b| a.0.cmp(b.0));
+    let mut images = Vec::new();
+    let mut volumes = Vec::new();
+    let mut known_images = HashSet::new();
+    let mut known_volumes = HashSet::new();
+    for (id, deployment) in deployments {
+        let usage = match deployment.disk_usage().await {
+            Ok(usage) => usage,
+            Err(e) => {
+                warn!("Could not read disk usage of deployment {id}: {e}");
+                continue;
+            }
+        };
+        for image in usage.images {
+            if !known_images.insert(image.id.clone()) {
+                continue;
+            }
+            let referenced = image
+                .tags
+                .iter()
+                .any(|tag| references.images.contains(&normalize_image(tag)));
+            images.push(ImageStorage {
+                deployment: id.clone(),
+                id: image.id,
+                tags: image.tags,
+                size: image.size,
+                containers: image.containers,
+                referenced,
+            });
+        }
+        for volume in usage.volumes {
+            if !known_volumes.insert(volume.name.clone()) {
+                continue;
+            }
+            let instance = volume_instance(&volume.name);
+            volumes.push(VolumeStorage {
+                deployment: id.clone(),
+                orphaned: instance
+                    .is_some_and(|instance| !references.instances.contains(&instance)),
+                name: volume.name,
+                instance,
+                size: volume.size,
+                containers: volume.containers,
+            });
+        }
+    }
+    (images, volumes)
+}
+
+/// Only images of repositories which FLECS installed apps from are pruned, other images of the
+/// deployment, e.g. images of containers started by hand, are left untouched
+fn is_prunable_image(image: &ImageStorage, repositories: &HashSet<String>) -> bool {
+    !image.referenced
+        && image.containers == 0
+        && image
+            .tags
+            .iter()
+            .any(|tag| repositories.contains(image_repository(&normalize_image(tag))))
+}
+
+fn is_prunable_volume(volume: &VolumeStorage) -> bool {
+    volume.orphaned && volume.containers == 0
+}
+
+async fn prune_images(
+    quest: SyncQuest,
+    deployments: HashMap<DeploymentId, Deployment>,
+    repositories: HashSet<String>,
+    images: Vec<ImageStorage>,
+) -> crate::Result<(Vec<String>, u64)> {
+    let mut removed = Vec::new();
+    let mut freed = 0;
+    for image in images
+        .into_iter()
+        .filter(|image| is_prunable_image(image, &repositories))
+    {
+        let Some(deployment) = deployments.get(&image.deployment) else {
+            continue;
+        };
+        // Images with multiple tags can only be removed by id if forced
+        let references = image.tags.clone();
+        let mut image_removed = false;
+        for reference in references {
+            match deployment.remove_image(&reference).await {
+                Ok(result) => image_removed = result,
+                Err(e) => {
+                    warn!("Could not remove image {reference}: {e}");
+                    image_removed = false;
+                    break;
+                }
+            }
+        }
+        if image_removed {
+            freed += image.size;
+            removed.push(image.id);
+        }
+    }
+    quest.lock().await.detail = Some(format!("Removed {} images", removed.len()));
+    Ok((removed, freed))
+}
+
+async fn prune_volumes(
+    quest: SyncQuest,
+    deployments: HashMap<DeploymentId, Deployment>,
+    volumes: Vec<VolumeStorage>,
+) -> crate::Result<(Vec<String>, u64)> {
+    let mut removed = Vec::new();
+    let mut freed = 0;
+    for volume in volumes.into_iter().filter(is_prunable_volume) {
+        let Some(deployment) = deployments.get(&volume.deployment) else {
+            continue;
+        };
+        match deployment
+            .delete_volume(quest.clone(), volume.name.clone())
+            .await
+        {
+            Ok(()) => {
+                freed += volume.size.unwrap_or_default();
+                removed.push(volume.name);
+            }
+            Err(e) => warn!("Could not remove volume {}: {e}", volume.name),
+        }
+    }
+    quest.lock().await.detail = Some(format!("Removed {} volumes", removed.len()));
+    Ok((removed, freed))
+}
+
+async fn prune_exports(
+    quest: SyncQuest,
+    export_dir: PathBuf,
+    keep: usize,
+) -> crate::Result<(Vec<String>, u64)> {
+    let mut removed = Vec::new();
+    let mut freed = 0;
+    for export in outdated_exports(read_exports(&export_dir).await?, keep) {
+        if flecsport::delete_export(&export_dir, export.id.clone()).await? {
+            freed += export.size;
+            removed.push(export.id);
+        }
+    }
+    quest.lock().await.detail = Some(format!("Removed {} exports", removed.len()));
+    Ok((removed, freed))
+}
+
+async fn prune_backups(
+    quest: SyncQuest,
+    instance_dir: PathBuf,
+    keep: usize,
+) -> crate::Result<(Vec<PathBuf>, u64)> {
+    let mut removed = Vec::new();
+    let mut freed = 0;
+    for backup in outdated_backups(read_backups(&instance_dir).await?, keep) {
+        tokio::fs::remove_dir_all(&backup.path).await?;
+        // Removes the version directory if this was its last backup
+        if let Some(version_dir) = backup.path.parent() {
+            _ = tokio::fs::remove_dir(version_dir).await;
+        }
+        freed += backup.size;
+        removed.push(backup.path);
+    }
+    quest.lock().await.detail = Some(format!("Removed {} backups", removed.len()));
+    Ok((removed, freed))
+}
+
+/// Returns all exports except the newest `keep` exports and the bases they are incremented from.
+/// The outdated exports are ordered from newest to oldest, so that increments are deleted before
+/// their bases.
+fn outdated_exports(mut exports: Vec<Export>, keep: usize) -> Vec<Export> {
+    exports.sort_by(|a, b| b.modified.cmp(&a.modified));
+    let mut required: HashSet<String> = HashSet::new();
+    let mut bases: Vec<Option<String>> = exports
+        .iter()
+        .take(keep)
+        .map(|export| export.base.clone())
+        .collect();
+    while let Some(base) = bases.pop() {
+        let Some(base) = base else {
+            continue;
+        };
+        if required.insert(base.clone()) {
+            bases.extend(
+                exports
+                    .iter()
+                    .filter(|export| export.id == base)
+                    .map(|export| export.base.clone()),
+            );
+        }
+    }
+    exports
+        .into_iter()
+        .skip(keep)
+        .filter(|export| !required.contains(&export.id))
+        .collect()
+}
+
+fn outdated_backups(backups: Vec<Backup>, keep: usize) -> Vec<Backup> {
+    let mut instances: HashMap<InstanceId, Vec<Backup>> = HashMap::new();
+    for backup in backups {
+        instances.entry(backup.instance).or_default().push(backup);
+    }
+    instances
+        .into_values()
+        .flat_map(|mut backups| {
+            backups.sort_by(|a, b| b.created.cmp(&a.created));
+            backups.into_iter().skip(keep)
+        })
+        .collect()
+}
+
+fn backup_storage(backups: Vec<Backup>) -> Vec<BackupStorage> {
+    let mut storage: Vec<BackupStorage> = Vec::new();
+    for backup in backups {
+        match storage
+            .iter_mut()
+            .find(|storage| storage.instance == backup.instance)
+        {
+            Some(storage) => {
+                storage.count += 1;
+                storage.size += backup.size;
+            }
+            None => storage.push(BackupStorage {
+                instance: backup.instance,
+                count: 1,
+                size: backup.size,
+            }),
+        }
+    }
+    storage.sort_by_key(|storage| storage.instance.value);
+    storage
+}
+
+/// Returns the paths and names of all directories in `path`, a missing `path` has no directories
+async fn read_directories(path: &Path) -> std::io::Result<Vec<(PathBuf, String)>> {
+    let mut entries = match tokio::fs::read_dir(path).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut directories = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        if entry.file_type().await?.is_dir() {
+            directories.push((
+                entry.path(),
+                entry.file_name().to_string_lossy().to_string(),
+            ));
+        }
+    }
+    Ok(directories)
+}
+
+/// Reads the backups created by instance updates, located at
+/// '{instance_dir}/{instance_id}/backup/{version}/{unix_millis}'
+async fn read_backups(instance_dir: &Path) -> crate::Result<Vec<Backup>> {
+    let mut backups = Vec::new();
+    for (instance_path, name) in read_directories(instance_dir).await? {
+        let Some(instance) = parse_instance_id(&name) else {
+            continue;
+        };
+        for (version_path, _) in read_directories(&instance_path.join("backup")).await? {
+            for (path, name) in read_directories(&version_path).await? {
+                let Ok(created) = name.parse::<u64>() else {
+                    continue;
+                };
+                let size = relic::system::directory_size(&path).await?;
+                backups.push(Backup {
+                    instance,
+                    path,
+                    created,
+                    size,
+                });
+            }
+        }
+    }
+    Ok(backups)
+}
+
+async fn read_exports(export_dir: &Path) -> crate::Result<Vec<Export>> {
+    let mut entries = match tokio::fs::read_dir(export_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let encrypted_suffix = format!(".tar.{}", encryption::EXTENSION);
+    let mut exports = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if let Some(id) = file_name
+            .strip_suffix(&encrypted_suffix)
+            .or_else(|| file_name.strip_suffix(".tar"))
+        {
+            let base =
+                if tokio::fs::try_exists(flecsport::export_index_path(export_dir, id)).await? {
+                    match flecsport::read_export_index(export_dir, id).await {
+                        Ok(index) => index.base,
+                        Err(e) => {
+                            warn!("Could not determine base of export {id}: {e}");
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+            exports.push(Export {
+                id: id.to_string(),
+                base,
+                modified: metadata.modified()?,
+                size: metadata.len(),
+            });
+        }
+    }
+    Ok(exports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jeweler::gem::deployment::docker::tests::MockedDockerDeployment;
+    use crate::jeweler::storage::{DiskUsage, ImageUsage, VolumeUsage};
+    use crate::lore;
+    use crate::quest::Quest;
+    use crate::relic::var::test::MockVarReader;
+    use crate::vault::tests::create_empty_test_vault;
+    use std::time::Duration;
+    use testdir::testdir;
+
+    fn mocked_deployment(id: &str, usage: DiskUsage) -> Deployment {
+        let mut deployment = MockedDockerDeployment::new();
+        deployment.expect_id().return_const(id.to_string());
+        deployment
+            .expect_disk_usage()
+            .returning(move || Ok(usage.clone()));
+        Deployment::Docker(Arc::new(deployment))
+    }
+
+    fn test_backup(instance: u32, created: u64) -> Backup {
+        Backup {
+            instance: InstanceId::new(instance),
+            path: PathBuf::from(format!("/backup/{instance}/{created}")),
+            created,
+            size: 10,
+        }
+    }
+
+    #[test]
+    fn normalize_image_ok() {
+        assert_eq!(normalize_image("alpine"), "alpine:latest");
+        assert_eq!(
+            normalize_image("docker.io/library/alpine:3.21"),
+            "alpine:3.21"
+        );
+        assert_eq!(normalize_image("docker.io/flecs/app"), "flecs/app:latest");
+        assert_eq!(
+            normalize_image("localhost:5000/flecs/app"),
+            "localhost:5000/flecs/app:latest"
+        );
+        assert_eq!(
+            normalize_image("flecs.azurecr.io/tech.flecs.mqtt-bridge:3.0.0"),
+            "flecs.azurecr.io/tech.flecs.mqtt-bridge:3.0.0"
+        );
+    }
+
+    #[test]
+    fn volume_instance_ok() {
+        assert_eq!(
+            volume_instance("flecs-0123abcd-data"),
+            Some(InstanceId::new(0x0123abcd))
+        );
+        assert_eq!(volume_instance("flecs-0123abcd"), None);
+        assert_eq!(volume_instance("flecs-+123abcd-data"), None);
+        assert_eq!(volume_instance("flecs-123abcd-data"), None);
+        assert_eq!(volume_instance("other-0123abcd-data"), None);
+    }
+
+    #[tokio::test]
+    async fn deployment_storage_deduplicates() {
+        let usage = DiskUsage {
+            images: vec![
+                ImageUsage {
+                    id: "sha256:1".to_string(),
+                    tags: vec!["docker.io/library/alpine:latest".to_string()],
+                    size: 100,
+                    containers: 0,
+                },
+                ImageUsage {
+                    id: "sha256:2".to_string(),
+                    tags: vec!["flecs.azurecr.io/tech.flecs.app:1.0.0".to_string()],
+                    size: 50,
+                    containers: 0,
+                },
+                ImageUsage {
+                    id: "sha256:3".to_string(),
+                    tags: vec!["docker.io/library/nginx:latest".to_string()],
+                    size: 50,
+                    containers: 0,
+                },
+                ImageUsage {
+                    id: "sha256:4".to_string(),
+                    tags: Vec::new(),
+                    size: 50,
+                    containers: 0,
+                },
+            ],
+            volumes: vec![
+                VolumeUsage {
+                    name: "flecs-00000001-data".to_string(),
+                    size: Some(10),
+                    containers: 0,
+                },
+                VolumeUsage {
+                    name: "flecs-00000002-data".to_string(),
+                    size: None,
+                    containers: 1,
+                },
+                VolumeUsage {
+                    name: "foreign".to_string(),
+                    size: None,
+                    containers: 0,
+                },
+            ],
+        };
+        let references = References {
+            apps: Vec::new(),
+            deployments: HashMap::from([
+                ("a".to_string(), mocked_deployment("a", usage.clone())),
+                ("b".to_string(), mocked_deployment("b", usage)),
+            ]),
+            images: HashSet::from(["alpine:latest".to_string()]),
+            repositories: HashSet::from([
+                "alpine".to_string(),
+                "flecs.azurecr.io/tech.flecs.app".to_string(),
+            ]),
+            instances: HashSet::from([InstanceId::new(1)]),
+        };
+        let (images, volumes) = deployment_storage(&references).await;
+        assert_eq!(images.len(), 4);
+        assert!(images.iter().all(|image| image.deployment == "a"));
+        assert!(images[0].referenced);
+        assert!(!is_prunable_image(&images[0], &references.repositories));
+        assert!(is_prunable_image(&images[1], &references.repositories));
+        assert!(!is_prunable_image(&images[2], &references.repositories));
+        assert!(!is_prunable_image(&images[3], &references.repositories));
+        assert_eq!(volumes.len(), 3);
+        assert_eq!(volumes[0].instance, Some(InstanceId::new(1)));
+        assert!(!volumes[0].orphaned);
+        assert!(volumes[1].orphaned);
+        assert!(!is_prunable_volume(&volumes[1]));
+        assert_eq!(volumes[2].instance, None);
+        assert!(!volumes[2].orphaned);
+    }
+
+    #[test]
+    fn outdated_backups_per_instance() {
+        let mut outdated = outdated_backups(
+            vec![
+                test_backup(1, 100),
+                test_backup(1, 300),
+                test_backup(1, 200),
+                test_backup(2, 100),
+            ],
+            1,
+        );
+        outdated.sort_by_key(|backup| backup.created);
+        assert_eq!(outdated, vec![test_backup(1, 100), test_backup(1, 200)]);
+        assert_eq!(outdated_backups(vec![test_backup(1, 100)], 0).len(), 1);
+    }
+
+    #[test]
+    fn outdated_exports_ok() {
+        let export = |id: &str, seconds: u64| Export {
+            id: id.to_string(),
+            base: None,
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+            size: 1,
+        };
+        assert_eq!(
+            outdated_exports(vec![export("a", 1), export("b", 3), export("c", 2)], 2),
+            vec![export("a", 1)]
+        );
+    }
+
+    #[test]
+    fn outdated_exports_keeps_bases() {
+        let export = |id: &str, base: Option<&str>, seconds: u64| Export {
+            id: id.to_string(),
+            base: base.map(str::to_string),
+            modified: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+            size: 1,
+        };
+        let exports = vec![
+            export("full", None, 1),
+            export("increment", Some("full"), 2),
+            export("latest", Some("increment"), 4),
+            export("old", None, 0),
+            export("old_increment", Some("old"), 3),
+        ];
+        assert_eq!(
+            outdated_exports(exports, 1),
+            vec![
+                export("old_increment", Some("old"), 3),
+                export("old", None, 0)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn read_backups_ok() {
+        let path = testdir!();
+        let backup = path.join("0000000a/backup/1.0.0/1700000000000");
+        std::fs::create_dir_all(backup.join("conf")).unwrap();
+        std::fs::write(backup.join("conf/config.json"), [0; 30]).unwrap();
+        std::fs::create_dir_all(path.join("0000000a/backup/1.0.0/invalid")).unwrap();
+        std::fs::create_dir_all(path.join("not_an_instance/backup/1.0.0/1")).unwrap();
+        assert_eq!(
+            read_backups(&path).await.unwrap(),
+            vec![Backup {
+                instance: InstanceId::new(10),
+                path: backup,
+                created: 1700000000000,
+                size: 30,
+            }]
+        );
+        assert!(
+            read_backups(&path.join("missing"))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn prune_storage_exports_and_backups() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let export_dir = &lore.export.base_path;
+        std::fs::create_dir_all(export_dir).unwrap();
+        std::fs::write(export_dir.join("old.tar"), [0; 20]).unwrap();
+        std::fs::write(export_dir.join("old.index.json"), "{}").unwrap();
+        let new_export = std::fs::File::create(export_dir.join("new.tar")).unwrap();
+        new_export
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        let backup_dir = lore.instance.base_path.join("0000000a/backup/1.0.0");
+        std::fs::create_dir_all(backup_dir.join("100")).unwrap();
+        std::fs::write(backup_dir.join("100/file"), [0; 5]).unwrap();
+        std::fs::create_dir_all(backup_dir.join("200")).unwrap();
+        let report = prune_storage(
+            Quest::new_synced("Prune"),
+            create_empty_test_vault(),
+            lore.clone(),
+            PruneOptions {
+                keep_exports: Some(1),
+                keep_backups: Some(1),
+                ..PruneOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            report,
+            PruneReport {
+                exports: vec!["old".to_string()],
+                backups: vec![backup_dir.join("100")],
+                freed: 25,
+                ..PruneReport::default()
+            }
+        );
+        assert!(!export_dir.join("old.tar").exists());
+        assert!(!export_dir.join("old.index.json").exists());
+        assert!(export_dir.join("new.tar").exists());
+        assert!(backup_dir.join("200").exists());
+    }
+}
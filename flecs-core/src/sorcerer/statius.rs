@@ -0,0 +1,190 @@
+use super::spell;
+use crate::jeweler::deployment::DeploymentId;
+use crate::jeweler::gem::instance::InstanceId;
+use crate::jeweler::gem::instance::docker::config::InstanceConfig;
+use crate::jeweler::gem::manifest::FeatureKey;
+use crate::jeweler::network::{NetworkConfig, NetworkKind};
+use crate::sorcerer::Sorcerer;
+use crate::vault::Vault;
+use crate::vault::pouch::AppKey;
+use async_trait::async_trait;
+use ipnet::Ipv4Net;
+use serde::{Deserialize, Serialize};
+pub use spell::state::PlanStateError;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::Ipv4Addr;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+/// Declarative description of the apps, instances, networks and default providers of a device
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct DeviceState {
+    /// Apps that should be installed, apps of instances are installed implicitly
+    #[serde(default)]
+    pub apps: Vec<AppKey>,
+    #[serde(default)]
+    pub networks: Vec<NetworkState>,
+    #[serde(default)]
+    pub instances: Vec<InstanceState>,
+    /// Mapping of feature -> name of the instance that should be the default provider
+    #[serde(default)]
+    pub default_providers: HashMap<FeatureKey, String>,
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DesiredStatus {
+    Running,
+    #[default]
+    Stopped,
+}
+
+/// Instances are identified by their name which has to be unique within the state
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct InstanceState {
+    pub name: String,
+    pub app_key: AppKey,
+    #[serde(default)]
+    pub desired: DesiredStatus,
+    /// Complete config of the instance, volume mounts are created with the instance and are
+    /// therefore not applied. Compose instances have no config.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub config: Option<InstanceConfig>,
+}
+
+/// Networks are identified by their name and are created in the default deployment
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, ToSchema)]
+pub struct NetworkState {
+    pub name: String,
+    #[schema(value_type = String)]
+    pub kind: NetworkKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub subnet: Option<Ipv4Net>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub gateway: Option<Ipv4Addr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_adapter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<HashMap<String, String>>,
+}
+
+impl From<NetworkState> for NetworkConfig {
+    fn from(value: NetworkState) -> Self {
+        Self {
+            kind: value.kind,
+            name: value.name,
+            cidr_subnet: value.subnet,
+            gateway: value.gateway,
+            parent_adapter: value.parent_adapter,
+            options: value.options,
+        }
+    }
+}
+
+/// Single step of the plan to reach a [DeviceState]. Instances that do not exist yet are
+/// referenced by name only, their id is known after the corresponding
+/// [StateAction::CreateInstance] was executed.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum StateAction {
+    InstallApp {
+        app_key: AppKey,
+    },
+    CreateNetwork {
+        deployment_id: DeploymentId,
+        network: NetworkState,
+    },
+    StopInstance {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<InstanceId>,
+    },
+    UpdateInstance {
+        name: String,
+        id: InstanceId,
+        app_key: AppKey,
+    },
+    DeleteInstance {
+        name: String,
+        id: InstanceId,
+    },
+    UninstallApp {
+        app_key: AppKey,
+    },
+    CreateInstance {
+        name: String,
+        app_key: AppKey,
+    },
+    ConfigureInstance {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<InstanceId>,
+        #[schema(value_type = Object)]
+        config: InstanceConfig,
+    },
+    SetDefaultProvider {
+        feature: FeatureKey,
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<InstanceId>,
+    },
+    StartInstance {
+        name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<InstanceId>,
+    },
+}
+
+impl Display for StateAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InstallApp { app_key } => write!(f, "Install app {app_key}"),
+            Self::CreateNetwork {
+                deployment_id,
+                network,
+            } => write!(
+                f,
+                "Create network {} in deployment {deployment_id}",
+                network.name
+            ),
+            Self::StopInstance { name, .. } => write!(f, "Stop instance '{name}'"),
+            Self::UpdateInstance { name, app_key, .. } => {
+                write!(f, "Update instance '{name}' to {app_key}")
+            }
+            Self::DeleteInstance { name, id } => write!(f, "Delete instance '{name}' ({id})"),
+            Self::UninstallApp { app_key } => write!(f, "Uninstall app {app_key}"),
+            Self::CreateInstance { name, app_key } => {
+                write!(f, "Create instance '{name}' of {app_key}")
+            }
+            Self::ConfigureInstance { name, .. } => write!(f, "Configure instance '{name}'"),
+            Self::SetDefaultProvider { feature, name, .. } => {
+                write!(f, "Set '{name}' as default provider for {feature}")
+            }
+            Self::StartInstance { name, .. } => write!(f, "Start instance '{name}'"),
+        }
+    }
+}
+
+pub mod statius_impl;
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait Statius: Sorcerer {
+    /// Exports the current state of the device in the format accepted by [Statius::plan_state]
+    async fn export_state(&self, vault: Arc<Vault>) -> crate::Result<DeviceState>;
+    /// Computes the actions necessary to reach the desired state, the actions have to be
+    /// executed in the returned order. If `prune` is set, instances and apps that are not part
+    /// of the desired state are removed.
+    async fn plan_state(
+        &self,
+        vault: Arc<Vault>,
+        desired: &DeviceState,
+        prune: bool,
+    ) -> Result<Vec<StateAction>, PlanStateError>;
+}
+
+#[cfg(test)]
+impl Sorcerer for MockStatius {}
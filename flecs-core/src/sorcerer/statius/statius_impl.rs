@@ -0,0 +1,78 @@
+use crate::sorcerer::Sorcerer;
+use crate::sorcerer::spell::state::{get_current_state, plan_state};
+use crate::sorcerer::statius::{DeviceState, PlanStateError, StateAction, Statius};
+use crate::vault::Vault;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub struct StatiusImpl;
+
+impl Sorcerer for StatiusImpl {}
+
+#[async_trait]
+impl Statius for StatiusImpl {
+    async fn export_state(&self, vault: Arc<Vault>) -> crate::Result<DeviceState> {
+        Ok(get_current_state(vault).await?.to_device_state())
+    }
+
+    async fn plan_state(
+        &self,
+        vault: Arc<Vault>,
+        desired: &DeviceState,
+        prune: bool,
+    ) -> Result<Vec<StateAction>, PlanStateError> {
+        let current = get_current_state(vault).await?;
+        plan_state(&current, desired, prune)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorcerer::statius::{DesiredStatus, InstanceState};
+    use crate::vault::pouch::AppKey;
+    use crate::vault::tests::create_empty_test_vault;
+
+    #[tokio::test]
+    async fn export_state_empty() {
+        assert_eq!(
+            StatiusImpl
+                .export_state(create_empty_test_vault())
+                .await
+                .unwrap(),
+            DeviceState::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn plan_state_empty_vault() {
+        let app_key = AppKey {
+            name: "tech.flecs.test".to_string(),
+            version: "1.0.0".to_string(),
+        };
+        let desired = DeviceState {
+            instances: vec![InstanceState {
+                name: "test".to_string(),
+                app_key: app_key.clone(),
+                desired: DesiredStatus::Stopped,
+                config: None,
+            }],
+            ..DeviceState::default()
+        };
+        assert_eq!(
+            StatiusImpl
+                .plan_state(create_empty_test_vault(), &desired, false)
+                .await
+                .unwrap(),
+            vec![
+                StateAction::InstallApp {
+                    app_key: app_key.clone()
+                },
+                StateAction::CreateInstance {
+                    name: "test".to_string(),
+                    app_key,
+                },
+            ]
+        );
+    }
+}
@@ -1,13 +1,128 @@
+pub mod rotation;
 mod systemus_impl;
 
 pub use super::Result;
+use crate::jeweler::deployment::DeploymentId;
+use crate::jeweler::gem::instance::InstanceId;
+use crate::lore::{CertificateLore, Lore};
+use crate::quest::SyncQuest;
+use crate::relic::tls::CertifiedKey;
 use crate::sorcerer::Sorcerer;
+use crate::vault::Vault;
+use crate::vault::pouch::AppKey;
 use async_trait::async_trait;
 #[cfg(test)]
 use mockall::automock;
 use net_spider::network_adapter::{NetworkAdapter, NetworkAdapterReader};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 pub use systemus_impl::SystemusImpl;
+use utoipa::ToSchema;
+
+/// Disk space used by apps, instances, backups and exports, all sizes are in bytes
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    pub apps: Vec<AppStorage>,
+    pub images: Vec<ImageStorage>,
+    pub volumes: Vec<VolumeStorage>,
+    pub backups: Vec<BackupStorage>,
+    pub exports: ExportStorage,
+    /// Space available on the filesystem of the FLECS base directory
+    pub available: Option<u64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStorage {
+    pub app: AppKey,
+    /// Installed size as reported by the deployment, missing if the app is not installed
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageStorage {
+    pub deployment: DeploymentId,
+    pub id: String,
+    pub tags: Vec<String>,
+    pub size: u64,
+    /// Number of containers using the image
+    pub containers: u64,
+    /// The image belongs to an installed app or an instance
+    pub referenced: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VolumeStorage {
+    pub deployment: DeploymentId,
+    pub name: String,
+    /// Instance the volume was created for, missing for volumes not created by FLECS
+    pub instance: Option<InstanceId>,
+    pub size: Option<u64>,
+    /// Number of containers using the volume
+    pub containers: u64,
+    /// The volume was created for an instance which does not exist anymore
+    pub orphaned: bool,
+}
+
+/// Backups created by instance updates
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupStorage {
+    pub instance: InstanceId,
+    pub count: usize,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportStorage {
+    pub count: usize,
+    pub size: u64,
+}
+
+/// Selects what is removed by [Systemus::prune_storage], nothing is removed by default
+#[derive(Debug, Clone, Default, Eq, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PruneOptions {
+    /// Remove images of app repositories known to FLECS which are neither referenced by an app or
+    /// instance nor used by a container
+    pub images: bool,
+    /// Remove volumes of instances which do not exist anymore
+    pub volumes: bool,
+    /// Number of the newest exports to keep, exports are not removed if missing. Bases of kept
+    /// incremental exports are kept as well
+    pub keep_exports: Option<usize>,
+    /// Number of the newest backups to keep per instance, backups are not removed if missing
+    pub keep_backups: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneReport {
+    pub images: Vec<String>,
+    pub volumes: Vec<String>,
+    pub exports: Vec<String>,
+    #[schema(value_type = Vec<String>)]
+    pub backups: Vec<PathBuf>,
+    /// Freed disk space, volumes of unknown size are not included
+    pub freed: u64,
+}
+
+/// Device certificates as ensured by [Systemus::ensure_device_certificates]
+#[derive(Debug, Clone)]
+pub struct DeviceCertificates {
+    pub server: CertifiedKey,
+    /// The device certificate authority was created because it was missing or about to expire
+    pub ca_renewed: bool,
+    /// The server certificate was issued because it was missing, about to expire, issued by a
+    /// previous certificate authority or the host names of the device changed
+    pub server_renewed: bool,
+}
 
 #[cfg_attr(test, automock)]
 #[async_trait]
@@ -21,6 +136,24 @@ pub trait Systemus: Sorcerer {
         network_adapter_reader: &dyn NetworkAdapterReader,
         network_id: &str,
     ) -> Result<Option<NetworkAdapter>>;
+    /// Creates or renews the device certificate authority and the server certificate issued by
+    /// it for the hostname and the addresses of the network adapters of the device
+    fn ensure_device_certificates(
+        &self,
+        lore: &CertificateLore,
+        network_adapter_reader: &dyn NetworkAdapterReader,
+    ) -> Result<DeviceCertificates>;
+    /// PEM encoded certificate of the device certificate authority, None if it was not created
+    /// yet
+    fn device_ca_certificate(&self, lore: &CertificateLore) -> Result<Option<String>>;
+    async fn storage_usage(&self, vault: Arc<Vault>, lore: Arc<Lore>) -> Result<StorageUsage>;
+    async fn prune_storage(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        lore: Arc<Lore>,
+        options: PruneOptions,
+    ) -> Result<PruneReport>;
 }
 
 #[cfg(test)]
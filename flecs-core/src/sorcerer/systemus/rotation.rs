@@ -0,0 +1,185 @@
+//! Periodic renewal of the device certificates, see
+//! [crate::lore::CertificateLore::rotation_check_interval]
+use crate::lore::Lore;
+use crate::relic::floxy::Floxy;
+use crate::sorcerer::systemus::Systemus;
+use net_spider::network_adapter::NetworkAdapterReader;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Renews the device certificates if necessary and installs the server certificate in floxy if
+/// it was renewed or is not `installed` yet. Returns whether the current server certificate is
+/// installed in floxy.
+pub fn rotate_device_certificates<SYS: Systemus + ?Sized>(
+    systemus: &SYS,
+    network_adapter_reader: &dyn NetworkAdapterReader,
+    floxy: &dyn Floxy,
+    lore: Arc<Lore>,
+    installed: bool,
+) -> bool {
+    let certificates =
+        match systemus.ensure_device_certificates(&lore.certificate, network_adapter_reader) {
+            Ok(certificates) => certificates,
+            Err(e) => {
+                error!("Could not ensure device certificates: {e}");
+                return installed;
+            }
+        };
+    if certificates.ca_renewed {
+        info!("Created new device certificate authority");
+    }
+    if certificates.server_renewed {
+        match certificates.server.host_names() {
+            Ok(host_names) => info!("Issued new device server certificate for {host_names:?}"),
+            Err(_) => info!("Issued new device server certificate"),
+        }
+    } else if installed {
+        return true;
+    }
+    match floxy.set_device_certificate(lore, &certificates.server) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Could not install device server certificate: {e}");
+            false
+        }
+    }
+}
+
+/// Checks the device certificates in the configured interval, never returns
+pub async fn run_device_certificate_rotation<SYS: Systemus, NAR: NetworkAdapterReader>(
+    systemus: Arc<SYS>,
+    network_adapter_reader: Arc<NAR>,
+    floxy: Arc<dyn Floxy>,
+    lore: Arc<Lore>,
+) {
+    let mut interval = tokio::time::interval(lore.certificate.rotation_check_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut installed = false;
+    loop {
+        interval.tick().await;
+        installed = rotate_device_certificates(
+            systemus.as_ref(),
+            network_adapter_reader.as_ref(),
+            floxy.as_ref(),
+            lore.clone(),
+            installed,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relic::floxy::MockFloxy;
+    use crate::relic::network::tests::MockNetworkAdapterReader;
+    use crate::relic::tls::CertifiedKey;
+    use crate::relic::var::test::MockVarReader;
+    use crate::sorcerer::systemus::{DeviceCertificates, MockSystemus};
+    use testdir::testdir;
+
+    fn systemus_ensuring(server_renewed: bool) -> MockSystemus {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_ensure_device_certificates()
+            .once()
+            .returning(move |_, _| {
+                Ok(DeviceCertificates {
+                    server: CertifiedKey::generate_self_signed(&["flecs".to_string()], 10).unwrap(),
+                    ca_renewed: false,
+                    server_renewed,
+                })
+            });
+        systemus
+    }
+
+    fn lore() -> Arc<Lore> {
+        Arc::new(crate::lore::test_lore(testdir!(), &MockVarReader::new()))
+    }
+
+    #[test]
+    fn rotate_device_certificates_unchanged_installed() {
+        let mut floxy = MockFloxy::new();
+        floxy.expect_set_device_certificate().never();
+        assert!(rotate_device_certificates(
+            &systemus_ensuring(false),
+            &MockNetworkAdapterReader::default(),
+            &floxy,
+            lore(),
+            true,
+        ));
+    }
+
+    #[test]
+    fn rotate_device_certificates_unchanged_not_installed() {
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_set_device_certificate()
+            .once()
+            .returning(|_, _| Ok(()));
+        assert!(rotate_device_certificates(
+            &systemus_ensuring(false),
+            &MockNetworkAdapterReader::default(),
+            &floxy,
+            lore(),
+            false,
+        ));
+    }
+
+    #[test]
+    fn rotate_device_certificates_renewed() {
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_set_device_certificate()
+            .once()
+            .returning(|_, _| Ok(()));
+        assert!(rotate_device_certificates(
+            &systemus_ensuring(true),
+            &MockNetworkAdapterReader::default(),
+            &floxy,
+            lore(),
+            true,
+        ));
+    }
+
+    #[test]
+    fn rotate_device_certificates_install_failed() {
+        let mut floxy = MockFloxy::new();
+        floxy
+            .expect_set_device_certificate()
+            .once()
+            .returning(|_, _| Err(anyhow::anyhow!("TestError")));
+        assert!(!rotate_device_certificates(
+            &systemus_ensuring(true),
+            &MockNetworkAdapterReader::default(),
+            &floxy,
+            lore(),
+            true,
+        ));
+    }
+
+    #[test]
+    fn rotate_device_certificates_ensure_failed() {
+        let mut systemus = MockSystemus::new();
+        systemus
+            .expect_ensure_device_certificates()
+            .times(2)
+            .returning(|_, _| Err(anyhow::anyhow!("TestError")));
+        let mut floxy = MockFloxy::new();
+        floxy.expect_set_device_certificate().never();
+        let network_adapter_reader = MockNetworkAdapterReader::default();
+        assert!(rotate_device_certificates(
+            &systemus,
+            &network_adapter_reader,
+            &floxy,
+            lore(),
+            true,
+        ));
+        assert!(!rotate_device_certificates(
+            &systemus,
+            &network_adapter_reader,
+            &floxy,
+            lore(),
+            false,
+        ));
+    }
+}
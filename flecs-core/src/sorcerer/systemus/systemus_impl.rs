@@ -1,8 +1,17 @@
+use crate::lore::{CertificateLore, Lore};
+use crate::quest::SyncQuest;
+use crate::relic;
 use crate::sorcerer::Sorcerer;
-use crate::sorcerer::systemus::Systemus;
+use crate::sorcerer::spell;
+use crate::sorcerer::systemus::{
+    DeviceCertificates, PruneOptions, PruneReport, StorageUsage, Systemus,
+};
+use crate::vault::Vault;
 use async_trait::async_trait;
 use net_spider::network_adapter::{NetworkAdapter, NetworkAdapterReader};
 use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::warn;
 
 #[derive(Default)]
 pub struct SystemusImpl {}
@@ -27,6 +36,49 @@ impl Systemus for SystemusImpl {
             .try_read_network_adapters()?
             .remove(network_id))
     }
+
+    fn ensure_device_certificates(
+        &self,
+        lore: &CertificateLore,
+        network_adapter_reader: &dyn NetworkAdapterReader,
+    ) -> anyhow::Result<DeviceCertificates> {
+        let hostname = match relic::system::hostname() {
+            Ok(hostname) => Some(hostname),
+            Err(e) => {
+                warn!("Could not read hostname for the device certificate: {e}");
+                None
+            }
+        };
+        let network_adapters = self.read_network_adapters(network_adapter_reader)?;
+        let host_names =
+            spell::certificate::device_host_names(hostname.as_deref(), &network_adapters);
+        Ok(spell::certificate::ensure_device_certificates(
+            lore,
+            &host_names,
+        )?)
+    }
+
+    fn device_ca_certificate(&self, lore: &CertificateLore) -> anyhow::Result<Option<String>> {
+        Ok(spell::certificate::device_ca_certificate(lore)?)
+    }
+
+    async fn storage_usage(
+        &self,
+        vault: Arc<Vault>,
+        lore: Arc<Lore>,
+    ) -> anyhow::Result<StorageUsage> {
+        spell::storage::storage_usage(vault, lore).await
+    }
+
+    async fn prune_storage(
+        &self,
+        quest: SyncQuest,
+        vault: Arc<Vault>,
+        lore: Arc<Lore>,
+        options: PruneOptions,
+    ) -> anyhow::Result<PruneReport> {
+        spell::storage::prune_storage(quest, vault, lore, options).await
+    }
 }
 
 #[cfg(test)]
@@ -55,6 +107,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn ensure_device_certificates_ok() {
+        let test_adapters = test_adapters();
+        let lore = crate::lore::test_lore(
+            testdir::testdir!(),
+            &crate::relic::var::test::MockVarReader::new(),
+        );
+        let systemus = SystemusImpl::default();
+        let mut network_adapter_reader = MockNetworkAdapterReader::default();
+        network_adapter_reader
+            .expect_try_read_network_adapters()
+            .times(2)
+            .returning(move || Ok(test_adapters.clone()));
+        assert_eq!(
+            systemus.device_ca_certificate(&lore.certificate).unwrap(),
+            None
+        );
+        let certificates = systemus
+            .ensure_device_certificates(&lore.certificate, &network_adapter_reader)
+            .unwrap();
+        assert!(certificates.ca_renewed);
+        assert!(certificates.server_renewed);
+        assert!(
+            certificates
+                .server
+                .host_names()
+                .unwrap()
+                .contains(&"22.41.87.55".to_string())
+        );
+        let certificates = systemus
+            .ensure_device_certificates(&lore.certificate, &network_adapter_reader)
+            .unwrap();
+        assert!(!certificates.ca_renewed);
+        assert!(!certificates.server_renewed);
+        assert!(
+            systemus
+                .device_ca_certificate(&lore.certificate)
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn ensure_device_certificates_err() {
+        let lore = crate::lore::test_lore(
+            testdir::testdir!(),
+            &crate::relic::var::test::MockVarReader::new(),
+        );
+        let systemus = SystemusImpl::default();
+        let mut network_adapter_reader = MockNetworkAdapterReader::default();
+        network_adapter_reader
+            .expect_try_read_network_adapters()
+            .once()
+            .returning(|| Err(net_spider::Error::PropertyNull("TestError")));
+        assert!(
+            systemus
+                .ensure_device_certificates(&lore.certificate, &network_adapter_reader)
+                .is_err()
+        );
+        assert!(matches!(
+            lore.certificate.ca_certificate_path().try_exists(),
+            Ok(false)
+        ));
+    }
+
     #[tokio::test]
     async fn read_network_adapters_err() {
         let systemus = SystemusImpl::default();
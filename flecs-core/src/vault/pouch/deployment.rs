@@ -100,14 +100,19 @@ impl DeploymentPouch {
         self.set_default_compose_deployment();
     }
 
+    /// Podman deployments run the same apps as docker deployments and are candidates for the
+    /// default docker deployment as well, docker deployments are preferred if none is marked as
+    /// default
     pub fn set_default_docker_deployment(&mut self) {
         let mut docker_deployments = self
             .deployments
             .values()
-            .filter_map(|deployment| match deployment {
-                Deployment::Docker(docker) => Some(docker),
-                _ => None,
-            })
+            .filter(|deployment| matches!(deployment, Deployment::Docker(_)))
+            .chain(
+                self.deployments
+                    .values()
+                    .filter(|deployment| matches!(deployment, Deployment::Podman(_))),
+            )
             .peekable();
         let mut id = docker_deployments.peek().map(|deployment| deployment.id());
         if let Some(default_id) = docker_deployments.find_map(|deployment| {
@@ -166,6 +171,7 @@ pub mod tests {
     use super::*;
     use crate::jeweler::gem::deployment::docker::DockerDeploymentImpl;
     use crate::jeweler::gem::deployment::docker::tests::MockedDockerDeployment;
+    use crate::jeweler::gem::deployment::podman::PodmanDeploymentImpl;
     use crate::lore;
     use crate::relic::var::test::MockVarReader;
     use crate::tests::prepare_test_path;
@@ -272,6 +278,52 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn set_default_docker_deployment_prefers_docker() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let docker = Deployment::Docker(Arc::new(DockerDeploymentImpl::new(
+            "docker".to_string(),
+            PathBuf::from(TEST_DEPLOYMENT_SOCK_PATH),
+        )));
+        let podman = Deployment::Podman(Arc::new(PodmanDeploymentImpl::new(
+            "podman".to_string(),
+            PathBuf::from("/path/to/podman.sock"),
+        )));
+        let mut deployment_pouch = DeploymentPouch::new(lore);
+        deployment_pouch.deployments = HashMap::from([
+            ("podman".to_string(), podman),
+            ("docker".to_string(), docker),
+        ]);
+        deployment_pouch.set_default_docker_deployment();
+        assert_eq!(
+            deployment_pouch.default_docker_deployment_id.as_deref(),
+            Some("docker")
+        );
+    }
+
+    #[test]
+    fn set_default_docker_deployment_podman_default() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let docker = Deployment::Docker(Arc::new(DockerDeploymentImpl::new(
+            "docker".to_string(),
+            PathBuf::from(TEST_DEPLOYMENT_SOCK_PATH),
+        )));
+        let podman = Deployment::Podman(Arc::new(PodmanDeploymentImpl::new_default(
+            "podman".to_string(),
+            PathBuf::from("/path/to/podman.sock"),
+        )));
+        let mut deployment_pouch = DeploymentPouch::new(lore);
+        deployment_pouch.deployments = HashMap::from([
+            ("podman".to_string(), podman),
+            ("docker".to_string(), docker),
+        ]);
+        deployment_pouch.set_default_docker_deployment();
+        assert_eq!(
+            deployment_pouch.default_docker_deployment_id.as_deref(),
+            Some("podman")
+        );
+    }
+
     #[test]
     fn new_deployment_pouch() {
         let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
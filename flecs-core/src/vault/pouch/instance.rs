@@ -610,8 +610,11 @@ pub mod tests {
                     port: "test_port".to_string(),
                 },
             )]),
+            editor_hosts: Default::default(),
+            editor_roles: Default::default(),
             mapped_editor_ports: HashMap::from([(3000, 4000)]),
             dependencies: Default::default(),
+            provider_connections: Default::default(),
         }
     }
 
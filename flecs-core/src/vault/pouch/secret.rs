@@ -1,8 +1,11 @@
 use super::Result;
 use super::{Pouch, combine_results};
+use crate::jeweler::license::{LicenseValidation, OfflineLicense};
 use crate::lore::SecretLoreRef;
 use flecs_console_client::models::SessionId;
 use flecsd_axum_server::models::AuthResponseData;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use std::fs;
 use std::path::Path;
 
@@ -11,6 +14,10 @@ pub struct Secrets {
     pub license_key: Option<String>,
     session_id: SessionId,
     pub authentication: Option<AuthResponseData>,
+    /// License imported from a license file, see [crate::jeweler::license::LicenseFile]
+    pub offline_license: Option<OfflineLicense>,
+    /// Outcome of the latest license validation, None if the license was never validated
+    pub license_validation: Option<LicenseValidation>,
 }
 
 impl Secrets {
@@ -38,12 +45,16 @@ impl Secrets {
             license_key,
             session_id,
             authentication,
+            offline_license: None,
+            license_validation: None,
         }
     }
 }
 
 const SESSION_FILE_NAME: &str = ".session_id";
 const LICENSE_FILE_NAME: &str = ".license";
+const OFFLINE_LICENSE_FILE_NAME: &str = ".offline_license.json";
+const LICENSE_VALIDATION_FILE_NAME: &str = ".license_validation.json";
 pub struct SecretPouch {
     secrets: Secrets,
     lore: SecretLoreRef,
@@ -64,11 +75,31 @@ impl Pouch for SecretPouch {
 impl SecretPouch {
     pub(in super::super) fn close(&mut self) -> crate::vault::Result<()> {
         fs::create_dir_all(self.base_path())?;
-        combine_results(self.save_session(), self.save_license())
+        combine_results(
+            combine_results(self.save_session(), self.save_license()),
+            combine_results(
+                self.save_json(
+                    OFFLINE_LICENSE_FILE_NAME,
+                    self.secrets.offline_license.as_ref(),
+                ),
+                self.save_json(
+                    LICENSE_VALIDATION_FILE_NAME,
+                    self.secrets.license_validation.as_ref(),
+                ),
+            ),
+        )
     }
 
     pub(in super::super) fn open(&mut self) -> crate::vault::Result<()> {
-        combine_results(self.read_session(), self.read_license())
+        let offline_license = self.read_json(OFFLINE_LICENSE_FILE_NAME);
+        let license_validation = self.read_json(LICENSE_VALIDATION_FILE_NAME);
+        let offline_license = offline_license.map(|license| self.secrets.offline_license = license);
+        let license_validation =
+            license_validation.map(|validation| self.secrets.license_validation = validation);
+        combine_results(
+            combine_results(self.read_session(), self.read_license()),
+            combine_results(offline_license, license_validation),
+        )
     }
 }
 
@@ -119,6 +150,32 @@ impl SecretPouch {
         self.secrets.license_key = license_file.lines().next().map(str::to_string);
         Ok(())
     }
+
+    /// The file is removed if there is no value
+    fn save_json<T: Serialize>(
+        &self,
+        file_name: &str,
+        value: Option<&T>,
+    ) -> crate::vault::Result<()> {
+        let path = self.base_path().join(file_name);
+        match value {
+            Some(value) => fs::write(path, serde_json::to_vec_pretty(value)?)?,
+            None => match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e)?,
+                _ => {}
+            },
+        }
+        Ok(())
+    }
+
+    /// A missing file is read as no value
+    fn read_json<T: DeserializeOwned>(&self, file_name: &str) -> Result<Option<T>> {
+        match fs::read(self.base_path().join(file_name)) {
+            Ok(content) => Ok(Some(serde_json::from_slice(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e)?,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -137,6 +194,8 @@ pub mod tests {
                 license_key: None,
                 session_id: Default::default(),
                 authentication: None,
+                offline_license: None,
+                license_validation: None,
             },
         }
     }
@@ -186,6 +245,8 @@ pub mod tests {
                     timestamp: Some(timestamp),
                 },
                 authentication: None,
+                offline_license: None,
+                license_validation: None,
             },
         };
         secrets.close().unwrap();
@@ -238,6 +299,8 @@ pub mod tests {
                     timestamp: None,
                 },
                 authentication: None,
+                offline_license: None,
+                license_validation: None,
             },
         };
         secrets.close().unwrap();
@@ -291,6 +354,8 @@ pub mod tests {
                     timestamp: None,
                 },
                 authentication: None,
+                offline_license: None,
+                license_validation: None,
             },
         };
         secrets.close().unwrap();
@@ -306,6 +371,44 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn close_and_open_offline_license() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let test_path = lore.secret.base_path.clone();
+        let license = crate::jeweler::license::tests::test_license();
+        let mut secrets = SecretPouch::new(lore.clone());
+        secrets.secrets.offline_license = Some(license.clone());
+        secrets.close().unwrap();
+        assert!(test_path.join(OFFLINE_LICENSE_FILE_NAME).exists());
+        let mut secrets = SecretPouch::new(lore.clone());
+        secrets.open().unwrap();
+        assert_eq!(secrets.secrets.offline_license, Some(license));
+        secrets.secrets.offline_license = None;
+        secrets.close().unwrap();
+        assert!(!test_path.join(OFFLINE_LICENSE_FILE_NAME).exists());
+        let mut secrets = SecretPouch::new(lore);
+        secrets.open().unwrap();
+        assert!(secrets.secrets.offline_license.is_none());
+    }
+
+    #[test]
+    fn close_and_open_license_validation() {
+        let lore = Arc::new(lore::test_lore(testdir!(), &MockVarReader::new()));
+        let test_path = lore.secret.base_path.clone();
+        let validation = LicenseValidation {
+            state: crate::jeweler::license::LicenseState::GracePeriod,
+            last_validated: Some(1_700_000_000),
+            grace_until: Some(1_700_604_800),
+        };
+        let mut secrets = SecretPouch::new(lore.clone());
+        secrets.secrets.license_validation = Some(validation.clone());
+        secrets.close().unwrap();
+        assert!(test_path.join(LICENSE_VALIDATION_FILE_NAME).exists());
+        let mut secrets = SecretPouch::new(lore);
+        secrets.open().unwrap();
+        assert_eq!(secrets.secrets.license_validation, Some(validation));
+    }
+
     #[test]
     fn set_session_id_newer() {
         let current = SessionId {
@@ -320,6 +423,8 @@ pub mod tests {
             session_id: current.clone(),
             license_key: None,
             authentication: None,
+            offline_license: None,
+            license_validation: None,
         };
         secrets.set_session_id(next.clone());
         assert_eq!(secrets.session_id, next);
@@ -339,6 +444,8 @@ pub mod tests {
             session_id: current.clone(),
             license_key: None,
             authentication: None,
+            offline_license: None,
+            license_validation: None,
         };
         secrets.set_session_id(next.clone());
         assert_eq!(secrets.session_id, current);
@@ -358,6 +465,8 @@ pub mod tests {
             session_id: current.clone(),
             license_key: None,
             authentication: None,
+            offline_license: None,
+            license_validation: None,
         };
         secrets.set_session_id(next.clone());
         assert_eq!(secrets.session_id, next);
@@ -377,6 +486,8 @@ pub mod tests {
             session_id: current.clone(),
             license_key: None,
             authentication: None,
+            offline_license: None,
+            license_validation: None,
         };
         secrets.set_session_id(next.clone());
         assert_eq!(secrets.session_id, next);
@@ -396,6 +507,8 @@ pub mod tests {
             session_id: current.clone(),
             license_key: None,
             authentication: None,
+            offline_license: None,
+            license_validation: None,
         };
         secrets.set_session_id(next.clone());
         assert_eq!(secrets.session_id, next);
@@ -10,6 +10,10 @@ use thiserror::Error;
 use tokio::sync::{RwLock, RwLockWriteGuard};
 
 const INITIAL_SETUP_ROLE: &str = "tech.flecs.core.initial_setup";
+/// Cookie carrying the access token of a browser session. Browsers navigating to an editor can
+/// not send an Authorization header, the authorization requests of floxy for editors pass on the
+/// cookies of the original request instead.
+pub const SESSION_COOKIE_NAME: &str = "flecs_session";
 
 pub struct Watch {
     client: reqwest::Client,
@@ -18,6 +22,12 @@ pub struct Watch {
 }
 
 pub struct AuthToken(pub Option<String>);
+
+/// Access token of a request to an editor, taken from the Authorization header or, if there is
+/// none, from the session cookie [SESSION_COOKIE_NAME]. Only used for the authorization requests
+/// of editors as accepting cookies for the whole api would allow cross site request forgery.
+pub struct EditorAuthToken(pub Option<String>);
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Key algorithm '{0}' unsupported")]
@@ -68,6 +78,29 @@ where
     }
 }
 
+#[async_trait]
+impl<S> axum::extract::FromRequestParts<S> for EditorAuthToken
+where
+    S: Send + Sync,
+{
+    type Rejection = http::StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut http::request::Parts,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        if let AuthToken(Some(token)) = AuthToken::from_request_parts(parts, state).await? {
+            return Ok(EditorAuthToken(Some(token)));
+        }
+        Ok(EditorAuthToken(
+            parts
+                .headers
+                .typed_get::<axum_extra::headers::Cookie>()
+                .and_then(|cookie| cookie.get(SESSION_COOKIE_NAME).map(str::to_string)),
+        ))
+    }
+}
+
 impl Watch {
     async fn fetch_meta(
         client: &reqwest::Client,
@@ -255,3 +288,63 @@ impl MetaData {
             .ok_or_else(|| Error::UnknownKid(kid.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::FromRequestParts;
+
+    async fn editor_auth_token(headers: &[(&str, &str)]) -> Option<String> {
+        let mut request = http::Request::builder().uri("/v2/instances/00001234/editor/80/access");
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let (mut parts, _) = request.body(()).unwrap().into_parts();
+        EditorAuthToken::from_request_parts(&mut parts, &())
+            .await
+            .unwrap()
+            .0
+    }
+
+    #[tokio::test]
+    async fn editor_auth_token_from_session_cookie() {
+        assert_eq!(
+            editor_auth_token(&[("cookie", "theme=dark; flecs_session=token.from.cookie")]).await,
+            Some("token.from.cookie".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn editor_auth_token_prefers_authorization_header() {
+        assert_eq!(
+            editor_auth_token(&[
+                ("authorization", "Bearer token.from.header"),
+                ("cookie", "flecs_session=token.from.cookie"),
+            ])
+            .await,
+            Some("token.from.header".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn editor_auth_token_missing() {
+        assert_eq!(editor_auth_token(&[("cookie", "theme=dark")]).await, None);
+        assert_eq!(editor_auth_token(&[]).await, None);
+    }
+
+    #[tokio::test]
+    async fn auth_token_ignores_session_cookie() {
+        let (mut parts, _) = http::Request::builder()
+            .header("cookie", "flecs_session=token.from.cookie")
+            .body(())
+            .unwrap()
+            .into_parts();
+        assert_eq!(
+            AuthToken::from_request_parts(&mut parts, &())
+                .await
+                .unwrap()
+                .0,
+            None
+        );
+    }
+}
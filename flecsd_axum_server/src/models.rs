@@ -3558,12 +3558,28 @@ impl std::convert::TryFrom<HeaderValue>
 pub struct DeviceLicenseActivationStatusGet200Response {
     #[serde(rename = "isValid")]
     pub is_valid: bool,
+
+    /// Unix timestamp in seconds of the last successful validation
+    #[serde(rename = "lastValidated")]
+    #[validate(range(min = 0u64, max = 9223372036854775807u64))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_validated: Option<u64>,
+
+    /// Unix timestamp in seconds until which the license stays valid while the console is unreachable
+    #[serde(rename = "graceUntil")]
+    #[validate(range(min = 0u64, max = 9223372036854775807u64))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grace_until: Option<u64>,
 }
 
 impl DeviceLicenseActivationStatusGet200Response {
     #[allow(clippy::new_without_default, clippy::too_many_arguments)]
     pub fn new(is_valid: bool) -> DeviceLicenseActivationStatusGet200Response {
-        DeviceLicenseActivationStatusGet200Response { is_valid }
+        DeviceLicenseActivationStatusGet200Response {
+            is_valid,
+            last_validated: None,
+            grace_until: None,
+        }
     }
 }
 
@@ -3572,8 +3588,16 @@ impl DeviceLicenseActivationStatusGet200Response {
 /// Should be implemented in a serde serializer
 impl std::fmt::Display for DeviceLicenseActivationStatusGet200Response {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let params: Vec<Option<String>> =
-            vec![Some("isValid".to_string()), Some(self.is_valid.to_string())];
+        let params: Vec<Option<String>> = vec![
+            Some("isValid".to_string()),
+            Some(self.is_valid.to_string()),
+            self.last_validated.as_ref().map(|last_validated| {
+                ["lastValidated".to_string(), last_validated.to_string()].join(",")
+            }),
+            self.grace_until
+                .as_ref()
+                .map(|grace_until| ["graceUntil".to_string(), grace_until.to_string()].join(",")),
+        ];
 
         write!(
             f,
@@ -3595,6 +3619,8 @@ impl std::str::FromStr for DeviceLicenseActivationStatusGet200Response {
         #[allow(dead_code)]
         struct IntermediateRep {
             pub is_valid: Vec<bool>,
+            pub last_validated: Vec<u64>,
+            pub grace_until: Vec<u64>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -3620,6 +3646,14 @@ impl std::str::FromStr for DeviceLicenseActivationStatusGet200Response {
                     "isValid" => intermediate_rep.is_valid.push(
                         <bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
                     ),
+                    #[allow(clippy::redundant_clone)]
+                    "lastValidated" => intermediate_rep.last_validated.push(
+                        <u64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
+                    ),
+                    #[allow(clippy::redundant_clone)]
+                    "graceUntil" => intermediate_rep.grace_until.push(
+                        <u64 as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
+                    ),
                     _ => return std::result::Result::Err(
                         "Unexpected key while parsing DeviceLicenseActivationStatusGet200Response"
                             .to_string(),
@@ -3640,6 +3674,8 @@ impl std::str::FromStr for DeviceLicenseActivationStatusGet200Response {
                 .ok_or_else(|| {
                     "isValid missing in DeviceLicenseActivationStatusGet200Response".to_string()
                 })?,
+            last_validated: intermediate_rep.last_validated.into_iter().next(),
+            grace_until: intermediate_rep.grace_until.into_iter().next(),
         })
     }
 }
@@ -6323,6 +6359,21 @@ pub struct InstanceEditor {
     /// Link to the editor of an instance
     #[serde(rename = "url")]
     pub url: String,
+
+    /// Dedicated host name the editor is served under
+    #[serde(rename = "host_name")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_name: Option<String>,
+
+    /// Link to the editor under its dedicated host name
+    #[serde(rename = "host_url")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_url: Option<String>,
+
+    /// Whether the certificate of the dedicated host name was generated by flecs
+    #[serde(rename = "self_signed_certificate")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub self_signed_certificate: Option<bool>,
 }
 
 impl InstanceEditor {
@@ -6333,6 +6384,9 @@ impl InstanceEditor {
             port,
             path_prefix: None,
             url,
+            host_name: None,
+            host_url: None,
+            self_signed_certificate: None,
         }
     }
 }
@@ -6352,6 +6406,21 @@ impl std::fmt::Display for InstanceEditor {
                 .map(|path_prefix| ["path_prefix".to_string(), path_prefix.to_string()].join(",")),
             Some("url".to_string()),
             Some(self.url.to_string()),
+            self.host_name
+                .as_ref()
+                .map(|host_name| ["host_name".to_string(), host_name.to_string()].join(",")),
+            self.host_url
+                .as_ref()
+                .map(|host_url| ["host_url".to_string(), host_url.to_string()].join(",")),
+            self.self_signed_certificate
+                .as_ref()
+                .map(|self_signed_certificate| {
+                    [
+                        "self_signed_certificate".to_string(),
+                        self_signed_certificate.to_string(),
+                    ]
+                    .join(",")
+                }),
         ];
 
         write!(
@@ -6377,6 +6446,9 @@ impl std::str::FromStr for InstanceEditor {
             pub port: Vec<u16>,
             pub path_prefix: Vec<String>,
             pub url: Vec<String>,
+            pub host_name: Vec<String>,
+            pub host_url: Vec<String>,
+            pub self_signed_certificate: Vec<bool>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -6414,6 +6486,18 @@ impl std::str::FromStr for InstanceEditor {
                     "url" => intermediate_rep.url.push(
                         <String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
                     ),
+                    #[allow(clippy::redundant_clone)]
+                    "host_name" => intermediate_rep.host_name.push(
+                        <String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
+                    ),
+                    #[allow(clippy::redundant_clone)]
+                    "host_url" => intermediate_rep.host_url.push(
+                        <String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
+                    ),
+                    #[allow(clippy::redundant_clone)]
+                    "self_signed_certificate" => intermediate_rep.self_signed_certificate.push(
+                        <bool as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
+                    ),
                     _ => {
                         return std::result::Result::Err(
                             "Unexpected key while parsing InstanceEditor".to_string(),
@@ -6444,6 +6528,9 @@ impl std::str::FromStr for InstanceEditor {
                 .into_iter()
                 .next()
                 .ok_or_else(|| "url missing in InstanceEditor".to_string())?,
+            host_name: intermediate_rep.host_name.into_iter().next(),
+            host_url: intermediate_rep.host_url.into_iter().next(),
+            self_signed_certificate: intermediate_rep.self_signed_certificate.into_iter().next(),
         })
     }
 }
@@ -7988,6 +8075,11 @@ pub struct InstancesCreatePostRequest {
     #[serde(rename = "instanceName")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub instance_name: Option<String>,
+
+    /// Deployment to create the instance in, the default deployment for the app type is used if not specified
+    #[serde(rename = "deploymentId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deployment_id: Option<String>,
 }
 
 impl InstancesCreatePostRequest {
@@ -7996,6 +8088,7 @@ impl InstancesCreatePostRequest {
         InstancesCreatePostRequest {
             app_key,
             instance_name: None,
+            deployment_id: None,
         }
     }
 }
@@ -8010,6 +8103,9 @@ impl std::fmt::Display for InstancesCreatePostRequest {
             self.instance_name.as_ref().map(|instance_name| {
                 ["instanceName".to_string(), instance_name.to_string()].join(",")
             }),
+            self.deployment_id.as_ref().map(|deployment_id| {
+                ["deploymentId".to_string(), deployment_id.to_string()].join(",")
+            }),
         ];
 
         write!(
@@ -8033,6 +8129,7 @@ impl std::str::FromStr for InstancesCreatePostRequest {
         struct IntermediateRep {
             pub app_key: Vec<models::AppKey>,
             pub instance_name: Vec<String>,
+            pub deployment_id: Vec<String>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -8063,6 +8160,10 @@ impl std::str::FromStr for InstancesCreatePostRequest {
                     "instanceName" => intermediate_rep.instance_name.push(
                         <String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
                     ),
+                    #[allow(clippy::redundant_clone)]
+                    "deploymentId" => intermediate_rep.deployment_id.push(
+                        <String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
+                    ),
                     _ => {
                         return std::result::Result::Err(
                             "Unexpected key while parsing InstancesCreatePostRequest".to_string(),
@@ -8083,6 +8184,7 @@ impl std::str::FromStr for InstancesCreatePostRequest {
                 .next()
                 .ok_or_else(|| "appKey missing in InstancesCreatePostRequest".to_string())?,
             instance_name: intermediate_rep.instance_name.into_iter().next(),
+            deployment_id: intermediate_rep.deployment_id.into_iter().next(),
         })
     }
 }
@@ -13069,6 +13171,11 @@ pub struct SystemInfo {
 
     #[serde(rename = "platform")]
     pub platform: String,
+
+    /// Serial number of the device, if it could be determined
+    #[serde(rename = "serialNumber")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub serial_number: Option<String>,
 }
 
 impl SystemInfo {
@@ -13084,6 +13191,7 @@ impl SystemInfo {
             distro,
             kernel,
             platform,
+            serial_number: None,
         }
     }
 }
@@ -13101,6 +13209,9 @@ impl std::fmt::Display for SystemInfo {
             // Skipping kernel in query parameter serialization
             Some("platform".to_string()),
             Some(self.platform.to_string()),
+            self.serial_number.as_ref().map(|serial_number| {
+                ["serialNumber".to_string(), serial_number.to_string()].join(",")
+            }),
         ];
 
         write!(
@@ -13126,6 +13237,7 @@ impl std::str::FromStr for SystemInfo {
             pub distro: Vec<models::SystemDistro>,
             pub kernel: Vec<models::SystemKernel>,
             pub platform: Vec<String>,
+            pub serial_number: Vec<String>,
         }
 
         let mut intermediate_rep = IntermediateRep::default();
@@ -13165,6 +13277,10 @@ impl std::str::FromStr for SystemInfo {
                     "platform" => intermediate_rep.platform.push(
                         <String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
                     ),
+                    #[allow(clippy::redundant_clone)]
+                    "serialNumber" => intermediate_rep.serial_number.push(
+                        <String as std::str::FromStr>::from_str(val).map_err(|x| x.to_string())?,
+                    ),
                     _ => {
                         return std::result::Result::Err(
                             "Unexpected key while parsing SystemInfo".to_string(),
@@ -13199,6 +13315,7 @@ impl std::str::FromStr for SystemInfo {
                 .into_iter()
                 .next()
                 .ok_or_else(|| "platform missing in SystemInfo".to_string())?,
+            serial_number: intermediate_rep.serial_number.into_iter().next(),
         })
     }
 }